@@ -16,14 +16,14 @@
 
 //! Account management.
 
-use std::{fs, fmt};
+use std::{cmp, fs, fmt};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::{Instant, Duration};
 use util::{Mutex, RwLock};
 use ethstore::{SecretStore, Error as SSError, SafeAccount, EthStore};
 use ethstore::dir::{KeyDirectory};
-use ethstore::ethkey::{Address, Message, Secret, Random, Generator};
+use ethstore::ethkey::{Address, Message, Secret, Random, Generator, sign};
 use ethjson::misc::AccountMeta;
 pub use ethstore::ethkey::Signature;
 
@@ -44,6 +44,46 @@ enum Unlock {
 struct AccountData {
 	unlock: Unlock,
 	password: String,
+	/// Decrypted secret, kept only for accounts unlocked with `unlock_account_fast`.
+	secret: Option<Secret>,
+}
+
+/// Limits password guessing by locking an account out after repeated failed unlocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnlockPolicy {
+	/// Number of consecutive failures before the account is locked out.
+	pub max_attempts: u32,
+	/// Lockout after `max_attempts` failures, doubled with every further failure.
+	pub lockout: Duration,
+	/// Upper bound of the lockout.
+	pub max_lockout: Duration,
+}
+
+impl Default for UnlockPolicy {
+	fn default() -> Self {
+		UnlockPolicy {
+			max_attempts: 5,
+			lockout: Duration::from_secs(1),
+			max_lockout: Duration::from_secs(3600),
+		}
+	}
+}
+
+impl UnlockPolicy {
+	/// Lockout after given number of consecutive failures, if any.
+	fn lockout_after(&self, failures: u32) -> Option<Duration> {
+		match failures.checked_sub(self.max_attempts) {
+			Some(excess) => Some(cmp::min(self.lockout * (1u32 << cmp::min(excess, 16)), self.max_lockout)),
+			None => None,
+		}
+	}
+}
+
+/// Failed and ongoing unlock attempts of an account.
+struct FailedUnlocks {
+	count: u32,
+	in_flight: u32,
+	locked_until: Option<Instant>,
 }
 
 /// `AccountProvider` errors.
@@ -51,6 +91,8 @@ struct AccountData {
 pub enum Error {
 	/// Returned when account is not unlocked.
 	NotUnlocked,
+	/// Returned when account is locked out after too many failed unlock attempts.
+	TooManyAttempts,
 	/// Returned when signing fails.
 	SStore(SSError),
 }
@@ -59,6 +101,7 @@ impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
 		match *self {
 			Error::NotUnlocked => write!(f, "Account is locked"),
+			Error::TooManyAttempts => write!(f, "Too many failed unlock attempts, try again later"),
 			Error::SStore(ref e) => write!(f, "{}", e),
 		}
 	}
@@ -159,6 +202,8 @@ pub struct AccountProvider {
 	unlocked: Mutex<HashMap<Address, AccountData>>,
 	sstore: Box<SecretStore>,
 	address_book: Mutex<AddressBook>,
+	unlock_policy: Option<UnlockPolicy>,
+	failed_unlocks: Mutex<HashMap<Address, FailedUnlocks>>,
 }
 
 impl AccountProvider {
@@ -168,6 +213,8 @@ impl AccountProvider {
 			unlocked: Mutex::new(HashMap::new()),
 			address_book: Mutex::new(AddressBook::new(sstore.local_path().into())),
 			sstore: sstore,
+			unlock_policy: None,
+			failed_unlocks: Mutex::new(HashMap::new()),
 		}
 	}
 
@@ -176,10 +223,18 @@ impl AccountProvider {
 		AccountProvider {
 			unlocked: Mutex::new(HashMap::new()),
 			address_book: Mutex::new(AddressBook::new(Default::default())),
-			sstore: Box::new(EthStore::open(Box::new(NullDir::default())).unwrap())
+			sstore: Box::new(EthStore::open(Box::new(NullDir::default())).unwrap()),
+			unlock_policy: None,
+			failed_unlocks: Mutex::new(HashMap::new()),
 		}
 	}
 
+	/// Locks accounts out after repeated failed unlock attempts. Attempts are unlimited by default.
+	pub fn with_unlock_policy(mut self, policy: UnlockPolicy) -> Self {
+		self.unlock_policy = Some(policy);
+		self
+	}
+
 	/// Creates new random account.
 	pub fn new_account(&self, password: &str) -> Result<Address, Error> {
 		let secret = Random.generate().unwrap().secret().clone();
@@ -206,6 +261,13 @@ impl AccountProvider {
 		Ok(Address::from(address).into())
 	}
 
+	/// Exports an account as V3 keystore JSON encrypted with the account password.
+	/// The secret is never revealed; fails if the password is invalid.
+	pub fn export_wallet(&self, account: Address, password: &str) -> Result<Vec<u8>, Error> {
+		let json = try!(self.sstore.export_wallet(&account, password));
+		Ok(json)
+	}
+
 	/// Returns addresses of all accounts.
 	pub fn accounts(&self) -> Result<Vec<Address>, Error> {
 		let accounts = try!(self.sstore.accounts());
@@ -257,23 +319,73 @@ impl AccountProvider {
 		Ok(())
 	}
 
+	/// Runs a password check, refusing it while the account is locked out and tracking failures.
+	fn check_password<T, F>(&self, account: &Address, check: F) -> Result<T, Error> where F: FnOnce() -> Result<T, SSError> {
+		let policy = match self.unlock_policy {
+			Some(ref policy) => policy,
+			None => return check().map_err(Into::into),
+		};
+
+		{
+			let mut failed_unlocks = self.failed_unlocks.lock();
+			let failed = failed_unlocks.entry(account.clone()).or_insert(FailedUnlocks { count: 0, in_flight: 0, locked_until: None });
+			if failed.locked_until.map_or(false, |until| Instant::now() < until) {
+				return Err(Error::TooManyAttempts);
+			}
+			// attempts still running count as failed, so that parallel ones can't exceed the limit
+			if failed.in_flight > 0 && failed.count + failed.in_flight >= policy.max_attempts {
+				return Err(Error::TooManyAttempts);
+			}
+			failed.in_flight += 1;
+		}
+
+		// the lock isn't held while checking, key derivation is slow
+		let result = check();
+		let mut failed_unlocks = self.failed_unlocks.lock();
+		let forget = match failed_unlocks.get_mut(account) {
+			Some(failed) => {
+				failed.in_flight -= 1;
+				match result {
+					Err(SSError::InvalidPassword) => {
+						failed.count += 1;
+						failed.locked_until = policy.lockout_after(failed.count).map(|lockout| Instant::now() + lockout);
+					},
+					Ok(_) => {
+						failed.count = 0;
+						failed.locked_until = None;
+					},
+					Err(_) => {},
+				}
+				failed.count == 0 && failed.in_flight == 0
+			},
+			None => false,
+		};
+		if forget {
+			failed_unlocks.remove(account);
+		}
+		result.map_err(Into::into)
+	}
+
 	/// Helper method used for unlocking accounts.
-	fn unlock_account(&self, account: Address, password: String, unlock: Unlock) -> Result<(), Error> {
-		// verify password by signing dump message
-		// result may be discarded
-		let _ = try!(self.sstore.sign(&account, &password, &Default::default()));
+	fn unlock_account(&self, account: Address, password: String, unlock: Unlock, keep_secret: bool) -> Result<(), Error> {
+		// verify password by decrypting the secret
+		// it's dropped unless it should be kept
+		let secret = try!(self.check_password(&account, || self.sstore.secret(&account, &password)));
 
 		// check if account is already unlocked pernamently, if it is, do nothing
 		let mut unlocked = self.unlocked.lock();
 		if let Some(data) = unlocked.get(&account) {
 			if let Unlock::Perm = data.unlock {
-				return Ok(())
+				if data.secret.is_some() || !keep_secret {
+					return Ok(())
+				}
 			}
 		}
 
 		let data = AccountData {
 			unlock: unlock,
 			password: password,
+			secret: if keep_secret { Some(secret) } else { None },
 		};
 
 		unlocked.insert(account, data);
@@ -282,17 +394,24 @@ impl AccountProvider {
 
 	/// Unlocks account permanently.
 	pub fn unlock_account_permanently(&self, account: Address, password: String) -> Result<(), Error> {
-		self.unlock_account(account, password, Unlock::Perm)
+		self.unlock_account(account, password, Unlock::Perm, false)
+	}
+
+	/// Unlocks account permanently and keeps its decrypted secret in memory, so that signing
+	/// doesn't derive the key from the password again. This is much faster, but the plain secret
+	/// stays in the process memory (and possibly swap or core dumps) until the node exits.
+	pub fn unlock_account_fast(&self, account: Address, password: String) -> Result<(), Error> {
+		self.unlock_account(account, password, Unlock::Perm, true)
 	}
 
 	/// Unlocks account temporarily (for one signing).
 	pub fn unlock_account_temporarily(&self, account: Address, password: String) -> Result<(), Error> {
-		self.unlock_account(account, password, Unlock::Temp)
+		self.unlock_account(account, password, Unlock::Temp, false)
 	}
 
 	/// Unlocks account temporarily with a timeout.
 	pub fn unlock_account_timed(&self, account: Address, password: String, duration_ms: u32) -> Result<(), Error> {
-		self.unlock_account(account, password, Unlock::Timed((Instant::now(), duration_ms)))
+		self.unlock_account(account, password, Unlock::Timed((Instant::now(), duration_ms)), false)
 	}
 
 	/// Checks if given account is unlocked
@@ -318,13 +437,16 @@ impl AccountProvider {
 			data
 		};
 
-		let signature = try!(self.sstore.sign(&account, &data.password, &message));
+		let signature = match data.secret {
+			Some(ref secret) => try!(sign(secret, &message).map_err(SSError::from)),
+			None => try!(self.sstore.sign(&account, &data.password, &message)),
+		};
 		Ok(signature)
 	}
 
 	/// Unlocks an account, signs the message, and locks it again.
 	pub fn sign_with_password(&self, account: Address, password: String, message: Message) -> Result<Signature, Error> {
-		let signature = try!(self.sstore.sign(&account, &password, &message));
+		let signature = try!(self.check_password(&account, || self.sstore.sign(&account, &password, &message)));
 		Ok(signature)
 	}
 
@@ -341,7 +463,8 @@ impl AccountProvider {
 
 #[cfg(test)]
 mod tests {
-	use super::{AccountProvider, AddressBook};
+	use super::{AccountProvider, AddressBook, UnlockPolicy, Error};
+	use ethstore::Error as SSError;
 	use std::collections::HashMap;
 	use ethjson::misc::AccountMeta;
 	use ethstore::ethkey::{Generator, Random};
@@ -384,6 +507,18 @@ mod tests {
 		assert!(ap.sign(kp.address(), Default::default()).is_ok());
 	}
 
+	#[test]
+	fn unlock_account_fast() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		assert!(ap.unlock_account_fast(kp.address(), "test1".into()).is_err());
+		assert!(ap.unlock_account_fast(kp.address(), "test".into()).is_ok());
+		let expected = ap.sign_with_password(kp.address(), "test".into(), Default::default()).unwrap();
+		assert_eq!(ap.sign(kp.address(), Default::default()).unwrap(), expected);
+		assert_eq!(ap.sign(kp.address(), Default::default()).unwrap(), expected);
+	}
+
 	#[test]
 	fn unlock_account_timer() {
 		let kp = Random.generate().unwrap();
@@ -395,4 +530,90 @@ mod tests {
 		::std::thread::sleep(Duration::from_millis(2000));
 		assert!(ap.sign(kp.address(), Default::default()).is_err());
 	}
+
+	#[test]
+	fn locks_out_after_failed_unlocks() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider().with_unlock_policy(UnlockPolicy {
+			max_attempts: 2,
+			lockout: Duration::from_secs(1),
+			max_lockout: Duration::from_secs(10),
+		});
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+
+		// the first failure is free, the second one locks the account out
+		assert!(ap.unlock_account_temporarily(kp.address(), "test1".into()).is_err());
+		assert!(ap.unlock_account_temporarily(kp.address(), "test".into()).is_ok());
+		assert!(ap.unlock_account_temporarily(kp.address(), "test1".into()).is_err());
+		assert!(ap.unlock_account_temporarily(kp.address(), "test2".into()).is_err());
+		match ap.unlock_account_temporarily(kp.address(), "test".into()) {
+			Err(Error::TooManyAttempts) => {},
+			r => panic!("Expected lockout, got {:?}", r),
+		}
+
+		// the lockout doubles with the next failure; every check is at least 500ms away from a deadline
+		::std::thread::sleep(Duration::from_millis(1500));
+		assert!(ap.unlock_account_temporarily(kp.address(), "test1".into()).is_err());
+		::std::thread::sleep(Duration::from_millis(1500));
+		match ap.sign_with_password(kp.address(), "test".into(), Default::default()) {
+			Err(Error::TooManyAttempts) => {},
+			r => panic!("Expected lockout, got {:?}", r),
+		}
+
+		::std::thread::sleep(Duration::from_millis(1000));
+		assert!(ap.unlock_account_temporarily(kp.address(), "test".into()).is_ok());
+		assert!(ap.sign(kp.address(), Default::default()).is_ok());
+	}
+
+	#[test]
+	fn counts_attempts_in_progress() {
+		let kp = Random.generate().unwrap();
+		let address = kp.address();
+		let ap = AccountProvider::transient_provider().with_unlock_policy(UnlockPolicy {
+			max_attempts: 2,
+			lockout: Duration::from_secs(10),
+			max_lockout: Duration::from_secs(10),
+		});
+
+		// while the first attempt is being checked, a second one may still run,
+		// but a third one would exceed the limit if both of them failed
+		let result = ap.check_password(&address, || {
+			assert!(ap.check_password(&address, || Err::<(), _>(SSError::InvalidPassword)).is_err());
+			match ap.check_password(&address, || Ok(())) {
+				Err(Error::TooManyAttempts) => {},
+				r => panic!("Expected the attempt to be refused, got {:?}", r),
+			}
+			Err::<(), _>(SSError::InvalidPassword)
+		});
+		assert!(result.is_err());
+
+		// both failures were counted
+		match ap.check_password(&address, || Ok(())) {
+			Err(Error::TooManyAttempts) => {},
+			r => panic!("Expected lockout, got {:?}", r),
+		}
+	}
+
+	#[test]
+	fn export_wallet() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		let json = ap.export_wallet(kp.address(), "test").unwrap();
+
+		// the exported key file is accepted with the same password
+		let other = AccountProvider::transient_provider();
+		assert!(other.import_wallet(&json, "test1").is_err());
+		assert_eq!(other.import_wallet(&json, "test").unwrap(), kp.address());
+		assert!(other.sign_with_password(kp.address(), "test".into(), Default::default()).is_ok());
+	}
+
+	#[test]
+	fn export_wallet_with_invalid_password() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		assert!(ap.export_wallet(kp.address(), "test1").is_err());
+		assert!(ap.export_wallet(1.into(), "test").is_err());
+	}
 }
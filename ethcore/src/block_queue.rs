@@ -102,6 +102,18 @@ struct VerifyingBlock {
 	block: Option<PreverifiedBlock>,
 }
 
+/// Block which failed verification after being queued.
+pub struct FailedBlock {
+	/// Hash of the block.
+	pub hash: H256,
+	/// Number of the block.
+	pub number: BlockNumber,
+	/// Reason of the failure.
+	pub error: String,
+	/// Size of the block in bytes.
+	pub size: usize,
+}
+
 struct QueueSignal {
 	deleting: Arc<AtomicBool>,
 	signalled: AtomicBool,
@@ -134,6 +146,7 @@ struct Verification {
 	verified: Mutex<VecDeque<PreverifiedBlock>>,
 	verifying: Mutex<VecDeque<VerifyingBlock>>,
 	bad: Mutex<HashSet<H256>>,
+	failed: Mutex<Vec<FailedBlock>>,
 	more_to_verify: SMutex<()>,
 	empty: SMutex<()>,
 }
@@ -146,6 +159,7 @@ impl BlockQueue {
 			verified: Mutex::new(VecDeque::new()),
 			verifying: Mutex::new(VecDeque::new()),
 			bad: Mutex::new(HashSet::new()),
+			failed: Mutex::new(Vec::new()),
 			more_to_verify: SMutex::new(()),
 			empty: SMutex::new(()),
 
@@ -226,6 +240,7 @@ impl BlockQueue {
 			};
 
 			let block_hash = block.header.hash();
+			let (block_number, block_size) = (block.header.number(), block.bytes.len());
 			match verify_block_unordered(block.header, block.bytes, &*engine) {
 				Ok(verified) => {
 					let mut verifying = verification.verifying.lock();
@@ -239,7 +254,8 @@ impl BlockQueue {
 						// we're next!
 						let mut verified = verification.verified.lock();
 						let mut bad = verification.bad.lock();
-						BlockQueue::drain_verifying(&mut verifying, &mut verified, &mut bad);
+						let mut failed = verification.failed.lock();
+						BlockQueue::drain_verifying(&mut verifying, &mut verified, &mut bad, &mut failed);
 						ready.set();
 					}
 				},
@@ -247,21 +263,34 @@ impl BlockQueue {
 					let mut verifying = verification.verifying.lock();
 					let mut verified = verification.verified.lock();
 					let mut bad = verification.bad.lock();
+					let mut failed = verification.failed.lock();
 					warn!(target: "client", "Stage 2 block verification failed for {}\nError: {:?}", block_hash, err);
 					bad.insert(block_hash.clone());
+					failed.push(FailedBlock {
+						hash: block_hash.clone(),
+						number: block_number,
+						error: format!("Stage 2 block verification failed: {:?}", err),
+						size: block_size,
+					});
 					verifying.retain(|e| e.hash != block_hash);
-					BlockQueue::drain_verifying(&mut verifying, &mut verified, &mut bad);
+					BlockQueue::drain_verifying(&mut verifying, &mut verified, &mut bad, &mut failed);
 					ready.set();
 				}
 			}
 		}
 	}
 
-	fn drain_verifying(verifying: &mut VecDeque<VerifyingBlock>, verified: &mut VecDeque<PreverifiedBlock>, bad: &mut HashSet<H256>) {
+	fn drain_verifying(verifying: &mut VecDeque<VerifyingBlock>, verified: &mut VecDeque<PreverifiedBlock>, bad: &mut HashSet<H256>, failed: &mut Vec<FailedBlock>) {
 		while !verifying.is_empty() && verifying.front().unwrap().block.is_some() {
 			let block = verifying.pop_front().unwrap().block.unwrap();
 			if bad.contains(block.header.parent_hash()) {
 				bad.insert(block.header.hash());
+				failed.push(FailedBlock {
+					hash: block.header.hash(),
+					number: block.header.number(),
+					error: format!("Parent block {} is bad", block.header.parent_hash()),
+					size: block.bytes.len(),
+				});
 			}
 			else {
 				verified.push_back(block);
@@ -277,6 +306,7 @@ impl BlockQueue {
 		unverified.clear();
 		verifying.clear();
 		verified.clear();
+		self.verification.failed.lock().clear();
 		self.processing.write().clear();
 	}
 
@@ -326,6 +356,11 @@ impl BlockQueue {
 				self.more_to_verify.notify_all();
 				Ok(h)
 			},
+			Err(Error::Block(err)) if err.is_temporary() => {
+				// the block may be imported once our clock catches up, so it is not marked as bad.
+				debug!(target: "client", "Block {} is ahead of the local clock: {}", h, err);
+				Err(Error::Block(err))
+			},
 			Err(err) => {
 				warn!(target: "client", "Stage 1 block verification failed for {}\nError: {:?}", BlockView::new(&bytes).header_view().sha3(), err);
 				self.verification.bad.lock().insert(h.clone());
@@ -372,6 +407,12 @@ impl BlockQueue {
 		}
 	}
 
+	/// Removes the blocks which failed verification after being queued.
+	/// They are already marked as bad, but should be reported by the caller.
+	pub fn drain_failed(&self) -> Vec<FailedBlock> {
+		mem::replace(&mut *self.verification.failed.lock(), Vec::new())
+	}
+
 	/// Removes up to `max` verified blocks from the queue
 	pub fn drain(&self, max: usize) -> Vec<PreverifiedBlock> {
 		let mut verified = self.verification.verified.lock();
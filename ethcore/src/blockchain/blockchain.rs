@@ -321,45 +321,9 @@ impl BlockProvider for BlockChain {
 			.collect()
 	}
 
-	fn logs<F>(&self, mut blocks: Vec<BlockNumber>, matches: F, limit: Option<usize>) -> Vec<LocalizedLogEntry>
+	fn logs<F>(&self, blocks: Vec<BlockNumber>, matches: F, limit: Option<usize>) -> Vec<LocalizedLogEntry>
 		where F: Fn(&LogEntry) -> bool, Self: Sized {
-		// sort in reverse order
-		blocks.sort_by(|a, b| b.cmp(a));
-
-		let mut log_index = 0;
-		let mut logs = blocks.into_iter()
-			.filter_map(|number| self.block_hash(number).map(|hash| (number, hash)))
-			.filter_map(|(number, hash)| self.block_receipts(&hash).map(|r| (number, hash, r.receipts)))
-			.filter_map(|(number, hash, receipts)| self.block_body(&hash).map(|ref b| (number, hash, receipts, BodyView::new(b).transaction_hashes())))
-			.flat_map(|(number, hash, mut receipts, hashes)| {
-				assert_eq!(receipts.len(), hashes.len());
-				log_index = receipts.iter().fold(0, |sum, receipt| sum + receipt.logs.len());
-
-				let receipts_len = receipts.len();
-				receipts.reverse();
-				receipts.into_iter()
-					.map(|receipt| receipt.logs)
-					.zip(hashes)
-					.enumerate()
-					.flat_map(move |(index, (mut logs, tx_hash))| {
-						let current_log_index = log_index;
-						log_index -= logs.len();
-
-						logs.reverse();
-						logs.into_iter()
-							.enumerate()
-							.map(move |(i, log)| LocalizedLogEntry {
-								entry: log,
-								block_hash: hash,
-								block_number: number,
-								transaction_hash: tx_hash,
-								// iterating in reverse order
-								transaction_index: receipts_len - index - 1,
-								log_index: current_log_index - i - 1,
-							})
-					})
-			})
-			.filter(|log_entry| matches(&log_entry.entry))
+		let mut logs = self.logs_iter(blocks, matches)
 			.take(limit.unwrap_or(::std::usize::MAX))
 			.collect::<Vec<LocalizedLogEntry>>();
 		logs.reverse();
@@ -367,6 +331,47 @@ impl BlockProvider for BlockChain {
 	}
 }
 
+/// Iterator over logs of given blocks, from the newest to the oldest one.
+/// Receipts are loaded one block at a time, so the memory used doesn't depend on the number of blocks.
+pub struct LogsIter<'a, F> {
+	chain: &'a BlockChain,
+	blocks: ::std::vec::IntoIter<BlockNumber>,
+	matches: F,
+	current: ::std::vec::IntoIter<LocalizedLogEntry>,
+	blocks_read: usize,
+}
+
+impl<'a, F> LogsIter<'a, F> {
+	/// Number of blocks whose logs have been loaded so far.
+	pub fn blocks_read(&self) -> usize {
+		self.blocks_read
+	}
+}
+
+impl<'a, F> Iterator for LogsIter<'a, F> where F: Fn(&LogEntry) -> bool {
+	type Item = LocalizedLogEntry;
+
+	fn next(&mut self) -> Option<LocalizedLogEntry> {
+		loop {
+			if let Some(entry) = self.current.next() {
+				if (self.matches)(&entry.entry) {
+					return Some(entry);
+				}
+				continue;
+			}
+
+			let number = match self.blocks.next() {
+				Some(number) => number,
+				None => return None,
+			};
+			let mut logs = self.chain.block_logs(number);
+			logs.reverse();
+			self.current = logs.into_iter();
+			self.blocks_read += 1;
+		}
+	}
+}
+
 pub struct AncestryIter<'a> {
 	current: H256,
 	chain: &'a BlockChain,
@@ -386,6 +391,47 @@ impl<'a> Iterator for AncestryIter<'a> {
 }
 
 impl BlockChain {
+	/// Iterate over logs in given blocks matching the predicate, from the newest to the oldest one.
+	pub fn logs_iter<'a, F>(&'a self, mut blocks: Vec<BlockNumber>, matches: F) -> LogsIter<'a, F> where F: Fn(&LogEntry) -> bool {
+		// sort in reverse order
+		blocks.sort_by(|a, b| b.cmp(a));
+		LogsIter {
+			chain: self,
+			blocks: blocks.into_iter(),
+			matches: matches,
+			current: Vec::new().into_iter(),
+			blocks_read: 0,
+		}
+	}
+
+	/// All logs of the block with given number, or none if the block or its receipts are unknown.
+	fn block_logs(&self, number: BlockNumber) -> Vec<LocalizedLogEntry> {
+		let block = self.block_hash(number)
+			.and_then(|hash| self.block_receipts(&hash).map(|r| (hash, r.receipts)))
+			.and_then(|(hash, receipts)| self.block_body(&hash).map(|b| (hash, receipts, BodyView::new(&b).transaction_hashes())));
+		let (hash, receipts, hashes) = match block {
+			Some(block) => block,
+			None => return Vec::new(),
+		};
+		assert_eq!(receipts.len(), hashes.len());
+
+		let mut logs = Vec::new();
+		for (index, (receipt, tx_hash)) in receipts.into_iter().zip(hashes).enumerate() {
+			for entry in receipt.logs {
+				let log_index = logs.len();
+				logs.push(LocalizedLogEntry {
+					entry: entry,
+					block_hash: hash,
+					block_number: number,
+					transaction_hash: tx_hash,
+					transaction_index: index,
+					log_index: log_index,
+				});
+			}
+		}
+		logs
+	}
+
 	/// Create new instance of blockchain from given Genesis
 	pub fn new(config: Config, genesis: &[u8], db: Arc<Database>) -> BlockChain {
 		// 400 is the avarage size of the key
@@ -1791,6 +1837,49 @@ mod tests {
 		]);
 	}
 
+	#[test]
+	fn logs_stop_loading_blocks_at_limit() {
+		// given
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let temp = RandomTempPath::new();
+		let db = new_db(temp.as_str());
+		let bc = BlockChain::new(Config::default(), &genesis, db.clone());
+
+		let log = |data: u8| LogEntry { address: Default::default(), topics: vec![], data: vec![data] };
+		for number in 1..11u8 {
+			let t = Transaction {
+				nonce: 0.into(),
+				gas_price: 0.into(),
+				gas: 100_000.into(),
+				action: Action::Create,
+				value: (number as u64).into(),
+				data: vec![],
+			}.sign(&"".sha3());
+			let block = canon_chain.with_transaction(t).generate(&mut finalizer).unwrap();
+			insert_block(&db, &bc, &block, vec![Receipt {
+				state_root: H256::default(),
+				gas_used: 10_000.into(),
+				log_bloom: Default::default(),
+				logs: vec![log(number * 2), log(number * 2 + 1)],
+			}]);
+		}
+		let blocks: Vec<_> = (1..11).collect();
+
+		// when
+		let mut iter = bc.logs_iter(blocks.clone(), |entry| entry.data[0] % 2 == 0);
+		let newest: Vec<_> = iter.by_ref().take(3).map(|log| log.entry.data[0]).collect();
+		let logs = bc.logs(blocks, |entry| entry.data[0] % 2 == 0, Some(3));
+
+		// then
+		assert_eq!(newest, vec![20, 18, 16]);
+		assert_eq!(iter.blocks_read(), 3);
+		assert_eq!(logs.iter().map(|log| log.entry.data[0]).collect::<Vec<_>>(), vec![16, 18, 20]);
+		assert_eq!(logs.iter().map(|log| log.block_number).collect::<Vec<_>>(), vec![8, 9, 10]);
+		assert!(logs.iter().all(|log| log.transaction_index == 0 && log.log_index == 0));
+	}
+
 	#[test]
 	fn test_bloom_filter_simple() {
 		// TODO: From here
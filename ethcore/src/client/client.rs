@@ -33,7 +33,7 @@ use util::kvdb::*;
 use io::*;
 use views::{BlockView, HeaderView, BodyView};
 use error::{ImportError, ExecutionError, CallError, BlockError, ImportResult, Error as EthcoreError};
-use header::BlockNumber;
+use header::{BlockNumber, Header};
 use state::State;
 use spec::Spec;
 use basic_types::Seal;
@@ -46,6 +46,8 @@ use block::*;
 use transaction::{LocalizedTransaction, SignedTransaction, Action};
 use blockchain::extras::TransactionAddress;
 use types::filter::Filter;
+use types::bad_block::BadBlock;
+use types::account_info::AccountInfo;
 use log_entry::LocalizedLogEntry;
 use block_queue::{BlockQueue, BlockQueueInfo};
 use blockchain::{BlockChain, BlockProvider, TreeRoute, ImportRoute};
@@ -125,6 +127,7 @@ pub struct Client {
 	chain: RwLock<Arc<BlockChain>>,
 	tracedb: RwLock<TraceDB<BlockChain>>,
 	engine: Arc<Engine>,
+	chain_name: String,
 	config: ClientConfig,
 	db: RwLock<Arc<Database>>,
 	pruning: journaldb::Algorithm,
@@ -137,14 +140,18 @@ pub struct Client {
 	miner: Arc<Miner>,
 	sleep_state: Mutex<SleepState>,
 	liveness: AtomicBool,
+	shut_down: AtomicBool,
 	io_channel: IoChannel<ClientIoMessage>,
 	notify: RwLock<Vec<Weak<ChainNotify>>>,
 	queue_transactions: AtomicUsize,
 	last_hashes: RwLock<VecDeque<H256>>,
+	bad_blocks: RwLock<VecDeque<BadBlock>>,
 	factories: Factories,
 }
 
 const HISTORY: u64 = 1200;
+// Number of recently rejected blocks to remember.
+const MAX_BAD_BLOCKS: usize = 32;
 
 /// Append a path element to the given path and return the string.
 pub fn append_path<P>(path: P, item: &str) -> String where P: AsRef<Path> {
@@ -198,10 +205,12 @@ impl Client {
 		let client = Client {
 			sleep_state: Mutex::new(SleepState::new(awake)),
 			liveness: AtomicBool::new(awake),
+			shut_down: AtomicBool::new(false),
 			mode: config.mode.clone(),
 			chain: RwLock::new(chain),
 			tracedb: tracedb,
 			engine: engine,
+			chain_name: spec.fork_name.clone().unwrap_or_else(|| spec.name.clone()),
 			pruning: config.pruning.clone(),
 			verifier: verification::new(config.verifier_type.clone()),
 			config: config,
@@ -216,6 +225,7 @@ impl Client {
 			notify: RwLock::new(Vec::new()),
 			queue_transactions: AtomicUsize::new(0),
 			last_hashes: RwLock::new(VecDeque::new()),
+			bad_blocks: RwLock::new(VecDeque::with_capacity(MAX_BAD_BLOCKS)),
 			factories: factories,
 		};
 		Ok(Arc::new(client))
@@ -234,6 +244,29 @@ impl Client {
 		}
 	}
 
+	/// Stop importing blocks and flush the database.
+	///
+	/// Blocks queued or arriving afterwards are rejected. An import already in progress
+	/// is allowed to finish and its changes are flushed. The database itself is closed
+	/// once the client is dropped.
+	/// The network should be stopped beforehand so that peers don't see their blocks rejected.
+	pub fn shutdown(&self) {
+		if self.shut_down.swap(true, AtomicOrdering::SeqCst) {
+			return;
+		}
+		self.block_queue.clear();
+
+		let _import_lock = self.import_lock.lock();
+		if let Err(e) = self.db.read().flush() {
+			warn!(target: "client", "Failed to flush the database on shutdown: {}", e);
+		}
+	}
+
+	/// Whether `shutdown` has been called.
+	pub fn is_shut_down(&self) -> bool {
+		self.shut_down.load(AtomicOrdering::SeqCst)
+	}
+
 	/// Flush the block import queue.
 	pub fn flush_queue(&self) {
 		self.block_queue.flush();
@@ -268,7 +301,21 @@ impl Client {
 		Arc::new(last_hashes)
 	}
 
-	fn check_and_close_block(&self, block: &PreverifiedBlock) -> Result<LockedBlock, ()> {
+	fn note_bad_block(&self, hash: H256, number: BlockNumber, error: String, size: usize) {
+		let mut bad_blocks = self.bad_blocks.write();
+		if bad_blocks.len() == MAX_BAD_BLOCKS {
+			bad_blocks.pop_front();
+		}
+		bad_blocks.push_back(BadBlock {
+			hash: hash,
+			number: number,
+			error: error,
+			size: size as u64,
+			peer: None,
+		});
+	}
+
+	fn check_and_close_block(&self, block: &PreverifiedBlock) -> Result<LockedBlock, String> {
 		let engine = &*self.engine;
 		let header = &block.header;
 
@@ -277,21 +324,21 @@ impl Client {
 		let best_block_number = chain.best_block_number();
 		if best_block_number >= HISTORY && header.number() <= best_block_number - HISTORY {
 			warn!(target: "client", "Block import failed for #{} ({})\nBlock is ancient (current best block: #{}).", header.number(), header.hash(), best_block_number);
-			return Err(());
+			return Err(format!("Block is ancient (current best block: #{}).", best_block_number));
 		}
 
 		// Verify Block Family
 		let verify_family_result = self.verifier.verify_block_family(header, &block.bytes, engine, &**chain);
 		if let Err(e) = verify_family_result {
 			warn!(target: "client", "Stage 3 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-			return Err(());
+			return Err(format!("Stage 3 block verification failed: {:?}", e));
 		};
 
 		// Check if Parent is in chain
 		let chain_has_parent = chain.block_header(header.parent_hash());
 		if let None = chain_has_parent {
 			warn!(target: "client", "Block import failed for #{} ({}): Parent not found ({}) ", header.number(), header.hash(), header.parent_hash());
-			return Err(());
+			return Err(format!("Parent not found ({}).", header.parent_hash()));
 		};
 
 		// Enact Verified Block
@@ -302,14 +349,14 @@ impl Client {
 		let enact_result = enact_verified(block, engine, self.tracedb.read().tracing_enabled(), db, &parent, last_hashes, self.factories.clone());
 		if let Err(e) = enact_result {
 			warn!(target: "client", "Block import failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-			return Err(());
+			return Err(format!("Block enactment failed: {:?}", e));
 		};
 
 		// Final Verification
 		let locked_block = enact_result.unwrap();
 		if let Err(e) = self.verifier.verify_block_final(header, locked_block.block().header()) {
 			warn!(target: "client", "Stage 4 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-			return Err(());
+			return Err(format!("Stage 4 block verification failed: {:?}", e));
 		}
 
 		Ok(locked_block)
@@ -350,8 +397,15 @@ impl Client {
 			let mut import_results = Vec::with_capacity(max_blocks_to_import);
 
 			let _import_lock = self.import_lock.lock();
+			if self.is_shut_down() {
+				return 0;
+			}
 			let _timer = PerfTimer::new("import_verified_blocks");
 			let start = precise_time_ns();
+			for failed in self.block_queue.drain_failed() {
+				self.note_bad_block(failed.hash.clone(), failed.number, failed.error, failed.size);
+				invalid_blocks.insert(failed.hash);
+			}
 			let blocks = self.block_queue.drain(max_blocks_to_import);
 
 			for block in blocks {
@@ -361,7 +415,8 @@ impl Client {
 					continue;
 				}
 				let closed_block = self.check_and_close_block(&block);
-				if let Err(_) = closed_block {
+				if let Err(e) = closed_block {
+					self.note_bad_block(header.hash(), header.number(), e, block.bytes.len());
 					invalid_blocks.insert(header.hash());
 					continue;
 				}
@@ -386,15 +441,19 @@ impl Client {
 					self.block_queue.mark_as_good(&imported_blocks);
 				}
 			}
+			// flush while holding the import lock so that `shutdown` can't close the database underneath us.
+			self.db.read().flush().expect("DB flush failed.");
 			let duration_ns = precise_time_ns() - start;
 			(imported_blocks, import_results, invalid_blocks, imported, duration_ns)
 		};
 
 		{
-			if !imported_blocks.is_empty() && self.block_queue.queue_info().is_empty() {
+			let notify_imported = !imported_blocks.is_empty() && self.block_queue.queue_info().is_empty();
+			// invalid blocks are reported without delay, so that their peers can be disabled.
+			if notify_imported || !invalid_blocks.is_empty() {
 				let (enacted, retracted) = self.calculate_enacted_retracted(&import_results);
 
-				if self.queue_info().is_empty() {
+				if notify_imported && self.queue_info().is_empty() {
 					self.miner.chain_new_blocks(self, &imported_blocks, &invalid_blocks, &enacted, &retracted);
 				}
 
@@ -411,7 +470,6 @@ impl Client {
 			}
 		}
 
-		self.db.read().flush().expect("DB flush failed.");
 		imported
 	}
 
@@ -622,7 +680,7 @@ impl Client {
 			},
 		};
 
-		try!(snapshot::take_snapshot(&self.chain.read(), start_hash, db.as_hashdb(), writer, p));
+		try!(snapshot::take_snapshot(&*self.engine, &self.chain.read(), start_hash, db.as_hashdb(), writer, p, &self.config.snapshot));
 
 		Ok(())
 	}
@@ -719,7 +777,7 @@ impl BlockChainClient for Client {
 			// give the sender a sufficient balance
 			state.add_balance(&sender, &(needed_balance - balance));
 		}
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false, deadline: analytics.deadline() };
 		let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, options));
 
 		// TODO gav move this into Executive.
@@ -739,7 +797,7 @@ impl BlockChainClient for Client {
 			return Err(CallError::TransactionNotFound);
 		}
 
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false, deadline: analytics.deadline() };
 		let view = HeaderView::new(&header_data);
 		let last_hashes = self.build_last_hashes(view.hash());
 		let mut env_info = EnvInfo {
@@ -777,6 +835,15 @@ impl BlockChainClient for Client {
 		self.chain.read().best_block_header()
 	}
 
+	fn next_block_difficulty(&self) -> Option<U256> {
+		let parent: Header = ::rlp::decode(&self.best_block_header());
+		let mut header = Header::new();
+		header.set_parent_hash(parent.hash());
+		header.set_number(parent.number() + 1);
+		header.set_timestamp_now(parent.timestamp());
+		self.engine.pow_difficulty(&header, &parent)
+	}
+
 	fn block_header(&self, id: BlockID) -> Option<Bytes> {
 		let chain = self.chain.read();
 		Self::block_hash(&chain, id).and_then(|hash| chain.block_header_data(&hash))
@@ -839,10 +906,25 @@ impl BlockChainClient for Client {
 		self.state_at(id).map(|s| s.storage_at(address, position))
 	}
 
+	fn account(&self, address: &Address, id: BlockID) -> Option<AccountInfo> {
+		self.state_at(id).and_then(|s| s.storage_root(address).map(|storage_root| AccountInfo {
+			balance: s.balance(address),
+			nonce: s.nonce(address),
+			code_hash: s.code_hash(address),
+			storage_root: storage_root,
+			code_size: s.code(address).map_or(0, |code| code.len() as u64),
+		}))
+	}
+
 	fn transaction(&self, id: TransactionID) -> Option<LocalizedTransaction> {
 		self.transaction_address(id).and_then(|address| self.chain.read().transaction(&address))
 	}
 
+	fn transaction_rlp(&self, id: TransactionID) -> Option<Bytes> {
+		self.transaction_address(id).and_then(|address| self.chain.read().block_body(&address.block_hash)
+			.and_then(|body| BodyView::new(&body).transaction_rlp_at(address.index)))
+	}
+
 	fn uncle(&self, id: UncleID) -> Option<Bytes> {
 		let index = id.position;
 		self.block_body(id.block).and_then(|body| BodyView::new(&body).uncle_rlp_at(index))
@@ -915,7 +997,10 @@ impl BlockChainClient for Client {
 	}
 
 	fn import_block(&self, bytes: Bytes) -> Result<H256, BlockImportError> {
-		{
+		if self.is_shut_down() {
+			return Err(BlockImportError::Import(ImportError::ShuttingDown));
+		}
+		let (hash, number) = {
 			let header = BlockView::new(&bytes).header_view();
 			if self.chain.read().is_known(&header.sha3()) {
 				return Err(BlockImportError::Import(ImportError::AlreadyInChain));
@@ -923,8 +1008,28 @@ impl BlockChainClient for Client {
 			if self.block_status(BlockID::Hash(header.parent_hash())) == BlockStatus::Unknown {
 				return Err(BlockImportError::Block(BlockError::UnknownParent(header.parent_hash())));
 			}
+			(header.sha3(), header.number())
+		};
+		let size = bytes.len();
+		match self.block_queue.import_block(bytes) {
+			Ok(hash) => Ok(hash),
+			Err(EthcoreError::Import(e)) => Err(BlockImportError::Import(e)),
+			Err(EthcoreError::Block(e)) if e.is_temporary() => Err(BlockImportError::Block(e)),
+			Err(e) => {
+				self.note_bad_block(hash, number, format!("Stage 1 block verification failed: {:?}", e), size);
+				Err(e.into())
+			}
 		}
-		Ok(try!(self.block_queue.import_block(bytes)))
+	}
+
+	fn note_bad_block_peer(&self, hash: &H256, peer: String) {
+		if let Some(bad) = self.bad_blocks.write().iter_mut().rev().find(|b| &b.hash == hash) {
+			bad.peer = Some(peer);
+		}
+	}
+
+	fn bad_blocks(&self) -> Vec<BadBlock> {
+		self.bad_blocks.read().iter().cloned().collect()
 	}
 
 	fn queue_info(&self) -> BlockQueueInfo {
@@ -950,6 +1055,10 @@ impl BlockChainClient for Client {
 		self.engine.additional_params().into_iter().collect()
 	}
 
+	fn chain_name(&self) -> String {
+		self.chain_name.clone()
+	}
+
 	fn blocks_with_bloom(&self, bloom: &H2048, from_block: BlockID, to_block: BlockID) -> Option<Vec<BlockNumber>> {
 		match (self.block_number(from_block), self.block_number(to_block)) {
 			(Some(from), Some(to)) => Some(self.chain.read().blocks_with_bloom(bloom, from, to)),
@@ -958,14 +1067,11 @@ impl BlockChainClient for Client {
 	}
 
 	fn logs(&self, filter: Filter, limit: Option<usize>) -> Vec<LocalizedLogEntry> {
-		let blocks = filter.bloom_possibilities().iter()
-			.filter_map(|bloom| self.blocks_with_bloom(bloom, filter.from_block.clone(), filter.to_block.clone()))
-			.flat_map(|m| m)
-			// remove duplicate elements
-			.collect::<HashSet<u64>>()
-			.into_iter()
-			.collect::<Vec<u64>>();
+		let blocks = self.possible_log_blocks(&filter);
+		self.logs_in_blocks(blocks, &filter, limit)
+	}
 
+	fn logs_in_blocks(&self, blocks: Vec<BlockNumber>, filter: &Filter, limit: Option<usize>) -> Vec<LocalizedLogEntry> {
 		self.chain.read().logs(blocks, |entry| filter.matches(entry), limit)
 	}
 
@@ -1071,6 +1177,9 @@ impl MiningBlockChainClient for Client {
 
 	fn import_sealed_block(&self, block: SealedBlock) -> ImportResult {
 		let _import_lock = self.import_lock.lock();
+		if self.is_shut_down() {
+			return Err(ImportError::ShuttingDown.into());
+		}
 		let _timer = PerfTimer::new("import_sealed_block");
 		let start = precise_time_ns();
 
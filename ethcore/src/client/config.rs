@@ -23,6 +23,7 @@ pub use evm::VMType;
 pub use verification::VerifierType;
 use util::{journaldb, CompactionProfile};
 use util::trie::TrieSpec;
+use snapshot::SnapshotConfig;
 
 /// Client state db compaction profile
 #[derive(Debug, PartialEq)]
@@ -107,6 +108,8 @@ pub struct ClientConfig {
 	pub mode: Mode,
 	/// Type of block verifier used by client.
 	pub verifier_type: VerifierType,
+	/// Snapshot creation settings.
+	pub snapshot: SnapshotConfig,
 }
 
 #[cfg(test)]
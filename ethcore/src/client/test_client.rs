@@ -28,11 +28,12 @@ use client::{
 	TransactionID, UncleID, TraceId, TraceFilter, LastHashes, CallAnalytics, BlockImportError
 };
 use header::{Header as BlockHeader, BlockNumber};
+use views::BlockView;
 use filter::Filter;
 use log_entry::LocalizedLogEntry;
 use receipt::{Receipt, LocalizedReceipt};
 use blockchain::extras::BlockReceipts;
-use error::{ImportResult};
+use error::{ImportResult, ImportError, BlockError};
 use evm::{Factory as EvmFactory, VMType};
 use miner::{Miner, MinerService, TransactionImportResult};
 use spec::Spec;
@@ -42,6 +43,8 @@ use block::{OpenBlock, SealedBlock};
 use executive::Executed;
 use error::CallError;
 use trace::LocalizedTrace;
+use types::bad_block::BadBlock;
+use types::account_info::AccountInfo;
 
 /// Test client.
 pub struct TestBlockChainClient {
@@ -57,6 +60,8 @@ pub struct TestBlockChainClient {
 	pub difficulty: RwLock<U256>,
 	/// Balances.
 	pub balances: RwLock<HashMap<Address, U256>>,
+	/// Balances at given blocks.
+	pub block_balances: RwLock<HashMap<(Address, BlockNumber), U256>>,
 	/// Nonces.
 	pub nonces: RwLock<HashMap<Address, U256>>,
 	/// Storage.
@@ -77,6 +82,14 @@ pub struct TestBlockChainClient {
 	pub vm_factory: EvmFactory,
 	/// Timestamp assigned to latest sealed block
 	pub latest_block_timestamp: RwLock<u64>,
+	/// Number of `keep_alive` calls received.
+	pub keep_alive_count: AtomicUsize,
+	/// Rejected blocks.
+	pub bad_blocks: RwLock<Vec<BadBlock>>,
+	/// Logs.
+	pub logs: RwLock<Vec<LocalizedLogEntry>>,
+	/// Number of upcoming block imports which fail as if the client was shutting down.
+	pub import_failures: AtomicUsize,
 }
 
 #[derive(Clone)]
@@ -109,6 +122,7 @@ impl TestBlockChainClient {
 			last_hash: RwLock::new(H256::new()),
 			difficulty: RwLock::new(From::from(0)),
 			balances: RwLock::new(HashMap::new()),
+			block_balances: RwLock::new(HashMap::new()),
 			nonces: RwLock::new(HashMap::new()),
 			storage: RwLock::new(HashMap::new()),
 			code: RwLock::new(HashMap::new()),
@@ -119,6 +133,10 @@ impl TestBlockChainClient {
 			spec: spec,
 			vm_factory: EvmFactory::new(VMType::Interpreter),
 			latest_block_timestamp: RwLock::new(10_000_000),
+			keep_alive_count: AtomicUsize::new(0),
+			bad_blocks: RwLock::new(Vec::new()),
+			logs: RwLock::new(Vec::new()),
+			import_failures: AtomicUsize::new(0),
 		};
 		client.add_blocks(1, EachBlockWith::Nothing); // add genesis block
 		client.genesis_hash = client.last_hash.read().clone();
@@ -140,6 +158,11 @@ impl TestBlockChainClient {
 		self.balances.write().insert(address, balance);
 	}
 
+	/// Set the balance of account `address` at block `number` to `balance`.
+	pub fn set_block_balance(&self, address: Address, number: BlockNumber, balance: U256) {
+		self.block_balances.write().insert((address, number), balance);
+	}
+
 	/// Set nonce of account `address` to `nonce`.
 	pub fn set_nonce(&self, address: Address, nonce: U256) {
 		self.nonces.write().insert(address, nonce);
@@ -155,11 +178,21 @@ impl TestBlockChainClient {
 		self.storage.write().insert((address, position), value);
 	}
 
+	/// Set logs returned by log queries.
+	pub fn set_logs(&self, logs: Vec<LocalizedLogEntry>) {
+		*self.logs.write() = logs;
+	}
+
 	/// Set block queue size for testing
 	pub fn set_queue_size(&self, size: usize) {
 		self.queue_size.store(size, AtomicOrder::Relaxed);
 	}
 
+	/// Make the next `count` block imports fail.
+	pub fn set_import_failures(&self, count: usize) {
+		self.import_failures.store(count, AtomicOrder::Relaxed);
+	}
+
 	/// Set timestamp assigned to latest sealed block
 	pub fn set_latest_block_timestamp(&self, ts: u64) {
 		*self.latest_block_timestamp.write() = ts;
@@ -247,6 +280,10 @@ impl TestBlockChainClient {
 		blocks_read[&index].clone()
 	}
 
+	fn block_number(&self, id: BlockID) -> Option<BlockNumber> {
+		self.block_header(id).map(|header| decode::<BlockHeader>(&header).number())
+	}
+
 	fn block_hash(&self, id: BlockID) -> Option<H256> {
 		match id {
 			BlockID::Hash(hash) => Some(hash),
@@ -320,6 +357,10 @@ impl MiningBlockChainClient for TestBlockChainClient {
 }
 
 impl BlockChainClient for TestBlockChainClient {
+	fn keep_alive(&self) {
+		self.keep_alive_count.fetch_add(1, AtomicOrder::Relaxed);
+	}
+
 	fn call(&self, _t: &SignedTransaction, _block: BlockID, _analytics: CallAnalytics) -> Result<Executed, CallError> {
 		self.execution_result.read().clone().unwrap()
 	}
@@ -355,10 +396,10 @@ impl BlockChainClient for TestBlockChainClient {
 	}
 
 	fn balance(&self, address: &Address, id: BlockID) -> Option<U256> {
-		if let BlockID::Latest = id {
-			Some(self.balances.read().get(address).cloned().unwrap_or_else(U256::zero))
-		} else {
-			None
+		match id {
+			BlockID::Latest => Some(self.balances.read().get(address).cloned().unwrap_or_else(U256::zero)),
+			BlockID::Number(number) => self.block_balances.read().get(&(address.clone(), number)).cloned(),
+			_ => None,
 		}
 	}
 
@@ -374,8 +415,39 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
-	fn transaction(&self, _id: TransactionID) -> Option<LocalizedTransaction> {
-		unimplemented!();
+	fn account(&self, address: &Address, id: BlockID) -> Option<AccountInfo> {
+		match id {
+			BlockID::Latest => {
+				let code = self.code.read().get(address).cloned();
+				let storage: Vec<_> = self.storage.read().iter()
+					.filter(|&(&(ref a, _), value)| a == address && !value.is_zero())
+					.map(|(&(_, ref key), value)| (key.to_vec(), encode(&U256::from(value)).to_vec()))
+					.collect();
+				Some(AccountInfo {
+					balance: self.latest_balance(address),
+					nonce: self.latest_nonce(address),
+					code_hash: code.as_ref().map_or(SHA3_EMPTY, |c| c.sha3()),
+					storage_root: sec_trie_root(storage),
+					code_size: code.map_or(0, |c| c.len() as u64),
+				})
+			},
+			_ => None,
+		}
+	}
+
+	fn transaction(&self, id: TransactionID) -> Option<LocalizedTransaction> {
+		match id {
+			TransactionID::Hash(hash) => self.blocks.read().values()
+				.flat_map(|b| BlockView::new(b).localized_transactions())
+				.find(|t| t.hash() == hash),
+			TransactionID::Location(block_id, index) => self.block(block_id)
+				.and_then(|b| BlockView::new(&b).localized_transaction_at(index)),
+		}
+	}
+
+	fn transaction_rlp(&self, id: TransactionID) -> Option<Bytes> {
+		self.transaction(id).and_then(|t| self.block(BlockID::Hash(t.block_hash))
+			.and_then(|b| BlockView::new(&b).transaction_rlp_at(t.transaction_index)))
 	}
 
 	fn uncle(&self, _id: UncleID) -> Option<Bytes> {
@@ -386,12 +458,39 @@ impl BlockChainClient for TestBlockChainClient {
 		self.receipts.read().get(&id).cloned()
 	}
 
-	fn blocks_with_bloom(&self, _bloom: &H2048, _from_block: BlockID, _to_block: BlockID) -> Option<Vec<BlockNumber>> {
-		unimplemented!();
+	fn blocks_with_bloom(&self, bloom: &H2048, from_block: BlockID, to_block: BlockID) -> Option<Vec<BlockNumber>> {
+		let (from, to) = match (self.block_number(from_block), self.block_number(to_block)) {
+			(Some(from), Some(to)) => (from, to),
+			_ => return None,
+		};
+		let mut blooms = BTreeMap::new();
+		for log in self.logs.read().iter() {
+			let block_bloom = blooms.entry(log.block_number).or_insert_with(H2048::default);
+			let combined = &*block_bloom | &log.bloom();
+			*block_bloom = combined;
+		}
+		Some((from..to + 1)
+			.filter(|number| blooms.get(number).unwrap_or(&H2048::default()).contains(bloom))
+			.collect())
 	}
 
-	fn logs(&self, _filter: Filter, _limit: Option<usize>) -> Vec<LocalizedLogEntry> {
-		Vec::new()
+	fn logs(&self, filter: Filter, limit: Option<usize>) -> Vec<LocalizedLogEntry> {
+		let blocks = self.possible_log_blocks(&filter);
+		self.logs_in_blocks(blocks, &filter, limit)
+	}
+
+	fn logs_in_blocks(&self, blocks: Vec<BlockNumber>, filter: &Filter, limit: Option<usize>) -> Vec<LocalizedLogEntry> {
+		let mut logs = self.logs.read().iter()
+			.filter(|log| blocks.contains(&log.block_number) && filter.matches(&log.entry))
+			.cloned()
+			.collect::<Vec<_>>();
+		if let Some(limit) = limit {
+			let len = logs.len();
+			if len > limit {
+				logs = logs.split_off(len - limit);
+			}
+		}
+		logs
 	}
 
 	fn last_hashes(&self) -> LastHashes {
@@ -402,6 +501,15 @@ impl BlockChainClient for TestBlockChainClient {
 		self.block_header(BlockID::Hash(self.chain_info().best_block_hash)).expect("Best block always have header.")
 	}
 
+	fn next_block_difficulty(&self) -> Option<U256> {
+		let parent: BlockHeader = decode(&self.best_block_header());
+		let mut header = BlockHeader::new();
+		header.set_parent_hash(parent.hash());
+		header.set_number(parent.number() + 1);
+		header.set_timestamp_now(parent.timestamp());
+		self.spec.engine.pow_difficulty(&header, &parent)
+	}
+
 	fn block_header(&self, id: BlockID) -> Option<Bytes> {
 		self.block_hash(id).and_then(|hash| self.blocks.read().get(&hash).map(|r| Rlp::new(r).at(0).as_raw().to_vec()))
 	}
@@ -487,9 +595,25 @@ impl BlockChainClient for TestBlockChainClient {
 	}
 
 	fn import_block(&self, b: Bytes) -> Result<H256, BlockImportError> {
+		if self.import_failures.load(AtomicOrder::Relaxed) > 0 {
+			self.import_failures.fetch_sub(1, AtomicOrder::Relaxed);
+			return Err(BlockImportError::Import(ImportError::ShuttingDown));
+		}
 		let header = Rlp::new(&b).val_at::<BlockHeader>(0);
 		let h = header.hash();
 		let number: usize = header.number() as usize;
+		let max_extra_data_size = self.spec.engine.maximum_extra_data_size();
+		if header.extra_data().len() > max_extra_data_size {
+			let err = BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(max_extra_data_size), found: header.extra_data().len() });
+			self.bad_blocks.write().push(BadBlock {
+				hash: h.clone(),
+				number: header.number(),
+				error: format!("{:?}", err),
+				size: b.len() as u64,
+				peer: None,
+			});
+			return Err(BlockImportError::Block(err));
+		}
 		if number > self.blocks.read().len() {
 			panic!("Unexpected block number. Expected {}, got {}", self.blocks.read().len(), number);
 		}
@@ -531,6 +655,16 @@ impl BlockChainClient for TestBlockChainClient {
 		Ok(h)
 	}
 
+	fn note_bad_block_peer(&self, hash: &H256, peer: String) {
+		if let Some(bad) = self.bad_blocks.write().iter_mut().rev().find(|b| &b.hash == hash) {
+			bad.peer = Some(peer);
+		}
+	}
+
+	fn bad_blocks(&self) -> Vec<BadBlock> {
+		self.bad_blocks.read().clone()
+	}
+
 	fn queue_info(&self) -> BlockQueueInfo {
 		BlockQueueInfo {
 			verified_queue_size: self.queue_size.load(AtomicOrder::Relaxed),
@@ -549,6 +683,10 @@ impl BlockChainClient for TestBlockChainClient {
 		Default::default()
 	}
 
+	fn chain_name(&self) -> String {
+		self.spec.fork_name.clone().unwrap_or_else(|| self.spec.name.clone())
+	}
+
 	fn chain_info(&self) -> BlockChainInfo {
 		BlockChainInfo {
 			total_difficulty: *self.difficulty.read(),
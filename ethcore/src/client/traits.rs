@@ -37,6 +37,8 @@ use block_import_error::BlockImportError;
 use ipc::IpcConfig;
 use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
+use types::bad_block::BadBlock;
+use types::account_info::AccountInfo;
 
 #[derive(Ipc)]
 #[ipc(client_ident="RemoteClient")]
@@ -77,6 +79,10 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get block hash.
 	fn block_hash(&self, id: BlockID) -> Option<H256>;
 
+	/// Get balance, nonce, code hash, storage root and code size of an account,
+	/// all read from the given block's state.
+	fn account(&self, address: &Address, id: BlockID) -> Option<AccountInfo>;
+
 	/// Get address code at given block's state.
 	fn code(&self, address: &Address, id: BlockID) -> Option<Option<Bytes>>;
 
@@ -115,6 +121,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionID) -> Option<LocalizedTransaction>;
 
+	/// Get raw transaction rlp with given hash as stored in its block.
+	fn transaction_rlp(&self, id: TransactionID) -> Option<Bytes>;
+
 	/// Get uncle with given id.
 	fn uncle(&self, id: UncleID) -> Option<Bytes>;
 
@@ -137,6 +146,12 @@ pub trait BlockChainClient : Sync + Send {
 	/// Import a block into the blockchain.
 	fn import_block(&self, bytes: Bytes) -> Result<H256, BlockImportError>;
 
+	/// Attribute a recently rejected block to the peer it was received from.
+	fn note_bad_block_peer(&self, _hash: &H256, _peer: String) {}
+
+	/// Get recently rejected blocks, oldest first.
+	fn bad_blocks(&self) -> Vec<BadBlock>;
+
 	/// Get block queue information.
 	fn queue_info(&self) -> BlockQueueInfo;
 
@@ -149,15 +164,36 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get the registrar address, if it exists.
 	fn additional_params(&self) -> BTreeMap<String, String>;
 
+	/// Get the name of the active chain; the fork name if the spec defines one.
+	fn chain_name(&self) -> String;
+
 	/// Get the best block header.
 	fn best_block_header(&self) -> Bytes;
 
+	/// Proof-of-work difficulty of the next block if it was sealed now. `None` if the engine does not use proof-of-work.
+	fn next_block_difficulty(&self) -> Option<U256>;
+
 	/// Returns numbers of blocks containing given bloom.
 	fn blocks_with_bloom(&self, bloom: &H2048, from_block: BlockID, to_block: BlockID) -> Option<Vec<BlockNumber>>;
 
+	/// Returns numbers of blocks which may contain logs matching given filter, according to their blooms.
+	fn possible_log_blocks(&self, filter: &Filter) -> Vec<BlockNumber> {
+		let mut blocks = filter.bloom_possibilities().iter()
+			.filter_map(|bloom| self.blocks_with_bloom(bloom, filter.from_block.clone(), filter.to_block.clone()))
+			.flat_map(|m| m)
+			.collect::<Vec<BlockNumber>>();
+		// remove duplicate elements
+		blocks.sort();
+		blocks.dedup();
+		blocks
+	}
+
 	/// Returns logs matching given filter.
 	fn logs(&self, filter: Filter, limit: Option<usize>) -> Vec<LocalizedLogEntry>;
 
+	/// Returns logs matching given filter found in given blocks.
+	fn logs_in_blocks(&self, blocks: Vec<BlockNumber>, filter: &Filter, limit: Option<usize>) -> Vec<LocalizedLogEntry>;
+
 	/// Makes a non-persistent transaction call.
 	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics) -> Result<Executed, CallError>;
 
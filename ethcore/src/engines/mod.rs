@@ -110,6 +110,10 @@ pub trait Engine : Sync + Send {
 		self.verify_block_basic(header, None).and_then(|_| self.verify_block_unordered(header, None))
 	}
 
+	/// Proof-of-work difficulty of a block with given header on top of `parent`.
+	/// `None` if the engine does not use proof-of-work.
+	fn pow_difficulty(&self, _header: &Header, _parent: &Header) -> Option<U256> { None }
+
 	/// Don't forget to call Super::populate_from_parent when subclassing & overriding.
 	// TODO: consider including State in the params.
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, _gas_floor_target: U256, _gas_ceil_target: U256) {
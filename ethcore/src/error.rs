@@ -148,6 +148,16 @@ pub enum BlockError {
 	UnknownUncleParent(H256),
 }
 
+impl BlockError {
+	/// Whether the block may become valid later, i.e. its timestamp is ahead of our clock.
+	pub fn is_temporary(&self) -> bool {
+		match *self {
+			BlockError::InvalidTimestamp(OutOfBounds { max: Some(max), found, .. }) => found > max,
+			_ => false,
+		}
+	}
+}
+
 impl fmt::Display for BlockError {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		use self::BlockError::*;
@@ -194,6 +204,8 @@ pub enum ImportError {
 	AlreadyQueued,
 	/// Already marked as bad from a previous import (could mean parent is bad).
 	KnownBad,
+	/// The client is shutting down and no longer accepts blocks.
+	ShuttingDown,
 }
 
 impl fmt::Display for ImportError {
@@ -202,6 +214,7 @@ impl fmt::Display for ImportError {
 			ImportError::AlreadyInChain => "block already in chain",
 			ImportError::AlreadyQueued => "block already in the block queue",
 			ImportError::KnownBad => "block known to be bad",
+			ImportError::ShuttingDown => "client is shutting down",
 		};
 
 		f.write_fmt(format_args!("Block import error ({})", msg))
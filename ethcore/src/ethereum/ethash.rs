@@ -114,6 +114,10 @@ impl Engine for Ethash {
 		}
 	}
 
+	fn pow_difficulty(&self, header: &Header, parent: &Header) -> Option<U256> {
+		Some(self.calculate_difficulty(header, parent))
+	}
+
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, gas_ceil_target: U256) {
 		let difficulty = self.calculate_difficulty(header, parent);
 		let gas_limit = {
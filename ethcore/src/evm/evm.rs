@@ -63,6 +63,8 @@ pub enum Error {
 	/// Likely to cause consensus issues.
 	#[allow(dead_code)] // created only by jit
 	Internal,
+	/// Returned when execution runs past the deadline set by the externalities.
+	Timeout,
 }
 
 impl fmt::Display for Error {
@@ -75,6 +77,7 @@ impl fmt::Display for Error {
 			StackUnderflow { .. } => "Stack underflow",
 			OutOfStack { .. } => "Out of stack",
 			Internal => "Internal error",
+			Timeout => "Timed out",
 		};
 		message.fmt(f)
 	}
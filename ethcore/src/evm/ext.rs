@@ -16,6 +16,7 @@
 
 //! Interface for Evm externalities.
 
+use std::time::Instant;
 use util::*;
 use evm::{self, Schedule};
 use env_info::*;
@@ -114,4 +115,7 @@ pub trait Ext {
 
 	/// Trace the finalised execution of a single instruction.
 	fn trace_executed(&mut self, _gas_used: U256, _stack_push: &[U256], _mem_diff: Option<(usize, &[u8])>, _store_diff: Option<(U256, U256)>) {}
+
+	/// Time after which execution should be aborted with `evm::Error::Timeout`.
+	fn deadline(&self) -> Option<Instant> { None }
 }
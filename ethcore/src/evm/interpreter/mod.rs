@@ -37,6 +37,7 @@ use self::stack::{Stack, VecStack};
 use self::memory::Memory;
 
 use std::marker::PhantomData;
+use std::time::Instant;
 use common::*;
 use types::executed::CallType;
 use super::instructions::{self, Instruction, InstructionInfo};
@@ -50,6 +51,9 @@ fn color(instruction: Instruction, name: &'static str) -> String {
 	format!("\x1B[1;{}m{}\x1B[0m", colors[c], name)
 }
 
+/// Number of instructions executed between checks of the execution deadline.
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
 type CodePosition = usize;
 type ProgramCounter = usize;
 
@@ -107,8 +111,17 @@ impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 			code: code
 		};
 		let infos = &*instructions::INSTRUCTIONS;
+		let deadline = ext.deadline();
+		let mut executed = 0usize;
 
 		while reader.position < code.len() {
+			if let Some(deadline) = deadline {
+				executed += 1;
+				if executed % DEADLINE_CHECK_INTERVAL == 0 && Instant::now() >= deadline {
+					return Err(evm::Error::Timeout);
+				}
+			}
+
 			let instruction = code[reader.position];
 			reader.position += 1;
 
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Transaction Execution environment.
+use std::time::Instant;
 use common::*;
 use state::{State, Substate};
 use engines::Engine;
@@ -49,6 +50,8 @@ pub struct TransactOptions {
 	pub vm_tracing: bool,
 	/// Check transaction nonce before execution.
 	pub check_nonce: bool,
+	/// Abort execution with `ExecutionError::Timeout` if it's still running at this time.
+	pub deadline: Option<Instant>,
 }
 
 /// Transaction executor.
@@ -58,6 +61,7 @@ pub struct Executive<'a> {
 	engine: &'a Engine,
 	vm_factory: &'a Factory,
 	depth: usize,
+	deadline: Option<Instant>,
 }
 
 impl<'a> Executive<'a> {
@@ -69,6 +73,7 @@ impl<'a> Executive<'a> {
 			engine: engine,
 			vm_factory: vm_factory,
 			depth: 0,
+			deadline: None,
 		}
 	}
 
@@ -80,9 +85,15 @@ impl<'a> Executive<'a> {
 			engine: engine,
 			vm_factory: vm_factory,
 			depth: parent_depth + 1,
+			deadline: None,
 		}
 	}
 
+	/// Set the time after which execution is aborted.
+	pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+		self.deadline = deadline;
+	}
+
 	/// Creates `Externalities` from `Executive`.
 	pub fn as_externalities<'any, T, V>(
 		&'any mut self,
@@ -92,12 +103,15 @@ impl<'a> Executive<'a> {
 		tracer: &'any mut T,
 		vm_tracer: &'any mut V
 	) -> Externalities<'any, T, V> where T: Tracer, V: VMTracer {
-		Externalities::new(self.state, self.info, self.engine, self.vm_factory, self.depth, origin_info, substate, output, tracer, vm_tracer)
+		let mut ext = Externalities::new(self.state, self.info, self.engine, self.vm_factory, self.depth, origin_info, substate, output, tracer, vm_tracer);
+		ext.set_deadline(self.deadline);
+		ext
 	}
 
 	/// This function should be used to execute transaction.
 	pub fn transact(&'a mut self, t: &SignedTransaction, options: TransactOptions) -> Result<Executed, ExecutionError> {
 		let check = options.check_nonce;
+		self.deadline = options.deadline;
 		match options.tracing {
 			true => match options.vm_tracing {
 				true => self.transact_with_tracer(t, check, ExecutiveTracer::default(), ExecutiveVMTracer::toplevel()),
@@ -199,6 +213,11 @@ impl<'a> Executive<'a> {
 			}
 		};
 
+		// a timed out nested call looks like an ordinary failure to its caller, so check the deadline here too.
+		if self.deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+			return Err(ExecutionError::Timeout);
+		}
+
 		// finalize here!
 		Ok(try!(self.finalize(t, substate, gas_left, output, tracer.traces(), vm_tracer.drain())))
 	}
@@ -471,7 +490,8 @@ impl<'a> Executive<'a> {
 				| Err(evm::Error::BadJumpDestination {..})
 				| Err(evm::Error::BadInstruction {.. })
 				| Err(evm::Error::StackUnderflow {..})
-				| Err(evm::Error::OutOfStack {..}) => {
+				| Err(evm::Error::OutOfStack {..})
+				| Err(evm::Error::Timeout) => {
 					self.state.revert_snapshot();
 			},
 			Ok(_) | Err(evm::Error::Internal) => {
@@ -485,6 +505,7 @@ impl<'a> Executive<'a> {
 #[cfg(test)]
 #[allow(dead_code)]
 mod tests {
+	use std::time::{Duration, Instant};
 	use ethkey::{Generator, Random};
 	use super::*;
 	use common::*;
@@ -1026,7 +1047,7 @@ mod tests {
 
 		let executed = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, deadline: None };
 			ex.transact(&t, opts).unwrap()
 		};
 
@@ -1060,7 +1081,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, deadline: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1092,7 +1113,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, deadline: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1126,7 +1147,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, deadline: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1160,7 +1181,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, deadline: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1171,6 +1192,41 @@ mod tests {
 		}
 	}
 
+	evm_test!{ignorejit => test_transact_timeout: test_transact_timeout_jit, test_transact_timeout_int}
+	fn test_transact_timeout(factory: Factory) {
+		// JUMPDEST PUSH1 0 JUMP: loops until out of gas.
+		let code = "5b600056".from_hex().unwrap();
+		let address = Address::from(0x155);
+		let keypair = Random.generate().unwrap();
+		let gas = U256::from(1u64 << 60);
+		let t = Transaction {
+			action: Action::Call(address.clone()),
+			value: U256::zero(),
+			data: vec![],
+			gas: gas,
+			gas_price: U256::zero(),
+			nonce: U256::zero()
+		}.sign(keypair.secret());
+
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address, code);
+		let mut info = EnvInfo::default();
+		info.gas_limit = gas;
+		let engine = TestEngine::new(0);
+
+		let start = Instant::now();
+		let res = {
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, deadline: Some(start + Duration::from_millis(100)) };
+			ex.transact(&t, opts)
+		};
+
+		assert_eq!(res.unwrap_err(), ExecutionError::Timeout);
+		// running out of gas would take hours.
+		assert!(start.elapsed() < Duration::from_secs(10));
+	}
+
 	evm_test!{test_sha3: test_sha3_jit, test_sha3_int}
 	fn test_sha3(factory: Factory) {
 		let code = "6064640fffffffff20600055".from_hex().unwrap();
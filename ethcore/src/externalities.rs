@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Transaction Execution environment.
+use std::time::Instant;
 use common::*;
 use state::{State, Substate};
 use engines::Engine;
@@ -67,6 +68,7 @@ pub struct Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMTracer {
 	output: OutputPolicy<'a, 'a>,
 	tracer: &'a mut T,
 	vm_tracer: &'a mut V,
+	deadline: Option<Instant>,
 }
 
 impl<'a, T, V> Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMTracer {
@@ -95,8 +97,14 @@ impl<'a, T, V> Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMTracer {
 			output: output,
 			tracer: tracer,
 			vm_tracer: vm_tracer,
+			deadline: None,
 		}
 	}
+
+	/// Set the time after which execution is aborted. Applies to nested calls and creations as well.
+	pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+		self.deadline = deadline;
+	}
 }
 
 impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMTracer {
@@ -153,6 +161,7 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 
 		self.state.inc_nonce(&self.origin_info.address);
 		let mut ex = Executive::from_parent(self.state, self.env_info, self.engine, self.vm_factory, self.depth);
+		ex.set_deadline(self.deadline);
 
 		// TODO: handle internal error separately
 		match ex.create(params, self.substate, self.tracer, self.vm_tracer) {
@@ -194,6 +203,7 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 		}
 
 		let mut ex = Executive::from_parent(self.state, self.env_info, self.engine, self.vm_factory, self.depth);
+		ex.set_deadline(self.deadline);
 
 		match ex.call(params, self.substate, BytesRef::Fixed(output), self.tracer, self.vm_tracer) {
 			Ok(gas_left) => MessageCallResult::Success(gas_left),
@@ -293,6 +303,10 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
 		self.vm_tracer.trace_executed(gas_used, stack_push, mem_diff, store_diff)
 	}
+
+	fn deadline(&self) -> Option<Instant> {
+		self.deadline
+	}
 }
 
 #[cfg(test)]
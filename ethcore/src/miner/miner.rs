@@ -30,7 +30,7 @@ use transaction::{Action, SignedTransaction};
 use receipt::{Receipt, RichReceipt};
 use spec::Spec;
 use engines::Engine;
-use miner::{MinerService, MinerStatus, TransactionQueue, AccountDetails, TransactionOrigin};
+use miner::{MinerService, MinerStatus, TransactionQueue, TransactionOrdering, AccountDetails, TransactionOrigin};
 use miner::work_notify::WorkPoster;
 use client::TransactionImportResult;
 use miner::price_info::PriceInfo;
@@ -67,6 +67,10 @@ pub struct MinerOptions {
 	pub tx_queue_size: usize,
 	/// Whether we should fallback to providing all the queue's transactions or just pending.
 	pub pending_set: PendingSet,
+	/// How transactions of the same nonce height are ordered when included in a block.
+	pub tx_ordering: TransactionOrdering,
+	/// Minimal gas price increase (in percents) required to replace a queued transaction with the same nonce.
+	pub tx_replacement_bump: usize,
 	/// How many historical work packages can we store before running out?
 	pub work_queue_size: usize,
 	/// Can we submit two different solutions for the same block and expect both to result in an import?
@@ -83,6 +87,8 @@ impl Default for MinerOptions {
 			tx_gas_limit: !U256::zero(),
 			tx_queue_size: 1024,
 			pending_set: PendingSet::AlwaysQueue,
+			tx_ordering: TransactionOrdering::GasPriceThenNonce,
+			tx_replacement_bump: 0,
 			reseal_min_period: Duration::from_secs(2),
 			work_queue_size: 20,
 			enable_resubmission: true,
@@ -194,9 +200,10 @@ impl Miner {
 			true => None,
 			false => Some(WorkPoster::new(&options.new_work_notify))
 		};
-		let txq = Arc::new(Mutex::new(TransactionQueue::with_limits(options.tx_queue_size, options.tx_gas_limit)));
+		let mut txq = TransactionQueue::with_limits(options.tx_ordering, options.tx_queue_size, options.tx_gas_limit);
+		txq.set_replacement_bump(options.tx_replacement_bump);
 		Miner {
-			transaction_queue: txq,
+			transaction_queue: Arc::new(Mutex::new(txq)),
 			next_allowed_reseal: Mutex::new(Instant::now()),
 			sealing_block_last_request: Mutex::new(0),
 			sealing_work: Mutex::new(SealingWork{
@@ -540,7 +547,7 @@ impl MinerService for Miner {
 					// give the sender a sufficient balance
 					state.add_balance(&sender, &(needed_balance - balance));
 				}
-				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false, deadline: analytics.deadline() };
 				let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, chain.vm_factory()).transact(t, options));
 
 				// TODO gav move this into Executive.
@@ -580,6 +587,12 @@ impl MinerService for Miner {
 		sealing_work.queue.peek_last_ref().map_or_else(|| chain.latest_code(address), |b| b.block().fields().state.code(address))
 	}
 
+	fn next_nonce(&self, chain: &MiningBlockChainClient, address: &Address) -> U256 {
+		let latest_nonce = chain.latest_nonce(address);
+		// the queue only tracks transactions following on from the latest nonce without gaps.
+		self.transaction_queue.lock().last_nonce(address).map_or(latest_nonce, |nonce| max(latest_nonce, nonce + U256::one()))
+	}
+
 	fn set_author(&self, author: Address) {
 		if self.seals_internally {
 			let mut sealing_work = self.sealing_work.lock();
@@ -853,6 +866,14 @@ impl MinerService for Miner {
 		ret.map(f)
 	}
 
+	fn map_pending_block<F, T>(&self, f: F) -> Option<T> where F: FnOnce(&ClosedBlock) -> T {
+		let sealing_work = self.sealing_work.lock();
+		if !sealing_work.queue.is_in_use() {
+			return None;
+		}
+		sealing_work.queue.peek_last_ref().map(f)
+	}
+
 	fn submit_seal(&self, chain: &MiningBlockChainClient, pow_hash: H256, seal: Vec<Bytes>) -> Result<(), Error> {
 		let result = if let Some(b) = self.sealing_work.lock().queue.get_used_if(if self.options.enable_resubmission { GetAction::Clone } else { GetAction::Take }, |b| &b.hash() == &pow_hash) {
 			b.lock().try_seal(&*self.engine, seal).or_else(|_| {
@@ -947,7 +968,7 @@ mod tests {
 	use super::super::MinerService;
 	use super::*;
 	use util::*;
-	use ethkey::{Generator, Random};
+	use ethkey::{Generator, Random, KeyPair};
 	use client::{BlockChainClient, TestBlockChainClient, EachBlockWith, TransactionImportResult};
 	use header::BlockNumber;
 	use types::transaction::{Transaction, SignedTransaction, Action};
@@ -998,6 +1019,8 @@ mod tests {
 				tx_gas_limit: !U256::zero(),
 				tx_queue_size: 1024,
 				pending_set: PendingSet::AlwaysSealing,
+				tx_ordering: TransactionOrdering::GasPriceThenNonce,
+				tx_replacement_bump: 0,
 				work_queue_size: 5,
 				enable_resubmission: true,
 			},
@@ -1019,6 +1042,57 @@ mod tests {
 		}.sign(keypair.secret())
 	}
 
+	fn transaction_with_nonce(keypair: &KeyPair, nonce: u64) -> SignedTransaction {
+		Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: "3331600055".from_hex().unwrap(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::from(nonce),
+		}.sign(keypair.secret())
+	}
+
+	#[test]
+	fn should_return_next_nonce_after_all_queued_transactions() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let keypair = Random.generate().unwrap();
+		let sender = keypair.address();
+		assert_eq!(miner.next_nonce(&client, &sender), U256::zero());
+
+		// when
+		let transactions = vec![transaction_with_nonce(&keypair, 0), transaction_with_nonce(&keypair, 1), transaction_with_nonce(&keypair, 3)];
+		for res in miner.import_external_transactions(&client, transactions) {
+			res.unwrap();
+		}
+
+		// then
+		assert_eq!(miner.next_nonce(&client, &sender), U256::from(2));
+
+		// when the gap is filled
+		miner.import_external_transactions(&client, vec![transaction_with_nonce(&keypair, 2)]).pop().unwrap().unwrap();
+
+		// then
+		assert_eq!(miner.next_nonce(&client, &sender), U256::from(4));
+	}
+
+	#[test]
+	fn should_ignore_gapped_transactions_for_next_nonce() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let keypair = Random.generate().unwrap();
+
+		// when
+		let res = miner.import_external_transactions(&client, vec![transaction_with_nonce(&keypair, 1)]).pop().unwrap();
+
+		// then
+		assert_eq!(res.unwrap(), TransactionImportResult::Future);
+		assert_eq!(miner.next_nonce(&client, &keypair.address()), U256::zero());
+	}
+
 	#[test]
 	fn should_make_pending_block_when_importing_own_transaction() {
 		// given
@@ -47,7 +47,7 @@ mod transaction_queue;
 mod work_notify;
 mod price_info;
 
-pub use self::transaction_queue::{TransactionQueue, AccountDetails, TransactionOrigin};
+pub use self::transaction_queue::{TransactionQueue, TransactionOrdering, AccountDetails, TransactionOrigin};
 pub use self::miner::{Miner, MinerOptions, PendingSet, GasPricer, GasPriceCalibratorOptions};
 pub use self::external::{ExternalMiner, ExternalMinerService};
 pub use client::TransactionImportResult;
@@ -134,6 +134,11 @@ pub trait MinerService : Send + Sync {
 	fn map_sealing_work<F, T>(&self, chain: &MiningBlockChainClient, f: F) -> Option<T>
 		where F: FnOnce(&ClosedBlock) -> T, Self: Sized;
 
+	/// Get the block currently being sealed, without preparing new work, and if `Some`, apply some transform.
+	/// Returns `None` when not sealing.
+	fn map_pending_block<F, T>(&self, f: F) -> Option<T>
+		where F: FnOnce(&ClosedBlock) -> T, Self: Sized;
+
 	/// Query pending transactions for hash.
 	fn transaction(&self, hash: &H256) -> Option<SignedTransaction>;
 
@@ -175,6 +180,10 @@ pub trait MinerService : Send + Sync {
 
 	/// Get contract code in pending state.
 	fn code(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<Bytes>;
+
+	/// Nonce of the next transaction from given sender, accounting for all queued transactions
+	/// and not only the ones in the pending block. Queued transactions after a nonce gap are ignored.
+	fn next_nonce(&self, chain: &MiningBlockChainClient, address: &Address) -> U256;
 }
 
 /// Mining status
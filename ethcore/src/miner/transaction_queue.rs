@@ -19,7 +19,9 @@
 //! `TransactionQueue` keeps track of all transactions seen by the node (received from other peers) and own transactions
 //! and orders them by priority. Top priority transactions are those with low nonce height (difference between
 //! transaction's nonce and next nonce expected from this sender). If nonces are equal transaction's gas price is used
-//! for comparison (higher gas price = higher priority).
+//! for comparison (higher gas price = higher priority), unless the queue is configured with `TransactionOrdering::FifoThenNonce`,
+//! in which case transactions which arrived earlier have higher priority, or with `TransactionOrdering::GasFactorThenNonce`,
+//! in which case transactions with lower gas limit have higher priority and gas price only breaks ties.
 //!
 //! # Usage Example
 //!
@@ -120,6 +122,24 @@ impl Ord for TransactionOrigin {
 	}
 }
 
+/// Strategy used to order transactions with equal nonce height.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionOrdering {
+	/// Higher gas price first.
+	GasPriceThenNonce,
+	/// Earlier arrival first.
+	FifoThenNonce,
+	/// Lower gas limit (in units of 2^15 gas) first, then higher gas price.
+	/// Cheap to execute transactions are preferred over larger ones paying slightly more.
+	GasFactorThenNonce,
+}
+
+impl Default for TransactionOrdering {
+	fn default() -> Self {
+		TransactionOrdering::GasPriceThenNonce
+	}
+}
+
 #[derive(Clone, Debug)]
 /// Light structure used to identify transaction and its order
 struct TransactionOrder {
@@ -130,20 +150,31 @@ struct TransactionOrder {
 	/// Gas Price of the transaction.
 	/// Low gas price = Low priority (processed later)
 	gas_price: U256,
+	/// Gas limit of the transaction divided by 2^15.
+	/// High gas factor = Low priority (processed later) when ordering by gas factor.
+	gas_factor: U256,
 	/// Hash to identify associated transaction
 	hash: H256,
 	/// Origin of the transaction
 	origin: TransactionOrigin,
+	/// Sequence number of the transaction in order of arrival to the queue.
+	/// High insertion_id = Low priority (processed later) when ordering first-in first-out.
+	insertion_id: u64,
+	/// Strategy used to compare transactions of the same nonce height.
+	ordering: TransactionOrdering,
 }
 
 
 impl TransactionOrder {
-	fn for_transaction(tx: &VerifiedTransaction, base_nonce: U256) -> Self {
+	fn for_transaction(tx: &VerifiedTransaction, base_nonce: U256, ordering: TransactionOrdering) -> Self {
 		TransactionOrder {
 			nonce_height: tx.nonce() - base_nonce,
 			gas_price: tx.transaction.gas_price,
+			gas_factor: tx.transaction.gas >> 15,
 			hash: tx.hash(),
 			origin: tx.origin,
+			insertion_id: tx.insertion_id,
+			ordering: ordering,
 		}
 	}
 
@@ -178,11 +209,30 @@ impl Ord for TransactionOrder {
 			return self.origin.cmp(&b.origin);
 		}
 
-		// Then compare gas_prices
-		let a_gas = self.gas_price;
-		let b_gas = b.gas_price;
-		if a_gas != b_gas {
-			return b_gas.cmp(&a_gas);
+		match self.ordering {
+			TransactionOrdering::GasPriceThenNonce => {
+				// Then compare gas_prices
+				let a_gas = self.gas_price;
+				let b_gas = b.gas_price;
+				if a_gas != b_gas {
+					return b_gas.cmp(&a_gas);
+				}
+			},
+			TransactionOrdering::FifoThenNonce => {
+				// Then compare arrival
+				if self.insertion_id != b.insertion_id {
+					return self.insertion_id.cmp(&b.insertion_id);
+				}
+			},
+			TransactionOrdering::GasFactorThenNonce => {
+				// Then compare gas factors and gas prices
+				if self.gas_factor != b.gas_factor {
+					return self.gas_factor.cmp(&b.gas_factor);
+				}
+				if self.gas_price != b.gas_price {
+					return b.gas_price.cmp(&self.gas_price);
+				}
+			},
 		}
 
 		// Compare hashes
@@ -196,14 +246,17 @@ struct VerifiedTransaction {
 	transaction: SignedTransaction,
 	/// transaction origin
 	origin: TransactionOrigin,
+	/// Sequence number of arrival to the queue
+	insertion_id: u64,
 }
 
 impl VerifiedTransaction {
-	fn new(transaction: SignedTransaction, origin: TransactionOrigin) -> Result<Self, Error> {
+	fn new(transaction: SignedTransaction, origin: TransactionOrigin, insertion_id: u64) -> Result<Self, Error> {
 		try!(transaction.sender());
 		Ok(VerifiedTransaction {
 			transaction: transaction,
 			origin: origin,
+			insertion_id: insertion_id,
 		})
 	}
 
@@ -405,6 +458,12 @@ pub struct TransactionQueue {
 	by_hash: HashMap<H256, VerifiedTransaction>,
 	/// Last nonce of transaction in current (to quickly check next expected transaction)
 	last_nonces: HashMap<Address, U256>,
+	/// Strategy used to order transactions of the same nonce height
+	ordering: TransactionOrdering,
+	/// Sequence number assigned to the next imported transaction
+	next_insertion_id: u64,
+	/// Minimal gas price increase (in percents) required to replace a transaction with the same `(sender, nonce)`
+	replacement_bump: usize,
 }
 
 impl Default for TransactionQueue {
@@ -416,11 +475,11 @@ impl Default for TransactionQueue {
 impl TransactionQueue {
 	/// Creates new instance of this Queue
 	pub fn new() -> Self {
-		Self::with_limits(1024, !U256::zero())
+		Self::with_limits(TransactionOrdering::default(), 1024, !U256::zero())
 	}
 
-	/// Create new instance of this Queue with specified limits
-	pub fn with_limits(limit: usize, tx_gas_limit: U256) -> Self {
+	/// Create new instance of this Queue with specified ordering strategy and limits
+	pub fn with_limits(ordering: TransactionOrdering, limit: usize, tx_gas_limit: U256) -> Self {
 		let current = TransactionSet {
 			by_priority: BTreeSet::new(),
 			by_address: Table::new(),
@@ -443,6 +502,9 @@ impl TransactionQueue {
 			future: future,
 			by_hash: HashMap::new(),
 			last_nonces: HashMap::new(),
+			ordering: ordering,
+			next_insertion_id: 0,
+			replacement_bump: 0,
 		}
 	}
 
@@ -471,6 +533,12 @@ impl TransactionQueue {
 		self.minimal_gas_price = min_gas_price;
 	}
 
+	/// Sets the minimal gas price increase (in percents) required to replace a transaction
+	/// with the same `(sender, nonce)`.
+	pub fn set_replacement_bump(&mut self, percent: usize) {
+		self.replacement_bump = percent;
+	}
+
 	/// Get one more than the lowest gas price in the queue iff the pool is
 	/// full, otherwise 0.
 	pub fn effective_minimum_gas_price(&self) -> U256 {
@@ -554,7 +622,9 @@ impl TransactionQueue {
 			}));
 		}
 
-		let vtx = try!(VerifiedTransaction::new(tx, origin));
+		let insertion_id = self.next_insertion_id;
+		self.next_insertion_id += 1;
+		let vtx = try!(VerifiedTransaction::new(tx, origin, insertion_id));
 		let client_account = fetch_account(&vtx.sender());
 
 		let cost = vtx.transaction.value + vtx.transaction.gas_price * vtx.transaction.gas;
@@ -751,7 +821,7 @@ impl TransactionQueue {
 	/// this.
 	///
 	/// It ignores transactions that has already been imported (same `hash`) and replaces the transaction
-	/// iff `(address, nonce)` is the same but `gas_price` is higher.
+	/// iff `(address, nonce)` is the same but `gas_price` is higher by at least `replacement_bump` percent.
 	///
 	/// Returns `true` when transaction was imported successfuly
 	fn import_tx(&mut self, tx: VerifiedTransaction, state_nonce: U256) -> Result<TransactionImportResult, TransactionError> {
@@ -782,12 +852,12 @@ impl TransactionQueue {
 			// Update nonces of transactions in future (remove old transactions)
 			self.update_future(&address, state_nonce);
 			// Insert transaction (or replace old one with lower gas price)
-			try!(check_too_cheap(Self::replace_transaction(tx, state_nonce, &mut self.future, &mut self.by_hash)));
+			try!(check_too_cheap(Self::replace_transaction(tx, state_nonce, self.ordering, self.replacement_bump, &mut self.future, &mut self.by_hash)));
 			// Return an error if this transaction is not imported because of limit.
 			try!(check_if_removed(&address, &nonce, self.future.enforce_limit(&mut self.by_hash)));
 			return Ok(TransactionImportResult::Future);
 		}
-		try!(check_too_cheap(Self::replace_transaction(tx, state_nonce, &mut self.current, &mut self.by_hash)));
+		try!(check_too_cheap(Self::replace_transaction(tx, state_nonce, self.ordering, self.replacement_bump, &mut self.current, &mut self.by_hash)));
 		// Keep track of highest nonce stored in current
 		let new_max = self.last_nonces.get(&address).map_or(nonce, |n| cmp::max(nonce, *n));
 		self.last_nonces.insert(address, new_max);
@@ -801,7 +871,7 @@ impl TransactionQueue {
 			// Let's insert that transaction to current (if it has higher gas_price)
 			let future_tx = self.by_hash.remove(&order.hash).expect("All transactions in `future` are always in `by_hash`.");
 			// if transaction in `current` (then one we are importing) is replaced it means that it has to low gas_price
-			try!(check_too_cheap(!Self::replace_transaction(future_tx, state_nonce, &mut self.current, &mut self.by_hash)));
+			try!(check_too_cheap(!Self::replace_transaction(future_tx, state_nonce, self.ordering, 0, &mut self.current, &mut self.by_hash)));
 		}
 
 		// Also enforce the limit
@@ -830,13 +900,14 @@ impl TransactionQueue {
 
 	/// Replaces transaction in given set (could be `future` or `current`).
 	///
-	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher.
+	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher
+	/// by at least `bump` percent.
 	/// One of the transactions is dropped from set and also removed from queue entirely (from `by_hash`).
 	///
 	/// Returns `true` if transaction actually got to the queue (`false` if there was already a transaction with higher
 	/// gas_price)
-	fn replace_transaction(tx: VerifiedTransaction, base_nonce: U256, set: &mut TransactionSet, by_hash: &mut HashMap<H256, VerifiedTransaction>) -> bool {
-		let order = TransactionOrder::for_transaction(&tx, base_nonce);
+	fn replace_transaction(tx: VerifiedTransaction, base_nonce: U256, ordering: TransactionOrdering, bump: usize, set: &mut TransactionSet, by_hash: &mut HashMap<H256, VerifiedTransaction>) -> bool {
+		let order = TransactionOrder::for_transaction(&tx, base_nonce, ordering);
 		let hash = tx.hash();
 		let address = tx.sender();
 		let nonce = tx.nonce();
@@ -849,8 +920,8 @@ impl TransactionQueue {
 			// There was already transaction in queue. Let's check which one should stay
 			let old_fee = old.gas_price;
 			let new_fee = order.gas_price;
-			if old_fee.cmp(&new_fee) == Ordering::Greater {
-				// Put back old transaction since it has greater priority (higher gas_price)
+			if replacement_gas_price(old_fee, bump).cmp(&new_fee) == Ordering::Greater {
+				// Put back old transaction since the new one does not pay enough more to replace it
 				set.insert(address, nonce, old);
 				// and remove new one
 				by_hash.remove(&hash).expect("The hash has been just inserted and no other line is altering `by_hash`.");
@@ -866,6 +937,18 @@ impl TransactionQueue {
 	}
 }
 
+/// Minimal gas price of a transaction replacing one with given `gas_price`.
+fn replacement_gas_price(gas_price: U256, bump: usize) -> U256 {
+	let percent = match bump.checked_add(100) {
+		Some(percent) => percent,
+		None => return !U256::zero(),
+	};
+	match gas_price.overflowing_mul(U256::from(percent)) {
+		(_, true) => !U256::zero(),
+		(val, false) => val / U256::from(100),
+	}
+}
+
 fn check_too_cheap(is_in: bool) -> Result<(), TransactionError> {
 	if is_in {
 		Ok(())
@@ -954,10 +1037,66 @@ mod test {
 		new_tx_pair_default(0.into(), 1.into())
 	}
 
+	#[test]
+	fn should_order_by_ordering_strategy() {
+		// given
+		let cheap = new_tx(default_nonce(), 1.into());
+		let expensive = new_tx(default_nonce(), 2.into());
+		let mut by_gas_price = TransactionQueue::with_limits(TransactionOrdering::GasPriceThenNonce, 1024, !U256::zero());
+		let mut fifo = TransactionQueue::with_limits(TransactionOrdering::FifoThenNonce, 1024, !U256::zero());
+
+		// when
+		for txq in vec![&mut by_gas_price, &mut fifo] {
+			txq.add(cheap.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+			txq.add(expensive.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+		}
+
+		// then
+		assert_eq!(by_gas_price.top_transactions(), vec![expensive.clone(), cheap.clone()]);
+		assert_eq!(fifo.top_transactions(), vec![cheap, expensive]);
+	}
+
+	#[test]
+	fn should_order_by_gas_factor() {
+		// given
+		let keypair = Random.generate().unwrap();
+		let mut small = new_unsigned_tx(default_nonce(), 1.into());
+		small.gas = U256::from(21_000);
+		let small = small.sign(keypair.secret());
+		let mut large = new_unsigned_tx(default_nonce(), 2.into());
+		large.gas = U256::from(1_000_000);
+		let large = large.sign(Random.generate().unwrap().secret());
+		let expensive = new_tx(default_nonce(), 3.into());
+		let cheap = new_tx(default_nonce(), 2.into());
+		let mut txq = TransactionQueue::with_limits(TransactionOrdering::GasFactorThenNonce, 1024, !U256::zero());
+
+		// when
+		for tx in vec![&large, &cheap, &small, &expensive] {
+			txq.add(tx.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+		}
+
+		// then
+		assert_eq!(txq.top_transactions(), vec![small, expensive, cheap, large]);
+	}
+
+	#[test]
+	fn should_keep_nonce_order_when_ordering_fifo() {
+		// given
+		let mut txq = TransactionQueue::with_limits(TransactionOrdering::FifoThenNonce, 1024, !U256::zero());
+		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
+
+		// when
+		txq.add(tx2.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+		txq.add(tx1.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+
+		// then
+		assert_eq!(txq.top_transactions(), vec![tx1, tx2]);
+	}
+
 	#[test]
 	fn should_return_correct_nonces_when_dropped_because_of_limit() {
 		// given
-		let mut txq = TransactionQueue::with_limits(2, !U256::zero());
+		let mut txq = TransactionQueue::with_limits(TransactionOrdering::GasPriceThenNonce, 2, !U256::zero());
 		let (tx1, tx2) = new_tx_pair(123.into(), 1.into(), 1.into(), 0.into());
 		let sender = tx1.sender().unwrap();
 		let nonce = tx1.nonce;
@@ -998,20 +1137,20 @@ mod test {
 			limit: 1
 		};
 		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
-		let tx1 = VerifiedTransaction::new(tx1, TransactionOrigin::External).unwrap();
-		let tx2 = VerifiedTransaction::new(tx2, TransactionOrigin::External).unwrap();
+		let tx1 = VerifiedTransaction::new(tx1, TransactionOrigin::External, 0).unwrap();
+		let tx2 = VerifiedTransaction::new(tx2, TransactionOrigin::External, 0).unwrap();
 		let mut by_hash = {
 			let mut x = HashMap::new();
-			let tx1 = VerifiedTransaction::new(tx1.transaction.clone(), TransactionOrigin::External).unwrap();
-			let tx2 = VerifiedTransaction::new(tx2.transaction.clone(), TransactionOrigin::External).unwrap();
+			let tx1 = VerifiedTransaction::new(tx1.transaction.clone(), TransactionOrigin::External, 0).unwrap();
+			let tx2 = VerifiedTransaction::new(tx2.transaction.clone(), TransactionOrigin::External, 0).unwrap();
 			x.insert(tx1.hash(), tx1);
 			x.insert(tx2.hash(), tx2);
 			x
 		};
 		// Insert both transactions
-		let order1 = TransactionOrder::for_transaction(&tx1, U256::zero());
+		let order1 = TransactionOrder::for_transaction(&tx1, U256::zero(), TransactionOrdering::GasPriceThenNonce);
 		set.insert(tx1.sender(), tx1.nonce(), order1.clone());
-		let order2 = TransactionOrder::for_transaction(&tx2, U256::zero());
+		let order2 = TransactionOrder::for_transaction(&tx2, U256::zero(), TransactionOrdering::GasPriceThenNonce);
 		set.insert(tx2.sender(), tx2.nonce(), order2.clone());
 		assert_eq!(set.by_priority.len(), 2);
 		assert_eq!(set.by_address.len(), 2);
@@ -1040,18 +1179,18 @@ mod test {
 		// Create two transactions with same nonce
 		// (same hash)
 		let (tx1, tx2) = new_tx_pair_default(0.into(), 0.into());
-		let tx1 = VerifiedTransaction::new(tx1, TransactionOrigin::External).unwrap();
-		let tx2 = VerifiedTransaction::new(tx2, TransactionOrigin::External).unwrap();
+		let tx1 = VerifiedTransaction::new(tx1, TransactionOrigin::External, 0).unwrap();
+		let tx2 = VerifiedTransaction::new(tx2, TransactionOrigin::External, 0).unwrap();
 		let by_hash = {
 			let mut x = HashMap::new();
-			let tx1 = VerifiedTransaction::new(tx1.transaction.clone(), TransactionOrigin::External).unwrap();
-			let tx2 = VerifiedTransaction::new(tx2.transaction.clone(), TransactionOrigin::External).unwrap();
+			let tx1 = VerifiedTransaction::new(tx1.transaction.clone(), TransactionOrigin::External, 0).unwrap();
+			let tx2 = VerifiedTransaction::new(tx2.transaction.clone(), TransactionOrigin::External, 0).unwrap();
 			x.insert(tx1.hash(), tx1);
 			x.insert(tx2.hash(), tx2);
 			x
 		};
 		// Insert both transactions
-		let order1 = TransactionOrder::for_transaction(&tx1, U256::zero());
+		let order1 = TransactionOrder::for_transaction(&tx1, U256::zero(), TransactionOrdering::GasPriceThenNonce);
 		set.insert(tx1.sender(), tx1.nonce(), order1.clone());
 		assert_eq!(set.by_priority.len(), 1);
 		assert_eq!(set.by_address.len(), 1);
@@ -1059,7 +1198,7 @@ mod test {
 		assert_eq!(*set.by_gas_price.iter().next().unwrap().0, 1.into());
 		assert_eq!(set.by_gas_price.iter().next().unwrap().1.len(), 1);
 		// Two different orders (imagine nonce changed in the meantime)
-		let order2 = TransactionOrder::for_transaction(&tx2, U256::one());
+		let order2 = TransactionOrder::for_transaction(&tx2, U256::one(), TransactionOrdering::GasPriceThenNonce);
 		set.insert(tx2.sender(), tx2.nonce(), order2.clone());
 		assert_eq!(set.by_priority.len(), 1);
 		assert_eq!(set.by_address.len(), 1);
@@ -1086,11 +1225,11 @@ mod test {
 			limit: 2
 		};
 		let tx = new_tx_default();
-		let tx1 = VerifiedTransaction::new(tx.clone(), TransactionOrigin::External).unwrap();
-		let order1 = TransactionOrder::for_transaction(&tx1, U256::zero());
+		let tx1 = VerifiedTransaction::new(tx.clone(), TransactionOrigin::External, 0).unwrap();
+		let order1 = TransactionOrder::for_transaction(&tx1, U256::zero(), TransactionOrdering::GasPriceThenNonce);
 		assert!(set.insert(tx1.sender(), tx1.nonce(), order1).is_none());
-		let tx2 = VerifiedTransaction::new(tx, TransactionOrigin::External).unwrap();
-		let order2 = TransactionOrder::for_transaction(&tx2, U256::zero());
+		let tx2 = VerifiedTransaction::new(tx, TransactionOrigin::External, 0).unwrap();
+		let order2 = TransactionOrder::for_transaction(&tx2, U256::zero(), TransactionOrdering::GasPriceThenNonce);
 		assert!(set.insert(tx2.sender(), tx2.nonce(), order2).is_some());
 	}
 
@@ -1105,8 +1244,8 @@ mod test {
 
 		assert_eq!(set.gas_price_entry_limit(), 0.into());
 		let tx = new_tx_default();
-		let tx1 = VerifiedTransaction::new(tx.clone(), TransactionOrigin::External).unwrap();
-		let order1 = TransactionOrder::for_transaction(&tx1, U256::zero());
+		let tx1 = VerifiedTransaction::new(tx.clone(), TransactionOrigin::External, 0).unwrap();
+		let order1 = TransactionOrder::for_transaction(&tx1, U256::zero(), TransactionOrdering::GasPriceThenNonce);
 		assert!(set.insert(tx1.sender(), tx1.nonce(), order1.clone()).is_none());
 		assert_eq!(set.gas_price_entry_limit(), 2.into());
 	}
@@ -1487,7 +1626,7 @@ mod test {
 	#[test]
 	fn should_drop_old_transactions_when_hitting_the_limit() {
 		// given
-		let mut txq = TransactionQueue::with_limits(1, !U256::zero());
+		let mut txq = TransactionQueue::with_limits(TransactionOrdering::GasPriceThenNonce, 1, !U256::zero());
 		let (tx, tx2) = new_tx_pair_default(1.into(), 0.into());
 		let sender = tx.sender().unwrap();
 		let nonce = tx.nonce;
@@ -1508,7 +1647,7 @@ mod test {
 
 	#[test]
 	fn should_limit_future_transactions() {
-		let mut txq = TransactionQueue::with_limits(1, !U256::zero());
+		let mut txq = TransactionQueue::with_limits(TransactionOrdering::GasPriceThenNonce, 1, !U256::zero());
 		txq.current.set_limit(10);
 		let (tx1, tx2) = new_tx_pair_default(4.into(), 1.into());
 		let (tx3, tx4) = new_tx_pair_default(4.into(), 2.into());
@@ -1630,6 +1769,44 @@ mod test {
 		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(200));
 	}
 
+	#[test]
+	fn should_replace_transaction_only_with_required_bump() {
+		// given
+		let mut txq = TransactionQueue::new();
+		txq.set_replacement_bump(10);
+		let keypair = Random.generate().unwrap();
+		let tx = new_unsigned_tx(123.into(), 100.into()).sign(keypair.secret());
+		let with_gas_price = |gas_price: u64| {
+			let mut tx2 = (*tx).clone();
+			tx2.gas_price = U256::from(gas_price);
+			tx2.sign(keypair.secret())
+		};
+		txq.add(tx.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+
+		// when
+		let res = txq.add(with_gas_price(109), &default_account_details, TransactionOrigin::External);
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::TooCheapToReplace);
+		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(100));
+
+		// when
+		txq.add(with_gas_price(110), &default_account_details, TransactionOrigin::External).unwrap();
+
+		// then
+		let stats = txq.status();
+		assert_eq!(stats.pending, 1);
+		assert_eq!(stats.future, 0);
+		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(110));
+	}
+
+	#[test]
+	fn should_saturate_replacement_gas_price_on_overflow() {
+		assert_eq!(super::replacement_gas_price(U256::from(100), 10), U256::from(110));
+		assert_eq!(super::replacement_gas_price(U256::from(100), usize::max_value()), !U256::zero());
+		assert_eq!(super::replacement_gas_price(!U256::zero(), 10), !U256::zero());
+	}
+
 	#[test]
 	fn should_replace_same_transaction_when_importing_to_futures() {
 		// given
@@ -1768,7 +1945,7 @@ mod test {
 	#[test]
 	fn should_keep_right_order_in_future() {
 		// given
-		let mut txq = TransactionQueue::with_limits(1, !U256::zero());
+		let mut txq = TransactionQueue::with_limits(TransactionOrdering::GasPriceThenNonce, 1, !U256::zero());
 		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
 		let prev_nonce = |a: &Address| AccountDetails { nonce: default_account_details(a).nonce - U256::one(), balance:
 			default_account_details(a).balance };
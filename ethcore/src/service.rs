@@ -25,6 +25,7 @@ use miner::Miner;
 use snapshot::ManifestData;
 use snapshot::service::{Service as SnapshotService, ServiceParams as SnapServiceParams};
 use std::sync::atomic::AtomicBool;
+use std::path::PathBuf;
 
 #[cfg(feature="ipc")]
 use nanoipc;
@@ -48,6 +49,8 @@ pub enum ClientIoMessage {
 	FeedBlockChunk(H256, Bytes),
 	/// Take a snapshot for the block with given number.
 	TakeSnapshot(u64),
+	/// Take a snapshot for the block with given number into a packed snapshot file.
+	TakeSnapshotTo(u64, PathBuf),
 }
 
 /// Client service setup. Creates and registers client and network services with the IO subsystem.
@@ -85,6 +88,7 @@ impl ClientService {
 		db_config.wal = config.db_wal;
 
 		let pruning = config.pruning;
+		let verify_state_chunks = config.snapshot.verify_state_chunks;
 		let client = try!(Client::new(config, &spec, client_path, miner, io_service.channel(), &db_config));
 
 		let snapshot_params = SnapServiceParams {
@@ -95,6 +99,7 @@ impl ClientService {
 			channel: io_service.channel(),
 			snapshot_root: snapshot_path.into(),
 			db_restore: client.clone(),
+			verify_state_chunks: verify_state_chunks,
 		};
 		let snapshot = Arc::new(try!(SnapshotService::new(snapshot_params)));
 
@@ -146,6 +151,15 @@ impl ClientService {
 	pub fn add_notify(&self, notify: Arc<ChainNotify>) {
 		self.client.add_notify(notify);
 	}
+
+	/// Shut the client down: abort any snapshot restoration, wait for in-flight block imports
+	/// to finish and flush the database. The database is closed when the service is dropped.
+	///
+	/// The network (and with it sync) should be stopped first so that no new blocks are fed in.
+	pub fn shutdown(&self) {
+		::snapshot::SnapshotService::abort_restore(&*self.snapshot);
+		self.client.shutdown();
+	}
 }
 
 impl MayPanic for ClientService {
@@ -197,6 +211,11 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 					warn!("Failed to take snapshot at block #{}: {}", num, e);
 				}
 			}
+			ClientIoMessage::TakeSnapshotTo(num, ref path) => {
+				if let Err(e) = self.snapshot.take_requested_snapshot(&*self.client, num, path) {
+					warn!("Failed to take snapshot at block #{} into {}: {}", num, path.display(), e);
+				}
+			}
 			_ => {} // ignore other messages
 		}
 	}
@@ -241,7 +260,13 @@ mod tests {
 	use devtools::*;
 	use client::ClientConfig;
 	use std::sync::Arc;
+	use std::sync::atomic::{AtomicBool, Ordering};
+	use std::thread;
+	use std::time::Duration;
 	use miner::Miner;
+	use client::{BlockChainClient, BlockImportError};
+	use error::ImportError;
+	use io::MayPanic;
 
 	#[test]
 	fn it_can_be_started() {
@@ -272,4 +297,54 @@ mod tests {
 		drop(service.unwrap());
 		::std::thread::park_timeout(::std::time::Duration::from_millis(100));
 	}
+
+	#[test]
+	fn shuts_down_cleanly_during_import() {
+		let temp_path = RandomTempPath::new();
+		let path = temp_path.as_path().to_owned();
+		let mut client_path = path.clone();
+		client_path.push("client");
+		let mut snapshot_path = path.clone();
+		snapshot_path.push("snapshot");
+
+		let spec = get_test_spec();
+		let service = ClientService::start(
+			ClientConfig::default(),
+			&spec,
+			&client_path,
+			&snapshot_path,
+			&path,
+			Arc::new(Miner::with_spec(&spec)),
+		).unwrap();
+
+		let panicked = Arc::new(AtomicBool::new(false));
+		let p = panicked.clone();
+		service.on_panic(move |_| p.store(true, Ordering::SeqCst));
+
+		let client = service.client();
+		let importer = {
+			let client = client.clone();
+			thread::spawn(move || {
+				for block in get_good_dummy_block_seq(500) {
+					if client.import_block(block).is_err() {
+						break;
+					}
+				}
+			})
+		};
+
+		thread::sleep(Duration::from_millis(50));
+		service.shutdown();
+		importer.join().unwrap();
+
+		match client.import_block(get_good_dummy_block()) {
+			Err(BlockImportError::Import(ImportError::ShuttingDown)) => {},
+			other => panic!("expected import to be rejected after shutdown, got {:?}", other),
+		}
+
+		// let the io service drain any verified blocks.
+		thread::sleep(Duration::from_millis(100));
+		assert!(!panicked.load(Ordering::SeqCst));
+		drop(service);
+	}
 }
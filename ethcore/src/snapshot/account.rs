@@ -19,8 +19,8 @@
 use account_db::{AccountDB, AccountDBMut};
 use snapshot::Error;
 
-use util::{U256, FixedHash, H256, Bytes, HashDB, SHA3_EMPTY};
-use util::trie::{TrieDB, Trie};
+use util::{U256, FixedHash, H256, Bytes, HashDB, Hashable, SHA3_EMPTY};
+use util::trie::{TrieDB, TrieDBIterator};
 use rlp::{Rlp, RlpStream, Stream, UntrustedRlp, View};
 
 use std::collections::{HashMap, HashSet};
@@ -92,8 +92,9 @@ impl Account {
 
 		let mut pairs = Vec::new();
 
-		for (k, v) in db.iter() {
-			pairs.push((k, v));
+		let mut iter = TrieDBIterator::new(&db);
+		while let Some(item) = iter.next_checked() {
+			pairs.push(try!(item));
 		}
 
 		let mut stream = RlpStream::new_list(pairs.len());
@@ -201,6 +202,57 @@ impl Account {
 	}
 }
 
+/// Account state decoded from a snapshot without rebuilding its storage trie.
+#[derive(PartialEq, Clone, Debug)]
+pub struct AccountState {
+	/// Account nonce.
+	pub nonce: U256,
+	/// Account balance.
+	pub balance: U256,
+	/// Hash of the account code.
+	pub code_hash: H256,
+	/// Account code, if it was stored along with the account.
+	/// Code shared with accounts written earlier is only referred to by hash.
+	pub code: Option<Bytes>,
+	/// Storage values, keyed by the hash of the storage key.
+	pub storage: HashMap<H256, H256>,
+}
+
+impl AccountState {
+	/// Decode the account state from a fat rlp.
+	pub fn from_fat_rlp(rlp: UntrustedRlp) -> Result<Self, ::error::Error> {
+		let code_state: CodeState = {
+			let raw: u8 = try!(rlp.val_at(2));
+			try!(CodeState::from(raw))
+		};
+
+		let (code_hash, code) = match code_state {
+			CodeState::Empty => (SHA3_EMPTY, None),
+			CodeState::Inline => {
+				let code: Bytes = try!(rlp.val_at(3));
+				(code.sha3(), Some(code))
+			}
+			CodeState::Hash => (try!(rlp.val_at(3)), None),
+		};
+
+		let mut storage = HashMap::new();
+		for pair_rlp in try!(rlp.at(4)).iter() {
+			let k: Bytes = try!(pair_rlp.val_at(0));
+			let v: Bytes = try!(pair_rlp.val_at(1));
+			let value: U256 = try!(UntrustedRlp::new(&v).as_val());
+			storage.insert(H256::from_slice(&k), value.into());
+		}
+
+		Ok(AccountState {
+			nonce: try!(rlp.val_at(0)),
+			balance: try!(rlp.val_at(1)),
+			code_hash: code_hash,
+			code: code,
+			storage: storage,
+		})
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use account_db::{AccountDB, AccountDBMut};
@@ -213,7 +265,7 @@ mod tests {
 
 	use std::collections::{HashSet, HashMap};
 
-	use super::Account;
+	use super::{Account, AccountState};
 
 	#[test]
 	fn encoding_basic() {
@@ -312,4 +364,32 @@ mod tests {
 		assert_eq!(maybe_code, Some(b"this is definitely code".to_vec()));
 		assert_eq!(acc, account1);
 	}
+
+	#[test]
+	fn decoding_state() {
+		let mut db = get_temp_journal_db();
+		let mut db = &mut **db;
+		let addr = Address::random();
+
+		let code_hash = {
+			let mut acct_db = AccountDBMut::new(db.as_hashdb_mut(), &addr);
+			acct_db.insert(b"this is definitely code")
+		};
+		let account = Account {
+			nonce: 50.into(),
+			balance: 123456789.into(),
+			storage_root: SHA3_NULL_RLP,
+			code_hash: code_hash,
+		};
+
+		let fat_rlp = account.to_fat_rlp(&AccountDB::new(db.as_hashdb(), &addr), &mut Default::default()).unwrap();
+		let state = AccountState::from_fat_rlp(UntrustedRlp::new(&fat_rlp)).unwrap();
+		assert_eq!(state, AccountState {
+			nonce: 50.into(),
+			balance: 123456789.into(),
+			code_hash: code_hash,
+			code: Some(b"this is definitely code".to_vec()),
+			storage: HashMap::new(),
+		});
+	}
 }
@@ -20,7 +20,7 @@ use std::fmt;
 
 use ids::BlockID;
 
-use util::H256;
+use util::{H256, Bytes, ToPretty};
 use util::trie::TrieError;
 use rlp::DecoderError;
 
@@ -29,6 +29,8 @@ use rlp::DecoderError;
 pub enum Error {
 	/// Invalid starting block for snapshot.
 	InvalidStartingBlock(BlockID),
+	/// Starting block for snapshot is not on the canonical chain.
+	NonCanonicalStartingBlock(H256),
 	/// Block not found.
 	BlockNotFound(H256),
 	/// Incomplete chain.
@@ -39,6 +41,22 @@ pub enum Error {
 	MissingCode(Vec<H256>),
 	/// Unrecognized code encoding.
 	UnrecognizedCodeState(u8),
+	/// Chunked block number differs from the snapshot's block number (expected, found).
+	BlockNumberMismatch(u64, u64),
+	/// Chunked block hash differs from the snapshot's block hash (expected, found).
+	BlockHashMismatch(H256, H256),
+	/// Chunked state root differs from the snapshot's state root (expected, found).
+	StateRootMismatch(H256, H256),
+	/// Accounts in a state chunk form a trie with an unexpected root (expected, found).
+	ChunkRootMismatch(H256, H256),
+	/// Snapshot relies on a format feature this client doesn't support.
+	UnsupportedSnapshotFeature(String),
+	/// No valid copy of a chunk could be found.
+	ChunkUnavailable(H256),
+	/// Requested header range is empty or too large (from, to).
+	BadHeaderRange(u64, u64),
+	/// Part of the state trie below the given account key is missing or corrupt.
+	CorruptAccount(Bytes, TrieError),
 	/// Trie error.
 	Trie(TrieError),
 	/// Decoder error.
@@ -51,12 +69,29 @@ impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
 			Error::InvalidStartingBlock(ref id) => write!(f, "Invalid starting block: {:?}", id),
+			Error::NonCanonicalStartingBlock(ref hash) => write!(f, "Starting block {} is not on the canonical chain", hash),
 			Error::BlockNotFound(ref hash) => write!(f, "Block not found in chain: {}", hash),
 			Error::IncompleteChain => write!(f, "Cannot create snapshot due to incomplete chain."),
 			Error::OldBlockPrunedDB => write!(f, "Attempted to create a snapshot at an old block while using \
 				a pruned database. Please re-run with the --pruning archive flag."),
 			Error::MissingCode(ref missing) => write!(f, "Incomplete snapshot: {} contract codes not found.", missing.len()),
 			Error::UnrecognizedCodeState(state) => write!(f, "Unrecognized code encoding ({})", state),
+			Error::BlockNumberMismatch(ref expected, ref found) => write!(f, "Inconsistent snapshot: expected block number {}, \
+				chunked up to block {}.", expected, found),
+			Error::BlockHashMismatch(ref expected, ref found) => write!(f, "Inconsistent snapshot: expected block hash {}, \
+				chunked up to block {}.", expected, found),
+			Error::StateRootMismatch(ref expected, ref found) => write!(f, "Inconsistent snapshot: expected state root {}, \
+				chunked state root {}.", expected, found),
+			Error::ChunkRootMismatch(ref expected, ref found) => write!(f, "Bad state chunk: expected account trie root {}, \
+				found {}.", expected, found),
+			Error::UnsupportedSnapshotFeature(ref name) => write!(f, "Snapshot uses unsupported format feature '{}'. \
+				Please upgrade to restore it.", name),
+			Error::ChunkUnavailable(ref hash) => write!(f, "No valid copy of chunk {} found.", hash),
+			Error::BadHeaderRange(ref from, ref to) if from > to => write!(f, "Cannot export headers {} to {}: \
+				the first block is after the last one.", from, to),
+			Error::BadHeaderRange(ref from, ref to) => write!(f, "Cannot export headers {} to {}: at most {} \
+				headers can be exported at once.", from, to, super::headers::MAX_EXPORTED_HEADERS),
+			Error::CorruptAccount(ref key, ref err) => write!(f, "Corrupt state at account key {}: {}", key.pretty(), err),
 			Error::Io(ref err) => err.fmt(f),
 			Error::Decoder(ref err) => err.fmt(f),
 			Error::Trie(ref err) => err.fmt(f),
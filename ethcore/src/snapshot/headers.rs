@@ -0,0 +1,113 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Header-only export and import.
+//! Header verifiers and checkpoint servers don't need block bodies or receipts,
+//! so a range of headers can be exported and imported on its own.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use blockchain::{BlockChain, BlockProvider};
+use header::{Header, BlockNumber};
+use views::BlockView;
+
+use util::hash::H256;
+use rlp::{RlpStream, Stream, UntrustedRlp, View};
+
+use super::Error;
+
+/// Maximum number of headers exported at once.
+pub const MAX_EXPORTED_HEADERS: u64 = 65536;
+
+/// Write the headers of blocks `from` to `to` (inclusive) to `out` as an RLP list.
+pub fn export_headers<W: Write>(chain: &BlockChain, from: BlockNumber, to: BlockNumber, out: &mut W) -> Result<(), Error> {
+	if from > to || to - from >= MAX_EXPORTED_HEADERS {
+		return Err(Error::BadHeaderRange(from, to));
+	}
+
+	let mut stream = RlpStream::new_list((to - from + 1) as usize);
+	for number in from..(to + 1) {
+		let hash = try!(chain.block_hash(number).ok_or(Error::IncompleteChain));
+		let block = try!(chain.block(&hash).ok_or(Error::BlockNotFound(hash)));
+		stream.append_raw(BlockView::new(&block).header_view().rlp().as_raw(), 1);
+	}
+
+	try!(out.write_all(&stream.out()));
+	Ok(())
+}
+
+/// Feed headers produced by `export_headers` into the store.
+/// Returns the number of imported headers.
+pub fn import_headers_only(store: &mut HeaderStore, data: &[u8]) -> Result<usize, Error> {
+	let rlp = UntrustedRlp::new(data);
+	let mut imported = 0;
+	for item in rlp.iter() {
+		let header: Header = try!(item.as_val());
+		try!(store.insert(header));
+		imported += 1;
+	}
+	Ok(imported)
+}
+
+/// In-memory store of headers, without bodies, receipts or state.
+#[derive(Default)]
+pub struct HeaderStore {
+	headers: HashMap<H256, Header>,
+	best: Option<H256>,
+}
+
+impl HeaderStore {
+	/// Create an empty store.
+	pub fn new() -> Self {
+		HeaderStore::default()
+	}
+
+	/// Insert a header. Its parent must already be known, unless the store is empty.
+	pub fn insert(&mut self, header: Header) -> Result<(), Error> {
+		if !self.headers.is_empty() && !self.headers.contains_key(header.parent_hash()) {
+			return Err(Error::BlockNotFound(header.parent_hash().clone()));
+		}
+
+		let hash = header.hash();
+		let is_best = self.best_header().map_or(true, |best| header.number() > best.number());
+		self.headers.insert(hash, header);
+		if is_best {
+			self.best = Some(hash);
+		}
+		Ok(())
+	}
+
+	/// Get a header by hash.
+	pub fn header(&self, hash: &H256) -> Option<&Header> {
+		self.headers.get(hash)
+	}
+
+	/// Get the header with the highest number.
+	pub fn best_header(&self) -> Option<&Header> {
+		self.best.as_ref().and_then(|hash| self.headers.get(hash))
+	}
+
+	/// Number of stored headers.
+	pub fn len(&self) -> usize {
+		self.headers.len()
+	}
+
+	/// Whether the store has no headers.
+	pub fn is_empty(&self) -> bool {
+		self.headers.is_empty()
+	}
+}
@@ -120,13 +120,16 @@ impl SnapshotWriter for PackedWriter {
 	fn finish(mut self, manifest: ManifestData) -> io::Result<()> {
 		// we ignore the hashes fields of the manifest under the assumption that
 		// they are consistent with ours.
-		let mut stream = RlpStream::new_list(5);
+		let mut stream = RlpStream::new_list(8);
 		stream
 			.append(&self.state_hashes)
 			.append(&self.block_hashes)
 			.append(&manifest.state_root)
 			.append(&manifest.block_number)
-			.append(&manifest.block_hash);
+			.append(&manifest.block_hash)
+			.append(&manifest.state_chunk_roots)
+			.append(&manifest.features)
+			.append(&manifest.engine);
 
 		let manifest_rlp = stream.out();
 
@@ -209,6 +212,9 @@ pub trait SnapshotReader {
 	/// Get raw chunk data by hash. implementation defined behavior
 	/// if a chunk not in the manifest is requested.
 	fn chunk(&self, hash: H256) -> io::Result<Bytes>;
+
+	/// Get the total size in bytes of all (compressed) chunks in the manifest.
+	fn chunks_size(&self) -> io::Result<u64>;
 }
 
 /// Packed snapshot reader.
@@ -266,6 +272,18 @@ impl PackedReader {
 			state_root: try!(rlp.val_at(2)),
 			block_number: try!(rlp.val_at(3)),
 			block_hash: try!(rlp.val_at(4)),
+			state_chunk_roots: match rlp.item_count() > 5 {
+				true => try!(rlp.val_at(5)),
+				false => Vec::new(),
+			},
+			features: match rlp.item_count() > 6 {
+				true => try!(rlp.val_at(6)),
+				false => Vec::new(),
+			},
+			engine: match rlp.item_count() > 7 {
+				true => try!(rlp.val_at(7)),
+				false => String::new(),
+			},
 		};
 
 		Ok(Some(PackedReader {
@@ -275,6 +293,11 @@ impl PackedReader {
 			manifest: manifest
 		}))
 	}
+
+	/// Get the length and offset in the file of the chunk with the given hash.
+	pub fn chunk_location(&self, hash: &H256) -> Option<(u64, u64)> {
+		self.state_hashes.get(hash).or_else(|| self.block_hashes.get(hash)).cloned()
+	}
 }
 
 impl SnapshotReader for PackedReader {
@@ -295,6 +318,10 @@ impl SnapshotReader for PackedReader {
 
 		Ok(buf)
 	}
+
+	fn chunks_size(&self) -> io::Result<u64> {
+		Ok(self.state_hashes.values().chain(self.block_hashes.values()).fold(0, |size, &(len, _)| size + len))
+	}
 }
 
 /// reader for "loose" snapshots
@@ -340,6 +367,14 @@ impl SnapshotReader for LooseReader {
 
 		Ok(buf)
 	}
+
+	fn chunks_size(&self) -> io::Result<u64> {
+		let mut size = 0;
+		for hash in self.manifest.state_hashes.iter().chain(&self.manifest.block_hashes) {
+			size += try!(fs::metadata(self.dir.join(hash.hex()))).len();
+		}
+		Ok(size)
+	}
 }
 
 #[cfg(test)]
@@ -348,6 +383,7 @@ mod tests {
 	use util::sha3::Hashable;
 
 	use snapshot::ManifestData;
+	use snapshot::tests::helpers::dummy_manifest;
 	use super::{SnapshotWriter, SnapshotReader, PackedWriter, PackedReader, LooseWriter, LooseReader};
 
 	const STATE_CHUNKS: &'static [&'static [u8]] = &[b"dog", b"cat", b"hello world", b"hi", b"notarealchunk"];
@@ -374,17 +410,16 @@ mod tests {
 		}
 
 		let manifest = ManifestData {
-			state_hashes: state_hashes,
-			block_hashes: block_hashes,
-			state_root: b"notarealroot".sha3(),
-			block_number: 12345678987654321,
-			block_hash: b"notarealblock".sha3(),
+			state_chunk_roots: vec![b"notarealchunkroot".sha3()],
+			features: vec!["code-dedup-global".into()],
+			..dummy_manifest(state_hashes, block_hashes, b"notarealroot".sha3(), 12345678987654321, b"notarealblock".sha3())
 		};
 
 		writer.finish(manifest.clone()).unwrap();
 
 		let reader = PackedReader::new(path.as_path()).unwrap().unwrap();
 		assert_eq!(reader.manifest(), &manifest);
+		assert_eq!(reader.chunks_size().unwrap(), STATE_CHUNKS.iter().chain(BLOCK_CHUNKS).map(|c| c.len() as u64).fold(0, |a, b| a + b));
 
 		for hash in manifest.state_hashes.iter().chain(&manifest.block_hashes) {
 			reader.chunk(hash.clone()).unwrap();
@@ -411,18 +446,13 @@ mod tests {
 			writer.write_block_chunk(chunk.sha3(), chunk).unwrap();
 		}
 
-		let manifest = ManifestData {
-			state_hashes: state_hashes,
-			block_hashes: block_hashes,
-			state_root: b"notarealroot".sha3(),
-			block_number: 12345678987654321,
-			block_hash: b"notarealblock".sha3(),
-		};
+		let manifest = dummy_manifest(state_hashes, block_hashes, b"notarealroot".sha3(), 12345678987654321, b"notarealblock".sha3());
 
 		writer.finish(manifest.clone()).unwrap();
 
 		let reader = LooseReader::new(path.as_path().into()).unwrap();
 		assert_eq!(reader.manifest(), &manifest);
+		assert_eq!(reader.chunks_size().unwrap(), STATE_CHUNKS.iter().chain(BLOCK_CHUNKS).map(|c| c.len() as u64).fold(0, |a, b| a + b));
 
 		for hash in manifest.state_hashes.iter().chain(&manifest.block_hashes) {
 			reader.chunk(hash.clone()).unwrap();
@@ -17,6 +17,7 @@
 //! Snapshot creation, restoration, and network service.
 
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
@@ -26,32 +27,38 @@ use engines::Engine;
 use ids::BlockID;
 use views::BlockView;
 
-use util::{Bytes, Hashable, HashDB, snappy};
+use util::{Address, Bytes, Hashable, HashDB, ToPretty, snappy};
 use util::memorydb::MemoryDB;
 use util::Mutex;
 use util::hash::{FixedHash, H256};
 use util::journaldb::{self, Algorithm, JournalDB};
 use util::kvdb::Database;
-use util::trie::{TrieDB, TrieDBMut, Trie, TrieMut};
+use util::trie::{TrieDB, TrieDBIterator, TrieDBMut, TrieError, TrieMut};
 use util::sha3::SHA3_NULL_RLP;
-use rlp::{RlpStream, Stream, UntrustedRlp, View, Compressible, RlpType};
+use util::triehash::{trie_root, ordered_trie_root};
+use rlp::{RlpStream, Stream, UntrustedRlp, View, Compressible, RlpType, Encodable};
 
 use self::account::Account;
+pub use self::account::AccountState;
 use self::block::AbridgedBlock;
-use self::io::SnapshotWriter;
+use self::io::{SnapshotReader, SnapshotWriter};
 
 use crossbeam::{scope, ScopedJoinHandle};
 use rand::{Rng, OsRng};
 
 pub use self::error::Error;
+pub use self::headers::{export_headers, import_headers_only, HeaderStore};
 
 pub use self::service::{Service, DatabaseRestore};
 pub use self::traits::{SnapshotService, RemoteSnapshotService};
 pub use self::watcher::Watcher;
 pub use types::snapshot_manifest::ManifestData;
 pub use types::restoration_status::RestorationStatus;
+pub use types::creation_status::CreationStatus;
 
+pub mod headers;
 pub mod io;
+pub mod repair;
 pub mod service;
 
 mod account;
@@ -73,6 +80,21 @@ const PREFERRED_CHUNK_SIZE: usize = 4 * 1024 * 1024;
 // How many blocks to include in a snapshot, starting from the head of the chain.
 const SNAPSHOT_BLOCKS: u64 = 30000;
 
+/// Snapshot format feature: contract code is written once for the whole snapshot and
+/// referred to by hash in later state chunks, rather than once per chunk.
+pub const FEATURE_CODE_DEDUP_GLOBAL: &'static str = "code-dedup-global";
+
+/// Snapshot format feature: the receipts of the included blocks are meant to be served after
+/// restoration, so they are checked against the receipts root of each block while restoring.
+pub const FEATURE_RECEIPT_INDEX: &'static str = "receipt-index";
+
+/// Snapshot format feature: corrupt accounts were left out, so the state doesn't match the
+/// state root of the manifest. Never supported, so such snapshots are neither served nor restored.
+pub const FEATURE_INCOMPLETE_STATE: &'static str = "incomplete-state";
+
+/// Snapshot format features understood when restoring snapshots.
+pub const SUPPORTED_FEATURES: &'static [&'static str] = &[FEATURE_CODE_DEDUP_GLOBAL, FEATURE_RECEIPT_INDEX];
+
 /// A progress indicator for snapshots.
 #[derive(Debug, Default)]
 pub struct Progress {
@@ -106,40 +128,155 @@ impl Progress {
 	/// Whether the snapshot is complete.
 	pub fn done(&self) -> bool  { self.done.load(Ordering::Acquire) }
 
+	/// Get a copy of the current progress.
+	pub fn status(&self) -> CreationStatus {
+		CreationStatus {
+			accounts: self.accounts() as u64,
+			blocks: self.blocks() as u64,
+			bytes: self.size() as u64,
+			done: self.done(),
+		}
+	}
+}
+
+/// Codec used to compress snapshot chunks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+	/// Snappy compression.
+	Snappy,
+}
+
+impl FromStr for Compression {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"snappy" => Ok(Compression::Snappy),
+			other => Err(format!("Unsupported snapshot compression: {}. Supported codecs: snappy.", other)),
+		}
+	}
+}
+
+/// Snapshot settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotConfig {
+	/// Maximum number of threads used to produce chunks. With a single thread state and blocks
+	/// are chunked one after another, otherwise concurrently, with the remaining threads
+	/// compressing state chunks in parallel. Chunks are identical whatever the limit.
+	pub threads: usize,
+	/// Codec used to compress chunks.
+	pub compression: Compression,
+	/// Whether to check each state chunk against the account trie root recorded for it
+	/// in the manifest while restoring, rather than only checking the final state root.
+	pub verify_state_chunks: bool,
+	/// Whether to mark taken snapshots as usable for serving the receipts of the included blocks.
+	pub include_receipt_index: bool,
+	/// Whether to leave out accounts whose state can't be read instead of aborting the snapshot.
+	/// Such snapshots don't match the state root of their manifest, so they are marked with
+	/// `FEATURE_INCOMPLETE_STATE` and can't be restored as they are; the skipped account keys are logged.
+	pub skip_corrupt_accounts: bool,
 }
+
+impl Default for SnapshotConfig {
+	fn default() -> Self {
+		SnapshotConfig {
+			threads: ::num_cpus::get(),
+			compression: Compression::Snappy,
+			verify_state_chunks: false,
+			include_receipt_index: false,
+			skip_corrupt_accounts: false,
+		}
+	}
+}
+
+/// Estimate the uncompressed size in bytes of the snapshot described by the given manifest.
+///
+/// Chunks are cut off at around `PREFERRED_CHUNK_SIZE` bytes before compression,
+/// so this is a rough upper bound on the decoded size of the snapshot data.
+pub fn estimate_size(manifest: &ManifestData) -> u64 {
+	(manifest.state_hashes.len() + manifest.block_hashes.len()) as u64 * PREFERRED_CHUNK_SIZE as u64
+}
+
+/// Check that the snapshot described by the given manifest doesn't rely on unsupported format features.
+pub fn check_features(manifest: &ManifestData) -> Result<(), Error> {
+	match manifest.features.iter().find(|f| !SUPPORTED_FEATURES.contains(&f.as_str())) {
+		Some(feature) => Err(Error::UnsupportedSnapshotFeature(feature.clone())),
+		None => Ok(()),
+	}
+}
+
 /// Take a snapshot using the given blockchain, starting block hash, and database, writing into the given writer.
+/// The name of the engine is recorded in the manifest.
 pub fn take_snapshot<W: SnapshotWriter + Send>(
+	engine: &Engine,
 	chain: &BlockChain,
 	block_at: H256,
 	state_db: &HashDB,
 	writer: W,
-	p: &Progress
+	p: &Progress,
+	config: &SnapshotConfig,
 ) -> Result<(), Error> {
 	let start_header = try!(chain.block_header(&block_at)
 		.ok_or(Error::InvalidStartingBlock(BlockID::Hash(block_at))));
 	let state_root = start_header.state_root();
 	let number = start_header.number();
 
+	// block chunks are walked back through the parents, which is only consistent with
+	// the canonical chain the manifest describes.
+	if chain.block_hash(number) != Some(block_at) {
+		return Err(Error::NonCanonicalStartingBlock(block_at));
+	}
+
 	info!("Taking snapshot starting at block {}", number);
 
 	let writer = Mutex::new(writer);
-	let (state_hashes, block_hashes) = try!(scope(|scope| {
-		let block_guard = scope.spawn(|| chunk_blocks(chain, (number, block_at), &writer, p));
-		let state_res = chunk_state(state_db, state_root, &writer, p);
-
-		state_res.and_then(|state_hashes| {
-			block_guard.join().map(|block_hashes| (state_hashes, block_hashes))
-		})
-	}));
+	let chunk_accounts = |compressors| match config.skip_corrupt_accounts {
+		true => chunk_state_skipping_corrupt(state_db, state_root, &writer, p, compressors).map(|(hashes, roots, skipped)| {
+			if !skipped.is_empty() {
+				warn!("Skipped {} corrupt parts of the state; the snapshot doesn't match its state root.", skipped.len());
+			}
+			(hashes, roots, !skipped.is_empty())
+		}),
+		false => chunk_state_with_limit(state_db, state_root, &writer, p, compressors).map(|(hashes, roots)| (hashes, roots, false)),
+	};
+	let ((state_hashes, state_chunk_roots, incomplete), (block_hashes, top_block)) = match config.threads {
+		0 | 1 => {
+			let state_res = try!(chunk_accounts(1));
+			(state_res, try!(chunk_blocks_with_top(chain, (number, block_at), &writer, p)))
+		},
+		threads => try!(scope(|scope| {
+			let block_guard = scope.spawn(|| chunk_blocks_with_top(chain, (number, block_at), &writer, p));
+			let state_res = chunk_accounts(threads - 1);
+
+			state_res.and_then(|state_res| {
+				block_guard.join().map(|block_res| (state_res, block_res))
+			})
+		})),
+	};
 
 	info!("produced {} state chunks and {} block chunks.", state_hashes.len(), block_hashes.len());
 
+	// the chunkers run concurrently, so make sure they agree on the snapshot height
+	// before committing to a manifest.
+	try!(check_consistency(chain, (number, block_at), state_root, top_block));
+
+	let mut features = vec![FEATURE_CODE_DEDUP_GLOBAL.to_owned()];
+	if config.include_receipt_index {
+		features.push(FEATURE_RECEIPT_INDEX.to_owned());
+	}
+	if incomplete {
+		features.push(FEATURE_INCOMPLETE_STATE.to_owned());
+	}
+
 	let manifest_data = ManifestData {
 		state_hashes: state_hashes,
 		block_hashes: block_hashes,
 		state_root: *state_root,
 		block_number: number,
 		block_hash: block_at,
+		state_chunk_roots: state_chunk_roots,
+		features: features,
+		engine: engine.name().to_owned(),
 	};
 
 	try!(writer.into_inner().finish(manifest_data));
@@ -159,6 +296,8 @@ struct BlockChunker<'a> {
 	snappy_buffer: Vec<u8>,
 	writer: &'a Mutex<SnapshotWriter + 'a>,
 	progress: &'a Progress,
+	// number and hash of the first (highest) block chunked.
+	top: Option<(u64, H256)>,
 }
 
 impl<'a> BlockChunker<'a> {
@@ -173,6 +312,10 @@ impl<'a> BlockChunker<'a> {
 				.ok_or(Error::BlockNotFound(self.current_hash)));
 
 			let view = BlockView::new(&block);
+			if self.top.is_none() {
+				self.top = Some((view.header_view().number(), self.current_hash));
+			}
+
 			let abridged_rlp = AbridgedBlock::from_block_view(&view).into_inner();
 
 			let pair = {
@@ -253,6 +396,12 @@ impl<'a> BlockChunker<'a> {
 /// This function assumes the directory exists already.
 /// Returns a list of chunk hashes, with the first having the blocks furthest from the genesis.
 pub fn chunk_blocks<'a>(chain: &'a BlockChain, start_block_info: (u64, H256), writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<Vec<H256>, Error> {
+	chunk_blocks_with_top(chain, start_block_info, writer, progress).map(|(hashes, _)| hashes)
+}
+
+// Like `chunk_blocks`, but also yields the number and hash of the highest block chunked,
+// if any blocks were chunked at all.
+fn chunk_blocks_with_top<'a>(chain: &'a BlockChain, start_block_info: (u64, H256), writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<(Vec<H256>, Option<(u64, H256)>), Error> {
 	let (start_number, start_hash) = start_block_info;
 
 	let first_hash = if start_number < SNAPSHOT_BLOCKS {
@@ -271,29 +420,62 @@ pub fn chunk_blocks<'a>(chain: &'a BlockChain, start_block_info: (u64, H256), wr
 		snappy_buffer: vec![0; snappy::max_compressed_len(PREFERRED_CHUNK_SIZE)],
 		writer: writer,
 		progress: progress,
+		top: None,
 	};
 
 	try!(chunker.chunk_all(first_hash));
 
-	Ok(chunker.hashes)
+	Ok((chunker.hashes, chunker.top))
+}
+
+/// Check that the results of the block and state chunkers are consistent with
+/// the header at the snapshot's starting block.
+///
+/// `start_block_info` is the number and hash the snapshot was requested at, `state_root`
+/// is the root the state chunker walked, and `top_block` is the highest block the block
+/// chunker processed, if any.
+fn check_consistency(chain: &BlockChain, start_block_info: (u64, H256), state_root: &H256, top_block: Option<(u64, H256)>) -> Result<(), Error> {
+	let (number, hash) = start_block_info;
+	let header = try!(chain.block_header(&hash).ok_or(Error::BlockNotFound(hash)));
+
+	if header.number() != number {
+		return Err(Error::BlockNumberMismatch(number, header.number()));
+	}
+
+	if header.state_root() != state_root {
+		return Err(Error::StateRootMismatch(*header.state_root(), *state_root));
+	}
+
+	match top_block {
+		Some((top_number, _)) if top_number != number => Err(Error::BlockNumberMismatch(number, top_number)),
+		Some((_, top_hash)) if top_hash != hash => Err(Error::BlockHashMismatch(hash, top_hash)),
+		_ => Ok(()),
+	}
 }
 
 /// State trie chunker.
 struct StateChunker<'a> {
 	hashes: Vec<H256>,
+	roots: Vec<H256>,
 	rlps: Vec<Bytes>,
+	thin_pairs: Vec<(Bytes, Bytes)>,
 	cur_size: usize,
 	snappy_buffer: Vec<u8>,
+	// uncompressed chunks with their number of accounts, waiting to be compressed.
+	pending: Vec<(Bytes, usize)>,
+	// maximum number of chunks compressed at once.
+	compressors: usize,
 	writer: &'a Mutex<SnapshotWriter + 'a>,
 	progress: &'a Progress,
 }
 
 impl<'a> StateChunker<'a> {
-	// Push a key, value pair to be encoded.
+	// Push a key, value pair to be encoded, along with the thin account RLP
+	// which goes into the chunk's account trie root.
 	//
 	// If the buffer is greater than the desired chunk size,
 	// this will write out the data to disk.
-	fn push(&mut self, account_hash: Bytes, data: Bytes) -> Result<(), Error> {
+	fn push(&mut self, account_hash: Bytes, data: Bytes, thin_rlp: Bytes) -> Result<(), Error> {
 		let pair = {
 			let mut stream = RlpStream::new_list(2);
 			stream.append(&account_hash).append_raw(&data, 1);
@@ -306,12 +488,13 @@ impl<'a> StateChunker<'a> {
 
 		self.cur_size += pair.len();
 		self.rlps.push(pair);
+		self.thin_pairs.push((account_hash, thin_rlp));
 
 		Ok(())
 	}
 
-	// Write out the buffer to disk, pushing the created chunk's hash to
-	// the list.
+	// Finish the chunk in the buffer, pushing its account trie root to the list.
+	// The chunk is written out once enough chunks are pending to keep all compressors busy.
 	fn write_chunk(&mut self) -> Result<(), Error> {
 		let num_entries = self.rlps.len();
 		let mut stream = RlpStream::new_list(num_entries);
@@ -319,22 +502,56 @@ impl<'a> StateChunker<'a> {
 			stream.append_raw(&rlp, 1);
 		}
 
-		let raw_data = stream.out();
+		self.pending.push((stream.out(), num_entries));
+		self.roots.push(trie_root(self.thin_pairs.drain(..).collect()));
+		self.cur_size = 0;
+
+		if self.pending.len() >= self.compressors {
+			try!(self.flush());
+		}
+
+		Ok(())
+	}
+
+	// Compress and write out all pending chunks, pushing the created chunks' hashes to the list.
+	// Chunks are written in the order they were produced.
+	fn flush(&mut self) -> Result<(), Error> {
+		let pending: Vec<_> = self.pending.drain(..).collect();
+		if pending.len() == 1 {
+			let (ref raw_data, num_entries) = pending[0];
+			let compressed_size = snappy::compress_into(raw_data, &mut self.snappy_buffer);
+			let hash = try!(self.write_compressed(&self.snappy_buffer[..compressed_size], raw_data.len(), num_entries));
+			self.hashes.push(hash);
+			return Ok(());
+		}
+
+		let compressed: Vec<Bytes> = scope(|scope| {
+			let handles: Vec<_> = pending.iter()
+				.map(|&(ref raw_data, _)| scope.spawn(move || snappy::compress(raw_data)))
+				.collect();
+
+			handles.into_iter().map(|handle| handle.join()).collect()
+		});
+
+		for (compressed, &(ref raw_data, num_entries)) in compressed.iter().zip(pending.iter()) {
+			let hash = try!(self.write_compressed(compressed, raw_data.len(), num_entries));
+			self.hashes.push(hash);
+		}
+
+		Ok(())
+	}
 
-		let compressed_size = snappy::compress_into(&raw_data, &mut self.snappy_buffer);
-		let compressed = &self.snappy_buffer[..compressed_size];
+	// Write out a compressed chunk, returning its hash.
+	fn write_compressed(&self, compressed: &[u8], raw_size: usize, num_entries: usize) -> Result<H256, Error> {
 		let hash = compressed.sha3();
 
 		try!(self.writer.lock().write_state_chunk(hash, compressed));
-		trace!(target: "snapshot", "wrote state chunk. size: {}, uncompressed size: {}", compressed_size, raw_data.len());
+		trace!(target: "snapshot", "wrote state chunk. size: {}, uncompressed size: {}", compressed.len(), raw_size);
 
 		self.progress.accounts.fetch_add(num_entries, Ordering::SeqCst);
-		self.progress.size.fetch_add(compressed_size, Ordering::SeqCst);
+		self.progress.size.fetch_add(compressed.len(), Ordering::SeqCst);
 
-		self.hashes.push(hash);
-		self.cur_size = 0;
-
-		Ok(())
+		Ok(hash)
 	}
 }
 
@@ -344,13 +561,55 @@ impl<'a> StateChunker<'a> {
 /// Returns a list of hashes of chunks created, or any error it may
 /// have encountered.
 pub fn chunk_state<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<Vec<H256>, Error> {
+	chunk_state_with_roots(db, root, writer, progress).map(|(hashes, _)| hashes)
+}
+
+/// Like `chunk_state`, but also returns the root of the trie formed by the accounts
+/// of each chunk. Chunks hold contiguous ranges of account keys, so these let a
+/// restoration verify each chunk as soon as it arrives.
+pub fn chunk_state_with_roots<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<(Vec<H256>, Vec<H256>), Error> {
+	chunk_state_with_limit(db, root, writer, progress, 1)
+}
+
+/// Like `chunk_state_with_roots`, but compresses up to `compressors` chunks at once.
+/// The produced chunks don't depend on the limit.
+pub fn chunk_state_with_limit<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress, compressors: usize) -> Result<(Vec<H256>, Vec<H256>), Error> {
+	chunk_state_inner(db, root, writer, progress, compressors, None)
+}
+
+/// Like `chunk_state_with_limit`, but leaves out accounts whose state trie nodes are missing
+/// instead of failing. Also returns the keys of the skipped accounts with the errors encountered;
+/// a key is shorter than an address hash when a whole part of the account trie is missing.
+pub fn chunk_state_skipping_corrupt<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress, compressors: usize) -> Result<(Vec<H256>, Vec<H256>, Vec<(Bytes, TrieError)>), Error> {
+	let mut skipped = Vec::new();
+	let (hashes, roots) = try!(chunk_state_inner(db, root, writer, progress, compressors, Some(&mut skipped)));
+	Ok((hashes, roots, skipped))
+}
+
+// Fail with the error or note the corrupt account as skipped.
+fn corrupt_account(key: Bytes, err: TrieError, skipped: &mut Option<&mut Vec<(Bytes, TrieError)>>) -> Result<(), Error> {
+	match *skipped {
+		Some(ref mut skipped) => {
+			warn!(target: "snapshot", "Skipping corrupt account {}: {}", key.pretty(), err);
+			skipped.push((key, err));
+			Ok(())
+		},
+		None => Err(Error::CorruptAccount(key, err)),
+	}
+}
+
+fn chunk_state_inner<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress, compressors: usize, mut skipped: Option<&mut Vec<(Bytes, TrieError)>>) -> Result<(Vec<H256>, Vec<H256>), Error> {
 	let account_trie = try!(TrieDB::new(db, &root));
 
 	let mut chunker = StateChunker {
 		hashes: Vec::new(),
+		roots: Vec::new(),
 		rlps: Vec::new(),
+		thin_pairs: Vec::new(),
 		cur_size: 0,
 		snappy_buffer: vec![0; snappy::max_compressed_len(PREFERRED_CHUNK_SIZE)],
+		pending: Vec::new(),
+		compressors: ::std::cmp::max(compressors, 1),
 		writer: writer,
 		progress: progress,
 	};
@@ -358,22 +617,40 @@ pub fn chunk_state<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter +
 	let mut used_code = HashSet::new();
 
 	// account_key here is the address' hash.
-	for (account_key, account_data) in account_trie.iter() {
+	let mut accounts = TrieDBIterator::new(&account_trie);
+	while let Some(item) = accounts.next_checked() {
+		let (account_key, account_data) = match item {
+			Ok(item) => item,
+			Err(err) => {
+				try!(corrupt_account(accounts.key(), *err, &mut skipped));
+				continue;
+			}
+		};
 		let account = Account::from_thin_rlp(account_data);
 		let account_key_hash = H256::from_slice(&account_key);
 
 		let account_db = AccountDB::from_hash(db, account_key_hash);
 
-		let fat_rlp = try!(account.to_fat_rlp(&account_db, &mut used_code));
+		let fat_rlp = match account.to_fat_rlp(&account_db, &mut used_code) {
+			Ok(fat_rlp) => fat_rlp,
+			Err(Error::Trie(err)) => {
+				try!(corrupt_account(account_key, err, &mut skipped));
+				continue;
+			}
+			Err(e) => return Err(e),
+		};
 		let compressed_rlp = UntrustedRlp::new(&fat_rlp).compress(RlpType::Snapshot).to_vec();
-		try!(chunker.push(account_key, compressed_rlp));
+		try!(chunker.push(account_key, compressed_rlp, account_data.to_vec()));
 	}
 
 	if chunker.cur_size != 0 {
 		try!(chunker.write_chunk());
 	}
+	if !chunker.pending.is_empty() {
+		try!(chunker.flush());
+	}
 
-	Ok(chunker.hashes)
+	Ok((chunker.hashes, chunker.roots))
 }
 
 /// Used to rebuild the state trie piece by piece.
@@ -397,6 +674,16 @@ impl StateRebuilder {
 
 	/// Feed an uncompressed state chunk into the rebuilder.
 	pub fn feed(&mut self, chunk: &[u8]) -> Result<(), ::error::Error> {
+		self.rebuild(chunk, None)
+	}
+
+	/// Feed an uncompressed state chunk into the rebuilder, failing before any of it
+	/// is committed to the database if its accounts don't form a trie with the expected root.
+	pub fn feed_verified(&mut self, chunk: &[u8], expected_root: &H256) -> Result<(), ::error::Error> {
+		self.rebuild(chunk, Some(expected_root))
+	}
+
+	fn rebuild(&mut self, chunk: &[u8], expected_root: Option<&H256>) -> Result<(), ::error::Error> {
 		let rlp = UntrustedRlp::new(chunk);
 		let account_fat_rlps: Vec<_> = rlp.iter().map(|r| r.as_raw()).collect();
 		let mut pairs = Vec::with_capacity(rlp.item_count());
@@ -411,7 +698,7 @@ impl StateRebuilder {
 
 		// build account tries in parallel.
 		// Todo [rob] keep a thread pool around so we don't do this per-chunk.
-		try!(scope(|scope| {
+		let rebuilt = try!(scope(|scope| {
 			let mut handles = Vec::new();
 			for (account_chunk, out_pairs_chunk) in account_fat_rlps.chunks(chunk_size).zip(pairs.chunks_mut(chunk_size)) {
 				let code_map = &self.code_map;
@@ -426,20 +713,33 @@ impl StateRebuilder {
 				handles.push(handle);
 			}
 
-			// consolidate all edits into the main overlay.
+			let mut rebuilt = Vec::with_capacity(handles.len());
 			for handle in handles {
 				let (thread_db, status): (MemoryDB, _) = try!(handle.join());
-				self.db.consolidate(thread_db);
+				rebuilt.push((thread_db, status));
+			}
 
-				chunk_code.extend(status.new_code);
+			Ok::<_, ::error::Error>(rebuilt)
+		}));
 
-				for (addr_hash, code_hash) in status.missing_code {
-					self.missing_code.entry(code_hash).or_insert_with(Vec::new).push(addr_hash);
-				}
+		// nothing of the chunk has touched the main overlay yet, so a mismatch leaves it intact.
+		if let Some(expected_root) = expected_root {
+			let root = trie_root(pairs.iter().map(|&(ref hash, ref thin_rlp)| (hash.to_vec(), thin_rlp.clone())).collect());
+			if root != *expected_root {
+				return Err(Error::ChunkRootMismatch(*expected_root, root).into());
 			}
+		}
 
-			Ok::<_, ::error::Error>(())
-		}));
+		// consolidate all edits into the main overlay.
+		for (thread_db, status) in rebuilt {
+			self.db.consolidate(thread_db);
+
+			chunk_code.extend(status.new_code);
+
+			for (addr_hash, code_hash) in status.missing_code {
+				self.missing_code.entry(code_hash).or_insert_with(Vec::new).push(addr_hash);
+			}
+		}
 
 		// patch up all missing code. must be done after collecting all new missing code entries.
 		for (code_hash, code) in chunk_code {
@@ -487,6 +787,38 @@ impl StateRebuilder {
 	pub fn state_root(&self) -> H256 { self.state_root }
 }
 
+/// Decode the state of given accounts from the state chunks of a snapshot, without rebuilding the state.
+/// Accounts which weren't requested are skipped along with their storage and code.
+pub fn extract_accounts(reader: &SnapshotReader, addresses: &[Address]) -> Result<HashMap<Address, AccountState>, ::error::Error> {
+	let mut wanted: HashMap<H256, Address> = addresses.iter().map(|address| (address.sha3(), address.clone())).collect();
+	let mut accounts = HashMap::new();
+
+	for chunk_hash in &reader.manifest().state_hashes {
+		if wanted.is_empty() {
+			break;
+		}
+
+		let raw = try!(reader.chunk(*chunk_hash));
+		let chunk = try!(snappy::decompress(&raw));
+		let rlp = UntrustedRlp::new(&chunk);
+
+		for account_rlp in rlp.iter() {
+			let hash: H256 = try!(account_rlp.val_at(0));
+			let address = match wanted.remove(&hash) {
+				Some(address) => address,
+				None => continue,
+			};
+
+			let decompressed = try!(account_rlp.at(1)).decompress(RlpType::Snapshot);
+			let state = try!(AccountState::from_fat_rlp(UntrustedRlp::new(&decompressed[..])));
+			trace!(target: "snapshot", "extracted account {:?}", address);
+			accounts.insert(address, state);
+		}
+	}
+
+	Ok(accounts)
+}
+
 #[derive(Default)]
 struct RebuiltStatus {
 	new_code: Vec<(H256, Bytes)>, // new code that's become available.
@@ -546,11 +878,15 @@ const POW_VERIFY_RATE: f32 = 0.02;
 /// chunk before it, as chunks may be submitted out-of-order.
 ///
 /// After all chunks have been submitted, we "glue" the chunks together.
+///
+/// Receipts and transaction addresses of every block, including the first block
+/// of each chunk and the best block, are indexed as the blocks are inserted.
 pub struct BlockRebuilder {
 	chain: BlockChain,
 	rng: OsRng,
 	disconnected: Vec<(u64, H256)>,
 	best_number: u64,
+	verify_receipts: bool,
 }
 
 impl BlockRebuilder {
@@ -561,14 +897,22 @@ impl BlockRebuilder {
 			rng: try!(OsRng::new()),
 			disconnected: Vec::new(),
 			best_number: best_number,
+			verify_receipts: false,
 		})
 	}
 
+	/// Check the receipts of every block against its receipts root before inserting it.
+	pub fn verify_receipts(mut self, verify: bool) -> Self {
+		self.verify_receipts = verify;
+		self
+	}
+
 	/// Feed the rebuilder an uncompressed block chunk.
 	/// Returns the number of blocks fed or any errors.
 	pub fn feed(&mut self, chunk: &[u8], engine: &Engine) -> Result<u64, ::error::Error> {
 		use basic_types::Seal::With;
-		use util::U256;
+		use error::BlockError;
+		use util::{U256, Mismatch};
 
 		let rlp = UntrustedRlp::new(chunk);
 		let item_count = rlp.item_count();
@@ -588,6 +932,16 @@ impl BlockRebuilder {
 			let block = try!(abridged_block.to_block(parent_hash, cur_number));
 			let block_bytes = block.rlp_bytes(With);
 
+			if self.verify_receipts {
+				let receipts_root = ordered_trie_root(receipts.iter().map(|r| r.rlp_bytes().to_vec()).collect());
+				if receipts_root != *block.header.receipts_root() {
+					return Err(BlockError::InvalidReceiptsRoot(Mismatch {
+						expected: *block.header.receipts_root(),
+						found: receipts_root,
+					}).into());
+				}
+			}
+
 			if self.rng.gen::<f32>() <= POW_VERIFY_RATE {
 				try!(engine.verify_block_seal(&block.header))
 			} else {
@@ -0,0 +1,196 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshot repair.
+//! Finds chunks of a packed snapshot which are missing or corrupt and patches
+//! them in place with valid copies taken from other copies of the same snapshot,
+//! so that a damaged snapshot doesn't have to be downloaded again as a whole.
+//! Valid copies come from any `ChunkSource`: local snapshot files and directories,
+//! or the peers of a running sync.
+
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+use util::{Bytes, Hashable};
+use util::hash::H256;
+
+use super::Error;
+use super::io::{SnapshotReader, PackedReader, LooseReader};
+
+/// Something which can provide compressed snapshot chunks by hash.
+pub trait ChunkSource {
+	/// Fetch the chunk with the given hash, if available.
+	/// The returned data is checked against the hash by the caller.
+	fn fetch_chunk(&self, hash: &H256) -> Option<Bytes>;
+}
+
+impl ChunkSource for PackedReader {
+	fn fetch_chunk(&self, hash: &H256) -> Option<Bytes> {
+		match self.chunk_location(hash) {
+			Some(_) => self.chunk(*hash).ok(),
+			None => None,
+		}
+	}
+}
+
+impl ChunkSource for LooseReader {
+	fn fetch_chunk(&self, hash: &H256) -> Option<Bytes> {
+		self.chunk(*hash).ok()
+	}
+}
+
+/// Sources are queried in order until one has a valid copy of the chunk.
+impl<T: ChunkSource> ChunkSource for Vec<T> {
+	fn fetch_chunk(&self, hash: &H256) -> Option<Bytes> {
+		self.iter().filter_map(|source| source.fetch_chunk(hash)).find(|chunk| chunk.sha3() == *hash)
+	}
+}
+
+impl ChunkSource for Box<ChunkSource> {
+	fn fetch_chunk(&self, hash: &H256) -> Option<Bytes> {
+		(**self).fetch_chunk(hash)
+	}
+}
+
+/// Get the hashes of chunks in the manifest which can't be read or whose data doesn't match their hash.
+pub fn corrupt_chunks(reader: &SnapshotReader) -> Vec<H256> {
+	let manifest = reader.manifest();
+	manifest.state_hashes.iter().chain(&manifest.block_hashes)
+		.filter(|hash| reader.chunk(**hash).map(|chunk| chunk.sha3() != **hash).unwrap_or(true))
+		.cloned()
+		.collect()
+}
+
+/// Replace the corrupt chunks of the packed snapshot at the given path with copies fetched from `source`.
+/// Returns the hashes of the repaired chunks. Fails without touching the file if any
+/// corrupt chunk has no valid copy in the source.
+pub fn repair_packed(path: &Path, source: &ChunkSource) -> Result<Vec<H256>, ::error::Error> {
+	let reader = try!(try!(PackedReader::new(path)).ok_or(Error::Io(::std::io::Error::new(
+		::std::io::ErrorKind::InvalidData, "not a packed snapshot"))));
+
+	let corrupt = corrupt_chunks(&reader);
+	let mut patches = Vec::with_capacity(corrupt.len());
+	for hash in &corrupt {
+		let (len, offset) = reader.chunk_location(hash).expect("corrupt chunks are taken from the manifest; qed");
+		match source.fetch_chunk(hash) {
+			Some(ref chunk) if chunk.sha3() == *hash && chunk.len() as u64 == len => patches.push((offset, chunk.clone())),
+			_ => return Err(Error::ChunkUnavailable(*hash).into()),
+		}
+	}
+
+	if patches.is_empty() {
+		return Ok(corrupt);
+	}
+
+	let mut file = try!(OpenOptions::new().write(true).open(path));
+	for (offset, chunk) in patches {
+		trace!(target: "snapshot", "patching chunk of size {} at offset {}", chunk.len(), offset);
+		try!(file.seek(SeekFrom::Start(offset)));
+		try!(file.write_all(&chunk));
+	}
+	try!(file.sync_all());
+
+	Ok(corrupt)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::fs::OpenOptions;
+	use std::io::{Seek, SeekFrom, Write};
+
+	use devtools::RandomTempPath;
+	use util::{Bytes, Hashable};
+	use util::hash::H256;
+
+	use snapshot::ManifestData;
+	use snapshot::tests::helpers::dummy_manifest;
+	use snapshot::io::{SnapshotWriter, SnapshotReader, PackedWriter, PackedReader};
+	use super::{ChunkSource, corrupt_chunks, repair_packed};
+
+	const STATE_CHUNKS: &'static [&'static [u8]] = &[b"dog", b"cat", b"hello world"];
+	const BLOCK_CHUNKS: &'static [&'static [u8]] = &[b"hello!", b"goodbye!"];
+
+	struct MockSource(HashMap<H256, Bytes>);
+
+	impl ChunkSource for MockSource {
+		fn fetch_chunk(&self, hash: &H256) -> Option<Bytes> {
+			self.0.get(hash).cloned()
+		}
+	}
+
+	fn write_snapshot(path: &RandomTempPath) -> ManifestData {
+		let mut writer = PackedWriter::new(path.as_path()).unwrap();
+		for chunk in STATE_CHUNKS {
+			writer.write_state_chunk(chunk.sha3(), chunk).unwrap();
+		}
+		for chunk in BLOCK_CHUNKS {
+			writer.write_block_chunk(chunk.sha3(), chunk).unwrap();
+		}
+
+		let manifest = dummy_manifest(STATE_CHUNKS.iter().map(|c| c.sha3()).collect(), BLOCK_CHUNKS.iter().map(|c| c.sha3()).collect(), b"notarealroot".sha3(), 1234, b"notarealblock".sha3());
+		writer.finish(manifest.clone()).unwrap();
+		manifest
+	}
+
+	// overwrite the first byte of the chunk with the given hash.
+	fn corrupt(path: &RandomTempPath, hash: &H256) {
+		let (_, offset) = PackedReader::new(path.as_path()).unwrap().unwrap().chunk_location(hash).unwrap();
+		let mut file = OpenOptions::new().write(true).open(path.as_path()).unwrap();
+		file.seek(SeekFrom::Start(offset)).unwrap();
+		file.write_all(b"X").unwrap();
+	}
+
+	#[test]
+	fn repairs_corrupt_chunk() {
+		let path = RandomTempPath::new();
+		let manifest = write_snapshot(&path);
+		let bad = STATE_CHUNKS[1].sha3();
+		corrupt(&path, &bad);
+
+		assert_eq!(corrupt_chunks(&PackedReader::new(path.as_path()).unwrap().unwrap()), vec![bad]);
+
+		let source = MockSource(vec![(bad, STATE_CHUNKS[1].to_vec())].into_iter().collect());
+		assert_eq!(repair_packed(path.as_path(), &source).unwrap(), vec![bad]);
+
+		let reader = PackedReader::new(path.as_path()).unwrap().unwrap();
+		assert!(corrupt_chunks(&reader).is_empty());
+		assert_eq!(reader.manifest(), &manifest);
+		assert_eq!(reader.chunk(bad).unwrap(), STATE_CHUNKS[1].to_vec());
+	}
+
+	#[test]
+	fn fails_without_valid_copy() {
+		let path = RandomTempPath::new();
+		write_snapshot(&path);
+		let bad = BLOCK_CHUNKS[0].sha3();
+		corrupt(&path, &bad);
+
+		// the source only has a corrupt copy itself.
+		let source = MockSource(vec![(bad, b"Xello!".to_vec())].into_iter().collect());
+		assert!(repair_packed(path.as_path(), &source).is_err());
+		assert!(repair_packed(path.as_path(), &MockSource(HashMap::new())).is_err());
+		assert_eq!(corrupt_chunks(&PackedReader::new(path.as_path()).unwrap().unwrap()), vec![bad]);
+	}
+
+	#[test]
+	fn intact_snapshot_needs_no_repair() {
+		let path = RandomTempPath::new();
+		write_snapshot(&path);
+		assert!(repair_packed(path.as_path(), &MockSource(HashMap::new())).unwrap().is_empty());
+	}
+}
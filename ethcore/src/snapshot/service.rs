@@ -16,15 +16,15 @@
 
 //! Snapshot network service implementation.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::ErrorKind;
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-use super::{ManifestData, StateRebuilder, BlockRebuilder, RestorationStatus, SnapshotService};
-use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
+use super::{ManifestData, StateRebuilder, BlockRebuilder, RestorationStatus, CreationStatus, SnapshotService, check_features, FEATURE_RECEIPT_INDEX};
+use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter, PackedWriter};
 
 use blockchain::BlockChain;
 use client::Client;
@@ -40,6 +40,9 @@ use util::journaldb::Algorithm;
 use util::kvdb::{Database, DatabaseConfig};
 use util::snappy;
 
+/// Maximum number of files kept in the directory of requested snapshots.
+pub const MAX_SNAPSHOT_EXPORTS: usize = 4;
+
 /// Helper for removing directories in case of error.
 struct Guard(bool, PathBuf);
 
@@ -68,6 +71,7 @@ struct Restoration {
 	manifest: ManifestData,
 	state_chunks_left: HashSet<H256>,
 	block_chunks_left: HashSet<H256>,
+	state_chunk_roots: HashMap<H256, H256>,
 	state: StateRebuilder,
 	blocks: BlockRebuilder,
 	writer: Option<LooseWriter>,
@@ -84,27 +88,41 @@ struct RestorationParams<'a> {
 	writer: Option<LooseWriter>, // writer for recovered snapshot.
 	genesis: &'a [u8], // genesis block of the chain.
 	guard: Guard, // guard for the restoration directory.
+	verify_state_chunks: bool, // whether to verify state chunks against the manifest's chunk roots.
 }
 
 impl Restoration {
 	// make a new restoration using the given parameters.
 	fn new(params: RestorationParams) -> Result<Self, Error> {
 		let manifest = params.manifest;
+		try!(check_features(&manifest));
 
 		let state_chunks = manifest.state_hashes.iter().cloned().collect();
 		let block_chunks = manifest.block_hashes.iter().cloned().collect();
 
+		let state_chunk_roots = match params.verify_state_chunks {
+			true if manifest.state_chunk_roots.len() == manifest.state_hashes.len() =>
+				manifest.state_hashes.iter().cloned().zip(manifest.state_chunk_roots.iter().cloned()).collect(),
+			true => {
+				warn!(target: "snapshot", "Manifest doesn't record state chunk roots; only the final state root will be verified.");
+				HashMap::new()
+			}
+			false => HashMap::new(),
+		};
+
 		let raw_db = Arc::new(try!(Database::open(params.db_config, &*params.db_path.to_string_lossy())
 			.map_err(UtilError::SimpleString)));
 
 		let chain = BlockChain::new(Default::default(), params.genesis, raw_db.clone());
-		let blocks = try!(BlockRebuilder::new(chain, manifest.block_number));
+		let blocks = try!(BlockRebuilder::new(chain, manifest.block_number))
+			.verify_receipts(manifest.features.iter().any(|f| f == FEATURE_RECEIPT_INDEX));
 
 		let root = manifest.state_root.clone();
 		Ok(Restoration {
 			manifest: manifest,
 			state_chunks_left: state_chunks,
 			block_chunks_left: block_chunks,
+			state_chunk_roots: state_chunk_roots,
 			state: StateRebuilder::new(raw_db, params.pruning),
 			blocks: blocks,
 			writer: params.writer,
@@ -119,7 +137,10 @@ impl Restoration {
 		if self.state_chunks_left.remove(&hash) {
 			let len = try!(snappy::decompress_into(chunk, &mut self.snappy_buffer));
 
-			try!(self.state.feed(&self.snappy_buffer[..len]));
+			match self.state_chunk_roots.get(&hash) {
+				Some(root) => try!(self.state.feed_verified(&self.snappy_buffer[..len], root)),
+				None => try!(self.state.feed(&self.snappy_buffer[..len])),
+			}
 
 			if let Some(ref mut writer) = self.writer.as_mut() {
 				try!(writer.write_state_chunk(hash, chunk));
@@ -196,6 +217,9 @@ pub struct ServiceParams {
 	pub snapshot_root: PathBuf,
 	/// A handle for database restoration.
 	pub db_restore: Arc<DatabaseRestore>,
+	/// Whether to verify each state chunk against the account trie root
+	/// recorded in the manifest as it is fed.
+	pub verify_state_chunks: bool,
 }
 
 /// `SnapshotService` implementation.
@@ -215,6 +239,7 @@ pub struct Service {
 	db_restore: Arc<DatabaseRestore>,
 	progress: super::Progress,
 	taking_snapshot: AtomicBool,
+	verify_state_chunks: bool,
 }
 
 impl Service {
@@ -235,6 +260,7 @@ impl Service {
 			db_restore: params.db_restore,
 			progress: Default::default(),
 			taking_snapshot: AtomicBool::new(false),
+			verify_state_chunks: params.verify_state_chunks,
 		};
 
 		// create the root snapshot dir if it doesn't exist.
@@ -278,6 +304,13 @@ impl Service {
 		dir
 	}
 
+	// get the directory for snapshots requested through `request_snapshot`.
+	fn export_dir(&self) -> PathBuf {
+		let mut dir = self.snapshot_root.clone();
+		dir.push("exports");
+		dir
+	}
+
 	// get the restoration directory.
 	fn restoration_dir(&self) -> PathBuf {
 		let mut dir = self.snapshot_root.clone();
@@ -349,6 +382,13 @@ impl Service {
 
 		info!("Finished taking snapshot at #{}", num);
 
+		// snapshots which can't be restored are not served, the guard removes them.
+		let taken = try!(LooseReader::new(temp_dir.clone()));
+		if let Err(e) = check_features(taken.manifest()) {
+			warn!("Not serving snapshot at #{}: {}", num, e);
+			return Ok(());
+		}
+
 		let mut reader = self.reader.write();
 
 		// destroy the old snapshot reader.
@@ -366,6 +406,30 @@ impl Service {
 		Ok(())
 	}
 
+	/// Take the snapshot requested through `request_snapshot` into a packed snapshot file.
+	/// The served snapshot is left untouched. An existing file is never overwritten.
+	pub fn take_requested_snapshot(&self, client: &Client, num: u64, path: &Path) -> Result<(), Error> {
+		info!("Taking snapshot at #{} into {}", num, path.display());
+
+		// claim the file first, so that only a file created here is removed on failure.
+		if let Err(e) = OpenOptions::new().write(true).create_new(true).open(path) {
+			self.taking_snapshot.store(false, Ordering::SeqCst);
+			return Err(e.into());
+		}
+
+		let res = PackedWriter::new(path).map_err(Into::into)
+			.and_then(|writer| client.take_snapshot(writer, BlockID::Number(num), &self.progress));
+
+		self.taking_snapshot.store(false, Ordering::SeqCst);
+		if res.is_err() {
+			let _ = fs::remove_file(path);
+		}
+		try!(res);
+
+		info!("Finished taking snapshot at #{} into {}", num, path.display());
+		Ok(())
+	}
+
 	/// Initialize the restoration synchronously.
 	/// The recover flag indicates whether to recover the restored snapshot.
 	pub fn init_restore(&self, manifest: ManifestData, recover: bool) -> Result<(), Error> {
@@ -403,6 +467,7 @@ impl Service {
 			writer: writer,
 			genesis: &self.genesis_block,
 			guard: Guard::new(rest_dir),
+			verify_state_chunks: self.verify_state_chunks,
 		};
 
 		*res = Some(try!(Restoration::new(params)));
@@ -514,6 +579,11 @@ impl Service {
 	}
 }
 
+// whether the name refers to a file directly inside a directory.
+fn is_plain_file_name(name: &str) -> bool {
+	!name.starts_with('.') && Path::new(name).file_name().map_or(false, |n| n.to_str() == Some(name))
+}
+
 impl SnapshotService for Service {
 	fn manifest(&self) -> Option<ManifestData> {
 		self.reader.read().as_ref().map(|r| r.manifest().clone())
@@ -533,6 +603,40 @@ impl SnapshotService for Service {
 		cur_status.clone()
 	}
 
+	fn creation_status(&self) -> Option<CreationStatus> {
+		match self.taking_snapshot.load(Ordering::SeqCst) {
+			true => Some(self.progress.status()),
+			false => None,
+		}
+	}
+
+	fn request_snapshot(&self, num: u64, name: String) -> Result<(), String> {
+		if !is_plain_file_name(&name) {
+			return Err(format!("Invalid snapshot file name '{}'", name));
+		}
+
+		let export_dir = self.export_dir();
+		try!(fs::create_dir_all(&export_dir).map_err(|e| format!("{}", e)));
+		let path = export_dir.join(&name);
+		if path.exists() {
+			return Err(format!("Snapshot file '{}' already exists", name));
+		}
+		let exports = try!(fs::read_dir(&export_dir).map_err(|e| format!("{}", e))).count();
+		if exports >= MAX_SNAPSHOT_EXPORTS {
+			return Err(format!("{} snapshots already exported, remove some of them first", exports));
+		}
+
+		if self.taking_snapshot.compare_and_swap(false, true, Ordering::SeqCst) {
+			return Err("Another snapshot is currently being taken".into());
+		}
+
+		self.progress.reset();
+		self.io_channel.send(ClientIoMessage::TakeSnapshotTo(num, path)).map_err(|e| {
+			self.taking_snapshot.store(false, Ordering::SeqCst);
+			format!("{}", e)
+		})
+	}
+
 	fn begin_restore(&self, manifest: ManifestData) {
 		self.io_channel.send(ClientIoMessage::BeginRestoration(manifest))
 			.expect("snapshot service and io service are kept alive by client service; qed");
@@ -562,6 +666,7 @@ impl Drop for Service {
 
 #[cfg(test)]
 mod tests {
+	use std::fs;
 	use std::sync::Arc;
 	use service::ClientIoMessage;
 	use io::{IoService};
@@ -569,7 +674,8 @@ mod tests {
 	use tests::helpers::get_test_spec;
 	use util::journaldb::Algorithm;
 	use error::Error;
-	use snapshot::{ManifestData, RestorationStatus, SnapshotService};
+	use snapshot::{RestorationStatus, SnapshotService};
+	use snapshot::tests::helpers::dummy_manifest;
 	use super::*;
 
 	struct NoopDBRestore;
@@ -598,6 +704,7 @@ mod tests {
 			channel: service.channel(),
 			snapshot_root: dir,
 			db_restore: Arc::new(NoopDBRestore),
+			verify_state_chunks: false,
 		};
 
 		let service = Service::new(snapshot_params).unwrap();
@@ -606,17 +713,44 @@ mod tests {
 		assert!(service.chunk(Default::default()).is_none());
 		assert_eq!(service.status(), RestorationStatus::Inactive);
 
-		let manifest = ManifestData {
-			state_hashes: vec![],
-			block_hashes: vec![],
-			state_root: Default::default(),
-			block_number: 0,
-			block_hash: Default::default(),
-		};
+		let manifest = dummy_manifest(vec![], vec![], Default::default(), 0, Default::default());
 
 		service.begin_restore(manifest);
 		service.abort_restore();
 		service.restore_state_chunk(Default::default(), vec![]);
 		service.restore_block_chunk(Default::default(), vec![]);
 	}
+
+	#[test]
+	fn caps_requested_snapshots() {
+		let service = IoService::<ClientIoMessage>::start().unwrap();
+		let spec = get_test_spec();
+
+		let dir = RandomTempPath::new();
+		let mut dir = dir.as_path().to_owned();
+		dir.push("snapshot");
+
+		let snapshot_params = ServiceParams {
+			engine: spec.engine.clone(),
+			genesis_block: spec.genesis_block(),
+			db_config: Default::default(),
+			pruning: Algorithm::Archive,
+			channel: service.channel(),
+			snapshot_root: dir,
+			db_restore: Arc::new(NoopDBRestore),
+			verify_state_chunks: false,
+		};
+
+		let service = Service::new(snapshot_params).unwrap();
+		fs::create_dir_all(service.export_dir()).unwrap();
+		for i in 0..MAX_SNAPSHOT_EXPORTS {
+			fs::File::create(service.export_dir().join(format!("old-{}", i))).unwrap();
+		}
+
+		let err = service.request_snapshot(0, "new".into()).unwrap_err();
+		assert_eq!(err, format!("{} snapshots already exported, remove some of them first", MAX_SNAPSHOT_EXPORTS));
+
+		fs::remove_file(service.export_dir().join("old-0")).unwrap();
+		assert!(service.request_snapshot(0, "new".into()).is_ok());
+	}
 }
\ No newline at end of file
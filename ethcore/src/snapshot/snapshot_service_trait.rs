@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::{ManifestData, RestorationStatus};
+use super::{ManifestData, RestorationStatus, CreationStatus};
 use util::{Bytes, H256};
 use ipc::IpcConfig;
 
@@ -34,6 +34,14 @@ pub trait SnapshotService : Sync + Send {
 	/// Ask the snapshot service for the restoration status.
 	fn status(&self) -> RestorationStatus;
 
+	/// Progress of the snapshot currently being taken, if any.
+	fn creation_status(&self) -> Option<CreationStatus>;
+
+	/// Request a snapshot at the block with the given number to be taken asynchronously
+	/// into a packed snapshot file with the given name in the snapshot exports directory.
+	/// Fails if a snapshot is already being taken, or the file name is invalid or taken.
+	fn request_snapshot(&self, num: u64, name: String) -> Result<(), String>;
+
 	/// Begin snapshot restoration.
 	/// If restoration in-progress, this will reset it.
 	/// From this point on, any previous snapshot may become unavailable.
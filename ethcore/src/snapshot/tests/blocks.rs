@@ -22,6 +22,7 @@ use blockchain::generator::{ChainGenerator, ChainIterator, BlockFinalizer};
 use blockchain::BlockChain;
 use snapshot::{chunk_blocks, BlockRebuilder, Progress};
 use snapshot::io::{PackedReader, PackedWriter, SnapshotReader, SnapshotWriter};
+use super::helpers::dummy_manifest;
 
 use util::{Mutex, snappy};
 use util::kvdb::{Database, DatabaseConfig};
@@ -58,13 +59,7 @@ fn chunk_and_restore(amount: u64) {
 	// snapshot it.
 	let writer = Mutex::new(PackedWriter::new(&snapshot_path).unwrap());
 	let block_hashes = chunk_blocks(&bc, (amount, best_hash), &writer, &Progress::default()).unwrap();
-	writer.into_inner().finish(::snapshot::ManifestData {
-		state_hashes: Vec::new(),
-		block_hashes: block_hashes,
-		state_root: Default::default(),
-		block_number: amount,
-		block_hash: best_hash,
-	}).unwrap();
+	writer.into_inner().finish(dummy_manifest(Vec::new(), block_hashes, Default::default(), amount, best_hash)).unwrap();
 
 	// restore it.
 	let new_db = Arc::new(Database::open(&db_cfg, new_path.as_str()).unwrap());
@@ -90,3 +85,143 @@ fn chunk_and_restore_500() { chunk_and_restore(500) }
 
 #[test]
 fn chunk_and_restore_40k() { chunk_and_restore(40000) }
+
+#[test]
+fn rejects_bad_receipts_root() {
+	use error::{Error, BlockError};
+	use receipt::Receipt;
+
+	let mut canon_chain = ChainGenerator::default();
+	let mut finalizer = BlockFinalizer::default();
+	let genesis = canon_chain.generate(&mut finalizer).unwrap();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	let orig_path = RandomTempPath::create_dir();
+	let mut snapshot_path = orig_path.as_path().to_owned();
+	snapshot_path.push("SNAP");
+
+	let old_db = Arc::new(Database::open(&db_cfg, orig_path.as_str()).unwrap());
+	let bc = BlockChain::new(Default::default(), &genesis, old_db.clone());
+
+	// the last block comes with a receipt its empty receipts root doesn't account for.
+	let mut batch = old_db.transaction();
+	for i in 0..10 {
+		let block = canon_chain.generate(&mut finalizer).unwrap();
+		let receipts = match i {
+			9 => vec![Receipt::new(Default::default(), 21000.into(), Vec::new())],
+			_ => Vec::new(),
+		};
+		bc.insert_block(&mut batch, &block, receipts);
+		bc.commit();
+	}
+	old_db.write(batch).unwrap();
+
+	let best_hash = bc.best_block_hash();
+	let writer = Mutex::new(PackedWriter::new(&snapshot_path).unwrap());
+	let block_hashes = chunk_blocks(&bc, (10, best_hash), &writer, &Progress::default()).unwrap();
+	writer.into_inner().finish(dummy_manifest(Vec::new(), block_hashes, Default::default(), 10, best_hash)).unwrap();
+
+	let reader = PackedReader::new(&snapshot_path).unwrap().unwrap();
+	let engine = ::engines::NullEngine::new(Default::default(), Default::default());
+	let restore = |verify: bool| -> Result<(), Error> {
+		let new_path = RandomTempPath::create_dir();
+		let new_db = Arc::new(Database::open(&db_cfg, new_path.as_str()).unwrap());
+		let new_chain = BlockChain::new(Default::default(), &genesis, new_db);
+		let mut rebuilder = BlockRebuilder::new(new_chain, 10).unwrap().verify_receipts(verify);
+		for chunk_hash in &reader.manifest().block_hashes {
+			let chunk = snappy::decompress(&reader.chunk(*chunk_hash).unwrap()).unwrap();
+			try!(rebuilder.feed(&chunk, &engine));
+		}
+		Ok(())
+	};
+
+	assert!(restore(false).is_ok());
+	match restore(true) {
+		Err(Error::Block(BlockError::InvalidReceiptsRoot(_))) => {},
+		other => panic!("unexpected result: {:?}", other),
+	}
+}
+
+#[test]
+fn refuses_non_canonical_starting_block() {
+	use snapshot::{take_snapshot, Error};
+	use util::memorydb::MemoryDB;
+	use views::BlockView;
+
+	let mut canon_chain = ChainGenerator::default();
+	let mut finalizer = BlockFinalizer::default();
+	let genesis = canon_chain.generate(&mut finalizer).unwrap();
+	let b1a = canon_chain.generate(&mut finalizer).unwrap();
+	let b2b = canon_chain.fork(1).generate(&mut finalizer.fork()).unwrap();
+	let b2a = canon_chain.generate(&mut finalizer).unwrap();
+	let b3a = canon_chain.generate(&mut finalizer).unwrap();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	let path = RandomTempPath::create_dir();
+	let db = Arc::new(Database::open(&db_cfg, path.as_str()).unwrap());
+	let bc = BlockChain::new(Default::default(), &genesis, db.clone());
+
+	let mut batch = db.transaction();
+	for block in &[&b1a, &b2a, &b3a, &b2b] {
+		bc.insert_block(&mut batch, block, vec![]);
+		bc.commit();
+	}
+	db.write(batch).unwrap();
+
+	let side_hash = BlockView::new(&b2b).header_view().sha3();
+	assert!(bc.block_header(&side_hash).is_some());
+	assert!(bc.block_hash(2) != Some(side_hash));
+
+	let mut snapshot_path = path.as_path().to_owned();
+	snapshot_path.push("SNAP");
+	let writer = PackedWriter::new(&snapshot_path).unwrap();
+	let engine = ::engines::NullEngine::new(Default::default(), Default::default());
+	match take_snapshot(&engine, &bc, side_hash, &MemoryDB::new(), writer, &Progress::default(), &Default::default()) {
+		Err(Error::NonCanonicalStartingBlock(hash)) => assert_eq!(hash, side_hash),
+		other => panic!("unexpected result: {:?}", other),
+	}
+}
+
+#[test]
+fn checks_chunker_consistency() {
+	use snapshot::{check_consistency, Error};
+
+	let mut canon_chain = ChainGenerator::default();
+	let mut finalizer = BlockFinalizer::default();
+	let genesis = canon_chain.generate(&mut finalizer).unwrap();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	let path = RandomTempPath::create_dir();
+	let db = Arc::new(Database::open(&db_cfg, path.as_str()).unwrap());
+	let bc = BlockChain::new(Default::default(), &genesis, db.clone());
+
+	let mut batch = db.transaction();
+	for _ in 0..10 {
+		let block = canon_chain.generate(&mut finalizer).unwrap();
+		bc.insert_block(&mut batch, &block, vec![]);
+		bc.commit();
+	}
+	db.write(batch).unwrap();
+
+	let best_hash = bc.best_block_hash();
+	let parent_hash = bc.block_hash(9).unwrap();
+	let state_root = *bc.block_header(&best_hash).unwrap().state_root();
+
+	assert!(check_consistency(&bc, (10, best_hash), &state_root, Some((10, best_hash))).is_ok());
+
+	// inject faults as if the chunkers had diverged.
+	match check_consistency(&bc, (10, best_hash), &state_root, Some((9, parent_hash))) {
+		Err(Error::BlockNumberMismatch(10, 9)) => {},
+		other => panic!("unexpected result: {:?}", other),
+	}
+
+	match check_consistency(&bc, (10, best_hash), &state_root, Some((10, parent_hash))) {
+		Err(Error::BlockHashMismatch(_, _)) => {},
+		other => panic!("unexpected result: {:?}", other),
+	}
+
+	match check_consistency(&bc, (10, best_hash), &Default::default(), Some((10, best_hash))) {
+		Err(Error::StateRootMismatch(_, _)) => {},
+		other => panic!("unexpected result: {:?}", other),
+	}
+}
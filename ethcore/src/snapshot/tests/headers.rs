@@ -0,0 +1,99 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Header export and import tests.
+
+use devtools::RandomTempPath;
+
+use blockchain::generator::{ChainGenerator, ChainIterator, BlockFinalizer};
+use blockchain::{BlockChain, BlockProvider};
+use snapshot::{export_headers, import_headers_only, HeaderStore};
+use snapshot::headers::MAX_EXPORTED_HEADERS;
+
+use util::kvdb::{Database, DatabaseConfig};
+
+use std::sync::Arc;
+
+fn make_chain(path: &RandomTempPath, amount: u64) -> BlockChain {
+	let mut canon_chain = ChainGenerator::default();
+	let mut finalizer = BlockFinalizer::default();
+	let genesis = canon_chain.generate(&mut finalizer).unwrap();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	let db = Arc::new(Database::open(&db_cfg, path.as_str()).unwrap());
+	let bc = BlockChain::new(Default::default(), &genesis, db.clone());
+
+	let mut batch = db.transaction();
+	for _ in 0..amount {
+		let block = canon_chain.generate(&mut finalizer).unwrap();
+		bc.insert_block(&mut batch, &block, vec![]);
+		bc.commit();
+	}
+	db.write(batch).unwrap();
+
+	bc
+}
+
+#[test]
+fn export_and_import_headers() {
+	let path = RandomTempPath::create_dir();
+	let bc = make_chain(&path, 100);
+
+	let mut exported = Vec::new();
+	export_headers(&bc, 40, 100, &mut exported).unwrap();
+
+	let mut store = HeaderStore::new();
+	assert_eq!(import_headers_only(&mut store, &exported).unwrap(), 61);
+	assert_eq!(store.len(), 61);
+
+	let best = store.best_header().unwrap();
+	assert_eq!(best.number(), 100);
+	assert_eq!(best.hash(), bc.best_block_hash());
+	assert_eq!(best, &bc.block_header(&bc.best_block_hash()).unwrap());
+	assert!(store.header(&bc.block_hash(40).unwrap()).is_some());
+	assert!(store.header(&bc.block_hash(39).unwrap()).is_none());
+}
+
+#[test]
+fn rejects_disconnected_headers() {
+	let path = RandomTempPath::create_dir();
+	let bc = make_chain(&path, 20);
+
+	let mut first = Vec::new();
+	let mut second = Vec::new();
+	export_headers(&bc, 1, 5, &mut first).unwrap();
+	export_headers(&bc, 10, 15, &mut second).unwrap();
+
+	let mut store = HeaderStore::new();
+	import_headers_only(&mut store, &first).unwrap();
+	assert!(import_headers_only(&mut store, &second).is_err());
+	assert_eq!(store.best_header().unwrap().number(), 5);
+}
+
+#[test]
+fn header_range_is_bounded() {
+	let path = RandomTempPath::create_dir();
+	let bc = make_chain(&path, 10);
+
+	let mut out = Vec::new();
+	let reversed = export_headers(&bc, 5, 4, &mut out).unwrap_err();
+	assert!(format!("{}", reversed).contains("after the last"));
+	let too_long = export_headers(&bc, 0, MAX_EXPORTED_HEADERS, &mut out).unwrap_err();
+	assert!(format!("{}", too_long).contains("at most"));
+	// blocks past the best block are not known.
+	assert!(export_headers(&bc, 5, 11, &mut out).is_err());
+	assert!(out.is_empty());
+}
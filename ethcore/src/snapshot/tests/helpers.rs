@@ -20,13 +20,30 @@
 use account_db::AccountDBMut;
 use rand::Rng;
 use snapshot::account::Account;
+use snapshot::ManifestData;
 
 use util::hash::{FixedHash, H256};
 use util::hashdb::HashDB;
+use util::memorydb::MemoryDB;
+use util::Bytes;
 use util::trie::{Alphabet, StandardMap, SecTrieDBMut, TrieMut, ValueMode};
 use util::trie::{TrieDB, TrieDBMut, Trie};
 use util::sha3::SHA3_NULL_RLP;
 
+/// Manifest of a snapshot with the given chunks, without chunk roots, features or engine name.
+pub fn dummy_manifest(state_hashes: Vec<H256>, block_hashes: Vec<H256>, state_root: H256, block_number: u64, block_hash: H256) -> ManifestData {
+	ManifestData {
+		state_hashes: state_hashes,
+		block_hashes: block_hashes,
+		state_root: state_root,
+		block_number: block_number,
+		block_hash: block_hash,
+		state_chunk_roots: Vec::new(),
+		features: Vec::new(),
+		engine: String::new(),
+	}
+}
+
 // the proportion of accounts we will alter each tick.
 const ACCOUNT_CHURN: f32 = 0.01;
 
@@ -115,11 +132,15 @@ pub fn fill_storage(mut db: AccountDBMut, root: &mut H256, seed: &mut H256) {
 	}
 }
 
-/// Compare two state dbs.
-pub fn compare_dbs(one: &HashDB, two: &HashDB) {
-	let keys = one.keys();
+/// Export the contents of a memory db ordered by key, so that runs can be compared.
+pub fn export_sorted(db: &MemoryDB) -> Vec<(H256, Bytes, i32)> {
+	db.iter_sorted().map(|(k, v, rc)| (k.clone(), v.to_vec(), rc)).collect()
+}
 
-	for key in keys.keys() {
-		assert_eq!(one.get(&key).unwrap(), two.get(&key).unwrap());
+/// Compare two state dbs. Keys are visited in order so that failures are reproducible.
+pub fn compare_dbs(one: &MemoryDB, two: &HashDB) {
+	for (key, _, rc) in one.iter_sorted() {
+		if rc == 0 { continue }
+		assert_eq!(one.get(key).unwrap(), two.get(key).unwrap());
 	}
 }
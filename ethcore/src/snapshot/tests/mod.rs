@@ -17,22 +17,77 @@
 //! Snapshot tests.
 
 mod blocks;
+mod headers;
 mod state;
 mod service;
 
 pub mod helpers;
 
 use super::ManifestData;
+use self::helpers::dummy_manifest;
 
 #[test]
 fn manifest_rlp() {
+	let manifest = dummy_manifest(Vec::new(), Vec::new(), Default::default(), 1234567, Default::default());
+	let raw = manifest.clone().into_rlp();
+	assert_eq!(ManifestData::from_rlp(&raw).unwrap(), manifest);
+}
+
+#[test]
+fn manifest_rlp_without_chunk_roots() {
+	use rlp::{RlpStream, Stream};
+	use util::H256;
+
+	let mut stream = RlpStream::new_list(5);
+	stream.append(&Vec::<H256>::new()).append(&Vec::<H256>::new())
+		.append(&H256::default()).append(&1234567u64).append(&H256::default());
+
+	let manifest = ManifestData::from_rlp(&stream.out()).unwrap();
+	assert_eq!(manifest.block_number, 1234567);
+	assert!(manifest.state_chunk_roots.is_empty());
+	assert!(manifest.features.is_empty());
+	assert!(manifest.engine.is_empty());
+	assert!(super::check_features(&manifest).is_ok());
+}
+
+#[test]
+fn manifest_rlp_with_features() {
 	let manifest = ManifestData {
-		block_hashes: Vec::new(),
-		state_hashes: Vec::new(),
-		block_number: 1234567,
-		state_root: Default::default(),
-		block_hash: Default::default(),
+		features: vec![super::FEATURE_CODE_DEDUP_GLOBAL.into()],
+		engine: "Ethash".into(),
+		..dummy_manifest(Vec::new(), Vec::new(), Default::default(), 1234567, Default::default())
 	};
 	let raw = manifest.clone().into_rlp();
 	assert_eq!(ManifestData::from_rlp(&raw).unwrap(), manifest);
-}
\ No newline at end of file
+	assert!(super::check_features(&manifest).is_ok());
+}
+
+#[test]
+fn rejects_unknown_features() {
+	use super::Error;
+
+	let manifest = ManifestData {
+		features: vec![super::FEATURE_CODE_DEDUP_GLOBAL.into(), "split-accounts".into()],
+		..dummy_manifest(Vec::new(), Vec::new(), Default::default(), 1234567, Default::default())
+	};
+
+	match super::check_features(&manifest) {
+		Err(Error::UnsupportedSnapshotFeature(ref name)) if name == "split-accounts" => {},
+		other => panic!("unexpected result: {:?}", other),
+	}
+}
+
+#[test]
+fn rejects_incomplete_state() {
+	use super::Error;
+
+	let manifest = ManifestData {
+		features: vec![super::FEATURE_CODE_DEDUP_GLOBAL.into(), super::FEATURE_INCOMPLETE_STATE.into()],
+		..dummy_manifest(Vec::new(), Vec::new(), Default::default(), 1234567, Default::default())
+	};
+
+	match super::check_features(&manifest) {
+		Err(Error::UnsupportedSnapshotFeature(ref name)) if name == super::FEATURE_INCOMPLETE_STATE => {},
+		other => panic!("unexpected result: {:?}", other),
+	}
+}
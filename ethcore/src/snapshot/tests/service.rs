@@ -18,12 +18,14 @@
 
 use std::sync::Arc;
 
-use client::{BlockChainClient, Client};
-use ids::BlockID;
+use client::{BlockChainClient, Client, ClientConfig};
+use ids::{BlockID, TransactionID};
 use snapshot::service::{Service, ServiceParams};
 use snapshot::{self, ManifestData, SnapshotService};
+use snapshot::io::{PackedReader, SnapshotReader};
 use spec::Spec;
-use tests::helpers::generate_dummy_client_with_spec_and_data;
+use super::helpers::dummy_manifest;
+use tests::helpers::{generate_dummy_client_with_spec_and_data, generate_dummy_client_with_spec_config_and_data};
 
 use devtools::RandomTempPath;
 use io::IoChannel;
@@ -73,6 +75,7 @@ fn restored_is_equivalent() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path,
 		db_restore: client2.clone(),
+		verify_state_chunks: true,
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -103,6 +106,145 @@ fn restored_is_equivalent() {
 	}
 }
 
+#[test]
+fn requested_snapshot_restores_to_head() {
+	const NUM_BLOCKS: u32 = 20;
+
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 5, &[1.into()]);
+
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let mut client_db = path.clone();
+
+	client_db.push("client_db");
+	path.push("snapshot");
+	let snapshot_file = path.join("exports").join("requested.snapshot");
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	let spec = Spec::new_null();
+	let client2 = Client::new(
+		Default::default(),
+		&spec,
+		&client_db,
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+		&db_config,
+	).unwrap();
+
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config,
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path,
+		db_restore: client2.clone(),
+		verify_state_chunks: true,
+	};
+
+	let service = Service::new(service_params).unwrap();
+	let best = client.chain_info();
+	let name = "requested.snapshot".to_owned();
+
+	assert!(service.request_snapshot(best.best_block_number, "../requested.snapshot".into()).is_err());
+	assert!(service.request_snapshot(best.best_block_number, snapshot_file.to_str().unwrap().into()).is_err());
+	assert!(service.creation_status().is_none());
+
+	service.request_snapshot(best.best_block_number, name.clone()).unwrap();
+	assert!(service.creation_status().is_some());
+	assert!(service.request_snapshot(best.best_block_number, "other.snapshot".into()).is_err());
+
+	service.take_requested_snapshot(&client, best.best_block_number, &snapshot_file).unwrap();
+	assert!(service.creation_status().is_none());
+	assert!(service.manifest().is_none());
+
+	let reader = PackedReader::new(&snapshot_file).unwrap().unwrap();
+	let manifest = reader.manifest().clone();
+	assert_eq!(manifest.block_hash, best.best_block_hash);
+
+	// an existing file is neither overwritten nor removed.
+	assert!(service.request_snapshot(best.best_block_number, name).is_err());
+	assert!(service.take_requested_snapshot(&client, best.best_block_number, &snapshot_file).is_err());
+	assert!(PackedReader::new(&snapshot_file).unwrap().is_some());
+
+	service.init_restore(manifest.clone(), false).unwrap();
+	for hash in manifest.state_hashes {
+		service.feed_state_chunk(hash, &reader.chunk(hash).unwrap());
+	}
+	for hash in manifest.block_hashes {
+		service.feed_block_chunk(hash, &reader.chunk(hash).unwrap());
+	}
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+	assert_eq!(client2.chain_info().best_block_hash, best.best_block_hash);
+}
+
+#[test]
+fn restores_receipt_index() {
+	const NUM_BLOCKS: u32 = 20;
+
+	let mut config = ClientConfig::default();
+	config.snapshot.include_receipt_index = true;
+	let client = generate_dummy_client_with_spec_config_and_data(Spec::new_null, config, NUM_BLOCKS, 5, &[1.into()]);
+
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let mut client_db = path.clone();
+
+	client_db.push("client_db");
+	path.push("snapshot");
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	let spec = Spec::new_null();
+	let client2 = Client::new(
+		Default::default(),
+		&spec,
+		&client_db,
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+		&db_config,
+	).unwrap();
+
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config,
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path,
+		db_restore: client2.clone(),
+		verify_state_chunks: false,
+	};
+
+	let service = Service::new(service_params).unwrap();
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+
+	let manifest = service.manifest().unwrap();
+	assert!(manifest.features.iter().any(|f| f == snapshot::FEATURE_RECEIPT_INDEX));
+	assert_eq!(manifest.engine, spec.engine.name());
+
+	service.init_restore(manifest.clone(), false).unwrap();
+	for hash in manifest.state_hashes {
+		let chunk = service.chunk(hash).unwrap();
+		service.feed_state_chunk(hash, &chunk);
+	}
+	for hash in manifest.block_hashes {
+		let chunk = service.chunk(hash).unwrap();
+		service.feed_block_chunk(hash, &chunk);
+	}
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+
+	// a transaction from the middle of the restored range.
+	let id = TransactionID::Location(BlockID::Number(NUM_BLOCKS as u64 / 2), 2);
+	let receipt = client2.transaction_receipt(id.clone()).unwrap();
+	assert_eq!(receipt, client.transaction_receipt(id).unwrap());
+	assert_eq!(receipt.block_number, NUM_BLOCKS as u64 / 2);
+	assert_eq!(receipt.transaction_index, 2);
+}
+
 #[test]
 fn guards_delete_folders() {
 	let spec = Spec::new_null();
@@ -116,18 +258,13 @@ fn guards_delete_folders() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path.clone(),
 		db_restore: Arc::new(NoopDBRestore),
+		verify_state_chunks: false,
 	};
 
 	let service = Service::new(service_params).unwrap();
 	path.push("restoration");
 
-	let manifest = ManifestData {
-		state_hashes: vec![],
-		block_hashes: vec![],
-		block_number: 0,
-		block_hash: Default::default(),
-		state_root: Default::default(),
-	};
+	let manifest = dummy_manifest(vec![], vec![], Default::default(), 0, Default::default());
 
 	service.init_restore(manifest.clone(), true).unwrap();
 	assert!(path.exists());
@@ -140,4 +277,32 @@ fn guards_delete_folders() {
 
 	drop(service);
 	assert!(!path.exists());
-}
\ No newline at end of file
+}
+
+#[test]
+fn refuses_restoring_unknown_features() {
+	let spec = Spec::new_null();
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.clone(),
+		db_restore: Arc::new(NoopDBRestore),
+		verify_state_chunks: false,
+	};
+
+	let service = Service::new(service_params).unwrap();
+	path.push("restoration");
+
+	let manifest = ManifestData {
+		features: vec!["split-accounts".into()],
+		..dummy_manifest(vec![], vec![], Default::default(), 0, Default::default())
+	};
+
+	assert!(service.init_restore(manifest, true).is_err());
+	assert!(!path.exists());
+}
@@ -16,16 +16,20 @@
 
 //! State snapshotting tests.
 
-use snapshot::{chunk_state, Progress, StateRebuilder};
+use snapshot::{chunk_state, chunk_state_with_roots, chunk_state_with_limit, chunk_state_skipping_corrupt, extract_accounts, Error, Progress, StateRebuilder};
+use account_db::AccountDBMut;
 use snapshot::io::{PackedReader, PackedWriter, SnapshotReader, SnapshotWriter};
-use super::helpers::{compare_dbs, StateProducer};
+use super::helpers::{compare_dbs, dummy_manifest, export_sorted, StateProducer};
 
 use rand::{XorShiftRng, SeedableRng};
 use util::hash::H256;
+use util::{Address, FixedHash, Hashable, HashDB, U256, SecTrieDBMut, TrieMut, SHA3_EMPTY, SHA3_NULL_RLP};
+use util::trie::TrieError;
 use util::journaldb::{self, Algorithm};
 use util::kvdb::{Database, DatabaseConfig};
 use util::memorydb::MemoryDB;
 use util::Mutex;
+use rlp::{RlpStream, Stream, UntrustedRlp, View};
 use devtools::RandomTempPath;
 
 use std::sync::Arc;
@@ -50,13 +54,7 @@ fn snap_and_restore() {
 
 	let state_hashes = chunk_state(&old_db, &state_root, &writer, &Progress::default()).unwrap();
 
-	writer.into_inner().finish(::snapshot::ManifestData {
-		state_hashes: state_hashes,
-		block_hashes: Vec::new(),
-		state_root: state_root,
-		block_number: 0,
-		block_hash: H256::default(),
-	}).unwrap();
+	writer.into_inner().finish(dummy_manifest(state_hashes, Vec::new(), state_root, 0, H256::default())).unwrap();
 
 	let mut db_path = snap_dir.as_path().to_owned();
 	db_path.push("db");
@@ -82,3 +80,249 @@ fn snap_and_restore() {
 
 	compare_dbs(&old_db, new_db.as_hashdb());
 }
+
+#[test]
+fn extract_single_account() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+	let mut db = MemoryDB::new();
+
+	for _ in 0..50 {
+		producer.tick(&mut rng, &mut db);
+	}
+
+	let addresses = [Address::from(1), Address::from(2), Address::from(3)];
+	let mut state_root = producer.state_root();
+	for (i, address) in addresses.iter().enumerate() {
+		let mut storage_root = SHA3_NULL_RLP;
+		{
+			let mut acct_db = AccountDBMut::new(&mut db, address);
+			let mut storage = SecTrieDBMut::new(&mut acct_db, &mut storage_root);
+			storage.insert(&H256::from(i as u64), &::rlp::encode(&U256::from(100 + i)).to_vec()).unwrap();
+		}
+
+		let mut account = RlpStream::new_list(4);
+		account.append(&U256::from(i))
+			.append(&U256::from(1000 * (i + 1)))
+			.append(&storage_root)
+			.append(&SHA3_EMPTY);
+
+		let mut state = SecTrieDBMut::from_existing(&mut db, &mut state_root).unwrap();
+		state.insert(address, &account.out()).unwrap();
+	}
+
+	let snap_dir = RandomTempPath::create_dir();
+	let mut snap_file = snap_dir.as_path().to_owned();
+	snap_file.push("SNAP");
+
+	let writer = Mutex::new(PackedWriter::new(&snap_file).unwrap());
+	let state_hashes = chunk_state(&db, &state_root, &writer, &Progress::default()).unwrap();
+
+	writer.into_inner().finish(dummy_manifest(state_hashes, Vec::new(), state_root, 0, H256::default())).unwrap();
+
+	let reader = PackedReader::new(&snap_file).unwrap().unwrap();
+	let extracted = extract_accounts(&reader, &[addresses[1], Address::from(42)]).unwrap();
+
+	assert_eq!(extracted.len(), 1);
+	let account = &extracted[&addresses[1]];
+	assert_eq!(account.nonce, U256::from(1));
+	assert_eq!(account.balance, U256::from(2000));
+	assert_eq!(account.code_hash, SHA3_EMPTY);
+	assert_eq!(account.code, None);
+	assert_eq!(account.storage.len(), 1);
+	assert_eq!(account.storage[&H256::from(1).sha3()], H256::from(U256::from(101)));
+}
+
+#[test]
+fn chunking_is_reproducible() {
+	let snap_dir = RandomTempPath::create_dir();
+	let mut hashes = Vec::new();
+
+	for run in 0..2 {
+		let mut producer = StateProducer::new();
+		let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+		let mut db = MemoryDB::new();
+
+		for _ in 0..50 {
+			producer.tick(&mut rng, &mut db);
+		}
+
+		let mut snap_file = snap_dir.as_path().to_owned();
+		snap_file.push(format!("SNAP{}", run));
+
+		let writer = Mutex::new(PackedWriter::new(&snap_file).unwrap());
+		let state_hashes = chunk_state(&db, &producer.state_root(), &writer, &Progress::default()).unwrap();
+
+		let entries = export_sorted(&db);
+		assert_eq!(entries.iter().filter(|e| e.2 != 0).count(), db.keys().len());
+		assert!(entries.windows(2).all(|w| w[0].0 < w[1].0), "entries must be exported in key order");
+
+		hashes.push((state_hashes, entries));
+	}
+
+	assert_eq!(hashes[0], hashes[1]);
+}
+
+#[test]
+fn chunking_does_not_depend_on_thread_limit() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+	let mut old_db = MemoryDB::new();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let snap_dir = RandomTempPath::create_dir();
+	let state_root = producer.state_root();
+	let mut results = Vec::new();
+
+	for &limit in &[1, 4] {
+		let mut snap_file = snap_dir.as_path().to_owned();
+		snap_file.push(format!("SNAP{}", limit));
+
+		let writer = Mutex::new(PackedWriter::new(&snap_file).unwrap());
+		let (state_hashes, chunk_roots) = chunk_state_with_limit(&old_db, &state_root, &writer, &Progress::default(), limit).unwrap();
+		results.push((state_hashes.clone(), chunk_roots));
+
+		writer.into_inner().finish(dummy_manifest(state_hashes, Vec::new(), state_root, 0, H256::default())).unwrap();
+
+		let mut db_path = snap_dir.as_path().to_owned();
+		db_path.push(format!("db{}", limit));
+		let new_db = Arc::new(Database::open(&db_cfg, &db_path.to_string_lossy()).unwrap());
+		let mut rebuilder = StateRebuilder::new(new_db.clone(), Algorithm::Archive);
+		let reader = PackedReader::new(&snap_file).unwrap().unwrap();
+
+		for chunk_hash in &reader.manifest().state_hashes {
+			let raw = reader.chunk(*chunk_hash).unwrap();
+			rebuilder.feed(&::util::snappy::decompress(&raw).unwrap()).unwrap();
+		}
+
+		assert_eq!(rebuilder.state_root(), state_root);
+		rebuilder.check_missing().unwrap();
+
+		compare_dbs(&old_db, journaldb::new(new_db, Algorithm::Archive, ::db::COL_STATE).as_hashdb());
+	}
+
+	assert_eq!(results[0], results[1]);
+}
+
+#[test]
+fn tampered_chunk_detected_early() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+	let mut old_db = MemoryDB::new();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	for _ in 0..50 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let snap_dir = RandomTempPath::create_dir();
+	let mut snap_file = snap_dir.as_path().to_owned();
+	snap_file.push("SNAP");
+
+	let state_root = producer.state_root();
+	let writer = Mutex::new(PackedWriter::new(&snap_file).unwrap());
+
+	let (state_hashes, chunk_roots) = chunk_state_with_roots(&old_db, &state_root, &writer, &Progress::default()).unwrap();
+	assert_eq!(state_hashes.len(), chunk_roots.len());
+
+	writer.into_inner().finish(::snapshot::ManifestData {
+		state_chunk_roots: chunk_roots.clone(),
+		..dummy_manifest(state_hashes, Vec::new(), state_root, 0, H256::default())
+	}).unwrap();
+
+	let reader = PackedReader::new(&snap_file).unwrap().unwrap();
+	let chunk = {
+		let raw = reader.chunk(reader.manifest().state_hashes[0]).unwrap();
+		::util::snappy::decompress(&raw).unwrap()
+	};
+
+	// drop the last account from the chunk.
+	let tampered = {
+		let rlp = UntrustedRlp::new(&chunk);
+		let count = rlp.item_count();
+		assert!(count > 1);
+
+		let mut stream = RlpStream::new_list(count - 1);
+		for account in rlp.iter().take(count - 1) {
+			stream.append_raw(account.as_raw(), 1);
+		}
+		stream.out()
+	};
+
+	let mut db_path = snap_dir.as_path().to_owned();
+	db_path.push("db");
+	let new_db = Arc::new(Database::open(&db_cfg, &db_path.to_string_lossy()).unwrap());
+
+	// without verification, the chunk is accepted and the problem only shows in the final root.
+	StateRebuilder::new(new_db.clone(), Algorithm::Archive).feed(&tampered).unwrap();
+
+	let mut rebuilder = StateRebuilder::new(new_db, Algorithm::Archive);
+	match rebuilder.feed_verified(&tampered, &chunk_roots[0]) {
+		Err(::error::Error::Snapshot(::snapshot::Error::ChunkRootMismatch(expected, _))) => assert_eq!(expected, chunk_roots[0]),
+		other => panic!("expected chunk root mismatch, got {:?}", other),
+	}
+	assert_eq!(rebuilder.state_root(), SHA3_NULL_RLP);
+
+	// the rejected chunk left nothing behind, so the intact one can be fed to the same rebuilder.
+	rebuilder.feed_verified(&chunk, &chunk_roots[0]).unwrap();
+}
+
+#[test]
+fn corrupt_account_aborts_or_is_skipped() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+	let mut db = MemoryDB::new();
+
+	for _ in 0..50 {
+		producer.tick(&mut rng, &mut db);
+	}
+
+	let address = Address::from(1);
+	let mut state_root = producer.state_root();
+	let mut storage_root = SHA3_NULL_RLP;
+	{
+		let mut acct_db = AccountDBMut::new(&mut db, &address);
+		let mut storage = SecTrieDBMut::new(&mut acct_db, &mut storage_root);
+		storage.insert(&H256::from(1), &::rlp::encode(&U256::from(100)).to_vec()).unwrap();
+	}
+
+	let mut account = RlpStream::new_list(4);
+	account.append(&U256::zero())
+		.append(&U256::from(1000))
+		.append(&storage_root)
+		.append(&SHA3_EMPTY);
+	{
+		let mut state = SecTrieDBMut::from_existing(&mut db, &mut state_root).unwrap();
+		state.insert(&address, &account.out()).unwrap();
+	}
+
+	// lose the storage trie of the account.
+	AccountDBMut::new(&mut db, &address).remove(&storage_root);
+
+	let snap_dir = RandomTempPath::create_dir();
+	let mut snap_file = snap_dir.as_path().to_owned();
+	snap_file.push("SNAP");
+
+	let writer = Mutex::new(PackedWriter::new(&snap_file).unwrap());
+	match chunk_state(&db, &state_root, &writer, &Progress::default()) {
+		Err(Error::CorruptAccount(key, TrieError::InvalidStateRoot(root))) => {
+			assert_eq!(key, address.sha3().to_vec());
+			assert_eq!(root, storage_root);
+		},
+		other => panic!("unexpected result: {:?}", other),
+	}
+
+	let writer = Mutex::new(PackedWriter::new(&snap_file).unwrap());
+	let (state_hashes, _, skipped) = chunk_state_skipping_corrupt(&db, &state_root, &writer, &Progress::default(), 1).unwrap();
+	assert_eq!(skipped, vec![(address.sha3().to_vec(), TrieError::InvalidStateRoot(storage_root))]);
+	assert!(!state_hashes.is_empty());
+
+	writer.into_inner().finish(dummy_manifest(state_hashes, Vec::new(), state_root, 0, H256::default())).unwrap();
+
+	let reader = PackedReader::new(&snap_file).unwrap().unwrap();
+	assert!(extract_accounts(&reader, &[address]).unwrap().is_empty());
+}
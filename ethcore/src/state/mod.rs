@@ -182,6 +182,18 @@ impl State {
 			|a| a.as_ref().map_or(self.account_start_nonce, |account| *account.nonce()))
 	}
 
+	/// Get the code hash of account `a`.
+	pub fn code_hash(&self, a: &Address) -> H256 {
+		self.ensure_cached(a, false,
+			|a| a.as_ref().map_or(SHA3_EMPTY, |account| account.code_hash()))
+	}
+
+	/// Get the storage root of account `a`. `None` if the account has uncommitted storage changes.
+	pub fn storage_root(&self, a: &Address) -> Option<H256> {
+		self.ensure_cached(a, false,
+			|a| a.as_ref().map_or(Some(SHA3_NULL_RLP), |account| account.storage_root().cloned()))
+	}
+
 	/// Mutate storage of account `address` so that it is `value` for `key`.
 	pub fn storage_at(&self, address: &Address, key: &H256) -> H256 {
 		self.ensure_cached(address, false, |a| a.as_ref().map_or(H256::new(), |a| {
@@ -241,7 +253,7 @@ impl State {
 	pub fn apply(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool) -> ApplyResult {
 //		let old = self.to_pod();
 
-		let options = TransactOptions { tracing: tracing, vm_tracing: false, check_nonce: true };
+		let options = TransactOptions { tracing: tracing, vm_tracing: false, check_nonce: true, deadline: None };
 		let vm_factory = self.factories.vm.clone();
 		let e = try!(Executive::new(self, env_info, engine, &vm_factory).transact(t, options));
 
@@ -0,0 +1,34 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Account info type definition
+
+use util::{H256, U256};
+
+/// Summary of an account, read from a single state.
+#[derive(Debug, Clone, PartialEq, Binary)]
+pub struct AccountInfo {
+	/// Account balance.
+	pub balance: U256,
+	/// Account nonce.
+	pub nonce: U256,
+	/// Hash of the account code.
+	pub code_hash: H256,
+	/// Root of the account storage trie.
+	pub storage_root: H256,
+	/// Size of the account code in bytes.
+	pub code_size: u64,
+}
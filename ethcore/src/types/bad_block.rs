@@ -0,0 +1,35 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bad block type definition
+
+use util::H256;
+use header::BlockNumber;
+
+/// A block which was rejected during import.
+#[derive(Debug, Clone, PartialEq, Binary)]
+pub struct BadBlock {
+	/// Block hash.
+	pub hash: H256,
+	/// Block number.
+	pub number: BlockNumber,
+	/// Reason the block was rejected.
+	pub error: String,
+	/// Size of the block RLP in bytes.
+	pub size: u64,
+	/// Peer the block was received from, if known.
+	pub peer: Option<String>,
+}
@@ -16,6 +16,8 @@
 
 //! Call analytics related types
 
+use std::time::{Duration, Instant};
+
 /// Options concerning what analytics we run on the call.
 #[derive(Eq, PartialEq, Default, Clone, Copy, Debug, Binary)]
 pub struct CallAnalytics {
@@ -25,4 +27,13 @@ pub struct CallAnalytics {
 	pub vm_tracing: bool,
 	/// Make a diff.
 	pub state_diffing: bool,
+	/// Abort execution if it takes longer than this many milliseconds.
+	pub timeout_ms: Option<u64>,
+}
+
+impl CallAnalytics {
+	/// Deadline for a call starting now.
+	pub fn deadline(&self) -> Option<Instant> {
+		self.timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms))
+	}
 }
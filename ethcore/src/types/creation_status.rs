@@ -0,0 +1,30 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshot creation status type definition
+
+/// Progress of a snapshot being taken.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Binary)]
+pub struct CreationStatus {
+	/// Number of accounts snapshotted thus far.
+	pub accounts: u64,
+	/// Number of blocks snapshotted thus far.
+	pub blocks: u64,
+	/// Written size of the snapshot in bytes.
+	pub bytes: u64,
+	/// Whether the snapshot is complete.
+	pub done: bool,
+}
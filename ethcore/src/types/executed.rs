@@ -140,6 +140,8 @@ pub enum ExecutionError {
 	},
 	/// Returned when internal evm error occurs.
 	Internal,
+	/// Returned when execution didn't finish before its deadline.
+	Timeout,
 	/// Returned when generic transaction occurs
 	TransactionMalformed(String),
 }
@@ -160,6 +162,7 @@ impl fmt::Display for ExecutionError {
 				format!("Cost of transaction exceeds sender balance. {} is required \
 					but the sender only has {}", required, got),
 			Internal => "Internal evm error".into(),
+			Timeout => "Execution timed out".into(),
 			TransactionMalformed(ref err) => format!("Malformed transaction: {}", err),
 		};
 
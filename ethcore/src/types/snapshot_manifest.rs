@@ -33,17 +33,29 @@ pub struct ManifestData {
 	pub block_number: u64,
 	/// Block hash this snapshot was taken at.
 	pub block_hash: H256,
+	/// Roots of the tries formed by the accounts of each state chunk, in the same
+	/// order as `state_hashes`. Empty for manifests which don't record them.
+	pub state_chunk_roots: Vec<H256>,
+	/// Snapshot format features the chunks rely on. Clients must refuse to restore
+	/// snapshots using features they don't know. Empty for legacy manifests.
+	pub features: Vec<String>,
+	/// Name of the consensus engine of the chain the snapshot was taken from.
+	/// Empty for legacy manifests.
+	pub engine: String,
 }
 
 impl ManifestData {
 	/// Encode the manifest data to rlp.
 	pub fn into_rlp(self) -> Bytes {
-		let mut stream = RlpStream::new_list(5);
+		let mut stream = RlpStream::new_list(8);
 		stream.append(&self.state_hashes);
 		stream.append(&self.block_hashes);
 		stream.append(&self.state_root);
 		stream.append(&self.block_number);
 		stream.append(&self.block_hash);
+		stream.append(&self.state_chunk_roots);
+		stream.append(&self.features);
+		stream.append(&self.engine);
 
 		stream.out()
 	}
@@ -57,6 +69,18 @@ impl ManifestData {
 		let state_root: H256 = try!(decoder.val_at(2));
 		let block_number: u64 = try!(decoder.val_at(3));
 		let block_hash: H256 = try!(decoder.val_at(4));
+		let state_chunk_roots: Vec<H256> = match decoder.item_count() > 5 {
+			true => try!(decoder.val_at(5)),
+			false => Vec::new(),
+		};
+		let features: Vec<String> = match decoder.item_count() > 6 {
+			true => try!(decoder.val_at(6)),
+			false => Vec::new(),
+		};
+		let engine: String = match decoder.item_count() > 7 {
+			true => try!(decoder.val_at(7)),
+			false => String::new(),
+		};
 
 		Ok(ManifestData {
 			state_hashes: state_hashes,
@@ -64,6 +88,9 @@ impl ManifestData {
 			state_root: state_root,
 			block_number: block_number,
 			block_hash: block_hash,
+			state_chunk_roots: state_chunk_roots,
+			features: features,
+			engine: engine,
 		})
 	}
 }
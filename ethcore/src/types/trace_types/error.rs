@@ -37,6 +37,8 @@ pub enum Error {
 	/// Returned on evm internal error. Should never be ignored during development.
 	/// Likely to cause consensus issues.
 	Internal,
+	/// Execution ran past its deadline.
+	Timeout,
 }
 
 impl From<EvmError> for Error {
@@ -48,6 +50,7 @@ impl From<EvmError> for Error {
 			EvmError::StackUnderflow { .. } => Error::StackUnderflow,
 			EvmError::OutOfStack { .. } => Error::OutOfStack,
 			EvmError::Internal => Error::Internal,
+			EvmError::Timeout => Error::Timeout,
 		}
 	}
 }
@@ -62,6 +65,7 @@ impl fmt::Display for Error {
 			StackUnderflow => "Stack underflow",
 			OutOfStack => "Out of stack",
 			Internal => "Internal error",
+			Timeout => "Timed out",
 		};
 		message.fmt(f)
 	}
@@ -77,6 +81,7 @@ impl Encodable for Error {
 			StackUnderflow => 3,
 			OutOfStack => 4,
 			Internal => 5,
+			Timeout => 6,
 		};
 		s.append(&value);
 	}
@@ -93,6 +98,7 @@ impl Decodable for Error {
 			3 => Ok(StackUnderflow),
 			4 => Ok(OutOfStack),
 			5 => Ok(Internal),
+			6 => Ok(Timeout),
 			_ => Err(DecoderError::Custom("Invalid error type")),
 		}
 	}
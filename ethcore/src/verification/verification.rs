@@ -178,6 +178,9 @@ pub fn verify_block_final(expected: &Header, got: &Header) -> Result<(), Error>
 	Ok(())
 }
 
+/// Number of seconds a block timestamp may be ahead of our clock.
+const ACCEPTABLE_DRIFT_SECS: u64 = 15;
+
 /// Check basic header parameters.
 fn verify_header(header: &Header, engine: &Engine) -> Result<(), Error> {
 	if header.number() >= From::from(BlockNumber::max_value()) {
@@ -194,6 +197,10 @@ fn verify_header(header: &Header, engine: &Engine) -> Result<(), Error> {
 	if header.number() != 0 && header.extra_data().len() > maximum_extra_data_size {
 		return Err(From::from(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(maximum_extra_data_size), found: header.extra_data().len() })));
 	}
+	let max_time = ::time::get_time().sec as u64 + ACCEPTABLE_DRIFT_SECS;
+	if header.timestamp() > max_time {
+		return Err(From::from(BlockError::InvalidTimestamp(OutOfBounds { max: Some(max_time), min: None, found: header.timestamp() })));
+	}
 	Ok(())
 }
 
@@ -483,6 +490,13 @@ mod tests {
 		check_fail(basic_test(&create_test_block_with_data(&header, &good_transactions, &good_uncles), engine),
 			InvalidUnclesHash(Mismatch { expected: good_uncles_hash.clone(), found: header.uncles_hash().clone() }));
 
+		header = good.clone();
+		header.set_timestamp(::time::get_time().sec as u64 + 3600);
+		match basic_test(&create_test_block_with_data(&header, &good_transactions, &good_uncles), engine) {
+			Err(Error::Block(ref e @ InvalidTimestamp(OutOfBounds { max: Some(_), min: None, .. }))) => assert!(e.is_temporary()),
+			other => panic!("Block verification failed.\nExpected: future timestamp\nGot: {:?}", other),
+		}
+
 		check_ok(family_test(&create_test_block(&good), engine, &bc));
 		check_ok(family_test(&create_test_block_with_data(&good, &good_transactions, &good_uncles), engine, &bc));
 
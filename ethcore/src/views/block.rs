@@ -108,6 +108,11 @@ impl<'a> BlockView<'a> {
 		self.rlp.at(1).iter().nth(index).map(|rlp| rlp.as_val())
 	}
 
+	/// Returns rlp of the transaction at given index.
+	pub fn transaction_rlp_at(&self, index: usize) -> Option<Bytes> {
+		self.rlp.at(1).iter().nth(index).map(|rlp| rlp.as_raw().to_vec())
+	}
+
 	/// Returns localized transaction at given index.
 	pub fn localized_transaction_at(&self, index: usize) -> Option<LocalizedTransaction> {
 		let header = self.header_view();
@@ -85,6 +85,11 @@ impl<'a> BodyView<'a> {
 		self.rlp.at(0).iter().nth(index).map(|rlp| rlp.as_val())
 	}
 
+	/// Returns rlp of the transaction at given index.
+	pub fn transaction_rlp_at(&self, index: usize) -> Option<Bytes> {
+		self.rlp.at(0).iter().nth(index).map(|rlp| rlp.as_raw().to_vec())
+	}
+
 	/// Returns localized transaction at given index.
 	pub fn localized_transaction_at(&self, block_hash: &H256, block_number: BlockNumber, index: usize) -> Option<LocalizedTransaction> {
 		self.transaction_at(index).map(|t| LocalizedTransaction {
@@ -25,6 +25,7 @@ use dir::KeyDirectory;
 use account::SafeAccount;
 use {Error, SecretStore};
 use json;
+use serde_json;
 use json::UUID;
 use presale::PresaleWallet;
 use import;
@@ -109,6 +110,14 @@ impl SecretStore for EthStore {
 		Ok(address)
 	}
 
+	fn export_wallet(&self, address: &Address, password: &str) -> Result<Vec<u8>, Error> {
+		// fresh salt and iv, so that the exported file does not share them with the stored one
+		let account = try!(self.get(address));
+		let account = try!(account.change_password(password, password, self.iterations));
+		let json_keyfile: json::KeyFile = account.into();
+		serde_json::to_vec(&json_keyfile).map_err(|e| Error::Custom(format!("Unable to serialize key file: {}", e)))
+	}
+
 	fn accounts(&self) -> Result<Vec<Address>, Error> {
 		try!(self.reload_accounts());
 		Ok(self.cache.read().unwrap().keys().cloned().collect())
@@ -144,6 +153,11 @@ impl SecretStore for EthStore {
 		account.sign(password, message)
 	}
 
+	fn secret(&self, address: &Address, password: &str) -> Result<Secret, Error> {
+		let account = try!(self.get(address));
+		account.crypto.secret(password)
+	}
+
 	fn uuid(&self, address: &Address) -> Result<UUID, Error> {
 		let account = try!(self.get(address));
 		Ok(account.id.into())
@@ -25,6 +25,9 @@ pub trait SecretStore: Send + Sync {
 
 	fn import_wallet(&self, json: &[u8], password: &str) -> Result<Address, Error>;
 
+	/// Returns the account as V3 keystore JSON, re-encrypted with the given password.
+	fn export_wallet(&self, account: &Address, password: &str) -> Result<Vec<u8>, Error>;
+
 	fn accounts(&self) -> Result<Vec<Address>, Error>;
 
 	fn change_password(&self, account: &Address, old_password: &str, new_password: &str) -> Result<(), Error>;
@@ -33,6 +36,9 @@ pub trait SecretStore: Send + Sync {
 
 	fn sign(&self, account: &Address, password: &str, message: &Message) -> Result<Signature, Error>;
 
+	/// Decrypts the secret of the account.
+	fn secret(&self, account: &Address, password: &str) -> Result<Secret, Error>;
+
 	fn uuid(&self, account: &Address) -> Result<UUID, Error>;
 
 	fn name(&self, account: &Address) -> Result<String, Error>;
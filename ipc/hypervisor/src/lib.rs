@@ -21,35 +21,125 @@
 extern crate ethcore_ipc as ipc;
 extern crate ethcore_ipc_nano as nanoipc;
 extern crate semver;
+extern crate libc;
 #[macro_use] extern crate log;
 
 pub mod service;
+mod limits;
 
 /// Default value for hypervisor ipc listener
 pub const HYPERVISOR_IPC_URL: &'static str = "parity-internal-hyper-status.ipc";
 
-use std::sync::{Arc,RwLock};
+use std::sync::{Arc,RwLock,Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
 use service::{HypervisorService, IpcModuleId};
 use std::process::{Command,Child};
 use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::fmt;
+use std::io;
+use semver::VersionReq;
 
-pub use service::{HypervisorServiceClient, ControlService, CLIENT_MODULE_ID, SYNC_MODULE_ID};
+pub use service::{HypervisorServiceClient, ControlService, StartupEvent, CLIENT_MODULE_ID, SYNC_MODULE_ID};
+pub use limits::{ResourceLimits, ResourceEvent};
 
 pub type BinaryId = &'static str;
 
+/// How often module processes are checked for unexpected exits
+const SUPERVISE_INTERVAL_MS: u64 = 250;
+/// How often resident memory of module processes is checked against their limits
+const RESOURCE_CHECK_INTERVAL_MS: u64 = 5000;
+/// How often module processes are checked for exit while shutting down
+const EXIT_POLL_INTERVAL_MS: u64 = 20;
+/// Default time modules are given to stop, both after the shutdown request and after SIGTERM
+pub const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+
+/// Callback invoked with the module id and the number of restarts once a module keeps failing
+pub type FailureHandler = Fn(IpcModuleId, usize) + Send + Sync;
+
 pub struct Hypervisor {
-	ipc_addr: String,
 	service: Arc<HypervisorService>,
-	ipc_worker: RwLock<nanoipc::Worker<HypervisorService>>,
-	processes: RwLock<HashMap<IpcModuleId, Child>>,
+	ipc_worker: Arc<Mutex<nanoipc::Worker<HypervisorService>>>,
+	poller: Mutex<Option<thread::JoinHandle<()>>>,
+	polling: Arc<AtomicBool>,
+	processes: Arc<RwLock<HashMap<BootArgs, Child>>>,
 	modules: HashMap<IpcModuleId, BootArgs>,
+	resource_listeners: Arc<Mutex<Vec<mpsc::Sender<ResourceEvent>>>>,
+	restart_policy: RestartPolicy,
+	restarts: Arc<Mutex<HashMap<BootArgs, usize>>>,
+	failure_handler: Option<Arc<FailureHandler>>,
+	supervisor: Mutex<Option<thread::JoinHandle<()>>>,
+	stopping: Arc<AtomicBool>,
+	shutdown_timeout: Duration,
 	pub io_path: String,
 }
 
+/// Hypervisor errors
+#[derive(Debug, PartialEq)]
+pub enum HypervisorError {
+	/// Listed modules did not check in within the startup timeout
+	StartupTimeout(Vec<IpcModuleId>),
+	/// The ipc listener could not be bound to the address
+	Listen(String, String),
+}
+
+impl fmt::Display for HypervisorError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HypervisorError::StartupTimeout(ref modules) => write!(f, "Modules {:?} did not check in in time", modules),
+			HypervisorError::Listen(ref addr, ref e) => write!(f, "Cannot listen on {}: {}", addr, e),
+		}
+	}
+}
+
+/// Restart policy for module processes exiting unexpectedly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RestartPolicy {
+	/// Number of times a module is restarted before it is given up
+	pub max_restarts: usize,
+	/// Delay between noticing the exit and respawning the module
+	pub backoff: Duration,
+}
+
+impl Default for RestartPolicy {
+	fn default() -> Self {
+		RestartPolicy {
+			max_restarts: 3,
+			backoff: Duration::from_secs(1),
+		}
+	}
+}
+
+/// Changes the logging levels of the running modules
+#[derive(Clone)]
+pub struct LoggingControl {
+	service: Arc<HypervisorService>,
+}
+
+impl LoggingControl {
+	/// Changes the logging levels of all running modules, see `Hypervisor::set_module_logging`
+	pub fn set_logging(&self, levels: &str) {
+		for module_id in self.service.running_modules() {
+			if !self.service.send_logging(module_id, levels) {
+				warn!(target: "hypervisor", "Module {} did not change its logging levels", module_id);
+			}
+		}
+	}
+}
+
 /// Boot arguments for binary
+/// Modules with equal boot arguments are served by the same process
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct BootArgs {
 	cli: Option<Vec<String>>,
 	stdin: Option<Vec<u8>>,
+	limits: ResourceLimits,
+	env: Vec<(String, String)>,
+	clear_env: bool,
+	current_dir: Option<PathBuf>,
 }
 
 impl BootArgs {
@@ -58,6 +148,10 @@ impl BootArgs {
 		BootArgs {
 			cli: None,
 			stdin: None,
+			limits: ResourceLimits::default(),
+			env: Vec::new(),
+			clear_env: false,
+			current_dir: None,
 		}
 	}
 
@@ -72,11 +166,35 @@ impl BootArgs {
 		self.stdin = Some(stdin);
 		self
 	}
+
+	/// Set resource limits of the spawned process
+	pub fn limits(mut self, limits: ResourceLimits) -> BootArgs {
+		self.limits = limits;
+		self
+	}
+
+	/// Set environment variable of the spawned process, on top of the inherited environment
+	pub fn env(mut self, key: &str, value: &str) -> BootArgs {
+		self.env.push((key.to_owned(), value.to_owned()));
+		self
+	}
+
+	/// Do not inherit the environment, so that the process only sees variables set with `env`
+	pub fn clear_env(mut self) -> BootArgs {
+		self.clear_env = true;
+		self
+	}
+
+	/// Set working directory of the spawned process
+	pub fn current_dir(mut self, directory: &str) -> BootArgs {
+		self.current_dir = Some(PathBuf::from(directory));
+		self
+	}
 }
 
 impl Hypervisor {
 	/// initializes the Hypervisor service with the open ipc socket for incoming clients
-	pub fn new() -> Hypervisor {
+	pub fn new() -> Result<Hypervisor, HypervisorError> {
 		Hypervisor::with_url(HYPERVISOR_IPC_URL)
 	}
 
@@ -91,24 +209,56 @@ impl Hypervisor {
 		self
 	}
 
+	/// Range of versions modules must report on check-in, any version is accepted by default
+	pub fn required_version(self, required: VersionReq) -> Hypervisor {
+		self.service.set_required_version(required);
+		self
+	}
+
 	pub fn io_path(mut self, directory: &str) -> Hypervisor {
 		self.io_path = directory.to_owned();
 		self
 	}
 
-	/// Starts with the specified address for the ipc listener and
-	/// the specified list of modules in form of created service
-	pub fn with_url(addr: &str) -> Hypervisor {
+	/// Restart modules exiting unexpectedly at most `max_restarts` times, waiting `backoff` before each restart
+	pub fn restart_policy(mut self, max_restarts: usize, backoff: Duration) -> Hypervisor {
+		self.restart_policy = RestartPolicy { max_restarts: max_restarts, backoff: backoff };
+		self
+	}
+
+	/// Callback invoked when a module exits after it was restarted the maximal number of times
+	pub fn on_module_failure<F>(mut self, handler: F) -> Hypervisor where F: Fn(IpcModuleId, usize) + Send + Sync + 'static {
+		self.failure_handler = Some(Arc::new(handler) as Arc<FailureHandler>);
+		self
+	}
+
+	/// Time module processes are given to exit after the shutdown request, and again after SIGTERM before they are killed
+	pub fn shutdown_timeout(mut self, timeout: Duration) -> Hypervisor {
+		self.shutdown_timeout = timeout;
+		self
+	}
+
+	/// Binds the ipc listener to the specified address, failing if the address is unusable
+	pub fn with_url(addr: &str) -> Result<Hypervisor, HypervisorError> {
 		let service = HypervisorService::new();
-		let worker = nanoipc::Worker::new(&service);
-		Hypervisor{
-			ipc_addr: addr.to_owned(),
+		let mut worker = nanoipc::Worker::new(&service);
+		try!(worker.add_reqrep(addr).map_err(|e| HypervisorError::Listen(addr.to_owned(), format!("{:?}", e))));
+		Ok(Hypervisor{
 			service: service,
-			ipc_worker: RwLock::new(worker),
-			processes: RwLock::new(HashMap::new()),
+			ipc_worker: Arc::new(Mutex::new(worker)),
+			poller: Mutex::new(None),
+			polling: Arc::new(AtomicBool::new(false)),
+			processes: Arc::new(RwLock::new(HashMap::new())),
 			modules: HashMap::new(),
+			resource_listeners: Arc::new(Mutex::new(Vec::new())),
+			restart_policy: RestartPolicy::default(),
+			restarts: Arc::new(Mutex::new(HashMap::new())),
+			failure_handler: None,
+			supervisor: Mutex::new(None),
+			stopping: Arc::new(AtomicBool::new(false)),
+			shutdown_timeout: Duration::from_secs(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
 			io_path: "/tmp".to_owned(),
-		}
+		})
 	}
 
 	/// Since one binary can host multiple modules
@@ -117,13 +267,46 @@ impl Hypervisor {
 		self.modules.get(module_id)
 	}
 
-	/// Creates IPC listener and starts all binaries
+	/// Starts the ipc polling thread, all binaries and the supervisor restarting them on exit
 	pub fn start(&self) {
-		let mut worker = self.ipc_worker.write().unwrap();
-		worker.add_reqrep(&self.ipc_addr).unwrap_or_else(|e| panic!("Hypervisor ipc worker can not start - critical! ({:?})", e));
+		// modules check in and report shutdown at any time, including after they were restarted
+		if !self.polling.swap(true, Ordering::SeqCst) {
+			let worker = self.ipc_worker.clone();
+			let polling = self.polling.clone();
+			let handle = thread::Builder::new()
+				.name("hypervisor-ipc".to_owned())
+				.spawn(move || {
+					while polling.load(Ordering::SeqCst) {
+						worker.lock().unwrap().poll();
+					}
+				})
+				.expect("Error creating hypervisor ipc thread");
+			*self.poller.lock().unwrap() = Some(handle);
+		}
+
+		// local modules have no boot arguments, modules sharing boot arguments share the process
+		for boot_args in self.modules.values() {
+			self.start_process(boot_args);
+		}
 
-		for module_id in self.service.module_ids() {
-			self.start_module(module_id);
+		if !self.modules.is_empty() {
+			let supervisor = Supervisor {
+				service: self.service.clone(),
+				processes: self.processes.clone(),
+				modules: self.modules.clone(),
+				policy: self.restart_policy,
+				restarts: self.restarts.clone(),
+				failure_handler: self.failure_handler.clone(),
+				resource_listeners: self.resource_listeners.clone(),
+				pending: HashMap::new(),
+				last_resource_check: Instant::now(),
+			};
+			let stopping = self.stopping.clone();
+			let handle = thread::Builder::new()
+				.name("hypervisor-supervisor".to_owned())
+				.spawn(move || supervisor.run(&stopping))
+				.expect("Error creating supervisor thread");
+			*self.supervisor.lock().unwrap() = Some(handle);
 		}
 	}
 
@@ -131,79 +314,187 @@ impl Hypervisor {
 	/// Does nothing when it is already started on module is inside the
 	/// main binary
 	fn start_module(&self, module_id: IpcModuleId) {
-		use std::io::Write;
+		self.match_module(&module_id).map(|boot_args| self.start_process(boot_args));
+	}
 
-		self.match_module(&module_id).map(|boot_args| {
-			let mut processes = self.processes.write().unwrap();
-			{
-				if processes.get(&module_id).is_some() {
-					// already started for another module
-					return;
-				}
-			}
+	/// Start binary with the given boot arguments, unless it is already started for another module
+	fn start_process(&self, boot_args: &BootArgs) {
+		let mut processes = self.processes.write().unwrap();
+		if !processes.contains_key(boot_args) {
+			let child = spawn_module(boot_args).unwrap_or_else(|e| panic!("Hypervisor cannot start module process: {}", e));
+			processes.insert(boot_args.clone(), child);
+		}
+	}
 
-			let mut command = Command::new(&std::env::current_exe().unwrap());
-			command.stderr(std::process::Stdio::inherit());
+	/// Number of times the module was restarted after exiting unexpectedly
+	pub fn restart_count(&self, module_id: IpcModuleId) -> usize {
+		self.match_module(&module_id)
+			.and_then(|boot_args| self.restarts.lock().unwrap().get(boot_args).cloned())
+			.unwrap_or(0)
+	}
 
-			if let Some(ref cli_args) = boot_args.cli {
-				for arg in cli_args { command.arg(arg); }
-			}
+	/// Reports if all modules are checked in
+	pub fn modules_ready(&self) -> bool {
+		self.service.unchecked_count() == 0
+	}
 
-			command.stdin(std::process::Stdio::piped());
+	/// Modules refused on check-in for reporting a version outside of the required range
+	pub fn incompatible_modules(&self) -> Vec<(IpcModuleId, String)> {
+		self.service.incompatible_modules()
+	}
 
-			trace!(target: "hypervisor", "Spawn executable: {:?}", command);
+	pub fn modules_shutdown(&self) -> bool {
+		self.service.running_count() == 0
+	}
 
-			let mut child = command.spawn().unwrap_or_else(
-				|e| panic!("Hypervisor cannot execute command ({:?}): {}", command, e));
+	/// Changes the logging levels of a running module at runtime, given in the `RUST_LOG` format.
+	/// Empty levels restore the ones the module was started with.
+	pub fn set_module_logging(&self, module_id: IpcModuleId, levels: &str) -> bool {
+		self.service.send_logging(module_id, levels)
+	}
 
-			if let Some(ref std_in) = boot_args.stdin {
-				trace!(target: "hypervisor", "Pushing std-in payload...");
-				child.stdin.as_mut()
-					.expect("std-in should be piped above")
-					.write(std_in)
-					.unwrap_or_else(|e| panic!(format!("Error trying to pipe stdin for {:?}: {:?}", &command, e)));
-				drop(child.stdin.take());
-			}
+	/// Changes the logging levels of all running modules, see `set_module_logging`
+	pub fn set_logging(&self, levels: &str) {
+		self.logging_control().set_logging(levels)
+	}
 
-			processes.insert(module_id, child);
-		});
+	/// Handle changing the logging levels of the modules from other threads
+	pub fn logging_control(&self) -> LoggingControl {
+		LoggingControl { service: self.service.clone() }
 	}
 
-	/// Reports if all modules are checked in
-	pub fn modules_ready(&self) -> bool {
-		self.service.unchecked_count() == 0
+	/// Channel of module check-in events, in the order the service processes them
+	pub fn startup_notification(&self) -> mpsc::Receiver<StartupEvent> {
+		self.service.startup_notification()
 	}
 
-	pub fn modules_shutdown(&self) -> bool {
-		self.service.running_count() == 0
+	/// Channel of resource limit events reported by `check_resources`
+	pub fn resource_notification(&self) -> mpsc::Receiver<ResourceEvent> {
+		let (tx, rx) = mpsc::channel();
+		self.resource_listeners.lock().unwrap().push(tx);
+		rx
 	}
 
-	/// Waits for every required module to check in
-	pub fn wait_for_startup(&self) {
-		let mut worker = self.ipc_worker.write().unwrap();
-		while !self.modules_ready() {
-			worker.poll()
+	/// Samples resident memory of all module processes against their limits.
+	/// Modules over the soft limit are reported, modules over the hard limit are restarted.
+	/// The supervisor started by `start` does this periodically.
+	pub fn check_resources(&self) {
+		let restart = sample_resources(&mut self.processes.write().unwrap(), &self.modules, &self.resource_listeners);
+		{
+			let mut processes = self.processes.write().unwrap();
+			for boot_args in &restart {
+				processes.remove(boot_args);
+			}
+		}
+		for boot_args in restart {
+			register_process_modules(&self.service, &self.modules, &boot_args);
+			self.start_process(&boot_args);
+		}
+	}
+
+	/// Serves pending ipc requests, or just waits a while if the polling thread serves them
+	fn poll_ipc(&self) {
+		let polled_elsewhere = self.poller.lock().unwrap().is_some();
+		match polled_elsewhere {
+			true => thread::sleep(Duration::from_millis(EXIT_POLL_INTERVAL_MS)),
+			false => self.ipc_worker.lock().unwrap().poll(),
+		}
+	}
+
+	/// Stops the ipc polling thread
+	fn stop_polling(&self) {
+		self.polling.store(false, Ordering::SeqCst);
+		if let Some(handle) = self.poller.lock().unwrap().take() {
+			if handle.join().is_err() {
+				warn!(target: "hypervisor", "Ipc thread panicked");
+			}
 		}
 	}
 
-	/// Waits for every required module to check in
-	pub fn wait_for_shutdown(&self) {
-		let mut worker = self.ipc_worker.write().unwrap();
+	/// Waits for every required module to check in, failing with the modules
+	/// still missing once the timeout passes
+	pub fn wait_for_startup(&self, timeout: Duration) -> Result<(), HypervisorError> {
+		let events = self.startup_notification();
+		let deadline = Instant::now() + timeout;
+		loop {
+			if self.modules_ready() {
+				return Ok(());
+			}
+			while let Ok(event) = events.try_recv() {
+				if event == StartupEvent::AllModulesReady {
+					return Ok(());
+				}
+			}
+			if Instant::now() >= deadline {
+				return Err(HypervisorError::StartupTimeout(self.service.unchecked_modules()));
+			}
+			self.poll_ipc();
+		}
+	}
+
+	/// Waits for the running modules to report shutdown, returning false if they did not in time
+	pub fn wait_for_shutdown(&self, timeout: Duration) -> bool {
+		let deadline = Instant::now() + timeout;
 		while !self.modules_shutdown() {
-			worker.poll()
+			if Instant::now() >= deadline {
+				return false;
+			}
+			self.poll_ipc();
+		}
+		true
+	}
+
+	/// Waits for module processes to exit until the deadline, then sends SIGTERM to the remaining ones
+	/// and finally kills those still running after another shutdown timeout
+	fn stop_processes(&self, deadline: Instant) {
+		let mut processes = self.processes.write().unwrap();
+		reap_processes(&mut processes, deadline);
+
+		for (boot_args, child) in processes.iter_mut() {
+			let module_ids = process_modules(&self.modules, boot_args);
+			warn!(target: "hypervisor", "Modules {:?} did not exit in time, terminating", module_ids);
+			if let Err(e) = terminate(child) {
+				warn!(target: "hypervisor", "Error terminating modules {:?}: {:?}", module_ids, e);
+			}
+		}
+		reap_processes(&mut processes, Instant::now() + self.shutdown_timeout);
+
+		for (boot_args, mut child) in processes.drain() {
+			let module_ids = process_modules(&self.modules, &boot_args);
+			warn!(target: "hypervisor", "Modules {:?} ignored SIGTERM, killing", module_ids);
+			if let Err(e) = child.kill().and_then(|_| child.wait()) {
+				warn!(target: "hypervisor", "Error killing modules {:?}: {:?}", module_ids, e);
+			}
 		}
 	}
 
 	/// Shutdown the ipc and all managed child processes
 	pub fn shutdown(&self) {
-		let mut childs = self.processes.write().unwrap();
-		for (ref mut module, _) in childs.iter_mut() {
-			trace!(target: "hypervisor", "Stopping process module: {}", module);
-			self.service.send_shutdown(**module);
+		// modules exit on shutdown, which must not trigger restarts
+		self.stopping.store(true, Ordering::SeqCst);
+		if let Some(handle) = self.supervisor.lock().unwrap().take() {
+			if handle.join().is_err() {
+				warn!(target: "hypervisor", "Supervisor thread panicked");
+			}
+		}
+
+		let deadline = Instant::now() + self.shutdown_timeout;
+		{
+			let childs = self.processes.read().unwrap();
+			for (module, boot_args) in &self.modules {
+				if childs.contains_key(boot_args) {
+					trace!(target: "hypervisor", "Stopping process module: {}", module);
+					self.service.send_shutdown(*module);
+				}
+			}
 		}
 		trace!(target: "hypervisor", "Waiting for shutdown...");
-		self.wait_for_shutdown();
-		trace!(target: "hypervisor", "All modules reported shutdown");
+		match self.wait_for_shutdown(self.shutdown_timeout) {
+			true => trace!(target: "hypervisor", "All modules reported shutdown"),
+			false => warn!(target: "hypervisor", "Modules did not report shutdown in time"),
+		}
+		self.stop_processes(deadline);
+		self.stop_polling();
 	}
 }
 
@@ -213,22 +504,271 @@ impl Drop for Hypervisor {
 	}
 }
 
+/// Removes exited processes, waiting until all exited or the deadline passed
+fn reap_processes(processes: &mut HashMap<BootArgs, Child>, deadline: Instant) {
+	loop {
+		let mut exited = Vec::new();
+		for (boot_args, child) in processes.iter_mut() {
+			if let Ok(Some(_)) = child.try_wait() {
+				trace!(target: "hypervisor", "Module process {} exited", child.id());
+				exited.push(boot_args.clone());
+			}
+		}
+		for boot_args in exited {
+			processes.remove(&boot_args);
+		}
+		if processes.is_empty() || Instant::now() >= deadline {
+			return;
+		}
+		thread::sleep(Duration::from_millis(EXIT_POLL_INTERVAL_MS));
+	}
+}
+
+/// Asks the process to exit with SIGTERM
+#[cfg(unix)]
+fn terminate(child: &mut Child) -> std::io::Result<()> {
+	match unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) } {
+		0 => Ok(()),
+		_ => Err(std::io::Error::last_os_error()),
+	}
+}
+
+/// There are no signals to ask the process to exit, so it is killed right away
+#[cfg(not(unix))]
+fn terminate(child: &mut Child) -> std::io::Result<()> {
+	child.kill()
+}
+
+/// Samples resident memory of the module processes against their limits and reports the events.
+/// Processes over the hard limit are killed, their boot arguments are returned.
+fn sample_resources(processes: &mut HashMap<BootArgs, Child>, modules: &HashMap<IpcModuleId, BootArgs>, listeners: &Mutex<Vec<mpsc::Sender<ResourceEvent>>>) -> Vec<BootArgs> {
+	let mut killed = Vec::new();
+	for (boot_args, child) in processes.iter_mut() {
+		// events are reported for the first module of the process
+		let module_id = match process_modules(modules, boot_args).first() {
+			Some(module_id) => *module_id,
+			None => continue,
+		};
+		let event = limits::resident_memory(child.id()).and_then(|rss| boot_args.limits.check_memory(module_id, rss));
+		if let Some(event) = event {
+			if let ResourceEvent::HardMemoryLimit(_, rss) = event {
+				warn!(target: "hypervisor", "Module {} uses {} bytes of memory, restarting", module_id, rss);
+				if let Err(e) = child.kill().and_then(|_| child.wait()) {
+					warn!(target: "hypervisor", "Error stopping module {}: {:?}", module_id, e);
+				}
+				killed.push(boot_args.clone());
+			} else {
+				warn!(target: "hypervisor", "Module {} exceeds its memory limit: {:?}", module_id, event);
+			}
+			listeners.lock().unwrap().retain(|tx| tx.send(event).is_ok());
+		}
+	}
+	killed
+}
+
+/// Modules served by the process with the given boot arguments, in ascending order
+fn process_modules(modules: &HashMap<IpcModuleId, BootArgs>, boot_args: &BootArgs) -> Vec<IpcModuleId> {
+	let mut module_ids: Vec<IpcModuleId> = modules.iter()
+		.filter(|&(_, args)| args == boot_args)
+		.map(|(module_id, _)| *module_id)
+		.collect();
+	module_ids.sort();
+	module_ids
+}
+
+/// Registers the modules of a (re)started process, which have to check in again
+fn register_process_modules(service: &HypervisorService, modules: &HashMap<IpcModuleId, BootArgs>, boot_args: &BootArgs) {
+	for module_id in process_modules(modules, boot_args) {
+		service.add_module(module_id);
+	}
+}
+
+/// Command running this very executable with the given boot arguments
+fn module_command(boot_args: &BootArgs) -> Command {
+	let mut command = Command::new(&std::env::current_exe().unwrap());
+	command.stderr(std::process::Stdio::inherit());
+
+	if let Some(ref cli_args) = boot_args.cli {
+		for arg in cli_args { command.arg(arg); }
+	}
+
+	if boot_args.clear_env {
+		command.env_clear();
+	}
+	for &(ref key, ref value) in &boot_args.env {
+		command.env(key, value);
+	}
+	if let Some(ref directory) = boot_args.current_dir {
+		command.current_dir(directory);
+	}
+
+	command.stdin(std::process::Stdio::piped());
+	boot_args.limits.apply(&mut command);
+	command
+}
+
+/// Spawns this very executable with the given boot arguments
+fn spawn_module(boot_args: &BootArgs) -> io::Result<Child> {
+	use std::io::Write;
+
+	let mut command = module_command(boot_args);
+
+	trace!(target: "hypervisor", "Spawn executable: {:?}", command);
+
+	let mut child = try!(command.spawn().map_err(
+		|e| io::Error::new(e.kind(), format!("cannot execute command ({:?}): {}", command, e))));
+
+	if let Some(ref std_in) = boot_args.stdin {
+		trace!(target: "hypervisor", "Pushing std-in payload...");
+		let written = child.stdin.as_mut()
+			.expect("std-in should be piped above")
+			.write_all(std_in);
+		drop(child.stdin.take());
+		if let Err(e) = written {
+			let _ = child.kill();
+			let _ = child.wait();
+			return Err(io::Error::new(e.kind(), format!("error trying to pipe stdin for {:?}: {}", command, e)));
+		}
+	}
+
+	Ok(child)
+}
+
+/// Watches module processes from a background thread and respawns the ones exiting unexpectedly
+struct Supervisor {
+	service: Arc<HypervisorService>,
+	processes: Arc<RwLock<HashMap<BootArgs, Child>>>,
+	modules: HashMap<IpcModuleId, BootArgs>,
+	policy: RestartPolicy,
+	restarts: Arc<Mutex<HashMap<BootArgs, usize>>>,
+	failure_handler: Option<Arc<FailureHandler>>,
+	resource_listeners: Arc<Mutex<Vec<mpsc::Sender<ResourceEvent>>>>,
+	/// Exited processes waiting for the backoff to pass
+	pending: HashMap<BootArgs, Instant>,
+	last_resource_check: Instant,
+}
+
+impl Supervisor {
+	fn run(mut self, stopping: &AtomicBool) {
+		while !stopping.load(Ordering::SeqCst) {
+			if self.last_resource_check.elapsed() >= Duration::from_millis(RESOURCE_CHECK_INTERVAL_MS) {
+				// processes killed over the hard limit are restarted below like any other exited process
+				sample_resources(&mut self.processes.write().unwrap(), &self.modules, &self.resource_listeners);
+				self.last_resource_check = Instant::now();
+			}
+			self.check_processes();
+			self.restart_pending();
+			thread::sleep(Duration::from_millis(SUPERVISE_INTERVAL_MS));
+		}
+	}
+
+	/// Removes exited processes and schedules their restart
+	fn check_processes(&mut self) {
+		let mut exited = Vec::new();
+		{
+			let mut processes = self.processes.write().unwrap();
+			for (boot_args, child) in processes.iter_mut() {
+				match child.try_wait() {
+					Ok(Some(status)) => exited.push((boot_args.clone(), status)),
+					Ok(None) => {},
+					Err(e) => warn!(target: "hypervisor", "Error checking modules {:?}: {:?}", process_modules(&self.modules, boot_args), e),
+				}
+			}
+			for &(ref boot_args, _) in &exited {
+				processes.remove(boot_args);
+			}
+		}
+
+		for (boot_args, status) in exited {
+			// the modules have to check in again once restarted
+			register_process_modules(&self.service, &self.modules, &boot_args);
+			self.schedule_restart(boot_args, &format!("exited ({})", status));
+		}
+	}
+
+	/// Schedules the restart of a process which exited or failed to respawn, unless it
+	/// has been restarted too many times already
+	fn schedule_restart(&mut self, boot_args: BootArgs, reason: &str) {
+		let module_ids = process_modules(&self.modules, &boot_args);
+		let restarts = self.restarts.lock().unwrap().get(&boot_args).cloned().unwrap_or(0);
+		if restarts >= self.policy.max_restarts {
+			warn!(target: "hypervisor", "Modules {:?} {} after {} restarts, giving up", module_ids, reason, restarts);
+			if let Some(ref handler) = self.failure_handler {
+				for module_id in module_ids {
+					handler(module_id, restarts);
+				}
+			}
+		} else {
+			warn!(target: "hypervisor", "Modules {:?} {}, restarting", module_ids, reason);
+			self.pending.insert(boot_args, Instant::now() + self.policy.backoff);
+		}
+	}
+
+	/// Respawns processes whose backoff has passed
+	fn restart_pending(&mut self) {
+		let now = Instant::now();
+		let due: Vec<BootArgs> = self.pending.iter().filter(|&(_, at)| *at <= now).map(|(boot_args, _)| boot_args.clone()).collect();
+		for boot_args in due {
+			self.pending.remove(&boot_args);
+			*self.restarts.lock().unwrap().entry(boot_args.clone()).or_insert(0) += 1;
+			let spawned = {
+				let mut processes = self.processes.write().unwrap();
+				if processes.contains_key(&boot_args) {
+					continue;
+				}
+				spawn_module(&boot_args).map(|child| { processes.insert(boot_args.clone(), child); })
+			};
+			// a failed respawn counts as a failed restart
+			if let Err(e) = spawned {
+				self.schedule_restart(boot_args, &format!("failed to restart ({})", e));
+			}
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use std::sync::atomic::{AtomicBool,Ordering};
 	use std::sync::Arc;
 	use nanoipc;
+	use semver::VersionReq;
 
 	#[test]
 	fn can_init() {
 		let url = "ipc:///tmp/test-parity-hypervisor-10.ipc";
 		let test_module_id = 8080u64;
 
-		let hypervisor = Hypervisor::with_url(url).local_module(test_module_id);
+		let hypervisor = Hypervisor::with_url(url).unwrap().local_module(test_module_id);
+		assert_eq!(false, hypervisor.modules_ready());
+	}
+
+	#[test]
+	fn binds_listener_on_init() {
+		let path = "/tmp/test-parity-hypervisor-70.ipc";
+		let url = "ipc:///tmp/test-parity-hypervisor-70.ipc";
+		let test_module_id = 8080u64;
+		let _ = ::std::fs::remove_file(path);
+
+		let hypervisor = Hypervisor::with_url(url).unwrap().local_module(test_module_id);
+		assert!(::std::path::Path::new(path).exists());
+
+		hypervisor.start();
+		assert!(hypervisor.processes.read().unwrap().is_empty());
 		assert_eq!(false, hypervisor.modules_ready());
 	}
 
+	#[test]
+	fn fails_to_listen_on_unusable_address() {
+		let url = "ipc:///tmp/test-parity-hypervisor-missing-dir/hypervisor.ipc";
+
+		match Hypervisor::with_url(url) {
+			Err(HypervisorError::Listen(addr, _)) => assert_eq!(addr, url),
+			Err(e) => panic!("Expected listen error, got {}", e),
+			Ok(_) => panic!("Expected listen error"),
+		}
+	}
+
 	#[test]
 	fn can_wait_for_startup() {
 		let url = "ipc:///tmp/test-parity-hypervisor-20.ipc";
@@ -240,16 +780,412 @@ mod tests {
 		::std::thread::spawn(move || {
 			while !hypervisor_ready.load(Ordering::Relaxed) { }
 
+			// check in only after the hypervisor started waiting
+			::std::thread::sleep(::std::time::Duration::from_millis(100));
 			let client = nanoipc::fast_client::<HypervisorServiceClient<_>>(url).unwrap();
 			client.handshake().unwrap();
-			client.module_ready(test_module_id);
+			client.module_ready(test_module_id, String::new(), "1.4.0".to_owned());
 		});
 
-		let hypervisor = Hypervisor::with_url(url).local_module(test_module_id);
+		let hypervisor = Hypervisor::with_url(url).unwrap().local_module(test_module_id);
 		hypervisor.start();
 		hypervisor_ready_local.store(true, Ordering::Relaxed);
-		hypervisor.wait_for_startup();
+		hypervisor.wait_for_startup(::std::time::Duration::from_secs(10)).unwrap();
 
 		assert_eq!(true, hypervisor.modules_ready());
 	}
+
+	#[test]
+	fn serves_modules_after_start() {
+		let url = "ipc:///tmp/test-parity-hypervisor-130.ipc";
+		let test_module_id = 8150u64;
+
+		let hypervisor = Hypervisor::with_url(url).unwrap().local_module(test_module_id);
+		hypervisor.start();
+
+		// nobody waits for startup, the check-in is served by the polling thread
+		::std::thread::spawn(move || {
+			let client = nanoipc::fast_client::<HypervisorServiceClient<_>>(url).unwrap();
+			client.handshake().unwrap();
+			client.module_ready(test_module_id, String::new(), "1.4.0".to_owned());
+			client.module_shutdown(test_module_id);
+		});
+
+		for _ in 0..200 {
+			if hypervisor.modules_ready() && hypervisor.modules_shutdown() {
+				break;
+			}
+			::std::thread::sleep(::std::time::Duration::from_millis(50));
+		}
+
+		assert_eq!(true, hypervisor.modules_ready());
+		assert_eq!(true, hypervisor.modules_shutdown());
+		hypervisor.shutdown();
+		assert!(hypervisor.poller.lock().unwrap().is_none());
+	}
+
+	#[test]
+	fn times_out_waiting_for_startup() {
+		let url = "ipc:///tmp/test-parity-hypervisor-80.ipc";
+		let test_module_id = 8080u64;
+
+		let hypervisor = Hypervisor::with_url(url).unwrap().local_module(test_module_id);
+		hypervisor.start();
+
+		assert_eq!(
+			hypervisor.wait_for_startup(::std::time::Duration::from_millis(300)),
+			Err(HypervisorError::StartupTimeout(vec![test_module_id]))
+		);
+		assert_eq!(false, hypervisor.modules_ready());
+	}
+
+	#[test]
+	fn notifies_startup_events_in_order() {
+		let url = "ipc:///tmp/test-parity-hypervisor-30.ipc";
+		let first_module_id = 8080u64;
+		let second_module_id = 8081u64;
+
+		let hypervisor_ready = Arc::new(AtomicBool::new(false));
+		let hypervisor_ready_local = hypervisor_ready.clone();
+
+		::std::thread::spawn(move || {
+			while !hypervisor_ready.load(Ordering::Relaxed) { }
+
+			let client = nanoipc::fast_client::<HypervisorServiceClient<_>>(url).unwrap();
+			client.handshake().unwrap();
+			client.module_ready(first_module_id, String::new(), "1.4.0".to_owned());
+			client.module_ready(second_module_id, String::new(), "1.4.0".to_owned());
+			client.module_shutdown(first_module_id);
+			client.module_shutdown(second_module_id);
+		});
+
+		let hypervisor = Hypervisor::with_url(url).unwrap().local_module(first_module_id).local_module(second_module_id);
+		let events = hypervisor.startup_notification();
+		hypervisor.start();
+		hypervisor_ready_local.store(true, Ordering::Relaxed);
+		hypervisor.wait_for_startup(::std::time::Duration::from_secs(10)).unwrap();
+
+		assert_eq!(events.try_recv(), Ok(StartupEvent::ModuleCheckedIn(first_module_id)));
+		assert_eq!(events.try_recv(), Ok(StartupEvent::ModuleCheckedIn(second_module_id)));
+		assert_eq!(events.try_recv(), Ok(StartupEvent::AllModulesReady));
+		assert!(events.try_recv().is_err());
+	}
+
+	#[test]
+	fn skips_shutdown_of_modules_not_checked_in() {
+		let url = "ipc:///tmp/test-parity-hypervisor-120.ipc";
+		let test_module_id = 8140u64;
+
+		let hypervisor = Hypervisor::with_url(url).unwrap().local_module(test_module_id);
+		hypervisor.start();
+
+		assert!(!hypervisor.service.send_shutdown(test_module_id));
+		assert!(!hypervisor.service.send_shutdown(test_module_id + 1));
+	}
+
+	#[test]
+	fn refuses_incompatible_module_version() {
+		let url = "ipc:///tmp/test-parity-hypervisor-50.ipc";
+		let old_module_id = 8080u64;
+		let current_module_id = 8081u64;
+
+		let hypervisor = Hypervisor::with_url(url).unwrap()
+			.local_module(old_module_id)
+			.local_module(current_module_id)
+			.required_version(VersionReq::parse("^1.4").unwrap());
+		hypervisor.start();
+
+		::std::thread::spawn(move || {
+			let client = nanoipc::fast_client::<HypervisorServiceClient<_>>(url).unwrap();
+			client.handshake().unwrap();
+			assert!(!client.module_ready(old_module_id, String::new(), "1.3.2".to_owned()));
+			assert!(client.module_ready(current_module_id, String::new(), "1.4.1".to_owned()));
+			client.module_shutdown(current_module_id);
+		});
+
+		while hypervisor.incompatible_modules().is_empty() || hypervisor.service.unchecked_count() != 1 {
+			hypervisor.poll_ipc();
+		}
+
+		assert_eq!(hypervisor.incompatible_modules(), vec![(old_module_id, "1.3.2".to_owned())]);
+		assert_eq!(false, hypervisor.modules_ready());
+	}
+
+	/// Module run in a thread, recording the logging levels it is sent
+	#[derive(Default)]
+	struct LoggingModule {
+		levels: Mutex<Vec<String>>,
+		stop: AtomicBool,
+	}
+
+	impl ControlService for LoggingModule {
+		fn shutdown(&self) -> bool {
+			true
+		}
+
+		fn set_logging(&self, levels: String) -> bool {
+			self.levels.lock().unwrap().push(levels);
+			true
+		}
+	}
+
+	fn run_logging_module(url: &'static str, control_url: &'static str, module_id: u64) -> Arc<LoggingModule> {
+		let module = Arc::new(LoggingModule::default());
+		let control = module.clone();
+		::std::thread::spawn(move || {
+			let service = control.clone() as Arc<ControlService>;
+			let mut worker = nanoipc::Worker::<ControlService>::new(&service);
+			worker.add_reqrep(control_url).unwrap();
+
+			let client = nanoipc::fast_client::<HypervisorServiceClient<_>>(url).unwrap();
+			client.handshake().unwrap();
+			client.module_ready(module_id, control_url.to_owned(), "1.4.0".to_owned());
+			while !control.stop.load(Ordering::SeqCst) {
+				worker.poll();
+			}
+			client.module_shutdown(module_id);
+		});
+		module
+	}
+
+	#[test]
+	fn changes_module_logging() {
+		let url = "ipc:///tmp/test-parity-hypervisor-110.ipc";
+
+		let hypervisor = Hypervisor::with_url(url).unwrap().local_module(8130).local_module(8131);
+		hypervisor.start();
+		let first = run_logging_module(url, "ipc:///tmp/test-parity-hypervisor-111.ipc", 8130);
+		let second = run_logging_module(url, "ipc:///tmp/test-parity-hypervisor-112.ipc", 8131);
+		hypervisor.wait_for_startup(::std::time::Duration::from_secs(10)).unwrap();
+
+		assert!(hypervisor.set_module_logging(8130, "sync=debug"));
+		assert!(!hypervisor.set_module_logging(8132, "debug"));
+		hypervisor.logging_control().set_logging("");
+
+		assert_eq!(*first.levels.lock().unwrap(), vec!["sync=debug".to_owned(), String::new()]);
+		assert_eq!(*second.levels.lock().unwrap(), vec![String::new()]);
+
+		first.stop.store(true, Ordering::SeqCst);
+		second.stop.store(true, Ordering::SeqCst);
+	}
+
+	/// Helper run as a module process by `reports_module_over_memory_limit`.
+	#[test]
+	#[ignore]
+	fn memory_hungry_module() {
+		let memory = vec![1u8; 64 * 1024 * 1024];
+		::std::thread::sleep(::std::time::Duration::from_secs(10));
+		assert_eq!(memory[0], 1);
+	}
+
+	/// Helper run as a module process by `spawns_one_process_per_binary`.
+	#[test]
+	#[ignore]
+	fn idle_module() {
+		::std::thread::sleep(::std::time::Duration::from_secs(10));
+	}
+
+	#[test]
+	fn spawns_one_process_per_binary() {
+		let url = "ipc:///tmp/test-parity-hypervisor-90.ipc";
+		let idle = || BootArgs::new().cli(vec!["idle_module".to_owned(), "--ignored".to_owned()]);
+
+		let hypervisor = Hypervisor::with_url(url).unwrap()
+			.module(8110, idle())
+			.module(8111, idle().env("PARITY_TEST_INSTANCE", "2"))
+			.module(8112, idle())
+			.local_module(8113);
+		hypervisor.start();
+		hypervisor.start_module(8112);
+
+		{
+			let mut processes = hypervisor.processes.write().unwrap();
+			assert_eq!(processes.len(), 2);
+			assert!(processes.contains_key(&idle()));
+			let mut served: Vec<_> = processes.keys().map(|boot_args| super::process_modules(&hypervisor.modules, boot_args)).collect();
+			served.sort();
+			assert_eq!(served, vec![vec![8110, 8112], vec![8111]]);
+
+			// the modules never check in, so stop them here rather than on drop
+			for (_, mut child) in processes.drain() {
+				child.kill().unwrap();
+				child.wait().unwrap();
+			}
+		}
+
+		assert_eq!(hypervisor.service.unchecked_count(), 4);
+	}
+
+	/// Helper run as a module process by `passes_environment_and_working_directory`.
+	#[test]
+	#[ignore]
+	fn echo_env_module() {
+		println!("echo: {}", ::std::env::var("PARITY_TEST_ECHO").unwrap_or(String::new()));
+		println!("inherited: {}", ::std::env::var("PARITY_TEST_INHERITED").is_ok());
+		println!("dir: {}", ::std::env::current_dir().unwrap().display());
+	}
+
+	#[test]
+	fn passes_environment_and_working_directory() {
+		use std::process::Stdio;
+
+		let run = |args: BootArgs| {
+			let args = args.cli(vec!["echo_env_module".to_owned(), "--ignored".to_owned(), "--nocapture".to_owned()]);
+			let output = super::module_command(&args).stdin(Stdio::null()).stderr(Stdio::null()).output().unwrap();
+			String::from_utf8(output.stdout).unwrap()
+		};
+
+		::std::env::set_var("PARITY_TEST_INHERITED", "1");
+		let dir = ::std::env::temp_dir().canonicalize().unwrap();
+
+		let output = run(BootArgs::new().env("PARITY_TEST_ECHO", "hello").current_dir(dir.to_str().unwrap()));
+		assert!(output.contains("echo: hello\n"), "{}", output);
+		assert!(output.contains("inherited: true\n"), "{}", output);
+		assert!(output.contains(&format!("dir: {}\n", dir.display())), "{}", output);
+
+		let output = run(BootArgs::new().env("PARITY_TEST_ECHO", "isolated").clear_env());
+		assert!(output.contains("echo: isolated\n"), "{}", output);
+		assert!(output.contains("inherited: false\n"), "{}", output);
+	}
+
+	/// Helper run as a module process by `restarts_exited_module`.
+	#[test]
+	#[ignore]
+	fn exiting_module() {
+	}
+
+	/// Helper run as a module process by `kills_module_ignoring_shutdown`.
+	#[cfg(unix)]
+	#[test]
+	#[ignore]
+	fn stubborn_module() {
+		unsafe { ::libc::signal(::libc::SIGTERM, ::libc::SIG_IGN); }
+		::std::thread::sleep(::std::time::Duration::from_secs(30));
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn kills_module_ignoring_shutdown() {
+		use std::time::{Duration, Instant};
+
+		let url = "ipc:///tmp/test-parity-hypervisor-100.ipc";
+		let test_module_id = 8120u64;
+		let args = BootArgs::new().cli(vec!["stubborn_module".to_owned(), "--ignored".to_owned()]);
+
+		let hypervisor = Hypervisor::with_url(url).unwrap()
+			.module(test_module_id, args)
+			.shutdown_timeout(Duration::from_millis(300));
+		hypervisor.start();
+		let pid = hypervisor.processes.read().unwrap().values().next().unwrap().id();
+
+		let started = Instant::now();
+		hypervisor.shutdown();
+
+		// one timeout for the shutdown request and one after SIGTERM
+		assert!(started.elapsed() < Duration::from_secs(5));
+		assert!(hypervisor.processes.read().unwrap().is_empty());
+		assert!(unsafe { ::libc::kill(pid as ::libc::pid_t, 0) } != 0);
+	}
+
+	#[test]
+	fn restarts_exited_module() {
+		use std::time::Duration;
+
+		let url = "ipc:///tmp/test-parity-hypervisor-60.ipc";
+		let test_module_id = 8100u64;
+		let args = BootArgs::new().cli(vec!["exiting_module".to_owned(), "--ignored".to_owned()]);
+
+		let failures = Arc::new(Mutex::new(Vec::new()));
+		let failures_local = failures.clone();
+		let hypervisor = Hypervisor::with_url(url).unwrap()
+			.module(test_module_id, args)
+			.restart_policy(2, Duration::from_millis(10))
+			.on_module_failure(move |module_id, restarts| failures_local.lock().unwrap().push((module_id, restarts)));
+		hypervisor.start();
+
+		for _ in 0..200 {
+			if !failures.lock().unwrap().is_empty() {
+				break;
+			}
+			::std::thread::sleep(Duration::from_millis(50));
+		}
+
+		assert_eq!(*failures.lock().unwrap(), vec![(test_module_id, 2)]);
+		assert_eq!(hypervisor.restart_count(test_module_id), 2);
+		assert!(hypervisor.processes.read().unwrap().is_empty());
+		assert_eq!(false, hypervisor.modules_ready());
+	}
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn supervisor_checks_module_resources() {
+		use std::time::Duration;
+
+		let url = "ipc:///tmp/test-parity-hypervisor-140.ipc";
+		let test_module_id = 8160u64;
+		let limits = ResourceLimits { soft_memory_bytes: Some(16 * 1024 * 1024), ..Default::default() };
+		let args = BootArgs::new()
+			.cli(vec!["memory_hungry_module".to_owned(), "--ignored".to_owned()])
+			.limits(limits);
+
+		let hypervisor = Hypervisor::with_url(url).unwrap()
+			.module(test_module_id, args)
+			.shutdown_timeout(Duration::from_millis(300));
+		let events = hypervisor.resource_notification();
+		hypervisor.start();
+
+		let mut event = None;
+		for _ in 0..200 {
+			if let Ok(e) = events.try_recv() {
+				event = Some(e);
+				break;
+			}
+			::std::thread::sleep(Duration::from_millis(50));
+		}
+
+		match event {
+			Some(ResourceEvent::SoftMemoryLimit(module_id, _)) => assert_eq!(module_id, test_module_id),
+			e => panic!("Expected soft memory limit event, got {:?}", e),
+		}
+	}
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn reports_module_over_memory_limit() {
+		use std::time::Duration;
+
+		let url = "ipc:///tmp/test-parity-hypervisor-40.ipc";
+		let test_module_id = 8090u64;
+		let limits = ResourceLimits { soft_memory_bytes: Some(16 * 1024 * 1024), ..Default::default() };
+		let args = BootArgs::new()
+			.cli(vec!["memory_hungry_module".to_owned(), "--ignored".to_owned()])
+			.limits(limits);
+
+		let hypervisor = Hypervisor::with_url(url).unwrap().module(test_module_id, args);
+		let events = hypervisor.resource_notification();
+		hypervisor.start_module(test_module_id);
+
+		let mut event = None;
+		for _ in 0..100 {
+			hypervisor.check_resources();
+			if let Ok(e) = events.try_recv() {
+				event = Some(e);
+				break;
+			}
+			::std::thread::sleep(Duration::from_millis(50));
+		}
+
+		// the module never checks in, so stop it here rather than on drop
+		let mut child = hypervisor.processes.write().unwrap().remove(&hypervisor.modules[&test_module_id]).unwrap();
+		child.kill().unwrap();
+		child.wait().unwrap();
+
+		match event {
+			Some(ResourceEvent::SoftMemoryLimit(module_id, rss)) => {
+				assert_eq!(module_id, test_module_id);
+				assert!(rss > 16 * 1024 * 1024);
+			},
+			e => panic!("Expected soft memory limit event, got {:?}", e),
+		}
+	}
 }
@@ -0,0 +1,146 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Resource limits of module processes
+
+use std::process::Command;
+use service::IpcModuleId;
+
+/// Resource limits applied to a module process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ResourceLimits {
+	/// Resident memory above which a warning event is emitted
+	pub soft_memory_bytes: Option<u64>,
+	/// Resident memory above which the module is restarted, also the size limit of its data segment
+	pub max_memory_bytes: Option<u64>,
+	/// Maximum number of file descriptors the process can open
+	pub max_open_files: Option<u64>,
+}
+
+/// Events emitted while supervising module resources
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResourceEvent {
+	/// Module resident memory exceeds its soft limit
+	SoftMemoryLimit(IpcModuleId, u64),
+	/// Module resident memory exceeds its hard limit, the module is being restarted
+	HardMemoryLimit(IpcModuleId, u64),
+}
+
+impl ResourceLimits {
+	/// Check if no limit is set
+	pub fn is_empty(&self) -> bool {
+		*self == ResourceLimits::default()
+	}
+
+	/// Event to be reported for the module with the given resident memory
+	pub fn check_memory(&self, module_id: IpcModuleId, rss_bytes: u64) -> Option<ResourceEvent> {
+		if self.max_memory_bytes.map_or(false, |max| rss_bytes > max) {
+			Some(ResourceEvent::HardMemoryLimit(module_id, rss_bytes))
+		} else if self.soft_memory_bytes.map_or(false, |soft| rss_bytes > soft) {
+			Some(ResourceEvent::SoftMemoryLimit(module_id, rss_bytes))
+		} else {
+			None
+		}
+	}
+
+	/// Make the command apply the limits to the spawned process
+	#[cfg(unix)]
+	pub fn apply(&self, command: &mut Command) {
+		use std::os::unix::process::CommandExt;
+
+		let limits = *self;
+		command.before_exec(move || set_limits(&limits));
+	}
+
+	/// Make the command apply the limits to the spawned process
+	#[cfg(not(unix))]
+	pub fn apply(&self, _command: &mut Command) {
+		if !self.is_empty() {
+			warn!(target: "hypervisor", "Module resource limits are not supported on this platform");
+		}
+	}
+}
+
+#[cfg(unix)]
+fn set_limits(limits: &ResourceLimits) -> ::std::io::Result<()> {
+	use std::io;
+	use libc;
+
+	fn rlimit(value: u64) -> libc::rlimit {
+		libc::rlimit { rlim_cur: value as libc::rlim_t, rlim_max: value as libc::rlim_t }
+	}
+
+	// the hard memory limit also caps the data segment, so a module allocating faster than the supervisor
+	// samples its resident memory fails to allocate and exits, and is then restarted by the supervisor.
+	if let Some(memory) = limits.max_memory_bytes {
+		if unsafe { libc::setrlimit(libc::RLIMIT_DATA, &rlimit(memory)) } != 0 {
+			return Err(io::Error::last_os_error());
+		}
+	}
+	if let Some(files) = limits.max_open_files {
+		if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &rlimit(files)) } != 0 {
+			return Err(io::Error::last_os_error());
+		}
+	}
+	Ok(())
+}
+
+/// Resident memory of the process in bytes
+#[cfg(target_os = "linux")]
+pub fn resident_memory(pid: u32) -> Option<u64> {
+	use std::fs::File;
+	use std::io::Read;
+	use libc;
+
+	let mut statm = String::new();
+	if File::open(format!("/proc/{}/statm", pid)).and_then(|mut f| f.read_to_string(&mut statm)).is_err() {
+		return None;
+	}
+	let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+	statm.split_whitespace().nth(1).and_then(|pages| pages.parse::<u64>().ok()).map(|pages| pages * page_size)
+}
+
+/// Resident memory of the process in bytes. Unknown on this platform.
+#[cfg(not(target_os = "linux"))]
+pub fn resident_memory(_pid: u32) -> Option<u64> {
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ResourceLimits, ResourceEvent};
+
+	#[test]
+	fn memory_limits() {
+		let limits = ResourceLimits {
+			soft_memory_bytes: Some(100),
+			max_memory_bytes: Some(200),
+			max_open_files: None,
+		};
+		assert_eq!(limits.check_memory(1, 100), None);
+		assert_eq!(limits.check_memory(1, 101), Some(ResourceEvent::SoftMemoryLimit(1, 101)));
+		assert_eq!(limits.check_memory(1, 201), Some(ResourceEvent::HardMemoryLimit(1, 201)));
+		assert_eq!(ResourceLimits::default().check_memory(1, u64::max_value()), None);
+	}
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn reads_own_resident_memory() {
+		let pid = unsafe { ::libc::getpid() } as u32;
+		let rss = super::resident_memory(pid).unwrap();
+		assert!(rss > 0);
+	}
+}
@@ -25,22 +25,31 @@ extern crate env_logger;
 extern crate time;
 #[macro_use]
 extern crate lazy_static;
+#[cfg(test)]
+extern crate ethcore_devtools as devtools;
+
+mod rotate;
 
 use std::{env, thread};
 use std::sync::Arc;
-use std::fs::File;
-use std::io::Write;
+use std::path::Path;
 use isatty::{stderr_isatty, stdout_isatty};
-use env_logger::LogBuilder;
+use env_logger::{LogBuilder, Logger};
+use rlog::{Log, LogRecord, LogMetadata, MaxLogLevelFilter};
 use regex::Regex;
-use util::RotatingLogger;
+use util::{RotatingLogger, Mutex, RwLock};
 use util::log::Colour;
+use rotate::RotatingFile;
+
+pub use rotate::Rotation;
 
 #[derive(Debug, PartialEq)]
 pub struct Config {
 	pub mode: Option<String>,
 	pub color: bool,
 	pub file: Option<String>,
+	pub rotate: Rotation,
+	pub keep: usize,
 }
 
 impl Default for Config {
@@ -49,15 +58,56 @@ impl Default for Config {
 			mode: None,
 			color: !cfg!(windows),
 			file: None,
+			rotate: Rotation::Never,
+			keep: 5,
 		}
 	}
 }
 
-/// Sets up the logger
-pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
-	use rlog::*;
+type Format = Fn(&LogRecord) -> String + Sync + Send;
+
+/// Logger delegating to an `env_logger` which can be replaced to change the levels at runtime
+struct ReloadableLogger(Arc<RwLock<Logger>>);
+
+impl Log for ReloadableLogger {
+	fn enabled(&self, metadata: &LogMetadata) -> bool {
+		self.0.read().enabled(metadata)
+	}
+
+	fn log(&self, record: &LogRecord) {
+		self.0.read().log(record)
+	}
+}
 
+/// State needed to rebuild the logger with other levels
+struct LogControl {
+	logger: Arc<RwLock<Logger>>,
+	max_level: MaxLogLevelFilter,
+	format: Arc<Format>,
+	mode: Option<String>,
+}
+
+lazy_static! {
+	static ref LOG_CONTROL: Mutex<Option<LogControl>> = Mutex::new(None);
+}
+
+/// Levels from `RUST_LOG` followed by the given ones
+fn log_levels(mode: Option<&str>) -> String {
 	let mut levels = String::new();
+	if let Ok(lvl) = env::var("RUST_LOG") {
+		levels.push_str(&lvl);
+		levels.push_str(",");
+	}
+	if let Some(s) = mode {
+		levels.push_str(s);
+	}
+	levels
+}
+
+/// Builds a logger with the default levels, the ones from `RUST_LOG` and then the given ones
+fn build_logger(mode: Option<&str>, format: Arc<Format>) -> Logger {
+	use rlog::LogLevelFilter;
+
 	let mut builder = LogBuilder::new();
 	// Disable ws info logging by default.
 	builder.filter(Some("ws"), LogLevelFilter::Warn);
@@ -65,25 +115,42 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 	builder.filter(Some("rustls"), LogLevelFilter::Warn);
 	builder.filter(None, LogLevelFilter::Info);
 
-	if env::var("RUST_LOG").is_ok() {
-		let lvl = &env::var("RUST_LOG").unwrap();
-		levels.push_str(lvl);
-		levels.push_str(",");
-		builder.parse(lvl);
+	if let Ok(lvl) = env::var("RUST_LOG") {
+		builder.parse(&lvl);
 	}
 
-	if let Some(ref s) = config.mode {
-		levels.push_str(s);
+	if let Some(s) = mode {
 		builder.parse(s);
 	}
 
+	builder.format(move |record: &LogRecord| format(record));
+	builder.build()
+}
+
+/// Replaces the levels given in the logger config with the given ones, in the `RUST_LOG` format.
+/// `None` restores the levels from the config.
+pub fn set_log_levels(mode: Option<&str>) -> Result<(), String> {
+	let control = LOG_CONTROL.lock();
+	let control = try!(control.as_ref().ok_or_else(|| "Logger is not set up".to_owned()));
+	let mode = mode.or(control.mode.as_ref().map(|s| s.as_str()));
+	let logger = build_logger(mode, control.format.clone());
+	control.max_level.set(logger.filter());
+	*control.logger.write() = logger;
+	Ok(())
+}
+
+/// Sets up the logger
+pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
+	use rlog::*;
+
 	let isatty = stderr_isatty();
 	let enable_color = config.color && isatty;
-	let logs = Arc::new(RotatingLogger::new(levels));
+	let logs = Arc::new(RotatingLogger::new(log_levels(config.mode.as_ref().map(|s| s.as_str()))));
 	let logger = logs.clone();
 
 	let maybe_file = match config.file.as_ref() {
-		Some(f) => Some(try!(File::create(f).map_err(|_| format!("Cannot write to log file given: {}", f)))),
+		Some(f) => Some(Mutex::new(try!(RotatingFile::open(Path::new(f), config.rotate, config.keep)
+			.map_err(|_| format!("Cannot write to log file given: {}", f))))),
 		None => None,
 	};
 
@@ -104,10 +171,9 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 			false => removed_color.clone(),
 		};
 
-		if let Some(mut file) = maybe_file.as_ref() {
+		if let Some(ref file) = maybe_file {
 			// ignore errors - there's nothing we can do
-			let _ = file.write_all(removed_color.as_bytes());
-			let _ = file.write_all(b"\n");
+			let _ = file.lock().write_line(&removed_color);
 		}
 		logger.append(removed_color);
 		if !isatty && record.level() <= LogLevel::Info && stdout_isatty() {
@@ -118,8 +184,20 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		ret
     };
 
-	builder.format(format);
-	builder.init().unwrap();
+	let format = Arc::new(format) as Arc<Format>;
+	let env_logger = build_logger(config.mode.as_ref().map(|s| s.as_str()), format.clone());
+	let env_logger = Arc::new(RwLock::new(env_logger));
+	let mode = config.mode.clone();
+	set_logger(|max_level| {
+		max_level.set(env_logger.read().filter());
+		*LOG_CONTROL.lock() = Some(LogControl {
+			logger: env_logger.clone(),
+			max_level: max_level,
+			format: format,
+			mode: mode,
+		});
+		Box::new(ReloadableLogger(env_logger))
+	}).unwrap();
 
 	Ok(logs)
 }
@@ -131,6 +209,25 @@ fn kill_color(s: &str) -> String {
 	RE.replace_all(s, "")
 }
 
+#[test]
+fn should_reload_log_levels() {
+	use rlog::{max_log_level, LogLevel, LogLevelFilter};
+
+	env::remove_var("RUST_LOG");
+	let config = Config { mode: Some("warn".into()), ..Default::default() };
+	setup_log(&config).unwrap();
+	assert_eq!(max_log_level(), LogLevelFilter::Warn);
+	assert!(!log_enabled!(LogLevel::Info));
+
+	set_log_levels(Some("debug")).unwrap();
+	assert_eq!(max_log_level(), LogLevelFilter::Debug);
+	assert!(log_enabled!(LogLevel::Debug));
+
+	set_log_levels(None).unwrap();
+	assert_eq!(max_log_level(), LogLevelFilter::Warn);
+	assert!(!log_enabled!(LogLevel::Info));
+}
+
 #[test]
 fn should_remove_colour() {
 	let before = "test";
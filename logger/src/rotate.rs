@@ -0,0 +1,236 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Log file rotation.
+
+use std::cmp;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use time;
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// When the log file is rotated.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Rotation {
+	/// The file grows forever.
+	Never,
+	/// Once the file would exceed given number of bytes.
+	Size(u64),
+	/// At UTC midnight.
+	Daily,
+}
+
+impl Default for Rotation {
+	fn default() -> Self {
+		Rotation::Never
+	}
+}
+
+impl FromStr for Rotation {
+	type Err = String;
+
+	/// Parses `never`, `daily` or a size in bytes with an optional `KB`, `MB` or `GB` suffix.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let multiplier = |suffix: &str| match suffix {
+			"" => Some(1),
+			"KB" => Some(1024),
+			"MB" => Some(1024 * 1024),
+			"GB" => Some(1024 * 1024 * 1024),
+			_ => None,
+		};
+
+		match s {
+			"never" => Ok(Rotation::Never),
+			"daily" => Ok(Rotation::Daily),
+			_ => {
+				let split = s.find(|c: char| !c.is_digit(10)).unwrap_or(s.len());
+				let (size, suffix) = s.split_at(split);
+				let bytes = match (size.parse::<u64>(), multiplier(&suffix.to_uppercase())) {
+					(Ok(size), Some(multiplier)) if size > 0 => size.checked_mul(multiplier),
+					_ => None,
+				};
+				match bytes {
+					Some(bytes) => Ok(Rotation::Size(bytes)),
+					None => Err(format!("Invalid log rotation: {}. Expected never, daily or a size like 100MB.", s)),
+				}
+			},
+		}
+	}
+}
+
+/// Log file which is renamed to `<path>.1` (shifting older files to `<path>.2` and so on) and reopened
+/// according to the rotation policy. At most `keep` rotated files are retained.
+pub struct RotatingFile {
+	path: PathBuf,
+	file: File,
+	rotation: Rotation,
+	keep: usize,
+	size: u64,
+	day: i64,
+}
+
+impl RotatingFile {
+	/// Opens the file at given path for appending.
+	pub fn open(path: &Path, rotation: Rotation, keep: usize) -> io::Result<Self> {
+		let file = try!(open_append(path));
+		let size = try!(file.metadata()).len();
+		Ok(RotatingFile {
+			path: path.to_owned(),
+			file: file,
+			rotation: rotation,
+			keep: keep,
+			size: size,
+			day: time::get_time().sec / SECS_PER_DAY,
+		})
+	}
+
+	/// Writes a line, rotating the file first if required.
+	pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+		self.write_line_at(line, time::get_time().sec)
+	}
+
+	fn write_line_at(&mut self, line: &str, now: i64) -> io::Result<()> {
+		let len = line.len() as u64 + 1;
+		let day = now / SECS_PER_DAY;
+		let rotate = match self.rotation {
+			Rotation::Never => false,
+			Rotation::Size(max) => self.size > 0 && self.size + len > max,
+			Rotation::Daily => day != self.day,
+		};
+
+		if rotate {
+			try!(self.rotate());
+		}
+		self.day = day;
+
+		try!(self.file.write_all(line.as_bytes()));
+		try!(self.file.write_all(b"\n"));
+		self.size += len;
+		Ok(())
+	}
+
+	fn rotate(&mut self) -> io::Result<()> {
+		// prune files beyond the retention limit, including leftovers of a larger limit.
+		let mut n = cmp::max(self.keep, 1);
+		while self.rotated(n).exists() {
+			try!(fs::remove_file(self.rotated(n)));
+			n += 1;
+		}
+
+		for n in (1..self.keep).rev() {
+			let from = self.rotated(n);
+			if from.exists() {
+				try!(fs::rename(from, self.rotated(n + 1)));
+			}
+		}
+
+		match self.keep {
+			0 => try!(fs::remove_file(&self.path)),
+			_ => try!(fs::rename(&self.path, self.rotated(1))),
+		}
+
+		self.file = try!(open_append(&self.path));
+		self.size = 0;
+		Ok(())
+	}
+
+	fn rotated(&self, n: usize) -> PathBuf {
+		let mut name = self.path.as_os_str().to_owned();
+		name.push(format!(".{}", n));
+		PathBuf::from(name)
+	}
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+	OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::File;
+	use std::io::Read;
+	use std::path::Path;
+	use devtools::RandomTempPath;
+	use super::{Rotation, RotatingFile, SECS_PER_DAY};
+
+	fn read(path: &Path) -> String {
+		let mut s = String::new();
+		File::open(path).unwrap().read_to_string(&mut s).unwrap();
+		s
+	}
+
+	#[test]
+	fn parses_rotation() {
+		assert_eq!("never".parse(), Ok(Rotation::Never));
+		assert_eq!("daily".parse(), Ok(Rotation::Daily));
+		assert_eq!("4096".parse(), Ok(Rotation::Size(4096)));
+		assert_eq!("10kb".parse(), Ok(Rotation::Size(10 * 1024)));
+		assert_eq!("100MB".parse(), Ok(Rotation::Size(100 * 1024 * 1024)));
+		assert!("0".parse::<Rotation>().is_err());
+		assert!("10TB".parse::<Rotation>().is_err());
+		assert!("18446744073709551615GB".parse::<Rotation>().is_err());
+		assert!("weekly".parse::<Rotation>().is_err());
+	}
+
+	#[test]
+	fn rotates_by_size_and_prunes_old_files() {
+		let dir = RandomTempPath::create_dir();
+		let path = dir.as_path().join("parity.log");
+		let mut file = RotatingFile::open(&path, Rotation::Size(10), 2).unwrap();
+
+		// each line takes 5 bytes with the newline, so two fit into a file.
+		for line in &["aaaa", "bbbb", "cccc", "dddd", "eeee", "ffff", "gggg"] {
+			file.write_line(line).unwrap();
+		}
+
+		assert_eq!(read(&path), "gggg\n");
+		assert_eq!(read(&dir.as_path().join("parity.log.1")), "eeee\nffff\n");
+		assert_eq!(read(&dir.as_path().join("parity.log.2")), "cccc\ndddd\n");
+		assert!(!dir.as_path().join("parity.log.3").exists());
+	}
+
+	#[test]
+	fn rotates_daily() {
+		let dir = RandomTempPath::create_dir();
+		let path = dir.as_path().join("parity.log");
+		let mut file = RotatingFile::open(&path, Rotation::Daily, 1).unwrap();
+		let today = file.day * SECS_PER_DAY;
+
+		file.write_line_at("morning", today + 1).unwrap();
+		file.write_line_at("evening", today + SECS_PER_DAY - 1).unwrap();
+		file.write_line_at("midnight", today + SECS_PER_DAY).unwrap();
+
+		assert_eq!(read(&path), "midnight\n");
+		assert_eq!(read(&dir.as_path().join("parity.log.1")), "morning\nevening\n");
+	}
+
+	#[test]
+	fn never_rotates_and_appends_to_existing_file() {
+		let dir = RandomTempPath::create_dir();
+		let path = dir.as_path().join("parity.log");
+		RotatingFile::open(&path, Rotation::Never, 1).unwrap().write_line("first").unwrap();
+
+		let mut file = RotatingFile::open(&path, Rotation::Never, 1).unwrap();
+		let later = (file.day + 10) * SECS_PER_DAY;
+		file.write_line_at("second", later).unwrap();
+
+		assert_eq!(read(&path), "first\nsecond\n");
+		assert!(!dir.as_path().join("parity.log.1").exists());
+	}
+}
@@ -14,6 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use util::Address;
 use ethcore::ethstore::{EthStore, import_accounts};
 use ethcore::ethstore::dir::DiskDirectory;
 use ethcore::account_provider::AccountProvider;
@@ -24,6 +28,7 @@ pub enum AccountCmd {
 	New(NewAccount),
 	List(String),
 	Import(ImportAccounts),
+	Export(ExportAccount),
 }
 
 #[derive(Debug, PartialEq)]
@@ -39,11 +44,23 @@ pub struct ImportAccounts {
 	pub to: String,
 }
 
+/// Exports a single account as encrypted keystore JSON.
+#[derive(Debug, PartialEq)]
+pub struct ExportAccount {
+	pub iterations: u32,
+	pub path: String,
+	pub address: Address,
+	pub password_file: Option<String>,
+	/// Output file, stdout if `None`.
+	pub file: Option<String>,
+}
+
 pub fn execute(cmd: AccountCmd) -> Result<String, String> {
 	match cmd {
 		AccountCmd::New(new_cmd) => new(new_cmd),
 		AccountCmd::List(path) => list(path),
 		AccountCmd::Import(import_cmd) => import(import_cmd),
+		AccountCmd::Export(export_cmd) => export(export_cmd),
 	}
 }
 
@@ -86,3 +103,43 @@ fn import(i: ImportAccounts) -> Result<String, String> {
 	}
 	Ok(format!("{}", imported))
 }
+
+#[cfg(not(windows))]
+fn restrict_permissions_to_owner(file_path: &Path) -> Result<(), i32> {
+	use std::ffi;
+	use libc;
+	let cstr = ffi::CString::new(file_path.to_str().unwrap()).unwrap();
+	match unsafe { libc::chmod(cstr.as_ptr(), libc::S_IWUSR | libc::S_IRUSR) } {
+		0 => Ok(()),
+		x => Err(x),
+	}
+}
+
+#[cfg(windows)]
+fn restrict_permissions_to_owner(_file_path: &Path) -> Result<(), i32> {
+	Ok(())
+}
+
+fn export(e: ExportAccount) -> Result<String, String> {
+	let password: String = match e.password_file {
+		Some(file) => try!(password_from_file(file)),
+		None => try!(password_prompt()),
+	};
+
+	let dir = Box::new(try!(keys_dir(e.path)));
+	let secret_store = Box::new(EthStore::open_with_iterations(dir, e.iterations).unwrap());
+	let acc_provider = AccountProvider::new(secret_store);
+	let json = try!(acc_provider.export_wallet(e.address, &password).map_err(|err| format!("Could not export account: {}", err)));
+	match e.file {
+		Some(file) => {
+			// never overwrite an existing file, and keep the key file private before writing to it.
+			let mut f = try!(OpenOptions::new().write(true).create_new(true).open(&file)
+				.map_err(|err| format!("Could not create {}: {}", file, err)));
+			try!(restrict_permissions_to_owner(Path::new(&file))
+				.map_err(|err| format!("Could not restrict permissions of {}: error {}", file, err)));
+			try!(f.write_all(&json).map_err(|err| format!("Could not write {}: {}", file, err)));
+			Ok(format!("{:?}", e.address))
+		},
+		None => String::from_utf8(json).map_err(|_| "Key file is not valid UTF-8.".to_owned()),
+	}
+}
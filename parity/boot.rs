@@ -63,7 +63,7 @@ pub fn payload<B: ipc::BinaryConvertable>() -> Result<B, BootError> {
 pub fn register(hv_url: &str, control_url: &str, module_id: IpcModuleId) -> GuardedSocket<HypervisorServiceClient<NanoSocket>>{
 	let hypervisor_client = nanoipc::fast_client::<HypervisorServiceClient<_>>(hv_url).unwrap();
 	hypervisor_client.handshake().unwrap();
-	hypervisor_client.module_ready(module_id, control_url.to_owned());
+	hypervisor_client.module_ready(module_id, control_url.to_owned(), env!("CARGO_PKG_VERSION").to_owned());
 
 	hypervisor_client
 }
@@ -110,6 +110,7 @@ Usage:
 				color: self.flag_no_color || cfg!(windows),
 				mode: self.flag_logging.clone(),
 				file: self.flag_log_file.clone(),
+				..Default::default()
 			}
 		}
 	}
@@ -17,6 +17,11 @@
 #[macro_use]
 mod usage;
 
+use rustc_serialize::hex::FromHex;
+
+/// Maximal length of the block extra data in bytes.
+const MAX_EXTRA_DATA_LEN: usize = 32;
+
 usage! {
 	{
 		// Commands
@@ -31,10 +36,12 @@ usage! {
 		cmd_new_token: bool,
 		cmd_snapshot: bool,
 		cmd_restore: bool,
+		cmd_repair: bool,
 		cmd_ui: bool,
 
 		// Arguments
 		arg_pid_file: String,
+		arg_address: String,
 		arg_file: Option<String>,
 		arg_path: Vec<String>,
 
@@ -85,8 +92,16 @@ usage! {
 			or |c: &Config| otry!(c.account).unlock.clone().map(|vec| Some(vec.join(","))),
 		flag_password: Vec<String> = Vec::new(),
 			or |c: &Config| otry!(c.account).password.clone(),
+		flag_fast_unlock: bool = false,
+			or |c: &Config| otry!(c.account).fast_unlock.clone(),
+		flag_unlock_attempts: u32 = 5u32,
+			or |c: &Config| otry!(c.account).unlock_attempts.clone(),
 		flag_keys_iterations: u32 = 10240u32,
 			or |c: &Config| otry!(c.account).keys_iterations.clone(),
+		flag_rpc_max_accounts: Option<usize> = None,
+			or |c: &Config| otry!(c.account).rpc_max_accounts.clone().map(Some),
+		flag_coinbase_fallback: bool = false,
+			or |c: &Config| otry!(c.account).coinbase_fallback.clone(),
 
 		flag_force_signer: bool = false,
 			or |c: &Config| otry!(c.signer).force.clone(),
@@ -106,6 +121,8 @@ usage! {
 			or |c: &Config| otry!(c.network).disable.clone(),
 		flag_port: u16 = 30303u16,
 			or |c: &Config| otry!(c.network).port.clone(),
+		flag_interface: String = "all",
+			or |c: &Config| otry!(c.network).interface.clone(),
 		flag_min_peers: u16 = 25u16,
 			or |c: &Config| otry!(c.network).min_peers.clone(),
 		flag_max_peers: u16 = 50u16,
@@ -124,6 +141,8 @@ usage! {
 			or |c: &Config| otry!(c.network).reserved_peers.clone().map(Some),
 		flag_reserved_only: bool = false,
 			or |c: &Config| otry!(c.network).reserved_only.clone(),
+		flag_sync_mem_limit: Option<usize> = None,
+			or |c: &Config| otry!(c.network).sync_mem_limit.clone().map(Some),
 
 		// -- API and Console Options
 		// RPC
@@ -139,6 +158,18 @@ usage! {
 			or |c: &Config| otry!(c.rpc).apis.clone().map(|vec| vec.join(",")),
 		flag_jsonrpc_hosts: String = "none",
 			or |c: &Config| otry!(c.rpc).hosts.clone().map(|vec| vec.join(",")),
+		flag_jsonrpc_local_senders_only: bool = false,
+			or |c: &Config| otry!(c.rpc).local_senders_only.clone(),
+		flag_jsonrpc_call_timeout: Option<u64> = None,
+			or |c: &Config| otry!(c.rpc).call_timeout.clone().map(Some),
+		flag_jsonrpc_keep_alive_interval: u64 = 30u64,
+			or |c: &Config| otry!(c.rpc).keep_alive_interval.clone(),
+		flag_health_min_peers: usize = 1usize,
+			or |c: &Config| otry!(c.rpc).health_min_peers.clone(),
+		flag_stable_block_confirmations: u64 = 12u64,
+			or |c: &Config| otry!(c.rpc).stable_block_confirmations.clone(),
+		flag_ntp_server: String = "pool.ntp.org:123",
+			or |c: &Config| otry!(c.rpc).ntp_server.clone(),
 
 		// IPC
 		flag_no_ipc: bool = false,
@@ -193,6 +224,12 @@ usage! {
 			or |c: &Config| otry!(c.mining).extra_data.clone().map(Some),
 		flag_tx_queue_size: usize = 1024usize,
 			or |c: &Config| otry!(c.mining).tx_queue_size.clone(),
+		flag_tx_ordering: String = "gas_price",
+			or |c: &Config| otry!(c.mining).tx_ordering.clone(),
+		flag_tx_queue_strategy: Option<String> = None,
+			or |c: &Config| otry!(c.mining).tx_queue_strategy.clone().map(Some),
+		flag_tx_replacement_bump: usize = 0usize,
+			or |c: &Config| otry!(c.mining).tx_replacement_bump.clone(),
 		flag_remove_solved: bool = false,
 			or |c: &Config| otry!(c.mining).remove_solved.clone(),
 		flag_notify_work: Option<String> = None,
@@ -227,6 +264,17 @@ usage! {
 		flag_at: String = "latest", or |_| None,
 		flag_no_periodic_snapshot: bool = false,
 			or |c: &Config| otry!(c.snapshots).disable_periodic.clone(),
+		flag_min_free_space: u64 = 1024u64,
+			or |c: &Config| otry!(c.snapshots).min_free_space.clone(),
+		flag_force: bool = false, or |_| None,
+		flag_snapshot_threads: Option<usize> = None,
+			or |c: &Config| otry!(c.snapshots).threads.clone().map(Some),
+		flag_snapshot_compression: Option<String> = None,
+			or |c: &Config| otry!(c.snapshots).compression.clone().map(Some),
+		flag_verify_snapshot_chunks: bool = false,
+			or |c: &Config| otry!(c.snapshots).verify_chunks.clone(),
+		flag_snapshot_receipt_index: bool = false,
+			or |c: &Config| otry!(c.snapshots).receipt_index.clone(),
 
 		// -- Virtual Machine Options
 		flag_jitvm: bool = false,
@@ -238,12 +286,41 @@ usage! {
 			or |c: &Config| otry!(c.misc).logging.clone().map(Some),
 		flag_log_file: Option<String> = None,
 			or |c: &Config| otry!(c.misc).log_file.clone().map(Some),
+		flag_log_rotate: String = "never",
+			or |c: &Config| otry!(c.misc).log_rotate.clone(),
+		flag_log_keep: usize = 5usize,
+			or |c: &Config| otry!(c.misc).log_keep.clone(),
 		flag_no_color: bool = false,
 			or |c: &Config| otry!(c.misc).color.map(|c| !c).clone(),
 	}
 }
 
 
+impl Args {
+	/// Extra data to include in sealed blocks, if set. `0x`-prefixed values are decoded as hex.
+	pub fn extra_data(&self) -> Result<Option<Vec<u8>>, String> {
+		let value = match self.flag_extradata.as_ref().or(self.flag_extra_data.as_ref()) {
+			Some(value) => value,
+			None => return Ok(None),
+		};
+
+		let bytes = match value.starts_with("0x") {
+			true => try!(value[2..].from_hex().map_err(|e| format!("{} is not valid hex: {}", value, e))),
+			false => value.as_bytes().to_owned(),
+		};
+
+		match bytes.len() > MAX_EXTRA_DATA_LEN {
+			true => Err(format!("{} is {} bytes long, at most {} bytes are allowed", value, bytes.len(), MAX_EXTRA_DATA_LEN)),
+			false => Ok(Some(bytes)),
+		}
+	}
+
+	fn validated(self) -> Result<Self, ArgsError> {
+		try!(self.extra_data().map_err(ArgsError::ExtraData));
+		Ok(self)
+	}
+}
+
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Config {
 	parity: Option<Operating>,
@@ -275,7 +352,11 @@ struct Operating {
 struct Account {
 	unlock: Option<Vec<String>>,
 	password: Option<Vec<String>>,
+	fast_unlock: Option<bool>,
+	unlock_attempts: Option<u32>,
 	keys_iterations: Option<u32>,
+	rpc_max_accounts: Option<usize>,
+	coinbase_fallback: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -291,6 +372,7 @@ struct Signer {
 struct Network {
 	disable: Option<bool>,
 	port: Option<u16>,
+	interface: Option<String>,
 	min_peers: Option<u16>,
 	max_peers: Option<u16>,
 	nat: Option<String>,
@@ -300,6 +382,7 @@ struct Network {
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
+	sync_mem_limit: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -310,6 +393,12 @@ struct Rpc {
 	cors: Option<String>,
 	apis: Option<Vec<String>>,
 	hosts: Option<Vec<String>>,
+	local_senders_only: Option<bool>,
+	call_timeout: Option<u64>,
+	keep_alive_interval: Option<u64>,
+	health_min_peers: Option<usize>,
+	stable_block_confirmations: Option<u64>,
+	ntp_server: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -346,6 +435,9 @@ struct Mining {
 	gas_cap: Option<String>,
 	extra_data: Option<String>,
 	tx_queue_size: Option<usize>,
+	tx_ordering: Option<String>,
+	tx_queue_strategy: Option<String>,
+	tx_replacement_bump: Option<usize>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
 }
@@ -366,6 +458,11 @@ struct Footprint {
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Snapshots {
 	disable_periodic: Option<bool>,
+	min_free_space: Option<u64>,
+	threads: Option<usize>,
+	compression: Option<String>,
+	verify_chunks: Option<bool>,
+	receipt_index: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -377,6 +474,8 @@ struct VM {
 struct Misc {
 	logging: Option<String>,
 	log_file: Option<String>,
+	log_rotate: Option<String>,
+	log_keep: Option<usize>,
 	color: Option<bool>,
 }
 
@@ -386,6 +485,7 @@ mod tests {
 		Args, ArgsError,
 		Config, Operating, Account, Signer, Network, Rpc, Ipc, Dapps, Mining, Footprint, Snapshots, VM, Misc
 	};
+	use std::iter::repeat;
 	use toml;
 
 	#[test]
@@ -440,10 +540,12 @@ mod tests {
 			cmd_new_token: false,
 			cmd_snapshot: false,
 			cmd_restore: false,
+			cmd_repair: false,
 			cmd_ui: false,
 
 			// Arguments
 			arg_pid_file: "".into(),
+			arg_address: "".into(),
 			arg_file: None,
 			arg_path: vec![],
 
@@ -459,7 +561,11 @@ mod tests {
 			// -- Account Options
 			flag_unlock: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			flag_password: vec!["~/.safe/password.file".into()],
+			flag_fast_unlock: false,
+			flag_unlock_attempts: 5u32,
 			flag_keys_iterations: 10240u32,
+			flag_rpc_max_accounts: Some(100),
+			flag_coinbase_fallback: false,
 
 			flag_force_signer: false,
 			flag_no_signer: false,
@@ -471,6 +577,7 @@ mod tests {
 			// -- Networking Options
 			flag_no_network: false,
 			flag_port: 30303u16,
+			flag_interface: "all".into(),
 			flag_min_peers: 25u16,
 			flag_max_peers: 50u16,
 			flag_nat: "any".into(),
@@ -480,6 +587,7 @@ mod tests {
 			flag_node_key: None,
 			flag_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
+			flag_sync_mem_limit: Some(256),
 
 			// -- API and Console Options
 			// RPC
@@ -489,6 +597,12 @@ mod tests {
 			flag_jsonrpc_cors: Some("null".into()),
 			flag_jsonrpc_apis: "web3,eth,net,personal,ethcore,traces,rpc".into(),
 			flag_jsonrpc_hosts: "none".into(),
+			flag_jsonrpc_local_senders_only: false,
+			flag_jsonrpc_call_timeout: Some(5000),
+			flag_jsonrpc_keep_alive_interval: 30u64,
+			flag_health_min_peers: 1usize,
+			flag_stable_block_confirmations: 12u64,
+			flag_ntp_server: "pool.ntp.org:123".into(),
 
 			// IPC
 			flag_no_ipc: false,
@@ -519,6 +633,9 @@ mod tests {
 			flag_gas_cap: "6283184".into(),
 			flag_extra_data: Some("Parity".into()),
 			flag_tx_queue_size: 1024usize,
+			flag_tx_ordering: "gas_factor".into(),
+			flag_tx_queue_strategy: Some("gas_factor".into()),
+			flag_tx_replacement_bump: 10usize,
 			flag_remove_solved: false,
 			flag_notify_work: Some("http://localhost:3001".into()),
 
@@ -541,6 +658,12 @@ mod tests {
 			// -- Snapshot Optons
 			flag_at: "latest".into(),
 			flag_no_periodic_snapshot: false,
+			flag_min_free_space: 1024u64,
+			flag_force: false,
+			flag_snapshot_threads: Some(2),
+			flag_snapshot_compression: Some("snappy".into()),
+			flag_verify_snapshot_chunks: false,
+			flag_snapshot_receipt_index: false,
 
 			// -- Virtual Machine Options
 			flag_jitvm: false,
@@ -577,11 +700,40 @@ mod tests {
 			flag_config: "$HOME/.parity/config.toml".into(),
 			flag_logging: Some("own_tx=trace".into()),
 			flag_log_file: Some("/var/log/parity.log".into()),
+			flag_log_rotate: "daily".into(),
+			flag_log_keep: 7usize,
 			flag_no_color: false,
 			flag_no_config: false,
 		});
 	}
 
+	#[test]
+	fn should_reject_too_long_extra_data() {
+		let args = Args::parse_without_config(&["parity", "--extra-data", "This extra data is way too long for a block"]);
+
+		match args {
+			Err(ArgsError::ExtraData(_)) => {},
+			a => panic!("Expected extra data error, got {:?}", a),
+		}
+	}
+
+	#[test]
+	fn should_parse_short_extra_data() {
+		let args = Args::parse_without_config(&["parity", "--extra-data", "Parity"]).unwrap();
+
+		assert_eq!(args.extra_data().unwrap(), Some(b"Parity".to_vec()));
+	}
+
+	#[test]
+	fn should_decode_hex_extra_data() {
+		let hex = format!("0x{}", repeat("ab").take(32).collect::<String>());
+		let args = Args::parse_without_config(&["parity", "--extra-data", &hex]).unwrap();
+		assert_eq!(args.extra_data().unwrap(), Some(vec![0xab; 32]));
+
+		let too_long = format!("0x{}", repeat("ab").take(33).collect::<String>());
+		assert!(Args::parse_without_config(&["parity", "--extra-data", &too_long]).is_err());
+	}
+
 	#[test]
 	fn should_parse_config_and_return_errors() {
 		let config1 = Args::parse_config(include_str!("./config.invalid1.toml"));
@@ -612,7 +764,11 @@ mod tests {
 			account: Some(Account {
 				unlock: Some(vec!["0x1".into(), "0x2".into(), "0x3".into()]),
 				password: Some(vec!["passwdfile path".into()]),
+				fast_unlock: None,
+				unlock_attempts: None,
 				keys_iterations: None,
+				rpc_max_accounts: None,
+				coinbase_fallback: None,
 			}),
 			signer: Some(Signer {
 				force: None,
@@ -624,6 +780,7 @@ mod tests {
 			network: Some(Network {
 				disable: Some(false),
 				port: None,
+				interface: None,
 				min_peers: Some(10),
 				max_peers: Some(20),
 				nat: Some("any".into()),
@@ -633,6 +790,7 @@ mod tests {
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
+				sync_mem_limit: None,
 			}),
 			rpc: Some(Rpc {
 				disable: Some(true),
@@ -641,6 +799,12 @@ mod tests {
 				cors: None,
 				apis: None,
 				hosts: None,
+				local_senders_only: None,
+				call_timeout: None,
+				keep_alive_interval: None,
+				health_min_peers: None,
+				stable_block_confirmations: None,
+				ntp_server: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
@@ -669,6 +833,9 @@ mod tests {
 				gas_floor_target: None,
 				gas_cap: None,
 				tx_queue_size: Some(2048),
+				tx_ordering: None,
+				tx_queue_strategy: Some("fifo".into()),
+				tx_replacement_bump: None,
 				tx_gas_limit: None,
 				extra_data: None,
 				remove_solved: None,
@@ -687,6 +854,11 @@ mod tests {
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
+				min_free_space: None,
+				threads: None,
+				compression: None,
+				verify_chunks: None,
+				receipt_index: None,
 			}),
 			vm: Some(VM {
 				jit: Some(false),
@@ -694,6 +866,8 @@ mod tests {
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
+				log_rotate: None,
+				log_keep: None,
 				color: Some(true),
 			})
 		});
@@ -51,6 +51,7 @@ macro_rules! usage {
 			Parsing(Vec<toml::ParserError>),
 			Decode(toml::DecodeError),
 			Config(String, io::Error),
+			ExtraData(String),
 		}
 
 		impl ArgsError {
@@ -73,7 +74,11 @@ macro_rules! usage {
 						println!("There was an error reading your config file at: {}", path);
 						println!("{}", e);
 						process::exit(2)
-					}
+					},
+					ArgsError::ExtraData(e) => {
+						println!("Invalid extra data: {}", e);
+						process::exit(2)
+					},
 				}
 			}
 		}
@@ -128,7 +133,7 @@ macro_rules! usage {
 
 				// Skip loading config file if no_config flag is specified
 				if raw_args.flag_no_config {
-					return Ok(raw_args.into_args(Config::default()));
+					return raw_args.into_args(Config::default()).validated();
 				}
 
 				let config_file = raw_args.flag_config.clone().unwrap_or_else(|| raw_args.clone().into_args(Config::default()).flag_config);
@@ -149,7 +154,7 @@ macro_rules! usage {
 					},
 				};
 
-				Ok(raw_args.into_args(config))
+				raw_args.into_args(config).validated()
 			}
 
 			#[cfg(test)]
@@ -159,7 +164,7 @@ macro_rules! usage {
 
 			#[cfg(test)]
 			fn parse_with_config<S: AsRef<str>>(command: &[S], config: Config) -> Result<Self, ArgsError> {
-				Ok(try!(RawArgs::parse(command)).into_args(config))
+				try!(RawArgs::parse(command)).into_args(config).validated()
 			}
 
 			fn parse_config(config: &str) -> Result<Config, ArgsError> {
@@ -24,13 +24,14 @@ use util::{Hashable, U256, Uint, Bytes, version_data, Secret, Address};
 use util::log::Colour;
 use ethsync::{NetworkConfiguration, is_valid_node_url};
 use ethcore::client::{VMType, Mode};
-use ethcore::miner::MinerOptions;
+use ethcore::miner::{MinerOptions, TransactionOrdering};
+use ethcore::snapshot::SnapshotConfig;
 
 use rpc::{IpcConfiguration, HttpConfiguration};
 use ethcore_rpc::NetworkSettings;
 use cache::CacheConfig;
-use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, replace_home,
-geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address};
+use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_tx_ordering, to_interface, to_price, replace_home,
+geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_identity};
 use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, SpecType};
 use ethcore_logger::Config as LogConfig;
 use dir::Directories;
@@ -39,7 +40,7 @@ use signer::Configuration as SignerConfiguration;
 use run::RunCmd;
 use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat};
 use presale::ImportWallet;
-use account::{AccountCmd, NewAccount, ImportAccounts};
+use account::{AccountCmd, NewAccount, ImportAccounts, ExportAccount};
 use snapshot::{self, SnapshotCommand};
 
 #[derive(Debug, PartialEq)]
@@ -75,7 +76,7 @@ impl Configuration {
 		let vm_type = try!(self.vm_type());
 		let mode = try!(to_mode(&self.args.flag_mode, self.args.flag_mode_timeout, self.args.flag_mode_alarm));
 		let miner_options = try!(self.miner_options());
-		let logger_config = self.logger_config();
+		let logger_config = try!(self.logger_config());
 		let http_conf = try!(self.http_config());
 		let ipc_conf = try!(self.ipc_config());
 		let net_conf = try!(self.net_config());
@@ -88,8 +89,8 @@ impl Configuration {
 		let enable_network = self.enable_network(&mode);
 		let geth_compatibility = self.args.flag_geth;
 		let signer_port = self.signer_port();
-		let dapps_conf = self.dapps_config();
-		let signer_conf = self.signer_config();
+		let dapps_conf = try!(self.dapps_config());
+		let signer_conf = try!(self.signer_config());
 		let format = try!(self.format());
 
 		let cmd = if self.args.flag_version {
@@ -112,6 +113,15 @@ impl Configuration {
 					to: dirs.keys,
 				};
 				AccountCmd::Import(import_acc)
+			} else if self.args.cmd_export {
+				let export_acc = ExportAccount {
+					iterations: self.args.flag_keys_iterations,
+					path: dirs.keys,
+					address: try!(to_address(Some(self.args.arg_address.clone()))),
+					password_file: self.args.flag_password.first().cloned(),
+					file: self.args.arg_file.clone(),
+				};
+				AccountCmd::Export(export_acc)
 			} else {
 				unreachable!();
 			};
@@ -171,6 +181,13 @@ impl Configuration {
 				wal: wal,
 				kind: snapshot::Kind::Take,
 				block_at: try!(to_block_id(&self.args.flag_at)),
+				min_free_space: self.args.flag_min_free_space * 1024 * 1024,
+				force: self.args.flag_force,
+				snapshot_conf: try!(self.snapshot_config()),
+				repair_sources: Vec::new(),
+				net_conf: net_conf,
+				network_id: network_id,
+				custom_bootnodes: self.args.flag_bootnodes.is_some(),
 			};
 			Cmd::Snapshot(snapshot_cmd)
 		} else if self.args.cmd_restore {
@@ -187,8 +204,38 @@ impl Configuration {
 				wal: wal,
 				kind: snapshot::Kind::Restore,
 				block_at: try!(to_block_id("latest")), // unimportant.
+				min_free_space: self.args.flag_min_free_space * 1024 * 1024,
+				force: self.args.flag_force,
+				snapshot_conf: try!(self.snapshot_config()),
+				repair_sources: Vec::new(),
+				net_conf: net_conf,
+				network_id: network_id,
+				custom_bootnodes: self.args.flag_bootnodes.is_some(),
 			};
 			Cmd::Snapshot(restore_cmd)
+		} else if self.args.cmd_repair {
+			let repair_cmd = SnapshotCommand {
+				cache_config: cache_config,
+				dirs: dirs,
+				spec: spec,
+				pruning: pruning,
+				logger_config: logger_config,
+				mode: mode,
+				tracing: tracing,
+				compaction: compaction,
+				file_path: self.args.arg_file.clone(),
+				wal: wal,
+				kind: snapshot::Kind::Repair,
+				block_at: try!(to_block_id("latest")), // unimportant.
+				min_free_space: self.args.flag_min_free_space * 1024 * 1024,
+				force: self.args.flag_force,
+				snapshot_conf: try!(self.snapshot_config()),
+				repair_sources: self.args.arg_path.clone(),
+				net_conf: net_conf,
+				network_id: network_id,
+				custom_bootnodes: self.args.flag_bootnodes.is_some(),
+			};
+			Cmd::Snapshot(repair_cmd)
 		} else {
 			let daemon = if self.args.cmd_daemon {
 				Some(self.args.arg_pid_file.clone())
@@ -207,6 +254,7 @@ impl Configuration {
 				http_conf: http_conf,
 				ipc_conf: ipc_conf,
 				net_conf: net_conf,
+				sync_mem_limit: self.args.flag_sync_mem_limit.map(|mb| mb * 1024 * 1024),
 				network_id: network_id,
 				acc_conf: try!(self.accounts_config()),
 				gas_pricer: try!(self.gas_pricer_config()),
@@ -218,6 +266,13 @@ impl Configuration {
 				vm_type: vm_type,
 				enable_network: enable_network,
 				geth_compatibility: geth_compatibility,
+				rpc_max_accounts: self.args.flag_rpc_max_accounts,
+				coinbase_fallback: self.args.flag_coinbase_fallback,
+				call_timeout: self.args.flag_jsonrpc_call_timeout.map(Duration::from_millis),
+				keep_alive_interval: Duration::from_secs(self.args.flag_jsonrpc_keep_alive_interval),
+				health_min_peers: self.args.flag_health_min_peers,
+				stable_block_confirmations: self.args.flag_stable_block_confirmations,
+				ntp_server: self.ntp_server(),
 				signer_port: signer_port,
 				net_settings: self.network_settings(),
 				dapps_conf: dapps_conf,
@@ -226,6 +281,7 @@ impl Configuration {
 				name: self.args.flag_identity,
 				custom_bootnodes: self.args.flag_bootnodes.is_some(),
 				no_periodic_snapshot: self.args.flag_no_periodic_snapshot,
+				snapshot_conf: try!(self.snapshot_config()),
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -248,6 +304,22 @@ impl Configuration {
 		}
 	}
 
+	fn snapshot_config(&self) -> Result<SnapshotConfig, String> {
+		let mut conf = SnapshotConfig::default();
+		conf.verify_state_chunks = self.args.flag_verify_snapshot_chunks;
+		conf.include_receipt_index = self.args.flag_snapshot_receipt_index;
+		if let Some(threads) = self.args.flag_snapshot_threads {
+			if threads == 0 {
+				return Err("--snapshot-threads must be at least 1.".into());
+			}
+			conf.threads = threads;
+		}
+		if let Some(ref codec) = self.args.flag_snapshot_compression {
+			conf.compression = try!(codec.parse());
+		}
+		Ok(conf)
+	}
+
 	fn miner_extras(&self) -> Result<MinerExtras, String> {
 		let extras = MinerExtras {
 			author: try!(self.author()),
@@ -278,12 +350,15 @@ impl Configuration {
 		}
 	}
 
-	fn logger_config(&self) -> LogConfig {
-		LogConfig {
+	fn logger_config(&self) -> Result<LogConfig, String> {
+		let config = LogConfig {
 			mode: self.args.flag_logging.clone(),
 			color: !self.args.flag_no_color && !cfg!(windows),
 			file: self.args.flag_log_file.clone(),
-		}
+			rotate: try!(self.args.flag_log_rotate.parse()),
+			keep: self.args.flag_log_keep,
+		};
+		Ok(config)
 	}
 
 	fn chain(&self) -> String {
@@ -314,11 +389,27 @@ impl Configuration {
 			testnet: self.args.flag_testnet,
 			password_files: self.args.flag_password.clone(),
 			unlocked_accounts: try!(to_addresses(&self.args.flag_unlock)),
+			fast_unlock: self.args.flag_fast_unlock,
+			unlock_attempts: self.args.flag_unlock_attempts,
 		};
 
 		Ok(cfg)
 	}
 
+	fn tx_ordering(&self) -> Result<TransactionOrdering, String> {
+		let ordering = try!(to_tx_ordering(&self.args.flag_tx_ordering));
+		let name = match self.args.flag_tx_queue_strategy {
+			Some(ref name) => name,
+			None => return Ok(ordering),
+		};
+		let strategy = try!(to_tx_ordering(name));
+		// --tx-ordering is only ever changed from its default on purpose
+		if self.args.flag_tx_ordering != Args::default().flag_tx_ordering && ordering != strategy {
+			return Err(format!("--tx-queue-strategy {} conflicts with --tx-ordering {}", name, self.args.flag_tx_ordering));
+		}
+		Ok(strategy)
+	}
+
 	fn miner_options(&self) -> Result<MinerOptions, String> {
 		let reseal = try!(self.args.flag_reseal_on_txs.parse::<ResealPolicy>());
 
@@ -333,6 +424,8 @@ impl Configuration {
 			},
 			tx_queue_size: self.args.flag_tx_queue_size,
 			pending_set: try!(to_pending_set(&self.args.flag_relay_set)),
+			tx_ordering: try!(self.tx_ordering()),
+			tx_replacement_bump: self.args.flag_tx_replacement_bump,
 			reseal_min_period: Duration::from_millis(self.args.flag_reseal_min_period),
 			work_queue_size: self.args.flag_work_queue_size,
 			enable_resubmission: !self.args.flag_remove_solved,
@@ -341,26 +434,30 @@ impl Configuration {
 		Ok(options)
 	}
 
-	fn signer_config(&self) -> SignerConfiguration {
-		SignerConfiguration {
+	fn signer_config(&self) -> Result<SignerConfiguration, String> {
+		let conf = SignerConfiguration {
 			enabled: self.signer_enabled(),
 			port: self.args.flag_signer_port,
-			interface: self.signer_interface(),
+			interface: try!(to_interface(&self.args.flag_signer_interface)),
 			signer_path: self.directories().signer,
 			skip_origin_validation: self.args.flag_signer_no_validation,
-		}
+		};
+
+		Ok(conf)
 	}
 
-	fn dapps_config(&self) -> DappsConfiguration {
-		DappsConfiguration {
+	fn dapps_config(&self) -> Result<DappsConfiguration, String> {
+		let conf = DappsConfiguration {
 			enabled: self.dapps_enabled(),
-			interface: self.dapps_interface(),
+			interface: try!(to_interface(&self.args.flag_dapps_interface)),
 			port: self.args.flag_dapps_port,
 			hosts: self.dapps_hosts(),
 			user: self.args.flag_dapps_user.clone(),
 			pass: self.args.flag_dapps_pass.clone(),
 			dapps_path: self.directories().dapps,
-		}
+		};
+
+		Ok(conf)
 	}
 
 	fn gas_pricer_config(&self) -> Result<GasPricerConfig, String> {
@@ -391,11 +488,7 @@ impl Configuration {
 	}
 
 	fn extra_data(&self) -> Result<Bytes, String> {
-		match self.args.flag_extradata.as_ref().or(self.args.flag_extra_data.as_ref()) {
-			Some(x) if x.len() <= 32 => Ok(x.as_bytes().to_owned()),
-			None => Ok(version_data()),
-			Some(_) => Err("Extra data must be at most 32 characters".into()),
-		}
+		self.args.extra_data().map(|extra_data| extra_data.unwrap_or_else(version_data))
 	}
 
 	fn init_reserved_nodes(&self) -> Result<Vec<String>, String> {
@@ -418,7 +511,7 @@ impl Configuration {
 
 	fn net_addresses(&self) -> Result<(Option<SocketAddr>, Option<SocketAddr>), String> {
 		let port = self.args.flag_port;
-		let listen_address = Some(SocketAddr::new("0.0.0.0".parse().unwrap(), port));
+		let listen_address = Some(SocketAddr::new(try!(to_interface(&self.args.flag_interface)), port));
 		let public_address = if self.args.flag_nat.starts_with("extip:") {
 			let host = &self.args.flag_nat[6..];
 			let host = try!(host.parse().map_err(|_| format!("Invalid host given with `--nat extip:{}`", host)));
@@ -447,6 +540,7 @@ impl Configuration {
 		ret.net_config_path = Some(net_specific_path.to_str().unwrap().to_owned());
 		ret.reserved_nodes = try!(self.init_reserved_nodes());
 		ret.allow_non_reserved = !self.args.flag_reserved_only;
+		ret.identity = try!(to_identity(&self.args.flag_identity));
 		Ok(ret)
 	}
 
@@ -506,14 +600,22 @@ impl Configuration {
 		Ok(conf)
 	}
 
+	fn ntp_server(&self) -> Option<String> {
+		match self.args.flag_ntp_server.as_ref() {
+			"none" => None,
+			server => Some(server.to_owned()),
+		}
+	}
+
 	fn http_config(&self) -> Result<HttpConfiguration, String> {
 		let conf = HttpConfiguration {
 			enabled: !self.args.flag_jsonrpc_off && !self.args.flag_no_jsonrpc,
-			interface: self.rpc_interface(),
+			interface: try!(to_interface(&self.network_settings().rpc_interface)),
 			port: self.args.flag_rpcport.unwrap_or(self.args.flag_jsonrpc_port),
 			apis: try!(self.rpc_apis().parse()),
 			hosts: self.rpc_hosts(),
 			cors: self.rpc_cors(),
+			local_senders_only: self.args.flag_jsonrpc_local_senders_only,
 		};
 
 		Ok(conf)
@@ -585,28 +687,6 @@ impl Configuration {
 		}
 	}
 
-	fn signer_interface(&self) -> String {
-		match self.args.flag_signer_interface.as_str() {
-			"local" => "127.0.0.1",
-			x => x,
-		}.into()
-	}
-
-	fn rpc_interface(&self) -> String {
-		match self.network_settings().rpc_interface.as_str() {
-			"all" => "0.0.0.0",
-			"local" => "127.0.0.1",
-			x => x,
-		}.into()
-	}
-
-	fn dapps_interface(&self) -> String {
-		match self.args.flag_dapps_interface.as_str() {
-			"local" => "127.0.0.1",
-			x => x,
-		}.into()
-	}
-
 	fn dapps_enabled(&self) -> bool {
 		!self.args.flag_dapps_off && !self.args.flag_no_dapps && cfg!(feature = "dapps")
 	}
@@ -630,15 +710,20 @@ mod tests {
 	use cli::Args;
 	use ethcore_rpc::NetworkSettings;
 	use ethcore::client::{VMType, BlockID};
+	use ethcore::snapshot::Compression;
+	use ethcore::miner::TransactionOrdering;
+	use ethcore_logger::Rotation;
 	use helpers::{replace_home, default_network_config};
 	use run::RunCmd;
 	use signer::Configuration as SignerConfiguration;
 	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat};
 	use presale::ImportWallet;
-	use account::{AccountCmd, NewAccount, ImportAccounts};
+	use account::{AccountCmd, NewAccount, ImportAccounts, ExportAccount};
+	use snapshot;
 	use devtools::{RandomTempPath};
 	use std::io::Write;
 	use std::fs::{File, create_dir};
+	use std::net::IpAddr;
 
 	#[derive(Debug, PartialEq)]
 	struct TestPasswordReader(&'static str);
@@ -686,6 +771,19 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_account_export() {
+		let args = vec!["parity", "account", "export", "0x0000000000000000000000000000000000000001", "key.json", "--password", "pwd"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap(), Cmd::Account(AccountCmd::Export(ExportAccount {
+			iterations: 10240,
+			path: replace_home("$HOME/.parity/keys"),
+			address: 1.into(),
+			password_file: Some("pwd".into()),
+			file: Some("key.json".into()),
+		})));
+	}
+
 	#[test]
 	fn test_command_wallet_import() {
 		let args = vec!["parity", "wallet", "import", "my_wallet.json", "--password", "pwd"];
@@ -760,6 +858,34 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_snapshot_repair() {
+		let args = vec!["parity", "repair", "snapshot", "peer1.snapshot", "peer2"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap() {
+			Cmd::Snapshot(cmd) => {
+				assert_eq!(cmd.kind, snapshot::Kind::Repair);
+				assert_eq!(cmd.file_path, Some("snapshot".into()));
+				assert_eq!(cmd.repair_sources, vec!["peer1.snapshot".to_owned(), "peer2".to_owned()]);
+			},
+			_ => panic!("Expected snapshot command"),
+		}
+	}
+
+	#[test]
+	fn test_command_snapshot_repair_from_peers() {
+		let args = vec!["parity", "repair", "snapshot"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap() {
+			Cmd::Snapshot(cmd) => {
+				assert_eq!(cmd.kind, snapshot::Kind::Repair);
+				assert!(cmd.repair_sources.is_empty());
+				assert!(!cmd.custom_bootnodes);
+			},
+			_ => panic!("Expected snapshot command"),
+		}
+	}
+
 	#[test]
 	fn test_command_signer_new_token() {
 		let args = vec!["parity", "signer", "new-token"];
@@ -783,6 +909,7 @@ mod tests {
 			http_conf: Default::default(),
 			ipc_conf: Default::default(),
 			net_conf: default_network_config(),
+			sync_mem_limit: None,
 			network_id: None,
 			acc_conf: Default::default(),
 			gas_pricer: Default::default(),
@@ -794,6 +921,13 @@ mod tests {
 			vm_type: Default::default(),
 			enable_network: true,
 			geth_compatibility: false,
+			rpc_max_accounts: None,
+			coinbase_fallback: false,
+			call_timeout: None,
+			keep_alive_interval: Duration::from_secs(30),
+			health_min_peers: 1,
+			stable_block_confirmations: 12,
+			ntp_server: Some("pool.ntp.org:123".into()),
 			signer_port: Some(8180),
 			net_settings: Default::default(),
 			dapps_conf: Default::default(),
@@ -802,6 +936,7 @@ mod tests {
 			name: "".into(),
 			custom_bootnodes: false,
 			no_periodic_snapshot: false,
+			snapshot_conf: Default::default(),
 		}));
 	}
 
@@ -823,6 +958,116 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_pass_identity_to_network_config() {
+		let conf = parse(&["parity", "--identity", "testname"]);
+		assert_eq!(conf.net_config().unwrap().identity, "testname".to_owned());
+
+		let conf = parse(&["parity", "--identity", "test/name"]);
+		assert!(conf.net_config().is_err());
+		assert!(conf.into_command().is_err());
+	}
+
+	#[test]
+	fn should_parse_snapshot_config() {
+		let conf = parse(&["parity", "--snapshot-threads", "1", "--snapshot-compression", "snappy"]);
+		assert_eq!(conf.snapshot_config().unwrap(), SnapshotConfig {
+			threads: 1,
+			compression: Compression::Snappy,
+			verify_state_chunks: false,
+			include_receipt_index: false,
+			skip_corrupt_accounts: false,
+		});
+
+		let conf = parse(&["parity", "--verify-snapshot-chunks"]);
+		assert!(conf.snapshot_config().unwrap().verify_state_chunks);
+
+		let conf = parse(&["parity", "--snapshot-receipt-index"]);
+		assert!(conf.snapshot_config().unwrap().include_receipt_index);
+
+		let conf = parse(&["parity", "--snapshot-compression", "zstd"]);
+		assert!(conf.snapshot_config().is_err());
+		assert!(conf.into_command().is_err());
+
+		let conf = parse(&["parity", "--snapshot-threads", "0"]);
+		assert!(conf.snapshot_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_tx_ordering() {
+		let conf = parse(&["parity"]);
+		assert_eq!(conf.miner_options().unwrap().tx_ordering, TransactionOrdering::GasPriceThenNonce);
+
+		let conf = parse(&["parity", "--tx-ordering", "fifo"]);
+		assert_eq!(conf.miner_options().unwrap().tx_ordering, TransactionOrdering::FifoThenNonce);
+
+		let conf = parse(&["parity", "--tx-ordering", "gas_factor"]);
+		assert_eq!(conf.miner_options().unwrap().tx_ordering, TransactionOrdering::GasFactorThenNonce);
+
+		let conf = parse(&["parity", "--tx-ordering", "random"]);
+		assert!(conf.miner_options().is_err());
+	}
+
+	#[test]
+	fn should_parse_tx_queue_strategy() {
+		let conf = parse(&["parity", "--tx-queue-strategy", "gas_factor"]);
+		assert_eq!(conf.miner_options().unwrap().tx_ordering, TransactionOrdering::GasFactorThenNonce);
+
+		let conf = parse(&["parity", "--tx-ordering", "fifo", "--tx-queue-strategy", "fifo"]);
+		assert_eq!(conf.miner_options().unwrap().tx_ordering, TransactionOrdering::FifoThenNonce);
+
+		let conf = parse(&["parity", "--tx-ordering", "gas_factor", "--tx-queue-strategy", "fifo"]);
+		assert!(conf.miner_options().is_err());
+
+		let conf = parse(&["parity", "--tx-queue-strategy", "lifo"]);
+		assert!(conf.miner_options().is_err());
+	}
+
+	#[test]
+	fn should_parse_tx_replacement_bump() {
+		let conf = parse(&["parity"]);
+		assert_eq!(conf.miner_options().unwrap().tx_replacement_bump, 0);
+
+		let conf = parse(&["parity", "--tx-replacement-bump", "10"]);
+		assert_eq!(conf.miner_options().unwrap().tx_replacement_bump, 10);
+	}
+
+	#[test]
+	fn should_parse_log_rotation() {
+		let conf = parse(&["parity"]);
+		let config = conf.logger_config().unwrap();
+		assert_eq!(config.rotate, Rotation::Never);
+		assert_eq!(config.keep, 5);
+
+		let conf = parse(&["parity", "--log-rotate", "100MB", "--log-keep", "3"]);
+		let config = conf.logger_config().unwrap();
+		assert_eq!(config.rotate, Rotation::Size(100 * 1024 * 1024));
+		assert_eq!(config.keep, 3);
+
+		let conf = parse(&["parity", "--log-rotate", "weekly"]);
+		assert!(conf.logger_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_fast_unlock() {
+		let conf = parse(&["parity"]);
+		assert_eq!(conf.accounts_config().unwrap().fast_unlock, false);
+
+		let conf = parse(&["parity", "--unlock", "0xdeadbeefcafe0000000000000000000000000000", "--fast-unlock"]);
+		let config = conf.accounts_config().unwrap();
+		assert_eq!(config.fast_unlock, true);
+		assert_eq!(config.unlocked_accounts.len(), 1);
+	}
+
+	#[test]
+	fn should_parse_unlock_attempts() {
+		let conf = parse(&["parity"]);
+		assert_eq!(conf.accounts_config().unwrap().unlock_attempts, 5);
+
+		let conf = parse(&["parity", "--unlock-attempts", "0"]);
+		assert_eq!(conf.accounts_config().unwrap().unlock_attempts, 0);
+	}
+
 	#[test]
 	fn should_parse_rpc_settings_with_geth_compatiblity() {
 		// given
@@ -920,39 +1165,70 @@ mod tests {
 		let conf0 = parse(&["parity", "--signer-path", "signer"]);
 		let conf1 = parse(&["parity", "--signer-path", "signer", "--signer-no-validation"]);
 		let conf2 = parse(&["parity", "--signer-path", "signer", "--signer-port", "3123"]);
-		let conf3 = parse(&["parity", "--signer-path", "signer", "--signer-interface", "test"]);
+		let conf3 = parse(&["parity", "--signer-path", "signer", "--signer-interface", "10.0.0.1"]);
 
 		// then
-		assert_eq!(conf0.signer_config(), SignerConfiguration {
+		assert_eq!(conf0.signer_config().unwrap(), SignerConfiguration {
 			enabled: true,
 			port: 8180,
-			interface: "127.0.0.1".into(),
+			interface: "127.0.0.1".parse().unwrap(),
 			signer_path: "signer".into(),
 			skip_origin_validation: false,
 		});
-		assert_eq!(conf1.signer_config(), SignerConfiguration {
+		assert_eq!(conf1.signer_config().unwrap(), SignerConfiguration {
 			enabled: true,
 			port: 8180,
-			interface: "127.0.0.1".into(),
+			interface: "127.0.0.1".parse().unwrap(),
 			signer_path: "signer".into(),
 			skip_origin_validation: true,
 		});
-		assert_eq!(conf2.signer_config(), SignerConfiguration {
+		assert_eq!(conf2.signer_config().unwrap(), SignerConfiguration {
 			enabled: true,
 			port: 3123,
-			interface: "127.0.0.1".into(),
+			interface: "127.0.0.1".parse().unwrap(),
 			signer_path: "signer".into(),
 			skip_origin_validation: false,
 		});
-		assert_eq!(conf3.signer_config(), SignerConfiguration {
+		assert_eq!(conf3.signer_config().unwrap(), SignerConfiguration {
 			enabled: true,
 			port: 8180,
-			interface: "test".into(),
+			interface: "10.0.0.1".parse().unwrap(),
 			signer_path: "signer".into(),
 			skip_origin_validation: false,
 		});
 	}
 
+	#[test]
+	fn should_parse_local_senders_only() {
+		assert!(!parse(&["parity"]).http_config().unwrap().local_senders_only);
+		assert!(parse(&["parity", "--jsonrpc-local-senders-only"]).http_config().unwrap().local_senders_only);
+	}
+
+	#[test]
+	fn should_disable_ntp_server() {
+		assert_eq!(parse(&["parity"]).ntp_server(), Some("pool.ntp.org:123".into()));
+		assert_eq!(parse(&["parity", "--ntp-server", "none"]).ntp_server(), None);
+	}
+
+	#[test]
+	fn should_resolve_interfaces() {
+		let conf = parse(&["parity", "--jsonrpc-interface", "all", "--dapps-interface", "::1", "--signer-interface", "local", "--interface", "10.0.0.1"]);
+		assert_eq!(conf.http_config().unwrap().interface, "0.0.0.0".parse::<IpAddr>().unwrap());
+		assert_eq!(conf.dapps_config().unwrap().interface, "::1".parse::<IpAddr>().unwrap());
+		assert_eq!(conf.signer_config().unwrap().interface, "127.0.0.1".parse::<IpAddr>().unwrap());
+		assert_eq!(conf.net_addresses().unwrap().0, Some("10.0.0.1:30303".parse().unwrap()));
+
+		let conf = parse(&["parity"]);
+		assert_eq!(conf.net_addresses().unwrap().0, Some("0.0.0.0:30303".parse().unwrap()));
+
+		let conf = parse(&["parity", "--signer-interface", "no-such-interface0"]);
+		assert!(conf.signer_config().is_err());
+		assert!(conf.into_command().is_err());
+
+		let conf = parse(&["parity", "--interface", "no-such-interface0"]);
+		assert!(conf.net_addresses().is_err());
+	}
+
 	#[test]
 	fn should_not_bail_on_empty_line_in_reserved_peers() {
 		let temp = RandomTempPath::new();
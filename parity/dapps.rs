@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use io::PanicHandler;
 use rpc_apis;
 use ethcore::client::Client;
@@ -24,7 +25,7 @@ use helpers::replace_home;
 #[derive(Debug, PartialEq, Clone)]
 pub struct Configuration {
 	pub enabled: bool,
-	pub interface: String,
+	pub interface: IpAddr,
 	pub port: u16,
 	pub hosts: Option<Vec<String>>,
 	pub user: Option<String>,
@@ -36,7 +37,7 @@ impl Default for Configuration {
 	fn default() -> Self {
 		Configuration {
 			enabled: true,
-			interface: "127.0.0.1".into(),
+			interface: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
 			port: 8080,
 			hosts: Some(Vec::new()),
 			user: None,
@@ -58,8 +59,7 @@ pub fn new(configuration: Configuration, deps: Dependencies) -> Result<Option<We
 		return Ok(None);
 	}
 
-	let url = format!("{}:{}", configuration.interface, configuration.port);
-	let addr = try!(url.parse().map_err(|_| format!("Invalid Webapps listen host/port given: {}", url)));
+	let addr = SocketAddr::new(configuration.interface, configuration.port);
 
 	let auth = configuration.user.as_ref().map(|username| {
 		let password = configuration.pass.as_ref().map_or_else(|| {
@@ -125,7 +125,7 @@ mod server {
 		);
 		let sync = deps.sync.clone();
 		server.with_sync_status(Arc::new(move || sync.status().is_major_syncing()));
-		let server = rpc_apis::setup_rpc(server, deps.apis.clone(), rpc_apis::ApiSet::UnsafeContext);
+		let server = rpc_apis::setup_rpc(server, deps.apis.clone(), rpc_apis::ApiSet::UnsafeContext, false);
 		let start_result = match auth {
 			None => {
 				server.start_unsecured_http(url, allowed_hosts)
@@ -49,6 +49,7 @@ impl Directories {
 		try!(fs::create_dir_all(&self.keys).map_err(|e| e.to_string()));
 		try!(fs::create_dir_all(&self.signer).map_err(|e| e.to_string()));
 		try!(fs::create_dir_all(&self.dapps).map_err(|e| e.to_string()));
+		try!(fs::create_dir_all(self.ipc_path()).map_err(|e| e.to_string()));
 		Ok(())
 	}
 
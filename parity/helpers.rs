@@ -17,18 +17,19 @@
 use std::{io, env};
 use std::io::{Write, Read, BufReader, BufRead};
 use std::time::Duration;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::Path;
 use std::fs::File;
 use util::{clean_0x, U256, Uint, Address, path, H256, CompactionProfile};
 use util::journaldb::Algorithm;
 use ethcore::client::{Mode, BlockID, Switch, VMType, DatabaseCompactionProfile, ClientConfig};
-use ethcore::miner::PendingSet;
+use ethcore::miner::{PendingSet, TransactionOrdering};
 use cache::CacheConfig;
 use dir::Directories;
 use params::Pruning;
 use upgrade::upgrade;
 use migration::migrate;
-use ethsync::is_valid_node_url;
+use ethsync::{is_valid_node_url, interface_address};
 
 pub fn to_duration(s: &str) -> Result<Duration, String> {
 	to_seconds(s).map(Duration::from_secs)
@@ -94,6 +95,28 @@ pub fn to_pending_set(s: &str) -> Result<PendingSet, String> {
 	}
 }
 
+pub fn to_tx_ordering(s: &str) -> Result<TransactionOrdering, String> {
+	match s {
+		"gas_price" => Ok(TransactionOrdering::GasPriceThenNonce),
+		"gas_factor" => Ok(TransactionOrdering::GasFactorThenNonce),
+		"fifo" => Ok(TransactionOrdering::FifoThenNonce),
+		other => Err(format!("Invalid transaction ordering value: {:?}", other)),
+	}
+}
+
+/// Resolve the address a server should listen on. `s` may be `all`, `local`,
+/// an IPv4 or IPv6 address, or the name of a network interface.
+pub fn to_interface(s: &str) -> Result<IpAddr, String> {
+	match s {
+		"all" => Ok(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0))),
+		"local" => Ok(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
+		other => other.parse()
+			.ok()
+			.or_else(|| interface_address(other))
+			.ok_or_else(|| format!("Invalid interface: {:?}. Expected all, local, an IP address or a network interface name.", other)),
+	}
+}
+
 pub fn to_address(s: Option<String>) -> Result<Address, String> {
 	match s {
 		Some(ref a) => clean_0x(a).parse().map_err(|_| format!("Invalid address: {:?}", a)),
@@ -167,6 +190,17 @@ pub fn to_bootnodes(bootnodes: &Option<String>) -> Result<Vec<String>, String> {
 	}
 }
 
+/// Validates node identity given with `--identity`.
+pub fn to_identity(identity: &str) -> Result<String, String> {
+	if identity.len() > 32 {
+		return Err(format!("Node identity must be at most 32 characters long: {}", identity));
+	}
+	if identity.chars().any(|c| c == '/' || c < ' ' || c > '~') {
+		return Err(format!("Node identity may only contain printable ASCII characters other than '/': {}", identity));
+	}
+	Ok(identity.to_owned())
+}
+
 #[cfg(test)]
 pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 	use ethsync::NetworkConfiguration;
@@ -178,12 +212,19 @@ pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 		udp_port: None,
 		nat_enabled: true,
 		discovery_enabled: true,
+		discovery_refresh_interval: None,
 		boot_nodes: Vec::new(),
 		use_secret: None,
 		max_peers: 50,
 		min_peers: 25,
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
+		identity: String::new(),
+		dial_backoff: None,
+		max_dial_backoff: None,
+		reserved_dial_interval: None,
+		read_error_grace: None,
+		packet_compression: false,
 	}
 }
 
@@ -300,8 +341,8 @@ mod tests {
 	use std::time::Duration;
 	use util::{U256};
 	use ethcore::client::{Mode, BlockID};
-	use ethcore::miner::PendingSet;
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes};
+	use ethcore::miner::{PendingSet, TransactionOrdering};
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_tx_ordering, to_interface, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, to_identity};
 
 	#[test]
 	fn test_to_duration() {
@@ -359,6 +400,14 @@ mod tests {
 		assert!(to_pending_set("othe").is_err());
 	}
 
+	#[test]
+	fn test_to_tx_ordering() {
+		assert_eq!(to_tx_ordering("gas_price").unwrap(), TransactionOrdering::GasPriceThenNonce);
+		assert_eq!(to_tx_ordering("gas_factor").unwrap(), TransactionOrdering::GasFactorThenNonce);
+		assert_eq!(to_tx_ordering("fifo").unwrap(), TransactionOrdering::FifoThenNonce);
+		assert!(to_tx_ordering("lifo").is_err());
+	}
+
 	#[test]
 	fn test_to_address() {
 		assert_eq!(
@@ -417,5 +466,35 @@ mod tests {
 		assert_eq!(to_bootnodes(&Some(one_bootnode.into())), Ok(vec![one_bootnode.into()]));
 		assert_eq!(to_bootnodes(&Some(two_bootnodes.into())), Ok(vec![one_bootnode.into(), one_bootnode.into()]));
 	}
-}
 
+	#[test]
+	fn test_to_identity() {
+		assert_eq!(to_identity(""), Ok("".into()));
+		assert_eq!(to_identity("my node #1"), Ok("my node #1".into()));
+		assert!(to_identity("my/node").is_err());
+		assert!(to_identity("node\n").is_err());
+		assert!(to_identity("nöde").is_err());
+		assert!(to_identity("abcdefghijklmnopqrstuvwxyz0123456").is_err());
+	}
+
+	#[test]
+	fn test_to_interface() {
+		assert_eq!(to_interface("all"), Ok("0.0.0.0".parse().unwrap()));
+		assert_eq!(to_interface("local"), Ok("127.0.0.1".parse().unwrap()));
+		assert_eq!(to_interface("10.0.0.1"), Ok("10.0.0.1".parse().unwrap()));
+		assert_eq!(to_interface("::1"), Ok("::1".parse().unwrap()));
+		assert!(to_interface("no-such-interface0").is_err());
+		assert!(to_interface("10.0.0.256").is_err());
+	}
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn test_to_interface_name() {
+		use std::net::IpAddr;
+
+		match to_interface("lo").unwrap() {
+			IpAddr::V4(addr) => assert!(addr.is_loopback()),
+			IpAddr::V6(addr) => assert!(addr.is_loopback()),
+		}
+	}
+}
@@ -65,6 +65,9 @@ extern crate lazy_static;
 #[cfg(feature="stratum")]
 extern crate ethcore_stratum;
 
+#[cfg(not(windows))]
+extern crate libc;
+
 #[cfg(feature = "dapps")]
 extern crate ethcore_dapps;
 
@@ -102,6 +105,7 @@ mod blockchain;
 mod presale;
 mod snapshot;
 mod run;
+mod sighup;
 #[cfg(feature="ipc")]
 mod sync;
 #[cfg(feature="ipc")]
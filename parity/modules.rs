@@ -71,21 +71,32 @@ mod ipc_deps {
 	pub use ethsync::{SyncClient, NetworkManagerClient, ServiceConfiguration};
 	pub use ethcore::client::ChainNotifyClient;
 	pub use hypervisor::{SYNC_MODULE_ID, BootArgs, HYPERVISOR_IPC_URL};
+	pub use semver::VersionReq;
 	pub use nanoipc::{GuardedSocket, NanoSocket, generic_client, fast_client};
 	pub use ipc::IpcSocket;
 	pub use ipc::binary::serialize;
+	pub use std::io;
+	pub use std::time::Duration;
 }
 
+/// How long the hypervisor waits for module processes to check in
 #[cfg(feature="ipc")]
-pub fn hypervisor(base_path: &Path) -> Option<Hypervisor> {
-	Some(Hypervisor
-		::with_url(&service_urls::with_base(base_path.to_str().unwrap(), HYPERVISOR_IPC_URL))
-		.io_path(base_path.to_str().unwrap()))
+const MODULE_STARTUP_TIMEOUT_SECS: u64 = 60;
+
+#[cfg(feature="ipc")]
+pub fn hypervisor(base_path: &Path) -> Result<Option<Hypervisor>, String> {
+	let hypervisor = try!(Hypervisor::with_url(&service_urls::with_base(base_path.to_str().unwrap(), HYPERVISOR_IPC_URL))
+		.map_err(|e| format!("Hypervisor error: {}", e)));
+	Ok(Some(hypervisor
+		.io_path(base_path.to_str().unwrap())
+		// modules are run from this very binary, so they must report the same version
+		.required_version(VersionReq::parse(&format!("={}", env!("CARGO_PKG_VERSION"))).expect("crate version is valid semver"))
+		.on_module_failure(|module_id, restarts| warn!("Module {} keeps failing after {} restarts and will not be restarted again", module_id, restarts))))
 }
 
 #[cfg(not(feature="ipc"))]
-pub fn hypervisor(_: &Path) -> Option<Hypervisor> {
-	None
+pub fn hypervisor(_: &Path) -> Result<Option<Hypervisor>, String> {
+	Ok(None)
 }
 
 #[cfg(feature="ipc")]
@@ -132,7 +143,8 @@ pub fn sync
 	hypervisor = hypervisor.module(SYNC_MODULE_ID, args);
 
 	hypervisor.start();
-	hypervisor.wait_for_startup();
+	try!(hypervisor.wait_for_startup(Duration::from_secs(MODULE_STARTUP_TIMEOUT_SECS))
+		.map_err(|e| NetworkError::StdIo(io::Error::new(io::ErrorKind::TimedOut, e.to_string()))));
 
 	let sync_client = generic_client::<SyncClient<_>>(
 		&service_urls::with_base(&hypervisor.io_path, service_urls::SYNC)).unwrap();
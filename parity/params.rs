@@ -156,6 +156,8 @@ pub struct AccountsConfig {
 	pub testnet: bool,
 	pub password_files: Vec<String>,
 	pub unlocked_accounts: Vec<Address>,
+	pub fast_unlock: bool,
+	pub unlock_attempts: u32,
 }
 
 impl Default for AccountsConfig {
@@ -166,6 +168,8 @@ impl Default for AccountsConfig {
 			testnet: false,
 			password_files: Vec::new(),
 			unlocked_accounts: Vec::new(),
+			fast_unlock: false,
+			unlock_attempts: 5,
 		}
 	}
 }
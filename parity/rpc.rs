@@ -16,7 +16,7 @@
 
 use std::fmt;
 use std::sync::Arc;
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use io::PanicHandler;
 use ethcore_rpc::{RpcServerError, RpcServer as Server};
 use jsonipc;
@@ -30,22 +30,24 @@ pub use ethcore_rpc::Server as HttpServer;
 #[derive(Debug, PartialEq)]
 pub struct HttpConfiguration {
 	pub enabled: bool,
-	pub interface: String,
+	pub interface: IpAddr,
 	pub port: u16,
 	pub apis: ApiSet,
 	pub cors: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
+	pub local_senders_only: bool,
 }
 
 impl Default for HttpConfiguration {
 	fn default() -> Self {
 		HttpConfiguration {
 			enabled: true,
-			interface: "127.0.0.1".into(),
+			interface: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
 			port: 8545,
 			apis: ApiSet::UnsafeContext,
 			cors: None,
 			hosts: Some(Vec::new()),
+			local_senders_only: false,
 		}
 	}
 }
@@ -87,14 +89,13 @@ pub fn new_http(conf: HttpConfiguration, deps: &Dependencies) -> Result<Option<H
 		return Ok(None);
 	}
 
-	let url = format!("{}:{}", conf.interface, conf.port);
-	let addr = try!(url.parse().map_err(|_| format!("Invalid JSONRPC listen host/port given: {}", url)));
-	Ok(Some(try!(setup_http_rpc_server(deps, &addr, conf.cors, conf.hosts, conf.apis))))
+	let addr = SocketAddr::new(conf.interface, conf.port);
+	Ok(Some(try!(setup_http_rpc_server(deps, &addr, conf.cors, conf.hosts, conf.apis, conf.local_senders_only))))
 }
 
-fn setup_rpc_server(apis: ApiSet, deps: &Dependencies) -> Result<Server, String> {
+fn setup_rpc_server(apis: ApiSet, deps: &Dependencies, local_senders_only: bool) -> Result<Server, String> {
 	let server = Server::new();
-	Ok(rpc_apis::setup_rpc(server, deps.apis.clone(), apis))
+	Ok(rpc_apis::setup_rpc(server, deps.apis.clone(), apis, local_senders_only))
 }
 
 pub fn setup_http_rpc_server(
@@ -102,9 +103,10 @@ pub fn setup_http_rpc_server(
 	url: &SocketAddr,
 	cors_domains: Option<Vec<String>>,
 	allowed_hosts: Option<Vec<String>>,
-	apis: ApiSet
+	apis: ApiSet,
+	local_senders_only: bool,
 ) -> Result<HttpServer, String> {
-	let server = try!(setup_rpc_server(apis, dependencies));
+	let server = try!(setup_rpc_server(apis, dependencies, local_senders_only));
 	let ph = dependencies.panic_handler.clone();
 	let start_result = server.start_http(url, cors_domains, allowed_hosts, ph);
 	match start_result {
@@ -120,7 +122,8 @@ pub fn new_ipc(conf: IpcConfiguration, deps: &Dependencies) -> Result<Option<Ipc
 }
 
 pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, apis: ApiSet) -> Result<IpcServer, String> {
-	let server = try!(setup_rpc_server(apis, dependencies));
+	// IPC is only reachable from this machine, so any sender may be used for calls.
+	let server = try!(setup_rpc_server(apis, dependencies, false));
 	match server.start_ipc(addr) {
 		Err(jsonipc::Error::Io(io_error)) => Err(format!("RPC io error: {}", io_error)),
 		Err(any_error) => Err(format!("Rpc error: {:?}", any_error)),
@@ -19,13 +19,15 @@ use std::collections::HashSet;
 use std::cmp::PartialEq;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use util::RotatingLogger;
 use ethcore::miner::{Miner, ExternalMiner};
 use ethcore::client::Client;
+use ethcore::snapshot::SnapshotService;
 use ethcore::account_provider::AccountProvider;
 use ethsync::{ManageNetwork, SyncProvider};
-use ethcore_rpc::{Extendable, NetworkSettings};
-pub use ethcore_rpc::ConfirmationsQueue;
+use ethcore_rpc::{Extendable, NetworkSettings, HealthChecks};
+pub use ethcore_rpc::{ConfirmationsQueue, NewHeadsSubscriptions, TimeSource, SntpTimeSource};
 
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
@@ -96,6 +98,7 @@ pub struct Dependencies {
 	pub signer_port: Option<u16>,
 	pub signer_queue: Arc<ConfirmationsQueue>,
 	pub client: Arc<Client>,
+	pub snapshot: Arc<SnapshotService>,
 	pub sync: Arc<SyncProvider>,
 	pub net: Arc<ManageNetwork>,
 	pub secret_store: Arc<AccountProvider>,
@@ -105,6 +108,15 @@ pub struct Dependencies {
 	pub settings: Arc<NetworkSettings>,
 	pub net_service: Arc<ManageNetwork>,
 	pub geth_compatibility: bool,
+	pub rpc_max_accounts: Option<usize>,
+	pub coinbase_fallback: bool,
+	pub call_timeout: Option<Duration>,
+	pub keep_alive_interval: Duration,
+	pub health_min_peers: usize,
+	pub stable_block_confirmations: u64,
+	pub time_source: Option<Arc<TimeSource>>,
+	pub reads_keep_alive: bool,
+	pub new_heads: Arc<NewHeadsSubscriptions>,
 }
 
 fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
@@ -142,7 +154,7 @@ impl ApiSet {
 	}
 }
 
-pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet) -> T {
+pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet, local_senders_only: bool) -> T {
 	use ethcore_rpc::v1::*;
 
 	// it's turned into vector, cause ont of the cases requires &[]
@@ -150,7 +162,7 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 	for api in &apis {
 		match *api {
 			Api::Web3 => {
-				server.add_delegate(Web3Client::new().to_delegate());
+				server.add_delegate(Web3Client::new().with_identity(deps.settings.name.clone()).to_delegate());
 			},
 			Api::Net => {
 				server.add_delegate(NetClient::new(&deps.sync).to_delegate());
@@ -165,11 +177,19 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 					EthClientOptions {
 						allow_pending_receipt_query: !deps.geth_compatibility,
 						send_block_number_in_get_work: !deps.geth_compatibility,
+						reads_keep_alive: deps.reads_keep_alive,
+						max_accounts: deps.rpc_max_accounts,
+						author_account_fallback: deps.coinbase_fallback,
+						local_call_senders_only: local_senders_only,
+						call_timeout: deps.call_timeout,
+						keep_alive_interval: deps.keep_alive_interval,
+						..Default::default()
 					}
 				);
 				server.add_delegate(client.to_delegate());
 
-				let filter_client = EthFilterClient::new(&deps.client, &deps.miner);
+				let filter_client = EthFilterClient::with_keep_alive_interval(&deps.client, &deps.miner, deps.keep_alive_interval)
+					.with_reads_keep_alive(deps.reads_keep_alive);
 				server.add_delegate(filter_client.to_delegate());
 
 				if deps.signer_port.is_some() {
@@ -186,10 +206,17 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 			},
 			Api::Ethcore => {
 				let queue = deps.signer_port.map(|_| deps.signer_queue.clone());
-				server.add_delegate(EthcoreClient::new(&deps.client, &deps.miner, &deps.sync, &deps.net_service, deps.logger.clone(), deps.settings.clone(), queue).to_delegate())
+				server.add_delegate(EthcoreClient::new(&deps.client, &deps.miner, &deps.sync, &deps.net_service, deps.logger.clone(), deps.settings.clone(), queue)
+					.with_new_heads(deps.new_heads.clone())
+					.with_snapshot_service(&deps.snapshot)
+					.with_health_checks(HealthChecks::new(deps.health_min_peers, deps.time_source.clone()))
+					.with_stable_block_confirmations(deps.stable_block_confirmations)
+					.to_delegate())
 			},
 			Api::EthcoreSet => {
-				server.add_delegate(EthcoreSetClient::new(&deps.client, &deps.miner, &deps.net_service).to_delegate())
+				server.add_delegate(EthcoreSetClient::new(&deps.client, &deps.miner, &deps.net_service)
+					.with_snapshot_service(&deps.snapshot)
+					.to_delegate())
 			},
 			Api::Traces => {
 				server.add_delegate(TracesClient::new(&deps.client, &deps.miner).to_delegate())
@@ -15,19 +15,21 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::{Arc, Mutex, Condvar};
+use std::time::Duration;
 use std::io::ErrorKind;
+use std::net::SocketAddr;
 use ctrlc::CtrlC;
 use fdlimit::raise_fd_limit;
-use ethcore_logger::{Config as LogConfig, setup_log};
+use ethcore_logger::{Config as LogConfig, setup_log, set_log_levels};
 use ethcore_rpc::NetworkSettings;
 use ethsync::NetworkConfiguration;
 use util::{Colour, version, U256};
 use io::{MayPanic, ForwardPanic, PanicHandler};
 use ethcore::client::{Mode, Switch, DatabaseCompactionProfile, VMType, ChainNotify};
 use ethcore::service::ClientService;
-use ethcore::account_provider::AccountProvider;
+use ethcore::account_provider::{AccountProvider, UnlockPolicy};
 use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
-use ethcore::snapshot;
+use ethcore::snapshot::{self, SnapshotConfig};
 use ethsync::{SyncConfig, SyncProvider};
 use informant::Informant;
 
@@ -42,6 +44,7 @@ use cache::CacheConfig;
 use dapps;
 use signer;
 use modules;
+use sighup;
 use rpc_apis;
 use rpc;
 use url;
@@ -65,6 +68,7 @@ pub struct RunCmd {
 	pub http_conf: HttpConfiguration,
 	pub ipc_conf: IpcConfiguration,
 	pub net_conf: NetworkConfiguration,
+	pub sync_mem_limit: Option<usize>,
 	pub network_id: Option<U256>,
 	pub acc_conf: AccountsConfig,
 	pub gas_pricer: GasPricerConfig,
@@ -76,6 +80,13 @@ pub struct RunCmd {
 	pub vm_type: VMType,
 	pub enable_network: bool,
 	pub geth_compatibility: bool,
+	pub rpc_max_accounts: Option<usize>,
+	pub coinbase_fallback: bool,
+	pub call_timeout: Option<Duration>,
+	pub keep_alive_interval: Duration,
+	pub health_min_peers: usize,
+	pub stable_block_confirmations: u64,
+	pub ntp_server: Option<String>,
 	pub signer_port: Option<u16>,
 	pub net_settings: NetworkSettings,
 	pub dapps_conf: dapps::Configuration,
@@ -84,6 +95,7 @@ pub struct RunCmd {
 	pub name: String,
 	pub custom_bootnodes: bool,
 	pub no_periodic_snapshot: bool,
+	pub snapshot_conf: SnapshotConfig,
 }
 
 pub fn execute(cmd: RunCmd) -> Result<(), String> {
@@ -137,6 +149,7 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 		None => spec.network_id(),
 	};
 	sync_config.fork_block = spec.fork_block();
+	sync_config.max_mem_use = cmd.sync_mem_limit;
 
 	// prepare account provider
 	let account_provider = Arc::new(try!(prepare_account_provider(&cmd.dirs, cmd.acc_conf)));
@@ -149,8 +162,11 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	miner.set_extra_data(cmd.miner_extras.extra_data);
 	miner.set_transactions_limit(cmd.miner_extras.transactions_limit);
 
+	// in passive modes only calls changing the state keep the client awake
+	let reads_keep_alive = cmd.mode == Mode::Active;
+
 	// create client config
-	let client_config = to_client_config(
+	let mut client_config = to_client_config(
 		&cmd.cache_config,
 		&cmd.dirs,
 		genesis_hash,
@@ -163,6 +179,7 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 		cmd.name,
 		fork_name.as_ref(),
 	);
+	client_config.snapshot = cmd.snapshot_conf.clone();
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -171,7 +188,7 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	}
 
 	// create supervisor
-	let mut hypervisor = modules::hypervisor(&cmd.dirs.ipc_path());
+	let mut hypervisor = try!(modules::hypervisor(&cmd.dirs.ipc_path()));
 
 	// create client service.
 	let service = try!(ClientService::start(
@@ -195,21 +212,44 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 
 	// create sync object
 	let (sync_provider, manage_network, chain_notify) = try!(modules::sync(
-		&mut hypervisor, sync_config, net_conf.into(), client.clone(), snapshot_service, &cmd.logger_config,
+		&mut hypervisor, sync_config, net_conf.into(), client.clone(), snapshot_service.clone(), &cmd.logger_config,
 	).map_err(|e| format!("Sync error: {}", e)));
 
 	service.add_notify(chain_notify.clone());
 
+	// SIGHUP switches the node and its modules to debug logging until the next SIGHUP
+	let module_logging = hypervisor.as_ref().map(|h| h.logging_control());
+	let mut debug_logging = false;
+	sighup::on_sighup(move || {
+		debug_logging = !debug_logging;
+		let levels = match debug_logging {
+			true => Some("debug"),
+			false => None,
+		};
+		info!("Received SIGHUP, debug logging {}", if debug_logging { "enabled" } else { "disabled" });
+		if let Err(e) = set_log_levels(levels) {
+			warn!("Could not change logging levels: {}", e);
+		}
+		if let Some(ref modules) = module_logging {
+			modules.set_logging(levels.unwrap_or(""));
+		}
+	});
+
 	// start network
 	if cmd.enable_network {
 		chain_notify.start();
 	}
 
+	// set up long-poll subscriptions for new blocks
+	let new_heads = Arc::new(rpc_apis::NewHeadsSubscriptions::new(client.clone()));
+	service.add_notify(new_heads.clone());
+
 	// set up dependencies for rpc servers
 	let deps_for_rpc_apis = Arc::new(rpc_apis::Dependencies {
 		signer_port: cmd.signer_port,
 		signer_queue: Arc::new(rpc_apis::ConfirmationsQueue::default()),
 		client: client.clone(),
+		snapshot: snapshot_service.clone(),
 		sync: sync_provider.clone(),
 		net: manage_network.clone(),
 		secret_store: account_provider.clone(),
@@ -219,6 +259,15 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 		settings: Arc::new(cmd.net_settings.clone()),
 		net_service: manage_network.clone(),
 		geth_compatibility: cmd.geth_compatibility,
+		rpc_max_accounts: cmd.rpc_max_accounts,
+		coinbase_fallback: cmd.coinbase_fallback,
+		call_timeout: cmd.call_timeout,
+		keep_alive_interval: cmd.keep_alive_interval,
+		health_min_peers: cmd.health_min_peers,
+		stable_block_confirmations: cmd.stable_block_confirmations,
+		time_source: cmd.ntp_server.as_ref().map(|server| Arc::new(rpc_apis::SntpTimeSource::new(server)) as Arc<rpc_apis::TimeSource>),
+		reads_keep_alive: reads_keep_alive,
+		new_heads: new_heads,
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -284,7 +333,7 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 		if !cmd.dapps_conf.enabled {
 			return Err("Cannot use UI command with Dapps turned off.".into())
 		}
-		url::open(&format!("http://{}:{}/", cmd.dapps_conf.interface, cmd.dapps_conf.port));
+		url::open(&format!("http://{}/", SocketAddr::new(cmd.dapps_conf.interface, cmd.dapps_conf.port)));
 	}
 
 	// Handle exit
@@ -299,6 +348,10 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	// terminated gracefully
 	drop(hypervisor);
 
+	// stop sync and let in-flight block imports finish, the database is closed when the client is dropped
+	manage_network.stop_network();
+	service.shutdown();
+
 	Ok(())
 }
 
@@ -347,13 +400,27 @@ fn prepare_account_provider(dirs: &Directories, cfg: AccountsConfig) -> Result<A
 		try!(EthStore::open_with_iterations(dir, cfg.iterations).map_err(|e| format!("Could not open keys directory: {}", e)))
 	));
 
-	for a in cfg.unlocked_accounts {
-		if passwords.iter().find(|p| account_service.unlock_account_permanently(a, (*p).clone()).is_ok()).is_none() {
-			return Err(format!("No password found to unlock account {}. Make sure valid password is present in files passed using `--password`.", a));
+	{
+		let fast_unlock = cfg.fast_unlock;
+		let unlock = |a, p: &String| match fast_unlock {
+			true => account_service.unlock_account_fast(a, p.clone()),
+			false => account_service.unlock_account_permanently(a, p.clone()),
+		};
+		for a in cfg.unlocked_accounts {
+			if passwords.iter().find(|p| unlock(a, p).is_ok()).is_none() {
+				return Err(format!("No password found to unlock account {}. Make sure valid password is present in files passed using `--password`.", a));
+			}
 		}
 	}
 
-	Ok(account_service)
+	// trying all the password files at startup shouldn't lock the accounts out
+	match cfg.unlock_attempts {
+		0 => Ok(account_service),
+		attempts => Ok(account_service.with_unlock_policy(UnlockPolicy {
+			max_attempts: attempts,
+			..Default::default()
+		})),
+	}
 }
 
 fn wait_for_exit(
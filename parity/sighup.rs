@@ -0,0 +1,58 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SIGHUP handling
+
+#[cfg(not(windows))]
+mod imp {
+	use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT, Ordering};
+	use std::thread;
+	use std::time::Duration;
+	use libc;
+
+	/// How often the handler thread checks for received signals
+	const POLL_INTERVAL_MS: u64 = 200;
+
+	static RECEIVED: AtomicBool = ATOMIC_BOOL_INIT;
+
+	extern "C" fn record_sighup(_: libc::c_int) {
+		RECEIVED.store(true, Ordering::SeqCst);
+	}
+
+	/// Calls the handler from a background thread whenever the process receives SIGHUP.
+	/// Signals received while the handler runs are coalesced.
+	pub fn on_sighup<F>(mut handler: F) where F: FnMut() + Send + 'static {
+		unsafe { libc::signal(libc::SIGHUP, record_sighup as libc::sighandler_t); }
+		thread::Builder::new()
+			.name("sighup".to_owned())
+			.spawn(move || loop {
+				thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+				if RECEIVED.swap(false, Ordering::SeqCst) {
+					handler();
+				}
+			})
+			.expect("Error creating SIGHUP thread");
+	}
+}
+
+#[cfg(windows)]
+mod imp {
+	/// There is no SIGHUP on Windows
+	pub fn on_sighup<F>(_handler: F) where F: FnMut() + Send + 'static {
+	}
+}
+
+pub use self::imp::on_sighup;
@@ -16,6 +16,7 @@
 
 use std::io;
 use std::sync::Arc;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 use ansi_term::Colour;
 use io::{ForwardPanic, PanicHandler};
@@ -31,7 +32,7 @@ const CODES_FILENAME: &'static str = "authcodes";
 pub struct Configuration {
 	pub enabled: bool,
 	pub port: u16,
-	pub interface: String,
+	pub interface: IpAddr,
 	pub signer_path: String,
 	pub skip_origin_validation: bool,
 }
@@ -41,7 +42,7 @@ impl Default for Configuration {
 		Configuration {
 			enabled: true,
 			port: 8180,
-			interface: "127.0.0.1".into(),
+			interface: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
 			signer_path: replace_home("$HOME/.parity/signer"),
 			skip_origin_validation: false,
 		}
@@ -84,9 +85,7 @@ fn generate_new_token(path: String) -> io::Result<String> {
 }
 
 fn do_start(conf: Configuration, deps: Dependencies) -> Result<SignerServer, String> {
-	let addr = try!(format!("{}:{}", conf.interface, conf.port)
-		.parse()
-		.map_err(|_| format!("Invalid port specified: {}", conf.port)));
+	let addr = SocketAddr::new(conf.interface, conf.port);
 
 	let start_result = {
 		let server = signer::ServerBuilder::new(
@@ -98,7 +97,7 @@ fn do_start(conf: Configuration, deps: Dependencies) -> Result<SignerServer, Str
 			info!("If you do not intend this, exit now.");
 		}
 		let server = server.skip_origin_validation(conf.skip_origin_validation);
-		let server = rpc_apis::setup_rpc(server, deps.apis, rpc_apis::ApiSet::SafeContext);
+		let server = rpc_apis::setup_rpc(server, deps.apis, rpc_apis::ApiSet::SafeContext, false);
 		server.start(addr)
 	};
 
@@ -21,13 +21,17 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use ethcore_logger::{setup_log, Config as LogConfig};
-use ethcore::snapshot::{Progress, RestorationStatus, SnapshotService as SS};
-use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter};
+use ethcore::snapshot::{self, Progress, RestorationStatus, SnapshotConfig, SnapshotService as SS};
+use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter, LooseReader};
+use ethcore::snapshot::repair::{ChunkSource, repair_packed};
 use ethcore::snapshot::service::Service as SnapshotService;
 use ethcore::service::ClientService;
 use ethcore::client::{Mode, DatabaseCompactionProfile, Switch, VMType};
 use ethcore::miner::Miner;
 use ethcore::ids::BlockID;
+use ethsync::{EthSync, SyncConfig, NetworkConfiguration, ManageNetwork};
+use util::{U256, H256};
+use util::disk::{available_space, directory_size};
 
 use cache::CacheConfig;
 use params::{SpecType, Pruning};
@@ -43,7 +47,9 @@ pub enum Kind {
 	/// Take a snapshot.
 	Take,
 	/// Restore a snapshot.
-	Restore
+	Restore,
+	/// Repair corrupt chunks of a snapshot.
+	Repair,
 }
 
 /// Command for snapshot creation or restoration.
@@ -61,6 +67,90 @@ pub struct SnapshotCommand {
 	pub wal: bool,
 	pub kind: Kind,
 	pub block_at: BlockID,
+	pub min_free_space: u64,
+	pub force: bool,
+	pub snapshot_conf: SnapshotConfig,
+	/// Snapshots to take valid copies of corrupt chunks from when repairing.
+	/// Corrupt chunks are fetched from peers if there are none.
+	pub repair_sources: Vec<String>,
+	pub net_conf: NetworkConfiguration,
+	pub network_id: Option<U256>,
+	pub custom_bootnodes: bool,
+}
+
+/// Factor by which compressed chunk data is expected to grow once restored into the database.
+const RESTORE_EXPANSION_FACTOR: u64 = 4;
+/// Extra space taken by the database while restoring (write-ahead log, compaction).
+const RESTORE_DB_OVERHEAD: u64 = 256 * 1024 * 1024;
+/// Seconds to wait for peers to provide a corrupt chunk before giving up on the repair.
+const REPAIR_CHUNK_TIMEOUT_SECS: u64 = 120;
+
+/// Source of free disk space information.
+pub trait DiskSpace {
+	/// Get the number of bytes available to unprivileged users at the given path.
+	fn available(&self, path: &Path) -> Result<u64, String>;
+}
+
+/// Disk space information queried from the operating system.
+pub struct SystemDiskSpace;
+
+impl DiskSpace for SystemDiskSpace {
+	fn available(&self, path: &Path) -> Result<u64, String> {
+		available_space(path).map_err(|e| format!("Unable to query free disk space at {}: {}", path.display(), e))
+	}
+}
+
+// find the closest ancestor of the given path which exists, so we can
+// check the space on the device something is about to be created on.
+fn existing_ancestor(path: &Path) -> PathBuf {
+	let mut path = path.to_path_buf();
+	while !path.exists() {
+		if !path.pop() || path.as_os_str().is_empty() {
+			return PathBuf::from(".");
+		}
+	}
+	path
+}
+
+// abort early if there's not enough space available to hold `required` bytes at `path`,
+// unless `force` is set.
+fn check_free_space<D: DiskSpace>(disk: &D, path: &Path, required: u64, force: bool) -> Result<(), String> {
+	let path = existing_ancestor(path);
+	let available = try!(disk.available(&path));
+
+	if available < required {
+		let msg = format!("Insufficient disk space at {}: {} MB available but at least {} MB required.",
+			path.display(), available / (1024 * 1024), required / (1024 * 1024));
+		if !force {
+			return Err(format!("{} Use --force to proceed anyway.", msg));
+		}
+		warn!("{} Proceeding anyway.", msg);
+	}
+
+	Ok(())
+}
+
+// estimate the disk space required to restore the given snapshot into the database.
+fn restore_space_estimate<R: SnapshotReader>(reader: &R) -> u64 {
+	let data = match reader.chunks_size() {
+		Ok(size) => size * RESTORE_EXPANSION_FACTOR,
+		Err(_) => snapshot::estimate_size(reader.manifest()),
+	};
+	data + RESTORE_DB_OVERHEAD
+}
+
+// open a packed snapshot file or a loose snapshot directory to fetch chunks from.
+fn open_chunk_source(path: &Path) -> Result<Box<ChunkSource>, String> {
+	if path.is_dir() {
+		LooseReader::new(path.to_path_buf())
+			.map(|reader| Box::new(reader) as Box<ChunkSource>)
+			.map_err(|e| format!("Couldn't open snapshot directory {}: {}", path.display(), e))
+	} else {
+		PackedReader::new(path)
+			.map_err(|e| format!("Couldn't open snapshot file {}: {}", path.display(), e))
+			.and_then(|x| x.ok_or(format!("Snapshot file {} has invalid format.", path.display())))
+			.map(|reader| Box::new(reader) as Box<ChunkSource>)
+	}
 }
 
 // helper for reading chunks from arbitrary reader and feeding them into the
@@ -69,6 +159,14 @@ fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService>, reader: &R,
 	let manifest = reader.manifest();
 
 	info!("Restoring to block #{} (0x{:?})", manifest.block_number, manifest.block_hash);
+	match manifest.features.is_empty() {
+		true => info!("Snapshot format features: none"),
+		false => info!("Snapshot format features: {}", manifest.features.join(", ")),
+	}
+	match manifest.engine.is_empty() {
+		true => info!("Snapshot engine: unknown"),
+		false => info!("Snapshot engine: {}", manifest.engine),
+	}
 
 	try!(snapshot.init_restore(manifest.clone(), recover).map_err(|e| {
 		format!("Failed to begin restoration: {}", e)
@@ -119,7 +217,7 @@ fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService>, reader: &R,
 
 impl SnapshotCommand {
 	// shared portion of snapshot commands: start the client service
-	fn start_service(self) -> Result<(ClientService, Arc<PanicHandler>), String> {
+	fn start_service(self) -> Result<(ClientService, Arc<PanicHandler>, PathBuf), String> {
 		// Setup panic handler
 		let panic_handler = PanicHandler::new_in_arc();
 
@@ -145,7 +243,8 @@ impl SnapshotCommand {
 		try!(execute_upgrades(&self.dirs, genesis_hash, spec.fork_name.as_ref(), algorithm, self.compaction.compaction_profile()));
 
 		// prepare client config
-		let client_config = to_client_config(&self.cache_config, &self.dirs, genesis_hash, self.mode, self.tracing, self.pruning, self.compaction, self.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
+		let mut client_config = to_client_config(&self.cache_config, &self.dirs, genesis_hash, self.mode, self.tracing, self.pruning, self.compaction, self.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
+		client_config.snapshot = self.snapshot_conf.clone();
 
 		let service = try!(ClientService::start(
 			client_config,
@@ -156,13 +255,16 @@ impl SnapshotCommand {
 			Arc::new(Miner::with_spec(&spec))
 		).map_err(|e| format!("Client service error: {:?}", e)));
 
-		Ok((service, panic_handler))
+		Ok((service, panic_handler, client_path))
 	}
 
 	/// restore from a snapshot
 	pub fn restore(self) -> Result<(), String> {
 		let file = self.file_path.clone();
-		let (service, _panic_handler) = try!(self.start_service());
+		let db_path = PathBuf::from(&self.dirs.db);
+		let min_free_space = self.min_free_space;
+		let force = self.force;
+		let (service, _panic_handler, _) = try!(self.start_service());
 
 		warn!("Snapshot restoration is experimental and the format may be subject to change.");
 		warn!("On encountering an unexpected error, please ensure that you have a recent snapshot.");
@@ -177,16 +279,33 @@ impl SnapshotCommand {
 				.and_then(|x| x.ok_or("Snapshot file has invalid format.".into()));
 
 			let reader = try!(reader);
-			try!(restore_using(snapshot, &reader, true));
+			let required = ::std::cmp::max(min_free_space, restore_space_estimate(&reader));
+			try!(check_free_space(&SystemDiskSpace, &db_path, required, force));
+
+			let res = restore_using(snapshot.clone(), &reader, true);
+			if res.is_err() {
+				// dropping the restoration clears out the partially restored database.
+				snapshot.abort_restore();
+			}
+			try!(res);
 		} else {
 			info!("Attempting to restore from local snapshot.");
 
 			// attempting restoration with recovery will lead to deadlock
 			// as we currently hold a read lock on the service's reader.
-			match *snapshot.reader() {
-				Some(ref reader) => try!(restore_using(snapshot.clone(), reader, false)),
+			let res = match *snapshot.reader() {
+				Some(ref reader) => {
+					let required = ::std::cmp::max(min_free_space, restore_space_estimate(reader));
+					try!(check_free_space(&SystemDiskSpace, &db_path, required, force));
+					restore_using(snapshot.clone(), reader, false)
+				}
 				None => return Err("No local snapshot found.".into()),
+			};
+
+			if res.is_err() {
+				snapshot.abort_restore();
 			}
+			try!(res);
 		}
 
 		Ok(())
@@ -197,10 +316,16 @@ impl SnapshotCommand {
 		let file_path = try!(self.file_path.clone().ok_or("No file path provided.".to_owned()));
 		let file_path: PathBuf = file_path.into();
 		let block_at = self.block_at;
-		let (service, _panic_handler) = try!(self.start_service());
+		let min_free_space = self.min_free_space;
+		let force = self.force;
+		let (service, _panic_handler, client_path) = try!(self.start_service());
 
 		warn!("Snapshots are currently experimental. File formats may be subject to change.");
 
+		// a snapshot is a compressed subset of the database, so its size bounds the snapshot's.
+		let estimate = directory_size(&client_path).unwrap_or(0);
+		try!(check_free_space(&SystemDiskSpace, &file_path, ::std::cmp::max(min_free_space, estimate), force));
+
 		let writer = try!(PackedWriter::new(&file_path)
 			.map_err(|e| format!("Failed to open snapshot writer: {}", e)));
 
@@ -235,6 +360,59 @@ impl SnapshotCommand {
 
 		Ok(())
 	}
+
+	// fetch corrupt chunks from peers offering a snapshot.
+	fn repair_from_peers(self, file_path: &Path) -> Result<Vec<H256>, String> {
+		let spec = try!(self.spec.spec());
+		let mut net_conf = self.net_conf.clone();
+		if !self.custom_bootnodes {
+			net_conf.boot_nodes = spec.nodes.clone();
+		}
+		let mut sync_config = SyncConfig::default();
+		sync_config.network_id = self.network_id.unwrap_or_else(|| spec.network_id());
+		sync_config.fork_block = spec.fork_block();
+
+		let (service, _panic_handler, _) = try!(self.start_service());
+		let sync = try!(EthSync::new(sync_config, service.client(), service.snapshot_service(), net_conf)
+			.map_err(|e| format!("Sync error: {}", e)));
+		sync.start_network();
+
+		info!("Fetching corrupt chunks from peers.");
+		let res = repair_packed(file_path, &sync.chunk_source(Duration::from_secs(REPAIR_CHUNK_TIMEOUT_SECS)));
+		sync.stop_network();
+		res.map_err(|e| format!("Failed to repair snapshot: {}", e))
+	}
+
+	/// Replace corrupt chunks of a packed snapshot with valid copies from the source snapshots,
+	/// which are local snapshot files or directories. Without sources, chunks are fetched from peers.
+	pub fn repair(self) -> Result<(), String> {
+		let file_path = try!(self.file_path.clone().ok_or("No file path provided.".to_owned()));
+		let repaired = if self.repair_sources.is_empty() {
+			// logging is set up along with the client service.
+			try!(self.repair_from_peers(Path::new(&file_path)))
+		} else {
+			let _logger = try!(setup_log(&self.logger_config));
+			let mut sources = Vec::new();
+			for path in &self.repair_sources {
+				sources.push(try!(open_chunk_source(Path::new(path))));
+			}
+
+			try!(repair_packed(Path::new(&file_path), &sources)
+				.map_err(|e| format!("Failed to repair snapshot: {}", e)))
+		};
+
+		match repaired.len() {
+			0 => info!("No corrupt chunks found."),
+			n => {
+				for hash in &repaired {
+					info!("Repaired chunk {}", hash);
+				}
+				info!("Repaired {} corrupt chunks.", n);
+			},
+		}
+
+		Ok(())
+	}
 }
 
 /// Execute this snapshot command.
@@ -242,7 +420,40 @@ pub fn execute(cmd: SnapshotCommand) -> Result<String, String> {
 	match cmd.kind {
 		Kind::Take => try!(cmd.take_snapshot()),
 		Kind::Restore => try!(cmd.restore()),
+		Kind::Repair => try!(cmd.repair()),
 	}
 
 	Ok(String::new())
 }
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+	use super::{DiskSpace, check_free_space};
+
+	struct FixedDiskSpace(u64);
+
+	impl DiskSpace for FixedDiskSpace {
+		fn available(&self, _path: &Path) -> Result<u64, String> {
+			Ok(self.0)
+		}
+	}
+
+	#[test]
+	fn aborts_early_when_space_is_short() {
+		let disk = FixedDiskSpace(100 * 1024 * 1024);
+		let path = Path::new("/this/path/does/not/exist/snapshot");
+
+		assert!(check_free_space(&disk, path, 101 * 1024 * 1024, false).is_err());
+		assert!(check_free_space(&disk, path, 100 * 1024 * 1024, false).is_ok());
+		assert!(check_free_space(&disk, path, 0, false).is_ok());
+	}
+
+	#[test]
+	fn proceeds_when_forced() {
+		let disk = FixedDiskSpace(100 * 1024 * 1024);
+		let path = Path::new("/this/path/does/not/exist/snapshot");
+
+		assert!(check_free_space(&disk, path, 101 * 1024 * 1024, true).is_ok());
+	}
+}
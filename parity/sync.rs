@@ -23,6 +23,7 @@ use ethcore::client::{RemoteClient, ChainNotify};
 use ethcore::snapshot::{RemoteSnapshotService};
 use ethsync::{SyncProvider, EthSync, ManageNetwork, ServiceConfiguration};
 use modules::service_urls;
+use ethcore_logger::set_log_levels;
 use boot;
 use nanoipc;
 
@@ -37,6 +38,17 @@ impl ControlService for SyncControlService {
 		self.stop.store(true, ::std::sync::atomic::Ordering::SeqCst);
 		true
 	}
+
+	fn set_logging(&self, levels: String) -> bool {
+		let levels = if levels.is_empty() { None } else { Some(levels.as_str()) };
+		match set_log_levels(levels) {
+			Ok(_) => true,
+			Err(e) => {
+				warn!(target: "hypervisor", "Could not change logging levels: {}", e);
+				false
+			},
+		}
+	}
 }
 
 pub fn main() {
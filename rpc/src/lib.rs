@@ -53,7 +53,8 @@ use self::jsonrpc_core::{IoHandler, IoDelegate};
 
 pub use jsonrpc_http_server::{ServerBuilder, Server, RpcServerError};
 pub mod v1;
-pub use v1::{SigningQueue, ConfirmationsQueue, NetworkSettings};
+pub use v1::{SigningQueue, ConfirmationsQueue, NetworkSettings, NewHeadsSubscriptions,
+	HealthChecks, TimeSource, SntpTimeSource, DEFAULT_MIN_HEALTHY_PEERS, DEFAULT_SNTP_SERVER};
 
 /// An object that can be extended with `IoDelegates`
 pub trait Extendable {
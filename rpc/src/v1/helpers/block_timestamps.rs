@@ -0,0 +1,185 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Resolution of block timestamps to block numbers.
+
+use std::cmp::min;
+use jsonrpc_core::Error;
+use ethcore::client::{BlockChainClient, BlockID};
+use ethcore::views::HeaderView;
+use util::{U256, Uint};
+use v1::helpers::errors;
+use v1::types::{BlockNumber, Filter, U256 as RpcU256};
+
+fn timestamp_at<C: BlockChainClient>(client: &C, number: u64) -> u64 {
+	client.block_header(BlockID::Number(number))
+		.map_or(u64::max_value(), |header| HeaderView::new(&header).timestamp())
+}
+
+/// Converts RPC timestamp to seconds, saturating at `u64::max_value()`.
+pub fn to_timestamp(timestamp: RpcU256) -> u64 {
+	let timestamp: U256 = timestamp.into();
+	min(timestamp, U256::from(u64::max_value())).low_u64()
+}
+
+/// Returns the number of the first block with timestamp greater or equal to `timestamp`.
+/// Returns `None` if all blocks in the chain are older.
+pub fn first_block_since<C: BlockChainClient>(client: &C, timestamp: u64) -> Option<u64> {
+	let best = client.chain_info().best_block_number;
+	// find the lowest block in `[lo, hi)` satisfying the bound; `hi` stays past the best block if none do
+	let (mut lo, mut hi) = (0, best + 1);
+	while lo < hi {
+		let mid = lo + (hi - lo) / 2;
+		match timestamp_at(client, mid) >= timestamp {
+			true => hi = mid,
+			false => lo = mid + 1,
+		}
+	}
+	match lo > best {
+		true => None,
+		false => Some(lo),
+	}
+}
+
+/// Returns the number of the last block with timestamp lower or equal to `timestamp`.
+/// Returns `None` if all blocks in the chain are newer.
+pub fn last_block_until<C: BlockChainClient>(client: &C, timestamp: u64) -> Option<u64> {
+	match timestamp == u64::max_value() {
+		true => Some(client.chain_info().best_block_number),
+		false => first_block_since(client, timestamp + 1)
+			.map_or(Some(client.chain_info().best_block_number), |first_newer| first_newer.checked_sub(1)),
+	}
+}
+
+/// Replaces `fromTimestamp` and `toTimestamp` bounds of the filter with the block numbers they cover.
+/// Returns `None` if no block lies within the requested time window.
+pub fn resolve_filter_timestamps<C: BlockChainClient>(client: &C, mut filter: Filter) -> Result<Option<Filter>, Error> {
+	if filter.from_block.is_some() && filter.from_timestamp.is_some() {
+		return Err(errors::invalid_params("filter", "Both fromBlock and fromTimestamp specified."));
+	}
+	if filter.to_block.is_some() && filter.to_timestamp.is_some() {
+		return Err(errors::invalid_params("filter", "Both toBlock and toTimestamp specified."));
+	}
+
+	if let Some(timestamp) = filter.from_timestamp.take() {
+		match first_block_since(client, to_timestamp(timestamp)) {
+			Some(number) => filter.from_block = Some(BlockNumber::Num(number)),
+			None => return Ok(None),
+		}
+	}
+	if let Some(timestamp) = filter.to_timestamp.take() {
+		match last_block_until(client, to_timestamp(timestamp)) {
+			Some(number) => filter.to_block = Some(BlockNumber::Num(number)),
+			None => return Ok(None),
+		}
+	}
+
+	match (&filter.from_block, &filter.to_block) {
+		(&Some(BlockNumber::Num(from)), &Some(BlockNumber::Num(to))) if from > to => Ok(None),
+		_ => Ok(Some(filter)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethcore::client::{BlockChainClient, TestBlockChainClient};
+	use ethcore::header::Header;
+	use util::U256;
+	use v1::types::{BlockNumber, Filter};
+	use v1::tests::helpers::empty_block;
+	use super::{first_block_since, last_block_until, resolve_filter_timestamps};
+
+	/// Chain with blocks 1 to 10 mined 15 seconds apart, starting at timestamp 1000.
+	fn client() -> TestBlockChainClient {
+		let client = TestBlockChainClient::new();
+		for n in 1..11 {
+			let mut header = Header::new();
+			header.set_number(n);
+			header.set_parent_hash(client.chain_info().best_block_hash);
+			header.set_timestamp(1000 + (n - 1) * 15);
+			header.set_difficulty(U256::from(n));
+			header.set_gas_limit(U256::from(1_000_000));
+			client.import_block(empty_block(&header)).unwrap();
+		}
+		client
+	}
+
+	fn filter(from_timestamp: Option<u64>, to_timestamp: Option<u64>) -> Filter {
+		Filter {
+			from_block: None,
+			to_block: None,
+			from_timestamp: from_timestamp.map(|t| U256::from(t).into()),
+			to_timestamp: to_timestamp.map(|t| U256::from(t).into()),
+			address: None,
+			topics: None,
+		}
+	}
+
+	#[test]
+	fn should_resolve_timestamps_to_blocks() {
+		let client = client();
+
+		assert_eq!(first_block_since(&client, 0), Some(0));
+		assert_eq!(first_block_since(&client, 1000), Some(1));
+		assert_eq!(first_block_since(&client, 1001), Some(2));
+		assert_eq!(first_block_since(&client, 1135), Some(10));
+		assert_eq!(first_block_since(&client, 1136), None);
+
+		assert_eq!(last_block_until(&client, 999), Some(0));
+		assert_eq!(last_block_until(&client, 1014), Some(1));
+		assert_eq!(last_block_until(&client, 1015), Some(2));
+		assert_eq!(last_block_until(&client, 5000), Some(10));
+	}
+
+	#[test]
+	fn should_resolve_filter_time_window() {
+		let client = client();
+
+		let resolved = resolve_filter_timestamps(&client, filter(Some(1010), Some(1050))).unwrap().unwrap();
+		assert_eq!(resolved.from_block, Some(BlockNumber::Num(2)));
+		assert_eq!(resolved.to_block, Some(BlockNumber::Num(4)));
+		assert_eq!(resolved.from_timestamp, None);
+		assert_eq!(resolved.to_timestamp, None);
+
+		// only the lower bound given
+		let resolved = resolve_filter_timestamps(&client, filter(Some(1120), None)).unwrap().unwrap();
+		assert_eq!(resolved.from_block, Some(BlockNumber::Num(9)));
+		assert_eq!(resolved.to_block, None);
+
+		// window between two blocks or past the best block
+		assert_eq!(resolve_filter_timestamps(&client, filter(Some(1001), Some(1014))).unwrap(), None);
+		assert_eq!(resolve_filter_timestamps(&client, filter(Some(2000), None)).unwrap(), None);
+	}
+
+	#[test]
+	fn should_reject_conflicting_bounds() {
+		let client = client();
+
+		let mut conflicting = filter(Some(1000), None);
+		conflicting.from_block = Some(BlockNumber::Num(1));
+		assert!(resolve_filter_timestamps(&client, conflicting).is_err());
+
+		let mut conflicting = filter(None, Some(1000));
+		conflicting.to_block = Some(BlockNumber::Latest);
+		assert!(resolve_filter_timestamps(&client, conflicting).is_err());
+
+		// block and timestamp bounds on different ends are fine
+		let mut mixed = filter(None, Some(1050));
+		mixed.from_block = Some(BlockNumber::Num(1));
+		let resolved = resolve_filter_timestamps(&client, mixed).unwrap().unwrap();
+		assert_eq!(resolved.to_block, Some(BlockNumber::Num(4)));
+	}
+}
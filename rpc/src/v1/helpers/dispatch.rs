@@ -17,6 +17,7 @@
 use util::{Address, H256, U256, Uint};
 use util::bytes::ToPretty;
 use ethcore::miner::MinerService;
+use ethcore::error::{Error as EthcoreError, TransactionError};
 use ethcore::client::MiningBlockChainClient;
 use ethcore::transaction::{Action, SignedTransaction, Transaction};
 use ethcore::account_provider::AccountProvider;
@@ -45,11 +46,11 @@ pub fn dispatch_transaction<C, M>(client: &C, miner: &M, signed_transaction: Sig
 	where C: MiningBlockChainClient, M: MinerService {
 	let hash = RpcH256::from(signed_transaction.hash());
 
-	let import = miner.import_own_transaction(client, signed_transaction);
-
-	import
-		.map_err(errors::from_transaction_error)
-		.map(|_| to_value(&hash))
+	match miner.import_own_transaction(client, signed_transaction) {
+		// already known transactions are not an error, the sender gets the same hash back.
+		Ok(_) | Err(EthcoreError::Transaction(TransactionError::AlreadyImported)) => Ok(to_value(&hash)),
+		Err(e) => Err(errors::from_transaction_error(e)),
+	}
 }
 
 pub fn signature_with_password(accounts: &AccountProvider, address: Address, hash: H256, pass: String) -> Result<Value, Error> {
@@ -33,6 +33,7 @@ mod codes {
 	pub const NO_NEW_WORK: i64 = -32003;
 	pub const UNKNOWN_ERROR: i64 = -32009;
 	pub const TRANSACTION_ERROR: i64 = -32010;
+	pub const EXECUTION_ERROR: i64 = -32015;
 	pub const ACCOUNT_LOCKED: i64 = -32020;
 	pub const PASSWORD_INVALID: i64 = -32021;
 	pub const ACCOUNT_ERROR: i64 = -32023;
@@ -40,7 +41,10 @@ mod codes {
 	pub const REQUEST_REJECTED: i64 = -32040;
 	pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
 	pub const REQUEST_NOT_FOUND: i64 = -32042;
+	pub const REQUEST_EXPIRED: i64 = -32043;
+	pub const REQUEST_FORBIDDEN: i64 = -32044;
 	pub const COMPILATION_ERROR: i64 = -32050;
+	pub const SNAPSHOT_ERROR: i64 = -32060;
 }
 
 pub fn unimplemented() -> Error {
@@ -67,6 +71,22 @@ pub fn request_rejected() -> Error {
 	}
 }
 
+pub fn request_expired() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_EXPIRED),
+		message: "Request has expired before being confirmed.".into(),
+		data: None,
+	}
+}
+
+pub fn request_forbidden() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_FORBIDDEN),
+		message: "Request has been created by a different account.".into(),
+		data: None,
+	}
+}
+
 pub fn request_rejected_limit() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -99,6 +119,14 @@ pub fn internal<T: fmt::Debug>(error: &str, data: T) -> Error {
 	}
 }
 
+pub fn snapshot(error: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::SNAPSHOT_ERROR),
+		message: format!("Snapshot error: {}", error),
+		data: None,
+	}
+}
+
 pub fn invalid_params<T: fmt::Debug>(param: &str, details: T) -> Error {
 	Error {
 		code: ErrorCode::InvalidParams,
@@ -107,6 +135,38 @@ pub fn invalid_params<T: fmt::Debug>(param: &str, details: T) -> Error {
 	}
 }
 
+pub fn param_over_limit<T: fmt::Display>(param: &str, limit: T) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: format!("Parameter {} exceeds the limit of {}", param, limit),
+		data: None,
+	}
+}
+
+pub fn non_local_sender() -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: "Calls are only allowed from local accounts".into(),
+		data: None,
+	}
+}
+
+pub fn transaction_index_out_of_range(index: usize, count: usize) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: format!("Transaction index {} is out of range, the block has {} transactions.", index, count),
+		data: None,
+	}
+}
+
+pub fn execution(error: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
+		message: format!("Transaction execution error: {}.", error),
+		data: None,
+	}
+}
+
 pub fn state_pruned() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
@@ -164,6 +224,14 @@ pub fn from_password_error(error: AccountError) -> Error {
 	}
 }
 
+pub fn unsupported_transaction_type(transaction_type: u8) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
+		message: format!("Transaction type 0x{:02x} is not supported.", transaction_type),
+		data: None,
+	}
+}
+
 pub fn from_transaction_error(error: EthcoreError) -> Error {
 	use ethcore::error::TransactionError::*;
 
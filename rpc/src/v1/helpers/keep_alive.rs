@@ -0,0 +1,82 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Throttling of client keep-alive notifications.
+
+use std::time::{Instant, Duration};
+use util::Mutex;
+
+/// Default minimal interval between two keep-alive notifications.
+pub const DEFAULT_KEEP_ALIVE_INTERVAL_SECS: u64 = 30;
+
+/// Decides whether the client should be told to stay active,
+/// letting through at most one notification per interval.
+pub struct KeepAliveThrottle {
+	interval: Duration,
+	last: Mutex<Option<Instant>>,
+}
+
+impl Default for KeepAliveThrottle {
+	fn default() -> Self {
+		KeepAliveThrottle::new(Duration::from_secs(DEFAULT_KEEP_ALIVE_INTERVAL_SECS))
+	}
+}
+
+impl KeepAliveThrottle {
+	/// Creates new throttle with given minimal interval between notifications.
+	pub fn new(interval: Duration) -> Self {
+		KeepAliveThrottle {
+			interval: interval,
+			last: Mutex::new(None),
+		}
+	}
+
+	/// Returns true if keep-alive should be sent now.
+	/// In such case the next one will be let through after the interval elapses.
+	pub fn should_notify(&self) -> bool {
+		let now = Instant::now();
+		let mut last = self.last.lock();
+		match *last {
+			Some(ref time) if now.duration_since(*time) < self.interval => false,
+			_ => {
+				*last = Some(now);
+				true
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::KeepAliveThrottle;
+
+	#[test]
+	fn should_notify_once_per_interval() {
+		let throttle = KeepAliveThrottle::new(Duration::from_secs(3600));
+		assert!(throttle.should_notify());
+		for _ in 0..100 {
+			assert!(!throttle.should_notify());
+		}
+	}
+
+	#[test]
+	fn should_always_notify_without_interval() {
+		let throttle = KeepAliveThrottle::new(Duration::from_secs(0));
+		assert!(throttle.should_notify());
+		assert!(throttle.should_notify());
+	}
+}
@@ -23,9 +23,18 @@ mod poll_filter;
 mod requests;
 mod signing_queue;
 mod network_settings;
+mod keep_alive;
+mod block_timestamps;
+mod new_heads;
+mod node_health;
+pub mod typed_data;
 
 pub use self::poll_manager::PollManager;
 pub use self::poll_filter::PollFilter;
 pub use self::requests::{TransactionRequest, FilledTransactionRequest, ConfirmationRequest, ConfirmationPayload, CallRequest};
-pub use self::signing_queue::{ConfirmationsQueue, ConfirmationPromise, ConfirmationResult, SigningQueue, QueueEvent};
+pub use self::signing_queue::{ConfirmationsQueue, ConfirmationPromise, ConfirmationResult, SigningQueue, QueueEvent, QueueReplaceError};
 pub use self::network_settings::NetworkSettings;
+pub use self::keep_alive::{KeepAliveThrottle, DEFAULT_KEEP_ALIVE_INTERVAL_SECS};
+pub use self::block_timestamps::{resolve_filter_timestamps, last_block_until, to_timestamp};
+pub use self::new_heads::{NewHeadsSubscriptions, DEFAULT_MAX_NEW_HEADS_SUBSCRIPTIONS};
+pub use self::node_health::{HealthChecks, TimeSource, SntpTimeSource, DEFAULT_MIN_HEALTHY_PEERS, DEFAULT_SNTP_SERVER};
@@ -0,0 +1,92 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Long-poll subscriptions for newly imported blocks.
+
+use std::mem;
+use std::sync::{Arc, Weak};
+use rlp;
+use jsonrpc_core::{Ready, to_value};
+use util::{H256, Mutex};
+use ethcore::client::{BlockChainClient, BlockID, ChainNotify};
+use ethcore::header::Header as EthHeader;
+use v1::helpers::errors;
+use v1::types::Header;
+
+/// Default limit of pending subscriptions.
+pub const DEFAULT_MAX_NEW_HEADS_SUBSCRIPTIONS: usize = 256;
+
+/// Pending requests waiting for the next imported block.
+/// Every request is completed with the header of the new best block.
+pub struct NewHeadsSubscriptions {
+	client: Weak<BlockChainClient>,
+	pending: Mutex<Vec<Ready>>,
+	limit: usize,
+}
+
+impl NewHeadsSubscriptions {
+	/// Creates new subscriptions queue with default limit.
+	pub fn new(client: Arc<BlockChainClient>) -> Self {
+		Self::with_limit(client, DEFAULT_MAX_NEW_HEADS_SUBSCRIPTIONS)
+	}
+
+	/// Creates new subscriptions queue accepting at most `limit` pending requests.
+	pub fn with_limit(client: Arc<BlockChainClient>, limit: usize) -> Self {
+		NewHeadsSubscriptions {
+			client: Arc::downgrade(&client),
+			pending: Mutex::new(Vec::new()),
+			limit: limit,
+		}
+	}
+
+	/// Registers a request to be completed when the next block is imported.
+	/// The request is rejected immediately if the limit of pending requests is reached.
+	pub fn subscribe(&self, ready: Ready) {
+		let mut pending = self.pending.lock();
+		if pending.len() >= self.limit {
+			ready.ready(Err(errors::request_rejected_limit()));
+			return;
+		}
+		pending.push(ready);
+	}
+
+	/// Number of requests waiting for a new block.
+	pub fn pending(&self) -> usize {
+		self.pending.lock().len()
+	}
+}
+
+impl ChainNotify for NewHeadsSubscriptions {
+	fn new_blocks(&self, _imported: Vec<H256>, _invalid: Vec<H256>, enacted: Vec<H256>, _retracted: Vec<H256>, _sealed: Vec<H256>, _duration: u64) {
+		let hash = match enacted.last() {
+			Some(hash) => hash.clone(),
+			None => return,
+		};
+		let client = match self.client.upgrade() {
+			Some(client) => client,
+			None => return,
+		};
+		let header = match client.block_header(BlockID::Hash(hash)) {
+			Some(bytes) => to_value(&Header::from(rlp::decode::<EthHeader>(&bytes))),
+			None => return,
+		};
+
+		let pending = mem::replace(&mut *self.pending.lock(), Vec::new());
+		for ready in pending {
+			ready.ready(Ok(header.clone()));
+		}
+	}
+}
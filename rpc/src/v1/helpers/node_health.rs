@@ -0,0 +1,201 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node health checks for load balancer probes.
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use util::Mutex;
+use util::sntp;
+use ethsync::SyncStatus;
+use v1::types::{NodeHealth, HealthCheck, TimeHealth};
+
+/// Default minimal number of connected peers of a healthy node.
+pub const DEFAULT_MIN_HEALTHY_PEERS: usize = 1;
+/// Default SNTP server used to measure the clock drift.
+pub const DEFAULT_SNTP_SERVER: &'static str = "pool.ntp.org:123";
+
+/// Maximal clock drift of a healthy node.
+const MAX_DRIFT_MS: i64 = 10_000;
+/// Sync is considered stalled if no block was imported for this long while syncing.
+const SYNC_STALL_SECS: u64 = 60;
+/// How long an SNTP measurement is reused.
+const SNTP_CACHE_SECS: u64 = 300;
+const SNTP_TIMEOUT_MS: u64 = 1_000;
+
+/// Source of the local clock drift.
+pub trait TimeSource: Send + Sync {
+	/// Drift of the local clock in milliseconds. Positive if the local clock is ahead.
+	fn drift_ms(&self) -> Result<i64, String>;
+}
+
+/// Measures the clock drift against an SNTP server.
+///
+/// Queries run on a background thread so that health probes never wait for the network;
+/// the last measurement is reported and refreshed once it is a few minutes old.
+pub struct SntpTimeSource {
+	server: String,
+	state: Arc<Mutex<SntpState>>,
+}
+
+#[derive(Default)]
+struct SntpState {
+	measured: Option<(Instant, Result<i64, String>)>,
+	updating: bool,
+}
+
+impl SntpTimeSource {
+	/// Creates a time source querying the given server (`host:port`) and starts the first measurement.
+	pub fn new(server: &str) -> Self {
+		let source = SntpTimeSource {
+			server: server.to_owned(),
+			state: Arc::new(Mutex::new(SntpState::default())),
+		};
+		{
+			let mut state = source.state.lock();
+			source.refresh(&mut state);
+		}
+		source
+	}
+
+	fn refresh(&self, state: &mut SntpState) {
+		let fresh = state.measured.as_ref().map_or(false, |&(ref time, _)| time.elapsed() < Duration::from_secs(SNTP_CACHE_SECS));
+		if fresh || state.updating {
+			return;
+		}
+
+		let server = self.server.clone();
+		let shared = self.state.clone();
+		let spawned = thread::Builder::new().name("sntp".into()).spawn(move || {
+			let drift = sntp::clock_offset_ms(&*server, Duration::from_millis(SNTP_TIMEOUT_MS))
+				.map_err(|e| format!("Could not query {}: {}", server, e));
+			let mut state = shared.lock();
+			state.measured = Some((Instant::now(), drift));
+			state.updating = false;
+		});
+		match spawned {
+			Ok(_) => state.updating = true,
+			Err(e) => warn!(target: "rpc", "Could not start clock drift measurement: {}", e),
+		}
+	}
+}
+
+impl TimeSource for SntpTimeSource {
+	fn drift_ms(&self) -> Result<i64, String> {
+		let mut state = self.state.lock();
+		self.refresh(&mut state);
+		match state.measured {
+			Some((_, ref drift)) => drift.clone(),
+			None => Err(format!("Clock drift against {} not measured yet", self.server)),
+		}
+	}
+}
+
+/// Evaluates peer, sync and clock health.
+pub struct HealthChecks {
+	min_peers: usize,
+	stall_timeout: Duration,
+	time: Option<Arc<TimeSource>>,
+	/// Best block seen and the time it was first seen
+	last_import: Mutex<Option<(u64, Instant)>>,
+}
+
+impl HealthChecks {
+	/// Creates health checks requiring at least `min_peers` connected peers.
+	/// The clock is not checked if `time` is `None`.
+	pub fn new(min_peers: usize, time: Option<Arc<TimeSource>>) -> Self {
+		HealthChecks {
+			min_peers: min_peers,
+			stall_timeout: Duration::from_secs(SYNC_STALL_SECS),
+			time: time,
+			last_import: Mutex::new(None),
+		}
+	}
+
+	/// Sets the time without imported blocks after which syncing is considered stalled.
+	pub fn with_stall_timeout(mut self, timeout: Duration) -> Self {
+		self.stall_timeout = timeout;
+		self
+	}
+
+	/// Evaluates all checks given the sync status and the current best block.
+	pub fn check(&self, status: &SyncStatus, best_block: u64) -> NodeHealth {
+		NodeHealth {
+			peers: self.check_peers(status),
+			sync: self.check_sync(status, best_block),
+			time: self.check_time(),
+		}
+	}
+
+	fn check_peers(&self, status: &SyncStatus) -> HealthCheck {
+		HealthCheck {
+			ok: status.num_peers >= self.min_peers,
+			details: format!("Connected to {} peers, at least {} required", status.num_peers, self.min_peers),
+		}
+	}
+
+	fn check_sync(&self, status: &SyncStatus, best_block: u64) -> HealthCheck {
+		let now = Instant::now();
+		let mut last_import = self.last_import.lock();
+		let since_import = match *last_import {
+			Some((block, time)) if block == best_block => now.duration_since(time),
+			_ => {
+				*last_import = Some((best_block, now));
+				Duration::from_secs(0)
+			},
+		};
+
+		if !status.is_major_syncing() {
+			return HealthCheck { ok: true, details: format!("Synced at block #{}", best_block) };
+		}
+
+		let highest = status.highest_block_number.map_or_else(|| "unknown".to_owned(), |n| format!("#{}", n));
+		match since_import > self.stall_timeout {
+			true => HealthCheck {
+				ok: false,
+				details: format!("Syncing stalled at block #{} for {}s, highest block {}", best_block, since_import.as_secs(), highest),
+			},
+			false => HealthCheck {
+				ok: true,
+				details: format!("Syncing at block #{}, highest block {}", best_block, highest),
+			},
+		}
+	}
+
+	fn check_time(&self) -> TimeHealth {
+		let time = match self.time {
+			Some(ref time) => time,
+			None => return TimeHealth {
+				ok: true,
+				drift_ms: None,
+				details: "Clock drift check disabled".into(),
+			},
+		};
+		match time.drift_ms() {
+			Ok(drift) => TimeHealth {
+				ok: drift.abs() <= MAX_DRIFT_MS,
+				drift_ms: Some(drift),
+				details: format!("Local clock is {}ms {}, at most {}ms allowed", drift.abs(), if drift > 0 { "ahead" } else { "behind" }, MAX_DRIFT_MS),
+			},
+			Err(e) => TimeHealth {
+				ok: false,
+				drift_ms: None,
+				details: e,
+			},
+		}
+	}
+}
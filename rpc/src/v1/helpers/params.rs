@@ -44,6 +44,14 @@ pub fn from_params_default_second<F>(params: Params) -> Result<(F, BlockNumber,
 	}
 }
 
+/// Deserialize request parameters with optional second parameter.
+pub fn from_params_optional_second<F1, F2>(params: Params) -> Result<(F1, Option<F2>, ), Error> where F1: serde::de::Deserialize, F2: serde::de::Deserialize {
+	match params_len(&params) {
+		1 => from_params::<(F1, )>(params).map(|(f1,)| (f1, None)),
+		_ => from_params::<(F1, F2)>(params).map(|(f1, f2)| (f1, Some(f2))),
+	}
+}
+
 /// Deserialize request parameters with optional third parameter `BlockNumber` defaulting to `BlockNumber::Latest`.
 pub fn from_params_default_third<F1, F2>(params: Params) -> Result<(F1, F2, BlockNumber, ), Error> where F1: serde::de::Deserialize, F2: serde::de::Deserialize {
 	match params_len(&params) {
@@ -16,6 +16,8 @@
 
 //! Indexes all rpc poll requests.
 
+use std::collections::HashMap;
+use time::get_time;
 use transient_hashmap::{TransientHashMap, Timer, StandardTimer};
 
 /// Lifetime of poll (in seconds).
@@ -23,11 +25,31 @@ const POLL_LIFETIME: u64 = 60;
 
 pub type PollId = usize;
 
+/// Bookkeeping of a single poll.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollInfo {
+	/// Unix timestamp of poll creation.
+	pub created: u64,
+	/// Unix timestamp of the last access to the poll.
+	pub last_poll: u64,
+}
+
+impl PollInfo {
+	fn new() -> Self {
+		let now = get_time().sec as u64;
+		PollInfo {
+			created: now,
+			last_poll: now,
+		}
+	}
+}
+
 /// Indexes all poll requests.
 ///
 /// Lazily garbage collects unused polls info.
 pub struct PollManager<F, T = StandardTimer> where T: Timer {
 	polls: TransientHashMap<PollId, F, T>,
+	info: HashMap<PollId, PollInfo>,
 	next_available_id: PollId,
 }
 
@@ -43,6 +65,7 @@ impl<F, T> PollManager<F, T> where T: Timer {
 	pub fn new_with_timer(timer: T) -> Self {
 		PollManager {
 			polls: TransientHashMap::new_with_timer(POLL_LIFETIME, timer),
+			info: HashMap::new(),
 			next_available_id: 0,
 		}
 	}
@@ -51,10 +74,11 @@ impl<F, T> PollManager<F, T> where T: Timer {
 	///
 	/// Stores information when last poll happend.
 	pub fn create_poll(&mut self, filter: F) -> PollId {
-		self.polls.prune();
+		self.prune();
 
 		let id = self.next_available_id;
 		self.polls.insert(id, filter);
+		self.info.insert(id, PollInfo::new());
 
 		self.next_available_id += 1;
 		id
@@ -63,19 +87,50 @@ impl<F, T> PollManager<F, T> where T: Timer {
 	// Implementation is always using `poll_mut`
 	/// Get a reference to stored poll filter
 	pub fn poll(&mut self, id: &PollId) -> Option<&F> {
-		self.polls.prune();
+		self.prune();
+		self.note_poll(id);
 		self.polls.get(id)
 	}
 
 	/// Get a mutable reference to stored poll filter
 	pub fn poll_mut(&mut self, id: &PollId) -> Option<&mut F> {
-		self.polls.prune();
+		self.prune();
+		self.note_poll(id);
 		self.polls.get_mut(id)
 	}
 
+	/// Returns all active polls without extending their lifetime.
+	pub fn polls(&mut self) -> Vec<(PollId, PollInfo, &F)> {
+		self.prune();
+		let polls = self.polls.direct();
+		let mut result: Vec<_> = self.info.iter()
+			.filter_map(|(id, info)| polls.get(id).map(|filter| (*id, *info, filter)))
+			.collect();
+		result.sort_by_key(|&(id, _, _)| id);
+		result
+	}
+
 	/// Removes poll info.
 	pub fn remove_poll(&mut self, id: &PollId) {
 		self.polls.remove(id);
+		self.info.remove(id);
+	}
+
+	fn note_poll(&mut self, id: &PollId) {
+		if let Some(info) = self.info.get_mut(id) {
+			info.last_poll = get_time().sec as u64;
+		}
+	}
+
+	fn prune(&mut self) {
+		self.polls.prune();
+		let expired: Vec<PollId> = {
+			let polls = self.polls.direct();
+			self.info.keys().filter(|id| !polls.contains_key(id)).cloned().collect()
+		};
+		for id in expired {
+			self.info.remove(&id);
+		}
 	}
 }
 
@@ -123,4 +178,26 @@ mod tests {
 		assert!(indexer.poll(&1).is_none());
 	}
 
+	#[test]
+	fn test_poll_listing() {
+		let time = Cell::new(0);
+		let timer = TestTimer {
+			time: &time,
+		};
+
+		let mut indexer = PollManager::new_with_timer(timer);
+		indexer.create_poll(20);
+		indexer.create_poll(21);
+		indexer.create_poll(22);
+		indexer.remove_poll(&1);
+
+		time.set(50);
+		let polls: Vec<_> = indexer.polls().into_iter().map(|(id, _, filter)| (id, *filter)).collect();
+		assert_eq!(polls, vec![(0, 20), (2, 22)]);
+
+		// listing doesn't keep polls alive
+		time.set(75);
+		assert!(indexer.polls().is_empty());
+	}
+
 }
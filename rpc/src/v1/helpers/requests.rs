@@ -104,3 +104,13 @@ pub enum ConfirmationPayload {
 	/// Sign request
 	Sign(Address, H256),
 }
+
+impl ConfirmationPayload {
+	/// Account on behalf of which the request is made.
+	pub fn sender(&self) -> Address {
+		match *self {
+			ConfirmationPayload::Transaction(ref request) => request.from,
+			ConfirmationPayload::Sign(address, _) => address,
+		}
+	}
+}
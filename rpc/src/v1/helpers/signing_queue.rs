@@ -18,6 +18,7 @@ use std::mem;
 use std::cell::RefCell;
 use std::sync::{mpsc, Arc};
 use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 use jsonrpc_core;
 use util::{Mutex, RwLock, U256};
 use v1::helpers::{ConfirmationRequest, ConfirmationPayload};
@@ -36,6 +37,10 @@ pub enum QueueEvent {
 	RequestRejected(U256),
 	/// Request resolved.
 	RequestConfirmed(U256),
+	/// Request was not resolved before its time to live elapsed.
+	RequestExpired(U256),
+	/// Request payload was replaced by the account which issued it.
+	RequestReplaced(U256),
 }
 
 /// Defines possible errors returned from queue receiving method.
@@ -53,6 +58,15 @@ pub enum QueueAddError {
 	LimitReached,
 }
 
+/// Defines possible errors when replacing a request
+#[derive(Debug, PartialEq)]
+pub enum QueueReplaceError {
+	/// There is no pending request with given id.
+	NotFound,
+	/// The replacement is made on behalf of a different account than the request.
+	OriginMismatch,
+}
+
 /// Message Receiver type
 pub type QueueEventReceiver = mpsc::Receiver<QueueEvent>;
 
@@ -63,7 +77,22 @@ const QUEUE_LIMIT: usize = 50;
 pub trait SigningQueue: Send + Sync {
 	/// Add new request to the queue.
 	/// Returns a `ConfirmationPromise` that can be used to await for resolution of given request.
-	fn add_request(&self, request: ConfirmationPayload) -> Result<ConfirmationPromise, QueueAddError>;
+	fn add_request(&self, request: ConfirmationPayload) -> Result<ConfirmationPromise, QueueAddError> {
+		self.add_request_with_ttl(request, None)
+	}
+
+	/// Add new request to the queue.
+	/// If `ttl` is given the request expires unless it is resolved within that time.
+	fn add_request_with_ttl(&self, request: ConfirmationPayload, ttl: Option<Duration>) -> Result<ConfirmationPromise, QueueAddError>;
+
+	/// Replaces the payload of a pending request.
+	/// RPC calls carry no session information, so the account a request is made on behalf of is its origin.
+	/// Fails if the request does not exist or the new payload is issued by a different account.
+	fn replace_request(&self, id: U256, request: ConfirmationPayload) -> Result<(), QueueReplaceError>;
+
+	/// Removes all requests which outlived their time to live.
+	/// Notifies possible token holders that requests have expired.
+	fn expire_requests(&self);
 
 	/// Removes a request from the queue.
 	/// Notifies possible token holders that request was rejected.
@@ -93,6 +122,8 @@ pub enum ConfirmationResult {
 	Waiting,
 	/// The request has been rejected.
 	Rejected,
+	/// The request has not been resolved before its time to live elapsed.
+	Expired,
 	/// The request has been confirmed.
 	Confirmed(RpcResult),
 }
@@ -105,6 +136,7 @@ pub struct ConfirmationToken {
 	result: Arc<Mutex<ConfirmationResult>>,
 	listeners: Arc<Mutex<Vec<Listener>>>,
 	request: ConfirmationRequest,
+	expires: Option<Instant>,
 }
 
 pub struct ConfirmationPromise {
@@ -117,6 +149,15 @@ impl ConfirmationToken {
 	/// Submit solution to all listeners
 	fn resolve(&self, result: Option<RpcResult>) {
 		let wrapped = result.clone().map_or(ConfirmationResult::Rejected, |h| ConfirmationResult::Confirmed(h));
+		self.resolve_as(wrapped, result);
+	}
+
+	/// Mark request as expired. Listeners are notified the same way as for rejection.
+	fn expire(&self) {
+		self.resolve_as(ConfirmationResult::Expired, None);
+	}
+
+	fn resolve_as(&self, wrapped: ConfirmationResult, result: Option<RpcResult>) {
 		{
 			let mut res = self.result.lock();
 			*res = wrapped.clone();
@@ -246,7 +287,8 @@ impl Drop for ConfirmationsQueue {
 }
 
 impl SigningQueue for ConfirmationsQueue {
-	fn add_request(&self, request: ConfirmationPayload) -> Result<ConfirmationPromise, QueueAddError> {
+	fn add_request_with_ttl(&self, request: ConfirmationPayload, ttl: Option<Duration>) -> Result<ConfirmationPromise, QueueAddError> {
+		self.expire_requests();
 		if self.len() > QUEUE_LIMIT {
 			return Err(QueueAddError::LimitReached);
 		}
@@ -270,6 +312,7 @@ impl SigningQueue for ConfirmationsQueue {
 					id: id,
 					payload: request,
 				},
+				expires: ttl.and_then(|ttl| Instant::now().checked_add(ttl)),
 			});
 			queue.get(&id).map(|token| token.as_promise()).expect("Token was just inserted.")
 		};
@@ -278,7 +321,41 @@ impl SigningQueue for ConfirmationsQueue {
 		Ok(res)
 	}
 
+	fn replace_request(&self, id: U256, request: ConfirmationPayload) -> Result<(), QueueReplaceError> {
+		self.expire_requests();
+		{
+			let mut queue = self.queue.write();
+			let token = try!(queue.get_mut(&id).ok_or(QueueReplaceError::NotFound));
+			if token.request.payload.sender() != request.sender() {
+				return Err(QueueReplaceError::OriginMismatch);
+			}
+			debug!(target: "own_tx", "Signer: Request replaced ({:?}).", id);
+			trace!(target: "own_tx", "Signer: ({:?}) : {:?}", id, request);
+			token.request.payload = request;
+		}
+		self.notify(QueueEvent::RequestReplaced(id));
+		Ok(())
+	}
+
+	fn expire_requests(&self) {
+		let now = Instant::now();
+		let expired: Vec<_> = {
+			let mut queue = self.queue.write();
+			let ids: Vec<_> = queue.iter()
+				.filter(|&(_, token)| token.expires.map_or(false, |expires| expires <= now))
+				.map(|(id, _)| *id)
+				.collect();
+			ids.into_iter().filter_map(|id| queue.remove(&id)).collect()
+		};
+		for token in expired {
+			debug!(target: "own_tx", "Signer: Request expired ({:?}).", token.request.id);
+			self.notify(QueueEvent::RequestExpired(token.request.id));
+			token.expire();
+		}
+	}
+
 	fn peek(&self, id: &U256) -> Option<ConfirmationRequest> {
+		self.expire_requests();
 		self.queue.read().get(id).map(|token| token.request.clone())
 	}
 
@@ -293,6 +370,7 @@ impl SigningQueue for ConfirmationsQueue {
 	}
 
 	fn requests(&self) -> Vec<ConfirmationRequest> {
+		self.expire_requests();
 		let queue = self.queue.read();
 		queue.values().map(|token| token.request.clone()).collect()
 	}
@@ -315,7 +393,7 @@ mod test {
 	use std::thread;
 	use std::sync::{mpsc, Arc};
 	use util::{Address, U256, H256, Mutex};
-	use v1::helpers::{SigningQueue, ConfirmationsQueue, QueueEvent, FilledTransactionRequest, ConfirmationPayload};
+	use v1::helpers::{SigningQueue, ConfirmationsQueue, ConfirmationResult, QueueEvent, QueueReplaceError, FilledTransactionRequest, ConfirmationPayload};
 	use v1::types::H256 as NH256;
 	use jsonrpc_core::to_value;
 
@@ -400,4 +478,66 @@ mod test {
 		assert_eq!(el.id, U256::from(1));
 		assert_eq!(el.payload, request);
 	}
+
+	#[test]
+	fn should_expire_requests() {
+		// given
+		let queue = ConfirmationsQueue::default();
+		let expiring = queue.add_request_with_ttl(request(), Some(Duration::from_secs(0))).unwrap();
+		let lasting = queue.add_request_with_ttl(request(), Some(Duration::from_secs(3600))).unwrap();
+
+		// when
+		let all = queue.requests();
+
+		// then
+		assert_eq!(all.len(), 1);
+		assert_eq!(all[0].id, lasting.id());
+		assert_eq!(expiring.result(), ConfirmationResult::Expired);
+		assert_eq!(lasting.result(), ConfirmationResult::Waiting);
+	}
+
+	#[test]
+	fn should_not_expire_requests_with_unrepresentable_ttl() {
+		// given
+		let queue = ConfirmationsQueue::default();
+		let promise = queue.add_request_with_ttl(request(), Some(Duration::from_secs(u64::max_value()))).unwrap();
+
+		// when
+		let all = queue.requests();
+
+		// then
+		assert_eq!(all.len(), 1);
+		assert_eq!(promise.result(), ConfirmationResult::Waiting);
+	}
+
+	#[test]
+	fn should_replace_request_from_same_account() {
+		// given
+		let queue = ConfirmationsQueue::default();
+		let promise = queue.add_request(request()).unwrap();
+		let replacement = ConfirmationPayload::Sign(Address::from(1), H256::from(5));
+
+		// when
+		let res = queue.replace_request(promise.id(), replacement.clone());
+
+		// then
+		assert_eq!(res, Ok(()));
+		assert_eq!(queue.peek(&promise.id()).unwrap().payload, replacement);
+		assert_eq!(promise.result(), ConfirmationResult::Waiting);
+	}
+
+	#[test]
+	fn should_not_replace_request_from_other_account() {
+		// given
+		let queue = ConfirmationsQueue::default();
+		let promise = queue.add_request(request()).unwrap();
+
+		// when
+		let res = queue.replace_request(promise.id(), ConfirmationPayload::Sign(Address::from(2), H256::from(5)));
+
+		// then
+		assert_eq!(res, Err(QueueReplaceError::OriginMismatch));
+		assert_eq!(queue.peek(&promise.id()).unwrap().payload, request());
+		assert_eq!(queue.replace_request(2.into(), request()), Err(QueueReplaceError::NotFound));
+	}
 }
@@ -0,0 +1,297 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Structured typed data hashing (EIP-712 style).
+//!
+//! The input is a JSON object with `types`, `primaryType`, `domain` and `message` fields.
+//! The resulting hash is `sha3(0x19 0x01 ‖ hashStruct(domain) ‖ hashStruct(message))`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use serde_json::{Map, Value};
+use util::{H256, U256, Address, FromHex, Uint};
+use util::sha3::Hashable;
+
+/// Name of the type describing the signing domain.
+const DOMAIN_TYPE: &'static str = "EIP712Domain";
+
+// struct name -> list of (field name, field type)
+type Types = BTreeMap<String, Vec<(String, String)>>;
+
+/// Computes the hash of the given typed data which should be signed.
+pub fn hash_typed_data(data: &Value) -> Result<H256, String> {
+	let data = try!(data.as_object().ok_or("Typed data must be an object."));
+	let types = try!(parse_types(try!(field(data, "types"))));
+	let primary_type = try!(try!(field(data, "primaryType")).as_str().ok_or("`primaryType` must be a string."));
+	let domain = try!(field(data, "domain"));
+	let message = try!(field(data, "message"));
+
+	if !types.contains_key(DOMAIN_TYPE) {
+		return Err(format!("`types` must define `{}`.", DOMAIN_TYPE));
+	}
+
+	let domain_hash = try!(hash_struct(&types, DOMAIN_TYPE, domain));
+	let message_hash = try!(hash_struct(&types, primary_type, message));
+
+	let mut encoded = vec![0x19u8, 0x01];
+	encoded.extend_from_slice(&domain_hash);
+	encoded.extend_from_slice(&message_hash);
+	Ok(encoded.sha3())
+}
+
+fn field<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Value, String> {
+	object.get(name).ok_or_else(|| format!("Missing `{}` field.", name))
+}
+
+fn parse_types(value: &Value) -> Result<Types, String> {
+	let object = try!(value.as_object().ok_or("`types` must be an object."));
+	let mut types = Types::new();
+	for (name, fields) in object {
+		let fields = try!(fields.as_array().ok_or_else(|| format!("Type `{}` must be a list of fields.", name)));
+		let mut parsed = Vec::with_capacity(fields.len());
+		for f in fields {
+			let name_and_type = f.as_object()
+				.and_then(|f| match (f.get("name").and_then(Value::as_str), f.get("type").and_then(Value::as_str)) {
+					(Some(n), Some(t)) => Some((n.to_owned(), t.to_owned())),
+					_ => None,
+				});
+			parsed.push(try!(name_and_type.ok_or_else(|| format!("Fields of type `{}` must have a `name` and a `type`.", name))));
+		}
+		types.insert(name.clone(), parsed);
+	}
+	Ok(types)
+}
+
+// strips array suffixes: `Person[][2]` -> `Person`
+fn base_type(ty: &str) -> &str {
+	match ty.find('[') {
+		Some(pos) => &ty[..pos],
+		None => ty,
+	}
+}
+
+fn collect_dependencies(types: &Types, ty: &str, found: &mut BTreeSet<String>) -> Result<(), String> {
+	let ty = base_type(ty);
+	if found.contains(ty) || !types.contains_key(ty) {
+		return Ok(());
+	}
+	found.insert(ty.to_owned());
+	for &(_, ref field_type) in &types[ty] {
+		try!(collect_dependencies(types, field_type, found));
+	}
+	Ok(())
+}
+
+fn encode_type(types: &Types, primary_type: &str) -> Result<String, String> {
+	if !types.contains_key(primary_type) {
+		return Err(format!("Unknown type `{}`.", primary_type));
+	}
+
+	let mut dependencies = BTreeSet::new();
+	try!(collect_dependencies(types, primary_type, &mut dependencies));
+	dependencies.remove(primary_type);
+
+	let mut encoded = String::new();
+	for ty in Some(primary_type.to_owned()).into_iter().chain(dependencies.into_iter()) {
+		let fields = types[&ty].iter().map(|&(ref name, ref ty)| format!("{} {}", ty, name)).collect::<Vec<_>>();
+		encoded.push_str(&format!("{}({})", ty, fields.join(",")));
+	}
+	Ok(encoded)
+}
+
+fn hash_struct(types: &Types, ty: &str, value: &Value) -> Result<H256, String> {
+	let object = try!(value.as_object().ok_or_else(|| format!("Value of type `{}` must be an object.", ty)));
+	let mut encoded = try!(encode_type(types, ty)).as_bytes().sha3().to_vec();
+	for &(ref name, ref field_type) in &types[ty] {
+		let field_value = try!(object.get(name).ok_or_else(|| format!("Missing field `{}` of type `{}`.", name, ty)));
+		encoded.extend_from_slice(&try!(encode_value(types, field_type, field_value)));
+	}
+	Ok(encoded.sha3())
+}
+
+fn encode_value(types: &Types, ty: &str, value: &Value) -> Result<H256, String> {
+	if ty.ends_with(']') {
+		let element_type = &ty[..ty.rfind('[').expect("type ends with `]`; qed")];
+		let items = try!(value.as_array().ok_or_else(|| format!("Value of type `{}` must be an array.", ty)));
+		let mut encoded = Vec::with_capacity(items.len() * 32);
+		for item in items {
+			encoded.extend_from_slice(&try!(encode_value(types, element_type, item)));
+		}
+		return Ok(encoded.sha3());
+	}
+
+	if types.contains_key(ty) {
+		return hash_struct(types, ty, value);
+	}
+
+	match ty {
+		"string" => value.as_str().map(|s| s.as_bytes().sha3()).ok_or_else(|| "Expected a string.".to_owned()),
+		"bytes" => parse_bytes(value).map(|b| b.sha3()),
+		"bool" => value.as_bool().map(|b| H256::from(b as u64)).ok_or_else(|| "Expected a boolean.".to_owned()),
+		"address" => {
+			let bytes = try!(parse_bytes(value));
+			if bytes.len() != 20 {
+				return Err("Expected a 20-byte address.".into());
+			}
+			Ok(H256::from(Address::from_slice(&bytes)))
+		},
+		_ if ty.starts_with("bytes") => {
+			let size = try!(type_size(ty, "bytes", 1, 32));
+			let bytes = try!(parse_bytes(value));
+			if bytes.len() != size {
+				return Err(format!("Expected {} bytes for `{}`.", size, ty));
+			}
+			let mut out = H256::new();
+			out[0..size].copy_from_slice(&bytes);
+			Ok(out)
+		},
+		_ if ty.starts_with("uint") => {
+			try!(type_size(ty, "uint", 8, 256));
+			let (negative, value) = try!(parse_integer(value));
+			if negative {
+				return Err(format!("Negative value for `{}`.", ty));
+			}
+			Ok(value.into())
+		},
+		_ if ty.starts_with("int") => {
+			try!(type_size(ty, "int", 8, 256));
+			let (negative, value) = try!(parse_integer(value));
+			match negative && !value.is_zero() {
+				// two's complement
+				true => Ok((U256::max_value() - (value - U256::one())).into()),
+				false => Ok(value.into()),
+			}
+		},
+		_ => Err(format!("Unknown type `{}`.", ty)),
+	}
+}
+
+// parses the size suffix of `uintN`/`intN`/`bytesN`, defaulting to `max` if there is none.
+fn type_size(ty: &str, prefix: &str, min: usize, max: usize) -> Result<usize, String> {
+	let suffix = &ty[prefix.len()..];
+	if suffix.is_empty() {
+		return Ok(max);
+	}
+	match suffix.parse::<usize>() {
+		Ok(size) if size >= min && size <= max => Ok(size),
+		_ => Err(format!("Invalid type `{}`.", ty)),
+	}
+}
+
+fn parse_bytes(value: &Value) -> Result<Vec<u8>, String> {
+	let s = try!(value.as_str().ok_or("Expected a hex string."));
+	if !s.starts_with("0x") {
+		return Err(format!("Expected 0x-prefixed hex string, got `{}`.", s));
+	}
+	s[2..].from_hex().map_err(|e| format!("Invalid hex string `{}`: {:?}", s, e))
+}
+
+// returns sign and magnitude of a JSON number or a decimal/hex string.
+fn parse_integer(value: &Value) -> Result<(bool, U256), String> {
+	match *value {
+		Value::U64(v) => Ok((false, U256::from(v))),
+		Value::I64(v) if v < 0 => Ok((true, U256::from(v.wrapping_neg() as u64))),
+		Value::I64(v) => Ok((false, U256::from(v as u64))),
+		Value::String(ref s) => {
+			let (negative, s) = match s.starts_with('-') {
+				true => (true, &s[1..]),
+				false => (false, &s[..]),
+			};
+			let value = match s.starts_with("0x") {
+				true => s[2..].parse::<U256>().map_err(|_| format!("Invalid hex number `{}`.", s)),
+				false => U256::from_dec_str(s).map_err(|_| format!("Invalid decimal number `{}`.", s)),
+			};
+			value.map(|v| (negative, v))
+		},
+		_ => Err("Expected an integer.".into()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::H256;
+	use super::{hash_typed_data, encode_type, parse_types};
+
+	pub const MAIL: &'static str = r#"{
+		"types": {
+			"EIP712Domain": [
+				{ "name": "name", "type": "string" },
+				{ "name": "version", "type": "string" },
+				{ "name": "chainId", "type": "uint256" },
+				{ "name": "verifyingContract", "type": "address" }
+			],
+			"Person": [
+				{ "name": "name", "type": "string" },
+				{ "name": "wallet", "type": "address" }
+			],
+			"Mail": [
+				{ "name": "from", "type": "Person" },
+				{ "name": "to", "type": "Person" },
+				{ "name": "contents", "type": "string" }
+			]
+		},
+		"primaryType": "Mail",
+		"domain": {
+			"name": "Ether Mail",
+			"version": "1",
+			"chainId": 1,
+			"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+		},
+		"message": {
+			"from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+			"to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+			"contents": "Hello, Bob!"
+		}
+	}"#;
+
+	#[test]
+	fn should_encode_type_with_dependencies() {
+		let data: serde_json::Value = serde_json::from_str(MAIL).unwrap();
+		let types = parse_types(data.find("types").unwrap()).unwrap();
+
+		assert_eq!(
+			encode_type(&types, "Mail").unwrap(),
+			"Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+		);
+	}
+
+	#[test]
+	fn should_hash_typed_data() {
+		let data: serde_json::Value = serde_json::from_str(MAIL).unwrap();
+
+		assert_eq!(
+			hash_typed_data(&data).unwrap(),
+			H256::from("0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+		);
+	}
+
+	#[test]
+	fn should_reject_malformed_typed_data() {
+		let missing_domain_type = r#"{ "types": {}, "primaryType": "Mail", "domain": {}, "message": {} }"#;
+		let unknown_field_type = r#"{
+			"types": { "EIP712Domain": [{ "name": "name", "type": "strin" }] },
+			"primaryType": "EIP712Domain",
+			"domain": { "name": "x" },
+			"message": { "name": "x" }
+		}"#;
+		let missing_message = r#"{ "types": { "EIP712Domain": [] }, "primaryType": "EIP712Domain", "domain": {} }"#;
+
+		for input in &[missing_domain_type, unknown_field_type, missing_message, "[]"] {
+			let data: serde_json::Value = serde_json::from_str(input).unwrap();
+			assert!(hash_typed_data(&data).is_err(), "{} should be rejected", input);
+		}
+	}
+}
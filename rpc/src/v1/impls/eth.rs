@@ -23,6 +23,7 @@ use std::process::{Command, Stdio};
 use std::thread;
 use std::time::{Instant, Duration};
 use std::sync::{Arc, Weak};
+use std::collections::HashSet;
 use time::get_time;
 use ethsync::{SyncProvider, SyncState};
 use ethcore::miner::{MinerService, ExternalMinerService};
@@ -32,7 +33,8 @@ use util::sha3::*;
 use util::{FromHex, Mutex};
 use rlp::{self, UntrustedRlp, View};
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{MiningBlockChainClient, BlockID, TransactionID, UncleID};
+use ethcore::client::{MiningBlockChainClient, BlockID, TransactionID, UncleID, CallAnalytics};
+use ethcore::error::{CallError, ExecutionError};
 use ethcore::header::Header as BlockHeader;
 use ethcore::block::IsBlock;
 use ethcore::views::*;
@@ -42,17 +44,40 @@ use ethcore::log_entry::LogEntry;
 use ethcore::filter::Filter as EthcoreFilter;
 use self::ethash::SeedHashCompute;
 use v1::traits::Eth;
-use v1::types::{Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo, Transaction, CallRequest, Index, Filter, Log, Receipt, H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256};
-use v1::helpers::{CallRequest as CRequest, errors};
+use v1::types::{AccountInfo, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo, Transaction, CallRequest, Index, Filter, Log, LogsDetailed, Receipt, H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256};
+use v1::helpers::{CallRequest as CRequest, KeepAliveThrottle, DEFAULT_KEEP_ALIVE_INTERVAL_SECS, errors, resolve_filter_timestamps, last_block_until, to_timestamp};
 use v1::helpers::dispatch::{default_gas_price, dispatch_transaction};
 use v1::helpers::params::{expect_no_params, params_len, from_params_default_second, from_params_default_third};
 
+/// Default maximal size of call data in bytes
+pub const DEFAULT_MAX_CALL_DATA: usize = 1024 * 1024;
+
 /// Eth RPC options
 pub struct EthClientOptions {
-	/// Returns receipt from pending blocks
+	/// Returns receipt from pending blocks.
+	/// Such receipts have `blockHash` and `blockNumber` set to `null` and their logs are of `pending` type.
 	pub allow_pending_receipt_query: bool,
 	/// Send additional block number when asking for work
 	pub send_block_number_in_get_work: bool,
+	/// Minimal interval between two keep-alive notifications sent to the client
+	pub keep_alive_interval: Duration,
+	/// Maximal execution time of `eth_call` and `eth_estimateGas`
+	pub call_timeout: Option<Duration>,
+	/// Report the first account as `eth_coinbase` when no author is set
+	pub author_account_fallback: bool,
+	/// Whether read-only calls keep the client active. Calls changing the state always do.
+	/// Disabled in passive modes, so that the client can go idle while being queried.
+	pub reads_keep_alive: bool,
+	/// Maximal number of accounts returned by `eth_accounts`, the list is truncated beyond it.
+	/// `Some(0)` hides all accounts. Unlimited if `None`.
+	pub max_accounts: Option<usize>,
+	/// Maximal size of the data of `eth_call` and `eth_estimateGas` in bytes
+	pub max_call_data: usize,
+	/// Maximal gas of `eth_call` and `eth_estimateGas`, also used when the request doesn't set it.
+	/// The gas limit of the latest block if `None`.
+	pub max_call_gas: Option<U256>,
+	/// Refuse calls from addresses which aren't local accounts, for clients served over public transports
+	pub local_call_senders_only: bool,
 }
 
 impl Default for EthClientOptions {
@@ -60,6 +85,14 @@ impl Default for EthClientOptions {
 		EthClientOptions {
 			allow_pending_receipt_query: true,
 			send_block_number_in_get_work: true,
+			keep_alive_interval: Duration::from_secs(DEFAULT_KEEP_ALIVE_INTERVAL_SECS),
+			call_timeout: None,
+			author_account_fallback: false,
+			reads_keep_alive: true,
+			max_accounts: None,
+			max_call_data: DEFAULT_MAX_CALL_DATA,
+			max_call_gas: None,
+			local_call_senders_only: false,
 		}
 	}
 }
@@ -77,6 +110,7 @@ pub struct EthClient<C, S: ?Sized, M, EM> where
 	miner: Weak<M>,
 	external_miner: Arc<EM>,
 	seed_compute: Mutex<SeedHashCompute>,
+	keep_alive: KeepAliveThrottle,
 	options: EthClientOptions,
 }
 
@@ -96,10 +130,18 @@ impl<C, S: ?Sized, M, EM> EthClient<C, S, M, EM> where
 			accounts: Arc::downgrade(accounts),
 			external_miner: em.clone(),
 			seed_compute: Mutex::new(SeedHashCompute::new()),
+			keep_alive: KeepAliveThrottle::new(options.keep_alive_interval),
 			options: options,
 		}
 	}
 
+	fn call_analytics(&self) -> CallAnalytics {
+		CallAnalytics {
+			timeout_ms: self.options.call_timeout.map(|t| t.as_secs() * 1000 + t.subsec_nanos() as u64 / 1_000_000),
+			..Default::default()
+		}
+	}
+
 	fn block(&self, id: BlockID, include_txs: bool) -> Result<Value, Error> {
 		let client = take_weak!(self.client);
 		match (client.block(id.clone()), client.block_total_difficulty(id)) {
@@ -137,10 +179,77 @@ impl<C, S: ?Sized, M, EM> EthClient<C, S, M, EM> where
 		}
 	}
 
+	/// Block currently being sealed by the miner, if any. The block has no hash nor size yet.
+	/// Reading it never prepares new sealing work.
+	fn pending_block(&self, include_txs: bool) -> Result<Option<Block>, Error> {
+		let client = take_weak!(self.client);
+		let miner = take_weak!(self.miner);
+		Ok(miner.map_pending_block(|b| {
+			let header = b.header();
+			let parent_difficulty = client.block_total_difficulty(BlockID::Hash(header.parent_hash().clone())).unwrap_or_else(U256::zero);
+			Block {
+				hash: None,
+				size: None,
+				parent_hash: header.parent_hash().clone().into(),
+				uncles_hash: header.uncles_hash().clone().into(),
+				author: header.author().clone().into(),
+				miner: header.author().clone().into(),
+				state_root: header.state_root().clone().into(),
+				transactions_root: header.transactions_root().clone().into(),
+				receipts_root: header.receipts_root().clone().into(),
+				number: Some(header.number().into()),
+				gas_used: header.gas_used().clone().into(),
+				gas_limit: header.gas_limit().clone().into(),
+				logs_bloom: header.log_bloom().clone().into(),
+				timestamp: header.timestamp().into(),
+				difficulty: header.difficulty().clone().into(),
+				total_difficulty: (parent_difficulty + *header.difficulty()).into(),
+				seal_fields: Vec::new(),
+				uncles: b.uncles().iter().map(|u| u.hash().into()).collect(),
+				transactions: match include_txs {
+					true => BlockTransactions::Full(b.transactions().iter().cloned().map(Into::into).collect()),
+					false => BlockTransactions::Hashes(b.transactions().iter().map(|t| t.hash().into()).collect()),
+				},
+				extra_data: Bytes::new(header.extra_data().clone()),
+			}
+		}))
+	}
+
 	fn transaction(&self, id: TransactionID) -> Result<Value, Error> {
-		match take_weak!(self.client).transaction(id) {
+		let client = take_weak!(self.client);
+		match client.transaction(id.clone()) {
 			Some(t) => Ok(to_value(&Transaction::from(t))),
-			None => Ok(Value::Null)
+			None => Self::missing_transaction(&*client, id),
+		}
+	}
+
+	fn raw_transaction(&self, id: TransactionID) -> Result<Value, Error> {
+		let client = take_weak!(self.client);
+		// the transaction is returned as stored in its block rather than re-encoded
+		let raw = client.transaction(id.clone())
+			.and_then(|t| client.block_body(BlockID::Hash(t.block_hash))
+				.and_then(|body| BodyView::new(&body).transaction_rlp_at(t.transaction_index)));
+		match raw {
+			Some(raw) => Ok(to_value(&Bytes::new(raw))),
+			None => Self::missing_transaction(&*client, id),
+		}
+	}
+
+	/// Result for a transaction which could not be found: `null` if the transaction or its block is unknown,
+	/// an error if the index is beyond the transactions of a known block.
+	fn missing_transaction(client: &C, id: TransactionID) -> Result<Value, Error> {
+		match id {
+			TransactionID::Location(block, index) => match client.block(block) {
+				Some(bytes) => {
+					let count = BlockView::new(&bytes).transactions_count();
+					match index >= count {
+						true => Err(errors::transaction_index_out_of_range(index, count)),
+						false => Ok(Value::Null),
+					}
+				},
+				None => Ok(Value::Null),
+			},
+			TransactionID::Hash(_) => Ok(Value::Null),
 		}
 	}
 
@@ -180,13 +289,85 @@ impl<C, S: ?Sized, M, EM> EthClient<C, S, M, EM> where
 		Ok(to_value(&block))
 	}
 
+	/// Returns logs matching the filter. Only blocks selected by the client's blooms are scanned.
+	fn filtered_logs(&self, filter: Filter, limit: Option<usize>) -> Result<LogsDetailed, Error> {
+		let client = take_weak!(self.client);
+		let filter = match try!(resolve_filter_timestamps(&*client, filter)) {
+			Some(filter) => filter,
+			None => return Ok(LogsDetailed {
+				logs: Vec::new(),
+				blocks_scanned: 0u64.into(),
+				blocks_matched: 0u64.into(),
+			}),
+		};
+		let include_pending = filter.to_block == Some(BlockNumber::Pending);
+		let filter: EthcoreFilter = filter.into();
+
+		let blocks = client.possible_log_blocks(&filter);
+		let blocks_scanned = blocks.len();
+		let entries = client.logs_in_blocks(blocks, &filter, limit);
+		let blocks_matched = entries.iter().map(|entry| entry.block_number).collect::<HashSet<_>>().len();
+		let mut logs = entries.into_iter()
+			.map(From::from)
+			.collect::<Vec<Log>>();
+
+		if include_pending {
+			let pending = pending_logs(&*take_weak!(self.miner), &filter);
+			logs.extend(pending);
+		}
+
+		let len = logs.len();
+		match limit {
+			Some(limit) if len >= limit => {
+				logs = logs.split_off(len - limit);
+			},
+			_ => {},
+		}
+
+		Ok(LogsDetailed {
+			logs: logs,
+			blocks_scanned: blocks_scanned.into(),
+			blocks_matched: blocks_matched.into(),
+		})
+	}
+
+	/// Checks the request of `eth_call` or `eth_estimateGas` against the limits,
+	/// returning the maximal gas of the call.
+	fn check_call_limits(&self, request: &CRequest) -> Result<U256, Error> {
+		let data_len = request.data.as_ref().map_or(0, |data| data.len());
+		if data_len > self.options.max_call_data {
+			return Err(errors::param_over_limit("data", format!("{} bytes", self.options.max_call_data)));
+		}
+
+		let max_gas = match self.options.max_call_gas {
+			Some(max_gas) => max_gas,
+			None => HeaderView::new(&take_weak!(self.client).best_block_header()).gas_limit(),
+		};
+		if request.gas.map_or(false, |gas| gas > max_gas) {
+			return Err(errors::param_over_limit("gas", max_gas));
+		}
+
+		if self.options.local_call_senders_only {
+			if let Some(ref from) = request.from {
+				let store = take_weak!(self.accounts);
+				let accounts = try!(store.accounts().map_err(|e| errors::internal("Could not fetch accounts.", e)));
+				if !accounts.contains(from) {
+					return Err(errors::non_local_sender());
+				}
+			}
+		}
+
+		Ok(max_gas)
+	}
+
 	fn sign_call(&self, request: CRequest) -> Result<SignedTransaction, Error> {
+		let max_gas = try!(self.check_call_limits(&request));
 		let (client, miner) = (take_weak!(self.client), take_weak!(self.miner));
 		let from = request.from.unwrap_or(Address::zero());
 		Ok(EthTransaction {
 			nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
 			action: request.to.map_or(Action::Create, Action::Call),
-			gas: request.gas.unwrap_or(U256::from(50_000_000)),
+			gas: request.gas.unwrap_or(max_gas),
 			gas_price: request.gas_price.unwrap_or_else(|| default_gas_price(&*client, &*miner)),
 			value: request.value.unwrap_or_else(U256::zero),
 			data: request.data.map_or_else(Vec::new, |d| d.to_vec())
@@ -215,15 +396,30 @@ pub fn pending_logs<M>(miner: &M, filter: &EthcoreFilter) -> Vec<Log> where M: M
 
 const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4;	// because uncles go back 6.
 
+/// Highest type byte of a typed transaction envelope (EIP-2718).
+const MAX_TRANSACTION_TYPE: u8 = 0x7f;
+
 impl<C, S: ?Sized, M, EM> EthClient<C, S, M, EM> where
 	C: MiningBlockChainClient + 'static,
 	S: SyncProvider + 'static,
 	M: MinerService + 'static,
 	EM: ExternalMinerService + 'static {
 
+	/// Notifies the client about a read-only call.
 	fn active(&self) -> Result<(), Error> {
-		// TODO: only call every 30s at most.
-		take_weak!(self.client).keep_alive();
+		self.notify_activity(self.options.reads_keep_alive)
+	}
+
+	/// Notifies the client about a call changing the state.
+	fn active_changing(&self) -> Result<(), Error> {
+		self.notify_activity(true)
+	}
+
+	fn notify_activity(&self, keep_alive: bool) -> Result<(), Error> {
+		let client = take_weak!(self.client);
+		if keep_alive && self.keep_alive.should_notify() {
+			client.keep_alive();
+		}
 		Ok(())
 	}
 }
@@ -278,7 +474,17 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 		try!(self.active());
 		try!(expect_no_params(params));
 
-		Ok(to_value(&RpcH160::from(take_weak!(self.miner).author())))
+		let mut author = take_weak!(self.miner).author();
+		// accounts hidden from `eth_accounts` are not revealed here either
+		if author.is_zero() && self.options.author_account_fallback && self.options.max_accounts != Some(0) {
+			let store = take_weak!(self.accounts);
+			let accounts = try!(store.accounts().map_err(|e| errors::internal("Could not fetch accounts.", e)));
+			if let Some(account) = accounts.into_iter().next() {
+				author = account;
+			}
+		}
+
+		Ok(to_value(&RpcH160::from(author)))
 	}
 
 	fn is_mining(&self, params: Params) -> Result<Value, Error> {
@@ -309,7 +515,8 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 
 		let store = take_weak!(self.accounts);
 		let accounts = try!(store.accounts().map_err(|e| errors::internal("Could not fetch accounts.", e)));
-		Ok(to_value(&accounts.into_iter().map(Into::into).collect::<Vec<RpcH160>>()))
+		let max_accounts = self.options.max_accounts.unwrap_or(accounts.len());
+		Ok(to_value(&accounts.into_iter().take(max_accounts).map(Into::into).collect::<Vec<RpcH160>>()))
 	}
 
 	fn block_number(&self, params: Params) -> Result<Value, Error> {
@@ -334,6 +541,23 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 			})
 	}
 
+	fn balance_at_timestamp(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(RpcH160, RpcU256)>(params)
+			.and_then(|(address, timestamp)| {
+				let address: Address = address.into();
+				let client = take_weak!(self.client);
+				let number = match last_block_until(&*client, to_timestamp(timestamp)) {
+					Some(number) => number,
+					None => return Err(errors::invalid_params("timestamp", "No block at or before given timestamp.")),
+				};
+				match client.balance(&address, BlockID::Number(number)) {
+					Some(balance) => Ok(to_value(&RpcU256::from(balance))),
+					None => Err(errors::state_pruned()),
+				}
+			})
+	}
+
 	fn storage_at(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		from_params_default_third::<RpcH160, RpcU256>(params)
@@ -351,13 +575,25 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 
 	}
 
+	fn account(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params_default_second(params)
+			.and_then(|(address, block_number,)| {
+				let address: Address = RpcH160::into(address);
+				match take_weak!(self.client).account(&address, block_number.into()) {
+					Some(account) => Ok(to_value(&AccountInfo::from(account))),
+					None => Err(errors::state_pruned()),
+				}
+			})
+	}
+
 	fn transaction_count(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		from_params_default_second(params)
 			.and_then(|(address, block_number,)| {
 				let address: Address = RpcH160::into(address);
 				match block_number {
-					BlockNumber::Pending => Ok(to_value(&RpcU256::from(take_weak!(self.miner).nonce(&*take_weak!(self.client), &address)))),
+					BlockNumber::Pending => Ok(to_value(&RpcU256::from(take_weak!(self.miner).next_nonce(&*take_weak!(self.client), &address)))),
 					id => match take_weak!(self.client).nonce(&address, id.into()) {
 						Some(nonce) => Ok(to_value(&RpcU256::from(nonce))),
 						None => Err(errors::state_pruned()),
@@ -428,7 +664,10 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 	fn block_by_number(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		from_params::<(BlockNumber, bool)>(params)
-			.and_then(|(number, include_txs)| self.block(number.into(), include_txs))
+			.and_then(|(number, include_txs)| match number {
+				BlockNumber::Pending => self.pending_block(include_txs).map(|block| block.map_or(Value::Null, |b| to_value(&b))),
+				number => self.block(number.into(), include_txs),
+			})
 	}
 
 	fn transaction_by_hash(&self, params: Params) -> Result<Value, Error> {
@@ -456,6 +695,25 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 			.and_then(|(number, index)| self.transaction(TransactionID::Location(number.into(), index.value())))
 	}
 
+	fn raw_transaction_by_hash(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(RpcH256,)>(params)
+			.and_then(|(hash,)| {
+				let miner = take_weak!(self.miner);
+				let hash: H256 = hash.into();
+				match miner.transaction(&hash) {
+					Some(pending_tx) => Ok(to_value(&Bytes::new(rlp::encode(&pending_tx).to_vec()))),
+					None => self.raw_transaction(TransactionID::Hash(hash))
+				}
+			})
+	}
+
+	fn raw_transaction_by_block_hash_and_index(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(RpcH256, Index)>(params)
+			.and_then(|(hash, index)| self.raw_transaction(TransactionID::Location(BlockID::Hash(hash.into()), index.value())))
+	}
+
 	fn transaction_receipt(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		from_params::<(RpcH256,)>(params)
@@ -502,29 +760,18 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 			1 => from_params::<(Filter, )>(params).map(|(filter, )| (filter, None)),
 			_ => from_params::<(Filter, usize)>(params).map(|(filter, val)| (filter, Some(val))),
 		};
-		params.and_then(|(filter, limit)| {
-			let include_pending = filter.to_block == Some(BlockNumber::Pending);
-			let filter: EthcoreFilter = filter.into();
-			let mut logs = take_weak!(self.client).logs(filter.clone(), limit)
-				.into_iter()
-				.map(From::from)
-				.collect::<Vec<Log>>();
-
-			if include_pending {
-				let pending = pending_logs(&*take_weak!(self.miner), &filter);
-				logs.extend(pending);
-			}
-
-			let len = logs.len();
-			match limit {
-				Some(limit) if len >= limit => {
-					logs = logs.split_off(len - limit);
-				},
-				_ => {},
-			}
+		params.and_then(|(filter, limit)| self.filtered_logs(filter, limit))
+			.map(|detailed| to_value(&detailed.logs))
+	}
 
-			Ok(to_value(&logs))
-		})
+	fn logs_detailed(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let params = match params_len(&params) {
+			1 => from_params::<(Filter, )>(params).map(|(filter, )| (filter, None)),
+			_ => from_params::<(Filter, usize)>(params).map(|(filter, val)| (filter, Some(val))),
+		};
+		params.and_then(|(filter, limit)| self.filtered_logs(filter, limit))
+			.map(|detailed| to_value(&detailed))
 	}
 
 	fn work(&self, params: Params) -> Result<Value, Error> {
@@ -570,7 +817,7 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 	}
 
 	fn submit_work(&self, params: Params) -> Result<Value, Error> {
-		try!(self.active());
+		try!(self.active_changing());
 		from_params::<(RpcH64, RpcH256, RpcH256)>(params).and_then(|(nonce, pow_hash, mix_hash)| {
 			let nonce: H64 = nonce.into();
 			let pow_hash: H256 = pow_hash.into();
@@ -585,7 +832,7 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 	}
 
 	fn submit_hashrate(&self, params: Params) -> Result<Value, Error> {
-		try!(self.active());
+		try!(self.active_changing());
 		from_params::<(RpcU256, RpcH256)>(params).and_then(|(rate, id)| {
 			self.external_miner.submit_hashrate(rate.into(), id.into());
 			Ok(to_value(&true))
@@ -593,10 +840,16 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 	}
 
 	fn send_raw_transaction(&self, params: Params) -> Result<Value, Error> {
-		try!(self.active());
+		try!(self.active_changing());
 		from_params::<(Bytes, )>(params)
 			.and_then(|(raw_transaction, )| {
 				let raw_transaction = raw_transaction.to_vec();
+				// typed transaction envelopes start with the type byte, legacy transactions with an RLP list prefix
+				if let Some(&transaction_type) = raw_transaction.first() {
+					if transaction_type <= MAX_TRANSACTION_TYPE {
+						return Err(errors::unsupported_transaction_type(transaction_type));
+					}
+				}
 				match UntrustedRlp::new(&raw_transaction).as_val() {
 					Ok(signed_transaction) => dispatch_transaction(&*take_weak!(self.client), &*take_weak!(self.miner), signed_transaction),
 					Err(_) => Ok(to_value(&RpcH256::from(H256::from(0)))),
@@ -611,9 +864,12 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 				let request = CallRequest::into(request);
 				let signed = try!(self.sign_call(request));
 				let r = match block_number {
-					BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, Default::default()),
-					block_number => take_weak!(self.client).call(&signed, block_number.into(), Default::default()),
+					BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, self.call_analytics()),
+					block_number => take_weak!(self.client).call(&signed, block_number.into(), self.call_analytics()),
 				};
+				if let Err(CallError::Execution(ExecutionError::Timeout)) = r {
+					return Err(errors::execution("timed out"));
+				}
 				Ok(to_value(&r.map(|e| Bytes(e.output)).unwrap_or(Bytes::new(vec![]))))
 			})
 	}
@@ -625,9 +881,12 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 				let request = CallRequest::into(request);
 				let signed = try!(self.sign_call(request));
 				let r = match block_number {
-					BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, Default::default()),
-					block => take_weak!(self.client).call(&signed, block.into(), Default::default()),
+					BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, self.call_analytics()),
+					block => take_weak!(self.client).call(&signed, block.into(), self.call_analytics()),
 				};
+				if let Err(CallError::Execution(ExecutionError::Timeout)) = r {
+					return Err(errors::execution("timed out"));
+				}
 				Ok(to_value(&RpcU256::from(r.map(|res| res.gas_used + res.refunded).unwrap_or(From::from(0)))))
 			})
 	}
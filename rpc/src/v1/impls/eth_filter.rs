@@ -17,6 +17,7 @@
 //! Eth Filter RPC implementation
 
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use std::collections::HashSet;
 use jsonrpc_core::*;
 use ethcore::miner::MinerService;
@@ -24,8 +25,8 @@ use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::client::{BlockChainClient, BlockID};
 use util::Mutex;
 use v1::traits::EthFilter;
-use v1::types::{BlockNumber, Index, Filter, Log, H256 as RpcH256, U256 as RpcU256};
-use v1::helpers::{PollFilter, PollManager};
+use v1::types::{BlockNumber, Index, Filter, FilterInfo, Log, H256 as RpcH256, U256 as RpcU256};
+use v1::helpers::{PollFilter, PollManager, KeepAliveThrottle, DEFAULT_KEEP_ALIVE_INTERVAL_SECS, errors};
 use v1::helpers::params::expect_no_params;
 use v1::impls::eth::pending_logs;
 
@@ -37,6 +38,8 @@ pub struct EthFilterClient<C, M> where
 	client: Weak<C>,
 	miner: Weak<M>,
 	polls: Mutex<PollManager<PollFilter>>,
+	keep_alive: KeepAliveThrottle,
+	reads_keep_alive: bool,
 }
 
 impl<C, M> EthFilterClient<C, M> where
@@ -45,16 +48,31 @@ impl<C, M> EthFilterClient<C, M> where
 
 	/// Creates new Eth filter client.
 	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+		EthFilterClient::with_keep_alive_interval(client, miner, Duration::from_secs(DEFAULT_KEEP_ALIVE_INTERVAL_SECS))
+	}
+
+	/// Creates new Eth filter client notifying the client about activity at most once per `keep_alive_interval`.
+	pub fn with_keep_alive_interval(client: &Arc<C>, miner: &Arc<M>, keep_alive_interval: Duration) -> Self {
 		EthFilterClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
 			polls: Mutex::new(PollManager::new()),
+			keep_alive: KeepAliveThrottle::new(keep_alive_interval),
+			reads_keep_alive: true,
 		}
 	}
 
+	/// Sets whether filter calls, which are all read-only, keep the client active.
+	pub fn with_reads_keep_alive(mut self, reads_keep_alive: bool) -> Self {
+		self.reads_keep_alive = reads_keep_alive;
+		self
+	}
+
 	fn active(&self) -> Result<(), Error> {
-		// TODO: only call every 30s at most.
-		take_weak!(self.client).keep_alive();
+		let client = take_weak!(self.client);
+		if self.reads_keep_alive && self.keep_alive.should_notify() {
+			client.keep_alive();
+		}
 		Ok(())
 	}
 }
@@ -67,6 +85,9 @@ impl<C, M> EthFilter for EthFilterClient<C, M> where
 		try!(self.active());
 		from_params::<(Filter,)>(params)
 			.and_then(|(filter,)| {
+				if filter.from_timestamp.is_some() || filter.to_timestamp.is_some() {
+					return Err(errors::invalid_params("filter", "Timestamp bounds are only supported by eth_getLogs."));
+				}
 				let mut polls = self.polls.lock();
 				let block_number = take_weak!(self.client).chain_info().best_block_number;
 				let id = polls.create_poll(PollFilter::Logs(block_number, Default::default(), filter));
@@ -219,4 +240,26 @@ impl<C, M> EthFilter for EthFilterClient<C, M> where
 				to_value(&true)
 			})
 	}
+
+	fn list_filters(&self, params: Params) -> Result<Value, Error> {
+		try!(expect_no_params(params));
+		let mut polls = self.polls.lock();
+		let filters = polls.polls().into_iter()
+			.map(|(id, info, filter)| {
+				let (kind, criteria) = match *filter {
+					PollFilter::Block(_) => ("block", None),
+					PollFilter::PendingTransaction(_) => ("pending", None),
+					PollFilter::Logs(_, _, ref filter) => ("logs", Some(filter.clone())),
+				};
+				FilterInfo {
+					id: id.into(),
+					kind: kind.into(),
+					created: info.created.into(),
+					last_poll: info.last_poll.into(),
+					filter: criteria,
+				}
+			})
+			.collect::<Vec<_>>();
+		Ok(to_value(&filters))
+	}
 }
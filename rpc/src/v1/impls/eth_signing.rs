@@ -17,14 +17,18 @@
 //! Eth Signing RPC implementation.
 
 use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
 use jsonrpc_core::*;
 use ethcore::miner::MinerService;
 use ethcore::client::MiningBlockChainClient;
-use util::{U256, Address, H256, Mutex};
+use util::{U256, Uint, Address, H256, Mutex};
 use transient_hashmap::TransientHashMap;
 use ethcore::account_provider::AccountProvider;
-use v1::helpers::{errors, SigningQueue, ConfirmationPromise, ConfirmationResult, ConfirmationsQueue, ConfirmationPayload, TransactionRequest as TRequest, FilledTransactionRequest as FilledRequest};
+use v1::helpers::{errors, SigningQueue, ConfirmationPromise, ConfirmationResult, ConfirmationsQueue, ConfirmationPayload, QueueReplaceError, TransactionRequest as TRequest, FilledTransactionRequest as FilledRequest};
+use v1::helpers::params::from_params_optional_second;
 use v1::helpers::dispatch::{default_gas_price, sign_and_dispatch};
+use v1::helpers::typed_data::hash_typed_data;
 use v1::traits::EthSigning;
 use v1::types::{TransactionRequest, H160 as RpcH160, H256 as RpcH256, H520 as RpcH520, U256 as RpcU256};
 
@@ -52,6 +56,8 @@ pub struct EthSigningQueueClient<C, M> where C: MiningBlockChainClient, M: Miner
 }
 
 const MAX_PENDING_DURATION: u64 = 60 * 60;
+/// Longest time (in seconds) a posted transaction can wait for confirmation.
+const MAX_REQUEST_TTL: u64 = 24 * 60 * 60;
 
 pub enum DispatchResult {
 	Promise(ConfirmationPromise),
@@ -78,24 +84,32 @@ impl<C, M> EthSigningQueueClient<C, M> where C: MiningBlockChainClient, M: Miner
 
 	fn dispatch_sign(&self, params: Params) -> Result<DispatchResult, Error> {
 		from_params::<(RpcH160, RpcH256)>(params).and_then(|(address, msg)| {
-			let address: Address = address.into();
-			let msg: H256 = msg.into();
-
-			let accounts = take_weak!(self.accounts);
-			if accounts.is_unlocked(address) {
-				return Ok(DispatchResult::Value(to_value(&accounts.sign(address, msg).ok().map_or_else(RpcH520::default, Into::into))))
-			}
+			self.dispatch_sign_hash(address.into(), msg.into())
+		})
+	}
 
-			let queue = take_weak!(self.queue);
-			queue.add_request(ConfirmationPayload::Sign(address, msg))
-				.map(DispatchResult::Promise)
-				.map_err(|_| errors::request_rejected_limit())
+	fn dispatch_sign_typed_data(&self, params: Params) -> Result<DispatchResult, Error> {
+		from_params::<(RpcH160, Value)>(params).and_then(|(address, data)| {
+			let hash = try!(hash_typed_data(&data).map_err(|e| errors::invalid_params("Typed data", e)));
+			self.dispatch_sign_hash(address.into(), hash)
 		})
 	}
 
+	fn dispatch_sign_hash(&self, address: Address, msg: H256) -> Result<DispatchResult, Error> {
+		let accounts = take_weak!(self.accounts);
+		if accounts.is_unlocked(address) {
+			return Ok(DispatchResult::Value(to_value(&accounts.sign(address, msg).ok().map_or_else(RpcH520::default, Into::into))))
+		}
+
+		let queue = take_weak!(self.queue);
+		queue.add_request(ConfirmationPayload::Sign(address, msg))
+			.map(DispatchResult::Promise)
+			.map_err(|_| errors::request_rejected_limit())
+	}
+
 	fn dispatch_transaction(&self, params: Params) -> Result<DispatchResult, Error> {
-		from_params::<(TransactionRequest, )>(params)
-			.and_then(|(request, )| {
+		from_params_optional_second::<TransactionRequest, RpcU256>(params)
+			.and_then(|(request, ttl)| {
 				let request: TRequest = request.into();
 				let accounts = take_weak!(self.accounts);
 				let (client, miner) = (take_weak!(self.client), take_weak!(self.miner));
@@ -107,11 +121,32 @@ impl<C, M> EthSigningQueueClient<C, M> where C: MiningBlockChainClient, M: Miner
 
 				let queue = take_weak!(self.queue);
 				let request = fill_optional_fields(request, &*client, &*miner);
-				queue.add_request(ConfirmationPayload::Transaction(request))
-					.map(DispatchResult::Promise)
-					.map_err(|_| errors::request_rejected_limit())
+				let ttl = match ttl.map(U256::from) {
+					Some(ttl) if ttl > U256::from(MAX_REQUEST_TTL) => return Err(errors::param_over_limit("ttl", MAX_REQUEST_TTL)),
+					ttl => ttl.map(|ttl| Duration::from_secs(ttl.low_u64())),
+				};
+				let promise = try!(queue.add_request_with_ttl(ConfirmationPayload::Transaction(request), ttl)
+					.map_err(|_| errors::request_rejected_limit()));
+				if let Some(ttl) = ttl {
+					self.expire_after(ttl);
+				}
+				Ok(DispatchResult::Promise(promise))
 			})
 	}
+
+	/// Rejects expired requests once `ttl` elapses, even if nobody polls the queue meanwhile.
+	fn expire_after(&self, ttl: Duration) {
+		let queue = self.queue.clone();
+		let spawned = thread::Builder::new().name("signer-expiry".into()).spawn(move || {
+			thread::sleep(ttl);
+			if let Some(queue) = queue.upgrade() {
+				queue.expire_requests();
+			}
+		});
+		if let Err(e) = spawned {
+			warn!(target: "own_tx", "Unable to schedule request expiry: {}", e);
+		}
+	}
 }
 
 impl<C, M> EthSigning for EthSigningQueueClient<C, M>
@@ -131,6 +166,19 @@ impl<C, M> EthSigning for EthSigningQueueClient<C, M>
 		}
 	}
 
+	fn sign_typed_data(&self, params: Params, ready: Ready) {
+		let res = self.active().and_then(|_| self.dispatch_sign_typed_data(params));
+		match res {
+			Ok(DispatchResult::Promise(promise)) => {
+				promise.wait_for_result(move |result| {
+					ready.ready(result.unwrap_or_else(|| Err(errors::request_rejected())))
+				})
+			},
+			Ok(DispatchResult::Value(v)) => ready.ready(Ok(v)),
+			Err(e) => ready.ready(Err(e)),
+		}
+	}
+
 	fn post_sign(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		self.dispatch_sign(params).map(|result| match result {
@@ -170,6 +218,7 @@ impl<C, M> EthSigning for EthSigningQueueClient<C, M>
 
 	fn check_request(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
+		take_weak!(self.queue).expire_requests();
 		let mut pending = self.pending.lock();
 		from_params::<(RpcU256, )>(params).and_then(|(id, )| {
 			let id: U256 = id.into();
@@ -177,6 +226,7 @@ impl<C, M> EthSigning for EthSigningQueueClient<C, M>
 				Some(ref promise) => match promise.result() {
 					ConfirmationResult::Waiting => { return Ok(Value::Null); }
 					ConfirmationResult::Rejected => Err(errors::request_rejected()),
+					ConfirmationResult::Expired => Err(errors::request_expired()),
 					ConfirmationResult::Confirmed(rpc_response) => rpc_response,
 				},
 				_ => { return Err(errors::request_not_found()); }
@@ -185,6 +235,21 @@ impl<C, M> EthSigning for EthSigningQueueClient<C, M>
 			res
 		})
 	}
+
+	fn replace_request(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(RpcU256, TransactionRequest)>(params).and_then(|(id, request)| {
+			let request: TRequest = request.into();
+			let (client, miner) = (take_weak!(self.client), take_weak!(self.miner));
+			let request = fill_optional_fields(request, &*client, &*miner);
+			take_weak!(self.queue).replace_request(id.into(), ConfirmationPayload::Transaction(request))
+				.map(|_| Value::Bool(true))
+				.map_err(|e| match e {
+					QueueReplaceError::NotFound => errors::request_not_found(),
+					QueueReplaceError::OriginMismatch => errors::request_forbidden(),
+				})
+		})
+	}
 }
 
 /// Implementation of functions that require signing when no trusted signer is used.
@@ -231,6 +296,16 @@ impl<C, M> EthSigning for EthSigningUnsafeClient<C, M> where
 			}))
 	}
 
+	fn sign_typed_data(&self, params: Params, ready: Ready) {
+		ready.ready(self.active()
+			.and_then(|_| from_params::<(RpcH160, Value)>(params))
+			.and_then(|(address, data)| {
+				let address: Address = address.into();
+				let hash = try!(hash_typed_data(&data).map_err(|e| errors::invalid_params("Typed data", e)));
+				Ok(to_value(&take_weak!(self.accounts).sign(address, hash).ok().map_or_else(RpcH520::default, Into::into)))
+			}))
+	}
+
 	fn send_transaction(&self, params: Params, ready: Ready) {
 		ready.ready(self.active()
 			.and_then(|_| from_params::<(TransactionRequest, )>(params))
@@ -255,4 +330,9 @@ impl<C, M> EthSigning for EthSigningUnsafeClient<C, M> where
 		// We don't support this in non-signer mode.
 		Err(errors::signer_disabled())
 	}
+
+	fn replace_request(&self, _: Params) -> Result<Value, Error> {
+		// We don't support this in non-signer mode.
+		Err(errors::signer_disabled())
+	}
 }
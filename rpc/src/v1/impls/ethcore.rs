@@ -17,21 +17,25 @@
 //! Ethcore-specific rpc implementation.
 use std::sync::{Arc, Weak};
 use std::str::FromStr;
+use std::cmp::min;
 use std::collections::{BTreeMap};
-use util::{RotatingLogger, Address};
-use util::misc::version_data;
+use serde_json;
+use util::{RotatingLogger, Address, Mutex, Uint, H256 as EthH256, U256 as EthU256};
+use util::misc::{version, version_data, short_sha};
 
 use ethkey::{Brain, Generator};
 use ethstore::random_phrase;
 use ethsync::{SyncProvider, ManageNetwork};
 use ethcore::miner::MinerService;
-use ethcore::client::{MiningBlockChainClient};
+use ethcore::client::{MiningBlockChainClient, BlockID, TransactionID};
+use ethcore::snapshot::SnapshotService;
+use ethcore::views::{BlockView, HeaderView};
 
 use jsonrpc_core::*;
 use v1::traits::Ethcore;
-use v1::types::{Bytes, U256, H160, Peers};
-use v1::helpers::{errors, SigningQueue, ConfirmationsQueue, NetworkSettings};
-use v1::helpers::params::expect_no_params;
+use v1::types::{Bytes, U256, H160, H256, Peers, BadBlock, BlockNumber, BlockUtilization, VersionInfo, SnapshotStatus};
+use v1::helpers::{errors, SigningQueue, ConfirmationsQueue, NetworkSettings, NewHeadsSubscriptions, HealthChecks};
+use v1::helpers::params::{expect_no_params, params_len};
 
 /// Ethcore implementation.
 pub struct EthcoreClient<C, M, S: ?Sized> where
@@ -46,8 +50,19 @@ pub struct EthcoreClient<C, M, S: ?Sized> where
 	logger: Arc<RotatingLogger>,
 	settings: Arc<NetworkSettings>,
 	confirmations_queue: Option<Arc<ConfirmationsQueue>>,
+	stable_block_confirmations: u64,
+	utilization_cache: Mutex<Option<(EthH256, u64, BlockUtilization)>>,
+	new_heads: Option<Arc<NewHeadsSubscriptions>>,
+	snapshot: Option<Weak<SnapshotService>>,
+	health: Option<HealthChecks>,
 }
 
+/// Default number of confirmations after which a block is considered stable.
+pub const DEFAULT_STABLE_BLOCK_CONFIRMATIONS: u64 = 12;
+
+/// Maximal number of blocks described by `parity_blockUtilization`.
+const MAX_UTILIZATION_BLOCKS: u64 = 1024;
+
 impl<C, M, S: ?Sized> EthcoreClient<C, M, S> where C: MiningBlockChainClient, M: MinerService, S: SyncProvider {
 	/// Creates new `EthcoreClient`.
 	pub fn new(
@@ -67,14 +82,70 @@ impl<C, M, S: ?Sized> EthcoreClient<C, M, S> where C: MiningBlockChainClient, M:
 			logger: logger,
 			settings: settings,
 			confirmations_queue: queue,
+			stable_block_confirmations: DEFAULT_STABLE_BLOCK_CONFIRMATIONS,
+			utilization_cache: Mutex::new(None),
+			new_heads: None,
+			snapshot: None,
+			health: None,
 		}
 	}
 
+	/// Sets the default number of confirmations used by `parity_stableBlockNumber`.
+	pub fn with_stable_block_confirmations(mut self, confirmations: u64) -> Self {
+		self.stable_block_confirmations = confirmations;
+		self
+	}
+
+	/// Enables `parity_subscribeNewHeads` backed by given subscriptions.
+	pub fn with_new_heads(mut self, new_heads: Arc<NewHeadsSubscriptions>) -> Self {
+		self.new_heads = Some(new_heads);
+		self
+	}
+
+	/// Enables `parity_snapshotStatus` backed by given snapshot service.
+	pub fn with_snapshot_service(mut self, snapshot: &Arc<SnapshotService>) -> Self {
+		self.snapshot = Some(Arc::downgrade(snapshot));
+		self
+	}
+
+	/// Enables `parity_nodeHealth` using given checks.
+	pub fn with_health_checks(mut self, health: HealthChecks) -> Self {
+		self.health = Some(health);
+		self
+	}
+
 	fn active(&self) -> Result<(), Error> {
 		// TODO: only call every 30s at most.
 		take_weak!(self.client).keep_alive();
 		Ok(())
 	}
+
+	/// Collects utilization of the `count` blocks up to and including the given best block.
+	fn collect_utilization(client: &C, best: u64, count: u64) -> BlockUtilization {
+		let oldest = (best + 1).saturating_sub(count);
+		let mut parent_timestamp = match oldest {
+			0 => None,
+			n => client.block(BlockID::Number(n - 1)).map(|b| BlockView::new(&b).header_view().timestamp()),
+		};
+
+		let mut utilization = BlockUtilization { oldest_block: oldest.into(), ..Default::default() };
+		for number in oldest..(best + 1) {
+			let block = match client.block(BlockID::Number(number)) {
+				Some(block) => block,
+				None => break,
+			};
+			let view = BlockView::new(&block);
+			let header = view.header_view();
+			let timestamp = header.timestamp();
+
+			utilization.gas_used.push(header.gas_used().into());
+			utilization.gas_limit.push(header.gas_limit().into());
+			utilization.transaction_count.push(view.transactions_count().into());
+			utilization.block_interval.push(timestamp.saturating_sub(parent_timestamp.unwrap_or(timestamp)).into());
+			parent_timestamp = Some(timestamp);
+		}
+		utilization
+	}
 }
 
 impl<C, M, S: ?Sized> Ethcore for EthcoreClient<C, M, S> where M: MinerService + 'static, C: MiningBlockChainClient + 'static, S: SyncProvider + 'static {
@@ -217,4 +288,158 @@ impl<C, M, S: ?Sized> Ethcore for EthcoreClient<C, M, S> where M: MinerService +
 			to_value(&H160::from(Brain::new(phrase).generate().unwrap().address()))
 		)
 	}
+
+	fn bad_blocks(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let bad_blocks = take_weak!(self.client).bad_blocks();
+		Ok(to_value(&bad_blocks.into_iter().map(BadBlock::from).collect::<Vec<_>>()))
+	}
+
+	fn stable_block_number(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let confirmations: EthU256 = match params_len(&params) {
+			0 => self.stable_block_confirmations.into(),
+			_ => try!(from_params::<(U256,)>(params)).0.into(),
+		};
+
+		let best = EthU256::from(take_weak!(self.client).chain_info().best_block_number);
+		let stable = match confirmations >= best {
+			true => EthU256::from(0u64),
+			false => best - confirmations,
+		};
+		Ok(to_value(&U256::from(stable)))
+	}
+
+	fn block_rlp(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let (block,) = try!(from_params::<(Value,)>(params));
+		let id: BlockID = match serde_json::from_value::<H256>(block.clone()) {
+			Ok(hash) => BlockID::Hash(hash.into()),
+			Err(_) => try!(serde_json::from_value::<BlockNumber>(block.clone())
+				.map_err(|_| errors::invalid_params("Expected block hash or number", block))).into(),
+		};
+
+		let block = take_weak!(self.client).block(id);
+		Ok(to_value(&block.map(Bytes::new)))
+	}
+
+	fn raw_transaction(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let (hash,) = try!(from_params::<(H256,)>(params));
+
+		let transaction = take_weak!(self.client).transaction_rlp(TransactionID::Hash(hash.into()));
+		Ok(to_value(&transaction.map(Bytes::new)))
+	}
+
+	fn block_utilization(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let (count,) = try!(from_params::<(U256,)>(params));
+		let count = min(count.into(), EthU256::from(MAX_UTILIZATION_BLOCKS)).low_u64();
+
+		let client = take_weak!(self.client);
+		let chain_info = client.chain_info();
+		match *self.utilization_cache.lock() {
+			Some((ref hash, cached_count, ref utilization)) if *hash == chain_info.best_block_hash && cached_count == count =>
+				return Ok(to_value(utilization)),
+			_ => {},
+		}
+
+		// blocks are fetched without holding the cache lock so concurrent callers aren't serialized behind it.
+		let utilization = Self::collect_utilization(&*client, chain_info.best_block_number, count);
+		*self.utilization_cache.lock() = Some((chain_info.best_block_hash, count, utilization.clone()));
+		Ok(to_value(&utilization))
+	}
+
+	fn version_info(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let commit = match short_sha() {
+			"" => None,
+			sha => Some(sha.to_owned()),
+		};
+		Ok(to_value(&VersionInfo {
+			version: version(),
+			commit: commit,
+			protocol_versions: take_weak!(self.sync).protocol_versions(),
+			chain: take_weak!(self.client).chain_name(),
+		}))
+	}
+
+	fn snapshot_status(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let snapshot = match self.snapshot {
+			Some(ref snapshot) => take_weak!(snapshot),
+			None => return Err(errors::unimplemented()),
+		};
+		Ok(to_value(&SnapshotStatus::new(snapshot.creation_status(), snapshot.status())))
+	}
+
+	fn node_health(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let health = match self.health {
+			Some(ref health) => health,
+			None => return Err(errors::unimplemented()),
+		};
+		let best_block = take_weak!(self.client).chain_info().best_block_number;
+		Ok(to_value(&health.check(&take_weak!(self.sync).status(), best_block)))
+	}
+
+	fn metrics(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let client = take_weak!(self.client);
+		let best_block = client.chain_info().best_block_number;
+		let queue = client.queue_info();
+		let sync = take_weak!(self.sync).status();
+		let net = take_weak!(self.net).network_metrics();
+		let miner = take_weak!(self.miner).status();
+
+		let mut metrics = BTreeMap::new();
+		metrics.insert("bestBlock", best_block);
+		metrics.insert("highestBlock", sync.highest_block_number.unwrap_or(best_block));
+		metrics.insert("peers", sync.num_peers as u64);
+		metrics.insert("activePeers", sync.num_active_peers as u64);
+		metrics.insert("inboundPeers", net.inbound_peers);
+		metrics.insert("outboundPeers", net.outbound_peers);
+		metrics.insert("unverifiedBlocks", queue.unverified_queue_size as u64);
+		metrics.insert("verifyingBlocks", queue.verifying_queue_size as u64);
+		metrics.insert("verifiedBlocks", queue.verified_queue_size as u64);
+		metrics.insert("pendingTransactions", miner.transactions_in_pending_queue as u64);
+		metrics.insert("queuedTransactions", miner.transactions_in_future_queue as u64);
+		metrics.insert("bytesSent", net.bytes_sent);
+		metrics.insert("bytesReceived", net.bytes_received);
+		Ok(to_value(&metrics))
+	}
+
+	fn current_difficulty(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let header = take_weak!(self.client).best_block_header();
+		Ok(to_value(&U256::from(HeaderView::new(&header).difficulty())))
+	}
+
+	fn next_difficulty(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		Ok(to_value(&take_weak!(self.client).next_block_difficulty().map(U256::from)))
+	}
+
+	fn subscribe_new_heads(&self, params: Params, ready: Ready) {
+		let res = self.active().and_then(|_| expect_no_params(params));
+		match (res, self.new_heads.as_ref()) {
+			(Ok(()), Some(new_heads)) => new_heads.subscribe(ready),
+			(Ok(()), None) => ready.ready(Err(errors::unimplemented())),
+			(Err(e), _) => ready.ready(Err(e)),
+		}
+	}
 }
@@ -19,6 +19,7 @@ use std::sync::{Arc, Weak};
 use jsonrpc_core::*;
 use ethcore::miner::MinerService;
 use ethcore::client::MiningBlockChainClient;
+use ethcore::snapshot::SnapshotService;
 use ethsync::ManageNetwork;
 use v1::helpers::errors;
 use v1::helpers::params::expect_no_params;
@@ -33,6 +34,7 @@ pub struct EthcoreSetClient<C, M> where
 	client: Weak<C>,
 	miner: Weak<M>,
 	net: Weak<ManageNetwork>,
+	snapshot: Option<Weak<SnapshotService>>,
 }
 
 impl<C, M> EthcoreSetClient<C, M> where
@@ -44,9 +46,16 @@ impl<C, M> EthcoreSetClient<C, M> where
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
 			net: Arc::downgrade(net),
+			snapshot: None,
 		}
 	}
 
+	/// Enables `parity_takeSnapshot` backed by given snapshot service.
+	pub fn with_snapshot_service(mut self, snapshot: &Arc<SnapshotService>) -> Self {
+		self.snapshot = Some(Arc::downgrade(snapshot));
+		self
+	}
+
 	fn active(&self) -> Result<(), Error> {
 		// TODO: only call every 30s at most.
 		take_weak!(self.client).keep_alive();
@@ -159,4 +168,17 @@ impl<C, M> EthcoreSet for EthcoreSetClient<C, M> where
 		take_weak!(self.net).stop_network();
 		Ok(Value::Bool(true))
 	}
+
+	fn take_snapshot(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let (name,) = try!(from_params::<(String,)>(params));
+
+		let snapshot = match self.snapshot {
+			Some(ref snapshot) => take_weak!(snapshot),
+			None => return Err(errors::unimplemented()),
+		};
+		let num = take_weak!(self.client).chain_info().best_block_number;
+		try!(snapshot.request_snapshot(num, name).map_err(errors::snapshot));
+		Ok(to_value(&U256::from(num)))
+	}
 }
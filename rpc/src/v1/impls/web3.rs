@@ -16,24 +16,36 @@
 
 //! Web3 rpc implementation.
 use jsonrpc_core::*;
-use util::version;
+use util::version_with_identity;
 use v1::traits::Web3;
 use v1::types::{H256, Bytes};
 use v1::helpers::params::expect_no_params;
 use util::sha3::Hashable;
 
 /// Web3 rpc implementation.
-pub struct Web3Client;
+pub struct Web3Client {
+	identity: String,
+}
 
 impl Web3Client {
 	/// Creates new Web3Client.
-	pub fn new() -> Self { Web3Client }
+	pub fn new() -> Self {
+		Web3Client {
+			identity: String::new(),
+		}
+	}
+
+	/// Sets the node identity reported in the client version.
+	pub fn with_identity(mut self, identity: String) -> Self {
+		self.identity = identity;
+		self
+	}
 }
 
 impl Web3 for Web3Client {
 	fn client_version(&self, params: Params) -> Result<Value, Error> {
 		try!(expect_no_params(params));
-		Ok(Value::String(version().to_owned().replace("Parity/", "Parity//")))
+		Ok(Value::String(version_with_identity(&self.identity)))
 	}
 
 	fn sha3(&self, params: Params) -> Result<Value, Error> {
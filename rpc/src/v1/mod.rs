@@ -28,4 +28,5 @@ pub mod types;
 
 pub use self::traits::{Web3, Eth, EthFilter, EthSigning, Personal, PersonalSigner, Net, Ethcore, EthcoreSet, Traces, Rpc};
 pub use self::impls::*;
-pub use self::helpers::{SigningQueue, ConfirmationsQueue, NetworkSettings};
+pub use self::helpers::{SigningQueue, ConfirmationsQueue, NetworkSettings, NewHeadsSubscriptions,
+	HealthChecks, TimeSource, SntpTimeSource, DEFAULT_MIN_HEALTHY_PEERS, DEFAULT_SNTP_SERVER};
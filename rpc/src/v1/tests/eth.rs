@@ -24,16 +24,18 @@ use ethcore::spec::{Genesis, Spec};
 use ethcore::block::Block;
 use ethcore::views::BlockView;
 use ethcore::ethereum;
-use ethcore::miner::{MinerOptions, GasPricer, MinerService, ExternalMiner, Miner, PendingSet};
+use ethcore::miner::{MinerOptions, GasPricer, MinerService, ExternalMiner, Miner, PendingSet, TransactionOrdering};
 use ethcore::account_provider::AccountProvider;
 use devtools::RandomTempPath;
 use util::Hashable;
+use util::sha3::SHA3_NULL_RLP;
 use io::IoChannel;
 use util::{U256, H256, Uint, Address};
 use jsonrpc_core::IoHandler;
 use ethjson::blockchain::BlockChain;
+use serde_json::{self, Value};
 
-use v1::types::U256 as NU256;
+use v1::types::{Bytes as RpcBytes, H256 as RpcH256, U256 as NU256};
 use v1::traits::eth::{Eth, EthSigning};
 use v1::impls::{EthClient, EthSigningUnsafeClient};
 use v1::tests::helpers::{TestSyncProvider, Config};
@@ -59,6 +61,8 @@ fn miner_service(spec: &Spec, accounts: Arc<AccountProvider>) -> Arc<Miner> {
 			tx_queue_size: 1024,
 			tx_gas_limit: !U256::zero(),
 			pending_set: PendingSet::SealingOrElseQueue,
+			tx_ordering: TransactionOrdering::GasPriceThenNonce,
+			tx_replacement_bump: 0,
 			reseal_min_period: Duration::from_secs(0),
 			work_queue_size: 50,
 			enable_resubmission: true,
@@ -292,6 +296,79 @@ const POSITIVE_NONCE_SPEC: &'static [u8] = br#"{
 }
 "#;
 
+// a frontier-like test spec with an externally owned account and a contract.
+const ACCOUNT_SPEC: &'static [u8] = br#"{
+	"name": "Frontier (Test)",
+	"engine": {
+		"Ethash": {
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"minimumDifficulty": "0x020000",
+				"difficultyBoundDivisor": "0x0800",
+				"durationLimit": "0x0d",
+				"blockReward": "0x4563918244F40000",
+				"registrar" : "0xc6d9d2cd449a754c494264e1809c50e34d64562b",
+				"frontierCompatibilityModeLimit": "0xffffffffffffffff",
+				"daoHardforkTransition": "0xffffffffffffffff",
+				"daoHardforkBeneficiary": "0x0000000000000000000000000000000000000000",
+				"daoHardforkAccounts": []
+			}
+		}
+	},
+	"params": {
+		"accountStartNonce": "0x00",
+		"maximumExtraDataSize": "0x20",
+		"minGasLimit": "0x50000",
+		"networkID" : "0x1"
+	},
+	"genesis": {
+		"seal": {
+			"ethereum": {
+				"nonce": "0x0000000000000042",
+				"mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+			}
+		},
+		"difficulty": "0x400000000",
+		"author": "0x0000000000000000000000000000000000000000",
+		"timestamp": "0x00",
+		"parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+		"extraData": "0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+		"gasLimit": "0x50000"
+	},
+	"accounts": {
+		"0000000000000000000000000000000000000001": { "builtin": { "name": "ecrecover", "pricing": { "linear": { "base": 3000, "word": 0 } } } },
+		"0000000000000000000000000000000000000002": { "builtin": { "name": "sha256", "pricing": { "linear": { "base": 60, "word": 12 } } } },
+		"0000000000000000000000000000000000000003": { "builtin": { "name": "ripemd160", "pricing": { "linear": { "base": 600, "word": 120 } } } },
+		"0000000000000000000000000000000000000004": { "builtin": { "name": "identity", "pricing": { "linear": { "base": 15, "word": 3 } } } },
+		"00000000000000000000000000000000000000aa": { "balance": "5", "nonce": "1", "code": "0x600160005500" },
+		"faa34835af5c2ea724333018a515fbb7d5bc0b33": { "balance": "10000000000000", "nonce": "3" }
+	}
+}
+"#;
+
+#[test]
+fn eth_get_account() {
+	fn result_of(tester: &EthTester, method: &str, address: &str) -> Value {
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "{}", "params": ["{}", "latest"], "id": 1}}"#, method, address);
+		let response: Value = serde_json::from_str(&tester.handler.handle_request_sync(&request).unwrap()).unwrap();
+		response.find("result").expect("request should succeed").clone()
+	}
+
+	let tester = EthTester::from_spec(Spec::load(ACCOUNT_SPEC).expect("invalid chain spec"));
+
+	for &(address, code_size) in &[("0xfaa34835af5c2ea724333018a515fbb7d5bc0b33", 0), ("0x00000000000000000000000000000000000000aa", 6)] {
+		let account = result_of(&tester, "eth_getAccount", address);
+		let code: RpcBytes = serde_json::from_value(result_of(&tester, "eth_getCode", address)).unwrap();
+
+		assert_eq!(account.find("balance"), Some(&result_of(&tester, "eth_getBalance", address)));
+		assert_eq!(account.find("nonce"), Some(&result_of(&tester, "eth_getTransactionCount", address)));
+		assert_eq!(account.find("codeSize"), Some(&serde_json::to_value(&NU256::from(code.0.len()))));
+		assert_eq!(account.find("codeSize"), Some(&serde_json::to_value(&NU256::from(code_size))));
+		assert_eq!(account.find("codeHash"), Some(&serde_json::to_value(&RpcH256::from(code.0.sha3()))));
+		assert_eq!(account.find("storageRoot"), Some(&serde_json::to_value(&RpcH256::from(SHA3_NULL_RLP))));
+	}
+}
+
 #[test]
 fn eth_transaction_count() {
 	let secret = "8a283037bb19c4fed7b1c569e40c7dcff366165eb869110a1b11532963eb9cb2".into();
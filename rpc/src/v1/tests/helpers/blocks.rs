@@ -0,0 +1,30 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test blocks.
+
+use util::Bytes;
+use rlp::{self, RlpStream, Stream};
+use ethcore::header::Header;
+
+/// Block with given header and no transactions or uncles.
+pub fn empty_block(header: &Header) -> Bytes {
+	let mut block = RlpStream::new_list(3);
+	block.append(header);
+	block.append_raw(&rlp::EMPTY_LIST_RLP, 1);
+	block.append_raw(&rlp::EMPTY_LIST_RLP, 1);
+	block.out()
+}
@@ -18,7 +18,7 @@
 
 use util::{Address, H256, Bytes, U256, FixedHash, Uint};
 use util::standard::*;
-use ethcore::error::{Error, CallError};
+use ethcore::error::{Error, CallError, TransactionError};
 use ethcore::client::{MiningBlockChainClient, Executed, CallAnalytics};
 use ethcore::block::{ClosedBlock, IsBlock};
 use ethcore::transaction::SignedTransaction;
@@ -149,6 +149,10 @@ impl MinerService for TestMinerService {
 	fn import_own_transaction(&self, chain: &MiningBlockChainClient, transaction: SignedTransaction) ->
 		Result<TransactionImportResult, Error> {
 
+		if self.imported_transactions.lock().iter().any(|t| t.hash() == transaction.hash()) {
+			return Err(Error::Transaction(TransactionError::AlreadyImported));
+		}
+
 		// keep the pending nonces up to date
 		if let Ok(ref sender) = transaction.sender() {
 			let nonce = self.last_nonce(sender).unwrap_or(chain.latest_nonce(sender));
@@ -186,6 +190,10 @@ impl MinerService for TestMinerService {
 		Some(f(&open_block.close()))
 	}
 
+	fn map_pending_block<F, T>(&self, f: F) -> Option<T> where F: FnOnce(&ClosedBlock) -> T {
+		self.latest_closed_block.lock().as_ref().map(f)
+	}
+
 	fn transaction(&self, hash: &H256) -> Option<SignedTransaction> {
 		self.pending_transactions.lock().get(hash).cloned()
 	}
@@ -202,7 +210,7 @@ impl MinerService for TestMinerService {
 		// Not much point implementing this since the logic is complex and the only thing it relies on is pending_receipts, which is already tested.
 		self.pending_receipts().get(hash).map(|r|
 			RichReceipt {
-				transaction_hash: Default::default(),
+				transaction_hash: hash.clone(),
 				transaction_index: Default::default(),
 				cumulative_gas_used: r.gas_used.clone(),
 				gas_used: r.gas_used.clone(),
@@ -252,4 +260,8 @@ impl MinerService for TestMinerService {
 		self.latest_closed_block.lock().as_ref().map_or(None, |b| b.block().fields().state.code(address).clone())
 	}
 
+	fn next_nonce(&self, _chain: &MiningBlockChainClient, address: &Address) -> U256 {
+		self.last_nonce(address).map_or_else(U256::zero, |nonce| nonce + U256::one())
+	}
+
 }
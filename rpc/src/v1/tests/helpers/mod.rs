@@ -16,8 +16,12 @@
 
 //! Test rpc services.
 
+mod blocks;
 mod sync_provider;
 mod miner_service;
+mod snapshot_service;
 
+pub use self::blocks::empty_block;
 pub use self::sync_provider::{Config, TestSyncProvider};
 pub use self::miner_service::TestMinerService;
+pub use self::snapshot_service::TestSnapshotService;
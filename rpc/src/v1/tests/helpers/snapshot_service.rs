@@ -0,0 +1,56 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test implementation of SnapshotService.
+
+use util::{Bytes, H256, Mutex};
+use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus, CreationStatus};
+
+/// Test snapshot service.
+#[derive(Default)]
+pub struct TestSnapshotService {
+	/// Status of the snapshot being taken.
+	pub creation: Mutex<Option<CreationStatus>>,
+	/// Restoration status.
+	pub restoration: Mutex<Option<RestorationStatus>>,
+	/// Block number and file name of the last requested snapshot.
+	pub requested: Mutex<Option<(u64, String)>>,
+}
+
+impl TestSnapshotService {
+	/// Creates new idle snapshot service.
+	pub fn new() -> Self {
+		TestSnapshotService::default()
+	}
+}
+
+impl SnapshotService for TestSnapshotService {
+	fn manifest(&self) -> Option<ManifestData> { None }
+	fn chunk(&self, _hash: H256) -> Option<Bytes> { None }
+	fn status(&self) -> RestorationStatus { self.restoration.lock().unwrap_or(RestorationStatus::Inactive) }
+	fn creation_status(&self) -> Option<CreationStatus> { *self.creation.lock() }
+	fn request_snapshot(&self, num: u64, name: String) -> Result<(), String> {
+		if self.creation.lock().is_some() {
+			return Err("Another snapshot is currently being taken".into());
+		}
+		*self.requested.lock() = Some((num, name));
+		Ok(())
+	}
+	fn begin_restore(&self, _manifest: ManifestData) { }
+	fn abort_restore(&self) { }
+	fn restore_state_chunk(&self, _hash: H256, _chunk: Bytes) { }
+	fn restore_block_chunk(&self, _hash: H256, _chunk: Bytes) { }
+}
@@ -17,7 +17,7 @@
 //! Test implementation of SyncProvider.
 
 use util::{RwLock, U256};
-use ethsync::{SyncProvider, SyncStatus, SyncState};
+use ethsync::{SyncProvider, SyncStatus, SyncState, PeerRequestInfo};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -49,6 +49,7 @@ impl TestSyncProvider {
 				num_peers: config.num_peers,
 				num_active_peers: 0,
 				mem_used: 0,
+				max_mem_use: None,
 				num_snapshot_chunks: 0,
 				snapshot_chunks_done: 0,
 			}),
@@ -60,5 +61,13 @@ impl SyncProvider for TestSyncProvider {
 	fn status(&self) -> SyncStatus {
 		self.status.read().clone()
 	}
+
+	fn protocol_versions(&self) -> Vec<u8> {
+		vec![62, 63, 64]
+	}
+
+	fn peers(&self) -> Vec<PeerRequestInfo> {
+		Vec::new()
+	}
 }
 
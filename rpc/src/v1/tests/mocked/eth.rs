@@ -17,18 +17,21 @@
 use std::str::FromStr;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::time::{Instant, Duration};
 use jsonrpc_core::IoHandler;
 use util::{Uint, U256, Address, H256, FixedHash, Mutex};
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{TestBlockChainClient, EachBlockWith, Executed, TransactionID};
+use ethcore::client::{BlockChainClient, MiningBlockChainClient, TestBlockChainClient, EachBlockWith, Executed, TransactionID};
+use ethcore::error::{CallError, ExecutionError};
+use ethcore::header::Header;
 use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
 use ethcore::receipt::LocalizedReceipt;
 use ethcore::transaction::{Transaction, Action};
 use ethcore::miner::{ExternalMiner, MinerService};
 use ethsync::SyncState;
-use v1::{Eth, EthClient, EthClientOptions, EthSigning, EthSigningUnsafeClient};
-use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService};
+use v1::{Eth, EthClient, EthClientOptions, EthFilter, EthFilterClient, EthSigning, EthSigningUnsafeClient};
+use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, empty_block};
 use rustc_serialize::hex::ToHex;
 use time::get_time;
 
@@ -171,6 +174,61 @@ fn rpc_eth_logs_with_limit() {
 	assert_eq!(tester.io.handle_request_sync(request2), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_logs_detailed_scans_only_matching_blocks() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(1000, EachBlockWith::Nothing);
+	let log = |number: u64, address: Address| LocalizedLogEntry {
+		entry: LogEntry {
+			address: address,
+			topics: vec![],
+			data: vec![],
+		},
+		block_hash: H256::from(number),
+		block_number: number,
+		transaction_hash: H256::default(),
+		transaction_index: 0,
+		log_index: 0,
+	};
+	tester.client.set_logs(vec![
+		log(100, Address::from(1)),
+		log(300, Address::from(2)),
+		log(600, Address::from(1)),
+		log(600, Address::from(1)),
+	]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getLogsDetailed", "params": [{"fromBlock":"earliest","address":"0x0000000000000000000000000000000000000001"}], "id": 1}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+
+	assert_eq!(response.matches(r#""blockNumber":"#).count(), 3);
+	assert!(response.contains(r#""blocksScanned":"0x2","blocksMatched":"0x2""#));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"fromBlock":"earliest","address":"0x0000000000000000000000000000000000000001"}], "id": 1}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+	assert_eq!(response.matches(r#""blockNumber":"#).count(), 3);
+	assert!(!response.contains("blocksScanned"));
+}
+
+#[test]
+fn rpc_eth_logs_with_conflicting_bounds() {
+	let tester = EthTester::default();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"fromBlock":"0x1","fromTimestamp":"0x57bd8e40"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: filter","data":"\"Both fromBlock and fromTimestamp specified.\""},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_logs_with_time_window() {
+	let tester = EthTester::default();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"fromTimestamp":"0x0","toTimestamp":"0x57bd8e40"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_submit_hashrate() {
 	let tester = EthTester::default();
@@ -213,6 +271,83 @@ fn rpc_eth_sign() {
 	assert_eq!(tester.io.handle_request_sync(&req), Some(res));
 }
 
+#[test]
+fn rpc_eth_sign_typed_data() {
+	use ethkey::{recover, public_to_address};
+	use util::sha3::Hashable;
+
+	let tester = EthTester::default();
+
+	let account = tester.accounts_provider.insert_account("cow".sha3(), "abcd").unwrap();
+	assert_eq!(account, Address::from_str("cd2a3d9f938e13cd947ec05abc7fe734df8dd826").unwrap());
+	tester.accounts_provider.unlock_account_permanently(account, "abcd".into()).unwrap();
+
+	let hash = H256::from("0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+	let signed = tester.accounts_provider.sign(account, hash).unwrap();
+	assert_eq!(public_to_address(&recover(&signed, &hash).unwrap()), account);
+
+	let req = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_signTypedData",
+		"params": [
+			"0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826",
+			{
+				"types": {
+					"EIP712Domain": [
+						{ "name": "name", "type": "string" },
+						{ "name": "version", "type": "string" },
+						{ "name": "chainId", "type": "uint256" },
+						{ "name": "verifyingContract", "type": "address" }
+					],
+					"Person": [
+						{ "name": "name", "type": "string" },
+						{ "name": "wallet", "type": "address" }
+					],
+					"Mail": [
+						{ "name": "from", "type": "Person" },
+						{ "name": "to", "type": "Person" },
+						{ "name": "contents", "type": "string" }
+					]
+				},
+				"primaryType": "Mail",
+				"domain": {
+					"name": "Ether Mail",
+					"version": "1",
+					"chainId": 1,
+					"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+				},
+				"message": {
+					"from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+					"to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+					"contents": "Hello, Bob!"
+				}
+			}
+		],
+		"id": 1
+	}"#;
+	let res = r#"{"jsonrpc":"2.0","result":""#.to_owned() + &format!("0x{}", signed) + r#"","id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(&req), Some(res));
+}
+
+#[test]
+fn rpc_eth_sign_typed_data_rejects_malformed_input() {
+	let tester = EthTester::default();
+
+	let req = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_signTypedData",
+		"params": [
+			"0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826",
+			{ "types": {}, "primaryType": "Mail", "domain": {}, "message": {} }
+		],
+		"id": 1
+	}"#;
+	let res = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: Typed data","data":"\"`types` must define `EIP712Domain`.\""},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(&req), Some(res.to_owned()));
+}
+
 #[test]
 fn rpc_eth_author() {
 	let make_res = |addr| r#"{"jsonrpc":"2.0","result":""#.to_owned() + &format!("0x{:?}", addr) + r#"","id":1}"#;
@@ -235,6 +370,47 @@ fn rpc_eth_author() {
 	}
 }
 
+#[test]
+fn rpc_eth_author_falls_back_to_first_account() {
+	let make_res = |addr| r#"{"jsonrpc":"2.0","result":""#.to_owned() + &format!("0x{:?}", addr) + r#"","id":1}"#;
+	let tester = EthTester::new_with_options(EthClientOptions {
+		author_account_fallback: true,
+		..Default::default()
+	});
+
+	let req = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_coinbase",
+		"params": [],
+		"id": 1
+	}"#;
+
+	// no accounts
+	assert_eq!(tester.io.handle_request_sync(req), Some(make_res(Address::zero())));
+
+	let account = tester.accounts_provider.new_account("").unwrap();
+	assert_eq!(tester.io.handle_request_sync(req), Some(make_res(account)));
+
+	// configured author takes precedence
+	let author = Address::from(1);
+	tester.miner.set_author(author.clone());
+	assert_eq!(tester.io.handle_request_sync(req), Some(make_res(author)));
+}
+
+#[test]
+fn rpc_eth_author_fallback_respects_hidden_accounts() {
+	let make_res = |addr| r#"{"jsonrpc":"2.0","result":""#.to_owned() + &format!("0x{:?}", addr) + r#"","id":1}"#;
+	let tester = EthTester::new_with_options(EthClientOptions {
+		author_account_fallback: true,
+		max_accounts: Some(0),
+		..Default::default()
+	});
+	tester.accounts_provider.new_account("").unwrap();
+
+	let req = r#"{"jsonrpc": "2.0", "method": "eth_coinbase", "params": [], "id": 1}"#;
+	assert_eq!(tester.io.handle_request_sync(req), Some(make_res(Address::zero())));
+}
+
 #[test]
 fn rpc_eth_mining() {
 	let tester = EthTester::default();
@@ -264,6 +440,37 @@ fn rpc_eth_accounts() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_accounts_capped() {
+	let tester = EthTester::new_with_options(EthClientOptions {
+		max_accounts: Some(2),
+		..Default::default()
+	});
+	for _ in 0..3 {
+		tester.accounts_provider.new_account("").unwrap();
+	}
+	let accounts = tester.accounts_provider.accounts().unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_accounts", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[""#.to_owned() + &format!("0x{:?}", accounts[0]) + r#"",""# + &format!("0x{:?}", accounts[1]) + r#""],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_accounts_disabled() {
+	let tester = EthTester::new_with_options(EthClientOptions {
+		max_accounts: Some(0),
+		..Default::default()
+	});
+	tester.accounts_provider.new_account("").unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_accounts", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_block_number() {
 	let tester = EthTester::default();
@@ -275,6 +482,114 @@ fn rpc_eth_block_number() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_pending_block() {
+	let tester = EthTester::default();
+	let address = tester.accounts_provider.new_account("").unwrap();
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::zero(),
+		gas: U256::from(21_000),
+		action: Action::Call(Address::from(1)),
+		value: U256::zero(),
+		data: vec![]
+	};
+	let signature = tester.accounts_provider.sign_with_password(address, "".into(), t.hash()).unwrap();
+	let t = t.with_signature(signature);
+
+	// nothing is being sealed
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["pending", false], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let mut block = tester.client.prepare_open_block(Address::zero(), (U256::from(12345), U256::from(54321)), vec![]);
+	block.push_transaction(t.clone(), None).unwrap();
+	*tester.miner.latest_closed_block.lock() = Some(block.close());
+
+	let response = tester.io.handle_request_sync(request).unwrap();
+
+	assert!(response.contains(r#""hash":null"#));
+	assert!(response.contains(r#""number":"0x1""#));
+	assert!(response.contains(r#""gasUsed":"0x5208""#));
+	assert!(response.contains(&format!(r#""transactions":["0x{:?}"]"#, t.hash())));
+}
+
+#[test]
+fn rpc_eth_keep_alive_is_throttled() {
+	let tester = EthTester::new_with_options(EthClientOptions {
+		keep_alive_interval: Duration::from_secs(3600),
+		..Default::default()
+	});
+	let filter = EthFilterClient::with_keep_alive_interval(&tester.client, &tester.miner, Duration::from_secs(3600));
+	tester.io.add_delegate(filter.to_delegate());
+
+	let block_number = r#"{"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 1}"#;
+	let new_filter = r#"{"jsonrpc": "2.0", "method": "eth_newBlockFilter", "params": [], "id": 1}"#;
+	for _ in 0..10 {
+		tester.io.handle_request_sync(block_number).unwrap();
+		tester.io.handle_request_sync(new_filter).unwrap();
+	}
+
+	// once for each of the rpc clients.
+	assert_eq!(tester.client.keep_alive_count.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn rpc_eth_reads_dont_keep_alive_in_passive_mode() {
+	let tester = EthTester::new_with_options(EthClientOptions {
+		keep_alive_interval: Duration::from_secs(0),
+		reads_keep_alive: false,
+		..Default::default()
+	});
+	let filter = EthFilterClient::with_keep_alive_interval(&tester.client, &tester.miner, Duration::from_secs(0))
+		.with_reads_keep_alive(false);
+	tester.io.add_delegate(filter.to_delegate());
+	let address = tester.accounts_provider.new_account("").unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "".into()).unwrap();
+
+	let block_number = r#"{"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 1}"#;
+	let new_filter = r#"{"jsonrpc": "2.0", "method": "eth_newBlockFilter", "params": [], "id": 1}"#;
+	tester.io.handle_request_sync(block_number).unwrap();
+	tester.io.handle_request_sync(new_filter).unwrap();
+	assert_eq!(tester.client.keep_alive_count.load(Ordering::Relaxed), 0);
+
+	let send_transaction = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"value": "0x9184e72a"
+		}],
+		"id": 1
+	}"#;
+	tester.io.handle_request_sync(&send_transaction).unwrap();
+	assert_eq!(tester.client.keep_alive_count.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn rpc_parity_list_filters() {
+	let tester = EthTester::default();
+	tester.io.add_delegate(EthFilterClient::new(&tester.client, &tester.miner).to_delegate());
+
+	let new_block_filter = r#"{"jsonrpc": "2.0", "method": "eth_newBlockFilter", "params": [], "id": 1}"#;
+	let new_filter = r#"{"jsonrpc": "2.0", "method": "eth_newFilter", "params": [{"fromBlock": "earliest"}], "id": 1}"#;
+	let new_pending_filter = r#"{"jsonrpc": "2.0", "method": "eth_newPendingTransactionFilter", "params": [], "id": 1}"#;
+	let uninstall = r#"{"jsonrpc": "2.0", "method": "eth_uninstallFilter", "params": ["0x2"], "id": 1}"#;
+	tester.io.handle_request_sync(new_block_filter).unwrap();
+	tester.io.handle_request_sync(new_filter).unwrap();
+	tester.io.handle_request_sync(new_pending_filter).unwrap();
+	tester.io.handle_request_sync(uninstall).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_listFilters", "params": [], "id": 1}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+
+	assert!(response.contains(r#"{"id":"0x0","kind":"block","#));
+	assert!(response.contains(r#"{"id":"0x1","kind":"logs","#));
+	assert!(response.contains(r#""filter":{"fromBlock":"earliest","toBlock":null,"fromTimestamp":null,"toTimestamp":null,"address":null,"topics":null}"#));
+	assert!(!response.contains(r#""kind":"pending""#));
+}
+
 #[test]
 fn rpc_eth_balance() {
 	let tester = EthTester::default();
@@ -291,6 +606,43 @@ fn rpc_eth_balance() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_balance_at_timestamp() {
+	let tester = EthTester::default();
+	// blocks 1 to 10 mined 15 seconds apart, starting at timestamp 1000
+	for n in 1..11 {
+		let mut header = Header::new();
+		header.set_number(n);
+		header.set_parent_hash(tester.client.chain_info().best_block_hash);
+		header.set_timestamp(1000 + (n - 1) * 15);
+		header.set_difficulty(U256::from(n));
+		header.set_gas_limit(U256::from(1_000_000));
+		tester.client.import_block(empty_block(&header)).unwrap();
+	}
+	tester.client.set_block_balance(Address::from(1), 4, U256::from(40));
+	tester.client.set_block_balance(Address::from(1), 5, U256::from(50));
+
+	let request = |timestamp: u64| format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "eth_getBalanceAtTimestamp",
+		"params": ["0x0000000000000000000000000000000000000001", "0x{:x}"],
+		"id": 1
+	}}"#, timestamp);
+
+	// between blocks 4 and 5
+	let response = r#"{"jsonrpc":"2.0","result":"0x28","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request(1050)), Some(response.to_owned()));
+
+	// exactly at block 5 and before block 6
+	let response = r#"{"jsonrpc":"2.0","result":"0x32","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request(1060)), Some(response.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(&request(1074)), Some(response.to_owned()));
+
+	// state of block 6 is not available
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive.","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request(1075)), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_balance_pending() {
 	let tester = EthTester::default();
@@ -311,6 +663,22 @@ fn rpc_eth_balance_pending() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_account_pruned() {
+	let tester = EthTester::default();
+	tester.client.set_balance(Address::from(1), U256::from(5));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getAccount",
+		"params": ["0x0000000000000000000000000000000000000001", "0x1"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive.","data":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_storage_at() {
 	let tester = EthTester::default();
@@ -340,6 +708,21 @@ fn rpc_eth_transaction_count() {
 	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_transaction_count_pending() {
+	let tester = EthTester::default();
+	tester.miner.last_nonces.write().insert(Address::from(1), U256::from(2));
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getTransactionCount",
+		"params": ["0x0000000000000000000000000000000000000001", "pending"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x3","id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_block_transaction_count_by_hash() {
 	let request = r#"{
@@ -401,6 +784,109 @@ fn rpc_eth_pending_transaction_by_hash() {
 }
 
 
+fn raw_transaction_result(response: Option<String>) -> Option<::ethcore::transaction::SignedTransaction> {
+	use serde_json::{self, Value};
+	use util::FromHex;
+
+	let response: Value = serde_json::from_str(&response.unwrap()).unwrap();
+	response.find("result").unwrap().as_str().map(|raw| ::rlp::decode(&FromHex::from_hex(&raw[2..]).unwrap()))
+}
+
+#[test]
+fn rpc_eth_raw_pending_transaction_by_hash() {
+	use util::*;
+	use ethcore::transaction::*;
+
+	let tester = EthTester::default();
+	let tx: SignedTransaction = ::rlp::decode(&FromHex::from_hex("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap());
+	let hash = tx.hash();
+	tester.miner.pending_transactions.lock().insert(hash.clone(), tx);
+
+	let request = format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "eth_getRawTransactionByHash",
+		"params": ["0x{}"],
+		"id": 1
+	}}"#, hash.hex());
+
+	let decoded = raw_transaction_result(tester.io.handle_request_sync(&request)).unwrap();
+	assert_eq!(decoded.hash(), hash);
+}
+
+#[test]
+fn rpc_eth_raw_transaction_by_hash_and_index() {
+	use ethcore::client::{BlockChainClient, BlockID};
+	use ethcore::views::BlockView;
+
+	let tester = EthTester::default();
+	tester.client.add_blocks(1, EachBlockWith::Transaction);
+	let block = tester.client.block(BlockID::Number(1)).unwrap();
+	let block_hash = BlockView::new(&block).hash();
+	let hash = BlockView::new(&block).transaction_hashes()[0];
+
+	let by_hash = format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "eth_getRawTransactionByHash",
+		"params": ["0x{}"],
+		"id": 1
+	}}"#, hash.hex());
+	let by_block_hash_and_index = format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "eth_getRawTransactionByBlockHashAndIndex",
+		"params": ["0x{}", "0x0"],
+		"id": 1
+	}}"#, block_hash.hex());
+
+	let decoded = raw_transaction_result(tester.io.handle_request_sync(&by_hash)).unwrap();
+	assert_eq!(decoded.hash(), hash);
+	let decoded = raw_transaction_result(tester.io.handle_request_sync(&by_block_hash_and_index)).unwrap();
+	assert_eq!(decoded.hash(), hash);
+}
+
+#[test]
+fn rpc_eth_raw_transaction_by_unknown_hash() {
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getRawTransactionByHash",
+		"params": ["0x0000000000000000000000000000000000000000000000000000000000000001"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_transaction_by_block_hash_and_index() {
+	use serde_json::{self, Value};
+	use ethcore::client::BlockID;
+	use ethcore::views::BlockView;
+
+	let tester = EthTester::default();
+	tester.client.add_blocks(1, EachBlockWith::Transaction);
+	let block = tester.client.block(BlockID::Number(1)).unwrap();
+	let block_hash = BlockView::new(&block).hash();
+	let hash = BlockView::new(&block).transaction_hashes()[0];
+	let request = |block: &H256, index: &str| format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "eth_getTransactionByBlockHashAndIndex",
+		"params": ["0x{}", "{}"],
+		"id": 1
+	}}"#, block.hex(), index);
+
+	// unknown block
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request(&H256::from(1), "0x0")), Some(response.to_owned()));
+
+	// valid index
+	let response: Value = serde_json::from_str(&tester.io.handle_request_sync(&request(&block_hash, "0x0")).unwrap()).unwrap();
+	assert_eq!(response.find("result").unwrap().find("hash").unwrap().as_str().unwrap(), &format!("0x{}", hash.hex()));
+
+	// index out of range
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Transaction index 1 is out of range, the block has 1 transactions.","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request(&block_hash, "0x1")), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_uncle_count_by_block_hash() {
 	let request = r#"{
@@ -547,6 +1033,30 @@ fn rpc_eth_call_default_block() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_timed_out() {
+	let tester = EthTester::new_with_options(EthClientOptions {
+		call_timeout: Some(Duration::from_millis(100)),
+		..Default::default()
+	});
+	tester.client.set_execution_result(Err(CallError::Execution(ExecutionError::Timeout)));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0"
+		},
+		"latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32015,"message":"Transaction execution error: timed out.","data":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_estimate_gas() {
 	let tester = EthTester::default();
@@ -616,6 +1126,99 @@ fn rpc_eth_estimate_gas_default_block() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+fn call_request(method: &str, from: &str, gas: &str, data: &str) -> String {
+	format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "{}",
+		"params": [{{
+			"from": "{}",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "{}",
+			"data": "{}"
+		}}],
+		"id": 1
+	}}"#, method, from, gas, data)
+}
+
+fn limited_call_tester(options: EthClientOptions) -> EthTester {
+	let tester = EthTester::new_with_options(options);
+	tester.client.set_execution_result(Ok(Executed {
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+	tester
+}
+
+#[test]
+fn rpc_eth_call_data_limit() {
+	let tester = limited_call_tester(EthClientOptions {
+		max_call_data: 4,
+		..Default::default()
+	});
+	let from = "0xb60e8dd61c5d32be8058bb8eb970870f07233155";
+
+	let at_limit = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_call", from, "0x76c0", "0x12345678")), Some(at_limit.to_owned()));
+
+	let over_limit = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Parameter data exceeds the limit of 4 bytes","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_call", from, "0x76c0", "0x1234567890")), Some(over_limit.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_estimateGas", from, "0x76c0", "0x1234567890")), Some(over_limit.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_gas_limit_defaults_to_block_gas_limit() {
+	let tester = limited_call_tester(Default::default());
+	let from = "0xb60e8dd61c5d32be8058bb8eb970870f07233155";
+
+	// the test client's blocks have a gas limit of 1_000_000
+	let at_limit = r#"{"jsonrpc":"2.0","result":"0xff35","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_estimateGas", from, "0xf4240", "0x12")), Some(at_limit.to_owned()));
+
+	let over_limit = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Parameter gas exceeds the limit of 1000000","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_estimateGas", from, "0xf4241", "0x12")), Some(over_limit.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_call", from, "0xf4241", "0x12")), Some(over_limit.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_configured_gas_limit() {
+	let tester = limited_call_tester(EthClientOptions {
+		max_call_gas: Some(U256::from(0x76c0)),
+		..Default::default()
+	});
+	let from = "0xb60e8dd61c5d32be8058bb8eb970870f07233155";
+
+	let at_limit = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_call", from, "0x76c0", "0x12")), Some(at_limit.to_owned()));
+
+	let over_limit = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Parameter gas exceeds the limit of 30400","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_call", from, "0x76c1", "0x12")), Some(over_limit.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_local_senders_only() {
+	let tester = limited_call_tester(EthClientOptions {
+		local_call_senders_only: true,
+		..Default::default()
+	});
+	let local = format!("0x{:?}", tester.accounts_provider.new_account("").unwrap());
+
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_call", &local, "0x76c0", "0x12")), Some(response.to_owned()));
+
+	let refused = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Calls are only allowed from local accounts","data":null},"id":1}"#;
+	let remote = "0xb60e8dd61c5d32be8058bb8eb970870f07233155";
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_call", remote, "0x76c0", "0x12")), Some(refused.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(&call_request("eth_estimateGas", remote, "0x76c0", "0x12")), Some(refused.to_owned()));
+}
+
 #[test]
 fn rpc_eth_send_transaction() {
 	let tester = EthTester::default();
@@ -743,6 +1346,48 @@ fn rpc_eth_send_raw_transaction() {
 	assert_eq!(tester.io.handle_request_sync(&req), Some(res));
 }
 
+#[test]
+fn rpc_eth_send_raw_transaction_already_known() {
+	let tester = EthTester::default();
+	let address = tester.accounts_provider.new_account("abcd").unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "abcd".into()).unwrap();
+
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0x9184e72a000u64),
+		gas: U256::from(0x76c0),
+		action: Action::Call(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		value: U256::from(0x9184e72au64),
+		data: vec![]
+	};
+	let signature = tester.accounts_provider.sign(address, t.hash()).unwrap();
+	let t = t.with_signature(signature);
+
+	let req = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendRawTransaction",
+		"params": ["0x"#.to_owned() + &::rlp::encode(&t).to_vec().to_hex() + r#""],
+		"id": 1
+	}"#;
+	let res = r#"{"jsonrpc":"2.0","result":""#.to_owned() + &format!("0x{:?}", t.hash()) + r#"","id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(&req), Some(res.clone()));
+	// submitting the same transaction again returns its hash
+	assert_eq!(tester.io.handle_request_sync(&req), Some(res));
+	assert_eq!(tester.miner.imported_transactions.lock().len(), 1);
+}
+
+#[test]
+fn rpc_eth_send_raw_transaction_of_unknown_type() {
+	let tester = EthTester::default();
+
+	// type byte followed by an (irrelevant) payload
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_sendRawTransaction", "params": ["0x02c0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Transaction type 0x02 is not supported.","data":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_transaction_receipt() {
 	let receipt = LocalizedReceipt {
@@ -800,6 +1445,58 @@ fn rpc_eth_transaction_receipt_null() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_pending_transaction_receipt() {
+	use serde_json::{self, Value};
+	use ethcore::receipt::Receipt;
+
+	let log = LogEntry {
+		address: Address::from_str("33990122638b9132ca29c723bdf037f1a891a70c").unwrap(),
+		topics: vec![H256::from_str("a6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc").unwrap()],
+		data: vec![],
+	};
+	let pending = H256::from_str("b903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238").unwrap();
+	let mined = H256::from_str("b903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568239").unwrap();
+
+	let tester = EthTester::default();
+	tester.miner.pending_receipts.lock().insert(pending.clone(), Receipt::new(H256::zero(), U256::from(0x10), vec![log.clone()]));
+	tester.client.set_transaction_receipt(TransactionID::Hash(mined.clone()), LocalizedReceipt {
+		transaction_hash: mined.clone(),
+		transaction_index: 0,
+		block_hash: H256::from_str("ed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5").unwrap(),
+		block_number: 0x4510c,
+		cumulative_gas_used: U256::from(0x10),
+		gas_used: U256::from(0x10),
+		contract_address: None,
+		logs: vec![LocalizedLogEntry {
+			entry: log,
+			block_hash: H256::from_str("ed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5").unwrap(),
+			block_number: 0x4510c,
+			transaction_hash: mined.clone(),
+			transaction_index: 0,
+			log_index: 0,
+		}],
+	});
+
+	let receipt = |hash: &H256| {
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": ["0x{}"], "id": 1}}"#, hash.hex());
+		let response: Value = serde_json::from_str(&tester.io.handle_request_sync(&request).unwrap()).unwrap();
+		response.find("result").unwrap().clone()
+	};
+	let pending = receipt(&pending);
+	let mined = receipt(&mined);
+
+	for field in &["blockHash", "blockNumber"] {
+		assert_eq!(pending.find(*field), Some(&Value::Null));
+		assert!(mined.find(*field).unwrap().is_string());
+		assert_eq!(pending.find("logs").unwrap().as_array().unwrap()[0].find(*field), Some(&Value::Null));
+		assert!(mined.find("logs").unwrap().as_array().unwrap()[0].find(*field).unwrap().is_string());
+	}
+	assert_eq!(pending.find("logs").unwrap().as_array().unwrap()[0].find("type").unwrap().as_str(), Some("pending"));
+	assert_eq!(mined.find("logs").unwrap().as_array().unwrap()[0].find("type").unwrap().as_str(), Some("mined"));
+	assert_eq!(pending.find("transactionHash"), pending.find("logs").unwrap().as_array().unwrap()[0].find("transactionHash"));
+}
+
 // These tests are incorrect: their output is undefined as long as eth_getCompilers is [].
 // Will ignore for now, but should probably be replaced by more substantial tests which check
 // the output of eth_getCompilers to determine whether to test. CI systems can then be preinstalled
@@ -867,6 +1564,9 @@ fn rpc_get_work_should_not_return_block_number() {
 	let eth_tester = EthTester::new_with_options(EthClientOptions {
 		allow_pending_receipt_query: true,
 		send_block_number_in_get_work: false,
+		keep_alive_interval: Duration::from_secs(30),
+		call_timeout: None,
+		..Default::default()
 	});
 	eth_tester.miner.set_author(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap());
 
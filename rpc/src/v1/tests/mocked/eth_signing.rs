@@ -19,7 +19,7 @@ use std::sync::Arc;
 use jsonrpc_core::{IoHandler, to_value};
 use v1::impls::EthSigningQueueClient;
 use v1::traits::EthSigning;
-use v1::helpers::{ConfirmationsQueue, SigningQueue};
+use v1::helpers::{ConfirmationsQueue, ConfirmationPayload, SigningQueue};
 use v1::types::{H256 as RpcH256, H520 as RpcH520};
 use v1::tests::helpers::TestMinerService;
 use util::{Address, FixedHash, Uint, U256, H256, H520};
@@ -263,3 +263,133 @@ fn should_dispatch_transaction_if_account_is_unlock() {
 	// then
 	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
 }
+
+fn post_transaction(io: &IoHandler, address: &Address, gas_price: &str, ttl: Option<&str>) -> Option<String> {
+	let ttl = ttl.map_or_else(String::new, |ttl| format!(r#", "{}""#, ttl));
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_postTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": ""# + gas_price + r#"",
+			"value": "0x9184e72a"
+		}"# + &ttl + r#"],
+		"id": 1
+	}"#;
+	io.handle_request_sync(&request)
+}
+
+fn replace_request(io: &IoHandler, id: &str, address: &Address, gas_price: &str) -> Option<String> {
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_replaceRequest",
+		"params": [""#.to_owned() + id + r#"", {
+			"from": ""# + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": ""# + gas_price + r#"",
+			"value": "0x9184e72a"
+		}],
+		"id": 1
+	}"#;
+	io.handle_request_sync(&request)
+}
+
+fn queued_gas_price(queue: &ConfirmationsQueue, id: u64) -> U256 {
+	match queue.peek(&U256::from(id)).expect("Request is queued.").payload {
+		ConfirmationPayload::Transaction(ref request) => request.gas_price,
+		_ => panic!("Expected transaction request."),
+	}
+}
+
+#[test]
+fn should_expire_posted_transaction_after_ttl() {
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(post_transaction(&tester.io, &address, "0x9184e72a000", Some("0x0")), Some(response.to_owned()));
+
+	// when
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_checkRequest",
+		"params": ["0x1"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32043,"message":"Request has expired before being confirmed.","data":null},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.queue.requests().len(), 0);
+}
+
+#[test]
+fn should_reject_posted_transaction_with_excessive_ttl() {
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+	let ttl = format!("0x{:x}", U256::max_value());
+
+	// when
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Parameter ttl exceeds the limit of 86400","data":null},"id":1}"#;
+	assert_eq!(post_transaction(&tester.io, &address, "0x9184e72a000", Some(&ttl)), Some(response.to_owned()));
+
+	// then
+	assert_eq!(tester.queue.requests().len(), 0);
+}
+
+#[test]
+fn should_replace_posted_transaction() {
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+	post_transaction(&tester.io, &address, "0x9184e72a000", Some("0xe10")).expect("Sent");
+	assert_eq!(queued_gas_price(&tester.queue, 1), U256::from(0x9184e72a000u64));
+
+	// when
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(replace_request(&tester.io, "0x1", &address, "0x12309ce54000"), Some(response.to_owned()));
+
+	// then
+	assert_eq!(tester.queue.requests().len(), 1);
+	assert_eq!(queued_gas_price(&tester.queue, 1), U256::from(0x12309ce54000u64));
+}
+
+#[test]
+fn should_not_replace_transaction_posted_by_other_account() {
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+	post_transaction(&tester.io, &address, "0x9184e72a000", None).expect("Sent");
+
+	// when
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32044,"message":"Request has been created by a different account.","data":null},"id":1}"#;
+	assert_eq!(replace_request(&tester.io, "0x1", &Address::random(), "0x12309ce54000"), Some(response.to_owned()));
+
+	// then
+	assert_eq!(queued_gas_price(&tester.queue, 1), U256::from(0x9184e72a000u64));
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32042,"message":"Request not found.","data":null},"id":1}"#;
+	assert_eq!(replace_request(&tester.io, "0x2", &address, "0x12309ce54000"), Some(response.to_owned()));
+}
+
+#[test]
+fn should_expire_posted_transaction_without_polling() {
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+
+	// when
+	post_transaction(&tester.io, &address, "0x9184e72a000", Some("0x0")).expect("Sent");
+
+	// then
+	for _ in 0..100 {
+		if tester.queue.is_empty() {
+			break;
+		}
+		::std::thread::sleep(::std::time::Duration::from_millis(10));
+	}
+	assert!(tester.queue.is_empty());
+}
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::time::Duration;
 use util::log::RotatingLogger;
 use util::U256;
 use ethsync::ManageNetwork;
@@ -22,7 +23,7 @@ use ethcore::client::{TestBlockChainClient};
 
 use jsonrpc_core::IoHandler;
 use v1::{Ethcore, EthcoreClient};
-use v1::helpers::{ConfirmationsQueue, NetworkSettings};
+use v1::helpers::{ConfirmationsQueue, NetworkSettings, HealthChecks, TimeSource};
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService};
 use super::manage_network::TestManageNetwork;
 
@@ -286,3 +287,454 @@ fn rpc_ethcore_unsigned_transactions_count_when_signer_disabled() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_bad_blocks() {
+	use v1::tests::helpers::empty_block;
+	use ethcore::client::BlockChainClient;
+	use ethcore::header::Header;
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let mut header = Header::new();
+	header.set_number(1);
+	header.set_parent_hash(client.chain_info().best_block_hash);
+	header.set_extra_data(vec![0u8; 64]);
+	let block = empty_block(&header);
+	let size = block.len();
+
+	assert!(client.import_block(block).is_err());
+	client.note_bad_block_peer(&header.hash(), "peer1".into());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_badBlocks", "params":[], "id": 1}"#;
+	let response = format!(
+		r#"{{"jsonrpc":"2.0","result":[{{"hash":"0x{}","number":"0x1","error":"ExtraDataOutOfBounds(OutOfBounds {{ min: None, max: Some(32), found: 64 }})","size":"0x{:x}","peer":"peer1"}}],"id":1}}"#,
+		header.hash().hex(),
+		size,
+	);
+
+	assert_eq!(io.handle_request_sync(request), Some(response));
+}
+
+#[test]
+fn rpc_parity_stable_block_number() {
+	use ethcore::client::EachBlockWith;
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).with_stable_block_confirmations(10).to_delegate());
+
+	let default_request = r#"{"jsonrpc": "2.0", "method": "parity_stableBlockNumber", "params":[], "id": 1}"#;
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stableBlockNumber", "params":["0x5"], "id": 1}"#;
+
+	// never goes below genesis
+	assert_eq!(io.handle_request_sync(default_request), Some(r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#.to_owned()));
+	assert_eq!(io.handle_request_sync(request), Some(r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#.to_owned()));
+
+	client.add_blocks(20, EachBlockWith::Nothing);
+	assert_eq!(io.handle_request_sync(default_request), Some(r#"{"jsonrpc":"2.0","result":"0xa","id":1}"#.to_owned()));
+	assert_eq!(io.handle_request_sync(request), Some(r#"{"jsonrpc":"2.0","result":"0xf","id":1}"#.to_owned()));
+
+	client.add_blocks(10, EachBlockWith::Nothing);
+	assert_eq!(io.handle_request_sync(default_request), Some(r#"{"jsonrpc":"2.0","result":"0x14","id":1}"#.to_owned()));
+	assert_eq!(io.handle_request_sync(request), Some(r#"{"jsonrpc":"2.0","result":"0x19","id":1}"#.to_owned()));
+}
+
+#[test]
+fn rpc_parity_get_block_rlp() {
+	use serde_json::{self, Value};
+	use util::{FromHex, Hashable};
+	use ethcore::client::{BlockChainClient, EachBlockWith, BlockID};
+	use ethcore::views::BlockView;
+
+	let miner = miner_service();
+	let client = client_service();
+	client.add_blocks(2, EachBlockWith::Transaction);
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let expected = client.block(BlockID::Number(2)).unwrap();
+	let hash = BlockView::new(&expected).sha3();
+	let block_rlp = |param: String| {
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_getBlockRlp", "params":[{}], "id": 1}}"#, param);
+		let response: Value = serde_json::from_str(&io.handle_request_sync(&request).unwrap()).unwrap();
+		response.find("result").unwrap().as_str().map(|raw| raw[2..].from_hex().unwrap())
+	};
+
+	for param in vec![r#""0x2""#.to_owned(), r#""latest""#.to_owned(), format!(r#""0x{}""#, hash.hex())] {
+		let rlp = block_rlp(param).unwrap();
+		assert_eq!(rlp, expected);
+		assert_eq!(BlockView::new(&rlp).sha3(), hash);
+	}
+	assert_eq!(block_rlp(r#""0x10""#.to_owned()), None);
+	assert_eq!(block_rlp(format!(r#""0x{}""#, "0101010101010101010101010101010101010101010101010101010101010101")), None);
+}
+
+#[test]
+fn rpc_parity_get_raw_transaction() {
+	use serde_json::{self, Value};
+	use util::FromHex;
+	use ethcore::client::{BlockChainClient, EachBlockWith, BlockID};
+	use ethcore::transaction::SignedTransaction;
+	use ethcore::views::BlockView;
+
+	let miner = miner_service();
+	let client = client_service();
+	client.add_blocks(2, EachBlockWith::Transaction);
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let block = client.block(BlockID::Number(2)).unwrap();
+	let expected = BlockView::new(&block).transactions()[0].clone();
+	let raw_transaction = |hash: String| {
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_getRawTransaction", "params":["0x{}"], "id": 1}}"#, hash);
+		let response: Value = serde_json::from_str(&io.handle_request_sync(&request).unwrap()).unwrap();
+		response.find("result").unwrap().as_str().map(|raw| raw[2..].from_hex().unwrap())
+	};
+
+	let raw = raw_transaction(expected.hash().hex()).unwrap();
+	let decoded: SignedTransaction = ::rlp::decode(&raw);
+	assert_eq!(decoded, expected);
+	assert_eq!(raw_transaction("0101010101010101010101010101010101010101010101010101010101010101".to_owned()), None);
+}
+
+#[test]
+fn rpc_parity_block_utilization() {
+	use serde_json::{self, Value};
+	use ethcore::client::{BlockChainClient, EachBlockWith, BlockID};
+	use ethcore::views::BlockView;
+
+	let miner = miner_service();
+	let client = client_service();
+	client.add_blocks(5, EachBlockWith::Transaction);
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let utilization = |count: &str| {
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_blockUtilization", "params":["{}"], "id": 1}}"#, count);
+		let response: Value = serde_json::from_str(&io.handle_request_sync(&request).unwrap()).unwrap();
+		response.find("result").unwrap().clone()
+	};
+	let hex = |v: U256| serde_json::to_value(&::v1::types::U256::from(v));
+
+	let result = utilization("0x3");
+	assert_eq!(result.find("oldestBlock").unwrap(), &hex(U256::from(3)));
+	for (i, number) in (3..6).enumerate() {
+		let block = client.block(BlockID::Number(number)).unwrap();
+		let view = BlockView::new(&block);
+		let header = view.header_view();
+		assert_eq!(result.find("gasUsed").unwrap()[i], hex(header.gas_used()));
+		assert_eq!(result.find("gasLimit").unwrap()[i], hex(header.gas_limit()));
+		assert_eq!(result.find("transactionCount").unwrap()[i], hex(U256::from(view.transactions_count())));
+		assert_eq!(result.find("blockInterval").unwrap()[i], hex(U256::zero()));
+	}
+	assert_eq!(result.find("gasUsed").unwrap().as_array().unwrap().len(), 3);
+
+	// asking for more blocks than there are returns the whole chain, starting with genesis
+	let result = utilization("0x10000");
+	assert_eq!(result.find("oldestBlock").unwrap(), &hex(U256::zero()));
+	assert_eq!(result.find("transactionCount").unwrap().as_array().unwrap().len(), 6);
+	assert_eq!(result.find("transactionCount").unwrap()[0], hex(U256::zero()));
+
+	// cached result is refreshed once the best block changes
+	client.add_blocks(1, EachBlockWith::Nothing);
+	let result = utilization("0x3");
+	assert_eq!(result.find("oldestBlock").unwrap(), &hex(U256::from(4)));
+	assert_eq!(result.find("transactionCount").unwrap()[2], hex(U256::zero()));
+}
+
+#[test]
+fn rpc_parity_block_utilization_intervals() {
+	use serde_json::{self, Value};
+	use v1::tests::helpers::empty_block;
+	use ethcore::client::BlockChainClient;
+	use ethcore::header::Header;
+
+	let miner = miner_service();
+	let client = client_service();
+	// blocks 1 to 5 mined at uneven intervals
+	for (n, timestamp) in [1000u64, 1012, 1027, 1045, 1050].iter().enumerate() {
+		let mut header = Header::new();
+		header.set_number(n as u64 + 1);
+		header.set_parent_hash(client.chain_info().best_block_hash);
+		header.set_timestamp(*timestamp);
+		header.set_difficulty(U256::from(n + 1));
+		header.set_gas_limit(U256::from(1_000_000));
+		client.import_block(empty_block(&header)).unwrap();
+	}
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_blockUtilization", "params":["0x4"], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let result = response.find("result").unwrap();
+
+	// the oldest interval is measured against block 1, which is outside the requested range
+	let intervals: Vec<_> = [12u64, 15, 18, 5].iter().map(|i| serde_json::to_value(&::v1::types::U256::from(*i))).collect();
+	assert_eq!(result.find("oldestBlock").unwrap(), &serde_json::to_value(&::v1::types::U256::from(2)));
+	assert_eq!(result.find("blockInterval").unwrap().as_array().unwrap(), &intervals);
+}
+
+#[test]
+fn rpc_parity_version_info() {
+	use util::misc::{version, short_sha};
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let commit = match short_sha() {
+		"" => "null".to_owned(),
+		sha => format!("\"{}\"", sha),
+	};
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_versionInfo", "params":[], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":{{"version":"{}","commit":{},"protocolVersions":[62,63,64],"chain":"Morden"}},"id":1}}"#, version(), commit);
+
+	assert_eq!(io.handle_request_sync(request), Some(response));
+}
+
+#[test]
+fn rpc_parity_subscribe_new_heads() {
+	use serde_json::{self, Value};
+	use ethcore::client::{BlockChainClient, ChainNotify, EachBlockWith, BlockID};
+	use v1::helpers::NewHeadsSubscriptions;
+	use v1::types::H256;
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let new_heads = Arc::new(NewHeadsSubscriptions::with_limit(client.clone(), 1));
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).with_new_heads(new_heads.clone()).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribeNewHeads", "params": [], "id": 1}"#;
+	let async_result = io.handle_request(request).unwrap();
+	assert_eq!(new_heads.pending(), 1);
+
+	// subscriptions over the limit are rejected right away
+	let rejected = r#"{"jsonrpc":"2.0","error":{"code":-32041,"message":"Request has been rejected because of queue limit.","data":null},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(rejected.to_owned()));
+
+	client.add_blocks(1, EachBlockWith::Nothing);
+	let hash = client.block_hash(BlockID::Latest).unwrap();
+	new_heads.new_blocks(vec![hash.clone()], vec![], vec![hash.clone()], vec![], vec![], 0);
+	assert_eq!(new_heads.pending(), 0);
+
+	assert!(async_result.on_result(move |res| {
+		let response: Value = serde_json::from_str(&res).unwrap();
+		let header = response.find("result").unwrap();
+		assert_eq!(header.find("hash").unwrap(), &serde_json::to_value(&H256::from(hash)));
+		assert_eq!(header.find("number").unwrap(), &serde_json::to_value(&::v1::types::U256::from(1u64)));
+	}));
+}
+
+#[test]
+fn rpc_parity_snapshot_status() {
+	use ethcore::snapshot::{SnapshotService, CreationStatus, RestorationStatus};
+	use v1::tests::helpers::TestSnapshotService;
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let snapshot = Arc::new(TestSnapshotService::new());
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).with_snapshot_service(&(snapshot.clone() as Arc<SnapshotService>)).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_snapshotStatus", "params": [], "id": 1}"#;
+
+	let response = r#"{"jsonrpc":"2.0","result":"idle","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	*snapshot.restoration.lock() = Some(RestorationStatus::Ongoing { state_chunks_done: 3, block_chunks_done: 1 });
+	let response = r#"{"jsonrpc":"2.0","result":{"restoring":{"stateChunksDone":3,"blockChunksDone":1}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	*snapshot.restoration.lock() = None;
+	*snapshot.creation.lock() = Some(CreationStatus { accounts: 100, blocks: 20, bytes: 4096, done: false });
+	let response = r#"{"jsonrpc":"2.0","result":{"creating":{"accounts":100,"blocks":20,"bytes":4096,"done":false}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_snapshot_status_without_service() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_snapshotStatus", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not implemented yet. Please create an issue on Github repo.","data":null},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+struct FixedTimeSource(Result<i64, String>);
+
+impl TimeSource for FixedTimeSource {
+	fn drift_ms(&self) -> Result<i64, String> {
+		self.0.clone()
+	}
+}
+
+fn node_health_io(client: &Arc<TestBlockChainClient>, sync: &Arc<TestSyncProvider>, drift: Result<i64, String>, stall_timeout: Duration) -> IoHandler {
+	let health = HealthChecks::new(10, Some(Arc::new(FixedTimeSource(drift)))).with_stall_timeout(stall_timeout);
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(client, &miner_service(), sync, &network_service()).with_health_checks(health).to_delegate());
+	io
+}
+
+#[test]
+fn rpc_parity_node_health() {
+	let client = client_service();
+	let sync = sync_provider();
+	let io = node_health_io(&client, &sync, Ok(20), Duration::from_secs(60));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeHealth", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"peers":{"ok":true,"details":"Connected to 120 peers, at least 10 required"},"sync":{"ok":true,"details":"Synced at block #0"},"time":{"ok":true,"driftMs":20,"details":"Local clock is 20ms ahead, at most 10000ms allowed"}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_node_health_too_few_peers() {
+	let client = client_service();
+	let sync = sync_provider();
+	sync.status.write().num_peers = 3;
+	let io = node_health_io(&client, &sync, Ok(20), Duration::from_secs(60));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeHealth", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"peers":{"ok":false,"details":"Connected to 3 peers, at least 10 required"},"sync":{"ok":true,"details":"Synced at block #0"},"time":{"ok":true,"driftMs":20,"details":"Local clock is 20ms ahead, at most 10000ms allowed"}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_node_health_sync_stalled() {
+	use ethsync::SyncState;
+
+	let client = client_service();
+	let sync = sync_provider();
+	sync.status.write().state = SyncState::Blocks;
+	sync.status.write().highest_block_number = Some(100);
+	let io = node_health_io(&client, &sync, Ok(20), Duration::from_secs(0));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeHealth", "params": [], "id": 1}"#;
+	// no block has been imported since the first probe.
+	let response = r#"{"jsonrpc":"2.0","result":{"peers":{"ok":true,"details":"Connected to 120 peers, at least 10 required"},"sync":{"ok":true,"details":"Syncing at block #0, highest block #100"},"time":{"ok":true,"driftMs":20,"details":"Local clock is 20ms ahead, at most 10000ms allowed"}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	let response = r#"{"jsonrpc":"2.0","result":{"peers":{"ok":true,"details":"Connected to 120 peers, at least 10 required"},"sync":{"ok":false,"details":"Syncing stalled at block #0 for 0s, highest block #100"},"time":{"ok":true,"driftMs":20,"details":"Local clock is 20ms ahead, at most 10000ms allowed"}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_node_health_clock_drift() {
+	let client = client_service();
+	let sync = sync_provider();
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeHealth", "params": [], "id": 1}"#;
+
+	let io = node_health_io(&client, &sync, Ok(-15000), Duration::from_secs(60));
+	let response = r#"{"jsonrpc":"2.0","result":{"peers":{"ok":true,"details":"Connected to 120 peers, at least 10 required"},"sync":{"ok":true,"details":"Synced at block #0"},"time":{"ok":false,"driftMs":-15000,"details":"Local clock is 15000ms behind, at most 10000ms allowed"}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let io = node_health_io(&client, &sync, Err("Could not query time server".into()), Duration::from_secs(60));
+	let response = r#"{"jsonrpc":"2.0","result":{"peers":{"ok":true,"details":"Connected to 120 peers, at least 10 required"},"sync":{"ok":true,"details":"Synced at block #0"},"time":{"ok":false,"driftMs":null,"details":"Could not query time server"}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_node_health_clock_check_disabled() {
+	let client = client_service();
+	let sync = sync_provider();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner_service(), &sync, &network_service()).with_health_checks(HealthChecks::new(10, None)).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeHealth", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"peers":{"ok":true,"details":"Connected to 120 peers, at least 10 required"},"sync":{"ok":true,"details":"Synced at block #0"},"time":{"ok":true,"driftMs":null,"details":"Clock drift check disabled"}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_metrics() {
+	use ethcore::client::EachBlockWith;
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+	client.add_blocks(10, EachBlockWith::Nothing);
+	client.set_queue_size(3);
+	sync.status.write().highest_block_number = Some(20);
+	sync.status.write().num_active_peers = 5;
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_metrics", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"activePeers":5,"bestBlock":10,"bytesReceived":4096,"bytesSent":1024,"highestBlock":20,"inboundPeers":7,"outboundPeers":13,"peers":120,"pendingTransactions":0,"queuedTransactions":0,"unverifiedBlocks":0,"verifiedBlocks":3,"verifyingBlocks":0},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_difficulty() {
+	use v1::tests::helpers::empty_block;
+	use ethcore::client::BlockChainClient;
+	use ethcore::ethereum;
+	use ethcore::header::Header;
+
+	let miner = miner_service();
+	let mut client = TestBlockChainClient::new();
+	client.spec = ethereum::new_homestead_test();
+	let client = Arc::new(client);
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let mut header = Header::new();
+	header.set_number(1);
+	header.set_parent_hash(client.chain_info().best_block_hash);
+	header.set_difficulty(U256::from(2_048_000));
+	client.import_block(empty_block(&header)).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_currentDifficulty", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1f4000","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	// the best block is from 1970, so the difficulty drops by the maximal 99 / 2048.
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nextDifficulty", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1dbd48","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_next_difficulty_without_pow() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nextDifficulty", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -116,3 +116,28 @@ fn rpc_ethcore_set_transactions_limit() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 	assert_eq!(miner.transactions_limit(), 10_240_240);
 }
+
+#[test]
+fn rpc_parity_take_snapshot() {
+	use ethcore::client::EachBlockWith;
+	use ethcore::snapshot::{SnapshotService, CreationStatus};
+	use v1::tests::helpers::TestSnapshotService;
+
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let snapshot = Arc::new(TestSnapshotService::new());
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&client, &miner, &network).with_snapshot_service(&(snapshot.clone() as Arc<SnapshotService>)).to_delegate());
+	client.add_blocks(10, EachBlockWith::Nothing);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_takeSnapshot", "params": ["backup.snapshot"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0xa","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(*snapshot.requested.lock(), Some((10, "backup.snapshot".to_owned())));
+
+	// only one snapshot may be taken at a time.
+	*snapshot.creation.lock() = Some(CreationStatus { accounts: 100, blocks: 20, bytes: 4096, done: false });
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32060,"message":"Snapshot error: Another snapshot is currently being taken","data":null},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethsync::{ManageNetwork, NetworkConfiguration};
+use ethsync::{ManageNetwork, NetworkConfiguration, ReservedPeerStatus, NetworkMetrics};
+use util::H256;
 
 pub struct TestManageNetwork;
 
@@ -27,4 +28,9 @@ impl ManageNetwork for TestManageNetwork {
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn network_config(&self) -> NetworkConfiguration { NetworkConfiguration::new_local() }
+	fn reserved_peers_status(&self) -> Vec<ReservedPeerStatus> { Vec::new() }
+	fn ban_block(&self, _hash: H256) {}
+	fn network_metrics(&self) -> NetworkMetrics {
+		NetworkMetrics { inbound_peers: 7, outbound_peers: 13, bytes_sent: 1024, bytes_received: 4096 }
+	}
 }
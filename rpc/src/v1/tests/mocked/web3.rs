@@ -32,6 +32,20 @@ fn rpc_web3_version() {
 	assert_eq!(io.handle_request_sync(request), Some(response));
 }
 
+#[test]
+fn rpc_web3_version_with_identity() {
+	let web3 = Web3Client::new().with_identity("mynode".into()).to_delegate();
+	let io = IoHandler::new();
+	io.add_delegate(web3);
+
+	let v = version().to_owned().replace("Parity/", "Parity/mynode/");
+
+	let request = r#"{"jsonrpc": "2.0", "method": "web3_clientVersion", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"VER","id":1}"#.to_owned().replace("VER", v.as_ref());
+
+	assert_eq!(io.handle_request_sync(request), Some(response));
+}
+
 #[test]
 fn rpc_web3_sha3() {
 	let web3 = Web3Client::new().to_delegate();
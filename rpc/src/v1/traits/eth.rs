@@ -47,9 +47,16 @@ pub trait Eth: Sized + Send + Sync + 'static {
 	/// Returns balance of the given account.
 	fn balance(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns balance of the given account at the latest block mined at or before given timestamp.
+	fn balance_at_timestamp(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns content of the storage at given address.
 	fn storage_at(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns balance, nonce, code hash, storage root and code size of the given account,
+	/// all read from the same state.
+	fn account(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns block with given hash.
 	fn block_by_hash(&self, _: Params) -> Result<Value, Error>;
 
@@ -87,11 +94,20 @@ pub trait Eth: Sized + Send + Sync + 'static {
 	fn transaction_by_hash(&self, _: Params) -> Result<Value, Error>;
 
 	/// Returns transaction at given block hash and index.
+	/// Returns `null` for an unknown block and an error if the index is out of range.
 	fn transaction_by_block_hash_and_index(&self, _: Params) -> Result<Value, Error>;
 
 	/// Returns transaction by given block number and index.
+	/// Returns `null` for an unknown block and an error if the index is out of range.
 	fn transaction_by_block_number_and_index(&self, _: Params) -> Result<Value, Error>;
 
+	/// Get signed transaction RLP by transaction hash.
+	fn raw_transaction_by_hash(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns signed transaction RLP at given block hash and index.
+	/// Returns `null` for an unknown block and an error if the index is out of range.
+	fn raw_transaction_by_block_hash_and_index(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns transaction receipt.
 	fn transaction_receipt(&self, _: Params) -> Result<Value, Error>;
 
@@ -116,6 +132,9 @@ pub trait Eth: Sized + Send + Sync + 'static {
 	/// Returns logs matching given filter object.
 	fn logs(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns logs matching given filter object together with the number of blocks scanned and matched.
+	fn logs_detailed(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns the hash of the current block, the seedHash, and the boundary condition to be met.
 	fn work(&self, _: Params) -> Result<Value, Error>;
 
@@ -137,7 +156,9 @@ pub trait Eth: Sized + Send + Sync + 'static {
 		delegate.add_method("eth_accounts", Eth::accounts);
 		delegate.add_method("eth_blockNumber", Eth::block_number);
 		delegate.add_method("eth_getBalance", Eth::balance);
+		delegate.add_method("eth_getBalanceAtTimestamp", Eth::balance_at_timestamp);
 		delegate.add_method("eth_getStorageAt", Eth::storage_at);
+		delegate.add_method("eth_getAccount", Eth::account);
 		delegate.add_method("eth_getTransactionCount", Eth::transaction_count);
 		delegate.add_method("eth_getBlockTransactionCountByHash", Eth::block_transaction_count_by_hash);
 		delegate.add_method("eth_getBlockTransactionCountByNumber", Eth::block_transaction_count_by_number);
@@ -152,6 +173,8 @@ pub trait Eth: Sized + Send + Sync + 'static {
 		delegate.add_method("eth_getTransactionByHash", Eth::transaction_by_hash);
 		delegate.add_method("eth_getTransactionByBlockHashAndIndex", Eth::transaction_by_block_hash_and_index);
 		delegate.add_method("eth_getTransactionByBlockNumberAndIndex", Eth::transaction_by_block_number_and_index);
+		delegate.add_method("eth_getRawTransactionByHash", Eth::raw_transaction_by_hash);
+		delegate.add_method("eth_getRawTransactionByBlockHashAndIndex", Eth::raw_transaction_by_block_hash_and_index);
 		delegate.add_method("eth_getTransactionReceipt", Eth::transaction_receipt);
 		delegate.add_method("eth_getUncleByBlockHashAndIndex", Eth::uncle_by_block_hash_and_index);
 		delegate.add_method("eth_getUncleByBlockNumberAndIndex", Eth::uncle_by_block_number_and_index);
@@ -160,6 +183,7 @@ pub trait Eth: Sized + Send + Sync + 'static {
 		delegate.add_method("eth_compileSolidity", Eth::compile_solidity);
 		delegate.add_method("eth_compileSerpent", Eth::compile_serpent);
 		delegate.add_method("eth_getLogs", Eth::logs);
+		delegate.add_method("parity_getLogsDetailed", Eth::logs_detailed);
 		delegate.add_method("eth_getWork", Eth::work);
 		delegate.add_method("eth_submitWork", Eth::submit_work);
 		delegate.add_method("eth_submitHashrate", Eth::submit_hashrate);
@@ -188,6 +212,9 @@ pub trait EthFilter: Sized + Send + Sync + 'static {
 	/// Uninstalls filter.
 	fn uninstall_filter(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns all active filters.
+	fn list_filters(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -197,6 +224,7 @@ pub trait EthFilter: Sized + Send + Sync + 'static {
 		delegate.add_method("eth_getFilterChanges", EthFilter::filter_changes);
 		delegate.add_method("eth_getFilterLogs", EthFilter::filter_logs);
 		delegate.add_method("eth_uninstallFilter", EthFilter::uninstall_filter);
+		delegate.add_method("parity_listFilters", EthFilter::list_filters);
 		delegate
 	}
 }
@@ -206,6 +234,9 @@ pub trait EthSigning: Sized + Send + Sync + 'static {
 	/// Signs the data with given address signature.
 	fn sign(&self, _: Params, _: Ready);
 
+	/// Signs the hash of given structured typed data with given address signature.
+	fn sign_typed_data(&self, _: Params, _: Ready);
+
 	/// Posts sign request asynchronously.
 	/// Will return a confirmation ID for later use with check_transaction.
 	fn post_sign(&self, _: Params) -> Result<Value, Error>;
@@ -227,14 +258,20 @@ pub trait EthSigning: Sized + Send + Sync + 'static {
 	/// or an error.
 	fn check_request(&self, _: Params) -> Result<Value, Error>;
 
+	/// Replaces the transaction of a previously posted request which is still awaiting confirmation.
+	/// Only requests made on behalf of the same account can be replaced.
+	fn replace_request(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
 		delegate.add_async_method("eth_sign", EthSigning::sign);
+		delegate.add_async_method("eth_signTypedData", EthSigning::sign_typed_data);
 		delegate.add_async_method("eth_sendTransaction", EthSigning::send_transaction);
 		delegate.add_method("eth_postSign", EthSigning::post_sign);
 		delegate.add_method("eth_postTransaction", EthSigning::post_transaction);
 		delegate.add_method("eth_checkRequest", EthSigning::check_request);
+		delegate.add_method("parity_replaceRequest", EthSigning::replace_request);
 		delegate
 	}
 }
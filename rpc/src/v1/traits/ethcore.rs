@@ -76,6 +76,42 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns the value of the registrar for this network.
 	fn registry_address(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns recently rejected blocks.
+	fn bad_blocks(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the number of the block the given number of confirmations (or the default) below the best block.
+	fn stable_block_number(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the RLP of the block with given hash or number, as stored by the client.
+	fn block_rlp(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the RLP of the mined transaction with given hash, as stored by the client.
+	fn raw_transaction(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns gas usage, gas limits, transaction counts and intervals of the given number of latest blocks.
+	fn block_utilization(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns client version, build commit, supported protocol versions and chain name.
+	fn version_info(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the progress of the snapshot being created or restored, or `"idle"`.
+	fn snapshot_status(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the health of peer connections, sync and the local clock.
+	fn node_health(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns a flat map of sync, network and transaction queue gauges and counters.
+	fn metrics(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the difficulty of the best block.
+	fn current_difficulty(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the difficulty of the next block if it was sealed now, or `null` if the chain does not use proof-of-work.
+	fn next_difficulty(&self, _: Params) -> Result<Value, Error>;
+
+	/// Waits for the next imported block and returns its header.
+	fn subscribe_new_heads(&self, _: Params, _: Ready);
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -98,6 +134,18 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_generateSecretPhrase", Ethcore::generate_secret_phrase);
 		delegate.add_method("ethcore_phraseToAddress", Ethcore::phrase_to_address);
 		delegate.add_method("ethcore_registryAddress", Ethcore::registry_address);
+		delegate.add_method("parity_badBlocks", Ethcore::bad_blocks);
+		delegate.add_method("parity_stableBlockNumber", Ethcore::stable_block_number);
+		delegate.add_method("parity_getBlockRlp", Ethcore::block_rlp);
+		delegate.add_method("parity_getRawTransaction", Ethcore::raw_transaction);
+		delegate.add_method("parity_blockUtilization", Ethcore::block_utilization);
+		delegate.add_method("parity_versionInfo", Ethcore::version_info);
+		delegate.add_method("parity_snapshotStatus", Ethcore::snapshot_status);
+		delegate.add_method("parity_nodeHealth", Ethcore::node_health);
+		delegate.add_method("parity_metrics", Ethcore::metrics);
+		delegate.add_method("parity_currentDifficulty", Ethcore::current_difficulty);
+		delegate.add_method("parity_nextDifficulty", Ethcore::next_difficulty);
+		delegate.add_async_method("parity_subscribeNewHeads", Ethcore::subscribe_new_heads);
 
 		delegate
 	}
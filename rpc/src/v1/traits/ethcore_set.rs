@@ -61,6 +61,11 @@ pub trait EthcoreSet: Sized + Send + Sync + 'static {
 	/// Stop the network.
 	fn stop_network(&self, _: Params) -> Result<Value, Error>;
 
+	/// Starts taking a snapshot at the current best block into a new file with the given name
+	/// in the snapshot exports directory.
+	/// Returns the number of the block; progress is reported by `parity_snapshotStatus`.
+	fn take_snapshot(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -75,6 +80,7 @@ pub trait EthcoreSet: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_removeReservedPeer", EthcoreSet::remove_reserved_peer);
 		delegate.add_method("ethcore_dropNonReservedPeers", EthcoreSet::drop_non_reserved_peers);
 		delegate.add_method("ethcore_acceptNonReservedPeers", EthcoreSet::accept_non_reserved_peers);
+		delegate.add_method("parity_takeSnapshot", EthcoreSet::take_snapshot);
 
 		delegate
 	}
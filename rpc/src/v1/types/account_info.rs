@@ -0,0 +1,68 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::account_info::AccountInfo as EthAccountInfo;
+use v1::types::{H256, U256};
+
+/// Account summary
+#[derive(Debug, Serialize, PartialEq)]
+pub struct AccountInfo {
+	/// Balance
+	pub balance: U256,
+	/// Nonce
+	pub nonce: U256,
+	/// Code hash
+	#[serde(rename="codeHash")]
+	pub code_hash: H256,
+	/// Storage root
+	#[serde(rename="storageRoot")]
+	pub storage_root: H256,
+	/// Code size in bytes
+	#[serde(rename="codeSize")]
+	pub code_size: U256,
+}
+
+impl From<EthAccountInfo> for AccountInfo {
+	fn from(a: EthAccountInfo) -> Self {
+		AccountInfo {
+			balance: a.balance.into(),
+			nonce: a.nonce.into(),
+			code_hash: a.code_hash.into(),
+			storage_root: a.storage_root.into(),
+			code_size: a.code_size.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::{H256, U256};
+	use super::AccountInfo;
+
+	#[test]
+	fn test_serialize_account_info() {
+		let a = AccountInfo {
+			balance: U256::from(9),
+			nonce: U256::from(1),
+			code_hash: H256::default(),
+			storage_root: H256::default(),
+			code_size: U256::from(32),
+		};
+		let serialized = serde_json::to_string(&a).unwrap();
+		assert_eq!(serialized, r#"{"balance":"0x9","nonce":"0x1","codeHash":"0x0000000000000000000000000000000000000000000000000000000000000000","storageRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","codeSize":"0x20"}"#);
+	}
+}
@@ -0,0 +1,65 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::bad_block::BadBlock as EthBadBlock;
+use v1::types::{H256, U256};
+
+/// Recently rejected block
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BadBlock {
+	/// Block hash
+	pub hash: H256,
+	/// Block number
+	pub number: U256,
+	/// Reason of rejection
+	pub error: String,
+	/// Size of block RLP in bytes
+	pub size: U256,
+	/// Peer the block came from
+	pub peer: Option<String>,
+}
+
+impl From<EthBadBlock> for BadBlock {
+	fn from(b: EthBadBlock) -> Self {
+		BadBlock {
+			hash: b.hash.into(),
+			number: b.number.into(),
+			error: b.error,
+			size: b.size.into(),
+			peer: b.peer,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::{H256, U256};
+	use super::BadBlock;
+
+	#[test]
+	fn test_serialize_bad_block() {
+		let b = BadBlock {
+			hash: H256::default(),
+			number: U256::from(5),
+			error: "Invalid seal".into(),
+			size: U256::from(512),
+			peer: None,
+		};
+		let serialized = serde_json::to_string(&b).unwrap();
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","number":"0x5","error":"Invalid seal","size":"0x200","peer":null}"#);
+	}
+}
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use serde::{Deserialize, Deserializer, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, Error};
 use serde::de::Visitor;
 use ethcore::client::BlockID;
 
@@ -38,6 +38,18 @@ impl Deserialize for BlockNumber {
 	}
 }
 
+impl Serialize for BlockNumber {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		match *self {
+			BlockNumber::Num(ref n) => serializer.serialize_str(&format!("0x{:x}", n)),
+			BlockNumber::Latest => serializer.serialize_str("latest"),
+			BlockNumber::Earliest => serializer.serialize_str("earliest"),
+			BlockNumber::Pending => serializer.serialize_str("pending"),
+		}
+	}
+}
+
 struct BlockNumberVisitor;
 
 impl Visitor for BlockNumberVisitor {
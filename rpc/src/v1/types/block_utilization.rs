@@ -0,0 +1,58 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use v1::types::U256;
+
+/// Gas utilization of consecutive recent blocks, oldest first.
+/// All arrays have one entry per block.
+#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+pub struct BlockUtilization {
+	/// Number of the first block described
+	#[serde(rename="oldestBlock")]
+	pub oldest_block: U256,
+	/// Gas used by each block
+	#[serde(rename="gasUsed")]
+	pub gas_used: Vec<U256>,
+	/// Gas limit of each block
+	#[serde(rename="gasLimit")]
+	pub gas_limit: Vec<U256>,
+	/// Number of transactions in each block
+	#[serde(rename="transactionCount")]
+	pub transaction_count: Vec<U256>,
+	/// Seconds between each block and its parent (zero for the genesis block)
+	#[serde(rename="blockInterval")]
+	pub block_interval: Vec<U256>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::U256;
+	use super::BlockUtilization;
+
+	#[test]
+	fn test_serialize_block_utilization() {
+		let u = BlockUtilization {
+			oldest_block: U256::from(9),
+			gas_used: vec![U256::from(21000), U256::from(0)],
+			gas_limit: vec![U256::from(0x47e7c4), U256::from(0x47e7c4)],
+			transaction_count: vec![U256::from(1), U256::from(0)],
+			block_interval: vec![U256::from(14), U256::from(3)],
+		};
+		let serialized = serde_json::to_string(&u).unwrap();
+		assert_eq!(serialized, r#"{"oldestBlock":"0x9","gasUsed":["0x5208","0x0"],"gasLimit":["0x47e7c4","0x47e7c4"],"transactionCount":["0x1","0x0"],"blockInterval":["0xe","0x3"]}"#);
+	}
+}
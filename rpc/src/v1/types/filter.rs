@@ -14,12 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use serde::{Deserialize, Deserializer, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, Error};
 use serde_json::value;
 use jsonrpc_core::Value;
 use ethcore::filter::Filter as EthFilter;
 use ethcore::client::BlockID;
-use v1::types::{BlockNumber, H160, H256};
+use v1::types::{BlockNumber, H160, H256, U256};
 
 /// Variadic value
 #[derive(Debug, PartialEq, Clone)]
@@ -47,13 +47,24 @@ impl<T> Deserialize for VariadicValue<T> where T: Deserialize {
 	}
 }
 
+impl<T> Serialize for VariadicValue<T> where T: Deserialize + Serialize {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		match *self {
+			VariadicValue::Single(ref v) => v.serialize(serializer),
+			VariadicValue::Multiple(ref v) => v.serialize(serializer),
+			VariadicValue::Null => serializer.serialize_unit(),
+		}
+	}
+}
+
 /// Filter Address
 pub type FilterAddress = VariadicValue<H160>;
 /// Topic
 pub type Topic = VariadicValue<H256>;
 
 /// Filter
-#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Filter {
 	/// From Block
@@ -62,6 +73,12 @@ pub struct Filter {
 	/// To Block
 	#[serde(rename="toBlock")]
 	pub to_block: Option<BlockNumber>,
+	/// From Timestamp
+	#[serde(rename="fromTimestamp")]
+	pub from_timestamp: Option<U256>,
+	/// To Timestamp
+	#[serde(rename="toTimestamp")]
+	pub to_timestamp: Option<U256>,
 	/// Address
 	pub address: Option<FilterAddress>,
 	/// Topics
@@ -90,13 +107,29 @@ impl Into<EthFilter> for Filter {
 	}
 }
 
+/// Active filter description
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FilterInfo {
+	/// Filter id
+	pub id: U256,
+	/// Filter kind: `block`, `pending` or `logs`
+	pub kind: String,
+	/// Unix timestamp of filter creation
+	pub created: U256,
+	/// Unix timestamp of the last poll
+	#[serde(rename="lastPoll")]
+	pub last_poll: U256,
+	/// Log filter criteria
+	pub filter: Option<Filter>,
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use std::str::FromStr;
 	use util::hash::*;
 	use super::*;
-	use v1::types::BlockNumber;
+	use v1::types::{BlockNumber, U256};
 
 	#[test]
 	fn topic_deserialization() {
@@ -119,8 +152,38 @@ mod tests {
 		assert_eq!(deserialized, Filter {
 			from_block: Some(BlockNumber::Earliest),
 			to_block: Some(BlockNumber::Latest),
+			from_timestamp: None,
+			to_timestamp: None,
+			address: None,
+			topics: None
+		});
+	}
+
+	#[test]
+	fn filter_with_timestamps_deserialization() {
+		let s = r#"{"fromTimestamp":"0x57bd8e40","toTimestamp":"0x57bd9c50"}"#;
+		let deserialized: Filter = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, Filter {
+			from_block: None,
+			to_block: None,
+			from_timestamp: Some(U256::from(0x57bd8e40u64)),
+			to_timestamp: Some(U256::from(0x57bd9c50u64)),
 			address: None,
 			topics: None
 		});
 	}
+
+	#[test]
+	fn filter_serialization() {
+		let filter = Filter {
+			from_block: Some(BlockNumber::Num(10)),
+			to_block: Some(BlockNumber::Latest),
+			from_timestamp: None,
+			to_timestamp: None,
+			address: Some(VariadicValue::Single(H160::zero().into())),
+			topics: Some(vec![VariadicValue::Null]),
+		};
+		let serialized = serde_json::to_string(&filter).unwrap();
+		assert_eq!(serialized, r#"{"fromBlock":"0xa","toBlock":"latest","fromTimestamp":null,"toTimestamp":null,"address":"0x0000000000000000000000000000000000000000","topics":[null]}"#);
+	}
 }
@@ -46,6 +46,19 @@ pub struct Log {
 	pub log_type: String,
 }
 
+/// Logs together with statistics of the query
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LogsDetailed {
+	/// Matching logs
+	pub logs: Vec<Log>,
+	/// Number of blocks selected by blooms and scanned for logs
+	#[serde(rename="blocksScanned")]
+	pub blocks_scanned: U256,
+	/// Number of blocks containing matching logs
+	#[serde(rename="blocksMatched")]
+	pub blocks_matched: U256,
+}
+
 impl From<LocalizedLogEntry> for Log {
 	fn from(e: LocalizedLogEntry) -> Log {
 		Log {
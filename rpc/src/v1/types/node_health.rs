@@ -0,0 +1,63 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+/// Result of a single health check
+#[derive(Debug, Serialize, PartialEq)]
+pub struct HealthCheck {
+	/// Whether the check passed
+	pub ok: bool,
+	/// Human readable description of the result
+	pub details: String,
+}
+
+/// Result of the local clock check
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TimeHealth {
+	/// Whether the local clock is accurate enough
+	pub ok: bool,
+	/// Drift of the local clock in milliseconds, if it could be measured
+	#[serde(rename="driftMs")]
+	pub drift_ms: Option<i64>,
+	/// Human readable description of the result
+	pub details: String,
+}
+
+/// Node health
+#[derive(Debug, Serialize, PartialEq)]
+pub struct NodeHealth {
+	/// Connected peers check
+	pub peers: HealthCheck,
+	/// Sync progress check
+	pub sync: HealthCheck,
+	/// Local clock check
+	pub time: TimeHealth,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{NodeHealth, HealthCheck, TimeHealth};
+
+	#[test]
+	fn test_serialize_node_health() {
+		let t = NodeHealth {
+			peers: HealthCheck { ok: true, details: "Connected to 5 peers".into() },
+			sync: HealthCheck { ok: false, details: "Stalled".into() },
+			time: TimeHealth { ok: true, drift_ms: Some(-20), details: "Clock drift is 20ms".into() },
+		};
+		assert_eq!(serde_json::to_string(&t).unwrap(), r#"{"peers":{"ok":true,"details":"Connected to 5 peers"},"sync":{"ok":false,"details":"Stalled"},"time":{"ok":true,"driftMs":-20,"details":"Clock drift is 20ms"}}"#);
+	}
+}
@@ -26,10 +26,10 @@ pub struct Receipt {
 	/// Transaction index
 	#[serde(rename="transactionIndex")]
 	pub transaction_index: Option<U256>,
-	/// Block hash
+	/// Block hash (`None` if the transaction is still pending)
 	#[serde(rename="blockHash")]
 	pub block_hash: Option<H256>,
-	/// Block number
+	/// Block number (`None` if the transaction is still pending)
 	#[serde(rename="blockNumber")]
 	pub block_number: Option<U256>,
 	/// Cumulative gas used
@@ -62,15 +62,22 @@ impl From<LocalizedReceipt> for Receipt {
 
 impl From<RichReceipt> for Receipt {
 	fn from(r: RichReceipt) -> Self {
+		let transaction_hash: H256 = r.transaction_hash.into();
+		let transaction_index: U256 = r.transaction_index.into();
 		Receipt {
-			transaction_hash: Some(r.transaction_hash.into()),
-			transaction_index: Some(r.transaction_index.into()),
+			transaction_hash: Some(transaction_hash.clone()),
+			transaction_index: Some(transaction_index.clone()),
+			// not mined yet
 			block_hash: None,
 			block_number: None,
 			cumulative_gas_used: r.cumulative_gas_used.into(),
 			gas_used: Some(r.gas_used.into()),
 			contract_address: r.contract_address.map(Into::into),
-			logs: r.logs.into_iter().map(Into::into).collect(),
+			logs: r.logs.into_iter().map(|l| Log {
+				transaction_hash: Some(transaction_hash.clone()),
+				transaction_index: Some(transaction_index.clone()),
+				..l.into()
+			}).collect(),
 		}
 	}
 }
@@ -0,0 +1,122 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use serde::{Serialize, Serializer};
+use ethcore::snapshot::{CreationStatus, RestorationStatus};
+
+/// Progress of a snapshot being created
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct SnapshotCreation {
+	/// Number of accounts snapshotted so far
+	pub accounts: u64,
+	/// Number of blocks snapshotted so far
+	pub blocks: u64,
+	/// Bytes written so far
+	pub bytes: u64,
+	/// Whether the snapshot is complete
+	pub done: bool,
+}
+
+impl From<CreationStatus> for SnapshotCreation {
+	fn from(s: CreationStatus) -> Self {
+		SnapshotCreation {
+			accounts: s.accounts,
+			blocks: s.blocks,
+			bytes: s.bytes,
+			done: s.done,
+		}
+	}
+}
+
+/// Progress of a snapshot being restored
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct SnapshotRestoration {
+	/// Number of state chunks restored
+	#[serde(rename="stateChunksDone")]
+	pub state_chunks_done: u32,
+	/// Number of block chunks restored
+	#[serde(rename="blockChunksDone")]
+	pub block_chunks_done: u32,
+}
+
+/// Snapshot status
+#[derive(Debug, PartialEq)]
+pub enum SnapshotStatus {
+	/// A snapshot is being created
+	Creating(SnapshotCreation),
+	/// A snapshot is being restored
+	Restoring(SnapshotRestoration),
+	/// Neither creating nor restoring
+	Idle,
+}
+
+impl SnapshotStatus {
+	/// Status of the snapshot service. Creation takes precedence over restoration.
+	/// A failed restoration is reported as idle.
+	pub fn new(creation: Option<CreationStatus>, restoration: RestorationStatus) -> Self {
+		match (creation, restoration) {
+			(Some(creation), _) => SnapshotStatus::Creating(creation.into()),
+			(None, RestorationStatus::Ongoing { state_chunks_done, block_chunks_done }) => SnapshotStatus::Restoring(SnapshotRestoration {
+				state_chunks_done: state_chunks_done,
+				block_chunks_done: block_chunks_done,
+			}),
+			(None, _) => SnapshotStatus::Idle,
+		}
+	}
+}
+
+impl Serialize for SnapshotStatus {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		match *self {
+			SnapshotStatus::Creating(ref creation) => {
+				let mut map = BTreeMap::new();
+				map.insert("creating", creation);
+				map.serialize(serializer)
+			},
+			SnapshotStatus::Restoring(ref restoration) => {
+				let mut map = BTreeMap::new();
+				map.insert("restoring", restoration);
+				map.serialize(serializer)
+			},
+			SnapshotStatus::Idle => "idle".serialize(serializer),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::snapshot::{CreationStatus, RestorationStatus};
+	use super::SnapshotStatus;
+
+	#[test]
+	fn test_serialize_snapshot_status() {
+		let t = SnapshotStatus::new(None, RestorationStatus::Inactive);
+		assert_eq!(serde_json::to_string(&t).unwrap(), r#""idle""#);
+
+		let t = SnapshotStatus::new(None, RestorationStatus::Failed);
+		assert_eq!(serde_json::to_string(&t).unwrap(), r#""idle""#);
+
+		let t = SnapshotStatus::new(None, RestorationStatus::Ongoing { state_chunks_done: 2, block_chunks_done: 1 });
+		assert_eq!(serde_json::to_string(&t).unwrap(), r#"{"restoring":{"stateChunksDone":2,"blockChunksDone":1}}"#);
+
+		let creation = CreationStatus { accounts: 10, blocks: 5, bytes: 1024, done: false };
+		let t = SnapshotStatus::new(Some(creation), RestorationStatus::Inactive);
+		assert_eq!(serde_json::to_string(&t).unwrap(), r#"{"creating":{"accounts":10,"blocks":5,"bytes":1024,"done":false}}"#);
+	}
+}
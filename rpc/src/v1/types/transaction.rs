@@ -43,6 +43,15 @@ pub struct Transaction {
 	/// Gas Price
 	#[serde(rename="gasPrice")]
 	pub gas_price: U256,
+	/// Max fee per gas (typed transactions only)
+	#[serde(rename="maxFeePerGas", skip_serializing_if="Option::is_none")]
+	pub max_fee_per_gas: Option<U256>,
+	/// Max priority fee per gas (typed transactions only)
+	#[serde(rename="maxPriorityFeePerGas", skip_serializing_if="Option::is_none")]
+	pub max_priority_fee_per_gas: Option<U256>,
+	/// Transaction type (typed transactions only)
+	#[serde(rename="type", skip_serializing_if="Option::is_none")]
+	pub transaction_type: Option<U256>,
 	/// Gas
 	pub gas: U256,
 	/// Data
@@ -68,6 +77,9 @@ impl From<LocalizedTransaction> for Transaction {
 			},
 			value: t.value.into(),
 			gas_price: t.gas_price.into(),
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
+			transaction_type: None,
 			gas: t.gas.into(),
 			input: Bytes::new(t.data.clone()),
 			creates: match t.action {
@@ -94,6 +106,9 @@ impl From<SignedTransaction> for Transaction {
 			},
 			value: t.value.into(),
 			gas_price: t.gas_price.into(),
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
+			transaction_type: None,
 			gas: t.gas.into(),
 			input: Bytes::new(t.data.clone()),
 			creates: match t.action {
@@ -109,6 +124,28 @@ impl From<SignedTransaction> for Transaction {
 mod tests {
 	use super::Transaction;
 	use serde_json;
+	use v1::types::U256;
+
+	#[test]
+	fn test_legacy_transaction_serialize_omits_typed_fields() {
+		let t = Transaction::default();
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert!(!serialized.contains("maxFeePerGas"));
+		assert!(!serialized.contains("maxPriorityFeePerGas"));
+		assert!(!serialized.contains(r#""type""#));
+	}
+
+	#[test]
+	fn test_typed_transaction_serialize() {
+		let t = Transaction {
+			max_fee_per_gas: Some(U256::from(0x30)),
+			max_priority_fee_per_gas: Some(U256::from(0x2)),
+			transaction_type: Some(U256::from(2)),
+			..Transaction::default()
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0","blockHash":null,"blockNumber":null,"transactionIndex":null,"from":"0x0000000000000000000000000000000000000000","to":null,"value":"0x0","gasPrice":"0x0","maxFeePerGas":"0x30","maxPriorityFeePerGas":"0x2","type":"0x2","gas":"0x0","input":"0x","creates":null,"raw":"0x"}"#);
+	}
 
 	#[test]
 	fn test_transaction_serialize() {
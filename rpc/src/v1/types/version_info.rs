@@ -0,0 +1,47 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+/// Build and protocol details of the node.
+#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+pub struct VersionInfo {
+	/// Full client version string
+	pub version: String,
+	/// Short hash of the commit the client was built from, if known
+	pub commit: Option<String>,
+	/// Supported eth protocol versions
+	#[serde(rename="protocolVersions")]
+	pub protocol_versions: Vec<u8>,
+	/// Name of the active chain or fork
+	pub chain: String,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::VersionInfo;
+
+	#[test]
+	fn test_serialize_version_info() {
+		let v = VersionInfo {
+			version: "Parity/v1.4.0".into(),
+			commit: Some("abcdef0".into()),
+			protocol_versions: vec![62, 63, 64],
+			chain: "Morden".into(),
+		};
+		let serialized = serde_json::to_string(&v).unwrap();
+		assert_eq!(serialized, r#"{"version":"Parity/v1.4.0","commit":"abcdef0","protocolVersions":[62,63,64],"chain":"Morden"}"#);
+	}
+}
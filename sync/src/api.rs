@@ -15,25 +15,45 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::thread;
 use network::{NetworkProtocolHandler, NetworkService, NetworkContext, PeerId,
-	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, NetworkError};
+	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, NetworkError,
+	ReservedPeerStatus as BasicReservedPeerStatus, NodeId};
 use util::{U256, H256};
 use io::{TimerToken};
 use ethcore::client::{BlockChainClient, ChainNotify};
 use ethcore::snapshot::SnapshotService;
 use ethcore::header::BlockNumber;
 use sync_io::NetSyncIo;
-use chain::{ChainSync, SyncStatus};
+use chain::{ChainSync, SyncStatus, PeerRequest};
+use chunk_requests::PeerChunkSource;
+use packet_queue::{PacketQueue, Dispatch, MAX_INLINE_PACKET_SIZE, MAX_QUEUED_PACKETS_PER_PEER};
+use rate_limit::{RateLimiter, Verdict, MAX_RATE_PENALTY};
 use std::net::{SocketAddr, AddrParseError};
 use ipc::{BinaryConvertable, BinaryConvertError, IpcConfig};
 use std::str::FromStr;
+use std::time::Duration;
+use std::mem;
+use std::collections::{HashMap, VecDeque};
 use parking_lot::RwLock;
 
 /// Ethereum sync protocol
 pub const ETH_PROTOCOL: &'static str = "eth";
+/// Ethereum protocol versions supported by the sync
+pub const ETH_PROTOCOL_VERSIONS: &'static [u8] = &[62u8, 63u8, 64u8];
+
+/// How newly sealed blocks are propagated to lagging peers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockPropagation {
+	/// Send the full block to every lagging peer.
+	Full,
+	/// Send the full block to the given fraction of lagging peers (at least one)
+	/// and announce the hash to the rest.
+	Partial(f64),
+}
 
 /// Sync configuration
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct SyncConfig {
 	/// Max blocks to download ahead
 	pub max_download_ahead_blocks: usize,
@@ -41,6 +61,33 @@ pub struct SyncConfig {
 	pub network_id: U256,
 	/// Fork block to check
 	pub fork_block: Option<(BlockNumber, H256)>,
+	/// Sealed block propagation policy
+	pub block_propagation: BlockPropagation,
+	/// Maximum heap memory to use for downloaded blocks and sync caches.
+	/// When the limit is reached, no new headers are requested and block bodies are downloaded from
+	/// one peer at a time until the downloaded blocks are imported.
+	pub max_mem_use: Option<usize>,
+	/// Blocks which are never imported. Peers supplying them are disabled.
+	pub banned_hashes: Vec<H256>,
+	/// Imported blocks are not propagated while syncing more than this many blocks behind
+	/// the highest block known. Sealed blocks are always propagated.
+	pub propagation_distance: BlockNumber,
+	/// Packets of each type accepted from a peer per second on average. Responses to our requests are not limited.
+	/// Zero disables rate limiting.
+	pub packet_rate: u32,
+	/// Packets of each type a peer may send at once before the rate limit applies.
+	pub packet_burst: u32,
+	/// Block download is abandoned for warp sync when more than this many blocks behind the highest block
+	/// and a peer has a snapshot at least this far ahead. Zero disables switching.
+	pub warp_threshold: BlockNumber,
+	/// Snapshots which failed to restore this many times are never synced again.
+	pub max_restoration_failures: usize,
+	/// Maximum number of peers block headers are requested from concurrently while catching up.
+	/// This only throttles requests: subchain heads are the same for every peer, only subchain
+	/// headers are split between peers.
+	pub header_download_peers: usize,
+	/// Snapshot chunks which are only requested from and accepted from the node with the given public key.
+	pub pinned_chunks: HashMap<H256, NodeId>,
 }
 
 impl Default for SyncConfig {
@@ -49,23 +96,132 @@ impl Default for SyncConfig {
 			max_download_ahead_blocks: 20000,
 			network_id: U256::from(1),
 			fork_block: None,
+			block_propagation: BlockPropagation::Full,
+			max_mem_use: None,
+			banned_hashes: Vec::new(),
+			propagation_distance: 32,
+			packet_rate: 100,
+			packet_burst: 1000,
+			warp_threshold: 30000,
+			max_restoration_failures: 2,
+			header_download_peers: 4,
+			pinned_chunks: HashMap::new(),
 		}
 	}
 }
 
-binary_fixed_size!(SyncConfig);
+/// Size of a pinned chunk entry in the serialized `SyncConfig`.
+const PINNED_CHUNK_SIZE: usize = 32 + 64;
+
+/// Fixed-size part of `SyncConfig`, copied as is over IPC.
+#[derive(Clone, Copy)]
+struct FixedSyncConfig {
+	max_download_ahead_blocks: usize,
+	network_id: U256,
+	fork_block: Option<(BlockNumber, H256)>,
+	block_propagation: BlockPropagation,
+	max_mem_use: Option<usize>,
+	propagation_distance: BlockNumber,
+	packet_rate: u32,
+	packet_burst: u32,
+	warp_threshold: BlockNumber,
+	max_restoration_failures: usize,
+	header_download_peers: usize,
+}
+
+binary_fixed_size!(FixedSyncConfig);
+
+impl BinaryConvertable for SyncConfig {
+	fn size(&self) -> usize {
+		mem::size_of::<FixedSyncConfig>() + self.banned_hashes.size() + self.pinned_chunks.len() * PINNED_CHUNK_SIZE
+	}
+
+	fn to_bytes(&self, buffer: &mut [u8], length_stack: &mut VecDeque<usize>) -> Result<(), BinaryConvertError> {
+		let fixed = FixedSyncConfig {
+			max_download_ahead_blocks: self.max_download_ahead_blocks,
+			network_id: self.network_id,
+			fork_block: self.fork_block,
+			block_propagation: self.block_propagation,
+			max_mem_use: self.max_mem_use,
+			propagation_distance: self.propagation_distance,
+			packet_rate: self.packet_rate,
+			packet_burst: self.packet_burst,
+			warp_threshold: self.warp_threshold,
+			max_restoration_failures: self.max_restoration_failures,
+			header_download_peers: self.header_download_peers,
+		};
+		let fixed_size = mem::size_of::<FixedSyncConfig>();
+		try!(fixed.to_bytes(&mut buffer[..fixed_size], length_stack));
+
+		let banned_size = self.banned_hashes.size();
+		length_stack.push_back(banned_size);
+		let banned_end = fixed_size + banned_size;
+		try!(self.banned_hashes.to_bytes(&mut buffer[fixed_size..banned_end], length_stack));
+
+		let (chunks, nodes): (Vec<H256>, Vec<NodeId>) = self.pinned_chunks.iter().map(|(c, n)| (c.clone(), n.clone())).unzip();
+		let chunks_end = banned_end + chunks.size();
+		try!(chunks.to_bytes(&mut buffer[banned_end..chunks_end], length_stack));
+		nodes.to_bytes(&mut buffer[chunks_end..], length_stack)
+	}
+
+	fn from_bytes(buffer: &[u8], length_stack: &mut VecDeque<usize>) -> Result<Self, BinaryConvertError> {
+		let fixed_size = mem::size_of::<FixedSyncConfig>();
+		if buffer.len() < fixed_size {
+			return Err(BinaryConvertError::size(fixed_size, buffer.len()));
+		}
+		let fixed = try!(FixedSyncConfig::from_bytes(&buffer[..fixed_size], length_stack));
+
+		let banned_size = try!(length_stack.pop_front().ok_or(BinaryConvertError::length()));
+		let banned_end = fixed_size + banned_size;
+		if buffer.len() < banned_end || (buffer.len() - banned_end) % PINNED_CHUNK_SIZE != 0 {
+			return Err(BinaryConvertError::boundaries());
+		}
+		let banned_hashes = try!(Vec::<H256>::from_bytes(&buffer[fixed_size..banned_end], length_stack));
+
+		let chunks_end = banned_end + (buffer.len() - banned_end) / PINNED_CHUNK_SIZE * mem::size_of::<H256>();
+		let chunks = try!(Vec::<H256>::from_bytes(&buffer[banned_end..chunks_end], length_stack));
+		let nodes = try!(Vec::<NodeId>::from_bytes(&buffer[chunks_end..], length_stack));
+
+		Ok(SyncConfig {
+			max_download_ahead_blocks: fixed.max_download_ahead_blocks,
+			network_id: fixed.network_id,
+			fork_block: fixed.fork_block,
+			block_propagation: fixed.block_propagation,
+			max_mem_use: fixed.max_mem_use,
+			propagation_distance: fixed.propagation_distance,
+			packet_rate: fixed.packet_rate,
+			packet_burst: fixed.packet_burst,
+			warp_threshold: fixed.warp_threshold,
+			max_restoration_failures: fixed.max_restoration_failures,
+			header_download_peers: fixed.header_download_peers,
+			banned_hashes: banned_hashes,
+			pinned_chunks: chunks.into_iter().zip(nodes).collect(),
+		})
+	}
+
+	fn len_params() -> usize {
+		1
+	}
+}
+
 binary_fixed_size!(SyncStatus);
 
 /// Current sync status
 pub trait SyncProvider: Send + Sync {
 	/// Get sync status
 	fn status(&self) -> SyncStatus;
+
+	/// Get supported ethereum protocol versions
+	fn protocol_versions(&self) -> Vec<u8>;
+
+	/// Get the request outstanding to each connected peer
+	fn peers(&self) -> Vec<PeerRequestInfo>;
 }
 
 /// Ethereum network protocol handler
 pub struct EthSync {
 	/// Network service
-	network: NetworkService,
+	network: Arc<NetworkService>,
 	/// Protocol handler
 	handler: Arc<SyncProtocolHandler>,
 }
@@ -73,15 +229,40 @@ pub struct EthSync {
 impl EthSync {
 	/// Creates and register protocol with the network service
 	pub fn new(config: SyncConfig, chain: Arc<BlockChainClient>, snapshot_service: Arc<SnapshotService>, network_config: NetworkConfiguration) -> Result<Arc<EthSync>, NetworkError> {
+		let limiter = RateLimiter::new(config.packet_rate, config.packet_burst, MAX_RATE_PENALTY);
 		let chain_sync = ChainSync::new(config, &*chain);
-		let service = try!(NetworkService::new(try!(network_config.into_basic())));
+		let service = Arc::new(try!(NetworkService::new(try!(network_config.into_basic()))));
+		let handler = Arc::new(SyncProtocolHandler {
+			sync: RwLock::new(chain_sync),
+			chain: chain,
+			snapshot_service: snapshot_service,
+			packets: PacketQueue::new(MAX_INLINE_PACKET_SIZE, MAX_QUEUED_PACKETS_PER_PEER),
+			limiter: limiter,
+		});
+		{
+			let handler = handler.clone();
+			let service = service.clone();
+			try!(thread::Builder::new().name("Sync worker".into()).spawn(move || handler.process_queued_packets(&service)));
+		}
 		let sync = Arc::new(EthSync{
 			network: service,
-			handler: Arc::new(SyncProtocolHandler { sync: RwLock::new(chain_sync), chain: chain, snapshot_service: snapshot_service }),
+			handler: handler,
 		});
 
 		Ok(sync)
 	}
+
+	/// Snapshot chunk source fetching chunks from connected peers which offer a snapshot.
+	/// Each chunk is given up on after `timeout`.
+	pub fn chunk_source(&self, timeout: Duration) -> PeerChunkSource {
+		PeerChunkSource::new(self.handler.sync.read().chunk_requests(), timeout)
+	}
+}
+
+impl Drop for EthSync {
+	fn drop(&mut self) {
+		self.handler.packets.close();
+	}
 }
 
 #[derive(Ipc)]
@@ -91,6 +272,15 @@ impl SyncProvider for EthSync {
 	fn status(&self) -> SyncStatus {
 		self.handler.sync.write().status()
 	}
+
+	fn protocol_versions(&self) -> Vec<u8> {
+		ETH_PROTOCOL_VERSIONS.to_vec()
+	}
+
+	fn peers(&self) -> Vec<PeerRequestInfo> {
+		let now = ::time::precise_time_s();
+		self.handler.sync.read().peers().into_iter().map(|(peer_id, request)| PeerRequestInfo::new(peer_id, &request, now)).collect()
+	}
 }
 
 struct SyncProtocolHandler {
@@ -100,6 +290,31 @@ struct SyncProtocolHandler {
 	snapshot_service: Arc<SnapshotService>,
 	/// Sync strategy
 	sync: RwLock<ChainSync>,
+	/// Large packets waiting for the sync worker.
+	packets: PacketQueue,
+	/// Incoming packet rate limits.
+	limiter: RateLimiter,
+}
+
+impl SyncProtocolHandler {
+	/// Process queued packets until the queue is closed.
+	fn process_queued_packets(&self, network: &NetworkService) {
+		while let Some((peer, packet_id, data)) = self.packets.pop() {
+			network.with_context(ETH_PROTOCOL, |context| {
+				ChainSync::dispatch_packet(&self.sync, &mut NetSyncIo::new(context, &*self.chain, &*self.snapshot_service), peer, packet_id, &data);
+			});
+			self.packets.complete(peer);
+		}
+	}
+
+	/// Check an incoming packet against the rate limit.
+	fn admit(&self, peer: PeerId, packet_id: u8) -> Verdict {
+		// responses are bounded by the requests we make, dropping them would only stall our own sync
+		match ChainSync::is_response_packet(packet_id) {
+			true => Verdict::Accept,
+			false => self.limiter.check(peer, packet_id),
+		}
+	}
 }
 
 impl NetworkProtocolHandler for SyncProtocolHandler {
@@ -108,7 +323,30 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
 	}
 
 	fn read(&self, io: &NetworkContext, peer: &PeerId, packet_id: u8, data: &[u8]) {
-		ChainSync::dispatch_packet(&self.sync, &mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service), *peer, packet_id, data);
+		match self.admit(*peer, packet_id) {
+			Verdict::Accept => {},
+			Verdict::Drop => {
+				trace!(target: "sync", "{}: Packet {} over the rate limit, dropped", peer, packet_id);
+				return;
+			},
+			Verdict::Disable => {
+				debug!(target: "sync", "{}: Too many packets over the rate limit, disabling", peer);
+				self.limiter.clear_peer(*peer);
+				self.packets.clear_peer(*peer);
+				io.disable_peer(*peer);
+				return;
+			},
+		}
+
+		match self.packets.push(*peer, packet_id, data) {
+			Dispatch::Inline => ChainSync::dispatch_packet(&self.sync, &mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service), *peer, packet_id, data),
+			Dispatch::Queued => {},
+			Dispatch::Overflow => {
+				debug!(target: "sync", "{}: Too many packets queued, disconnecting", peer);
+				self.packets.clear_peer(*peer);
+				io.disconnect_peer(*peer);
+			},
+		}
 	}
 
 	fn connected(&self, io: &NetworkContext, peer: &PeerId) {
@@ -116,6 +354,8 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
 	}
 
 	fn disconnected(&self, io: &NetworkContext, peer: &PeerId) {
+		self.packets.clear_peer(*peer);
+		self.limiter.clear_peer(*peer);
 		self.sync.write().on_peer_aborting(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service), *peer);
 	}
 
@@ -149,7 +389,7 @@ impl ChainNotify for EthSync {
 
 	fn start(&self) {
 		self.network.start().unwrap_or_else(|e| warn!("Error starting network: {:?}", e));
-		self.network.register_protocol(self.handler.clone(), ETH_PROTOCOL, &[62u8, 63u8, 64u8])
+		self.network.register_protocol(self.handler.clone(), ETH_PROTOCOL, ETH_PROTOCOL_VERSIONS)
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
 	}
 
@@ -177,6 +417,12 @@ pub trait ManageNetwork : Send + Sync {
 	fn stop_network(&self);
 	/// Query the current configuration of the network
 	fn network_config(&self) -> NetworkConfiguration;
+	/// Get connection status of all reserved peers
+	fn reserved_peers_status(&self) -> Vec<ReservedPeerStatus>;
+	/// Refuse to import the block with given hash and its descendants
+	fn ban_block(&self, hash: H256);
+	/// Get connection and traffic counters of the network
+	fn network_metrics(&self) -> NetworkMetrics;
 }
 
 
@@ -214,6 +460,25 @@ impl ManageNetwork for EthSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn reserved_peers_status(&self) -> Vec<ReservedPeerStatus> {
+		self.network.reserved_peers_status().into_iter().map(Into::into).collect()
+	}
+
+	fn ban_block(&self, hash: H256) {
+		self.handler.sync.write().ban_block(hash);
+	}
+
+	fn network_metrics(&self) -> NetworkMetrics {
+		let (inbound, outbound) = self.network.session_counts();
+		let stats = self.network.stats();
+		NetworkMetrics {
+			inbound_peers: inbound as u64,
+			outbound_peers: outbound as u64,
+			bytes_sent: stats.send() as u64,
+			bytes_received: stats.recv() as u64,
+		}
+	}
 }
 
 #[derive(Binary, Debug, Clone, PartialEq, Eq)]
@@ -233,6 +498,8 @@ pub struct NetworkConfiguration {
 	pub nat_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Interval between discovery bucket refreshes in milliseconds. Default interval is used if none.
+	pub discovery_refresh_interval: Option<u64>,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -245,6 +512,18 @@ pub struct NetworkConfiguration {
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub allow_non_reserved: bool,
+	/// Node name advertised in the client version string.
+	pub identity: String,
+	/// Delay in milliseconds before re-dialing a failed endpoint. Default delay is used if none.
+	pub dial_backoff: Option<u64>,
+	/// Upper bound in milliseconds on the delay between dials to a failing endpoint. Default bound is used if none.
+	pub max_dial_backoff: Option<u64>,
+	/// Delay in milliseconds before re-dialing a failed reserved node. Default delay is used if none.
+	pub reserved_dial_interval: Option<u64>,
+	/// Time in milliseconds a session may keep failing to read with transient errors before it is dropped. Default period is used if none.
+	pub read_error_grace: Option<u64>,
+	/// Offer snappy compression of capability packets to peers.
+	pub packet_compression: bool,
 }
 
 impl NetworkConfiguration {
@@ -276,16 +555,27 @@ impl NetworkConfiguration {
 			udp_port: self.udp_port,
 			nat_enabled: self.nat_enabled,
 			discovery_enabled: self.discovery_enabled,
+			discovery_refresh_interval: self.discovery_refresh_interval.map(Duration::from_millis),
 			boot_nodes: self.boot_nodes,
 			use_secret: self.use_secret,
 			max_peers: self.max_peers,
 			min_peers: self.min_peers,
 			reserved_nodes: self.reserved_nodes,
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
+			identity: self.identity,
+			dial_backoff: self.dial_backoff.map(Duration::from_millis),
+			max_dial_backoff: self.max_dial_backoff.map(Duration::from_millis),
+			reserved_dial_interval: self.reserved_dial_interval.map(Duration::from_millis),
+			read_error_grace: self.read_error_grace.map(Duration::from_millis),
+			packet_compression: self.packet_compression,
 		})
 	}
 }
 
+fn as_millis(d: Duration) -> u64 {
+	d.as_secs() * 1000 + (d.subsec_nanos() / 1_000_000) as u64
+}
+
 impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 	fn from(other: BasicNetworkConfiguration) -> Self {
 		NetworkConfiguration {
@@ -296,19 +586,127 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			udp_port: other.udp_port,
 			nat_enabled: other.nat_enabled,
 			discovery_enabled: other.discovery_enabled,
+			discovery_refresh_interval: other.discovery_refresh_interval.map(as_millis),
 			boot_nodes: other.boot_nodes,
 			use_secret: other.use_secret,
 			max_peers: other.max_peers,
 			min_peers: other.min_peers,
 			reserved_nodes: other.reserved_nodes,
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
+			identity: other.identity,
+			dial_backoff: other.dial_backoff.map(as_millis),
+			max_dial_backoff: other.max_dial_backoff.map(as_millis),
+			reserved_dial_interval: other.reserved_dial_interval.map(as_millis),
+			read_error_grace: other.read_error_grace.map(as_millis),
+			packet_compression: other.packet_compression,
+		}
+	}
+}
+
+/// Connection status of a reserved peer
+#[derive(Binary, Debug, Clone, PartialEq, Eq)]
+pub struct ReservedPeerStatus {
+	/// Node URL of the peer
+	pub url: String,
+	/// Whether there is an established session with the peer
+	pub connected: bool,
+}
+
+impl From<BasicReservedPeerStatus> for ReservedPeerStatus {
+	fn from(other: BasicReservedPeerStatus) -> Self {
+		ReservedPeerStatus {
+			url: other.url,
+			connected: other.connected,
 		}
 	}
 }
 
+/// Request outstanding to a connected peer
+#[derive(Binary, Debug, Clone, PartialEq, Eq)]
+pub struct PeerRequestInfo {
+	/// Id of the peer
+	pub peer_id: u64,
+	/// Type of data being requested, `Nothing` if the peer is idle
+	pub asking: String,
+	/// Requested header hash, snapshot chunk hash or first block body hash, if any
+	pub target: Option<H256>,
+	/// Number of block bodies requested
+	pub blocks: u64,
+	/// Milliseconds since the request was sent, zero if the peer is idle
+	pub age_ms: u64,
+}
+
+impl PeerRequestInfo {
+	fn new(peer_id: PeerId, request: &PeerRequest, now: f64) -> Self {
+		let age_ms = if request.is_pending() { (request.age(now) * 1000f64) as u64 } else { 0 };
+		PeerRequestInfo {
+			peer_id: peer_id as u64,
+			asking: format!("{:?}", request.asking),
+			target: request.hash.or(request.snapshot_data).or(request.blocks.first().cloned()),
+			blocks: request.blocks.len() as u64,
+			age_ms: age_ms,
+		}
+	}
+}
+
+/// Connection and traffic counters of the network
+#[derive(Binary, Debug, Clone, PartialEq, Eq, Default)]
+pub struct NetworkMetrics {
+	/// Number of connected peers that dialed us
+	pub inbound_peers: u64,
+	/// Number of connected peers we dialed
+	pub outbound_peers: u64,
+	/// Total number of bytes sent since startup
+	pub bytes_sent: u64,
+	/// Total number of bytes received since startup
+	pub bytes_received: u64,
+}
+
 #[derive(Debug, Binary, Clone)]
 pub struct ServiceConfiguration {
 	pub sync: SyncConfig,
 	pub net: NetworkConfiguration,
 	pub io_path: String,
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use parking_lot::RwLock;
+	use ethcore::client::{TestBlockChainClient, EachBlockWith};
+	use chain::ChainSync;
+	use packet_queue::{PacketQueue, MAX_INLINE_PACKET_SIZE, MAX_QUEUED_PACKETS_PER_PEER};
+	use rate_limit::{RateLimiter, Verdict};
+	use tests::snapshot::TestSnapshotService;
+	use super::{SyncConfig, SyncProtocolHandler};
+
+	fn dummy_handler(limiter: RateLimiter) -> SyncProtocolHandler {
+		let chain = TestBlockChainClient::new();
+		chain.add_blocks(10, EachBlockWith::Nothing);
+		let sync = ChainSync::new(SyncConfig::default(), &chain);
+		SyncProtocolHandler {
+			chain: Arc::new(chain),
+			snapshot_service: Arc::new(TestSnapshotService::new()),
+			sync: RwLock::new(sync),
+			packets: PacketQueue::new(MAX_INLINE_PACKET_SIZE, MAX_QUEUED_PACKETS_PER_PEER),
+			limiter: limiter,
+		}
+	}
+
+	#[test]
+	fn rate_limits_requests_but_not_responses() {
+		let handler = dummy_handler(RateLimiter::new(1, 1, 3));
+
+		// BlockHeaders answers our own request and is never limited
+		for _ in 0..10 {
+			assert_eq!(handler.admit(1, 0x04), Verdict::Accept);
+		}
+		assert_eq!(handler.limiter.penalty(1), 0);
+
+		// while GetBlockHeaders from the same peer is
+		assert_eq!(handler.admit(1, 0x03), Verdict::Accept);
+		assert_eq!(handler.admit(1, 0x03), Verdict::Drop);
+		assert_eq!(handler.admit(1, 0x03), Verdict::Drop);
+		assert_eq!(handler.admit(1, 0x03), Verdict::Disable);
+	}
+}
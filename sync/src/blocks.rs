@@ -16,7 +16,6 @@
 
 use util::*;
 use rlp::*;
-use network::NetworkError;
 use ethcore::header::{ Header as BlockHeader};
 
 known_heap_size!(0, HeaderId, SyncBlock);
@@ -25,15 +24,29 @@ known_heap_size!(0, HeaderId, SyncBlock);
 struct SyncBlock {
 	header: Bytes,
 	body: Option<Bytes>,
+	header_id: HeaderId,
 }
 
 /// Used to identify header by transactions and uncles hashes
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Eq, PartialEq, Hash, Clone)]
 struct HeaderId {
 	transactions_root: H256,
 	uncles: H256
 }
 
+/// Reason a downloaded block body was not inserted.
+#[derive(Debug, PartialEq)]
+pub enum BodyMatchError {
+	/// Body is not valid RLP.
+	Malformed,
+	/// No known header has the body's transactions root and uncles hash.
+	NoMatchingHeader,
+	/// Body of the header with given hash has already been received.
+	Duplicate(H256),
+	/// Body of the header with given hash has not been requested.
+	Unsolicited(H256),
+}
+
 /// A collection of blocks and subchain pointers being downloaded. This keeps track of
 /// which headers/bodies need to be downloaded, which are being downloaded and also holds
 /// the downloaded blocks.
@@ -45,7 +58,7 @@ pub struct BlockCollection {
 	blocks: HashMap<H256, SyncBlock>,
 	/// Downloaded blocks by parent.
 	parents: HashMap<H256, H256>,
-	/// Used to map body to header.
+	/// Used to map body to header. Kept until the block is drained.
 	header_ids: HashMap<HeaderId, H256>,
 	/// First block in `blocks`.
 	head: Option<H256>,
@@ -96,18 +109,17 @@ impl BlockCollection {
 		self.update_heads();
 	}
 
-	/// Insert a collection of block bodies for previously downloaded headers.
-	pub fn insert_bodies(&mut self, bodies: Vec<Bytes>) -> usize {
-		let mut inserted = 0;
-		for b in bodies.into_iter() {
-			if let Err(e) =  self.insert_body(b) {
+	/// Insert a collection of block bodies for previously downloaded headers, `requested` being the hashes
+	/// of the headers the bodies were requested for. Returns the hash of the header each body matched
+	/// or the reason it was rejected.
+	pub fn insert_bodies(&mut self, bodies: Vec<Bytes>, requested: &[H256]) -> Vec<Result<H256, BodyMatchError>> {
+		bodies.into_iter().map(|b| {
+			let res = self.insert_body(b, requested);
+			if let Err(ref e) = res {
 				trace!(target: "sync", "Ignored invalid body: {:?}", e);
 			}
-			else {
-				inserted += 1;
-			}
-		}
-		inserted
+			res
+		}).collect()
 	}
 
 	/// Returns a set of block hashes that require a body download. The returned set is marked as being downloaded.
@@ -115,6 +127,22 @@ impl BlockCollection {
 		if self.head.is_none() {
 			return Vec::new();
 		}
+		let mut needed_bodies = self.needed_lowest_bodies(count);
+		for h in self.header_ids.values() {
+			if needed_bodies.len() >= count {
+				break;
+			}
+			if !self.downloading_bodies.contains(h) && self.blocks.get(h).map_or(false, |b| b.body.is_none()) {
+				needed_bodies.push(h.clone());
+				self.downloading_bodies.insert(h.clone());
+			}
+		}
+		needed_bodies
+	}
+
+	/// Returns a set of block hashes of the lowest subchain, which is imported next, that require a body download.
+	/// The returned set is marked as being downloaded.
+	pub fn needed_lowest_bodies(&mut self, count: usize) -> Vec<H256> {
 		let mut needed_bodies: Vec<H256> = Vec::new();
 		let mut head = self.head;
 		while head.is_some() && needed_bodies.len() < count {
@@ -129,15 +157,6 @@ impl BlockCollection {
 				}
 			}
 		}
-		for h in self.header_ids.values() {
-			if needed_bodies.len() >= count {
-				break;
-			}
-			if !self.downloading_bodies.contains(h) {
-				needed_bodies.push(h.clone());
-				self.downloading_bodies.insert(h.clone());
-			}
-		}
 		needed_bodies
 	}
 
@@ -157,6 +176,18 @@ impl BlockCollection {
 		download.map(|h| (h, count))
 	}
 
+	/// Returns the head of the lowest subchain, which is imported next, if its headers require a download.
+	/// The returned head is marked as being downloaded.
+	pub fn needed_lowest_headers(&mut self, count: usize, ignore_downloading: bool) -> Option<(H256, usize)> {
+		match self.heads.first().cloned() {
+			Some(h) if ignore_downloading || !self.downloading_headers.contains(&h) => {
+				self.downloading_headers.insert(h.clone());
+				Some((h, count))
+			},
+			_ => None,
+		}
+	}
+
 	/// Unmark a header as being downloaded.
 	pub fn clear_header_download(&mut self, hash: &H256) {
 		self.downloading_headers.remove(hash);
@@ -202,7 +233,11 @@ impl BlockCollection {
 			}
 		}
 		for h in hashes {
-			self.blocks.remove(&h);
+			if let Some(block) = self.blocks.remove(&h) {
+				if self.header_ids.get(&block.header_id) == Some(&h) {
+					self.header_ids.remove(&block.header_id);
+				}
+			}
 		}
 		trace!("Drained {} blocks, new head :{:?}", drained.len(), self.head);
 		drained
@@ -222,7 +257,7 @@ impl BlockCollection {
 	/// Return heap size.
 	pub fn heap_size(&self) -> usize {
 		//TODO: other collections
-		self.blocks.heap_size_of_children()
+		self.blocks.heap_size_of_children() + self.header_ids.heap_size_of_children()
 	}
 
 	/// Check if given block hash is marked as being downloaded.
@@ -230,34 +265,36 @@ impl BlockCollection {
 		self.downloading_headers.contains(hash) || self.downloading_bodies.contains(hash)
 	}
 
-	fn insert_body(&mut self, b: Bytes) -> Result<(), NetworkError> {
-		let body = UntrustedRlp::new(&b);
-		let tx = try!(body.at(0));
-		let tx_root = ordered_trie_root(tx.iter().map(|r| r.as_raw().to_vec()).collect()); //TODO: get rid of vectors here
-		let uncles = try!(body.at(1)).as_raw().sha3();
-		let header_id = HeaderId {
-			transactions_root: tx_root,
-			uncles: uncles
-		};
-		match self.header_ids.get(&header_id).cloned() {
-			Some(h) => {
-				self.header_ids.remove(&header_id);
-				self.downloading_bodies.remove(&h);
-				match self.blocks.get_mut(&h) {
-					Some(ref mut block) => {
-						trace!(target: "sync", "Got body {}", h);
-						block.body = Some(body.as_raw().to_vec());
-						Ok(())
-					},
-					None => {
-						warn!("Got body with no header {}", h);
-						Err(NetworkError::BadProtocol)
-					}
-				}
+	fn insert_body(&mut self, b: Bytes, requested: &[H256]) -> Result<H256, BodyMatchError> {
+		let header_id = {
+			let body = UntrustedRlp::new(&b);
+			let tx = try!(body.at(0).map_err(|_| BodyMatchError::Malformed));
+			let tx_root = ordered_trie_root(tx.iter().map(|r| r.as_raw().to_vec()).collect()); //TODO: get rid of vectors here
+			let uncles = try!(body.at(1).map_err(|_| BodyMatchError::Malformed)).as_raw().sha3();
+			HeaderId {
+				transactions_root: tx_root,
+				uncles: uncles
 			}
+		};
+		let h = match self.header_ids.get(&header_id) {
+			Some(h) => h.clone(),
 			None => {
 				trace!(target: "sync", "Ignored unknown/stale block body");
-				Err(NetworkError::BadProtocol)
+				return Err(BodyMatchError::NoMatchingHeader);
+			}
+		};
+		match self.blocks.get_mut(&h) {
+			Some(ref block) if block.body.is_some() => Err(BodyMatchError::Duplicate(h)),
+			Some(_) if !requested.contains(&h) => Err(BodyMatchError::Unsolicited(h)),
+			Some(block) => {
+				trace!(target: "sync", "Got body {}", h);
+				block.body = Some(b);
+				self.downloading_bodies.remove(&h);
+				Ok(h)
+			},
+			None => {
+				warn!("Got body with no header {}", h);
+				Err(BodyMatchError::NoMatchingHeader)
 			}
 		}
 	}
@@ -276,14 +313,15 @@ impl BlockCollection {
 			_ => ()
 		}
 
-		let mut block = SyncBlock {
-			header: header,
-			body: None,
-		};
 		let header_id = HeaderId {
 			transactions_root: info.transactions_root().clone(),
 			uncles: info.uncles_hash().clone(),
 		};
+		let mut block = SyncBlock {
+			header: header,
+			body: None,
+			header_id: header_id.clone(),
+		};
 		if header_id.transactions_root == sha3::SHA3_NULL_RLP && header_id.uncles == sha3::SHA3_EMPTY_LIST_RLP {
 			// empty body, just mark as downloaded
 			let mut body_stream = RlpStream::new_list(2);
@@ -333,7 +371,7 @@ impl BlockCollection {
 
 #[cfg(test)]
 mod test {
-	use super::BlockCollection;
+	use super::{BlockCollection, BodyMatchError};
 	use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockID, BlockChainClient};
 	use ethcore::views::HeaderView;
 	use ethcore::header::BlockNumber;
@@ -438,6 +476,33 @@ mod test {
 		assert_eq!(hashes[21], bc.heads[0]);
 	}
 
+	#[test]
+	fn needs_lowest_subchain_only() {
+		let mut bc = BlockCollection::new();
+		let client = TestBlockChainClient::new();
+		let nblocks = 200;
+		client.add_blocks(nblocks, EachBlockWith::Transaction);
+		let blocks: Vec<_> = (0 .. nblocks).map(|i| (&client as &BlockChainClient).block(BlockID::Number(i as BlockNumber)).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| Rlp::new(b).at(0).as_raw().to_vec()).collect();
+		let hashes: Vec<_> = headers.iter().map(|h| HeaderView::new(h).sha3()).collect();
+		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(h.clone()) } else { None }).collect();
+		bc.reset_to(heads);
+		bc.insert_headers(headers[0..6].to_vec());
+		bc.insert_headers(headers[20..26].to_vec());
+
+		assert_eq!(&bc.needed_lowest_bodies(128)[..], &hashes[1..6]);
+		assert!(bc.needed_lowest_bodies(128).is_empty());
+		assert_eq!(bc.needed_lowest_headers(128, false), Some((hashes[5], 128)));
+		assert_eq!(bc.needed_lowest_headers(128, false), None);
+		// the other subchain is still there for the unrestricted requests
+		let mut other_bodies = bc.needed_bodies(128, false);
+		other_bodies.sort();
+		let mut expected = hashes[20..26].to_vec();
+		expected.sort();
+		assert_eq!(other_bodies, expected);
+		assert_eq!(bc.needed_headers(128, false), Some((hashes[25], 128)));
+	}
+
 	#[test]
 	fn insert_headers_no_gap() {
 		let mut bc = BlockCollection::new();
@@ -456,5 +521,53 @@ mod test {
 		bc.insert_headers(headers[0..1].to_vec());
 		assert_eq!(bc.drain().len(), 2);
 	}
-}
 
+	#[test]
+	fn insert_bodies_reports_each_body() {
+		let mut bc = BlockCollection::new();
+		let client = TestBlockChainClient::new();
+		let nblocks = 10;
+		client.add_blocks(nblocks, EachBlockWith::Uncle);
+		let blocks: Vec<_> = (0 .. nblocks).map(|i| (&client as &BlockChainClient).block(BlockID::Number(i as BlockNumber)).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| Rlp::new(b).at(0).as_raw().to_vec()).collect();
+		let hashes: Vec<_> = headers.iter().map(|h| HeaderView::new(h).sha3()).collect();
+		let bodies: Vec<_> = blocks.iter().map(|b| {
+			let b = Rlp::new(b);
+			let mut body = RlpStream::new_list(2);
+			body.append_raw(b.at(1).as_raw(), 1);
+			body.append_raw(b.at(2).as_raw(), 1);
+			body.out()
+		}).collect();
+		bc.reset_to(vec![hashes[0].clone()]);
+		bc.insert_headers(headers[0..4].to_vec());
+
+		let requested = bc.needed_bodies(2, false);
+		assert_eq!(requested, vec![hashes[1], hashes[2]]);
+		let results = bc.insert_bodies(vec![
+			bodies[1].clone(),
+			bodies[1].clone(),
+			bodies[3].clone(),
+			bodies[5].clone(),
+			vec![0x80],
+		], &requested);
+		assert_eq!(results, vec![
+			Ok(hashes[1]),
+			Err(BodyMatchError::Duplicate(hashes[1])),
+			Err(BodyMatchError::Unsolicited(hashes[3])),
+			Err(BodyMatchError::NoMatchingHeader),
+			Err(BodyMatchError::Malformed),
+		]);
+
+		// only the missing bodies are requested again.
+		for h in &requested {
+			bc.clear_body_download(h);
+		}
+		let requested = bc.needed_bodies(10, false);
+		assert_eq!(requested, vec![hashes[2], hashes[3]]);
+
+		let results = bc.insert_bodies(vec![bodies[2].clone(), bodies[3].clone()], &requested);
+		assert_eq!(results, vec![Ok(hashes[2]), Ok(hashes[3])]);
+		assert_eq!(&bc.drain()[..], &blocks[0..4]);
+		assert!(bc.header_ids.is_empty());
+	}
+}
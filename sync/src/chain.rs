@@ -99,13 +99,13 @@ use ethcore::block::Block;
 use ethcore::snapshot::{ManifestData, RestorationStatus};
 use sync_io::SyncIo;
 use time;
-use super::SyncConfig;
-use blocks::BlockCollection;
+use super::{SyncConfig, BlockPropagation};
+use blocks::{BlockCollection, BodyMatchError};
+use import_fault::{ImportFault, import_fault, clock_drift};
 use snapshot::{Snapshot, ChunkType};
+use chunk_requests::ChunkRequests;
 use rand::{thread_rng, Rng};
 
-known_heap_size!(0, PeerInfo);
-
 type PacketDecodeError = DecoderError;
 
 const PROTOCOL_VERSION: u8 = 64u8;
@@ -120,10 +120,18 @@ const MIN_PEERS_PROPAGATION: usize = 4;
 const MAX_PEERS_PROPAGATION: usize = 128;
 const MAX_PEER_LAG_PROPAGATION: BlockNumber = 20;
 const SUBCHAIN_SIZE: usize = 256;
+/// Number of blocks skipped between subchain heads requested in `ChainHead` state.
+const SUBCHAIN_HEADS_SKIP: usize = MAX_HEADERS_TO_REQUEST - 2;
+/// Largest tolerated difference between the spacing of subchain heads returned by a peer and the one requested.
+const MAX_STRIDE_OFFSET: i64 = 1;
 const MAX_ROUND_PARENTS: usize = 32;
 const MAX_NEW_HASHES: usize = 64;
 const MAX_TX_TO_IMPORT: usize = 512;
 const MAX_NEW_BLOCK_AGE: BlockNumber = 20;
+// Maximal number of descendants of banned blocks to remember.
+const MAX_BANNED_DESCENDANTS: usize = 1024;
+// Maximal number of downloaded blocks to remember the supplying peer of.
+const MAX_BLOCK_PEERS: usize = 4096;
 
 const STATUS_PACKET: u8 = 0x00;
 const NEW_BLOCK_HASHES_PACKET: u8 = 0x01;
@@ -148,6 +156,11 @@ const BODIES_TIMEOUT_SEC: f64 = 5f64;
 const FORK_HEADER_TIMEOUT_SEC: f64 = 3f64;
 const SNAPSHOT_MANIFEST_TIMEOUT_SEC: f64 = 3f64;
 const SNAPSHOT_DATA_TIMEOUT_SEC: f64 = 10f64;
+// Fraction of the timeout after which an unanswered request is logged as stale.
+const STALE_REQUEST_RATIO: f64 = 0.5;
+
+const IMPORT_RETRY_DELAY_SEC: f64 = 5f64;
+const MAX_IMPORT_RETRIES: usize = 3;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -195,6 +208,8 @@ pub struct SyncStatus {
 	pub num_active_peers: usize,
 	/// Heap memory used in bytes.
 	pub mem_used: usize,
+	/// Heap memory limit in bytes, if any.
+	pub max_mem_use: Option<usize>,
 	/// Snapshot chunks
 	pub num_snapshot_chunks: usize,
 	/// Snapshot chunks downloaded
@@ -217,9 +232,9 @@ impl SyncStatus {
 	}
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 /// Peer data type requested
-enum PeerAsking {
+pub enum PeerAsking {
 	Nothing,
 	ForkHeader,
 	BlockHeaders,
@@ -229,6 +244,61 @@ enum PeerAsking {
 	SnapshotData,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+/// Request currently outstanding to a peer.
+pub struct PeerRequest {
+	/// Type of data being requested.
+	pub asking: PeerAsking,
+	/// Hashes of the block bodies or the subchain head being downloaded.
+	pub blocks: Vec<H256>,
+	/// Requested header hash if requesting block headers by hash.
+	pub hash: Option<H256>,
+	/// Requested snapshot chunk hash if any.
+	pub snapshot_data: Option<H256>,
+	/// Request timestamp.
+	pub time: f64,
+}
+
+impl PeerRequest {
+	fn new() -> Self {
+		PeerRequest {
+			asking: PeerAsking::Nothing,
+			blocks: Vec::new(),
+			hash: None,
+			snapshot_data: None,
+			time: 0f64,
+		}
+	}
+
+	/// Whether there is a request outstanding.
+	pub fn is_pending(&self) -> bool {
+		self.asking != PeerAsking::Nothing
+	}
+
+	/// Seconds since the request was sent.
+	pub fn age(&self, now: f64) -> f64 {
+		now - self.time
+	}
+
+	/// Seconds after which the request is considered lost.
+	fn timeout(&self) -> Option<f64> {
+		match self.asking {
+			PeerAsking::BlockHeaders | PeerAsking::Heads => Some(HEADERS_TIMEOUT_SEC),
+			PeerAsking::BlockBodies => Some(BODIES_TIMEOUT_SEC),
+			PeerAsking::ForkHeader => Some(FORK_HEADER_TIMEOUT_SEC),
+			PeerAsking::SnapshotManifest => Some(SNAPSHOT_MANIFEST_TIMEOUT_SEC),
+			PeerAsking::SnapshotData => Some(SNAPSHOT_DATA_TIMEOUT_SEC),
+			PeerAsking::Nothing => None,
+		}
+	}
+}
+
+impl HeapSizeOf for PeerRequest {
+	fn heap_size_of_children(&self) -> usize {
+		self.blocks.heap_size_of_children()
+	}
+}
+
 #[derive(Clone, Eq, PartialEq)]
 enum ForkConfirmation {
 	/// Fork block confirmation pending.
@@ -254,16 +324,8 @@ struct PeerInfo {
 	latest_number: Option<BlockNumber>,
 	/// Peer total difficulty if known
 	difficulty: Option<U256>,
-	/// Type of data currenty being requested from peer.
-	asking: PeerAsking,
-	/// A set of block numbers being requested
-	asking_blocks: Vec<H256>,
-	/// Holds requested header hash if currently requesting block header by hash
-	asking_hash: Option<H256>,
-	/// Holds requested snapshot chunk hash if any.
-	asking_snapshot_data: Option<H256>,
-	/// Request timestamp
-	ask_time: f64,
+	/// Request currently outstanding to the peer.
+	request: PeerRequest,
 	/// Holds a set of transactions recently sent to this peer to avoid spamming.
 	last_sent_transactions: HashSet<H256>,
 	/// Pending request is expired and result should be ignored
@@ -274,6 +336,9 @@ struct PeerInfo {
 	snapshot_hash: Option<H256>,
 	/// Best snapshot block number
 	snapshot_number: Option<BlockNumber>,
+	/// Difference between the spacing of the subchain heads this peer returned and the
+	/// spacing requested. Non-zero for peers which interpret the skip parameter off-by-one.
+	stride_offset: i64,
 }
 
 impl PeerInfo {
@@ -284,6 +349,25 @@ impl PeerInfo {
 	fn is_allowed(&self) -> bool {
 		self.confirmation != ForkConfirmation::Unconfirmed && !self.expired
 	}
+
+	/// Whether the peer has a better chain than ours, given our total difficulty and best block number.
+	/// Peers announcing blocks by hash only don't tell us their new total difficulty, so to tolerate
+	/// the lag a peer which announced a block above our best one is considered ahead as well.
+	/// Peers of unknown difficulty are assumed to be ahead.
+	fn is_ahead(&self, difficulty: &U256, best_block: BlockNumber) -> bool {
+		self.difficulty.map_or(true, |pd| pd > *difficulty) || self.latest_number.map_or(false, |n| n > best_block)
+	}
+
+	/// Skip parameter to send to this peer so that its response is spaced `skip + 1` apart.
+	fn adjusted_skip(&self, skip: usize) -> usize {
+		max(0, skip as i64 - self.stride_offset) as usize
+	}
+}
+
+impl HeapSizeOf for PeerInfo {
+	fn heap_size_of_children(&self) -> usize {
+		self.request.heap_size_of_children()
+	}
 }
 
 /// Blockchain sync handler.
@@ -319,12 +403,59 @@ pub struct ChainSync {
 	network_id: U256,
 	/// Optional fork block to check
 	fork_block: Option<(BlockNumber, H256)>,
+	/// Sealed block propagation policy
+	block_propagation: BlockPropagation,
 	/// Snapshot downloader.
 	snapshot: Snapshot,
+	/// Maximum heap memory to use
+	max_mem_use: Option<usize>,
+	/// Blocks that are never imported, along with their known descendants
+	banned_hashes: HashSet<H256>,
+	/// Banned descendants in `banned_hashes`, oldest first
+	banned_descendants: VecDeque<H256>,
+	/// Peers which supplied downloaded blocks that are not imported yet
+	block_peers: HashMap<H256, PeerId>,
+	/// Blocks are not propagated while syncing further than this behind the highest block.
+	propagation_distance: BlockNumber,
+	/// Blocks which failed to import through no fault of the peers, to be retried later
+	import_retry: Option<ImportRetry>,
+	/// Snapshot chunks which are only downloaded from the given node
+	pinned_chunks: HashMap<H256, NodeId>,
+	/// Switch from block download to warp sync when further behind than this
+	warp_threshold: BlockNumber,
+	/// Number of failed restorations of each snapshot. Kept for the lifetime of the process
+	restoration_failures: HashMap<H256, usize>,
+	/// Snapshots which failed to restore this many times are not synced again
+	max_restoration_failures: usize,
+	/// Maximum number of peers downloading headers at once in `ChainHead` and `Blocks` states
+	header_download_peers: usize,
+	/// Snapshot chunks wanted outside of warp sync
+	chunk_requests: Arc<ChunkRequests>,
+}
+
+/// Downloaded blocks set aside after a failed import.
+struct ImportRetry {
+	/// Blocks in import order, starting with the one that failed
+	blocks: Vec<Bytes>,
+	/// Time of the next attempt
+	at: f64,
+	/// Number of failed attempts so far
+	attempts: usize,
 }
 
 type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
 
+/// Total difficulty of a block with given difficulty on top of a parent with given total difficulty.
+/// Returns `None` if the total difficulty would not increase.
+fn next_total_difficulty(parent_td: &U256, difficulty: &U256) -> Option<U256> {
+	let (td, overflow) = parent_td.overflowing_add(*difficulty);
+	if overflow || td <= *parent_td {
+		None
+	} else {
+		Some(td)
+	}
+}
+
 impl ChainSync {
 	/// Create a new instance of syncing strategy.
 	pub fn new(config: SyncConfig, chain: &BlockChainClient) -> ChainSync {
@@ -345,10 +476,28 @@ impl ChainSync {
 			_max_download_ahead_blocks: max(MAX_HEADERS_TO_REQUEST, config.max_download_ahead_blocks),
 			network_id: config.network_id,
 			fork_block: config.fork_block,
+			block_propagation: config.block_propagation,
 			snapshot: Snapshot::new(),
+			max_mem_use: config.max_mem_use,
+			banned_hashes: config.banned_hashes.into_iter().collect(),
+			banned_descendants: VecDeque::new(),
+			block_peers: HashMap::new(),
+			propagation_distance: config.propagation_distance,
+			import_retry: None,
+			pinned_chunks: config.pinned_chunks,
+			warp_threshold: config.warp_threshold,
+			restoration_failures: HashMap::new(),
+			max_restoration_failures: config.max_restoration_failures,
+			header_download_peers: max(config.header_download_peers, 1),
+			chunk_requests: Arc::new(ChunkRequests::new()),
 		}
 	}
 
+	/// Snapshot chunks to fetch from peers outside of warp sync.
+	pub fn chunk_requests(&self) -> Arc<ChunkRequests> {
+		self.chunk_requests.clone()
+	}
+
 	/// @returns Synchonization status
 	pub fn status(&self) -> SyncStatus {
 		SyncStatus {
@@ -361,14 +510,61 @@ impl ChainSync {
 			blocks_received: if self.last_imported_block > self.starting_block { self.last_imported_block - self.starting_block } else { 0 },
 			blocks_total: match self.highest_block { Some(x) if x > self.starting_block => x - self.starting_block, _ => 0 },
 			num_peers: self.peers.values().filter(|p| p.is_allowed()).count(),
-			num_active_peers: self.peers.values().filter(|p| p.is_allowed() && p.asking != PeerAsking::Nothing).count(),
+			num_active_peers: self.peers.values().filter(|p| p.is_allowed() && p.request.is_pending()).count(),
 			num_snapshot_chunks: self.snapshot.total_chunks(),
 			snapshot_chunks_done: self.snapshot.done_chunks(),
-			mem_used:
-				self.blocks.heap_size()
-				+ self.peers.heap_size_of_children()
-				+ self.round_parents.heap_size_of_children(),
+			mem_used: self.heap_size(),
+			max_mem_use: self.max_mem_use,
+		}
+	}
+
+	/// Heap memory used by downloaded blocks and sync caches.
+	fn heap_size(&self) -> usize {
+		self.blocks.heap_size()
+			+ self.peers.heap_size_of_children()
+			+ self.round_parents.heap_size_of_children()
+	}
+
+	/// Check if the memory limit has been reached.
+	fn is_memory_full(&self) -> bool {
+		self.max_mem_use.map_or(false, |max| self.heap_size() >= max)
+	}
+
+	/// Refuse to import the block with given hash and its descendants.
+	pub fn ban_block(&mut self, hash: H256) {
+		self.banned_hashes.insert(hash);
+	}
+
+	/// Check if the block or its parent is banned. Children of banned blocks are banned as well,
+	/// but only the most recent `MAX_BANNED_DESCENDANTS` of them are remembered.
+	fn is_banned(&mut self, hash: &H256, parent: &H256) -> bool {
+		if self.banned_hashes.contains(hash) {
+			return true;
+		}
+		if !self.banned_hashes.contains(parent) {
+			return false;
+		}
+		if self.banned_descendants.len() == MAX_BANNED_DESCENDANTS {
+			if let Some(oldest) = self.banned_descendants.pop_front() {
+				self.banned_hashes.remove(&oldest);
+			}
 		}
+		self.banned_descendants.push_back(hash.clone());
+		self.banned_hashes.insert(hash.clone());
+		true
+	}
+
+	/// Remember the peer which supplied a downloaded block.
+	fn note_block_peer(&mut self, hash: H256, peer_id: PeerId) {
+		if self.block_peers.len() >= MAX_BLOCK_PEERS && !self.block_peers.contains_key(&hash) {
+			self.block_peers.clear();
+		}
+		self.block_peers.insert(hash, peer_id);
+	}
+
+	/// Requests currently outstanding to each peer.
+	pub fn peers(&self) -> Vec<(PeerId, PeerRequest)> {
+		self.peers.iter().map(|(id, p)| (*id, p.request.clone())).collect()
 	}
 
 	/// Abort all sync activity
@@ -381,16 +577,17 @@ impl ChainSync {
 	/// Reset sync. Clear all downloaded data but keep the queue
 	fn reset(&mut self, io: &mut SyncIo) {
 		self.blocks.clear();
+		self.import_retry = None;
 		self.snapshot.clear();
 		if self.state == SyncState::SnapshotData {
 			debug!(target:"sync", "Aborting snapshot restore");
 			io.snapshot_service().abort_restore();
 		}
 		for (_, ref mut p) in &mut self.peers {
-			p.asking_blocks.clear();
-			p.asking_hash = None;
+			p.request.blocks.clear();
+			p.request.hash = None;
 			// mark any pending requests as expired
-			if p.asking != PeerAsking::Nothing && p.is_allowed() {
+			if p.request.is_pending() && p.is_allowed() {
 				p.expired = true;
 			}
 		}
@@ -403,7 +600,11 @@ impl ChainSync {
 	pub fn restart(&mut self, io: &mut SyncIo) {
 		trace!(target: "sync", "Restarting");
 		self.reset(io);
-		self.start_sync_round(io);
+		if self.any_peer_ahead(io) {
+			self.start_sync_round(io);
+		} else {
+			trace!(target: "sync", "No peer is ahead of us, staying idle");
+		}
 		self.continue_sync(io);
 	}
 
@@ -421,6 +622,38 @@ impl ChainSync {
 		}
 	}
 
+	/// Whether a peer advertising the given snapshot can be warp synced from.
+	fn is_usable_snapshot(&self, number: Option<BlockNumber>, hash: &Option<H256>) -> bool {
+		number.unwrap_or(0) > 0 && hash.as_ref().map_or(false, |h| self.restoration_failures.get(h).map_or(true, |f| *f < self.max_restoration_failures))
+	}
+
+	/// Whether another active peer advertises a snapshot which can be warp synced from.
+	fn other_peer_has_usable_snapshot(&self, peer_id: PeerId) -> bool {
+		self.active_peers.iter()
+			.filter(|id| **id != peer_id)
+			.filter_map(|id| self.peers.get(id))
+			.any(|p| p.can_sync() && self.is_usable_snapshot(p.snapshot_number, &p.snapshot_hash))
+	}
+
+	/// Called when the snapshot service failed to restore the snapshot being synced.
+	fn on_restoration_failed(&mut self, io: &mut SyncIo) {
+		if let Some(hash) = self.snapshot.snapshot_hash() {
+			let failures = {
+				let failures = self.restoration_failures.entry(hash).or_insert(0);
+				*failures += 1;
+				*failures
+			};
+			if failures >= self.max_restoration_failures {
+				warn!(target: "sync", "Snapshot {} failed to restore {} times and will not be synced again. Falling back to full sync unless peers offer another snapshot.", hash, failures);
+			} else {
+				debug!(target: "sync", "Snapshot {} failed to restore, retrying", hash);
+			}
+		}
+		self.state = SyncState::Idle;
+		self.snapshot.clear();
+		self.continue_sync(io);
+	}
+
 	fn start_snapshot_sync(&mut self, io: &mut SyncIo, peer_id: PeerId) {
 		self.snapshot.clear();
 		self.request_snapshot_manifest(io, peer_id);
@@ -446,16 +679,13 @@ impl ChainSync {
 			latest_hash: try!(r.val_at(3)),
 			latest_number: None,
 			genesis: try!(r.val_at(4)),
-			asking: PeerAsking::Nothing,
-			asking_blocks: Vec::new(),
-			asking_hash: None,
-			ask_time: 0f64,
+			request: PeerRequest::new(),
 			last_sent_transactions: HashSet::new(),
 			expired: false,
 			confirmation: if self.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
-			asking_snapshot_data: None,
 			snapshot_hash: if protocol_version == 64 { Some(try!(r.val_at(5))) } else { None },
 			snapshot_number: if protocol_version == 64 { Some(try!(r.val_at(6))) } else { None },
+			stride_offset: 0,
 		};
 
 		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{})", peer_id, peer.protocol_version, peer.network_id, peer.difficulty, peer.latest_hash, peer.genesis);
@@ -495,10 +725,10 @@ impl ChainSync {
 	/// Called by peer once it has new block headers during sync
 	fn on_peer_block_headers(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
 		let confirmed = match self.peers.get_mut(&peer_id) {
-			Some(ref mut peer) if peer.asking == PeerAsking::ForkHeader => {
+			Some(ref mut peer) if peer.request.asking == PeerAsking::ForkHeader => {
 				let item_count = r.item_count();
 				if item_count == 0 || (item_count == 1 && try!(r.at(0)).as_raw().sha3() == self.fork_block.unwrap().1) {
-					peer.asking = PeerAsking::Nothing;
+					peer.request.asking = PeerAsking::Nothing;
 					if item_count == 0 {
 						trace!(target: "sync", "{}: Chain is too short to confirm the block", peer_id);
 						peer.confirmation = ForkConfirmation::TooShort;
@@ -522,7 +752,7 @@ impl ChainSync {
 
 		self.clear_peer_download(peer_id);
 		let expected_asking = if self.state == SyncState::ChainHead { PeerAsking::Heads } else { PeerAsking::BlockHeaders };
-		let expected_hash = self.peers.get(&peer_id).and_then(|p| p.asking_hash);
+		let expected_hash = self.peers.get(&peer_id).and_then(|p| p.request.hash);
 		if !self.reset_peer_asking(peer_id, expected_asking) || expected_hash.is_none() {
 			trace!(target: "sync", "{}: Ignored unexpected headers", peer_id);
 			self.continue_sync(io);
@@ -548,10 +778,12 @@ impl ChainSync {
 
 		let mut headers = Vec::new();
 		let mut hashes = Vec::new();
+		let mut numbers = Vec::new();
 		let mut valid_response = item_count == 0; //empty response is valid
 		for i in 0..item_count {
 			let info: BlockHeader = try!(r.val_at(i));
 			let number = BlockNumber::from(info.number());
+			numbers.push(number);
 			// Check if any of the headers matches the hash we requested
 			if !valid_response {
 				if let Some(expected) = expected_hash {
@@ -567,6 +799,11 @@ impl ChainSync {
 				self.highest_block = Some(number);
 			}
 			let hash = info.hash();
+			if self.is_banned(&hash, info.parent_hash()) {
+				warn!(target: "sync", "Banned header {} ({}) from {}: {}", number, hash, peer_id, io.peer_info(peer_id));
+				io.disable_peer(peer_id);
+				return Ok(());
+			}
 			match io.chain().block_status(BlockID::Hash(hash.clone())) {
 				BlockStatus::InChain | BlockStatus::Queued => {
 					match self.state {
@@ -601,13 +838,33 @@ impl ChainSync {
 		}
 		match self.state {
 			SyncState::ChainHead => {
+				// some peers interpret the skip parameter off-by-one. the heads are still
+				// members of the chain, so take the subchain boundaries from the actual numbers
+				// and remember the difference for future requests. anything further off is
+				// not a misinterpretation and the peer is dropped.
+				let stride = header_stride(&numbers);
+				let offset = stride.map_or(0, |stride| stride as i64 - (SUBCHAIN_HEADS_SKIP + 1) as i64);
+				let stride_offset = self.peers.get(&peer_id).map_or(0, |p| p.stride_offset) + offset;
 				if headers.is_empty() {
 					// peer is not on our chain
 					// track back and try again
 					self.imported_this_round = Some(0);
 					self.start_sync_round(io);
+				} else if numbers.len() > 1 && stride.is_none() {
+					trace!(target: "sync", "{} Disabled for unevenly spaced subchain heads", peer_id);
+					io.disable_peer(peer_id);
+					self.start_sync_round(io);
+				} else if stride_offset.abs() > MAX_STRIDE_OFFSET {
+					trace!(target: "sync", "{} Disabled for subchain heads {:?} blocks apart", peer_id, stride);
+					io.disable_peer(peer_id);
+					self.start_sync_round(io);
 				} else {
-					// TODO: validate heads better. E.g. check that there is enough distance between blocks.
+					if offset != 0 {
+						debug!(target: "sync", "{}: Subchain heads are {:?} blocks apart, requested {}", peer_id, stride, SUBCHAIN_HEADS_SKIP + 1);
+						if let Some(peer) = self.peers.get_mut(&peer_id) {
+							peer.stride_offset = stride_offset;
+						}
+					}
 					trace!(target: "sync", "Received {} subchain heads, proceeding to download", headers.len());
 					self.blocks.reset_to(hashes);
 					self.state = SyncState::Blocks;
@@ -615,6 +872,9 @@ impl ChainSync {
 			},
 			SyncState::Blocks | SyncState::NewBlocks | SyncState::Waiting => {
 				trace!(target: "sync", "Inserted {} headers", headers.len());
+				for hash in hashes {
+					self.note_block_peer(hash, peer_id);
+				}
 				self.blocks.insert_headers(headers);
 			},
 			_ => trace!(target: "sync", "Unexpected headers({}) from  {} ({}), state = {:?}", headers.len(), peer_id, io.peer_info(peer_id), self.state)
@@ -630,6 +890,10 @@ impl ChainSync {
 
 	/// Called by peer once it has new block bodies
 	fn on_peer_block_bodies(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		let requested = match self.peers.get(&peer_id) {
+			Some(peer) if peer.request.asking == PeerAsking::BlockBodies => peer.request.blocks.clone(),
+			_ => Vec::new(),
+		};
 		self.clear_peer_download(peer_id);
 		self.reset_peer_asking(peer_id, PeerAsking::BlockBodies);
 		let item_count = r.item_count();
@@ -649,7 +913,20 @@ impl ChainSync {
 			for i in 0..item_count {
 				bodies.push(try!(r.at(i)).as_raw().to_vec());
 			}
-			if self.blocks.insert_bodies(bodies) != item_count {
+			let results = self.blocks.insert_bodies(bodies, &requested);
+			for hash in results.iter().filter_map(|r| r.as_ref().ok()) {
+				self.note_block_peer(hash.clone(), peer_id);
+			}
+			let matched = results.iter().filter(|r| r.is_ok()).count();
+			if matched < requested.len() {
+				// bodies which were not delivered are no longer marked as downloading and will be requested again.
+				trace!(target: "sync", "{} -> {} of {} requested bodies missing", peer_id, requested.len() - matched, requested.len());
+			}
+			let invalid = results.iter().any(|r| match *r {
+				Ok(_) | Err(BodyMatchError::Duplicate(_)) => false,
+				Err(_) => true,
+			});
+			if invalid {
 				trace!(target: "sync", "Deactivating peer for giving invalid block bodies");
 				self.deactivate_peer(io, peer_id);
 			}
@@ -674,11 +951,20 @@ impl ChainSync {
 		if header.number() > self.highest_block.unwrap_or(0) {
 			self.highest_block = Some(header.number());
 		}
+		let difficulty: U256 = try!(r.val_at(1));
 		let mut unknown = false;
 		{
 			let peer = self.peers.get_mut(&peer_id).unwrap();
 			peer.latest_hash = header.hash();
 			peer.latest_number = Some(header.number());
+			if peer.difficulty.map_or(true, |pd| difficulty > pd) {
+				peer.difficulty = Some(difficulty);
+			}
+		}
+		if self.is_banned(&h, header.parent_hash()) {
+			warn!(target: "sync", "Banned new block {:?} from {}: {}", h, peer_id, io.peer_info(peer_id));
+			io.disable_peer(peer_id);
+			return Ok(());
 		}
 		if self.last_imported_block > header.number() && self.last_imported_block - header.number() > MAX_NEW_BLOCK_AGE {
 			trace!(target: "sync", "Ignored ancient new block {:?}", h);
@@ -698,14 +984,21 @@ impl ChainSync {
 					self.last_imported_hash = header.hash();
 				}
 				trace!(target: "sync", "New block queued {:?} ({})", h, header.number());
+				self.note_block_peer(h.clone(), peer_id);
 			},
 			Err(BlockImportError::Block(BlockError::UnknownParent(p))) => {
 				unknown = true;
 				trace!(target: "sync", "New block with unknown parent ({:?}) {:?}", p, h);
 			},
-			Err(e) => {
-				debug!(target: "sync", "Bad new block {:?} : {:?}", h, e);
-				io.disable_peer(peer_id);
+			Err(e) => match import_fault(&e) {
+				ImportFault::Peer => {
+					debug!(target: "sync", "Bad new block {:?} : {:?}", h, e);
+					let peer_info = io.peer_info(peer_id);
+					io.chain().note_bad_block_peer(&h, peer_info);
+					io.disable_peer(peer_id);
+				},
+				ImportFault::Local => ChainSync::warn_local_import_failure(&h, &e),
+				ImportFault::Transient => trace!(target: "sync", "New block {:?} can't be imported yet: {:?}", h, e),
 			}
 		};
 		if unknown {
@@ -714,14 +1007,7 @@ impl ChainSync {
 			} else {
 				trace!(target: "sync", "New unknown block {:?}", h);
 				//TODO: handle too many unknown blocks
-				let difficulty: U256 = try!(r.val_at(1));
-				if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
-					if peer.difficulty.map_or(true, |pd| difficulty > pd) {
-						//self.state = SyncState::ChainHead;
-						peer.difficulty = Some(difficulty);
-						trace!(target: "sync", "Received block {:?}  with no known parent. Peer needs syncing...", h);
-					}
-				}
+				trace!(target: "sync", "Received block {:?} with no known parent. Peer needs syncing...", h);
 				self.sync_peer(io, peer_id, true);
 			}
 		}
@@ -833,7 +1119,19 @@ impl ChainSync {
 			trace!(target: "sync", "Ignoring snapshot data from unconfirmed peer {}", peer_id);
 			return Ok(());
 		}
+		let wanted = match self.peers.get(&peer_id).and_then(|p| p.request.snapshot_data) {
+			Some(hash) if self.chunk_requests.is_asked(&hash, peer_id) => Some(hash),
+			_ => None,
+		};
 		self.clear_peer_download(peer_id);
+		if let Some(hash) = wanted {
+			if self.reset_peer_asking(peer_id, PeerAsking::SnapshotData) {
+				self.on_wanted_chunk(peer_id, &hash, r);
+			}
+			self.sync_peer(io, peer_id, false);
+			self.continue_sync(io);
+			return Ok(());
+		}
 		if !self.reset_peer_asking(peer_id, PeerAsking::SnapshotData) || self.state != SyncState::SnapshotData {
 			trace!(target: "sync", "{}: Ignored unexpected snapshot data", peer_id);
 			self.continue_sync(io);
@@ -842,7 +1140,12 @@ impl ChainSync {
 
 		// check service status
 		match io.snapshot_service().status() {
-			RestorationStatus::Inactive | RestorationStatus::Failed => {
+			RestorationStatus::Failed => {
+				trace!(target: "sync", "{}: Snapshot restoration failed", peer_id);
+				self.on_restoration_failed(io);
+				return Ok(());
+			},
+			RestorationStatus::Inactive => {
 				trace!(target: "sync", "{}: Snapshot restoration aborted", peer_id);
 				self.state = SyncState::Idle;
 				self.snapshot.clear();
@@ -855,6 +1158,12 @@ impl ChainSync {
 		}
 
 		let snapshot_data: Bytes = try!(r.val_at(0));
+		let hash = snapshot_data.sha3();
+		if !self.is_chunk_source(io, peer_id, &hash) {
+			trace!(target: "sync", "{}: Ignored snapshot chunk {:?} pinned to another node", peer_id, hash);
+			self.continue_sync(io);
+			return Ok(());
+		}
 		match self.snapshot.validate_chunk(&snapshot_data) {
 			Ok(ChunkType::Block(hash)) => {
 				trace!(target: "sync", "{}: Processing block chunk", peer_id);
@@ -883,6 +1192,23 @@ impl ChainSync {
 		Ok(())
 	}
 
+	/// Called when a peer responds to a request for a chunk wanted outside of warp sync.
+	/// Peers which don't have the chunk respond with an empty list.
+	fn on_wanted_chunk(&mut self, peer_id: PeerId, hash: &H256, r: &UntrustedRlp) {
+		let chunk: Option<Bytes> = match r.item_count() {
+			0 => None,
+			_ => r.val_at(0).ok(),
+		};
+		match chunk {
+			Some(chunk) => if self.chunk_requests.deliver(hash, chunk) {
+				trace!(target: "sync", "{}: Got wanted snapshot chunk {:?}", peer_id, hash);
+			} else {
+				trace!(target: "sync", "{}: Got bad wanted snapshot chunk {:?}", peer_id, hash);
+			},
+			None => trace!(target: "sync", "{}: Peer doesn't have wanted snapshot chunk {:?}", peer_id, hash),
+		}
+	}
+
 	/// Called by peer when it is disconnecting
 	pub fn on_peer_aborting(&mut self, io: &mut SyncIo, peer: PeerId) {
 		trace!(target: "sync", "== Disconnecting {}: {}", peer, io.peer_info(peer));
@@ -904,6 +1230,15 @@ impl ChainSync {
 		}
 	}
 
+	/// Whether any active peer has a better chain than ours.
+	fn any_peer_ahead(&self, io: &SyncIo) -> bool {
+		let chain_info = io.chain().chain_info();
+		let difficulty = max(self.syncing_difficulty, chain_info.pending_total_difficulty);
+		self.active_peers.iter()
+			.filter_map(|id| self.peers.get(id))
+			.any(|p| p.can_sync() && p.is_ahead(&difficulty, chain_info.best_block_number))
+	}
+
 	/// Resume downloading
 	fn continue_sync(&mut self, io: &mut SyncIo) {
 		let mut peers: Vec<(PeerId, U256, u32)> = self.peers.iter().filter_map(|(k, p)|
@@ -917,8 +1252,8 @@ impl ChainSync {
 				self.sync_peer(io, p, false);
 			}
 		}
-		if self.state != SyncState::Waiting && self.state != SyncState::SnapshotWaiting
-			&& !self.peers.values().any(|p| p.asking != PeerAsking::Nothing && p.can_sync()) {
+		if self.state != SyncState::Waiting && self.state != SyncState::SnapshotWaiting && self.import_retry.is_none()
+			&& !self.peers.values().any(|p| p.request.is_pending() && p.can_sync()) {
 			self.complete_sync(io);
 		}
 	}
@@ -930,6 +1265,17 @@ impl ChainSync {
 		self.state = SyncState::Idle;
 	}
 
+	/// Number of blocks between the last imported block and the highest block known.
+	pub fn block_gap(&self) -> BlockNumber {
+		self.highest_block.map_or(0, |highest| highest.saturating_sub(self.last_imported_block))
+	}
+
+	/// Whether to abandon block download for a snapshot of the given block.
+	fn should_warp(&self, snapshot_number: BlockNumber) -> bool {
+		self.warp_threshold != 0 && self.block_gap() > self.warp_threshold
+			&& snapshot_number > self.last_imported_block + self.warp_threshold
+	}
+
 	/// Enter waiting state
 	fn pause_sync(&mut self) {
 		trace!(target: "sync", "Block queue full, pausing sync");
@@ -942,9 +1288,11 @@ impl ChainSync {
 			trace!(target: "sync", "Skipping deactivated peer");
 			return;
 		}
-		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash) = {
+		let chain_info = io.chain().chain_info();
+		let syncing_difficulty = max(self.syncing_difficulty, chain_info.pending_total_difficulty);
+		let (peer_latest, peer_ahead, peer_snapshot_number, peer_snapshot_hash) = {
 			let peer = self.peers.get_mut(&peer_id).unwrap();
-			if peer.asking != PeerAsking::Nothing || !peer.can_sync() {
+			if peer.request.is_pending() || !peer.can_sync() {
 				return;
 			}
 			if self.state == SyncState::Waiting {
@@ -955,18 +1303,34 @@ impl ChainSync {
 				trace!(target: "sync", "Waiting for the snapshot restoration");
 				return;
 			}
-			(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned(), peer.snapshot_hash.as_ref().cloned())
+			let ahead = peer.is_ahead(&syncing_difficulty, chain_info.best_block_number);
+			(peer.latest_hash.clone(), ahead, peer.snapshot_number.as_ref().cloned(), peer.snapshot_hash.as_ref().cloned())
 		};
-		let chain_info = io.chain().chain_info();
-		let td = chain_info.pending_total_difficulty;
-		let syncing_difficulty = max(self.syncing_difficulty, td);
 
-		if force || self.state == SyncState::NewBlocks || peer_difficulty.map_or(true, |pd| pd > syncing_difficulty) {
+		// wanted chunks are asked for first, from any peer offering a snapshot.
+		if peer_snapshot_number.unwrap_or(0) > 0 && self.request_wanted_chunk(io, peer_id) {
+			return;
+		}
+
+		// a round against a peer which is not ahead of us can't import anything and only makes us retract.
+		if force || self.state == SyncState::NewBlocks || peer_ahead {
+			let downloading = match self.state {
+				SyncState::Idle | SyncState::ChainHead | SyncState::Blocks => true,
+				_ => false,
+			};
+			if downloading && self.is_usable_snapshot(peer_snapshot_number, &peer_snapshot_hash) && self.should_warp(peer_snapshot_number.unwrap_or(0)) {
+				info!(target: "sync", "{} blocks behind, switching to warp sync", self.block_gap());
+				self.reset(io);
+				self.start_snapshot_sync(io, peer_id);
+				return;
+			}
 			match self.state {
 				SyncState::Idle => {
 					// check if we can start snapshot sync with this peer
-					if peer_snapshot_number.unwrap_or(0) > 0 && chain_info.best_block_number == 0 {
+					if chain_info.best_block_number == 0 && self.is_usable_snapshot(peer_snapshot_number, &peer_snapshot_hash) {
 						self.start_snapshot_sync(io, peer_id);
+					} else if chain_info.best_block_number == 0 && self.other_peer_has_usable_snapshot(peer_id) {
+						trace!(target: "sync", "Leaving {} for a peer with a usable snapshot", peer_id);
 					} else {
 						if self.last_imported_block < chain_info.best_block_number {
 							self.last_imported_block = chain_info.best_block_number;
@@ -978,12 +1342,19 @@ impl ChainSync {
 					}
 				},
 				SyncState::ChainHead => {
-					// Request subchain headers
+					if self.header_downloads() >= self.header_download_peers {
+						trace!(target: "sync", "Not requesting subchain heads from {}, enough peers busy", peer_id);
+						return;
+					}
+					// Every peer is asked for the same heads starting from the last imported block;
+					// the first valid response starts the round and the rest are dropped, so the
+					// limit above only bounds the redundant requests.
 					trace!(target: "sync", "Starting sync with better chain");
 					let last = self.last_imported_hash.clone();
 					// Request MAX_HEADERS_TO_REQUEST - 2 headers apart so that
 					// MAX_HEADERS_TO_REQUEST would include headers for neighbouring subchains
-					self.request_headers_by_hash(io, peer_id, &last, SUBCHAIN_SIZE, MAX_HEADERS_TO_REQUEST - 2, false, PeerAsking::Heads);
+					let skip = self.peers.get(&peer_id).map_or(SUBCHAIN_HEADS_SKIP, |p| p.adjusted_skip(SUBCHAIN_HEADS_SKIP));
+					self.request_headers_by_hash(io, peer_id, &last, SUBCHAIN_SIZE, skip, false, PeerAsking::Heads);
 				},
 				SyncState::Blocks | SyncState::NewBlocks => {
 					if io.chain().block_status(BlockID::Hash(peer_latest)) == BlockStatus::Unknown {
@@ -1040,54 +1411,115 @@ impl ChainSync {
 			return;
 		}
 
+		// at the memory limit only the lowest subchain is downloaded. It is imported next and only
+		// its blocks can drain to free memory, so its headers and bodies are never suspended.
+		let memory_full = self.is_memory_full();
+
 		// check to see if we need to download any block bodies first
-		let needed_bodies = self.blocks.needed_bodies(MAX_BODIES_TO_REQUEST, ignore_others);
+		let needed_bodies = match memory_full {
+			true => self.blocks.needed_lowest_bodies(MAX_BODIES_TO_REQUEST),
+			false => self.blocks.needed_bodies(MAX_BODIES_TO_REQUEST, ignore_others),
+		};
 		if !needed_bodies.is_empty() {
-			replace(&mut self.peers.get_mut(&peer_id).unwrap().asking_blocks, needed_bodies.clone());
+			replace(&mut self.peers.get_mut(&peer_id).unwrap().request.blocks, needed_bodies.clone());
 			self.request_bodies(io, peer_id, needed_bodies);
 			return;
 		}
 
-		// find subchain to download
-		if let Some((h, count)) = self.blocks.needed_headers(MAX_HEADERS_TO_REQUEST, ignore_others) {
-			replace(&mut self.peers.get_mut(&peer_id).unwrap().asking_blocks, vec![h.clone()]);
-			self.request_headers_by_hash(io, peer_id, &h, count, 0, false, PeerAsking::BlockHeaders);
+		if self.state == SyncState::Blocks && self.header_downloads() >= self.header_download_peers {
+			trace!(target: "sync", "Not requesting headers from {}, enough peers busy", peer_id);
+			return;
 		}
+
+		// find subchain to download, other peers are given different ones
+		let needed_headers = match memory_full {
+			true => self.blocks.needed_lowest_headers(MAX_HEADERS_TO_REQUEST, ignore_others),
+			false => self.blocks.needed_headers(MAX_HEADERS_TO_REQUEST, ignore_others),
+		};
+		match needed_headers {
+			Some((h, count)) => {
+				replace(&mut self.peers.get_mut(&peer_id).unwrap().request.blocks, vec![h.clone()]);
+				self.request_headers_by_hash(io, peer_id, &h, count, 0, false, PeerAsking::BlockHeaders);
+			},
+			None if memory_full => trace!(target: "sync", "Sync memory limit reached, not requesting blocks from {}", peer_id),
+			None => (),
+		}
+	}
+
+	/// Number of peers with block headers requested.
+	fn header_downloads(&self) -> usize {
+		self.peers.values().filter(|p| p.request.asking == PeerAsking::Heads || p.request.asking == PeerAsking::BlockHeaders).count()
 	}
 
 	/// Find some headers or blocks to download for a peer.
 	fn request_snapshot_data(&mut self, io: &mut SyncIo, peer_id: PeerId) {
 		self.clear_peer_download(peer_id);
 		// find chunk data to download
-		if let Some(hash) = self.snapshot.needed_chunk() {
-			self.peers.get_mut(&peer_id).unwrap().asking_snapshot_data = Some(hash.clone());
+		let node_id = io.peer_node_id(peer_id);
+		let pinned_chunks = &self.pinned_chunks;
+		let chunk = self.snapshot.needed_chunk_where(|hash| pinned_chunks.get(hash).map_or(true, |pinned| Some(pinned) == node_id.as_ref()));
+		if let Some(hash) = chunk {
+			self.peers.get_mut(&peer_id).unwrap().request.snapshot_data = Some(hash.clone());
 			self.request_snapshot_chunk(io, peer_id, &hash);
 		}
 	}
 
+	/// Request a chunk wanted outside of warp sync from a peer. Returns `false` if there is
+	/// no chunk left to ask the peer for.
+	fn request_wanted_chunk(&mut self, io: &mut SyncIo, peer_id: PeerId) -> bool {
+		match self.chunk_requests.next_for(peer_id) {
+			Some(hash) => {
+				self.peers.get_mut(&peer_id).unwrap().request.snapshot_data = Some(hash.clone());
+				self.request_snapshot_chunk(io, peer_id, &hash);
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Give chunks wanted outside of warp sync to idle peers offering a snapshot.
+	fn request_wanted_chunks(&mut self, io: &mut SyncIo) {
+		let peers: Vec<PeerId> = self.peers.iter()
+			.filter(|&(id, p)| self.active_peers.contains(id) && p.can_sync() && !p.request.is_pending() && p.snapshot_number.unwrap_or(0) > 0)
+			.map(|(id, _)| *id)
+			.collect();
+		for peer_id in peers {
+			self.request_wanted_chunk(io, peer_id);
+		}
+	}
+
+	/// Whether the snapshot chunk may be downloaded from the peer.
+	fn is_chunk_source(&self, io: &SyncIo, peer_id: PeerId, hash: &H256) -> bool {
+		match self.pinned_chunks.get(hash) {
+			Some(pinned) => io.peer_node_id(peer_id).as_ref() == Some(pinned),
+			None => true,
+		}
+	}
+
 	/// Clear all blocks/headers marked as being downloaded by a peer.
 	fn clear_peer_download(&mut self, peer_id: PeerId) {
-		let peer = self.peers.get_mut(&peer_id).unwrap();
-		match peer.asking {
+		let request = &mut self.peers.get_mut(&peer_id).unwrap().request;
+		match request.asking {
 			PeerAsking::BlockHeaders | PeerAsking::Heads => {
-				for b in &peer.asking_blocks {
+				for b in &request.blocks {
 					self.blocks.clear_header_download(b);
 				}
 			},
 			PeerAsking::BlockBodies => {
-				for b in &peer.asking_blocks {
+				for b in &request.blocks {
 					self.blocks.clear_body_download(b);
 				}
 			},
 			PeerAsking::SnapshotData => {
-				if let Some(hash) = peer.asking_snapshot_data {
+				if let Some(hash) = request.snapshot_data {
 					self.snapshot.clear_chunk_download(&hash);
+					self.chunk_requests.clear_ask(&hash, peer_id);
 				}
 			},
 			_ => (),
 		}
-		peer.asking_blocks.clear();
-		peer.asking_snapshot_data = None;
+		request.blocks.clear();
+		request.snapshot_data = None;
 	}
 
 	fn block_imported(&mut self, hash: &H256, number: BlockNumber, parent: &H256) {
@@ -1101,14 +1533,26 @@ impl ChainSync {
 
 	/// Checks if there are blocks fully downloaded that can be imported into the blockchain and does the import.
 	fn collect_blocks(&mut self, io: &mut SyncIo) {
+		let now = time::precise_time_s();
+		if self.import_retry.as_ref().map_or(false, |r| r.at > now) {
+			trace!(target: "sync", "Waiting to retry block import");
+			return;
+		}
+		let (mut blocks, mut attempts) = match self.import_retry.take() {
+			Some(retry) => (retry.blocks, retry.attempts),
+			None => (Vec::new(), 0),
+		};
+		blocks.extend(self.blocks.drain());
+
 		let mut restart = false;
 		let mut imported = HashSet::new();
-		let blocks = self.blocks.drain();
+		let mut total_difficulties: HashMap<H256, U256> = HashMap::new();
 		let count = blocks.len();
-		for block in blocks {
-			let (h, number, parent) = {
+		let mut blocks = blocks.into_iter();
+		while let Some(block) = blocks.next() {
+			let (h, number, parent, difficulty) = {
 				let header = BlockView::new(&block).header_view();
-				(header.sha3(), header.number(), header.parent_hash())
+				(header.sha3(), header.number(), header.parent_hash(), header.difficulty())
 			};
 
 			// Perform basic block verification
@@ -1118,17 +1562,45 @@ impl ChainSync {
 				break;
 			}
 
-			match io.chain().import_block(block) {
+			if self.is_banned(&h, &parent) {
+				match self.block_peers.remove(&h) {
+					Some(peer_id) => {
+						warn!(target: "sync", "Banned block {:?} from {}: {}", h, peer_id, io.peer_info(peer_id));
+						io.disable_peer(peer_id);
+					},
+					None => debug!(target: "sync", "Banned block {:?}", h),
+				}
+				restart = true;
+				break;
+			}
+
+			// Check that the total difficulty keeps growing along the collected chain
+			let parent_td = total_difficulties.get(&parent).cloned().or_else(|| io.chain().block_total_difficulty(BlockID::Hash(parent.clone())));
+			if let Some(parent_td) = parent_td {
+				match next_total_difficulty(&parent_td, &difficulty) {
+					Some(td) => { total_difficulties.insert(h.clone(), td); },
+					None => {
+						debug!(target: "sync", "Block {:?} does not increase total difficulty", h);
+						restart = true;
+						break;
+					}
+				}
+			}
+
+			match io.chain().import_block(block.clone()) {
 				Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {
 					trace!(target: "sync", "Block already in chain {:?}", h);
+					self.block_peers.remove(&h);
 					self.block_imported(&h, number, &parent);
 				},
 				Err(BlockImportError::Import(ImportError::AlreadyQueued)) => {
 					trace!(target: "sync", "Block already queued {:?}", h);
+					self.block_peers.remove(&h);
 					self.block_imported(&h, number, &parent);
 				},
 				Ok(_) => {
 					trace!(target: "sync", "Block queued {:?}", h);
+					attempts = 0;
 					imported.insert(h.clone());
 					self.block_imported(&h, number, &parent);
 				},
@@ -1137,8 +1609,33 @@ impl ChainSync {
 					break;
 				},
 				Err(e) => {
-					debug!(target: "sync", "Bad block {:?} : {:?}", h, e);
-					restart = true;
+					let fault = import_fault(&e);
+					if fault == ImportFault::Peer {
+						debug!(target: "sync", "Bad block {:?} : {:?}", h, e);
+						if let Some(peer_id) = self.block_peers.remove(&h) {
+							let peer_info = io.peer_info(peer_id);
+							io.chain().note_bad_block_peer(&h, peer_info);
+							io.disable_peer(peer_id);
+						}
+						restart = true;
+						break;
+					}
+					if attempts >= MAX_IMPORT_RETRIES {
+						debug!(target: "sync", "Failed to import block {:?} : {:?}", h, e);
+						restart = true;
+						break;
+					}
+					match fault {
+						ImportFault::Local => ChainSync::warn_local_import_failure(&h, &e),
+						_ => debug!(target: "sync", "Block {:?} can't be imported yet, retrying in {}s: {:?}", h, IMPORT_RETRY_DELAY_SEC, e),
+					}
+					let mut remaining = vec![block];
+					remaining.extend(blocks);
+					self.import_retry = Some(ImportRetry {
+						blocks: remaining,
+						at: now + IMPORT_RETRY_DELAY_SEC,
+						attempts: attempts + 1,
+					});
 					break;
 				}
 			}
@@ -1151,13 +1648,24 @@ impl ChainSync {
 			return;
 		}
 
-		if self.blocks.is_empty() {
+		if self.blocks.is_empty() && self.import_retry.is_none() {
 			// complete sync round
 			trace!(target: "sync", "Sync round complete");
 			self.restart(io);
 		}
 	}
 
+	/// Report a block import which failed because of a problem on our side.
+	fn warn_local_import_failure(hash: &H256, e: &BlockImportError) {
+		match *e {
+			BlockImportError::Block(ref e) if clock_drift(e).is_some() => {
+				warn!(target: "sync", "Block {:?} is {}s ahead of the local clock. Please check that the system clock is correct.",
+					hash, clock_drift(e).expect("checked by the guard; qed"));
+			},
+			_ => warn!(target: "sync", "Failed to import block {:?}: {:?}", hash, e),
+		}
+	}
+
 	/// Request headers from a peer by block hash
 	#[cfg_attr(feature="dev", allow(too_many_arguments))]
 	fn request_headers_by_hash(&mut self, sync: &mut SyncIo, peer_id: PeerId, h: &H256, count: usize, skip: usize, reverse: bool, asking: PeerAsking) {
@@ -1170,7 +1678,7 @@ impl ChainSync {
 		self.send_request(sync, peer_id, asking, GET_BLOCK_HEADERS_PACKET, rlp.out());
 		self.peers.get_mut(&peer_id)
 			.expect("peer_id may originate either from on_packet, where it is already validated or from enumerating self.peers. qed")
-			.asking_hash = Some(h.clone());
+			.request.hash = Some(h.clone());
 	}
 
 	/// Request headers from a peer by block number
@@ -1214,13 +1722,13 @@ impl ChainSync {
 	fn reset_peer_asking(&mut self, peer_id: PeerId, asking: PeerAsking) -> bool {
 		let peer = self.peers.get_mut(&peer_id).unwrap();
 		peer.expired = false;
-		if peer.asking != asking {
-			trace!(target:"sync", "Asking {:?} while expected {:?}", peer.asking, asking);
-			peer.asking = PeerAsking::Nothing;
+		if peer.request.asking != asking {
+			trace!(target:"sync", "Asking {:?} while expected {:?}", peer.request.asking, asking);
+			peer.request.asking = PeerAsking::Nothing;
 			false
 		}
 		else {
-			peer.asking = PeerAsking::Nothing;
+			peer.request.asking = PeerAsking::Nothing;
 			true
 		}
 	}
@@ -1228,11 +1736,11 @@ impl ChainSync {
 	/// Generic request sender
 	fn send_request(&mut self, sync: &mut SyncIo, peer_id: PeerId, asking: PeerAsking,  packet_id: PacketId, packet: Bytes) {
 		let peer = self.peers.get_mut(&peer_id).unwrap();
-		if peer.asking != PeerAsking::Nothing {
-			warn!(target:"sync", "Asking {:?} while requesting {:?}", peer.asking, asking);
+		if peer.request.is_pending() {
+			warn!(target:"sync", "Asking {:?} while requesting {:?}", peer.request.asking, asking);
 		}
-		peer.asking = asking;
-		peer.ask_time = time::precise_time_s();
+		peer.request.asking = asking;
+		peer.request.time = time::precise_time_s();
 		if let Err(e) = sync.send(peer_id, packet_id, packet) {
 			debug!(target:"sync", "Error sending request: {:?}", e);
 			sync.disable_peer(peer_id);
@@ -1470,7 +1978,8 @@ impl ChainSync {
 		match response {
 			Err(e) => Err(e),
 			Ok(Some((packet_id, rlp_stream))) => {
-				io.respond(packet_id, rlp_stream.out()).unwrap_or_else(
+				// requests may be processed by the sync worker, which has no session to respond on
+				io.send(peer, packet_id, rlp_stream.out()).unwrap_or_else(
 					|e| debug!(target: "sync", "{:?}", error_func(e)));
 				Ok(())
 			}
@@ -1478,6 +1987,15 @@ impl ChainSync {
 		}
 	}
 
+	/// Whether the packet answers one of our requests. Such packets never need a response.
+	pub fn is_response_packet(packet_id: u8) -> bool {
+		match packet_id {
+			BLOCK_HEADERS_PACKET | BLOCK_BODIES_PACKET | NODE_DATA_PACKET | RECEIPTS_PACKET
+				| SNAPSHOT_MANIFEST_PACKET | SNAPSHOT_DATA_PACKET => true,
+			_ => false,
+		}
+	}
+
 	/// Dispatch incoming requests and responses
 	pub fn dispatch_packet(sync: &RwLock<ChainSync>, io: &mut SyncIo, peer: PeerId, packet_id: u8, data: &[u8]) {
 		let rlp = UntrustedRlp::new(data);
@@ -1541,23 +2059,22 @@ impl ChainSync {
 		})
 	}
 
-	#[cfg_attr(feature="dev", allow(match_same_arms))]
 	pub fn maintain_peers(&mut self, io: &mut SyncIo) {
 		let tick = time::precise_time_s();
 		let mut aborting = Vec::new();
 		for (peer_id, peer) in &self.peers {
-			let timeout = match peer.asking {
-				PeerAsking::BlockHeaders | PeerAsking::Heads => (tick - peer.ask_time) > HEADERS_TIMEOUT_SEC,
-				PeerAsking::BlockBodies => (tick - peer.ask_time) > BODIES_TIMEOUT_SEC,
-				PeerAsking::Nothing => false,
-				PeerAsking::ForkHeader => (tick - peer.ask_time) > FORK_HEADER_TIMEOUT_SEC,
-				PeerAsking::SnapshotManifest => (tick - peer.ask_time) > SNAPSHOT_MANIFEST_TIMEOUT_SEC,
-				PeerAsking::SnapshotData => (tick - peer.ask_time) > SNAPSHOT_DATA_TIMEOUT_SEC,
+			let request = &peer.request;
+			let timeout = match request.timeout() {
+				Some(timeout) => timeout,
+				None => continue,
 			};
-			if timeout {
+			let age = request.age(tick);
+			if age > timeout {
 				trace!(target:"sync", "Timeout {}", peer_id);
 				io.disconnect_peer(*peer_id);
 				aborting.push(*peer_id);
+			} else if age > timeout * STALE_REQUEST_RATIO {
+				debug!(target:"sync", "Stale request to {}: {:?} {:?} for {:.1}s", peer_id, request.asking, request.hash.or(request.snapshot_data).or(request.blocks.first().cloned()), age);
 			}
 		}
 		for p in aborting {
@@ -1569,9 +2086,15 @@ impl ChainSync {
 		if self.state == SyncState::Waiting && !io.chain().queue_info().is_full() && self.state == SyncState::Waiting {
 			self.state = SyncState::Blocks;
 			self.continue_sync(io);
-		} else if self.state == SyncState::SnapshotWaiting && io.snapshot_service().status() == RestorationStatus::Inactive {
-			self.state = SyncState::Idle;
-			self.continue_sync(io);
+		} else if self.state == SyncState::SnapshotWaiting {
+			match io.snapshot_service().status() {
+				RestorationStatus::Inactive => {
+					self.state = SyncState::Idle;
+					self.continue_sync(io);
+				},
+				RestorationStatus::Failed => self.on_restoration_failed(io),
+				RestorationStatus::Ongoing { .. } => {},
+			}
 		}
 	}
 
@@ -1649,6 +2172,21 @@ impl ChainSync {
 		peers
 	}
 
+	/// splits lagging peers into those receiving a sealed block in full and those only getting its hash
+	fn select_sealed_block_peers(&self, peers: &[(PeerId, BlockNumber)]) -> (Vec<(PeerId, BlockNumber)>, Vec<(PeerId, BlockNumber)>) {
+		match self.block_propagation {
+			BlockPropagation::Full => (peers.to_vec(), Vec::new()),
+			BlockPropagation::Partial(fraction) => {
+				let mut peers = peers.to_vec();
+				let count = (peers.len() as f64 * fraction.max(0f64).min(1f64)).ceil() as usize;
+				let count = min(max(count, 1), peers.len());
+				::rand::thread_rng().shuffle(&mut peers);
+				let announce = peers.split_off(count);
+				(peers, announce)
+			},
+		}
+	}
+
 	/// propagates latest block to lagging peers
 	fn propagate_blocks(&mut self, chain_info: &BlockChainInfo, io: &mut SyncIo, sealed: &[H256], peers: &[(PeerId, BlockNumber)]) -> usize {
 		trace!(target: "sync", "Sending NewBlocks to {:?}", peers);
@@ -1763,9 +2301,20 @@ impl ChainSync {
 		sent
 	}
 
+	/// Whether the initial sync is still far behind the network head. Blocks imported
+	/// meanwhile are old news to peers, so they are not propagated.
+	fn is_catching_up(&self, best_block: BlockNumber) -> bool {
+		match self.state {
+			SyncState::Idle | SyncState::NewBlocks => false,
+			_ => self.highest_block.map_or(false, |highest| highest > best_block + self.propagation_distance),
+		}
+	}
+
 	fn propagate_latest_blocks(&mut self, io: &mut SyncIo, sealed: &[H256]) {
 		let chain_info = io.chain().chain_info();
-		if (((chain_info.best_block_number as i64) - (self.last_sent_block_number as i64)).abs() as BlockNumber) < MAX_PEER_LAG_PROPAGATION {
+		if sealed.is_empty() && self.is_catching_up(chain_info.best_block_number) {
+			trace!(target: "sync", "Not propagating block #{} while syncing", chain_info.best_block_number);
+		} else if (((chain_info.best_block_number as i64) - (self.last_sent_block_number as i64)).abs() as BlockNumber) < MAX_PEER_LAG_PROPAGATION {
 			let mut peers = self.get_lagging_peers(&chain_info, io);
 			if sealed.is_empty() {
 				let hashes = self.propagate_new_hashes(&chain_info, io, &peers);
@@ -1775,9 +2324,10 @@ impl ChainSync {
 					trace!(target: "sync", "Sent latest {} blocks and {} hashes to peers.", blocks, hashes);
 				}
 			} else {
-				self.propagate_blocks(&chain_info, io, sealed, &peers);
-				self.propagate_new_hashes(&chain_info, io, &peers);
-				trace!(target: "sync", "Sent sealed block to all peers");
+				let (full, announce) = self.select_sealed_block_peers(&peers);
+				let blocks = self.propagate_blocks(&chain_info, io, sealed, &full);
+				let hashes = self.propagate_new_hashes(&chain_info, io, &announce);
+				trace!(target: "sync", "Sent sealed block to {} peers and announced it to {} peers", blocks, hashes);
 			};
 		}
 		self.propagate_new_transactions(io);
@@ -1787,13 +2337,29 @@ impl ChainSync {
 	/// Maintain other peers. Send out any new blocks and transactions
 	pub fn maintain_sync(&mut self, io: &mut SyncIo) {
 		self.check_resume(io);
+		self.request_wanted_chunks(io);
+		if self.import_retry.as_ref().map_or(false, |r| r.at <= time::precise_time_s()) {
+			self.collect_blocks(io);
+			self.continue_sync(io);
+		}
 	}
 
 	/// called when block is imported to chain - propagates the blocks and updates transactions sent to peers
-	pub fn chain_new_blocks(&mut self, io: &mut SyncIo, _imported: &[H256], invalid: &[H256], _enacted: &[H256], _retracted: &[H256], sealed: &[H256]) {
+	pub fn chain_new_blocks(&mut self, io: &mut SyncIo, imported: &[H256], invalid: &[H256], _enacted: &[H256], _retracted: &[H256], sealed: &[H256]) {
 		if io.is_chain_queue_empty() {
 			self.propagate_latest_blocks(io, sealed);
 		}
+		for hash in imported {
+			self.block_peers.remove(hash);
+		}
+		for hash in invalid {
+			if let Some(peer_id) = self.block_peers.remove(hash) {
+				let peer_info = io.peer_info(peer_id);
+				warn!(target: "sync", "Invalid block {:?} from {}: {}", hash, peer_id, peer_info);
+				io.chain().note_bad_block_peer(hash, peer_info);
+				io.disable_peer(peer_id);
+			}
+		}
 		if !invalid.is_empty() {
 			trace!(target: "sync", "Bad blocks in the queue, restarting");
 			self.restart_on_bad_block(io);
@@ -1804,18 +2370,31 @@ impl ChainSync {
 	}
 }
 
+/// Distance between consecutive block numbers, if they are ascending and evenly spaced.
+fn header_stride(numbers: &[BlockNumber]) -> Option<BlockNumber> {
+	if numbers.len() < 2 || numbers[1] <= numbers[0] {
+		return None;
+	}
+	let stride = numbers[1] - numbers[0];
+	match numbers.windows(2).all(|w| w[1] > w[0] && w[1] - w[0] == stride) {
+		true => Some(stride),
+		false => None,
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use tests::helpers::*;
 	use tests::snapshot::TestSnapshotService;
 	use super::*;
-	use ::SyncConfig;
+	use ::{SyncConfig, BlockPropagation};
 	use util::*;
 	use rlp::*;
 	use super::{PeerInfo, PeerAsking};
 	use ethcore::views::BlockView;
 	use ethcore::header::*;
 	use ethcore::client::*;
+	use ethcore::bad_block::BadBlock;
 	use ethcore::miner::MinerService;
 
 	fn get_dummy_block(order: u32, parent_hash: H256) -> Bytes {
@@ -1994,30 +2573,276 @@ mod tests {
 		assert_eq!(1, io.queue.len());
 	}
 
+	fn dummy_peer(peer_latest_hash: H256) -> PeerInfo {
+		PeerInfo {
+			protocol_version: 0,
+			genesis: H256::zero(),
+			network_id: U256::zero(),
+			latest_hash: peer_latest_hash,
+			latest_number: None,
+			difficulty: None,
+			request: PeerRequest::new(),
+			last_sent_transactions: HashSet::new(),
+			expired: false,
+			confirmation: super::ForkConfirmation::Confirmed,
+			snapshot_number: None,
+			snapshot_hash: None,
+			stride_offset: 0,
+		}
+	}
+
 	fn dummy_sync_with_peer(peer_latest_hash: H256, client: &BlockChainClient) -> ChainSync {
 		let mut sync = ChainSync::new(SyncConfig::default(), client);
-		sync.peers.insert(0,
-			PeerInfo {
-				protocol_version: 0,
-				genesis: H256::zero(),
-				network_id: U256::zero(),
-				latest_hash: peer_latest_hash,
-				latest_number: None,
-				difficulty: None,
-				asking: PeerAsking::Nothing,
-				asking_blocks: Vec::new(),
-				asking_hash: None,
-				ask_time: 0f64,
-				last_sent_transactions: HashSet::new(),
-				expired: false,
-				confirmation: super::ForkConfirmation::Confirmed,
-				snapshot_number: None,
-				snapshot_hash: None,
-				asking_snapshot_data: None,
-			});
+		sync.peers.insert(0, dummy_peer(peer_latest_hash));
+		sync
+	}
+
+	fn subchain_heads(client: &BlockChainClient, start: BlockNumber, spacing: &[BlockNumber]) -> Bytes {
+		let mut rlp = RlpStream::new_list(spacing.len() + 1);
+		let mut number = start;
+		rlp.append_raw(&client.block_header(BlockID::Number(number)).unwrap(), 1);
+		for step in spacing {
+			number += *step;
+			rlp.append_raw(&client.block_header(BlockID::Number(number)).unwrap(), 1);
+		}
+		rlp.out()
+	}
+
+	fn import_subchain_heads(spacing: &[BlockNumber]) -> ChainSync {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(1000, EachBlockWith::Nothing);
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		let ss = TestSnapshotService::new();
+		let start_hash = client.block_hash(BlockID::Number(10)).unwrap();
+		let heads = subchain_heads(&client, 10, spacing);
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.state = SyncState::ChainHead;
+		sync.request_headers_by_hash(&mut io, 0, &start_hash, SUBCHAIN_SIZE, SUBCHAIN_HEADS_SKIP, false, PeerAsking::Heads);
+		sync.on_peer_block_headers(&mut io, 0, &UntrustedRlp::new(&heads)).unwrap();
 		sync
 	}
 
+	#[test]
+	fn accepts_subchain_heads_with_shorter_stride() {
+		let stride = SUBCHAIN_HEADS_SKIP as BlockNumber;
+		let sync = import_subchain_heads(&[stride, stride, stride]);
+
+		assert_eq!(sync.state, SyncState::Blocks);
+		assert_eq!(sync.peers[&0].stride_offset, -1);
+		assert_eq!(sync.peers[&0].adjusted_skip(SUBCHAIN_HEADS_SKIP), SUBCHAIN_HEADS_SKIP + 1);
+	}
+
+	#[test]
+	fn accepts_subchain_heads_with_longer_stride() {
+		let stride = SUBCHAIN_HEADS_SKIP as BlockNumber + 2;
+		let sync = import_subchain_heads(&[stride, stride, stride]);
+
+		assert_eq!(sync.state, SyncState::Blocks);
+		assert_eq!(sync.peers[&0].stride_offset, 1);
+		assert_eq!(sync.peers[&0].adjusted_skip(SUBCHAIN_HEADS_SKIP), SUBCHAIN_HEADS_SKIP - 1);
+	}
+
+	#[test]
+	fn accepts_subchain_heads_with_requested_stride() {
+		let stride = SUBCHAIN_HEADS_SKIP as BlockNumber + 1;
+		let sync = import_subchain_heads(&[stride, stride, stride]);
+
+		assert_eq!(sync.state, SyncState::Blocks);
+		assert_eq!(sync.peers[&0].stride_offset, 0);
+	}
+
+	#[test]
+	fn rejects_subchain_heads_with_stride_too_far_off() {
+		let stride = SUBCHAIN_HEADS_SKIP as BlockNumber + 3;
+		let sync = import_subchain_heads(&[stride, stride, stride]);
+
+		assert_eq!(sync.state, SyncState::ChainHead);
+		assert_eq!(sync.peers[&0].stride_offset, 0);
+	}
+
+	#[test]
+	fn rejects_unevenly_spaced_subchain_heads() {
+		let stride = SUBCHAIN_HEADS_SKIP as BlockNumber + 1;
+		let sync = import_subchain_heads(&[stride, stride + 5, stride]);
+
+		assert_eq!(sync.state, SyncState::ChainHead);
+		assert_eq!(sync.peers[&0].stride_offset, 0);
+	}
+
+	fn sync_with_peer_difficulties(client: &TestBlockChainClient, difficulties: &[u64]) -> ChainSync {
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), client);
+		let peer = sync.peers[&0].clone();
+		for (id, difficulty) in difficulties.iter().enumerate() {
+			sync.peers.insert(id, PeerInfo { difficulty: Some(U256::from(*difficulty)), ..peer.clone() });
+		}
+		sync
+	}
+
+	#[test]
+	fn starts_round_only_with_peer_ahead() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		*client.difficulty.write() = U256::from(1000);
+		let mut queue = VecDeque::new();
+		let mut sync = sync_with_peer_difficulties(&client, &[500, 1000, 1500]);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.restart(&mut io);
+
+		assert_eq!(sync.state, SyncState::ChainHead);
+		assert_eq!(sync.peers[&0].request.asking, PeerAsking::Nothing);
+		assert_eq!(sync.peers[&1].request.asking, PeerAsking::Nothing);
+		assert_eq!(sync.peers[&2].request.asking, PeerAsking::Heads);
+	}
+
+	#[test]
+	fn stays_idle_without_peer_ahead() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		*client.difficulty.write() = U256::from(1000);
+		let mut queue = VecDeque::new();
+		let mut sync = sync_with_peer_difficulties(&client, &[500, 1000]);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.imported_this_round = Some(0);
+		sync.last_imported_block = 9;
+		sync.restart(&mut io);
+
+		assert_eq!(sync.state, SyncState::Idle);
+		assert!(sync.peers.values().all(|p| !p.request.is_pending()));
+		assert_eq!(sync.last_imported_block, 9);
+		assert_eq!(io.queue.len(), 0);
+	}
+
+	#[test]
+	fn peer_announcing_higher_block_is_ahead() {
+		let client = TestBlockChainClient::new();
+		let mut sync = sync_with_peer_difficulties(&client, &[1000]);
+		let peer = sync.peers.get_mut(&0).unwrap();
+
+		assert!(!peer.is_ahead(&U256::from(1000), 10));
+		peer.latest_number = Some(10);
+		assert!(!peer.is_ahead(&U256::from(1000), 10));
+		peer.latest_number = Some(11);
+		assert!(peer.is_ahead(&U256::from(1000), 10));
+	}
+
+	#[test]
+	fn reports_outstanding_peer_request() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		assert!(!sync.peers()[0].1.is_pending());
+
+		let hash = H256::from(42);
+		sync.request_headers_by_hash(&mut io, 0, &hash, 1, 0, false, PeerAsking::Heads);
+
+		let peers = sync.peers();
+		assert_eq!(1, peers.len());
+		assert_eq!(0, peers[0].0);
+		assert_eq!(PeerAsking::Heads, peers[0].1.asking);
+		assert_eq!(Some(hash), peers[0].1.hash);
+		assert_eq!(1, sync.status().num_active_peers);
+	}
+
+	#[test]
+	fn throttles_header_requests_at_memory_limit() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(200, EachBlockWith::Nothing);
+		let headers: Vec<_> = (0..200).map(|i| client.block_header(BlockID::Number(i)).unwrap()).collect();
+		let heads: Vec<_> = (0..10).map(|i| client.block_hash(BlockID::Number(i * 20)).unwrap()).collect();
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.state = SyncState::Blocks;
+		sync.blocks.reset_to(heads);
+		sync.blocks.insert_headers(headers[0..6].to_vec());
+
+		// downloaded headers fill the limit
+		let mem_used = sync.status().mem_used;
+		assert!(mem_used > 0);
+		sync.max_mem_use = Some(mem_used);
+		sync.request_blocks(&mut io, 0, false);
+		assert_eq!(0, io.queue.len());
+		assert!(!sync.peers()[0].1.is_pending());
+		assert_eq!(Some(mem_used), sync.status().max_mem_use);
+
+		// requests resume once there is room
+		sync.max_mem_use = Some(mem_used + 1);
+		sync.request_blocks(&mut io, 0, false);
+		assert_eq!(1, io.queue.len());
+		assert_eq!(PeerAsking::BlockHeaders, sync.peers()[0].1.asking);
+	}
+
+	#[test]
+	fn requests_only_lowest_subchain_at_memory_limit() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(200, EachBlockWith::Transaction);
+		let headers: Vec<_> = (0..200).map(|i| client.block_header(BlockID::Number(i)).unwrap()).collect();
+		let hashes: Vec<_> = (0..200).map(|i| client.block_hash(BlockID::Number(i)).unwrap()).collect();
+		let heads: Vec<_> = (0..10).map(|i| hashes[i * 20].clone()).collect();
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		sync.peers.insert(1, dummy_peer(client.block_hash_delta_minus(1)));
+		sync.peers.insert(2, dummy_peer(client.block_hash_delta_minus(1)));
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.state = SyncState::Blocks;
+		sync.blocks.reset_to(heads);
+		sync.blocks.insert_headers(headers[0..6].to_vec());
+		sync.blocks.insert_headers(headers[20..26].to_vec());
+		sync.max_mem_use = Some(sync.status().mem_used);
+
+		// bodies of the lowest subchain are requested, the other subchain is left alone
+		sync.request_blocks(&mut io, 0, false);
+		assert_eq!(1, io.queue.len());
+		assert_eq!(PeerAsking::BlockBodies, sync.peers.get(&0).unwrap().request.asking);
+		assert_eq!(&hashes[1..6], &sync.peers.get(&0).unwrap().request.blocks[..]);
+
+		// headers continuing the lowest subchain are requested as well
+		sync.request_blocks(&mut io, 1, false);
+		assert_eq!(2, io.queue.len());
+		assert_eq!(PeerAsking::BlockHeaders, sync.peers.get(&1).unwrap().request.asking);
+		assert_eq!(vec![hashes[5].clone()], sync.peers.get(&1).unwrap().request.blocks);
+
+		// nothing else is downloaded until memory is freed
+		sync.request_blocks(&mut io, 2, false);
+		assert_eq!(2, io.queue.len());
+		assert!(!sync.peers.get(&2).unwrap().request.is_pending());
+	}
+
+	#[test]
+	fn aborts_peer_with_timed_out_request() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.request_headers_by_hash(&mut io, 0, &H256::from(42), 1, 0, false, PeerAsking::Heads);
+
+		// a fresh request is kept
+		sync.maintain_peers(&mut io);
+		assert_eq!(1, sync.peers().len());
+
+		// backdate the request past its timeout
+		sync.peers.get_mut(&0).unwrap().request.time -= super::HEADERS_TIMEOUT_SEC + 1f64;
+		sync.maintain_peers(&mut io);
+		assert!(sync.peers().is_empty());
+	}
+
 	#[test]
 	fn finds_lagging_peers() {
 		let mut client = TestBlockChainClient::new();
@@ -2112,6 +2937,55 @@ mod tests {
 		assert_eq!(0x07, io.queue[0].packet_id);
 	}
 
+	#[test]
+	fn announces_sealed_block_to_remaining_peers() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let mut queue = VecDeque::new();
+		let hash = client.block_hash(BlockID::Number(99)).unwrap();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let peer = sync.peers[&0].clone();
+		for id in 1..20 {
+			sync.peers.insert(id, peer.clone());
+		}
+		sync.block_propagation = BlockPropagation::Partial(0.25);
+		sync.last_sent_block_number = client.chain_info().best_block_number;
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.propagate_latest_blocks(&mut io, &[hash]);
+
+		// NEW_BLOCK_PACKET
+		assert_eq!(5, io.queue.iter().filter(|p| p.packet_id == 0x07).count());
+		// NEW_BLOCK_HASHES_PACKET
+		assert_eq!(15, io.queue.iter().filter(|p| p.packet_id == 0x01).count());
+		// every peer hears about the block exactly once
+		let recipients = io.queue.iter().map(|p| p.recipient).collect::<HashSet<_>>();
+		assert_eq!(20, recipients.len());
+		assert_eq!(20, io.queue.len());
+	}
+
+	#[test]
+	fn sends_sealed_block_to_all_peers_by_default() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let mut queue = VecDeque::new();
+		let hash = client.block_hash(BlockID::Number(99)).unwrap();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let peer = sync.peers[&0].clone();
+		for id in 1..20 {
+			sync.peers.insert(id, peer.clone());
+		}
+		sync.last_sent_block_number = client.chain_info().best_block_number;
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.propagate_latest_blocks(&mut io, &[hash]);
+
+		assert_eq!(20, io.queue.len());
+		assert!(io.queue.iter().all(|p| p.packet_id == 0x07));
+	}
+
 	#[test]
 	fn propagates_transactions() {
 		let mut client = TestBlockChainClient::new();
@@ -2134,6 +3008,38 @@ mod tests {
 		assert_eq!(0x02, io.queue[0].packet_id);
 	}
 
+	#[test]
+	fn skips_block_propagation_while_catching_up() {
+		// returns the number of packets sent when a block is imported in the given state.
+		fn sent_packets(state: SyncState, highest_block: BlockNumber, seal: bool) -> usize {
+			let mut client = TestBlockChainClient::new();
+			client.add_blocks(100, EachBlockWith::Uncle);
+			let mut queue = VecDeque::new();
+			let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+			sync.state = state;
+			sync.highest_block = Some(highest_block);
+			sync.last_sent_block_number = 98;
+			let ss = TestSnapshotService::new();
+			let sealed = match seal {
+				true => vec![client.block_hash(BlockID::Number(99)).unwrap()],
+				false => Vec::new(),
+			};
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			sync.chain_new_blocks(&mut io, &[], &[], &[], &[], &sealed);
+			io.queue.len()
+		}
+
+		// far behind the network head
+		assert_eq!(sent_packets(SyncState::Blocks, 100_000, false), 0);
+		assert_eq!(sent_packets(SyncState::ChainHead, 100_000, false), 0);
+		assert_eq!(sent_packets(SyncState::SnapshotWaiting, 100_000, false), 0);
+		// close to the head or idle
+		assert_eq!(sent_packets(SyncState::Blocks, 110, false), 2);
+		assert_eq!(sent_packets(SyncState::Idle, 100_000, false), 2);
+		// sealed blocks are always sent
+		assert_eq!(sent_packets(SyncState::Blocks, 100_000, true), 1);
+	}
+
 	#[test]
 	fn propagates_transactions_again_after_new_block() {
 		let mut client = TestBlockChainClient::new();
@@ -2230,6 +3136,227 @@ mod tests {
 		assert!(result.is_ok());
 	}
 
+	#[test]
+	fn attributes_bad_new_block_to_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+
+		let mut header = Header::new();
+		header.set_number(10);
+		header.set_parent_hash(client.chain_info().best_block_hash);
+		header.set_extra_data(vec![0u8; 64]);
+		let mut block_rlp = RlpStream::new_list(3);
+		block_rlp.append(&header);
+		block_rlp.append_raw(&::rlp::EMPTY_LIST_RLP, 1);
+		block_rlp.append_raw(&::rlp::EMPTY_LIST_RLP, 1);
+		let mut rlp = RlpStream::new_list(2);
+		rlp.append_raw(&block_rlp.out(), 1);
+		rlp.append(&U256::from(1000));
+		let block_data = rlp.out();
+
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		{
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			let block = UntrustedRlp::new(&block_data);
+			assert!(sync.on_peer_new_block(&mut io, 0, &block).is_ok());
+		}
+
+		let bad_blocks = client.bad_blocks();
+		assert_eq!(bad_blocks.len(), 1);
+		assert_eq!(bad_blocks[0].hash, header.hash());
+		assert_eq!(bad_blocks[0].number, 10);
+		assert_eq!(bad_blocks[0].peer, Some("0".to_owned()));
+	}
+
+	#[test]
+	fn requests_are_not_response_packets() {
+		for packet_id in &[STATUS_PACKET, GET_BLOCK_HEADERS_PACKET, GET_BLOCK_BODIES_PACKET, GET_NODE_DATA_PACKET,
+			GET_RECEIPTS_PACKET, GET_SNAPSHOT_MANIFEST_PACKET, GET_SNAPSHOT_DATA_PACKET] {
+			assert!(!ChainSync::is_response_packet(*packet_id));
+		}
+		for packet_id in &[BLOCK_HEADERS_PACKET, BLOCK_BODIES_PACKET, NODE_DATA_PACKET, RECEIPTS_PACKET,
+			SNAPSHOT_MANIFEST_PACKET, SNAPSHOT_DATA_PACKET] {
+			assert!(ChainSync::is_response_packet(*packet_id));
+		}
+	}
+
+	#[test]
+	fn rejects_banned_new_block() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+
+		let best_hash = client.chain_info().best_block_hash;
+		let banned_hash = BlockView::new(&get_dummy_block(11, best_hash.clone())).header_view().sha3();
+		let banned_block = get_dummy_blocks(11, best_hash);
+		let child_block = get_dummy_blocks(12, banned_hash.clone());
+
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.ban_block(banned_hash);
+		let ss = TestSnapshotService::new();
+		{
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			assert!(sync.on_peer_new_block(&mut io, 0, &UntrustedRlp::new(&banned_block)).is_ok());
+			assert!(io.disabled_peers.contains(&0));
+
+			io.disabled_peers.clear();
+			assert!(sync.on_peer_new_block(&mut io, 0, &UntrustedRlp::new(&child_block)).is_ok());
+			assert!(io.disabled_peers.contains(&0));
+		}
+
+		assert_eq!(client.chain_info().best_block_number, 10);
+	}
+
+	#[test]
+	fn disables_peer_supplying_banned_block() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+
+		let best_hash = client.chain_info().best_block_hash;
+		let banned_block = get_dummy_block(11, best_hash);
+		let banned_hash = BlockView::new(&banned_block).header_view().sha3();
+
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.ban_block(banned_hash.clone());
+		sync.note_block_peer(banned_hash, 0);
+		sync.import_retry = Some(ImportRetry { blocks: vec![banned_block], at: 0f64, attempts: 0 });
+		let ss = TestSnapshotService::new();
+		{
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			sync.collect_blocks(&mut io);
+			assert!(io.disabled_peers.contains(&0));
+		}
+
+		assert_eq!(client.chain_info().best_block_number, 10);
+	}
+
+	#[test]
+	fn retries_block_import_after_local_failure() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+
+		let best_hash = client.chain_info().best_block_hash;
+		let block = get_dummy_block(11, best_hash);
+		let hash = BlockView::new(&block).header_view().sha3();
+
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.note_block_peer(hash, 0);
+		sync.import_retry = Some(ImportRetry { blocks: vec![block], at: 0f64, attempts: 0 });
+		client.set_import_failures(1);
+		let ss = TestSnapshotService::new();
+		{
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			// the block is kept for a later attempt and the peer is not blamed
+			sync.collect_blocks(&mut io);
+			assert!(io.disabled_peers.is_empty());
+			assert_eq!(sync.import_retry.as_ref().map(|r| r.attempts), Some(1));
+
+			// nothing happens until the retry is due
+			sync.collect_blocks(&mut io);
+			assert_eq!(io.chain.chain_info().best_block_number, 10);
+
+			sync.import_retry.as_mut().unwrap().at = 0f64;
+			sync.collect_blocks(&mut io);
+			assert!(sync.import_retry.is_none());
+		}
+
+		assert_eq!(client.chain_info().best_block_number, 11);
+	}
+
+	#[test]
+	fn disables_peer_supplying_invalid_block() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let invalid = H256::from(11);
+		client.bad_blocks.write().push(BadBlock {
+			hash: invalid.clone(),
+			number: 11,
+			error: "Stage 2 block verification failed".into(),
+			size: 0,
+			peer: None,
+		});
+
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.note_block_peer(invalid.clone(), 0);
+		let ss = TestSnapshotService::new();
+		{
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			sync.chain_new_blocks(&mut io, &[], &[invalid.clone()], &[], &[], &[]);
+			assert!(io.disabled_peers.contains(&0));
+		}
+
+		assert!(client.bad_blocks()[0].peer.is_some());
+		assert!(!sync.block_peers.contains_key(&invalid));
+	}
+
+	#[test]
+	fn remembers_limited_number_of_banned_descendants() {
+		let client = TestBlockChainClient::new();
+		let mut sync = dummy_sync_with_peer(H256::new(), &client);
+		let banned = H256::from(1);
+		sync.ban_block(banned.clone());
+
+		let mut parent = banned.clone();
+		for i in 0..MAX_BANNED_DESCENDANTS + 10 {
+			let hash = H256::from(i as u64 + 2);
+			assert!(sync.is_banned(&hash, &parent));
+			parent = hash;
+		}
+
+		assert_eq!(sync.banned_descendants.len(), MAX_BANNED_DESCENDANTS);
+		assert_eq!(sync.banned_hashes.len(), MAX_BANNED_DESCENDANTS + 1);
+		assert!(sync.banned_hashes.contains(&banned));
+		assert!(!sync.banned_hashes.contains(&H256::from(2)));
+	}
+
+	#[test]
+	fn rejects_block_not_increasing_total_difficulty() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		let ss = TestSnapshotService::new();
+		let best_hash = client.chain_info().best_block_hash;
+		let block_header = |difficulty: u64| {
+			let mut header = Header::new();
+			header.set_number(11);
+			header.set_parent_hash(best_hash.clone());
+			header.set_difficulty(difficulty.into());
+			header
+		};
+
+		let header = block_header(0);
+		{
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			sync.blocks.reset_to(vec![header.hash()]);
+			sync.blocks.insert_headers(vec![encode(&header).to_vec()]);
+			sync.collect_blocks(&mut io);
+		}
+		assert_eq!(client.chain_info().best_block_number, 10);
+
+		let header = block_header(1);
+		{
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			sync.blocks.reset_to(vec![header.hash()]);
+			sync.blocks.insert_headers(vec![encode(&header).to_vec()]);
+			sync.collect_blocks(&mut io);
+		}
+		assert_eq!(client.chain_info().best_block_number, 11);
+	}
+
+	#[test]
+	fn total_difficulty_must_increase() {
+		assert_eq!(super::next_total_difficulty(&U256::from(10), &U256::from(1)), Some(U256::from(11)));
+		assert_eq!(super::next_total_difficulty(&U256::from(10), &U256::zero()), None);
+		assert_eq!(super::next_total_difficulty(&!U256::zero(), &U256::from(1)), None);
+	}
+
 	#[test]
 	fn handles_peer_new_block_empty() {
 		let mut client = TestBlockChainClient::new();
@@ -2399,4 +3526,124 @@ mod tests {
 		assert_eq!(status.transactions_in_pending_queue, 0);
 		assert_eq!(status.transactions_in_future_queue, 0);
 	}
+
+	#[test]
+	fn pinned_snapshot_chunk_is_only_accepted_from_its_source() {
+		use ethcore::snapshot::{ManifestData, RestorationStatus, SnapshotService};
+		use network::NodeId;
+
+		let mut client = TestBlockChainClient::new();
+		let mut queue = VecDeque::new();
+		let ss = TestSnapshotService::new();
+		let chunk = H256::random().to_vec();
+		let manifest = ManifestData {
+			state_hashes: vec![chunk.sha3()],
+			block_hashes: Vec::new(),
+			state_root: H256::new(),
+			block_number: 1,
+			block_hash: H256::new(),
+			state_chunk_roots: Vec::new(),
+			features: Vec::new(),
+			engine: String::new(),
+		};
+
+		let mut sync = dummy_sync_with_peer(H256::new(), &client);
+		let peer = sync.peers[&0].clone();
+		sync.peers.insert(1, peer);
+		// the test io maps peer ids to node ids one to one.
+		sync.pinned_chunks.insert(chunk.sha3(), NodeId::from(1));
+		sync.state = SyncState::SnapshotData;
+		sync.snapshot.reset_to(&manifest, &manifest.clone().into_rlp().sha3());
+		ss.begin_restore(manifest);
+
+		let mut rlp = RlpStream::new_list(1);
+		rlp.append(&chunk);
+		let data = rlp.out();
+
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+		// the chunk is never requested from the other peer.
+		sync.request_snapshot_data(&mut io, 0);
+		assert_eq!(sync.peers[&0].request.snapshot_data, None);
+
+		sync.request_snapshot_data(&mut io, 1);
+		assert_eq!(sync.peers[&1].request.snapshot_data, Some(chunk.sha3()));
+
+		// and ignored when offered by it.
+		sync.peers.get_mut(&0).unwrap().request.asking = PeerAsking::SnapshotData;
+		sync.on_snapshot_data(&mut io, 0, &UntrustedRlp::new(&data)).unwrap();
+		assert_eq!(io.snapshot_service().status(), RestorationStatus::Ongoing { state_chunks_done: 0, block_chunks_done: 0 });
+		assert!(!sync.snapshot.is_complete());
+
+		sync.on_snapshot_data(&mut io, 1, &UntrustedRlp::new(&data)).unwrap();
+		assert!(sync.snapshot.is_complete());
+		assert_eq!(io.snapshot_service().status(), RestorationStatus::Inactive);
+	}
+
+	#[test]
+	fn switches_to_warp_sync_when_far_behind() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Nothing);
+		let mut queue = VecDeque::new();
+		let ss = TestSnapshotService::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		sync.active_peers.insert(0);
+		sync.warp_threshold = 1000;
+		assert_eq!(sync.block_gap(), 0);
+
+		sync.highest_block = Some(1100);
+		assert_eq!(sync.block_gap(), 1000);
+		sync.peers.get_mut(&0).unwrap().snapshot_number = Some(5000);
+		assert!(!sync.should_warp(5000));
+
+		sync.highest_block = Some(5100);
+		assert_eq!(sync.block_gap(), 5000);
+		assert!(sync.should_warp(5000));
+		// the snapshot must be far enough ahead.
+		assert!(!sync.should_warp(1100));
+
+		sync.state = SyncState::Blocks;
+		sync.blocks.reset_to(vec![client.block_hash_delta_minus(1)]);
+		{
+			let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+			sync.sync_peer(&mut io, 0, false);
+		}
+		assert_eq!(sync.state, SyncState::SnapshotManifest);
+		assert!(sync.blocks.is_empty());
+		assert_eq!(sync.peers[&0].request.asking, PeerAsking::SnapshotManifest);
+	}
+
+	#[test]
+	fn limits_concurrent_header_downloads() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Nothing);
+		let mut queue = VecDeque::new();
+		let mut config = SyncConfig::default();
+		config.header_download_peers = 3;
+		let mut sync = ChainSync::new(config, &client);
+		for peer_id in 0..6 {
+			sync.peers.insert(peer_id, dummy_peer(H256::from(1000 + peer_id as u64)));
+		}
+		sync.active_peers = sync.peers.keys().cloned().collect();
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		// subchain heads are requested from the first peers only
+		sync.continue_sync(&mut io);
+		assert_eq!(sync.state, SyncState::ChainHead);
+		assert_eq!(io.queue.iter().filter(|p| p.packet_id == GET_BLOCK_HEADERS_PACKET).count(), 3);
+		assert_eq!(sync.header_downloads(), 3);
+
+		// subchains are downloaded from as many peers, each a different one
+		io.queue.clear();
+		for peer in sync.peers.values_mut() {
+			peer.request = PeerRequest::new();
+		}
+		sync.state = SyncState::Blocks;
+		sync.blocks.reset_to((0..6u64).map(H256::from).collect());
+		sync.continue_sync(&mut io);
+		assert_eq!(io.queue.iter().filter(|p| p.packet_id == GET_BLOCK_HEADERS_PACKET).count(), 3);
+		let subchains: HashSet<_> = sync.peers.values().filter(|p| p.request.asking == PeerAsking::BlockHeaders)
+			.flat_map(|p| p.request.blocks.iter().cloned()).collect();
+		assert_eq!(subchains.len(), 3);
+	}
 }
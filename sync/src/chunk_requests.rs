@@ -0,0 +1,192 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshot chunks requested from peers outside of warp sync, e.g. to repair a damaged snapshot.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use util::{Bytes, H256, Hashable, Mutex, Condvar};
+use network::PeerId;
+use ethcore::snapshot::repair::ChunkSource;
+
+/// A chunk which has not been received yet.
+#[derive(Default)]
+struct Pending {
+	/// Peer the chunk is currently requested from
+	asked: Option<PeerId>,
+	/// Peers which failed to provide the chunk
+	tried: HashSet<PeerId>,
+}
+
+#[derive(Default)]
+struct Requests {
+	pending: HashMap<H256, Pending>,
+	fetched: HashMap<H256, Bytes>,
+}
+
+/// Snapshot chunks wanted by hash. Chunks are requested from idle peers offering a snapshot,
+/// each peer is asked for a chunk at most once.
+pub struct ChunkRequests {
+	requests: Mutex<Requests>,
+	fetched: Condvar,
+}
+
+impl ChunkRequests {
+	/// Create a new instance.
+	pub fn new() -> ChunkRequests {
+		ChunkRequests {
+			requests: Mutex::new(Requests::default()),
+			fetched: Condvar::new(),
+		}
+	}
+
+	/// Ask for a chunk to be fetched from peers.
+	pub fn request(&self, hash: &H256) {
+		let mut requests = self.requests.lock();
+		if !requests.fetched.contains_key(hash) {
+			requests.pending.entry(*hash).or_insert_with(Pending::default);
+		}
+	}
+
+	/// Take a fetched chunk.
+	pub fn take(&self, hash: &H256) -> Option<Bytes> {
+		self.requests.lock().fetched.remove(hash)
+	}
+
+	/// Fetch a chunk from peers, waiting for at most `timeout`.
+	pub fn fetch(&self, hash: &H256, timeout: Duration) -> Option<Bytes> {
+		self.request(hash);
+		let deadline = Instant::now() + timeout;
+		let mut requests = self.requests.lock();
+		loop {
+			if let Some(chunk) = requests.fetched.remove(hash) {
+				return Some(chunk);
+			}
+			let now = Instant::now();
+			if now >= deadline {
+				requests.pending.remove(hash);
+				return None;
+			}
+			self.fetched.wait_for(&mut requests, deadline - now);
+		}
+	}
+
+	/// Pick a pending chunk which hasn't been requested from the peer before and mark it as
+	/// requested from it.
+	pub fn next_for(&self, peer_id: PeerId) -> Option<H256> {
+		let mut requests = self.requests.lock();
+		let next = requests.pending.iter_mut()
+			.find(|&(_, ref p)| p.asked.is_none() && !p.tried.contains(&peer_id))
+			.map(|(hash, p)| {
+				p.asked = Some(peer_id);
+				*hash
+			});
+		next
+	}
+
+	/// Whether the chunk is currently requested from the peer.
+	pub fn is_asked(&self, hash: &H256, peer_id: PeerId) -> bool {
+		self.requests.lock().pending.get(hash).map_or(false, |p| p.asked == Some(peer_id))
+	}
+
+	/// The request for a chunk to the peer is over; the chunk is not requested from it again.
+	pub fn clear_ask(&self, hash: &H256, peer_id: PeerId) {
+		if let Some(p) = self.requests.lock().pending.get_mut(hash) {
+			if p.asked == Some(peer_id) {
+				p.asked = None;
+			}
+			p.tried.insert(peer_id);
+		}
+	}
+
+	/// Deliver the data of a pending chunk. Returns `false` if the chunk is not pending
+	/// or the data doesn't match its hash.
+	pub fn deliver(&self, hash: &H256, chunk: Bytes) -> bool {
+		let mut requests = self.requests.lock();
+		if !requests.pending.contains_key(hash) || chunk.sha3() != *hash {
+			return false;
+		}
+		requests.pending.remove(hash);
+		requests.fetched.insert(*hash, chunk);
+		self.fetched.notify_all();
+		true
+	}
+}
+
+/// Snapshot chunk source fetching chunks from the peers of a running sync.
+pub struct PeerChunkSource {
+	requests: Arc<ChunkRequests>,
+	timeout: Duration,
+}
+
+impl PeerChunkSource {
+	/// Create a chunk source giving up on each chunk after `timeout`.
+	pub fn new(requests: Arc<ChunkRequests>, timeout: Duration) -> PeerChunkSource {
+		PeerChunkSource {
+			requests: requests,
+			timeout: timeout,
+		}
+	}
+}
+
+impl ChunkSource for PeerChunkSource {
+	fn fetch_chunk(&self, hash: &H256) -> Option<Bytes> {
+		self.requests.fetch(hash, self.timeout)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use util::{H256, Hashable};
+	use super::ChunkRequests;
+
+	#[test]
+	fn asks_each_peer_once() {
+		let requests = ChunkRequests::new();
+		let hash = H256::random();
+		requests.request(&hash);
+		assert_eq!(requests.next_for(1), Some(hash));
+		assert!(requests.is_asked(&hash, 1));
+		// already being requested.
+		assert_eq!(requests.next_for(2), None);
+		requests.clear_ask(&hash, 1);
+		assert_eq!(requests.next_for(1), None);
+		assert_eq!(requests.next_for(2), Some(hash));
+	}
+
+	#[test]
+	fn accepts_only_matching_data() {
+		let requests = ChunkRequests::new();
+		let chunk = vec![1u8, 2, 3];
+		let hash = chunk.sha3();
+		assert!(!requests.deliver(&hash, chunk.clone()));
+		requests.request(&hash);
+		assert!(!requests.deliver(&hash, vec![4u8]));
+		assert!(requests.deliver(&hash, chunk.clone()));
+		assert_eq!(requests.fetch(&hash, Duration::from_millis(0)), Some(chunk));
+		assert_eq!(requests.take(&hash), None);
+	}
+
+	#[test]
+	fn gives_up_after_timeout() {
+		let requests = ChunkRequests::new();
+		let hash = H256::random();
+		assert_eq!(requests.fetch(&hash, Duration::from_millis(10)), None);
+		assert_eq!(requests.next_for(1), None);
+	}
+}
@@ -0,0 +1,115 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Classification of block import errors by whose fault they are.
+
+use ethcore::client::BlockImportError;
+use ethcore::error::{BlockError, ImportError};
+
+/// Who is to blame for a failed block import.
+#[derive(Debug, PartialEq)]
+pub enum ImportFault {
+	/// The block is invalid. The peer that sent it should be penalized.
+	Peer,
+	/// The block was rejected because of a problem on our side, e.g. clock skew or shutdown.
+	Local,
+	/// The block might be imported later, e.g. once its parent arrives.
+	Transient,
+}
+
+/// Decide who is to blame for the import error.
+pub fn import_fault(e: &BlockImportError) -> ImportFault {
+	match *e {
+		BlockImportError::Import(ImportError::AlreadyInChain) |
+		BlockImportError::Import(ImportError::AlreadyQueued) => ImportFault::Transient,
+		BlockImportError::Import(ImportError::KnownBad) => ImportFault::Peer,
+		BlockImportError::Import(ImportError::ShuttingDown) => ImportFault::Local,
+		BlockImportError::Block(BlockError::UnknownParent(_)) => ImportFault::Transient,
+		BlockImportError::Block(ref e) if clock_drift(e).is_some() => ImportFault::Local,
+		BlockImportError::Block(_) => ImportFault::Peer,
+		// the remaining errors come out of the basic verification of the block, e.g. undecodable
+		// transactions or uncles, for which the client marks the block as bad.
+		BlockImportError::Other(_) => ImportFault::Peer,
+	}
+}
+
+/// Number of seconds the block timestamp is ahead of the latest timestamp our clock allows,
+/// if the block was rejected for being from the future.
+pub fn clock_drift(e: &BlockError) -> Option<u64> {
+	match *e {
+		BlockError::InvalidTimestamp(ref oob) if e.is_temporary() => oob.max.map(|max| oob.found - max),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethcore::client::BlockImportError;
+	use ethcore::error::{BlockError, ImportError};
+	use util::{H256, U256, Mismatch, OutOfBounds};
+	use super::{ImportFault, import_fault, clock_drift};
+
+	fn block(e: BlockError) -> BlockImportError {
+		BlockImportError::Block(e)
+	}
+
+	#[test]
+	fn peer_fault_errors() {
+		let errors = vec![
+			BlockImportError::Import(ImportError::KnownBad),
+			block(BlockError::InvalidStateRoot(Mismatch { expected: H256::zero(), found: H256::from(1) })),
+			block(BlockError::InvalidProofOfWork(OutOfBounds { min: None, max: Some(U256::from(1)), found: U256::from(2) })),
+			block(BlockError::InvalidSeal),
+			block(BlockError::UnknownUncleParent(H256::zero())),
+			// too close to the parent is not a matter of our clock.
+			block(BlockError::InvalidTimestamp(OutOfBounds { min: Some(100), max: None, found: 99 })),
+			BlockImportError::Other("undecodable uncles".into()),
+		];
+		for e in errors {
+			assert_eq!(import_fault(&e), ImportFault::Peer, "{:?}", e);
+		}
+	}
+
+	#[test]
+	fn local_fault_errors() {
+		let errors = vec![
+			BlockImportError::Import(ImportError::ShuttingDown),
+			block(BlockError::InvalidTimestamp(OutOfBounds { min: None, max: Some(1000), found: 1030 })),
+		];
+		for e in errors {
+			assert_eq!(import_fault(&e), ImportFault::Local, "{:?}", e);
+		}
+	}
+
+	#[test]
+	fn transient_errors() {
+		let errors = vec![
+			BlockImportError::Import(ImportError::AlreadyInChain),
+			BlockImportError::Import(ImportError::AlreadyQueued),
+			block(BlockError::UnknownParent(H256::zero())),
+		];
+		for e in errors {
+			assert_eq!(import_fault(&e), ImportFault::Transient, "{:?}", e);
+		}
+	}
+
+	#[test]
+	fn measures_clock_drift() {
+		assert_eq!(clock_drift(&BlockError::InvalidTimestamp(OutOfBounds { min: None, max: Some(1000), found: 1030 })), Some(30));
+		assert_eq!(clock_drift(&BlockError::InvalidTimestamp(OutOfBounds { min: Some(100), max: None, found: 99 })), None);
+		assert_eq!(clock_drift(&BlockError::InvalidSeal), None);
+	}
+}
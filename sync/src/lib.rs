@@ -50,6 +50,10 @@ mod chain;
 mod blocks;
 mod sync_io;
 mod snapshot;
+mod packet_queue;
+mod rate_limit;
+mod import_fault;
+mod chunk_requests;
 
 #[cfg(test)]
 mod tests;
@@ -60,6 +64,7 @@ mod api {
 }
 
 pub use api::{EthSync, SyncProvider, SyncClient, NetworkManagerClient, ManageNetwork, SyncConfig,
-	ServiceConfiguration, NetworkConfiguration};
-pub use chain::{SyncStatus, SyncState};
-pub use network::{is_valid_node_url, NonReservedPeerMode, NetworkError};
+	BlockPropagation, ServiceConfiguration, NetworkConfiguration, ReservedPeerStatus, NetworkMetrics, PeerRequestInfo};
+pub use chain::{SyncStatus, SyncState, PeerRequest, PeerAsking};
+pub use chunk_requests::{ChunkRequests, PeerChunkSource};
+pub use network::{is_valid_node_url, interface_address, NonReservedPeerMode, NetworkError};
@@ -0,0 +1,215 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Queue of large packets processed by the sync worker thread instead of the network IO thread.
+
+use std::collections::{HashMap, VecDeque};
+use network::{PeerId, PacketId};
+use util::{Bytes, Mutex, Condvar};
+
+/// Packets larger than this are processed by the sync worker.
+pub const MAX_INLINE_PACKET_SIZE: usize = 16 * 1024;
+/// Maximum number of packets queued for a single peer.
+pub const MAX_QUEUED_PACKETS_PER_PEER: usize = 32;
+
+/// Where a received packet should be processed.
+#[derive(Debug, PartialEq)]
+pub enum Dispatch {
+	/// Process right away on the calling thread.
+	Inline,
+	/// The packet has been queued for the worker.
+	Queued,
+	/// The peer has too many packets queued. The packet has been dropped.
+	Overflow,
+}
+
+struct QueueState {
+	packets: VecDeque<(PeerId, PacketId, Bytes)>,
+	/// Queued and in-flight packets of each peer.
+	per_peer: HashMap<PeerId, usize>,
+	closed: bool,
+}
+
+/// Per-peer FIFO of packets waiting for the sync worker.
+pub struct PacketQueue {
+	state: Mutex<QueueState>,
+	ready: Condvar,
+	inline_size: usize,
+	peer_limit: usize,
+}
+
+impl PacketQueue {
+	/// Create a new queue. Packets larger than `inline_size` are queued, at most `peer_limit` per peer.
+	pub fn new(inline_size: usize, peer_limit: usize) -> PacketQueue {
+		PacketQueue {
+			state: Mutex::new(QueueState {
+				packets: VecDeque::new(),
+				per_peer: HashMap::new(),
+				closed: false,
+			}),
+			ready: Condvar::new(),
+			inline_size: inline_size,
+			peer_limit: peer_limit,
+		}
+	}
+
+	/// Decide where a received packet is processed and queue it if needed.
+	/// Small packets stay inline unless the peer already has packets waiting, so that packets of a peer are always processed in order.
+	pub fn push(&self, peer: PeerId, packet_id: PacketId, data: &[u8]) -> Dispatch {
+		let mut state = self.state.lock();
+		let pending = state.per_peer.get(&peer).cloned().unwrap_or(0);
+		if pending == 0 && data.len() <= self.inline_size {
+			return Dispatch::Inline;
+		}
+		if pending >= self.peer_limit {
+			return Dispatch::Overflow;
+		}
+		state.per_peer.insert(peer, pending + 1);
+		state.packets.push_back((peer, packet_id, data.to_vec()));
+		self.ready.notify_one();
+		Dispatch::Queued
+	}
+
+	/// Wait for the next packet. Returns `None` once the queue is closed.
+	/// `complete` must be called after the packet has been processed.
+	pub fn pop(&self) -> Option<(PeerId, PacketId, Bytes)> {
+		let mut state = self.state.lock();
+		loop {
+			if state.closed {
+				return None;
+			}
+			if let Some(packet) = state.packets.pop_front() {
+				return Some(packet);
+			}
+			self.ready.wait(&mut state);
+		}
+	}
+
+	/// Take the next packet without waiting.
+	#[cfg(test)]
+	pub fn try_pop(&self) -> Option<(PeerId, PacketId, Bytes)> {
+		self.state.lock().packets.pop_front()
+	}
+
+	/// Mark a packet of the peer as processed.
+	pub fn complete(&self, peer: PeerId) {
+		let mut state = self.state.lock();
+		let remove = match state.per_peer.get_mut(&peer) {
+			Some(count) => { *count -= 1; *count == 0 },
+			None => false,
+		};
+		if remove {
+			state.per_peer.remove(&peer);
+		}
+	}
+
+	/// Drop all packets queued for a peer.
+	pub fn clear_peer(&self, peer: PeerId) {
+		let mut state = self.state.lock();
+		let before = state.packets.len();
+		state.packets.retain(|&(p, _, _)| p != peer);
+		let dropped = before - state.packets.len();
+		let remove = match state.per_peer.get_mut(&peer) {
+			Some(count) => { *count -= dropped; *count == 0 },
+			None => false,
+		};
+		if remove {
+			state.per_peer.remove(&peer);
+		}
+	}
+
+	/// Stop the worker. Queued packets are dropped.
+	pub fn close(&self) {
+		let mut state = self.state.lock();
+		state.closed = true;
+		state.packets.clear();
+		state.per_peer.clear();
+		self.ready.notify_all();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::thread;
+	use super::{PacketQueue, Dispatch};
+
+	#[test]
+	fn small_packets_are_inline() {
+		let queue = PacketQueue::new(4, 2);
+		assert_eq!(queue.push(1, 0x00, &[0u8; 4]), Dispatch::Inline);
+		assert_eq!(queue.push(1, 0x06, &[0u8; 5]), Dispatch::Queued);
+	}
+
+	#[test]
+	fn preserves_order_per_peer() {
+		let queue = PacketQueue::new(4, 8);
+		assert_eq!(queue.push(1, 0x06, &[0u8; 10]), Dispatch::Queued);
+		assert_eq!(queue.push(2, 0x04, &[0u8; 10]), Dispatch::Queued);
+		// small packet of a peer with queued packets is processed after them
+		assert_eq!(queue.push(1, 0x02, &[0u8; 1]), Dispatch::Queued);
+		// other peers are not affected
+		assert_eq!(queue.push(3, 0x02, &[0u8; 1]), Dispatch::Inline);
+
+		assert_eq!(queue.pop().map(|(p, id, _)| (p, id)), Some((1, 0x06)));
+		queue.complete(1);
+		assert_eq!(queue.pop().map(|(p, id, _)| (p, id)), Some((2, 0x04)));
+		queue.complete(2);
+		assert_eq!(queue.pop().map(|(p, id, _)| (p, id)), Some((1, 0x02)));
+		// packet still being processed
+		assert_eq!(queue.push(1, 0x02, &[0u8; 1]), Dispatch::Queued);
+		queue.complete(1);
+		queue.pop();
+		queue.complete(1);
+		assert_eq!(queue.push(1, 0x02, &[0u8; 1]), Dispatch::Inline);
+	}
+
+	#[test]
+	fn overflowing_peer_is_reported() {
+		let queue = PacketQueue::new(0, 2);
+		assert_eq!(queue.push(1, 0x06, &[0u8; 1]), Dispatch::Queued);
+		assert_eq!(queue.push(1, 0x06, &[0u8; 1]), Dispatch::Queued);
+		assert_eq!(queue.push(1, 0x06, &[0u8; 1]), Dispatch::Overflow);
+		assert_eq!(queue.push(2, 0x06, &[0u8; 1]), Dispatch::Queued);
+
+		queue.clear_peer(1);
+		assert_eq!(queue.push(1, 0x06, &[0u8; 1]), Dispatch::Queued);
+		assert_eq!(queue.pop().map(|(p, _, _)| p), Some(2));
+	}
+
+	#[test]
+	fn close_stops_worker() {
+		let queue = Arc::new(PacketQueue::new(0, 2));
+		let worker = {
+			let queue = queue.clone();
+			thread::spawn(move || {
+				let mut processed = Vec::new();
+				while let Some((peer, _, data)) = queue.pop() {
+					processed.push((peer, data));
+					queue.complete(peer);
+				}
+				processed
+			})
+		};
+		queue.push(1, 0x06, &[1]);
+		queue.push(1, 0x06, &[2]);
+		while !queue.state.lock().per_peer.is_empty() {
+			thread::yield_now();
+		}
+		queue.close();
+		assert_eq!(worker.join().unwrap(), vec![(1, vec![1]), (1, vec![2])]);
+	}
+}
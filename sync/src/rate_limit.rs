@@ -0,0 +1,230 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Token bucket rate limiting of incoming packets, per peer and packet type.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use network::{PeerId, PacketId};
+use util::Mutex;
+
+/// Number of dropped packets after which a peer is disabled.
+pub const MAX_RATE_PENALTY: usize = 1000;
+/// Seconds after which one dropped packet of a peer is forgiven.
+const PENALTY_DECAY_SECS: u64 = 1;
+
+/// What to do with a received packet.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+	/// Within the limit, process the packet.
+	Accept,
+	/// Over the limit, drop the packet.
+	Drop,
+	/// Over the limit too many times. Drop the packet and disable the peer.
+	Disable,
+}
+
+struct Bucket {
+	tokens: f64,
+	updated: Instant,
+}
+
+struct Penalty {
+	dropped: usize,
+	updated: Instant,
+}
+
+impl Penalty {
+	/// Forget one dropped packet for every `PENALTY_DECAY_SECS` elapsed since the last update.
+	fn decay(&mut self, now: Instant) {
+		if now <= self.updated {
+			return;
+		}
+		let periods = now.duration_since(self.updated).as_secs() / PENALTY_DECAY_SECS;
+		if periods > 0 {
+			self.dropped = self.dropped.saturating_sub(periods as usize);
+			self.updated = self.updated + Duration::from_secs(periods * PENALTY_DECAY_SECS);
+		}
+	}
+}
+
+struct LimiterState {
+	buckets: HashMap<(PeerId, PacketId), Bucket>,
+	/// Recently dropped packets of each peer.
+	penalties: HashMap<PeerId, Penalty>,
+}
+
+/// Limits the rate of packets of each type a peer may send.
+pub struct RateLimiter {
+	state: Mutex<LimiterState>,
+	rate: f64,
+	burst: f64,
+	max_penalty: usize,
+}
+
+impl RateLimiter {
+	/// Create a new limiter allowing `rate` packets per second of each type, with bursts of up to `burst` packets.
+	/// A peer is disabled after `max_penalty` packets have been dropped. Zero `rate` disables limiting.
+	pub fn new(rate: u32, burst: u32, max_penalty: usize) -> RateLimiter {
+		RateLimiter {
+			state: Mutex::new(LimiterState {
+				buckets: HashMap::new(),
+				penalties: HashMap::new(),
+			}),
+			rate: rate as f64,
+			burst: burst as f64,
+			max_penalty: max_penalty,
+		}
+	}
+
+	/// Account for a packet received now.
+	pub fn check(&self, peer: PeerId, packet_id: PacketId) -> Verdict {
+		self.check_at(peer, packet_id, Instant::now())
+	}
+
+	fn check_at(&self, peer: PeerId, packet_id: PacketId, now: Instant) -> Verdict {
+		if self.rate == 0.0 {
+			return Verdict::Accept;
+		}
+
+		let mut state = self.state.lock();
+		let accepted = {
+			let burst = self.burst;
+			let bucket = state.buckets.entry((peer, packet_id)).or_insert_with(|| Bucket { tokens: burst, updated: now });
+			let elapsed = now.duration_since(bucket.updated);
+			let elapsed = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000.0;
+			bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+			bucket.updated = now;
+			if bucket.tokens >= 1.0 {
+				bucket.tokens -= 1.0;
+				true
+			} else {
+				false
+			}
+		};
+
+		if accepted {
+			return Verdict::Accept;
+		}
+
+		let penalty = state.penalties.entry(peer).or_insert_with(|| Penalty { dropped: 0, updated: now });
+		penalty.decay(now);
+		penalty.dropped += 1;
+		match penalty.dropped >= self.max_penalty {
+			true => Verdict::Disable,
+			false => Verdict::Drop,
+		}
+	}
+
+	/// Number of recently dropped packets of the peer. The penalty decays while the peer keeps to the limit.
+	pub fn penalty(&self, peer: PeerId) -> usize {
+		self.penalty_at(peer, Instant::now())
+	}
+
+	fn penalty_at(&self, peer: PeerId, now: Instant) -> usize {
+		let mut state = self.state.lock();
+		state.penalties.get_mut(&peer).map_or(0, |penalty| {
+			penalty.decay(now);
+			penalty.dropped
+		})
+	}
+
+	/// Forget about a disconnected peer.
+	pub fn clear_peer(&self, peer: PeerId) {
+		let mut state = self.state.lock();
+		let keys: Vec<_> = state.buckets.keys().filter(|&&(p, _)| p == peer).cloned().collect();
+		for key in keys {
+			state.buckets.remove(&key);
+		}
+		state.penalties.remove(&peer);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use super::{RateLimiter, Verdict};
+
+	#[test]
+	fn drops_packets_over_the_limit() {
+		let limiter = RateLimiter::new(8, 5, 100);
+		let start = Instant::now();
+
+		// a mock handler processing whatever gets through.
+		let mut processed = 0;
+		for _ in 0..20 {
+			if limiter.check_at(1, 0x05, start) == Verdict::Accept {
+				processed += 1;
+			}
+		}
+		assert_eq!(processed, 5);
+		assert_eq!(limiter.penalty_at(1, start), 15);
+
+		// other packet types and peers have their own buckets.
+		assert_eq!(limiter.check_at(1, 0x03, start), Verdict::Accept);
+		assert_eq!(limiter.check_at(2, 0x05, start), Verdict::Accept);
+		assert_eq!(limiter.penalty_at(2, start), 0);
+
+		// tokens are refilled over time.
+		let later = start + Duration::from_millis(250);
+		assert_eq!(limiter.check_at(1, 0x05, later), Verdict::Accept);
+		assert_eq!(limiter.check_at(1, 0x05, later), Verdict::Accept);
+		assert_eq!(limiter.check_at(1, 0x05, later), Verdict::Drop);
+		assert_eq!(limiter.penalty_at(1, later), 16);
+	}
+
+	#[test]
+	fn disables_chronic_offenders() {
+		let limiter = RateLimiter::new(1, 1, 3);
+		let now = Instant::now();
+		assert_eq!(limiter.check_at(1, 0x05, now), Verdict::Accept);
+		assert_eq!(limiter.check_at(1, 0x05, now), Verdict::Drop);
+		assert_eq!(limiter.check_at(1, 0x05, now), Verdict::Drop);
+		assert_eq!(limiter.check_at(1, 0x05, now), Verdict::Disable);
+
+		limiter.clear_peer(1);
+		assert_eq!(limiter.penalty_at(1, now), 0);
+		assert_eq!(limiter.check_at(1, 0x05, now), Verdict::Accept);
+	}
+
+	#[test]
+	fn penalty_decays_over_time() {
+		let limiter = RateLimiter::new(1, 1, 3);
+		let start = Instant::now();
+		assert_eq!(limiter.check_at(1, 0x05, start), Verdict::Accept);
+		assert_eq!(limiter.check_at(1, 0x05, start), Verdict::Drop);
+		assert_eq!(limiter.check_at(1, 0x05, start), Verdict::Drop);
+		assert_eq!(limiter.penalty_at(1, start), 2);
+
+		// a peer dropping a packet now and then is never disabled.
+		for i in 1..10 {
+			let now = start + Duration::from_secs(i * 2);
+			assert_eq!(limiter.check_at(1, 0x05, now), Verdict::Accept);
+			assert_eq!(limiter.check_at(1, 0x05, now), Verdict::Drop);
+		}
+		assert_eq!(limiter.penalty_at(1, start + Duration::from_secs(18)), 1);
+		assert_eq!(limiter.penalty_at(1, start + Duration::from_secs(19)), 0);
+	}
+
+	#[test]
+	fn zero_rate_disables_limiting() {
+		let limiter = RateLimiter::new(0, 0, 1);
+		let now = Instant::now();
+		for _ in 0..100 {
+			assert_eq!(limiter.check_at(1, 0x05, now), Verdict::Accept);
+		}
+	}
+}
@@ -84,13 +84,18 @@ impl Snapshot {
 
 	/// Find a chunk to download
 	pub fn needed_chunk(&mut self) -> Option<H256> {
+		self.needed_chunk_where(|_| true)
+	}
+
+	/// Find a chunk to download among the chunks accepted by the filter
+	pub fn needed_chunk_where<F>(&mut self, filter: F) -> Option<H256> where F: Fn(&H256) -> bool {
 		// check state chunks first
 		let mut chunk = self.pending_state_chunks.iter()
-			.find(|&h| !self.downloading_chunks.contains(h) && !self.completed_chunks.contains(h))
+			.find(|&h| !self.downloading_chunks.contains(h) && !self.completed_chunks.contains(h) && filter(h))
 			.cloned();
 		if chunk.is_none() {
 			chunk = self.pending_block_chunks.iter()
-				.find(|&h| !self.downloading_chunks.contains(h) && !self.completed_chunks.contains(h))
+				.find(|&h| !self.downloading_chunks.contains(h) && !self.completed_chunks.contains(h) && filter(h))
 				.cloned();
 		}
 
@@ -144,6 +149,9 @@ mod test {
 			state_root: H256::new(),
 			block_number: 42,
 			block_hash: H256::new(),
+			state_chunk_roots: Vec::new(),
+			features: Vec::new(),
+			engine: String::new(),
 		};
 		let mhash = manifest.clone().into_rlp().sha3();
 		(manifest, mhash, state_chunks, block_chunks)
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use network::{NetworkContext, PeerId, PacketId, NetworkError};
+use network::{NetworkContext, PeerId, PacketId, NetworkError, NodeId};
 use ethcore::client::BlockChainClient;
 use ethcore::snapshot::SnapshotService;
 use api::ETH_PROTOCOL;
@@ -39,6 +39,10 @@ pub trait SyncIo {
 	fn peer_info(&self, peer_id: PeerId) -> String {
 		peer_id.to_string()
 	}
+	/// Returns the public key of the peer node
+	fn peer_node_id(&self, _peer_id: PeerId) -> Option<NodeId> {
+		None
+	}
 	/// Maximum mutuallt supported ETH protocol version
 	fn eth_protocol_version(&self, peer_id: PeerId) -> u8;
 	/// Returns if the chain block queue empty
@@ -96,6 +100,10 @@ impl<'s, 'h> SyncIo for NetSyncIo<'s, 'h> {
 		self.network.peer_info(peer_id)
 	}
 
+	fn peer_node_id(&self, peer_id: PeerId) -> Option<NodeId> {
+		self.network.peer_node_id(peer_id)
+	}
+
 	fn is_expired(&self) -> bool {
 		self.network.is_expired()
 	}
@@ -15,8 +15,10 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use util::*;
+use rlp::{self, RlpStream, Stream};
 use ethcore::client::{TestBlockChainClient, BlockChainClient, BlockID, EachBlockWith};
 use chain::{SyncState};
+use packet_queue::{Dispatch, MAX_INLINE_PACKET_SIZE, MAX_QUEUED_PACKETS_PER_PEER};
 use super::helpers::*;
 
 #[test]
@@ -193,6 +195,50 @@ fn propagate_blocks() {
 	assert!(blocks > 0);
 }
 
+#[test]
+fn queued_packets_keep_peer_order() {
+	let mut net = TestNet::new(2);
+	net.peer_mut(1).chain.add_blocks(1000, EachBlockWith::Nothing);
+
+	// a request too large to be processed inline, followed by a small one
+	let count = MAX_INLINE_PACKET_SIZE / 32 + 1;
+	let mut bodies = RlpStream::new_list(count);
+	for n in 1..(count + 1) {
+		bodies.append(&net.peer(1).chain.block_hash(BlockID::Number(n as u64)).unwrap());
+	}
+	let mut headers = RlpStream::new_list(4);
+	headers.append(&1u64).append(&1u64).append(&0u64).append(&0u64);
+	// GET_BLOCK_BODIES_PACKET
+	net.peer_mut(0).queue.push_back(TestPacket { data: bodies.out(), packet_id: 0x05, recipient: 1 });
+	// GET_BLOCK_HEADERS_PACKET
+	net.peer_mut(0).queue.push_back(TestPacket { data: headers.out(), packet_id: 0x03, recipient: 1 });
+
+	assert_eq!(net.deliver_queued(0), Some(Dispatch::Queued));
+	assert_eq!(net.deliver_queued(0), Some(Dispatch::Queued));
+	net.process_queued_packets(1);
+
+	// BLOCK_BODIES_PACKET, then BLOCK_HEADERS_PACKET, both sent to the requesting peer
+	let responses: Vec<_> = net.peer(1).queue.iter().map(|p| (p.recipient, p.packet_id)).collect();
+	assert_eq!(responses, vec![(0, 0x06), (0, 0x04)]);
+}
+
+#[test]
+fn overflowing_packet_queue_drops_peer_packets() {
+	let mut net = TestNet::new(2);
+	for _ in 0..(MAX_QUEUED_PACKETS_PER_PEER + 1) {
+		// TRANSACTIONS_PACKET
+		net.peer_mut(0).queue.push_back(TestPacket { data: vec![0u8; MAX_INLINE_PACKET_SIZE + 1], packet_id: 0x02, recipient: 1 });
+	}
+	for _ in 0..MAX_QUEUED_PACKETS_PER_PEER {
+		assert_eq!(net.deliver_queued(0), Some(Dispatch::Queued));
+	}
+	assert_eq!(net.deliver_queued(0), Some(Dispatch::Overflow));
+
+	// nothing is left queued for the peer, so its packets are processed inline again
+	net.peer_mut(0).queue.push_back(TestPacket { data: rlp::EMPTY_LIST_RLP.to_vec(), packet_id: 0x02, recipient: 1 });
+	assert_eq!(net.deliver_queued(0), Some(Dispatch::Inline));
+}
+
 #[test]
 fn restart_on_malformed_block() {
 	let mut net = TestNet::new(2);
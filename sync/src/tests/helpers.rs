@@ -22,6 +22,7 @@ use ethcore::header::BlockNumber;
 use ethcore::snapshot::SnapshotService;
 use sync_io::SyncIo;
 use chain::ChainSync;
+use packet_queue::{PacketQueue, Dispatch, MAX_INLINE_PACKET_SIZE, MAX_QUEUED_PACKETS_PER_PEER};
 use ::SyncConfig;
 
 pub struct TestIo<'p> {
@@ -29,6 +30,7 @@ pub struct TestIo<'p> {
 	pub snapshot_service: &'p TestSnapshotService,
 	pub queue: &'p mut VecDeque<TestPacket>,
 	pub sender: Option<PeerId>,
+	pub disabled_peers: HashSet<PeerId>,
 }
 
 impl<'p> TestIo<'p> {
@@ -37,13 +39,15 @@ impl<'p> TestIo<'p> {
 			chain: chain,
 			snapshot_service: ss,
 			queue: queue,
-			sender: sender
+			sender: sender,
+			disabled_peers: HashSet::new(),
 		}
 	}
 }
 
 impl<'p> SyncIo for TestIo<'p> {
-	fn disable_peer(&mut self, _peer_id: PeerId) {
+	fn disable_peer(&mut self, peer_id: PeerId) {
+		self.disabled_peers.insert(peer_id);
 	}
 
 	fn disconnect_peer(&mut self, _peer_id: PeerId) {
@@ -79,6 +83,10 @@ impl<'p> SyncIo for TestIo<'p> {
 		self.snapshot_service
 	}
 
+	fn peer_node_id(&self, peer_id: PeerId) -> Option<NodeId> {
+		Some(NodeId::from(peer_id as u64))
+	}
+
 	fn eth_protocol_version(&self, _peer: PeerId) -> u8 {
 		64
 	}
@@ -95,6 +103,7 @@ pub struct TestPeer {
 	pub snapshot_service: Arc<TestSnapshotService>,
 	pub sync: RwLock<ChainSync>,
 	pub queue: VecDeque<TestPacket>,
+	pub packets: PacketQueue,
 }
 
 pub struct TestNet {
@@ -123,6 +132,7 @@ impl TestNet {
 				snapshot_service: ss,
 				chain: chain,
 				queue: VecDeque::new(),
+				packets: PacketQueue::new(MAX_INLINE_PACKET_SIZE, MAX_QUEUED_PACKETS_PER_PEER),
 			});
 		}
 		net
@@ -160,6 +170,31 @@ impl TestNet {
 		}
 	}
 
+	/// Deliver the next packet sent by a peer through the recipient's packet queue, like `SyncProtocolHandler` does.
+	pub fn deliver_queued(&mut self, from: usize) -> Option<Dispatch> {
+		let packet = match self.peers[from].queue.pop_front() {
+			Some(packet) => packet,
+			None => return None,
+		};
+		let mut p = self.peers.get_mut(packet.recipient).unwrap();
+		let dispatch = p.packets.push(from as PeerId, packet.packet_id, &packet.data);
+		match dispatch {
+			Dispatch::Inline => ChainSync::dispatch_packet(&p.sync, &mut TestIo::new(&mut p.chain, &p.snapshot_service, &mut p.queue, Some(from as PeerId)), from as PeerId, packet.packet_id, &packet.data),
+			Dispatch::Queued => {},
+			Dispatch::Overflow => p.packets.clear_peer(from as PeerId),
+		}
+		Some(dispatch)
+	}
+
+	/// Process the packets queued for a peer like the sync worker does, without a session to respond on.
+	pub fn process_queued_packets(&mut self, peer_num: usize) {
+		let mut p = self.peer_mut(peer_num);
+		while let Some((from, packet_id, data)) = p.packets.try_pop() {
+			ChainSync::dispatch_packet(&p.sync, &mut TestIo::new(&mut p.chain, &p.snapshot_service, &mut p.queue, None), from, packet_id, &data);
+			p.packets.complete(from);
+		}
+	}
+
 	pub fn sync_step_peer(&mut self, peer_num: usize) {
 		let mut peer = self.peer_mut(peer_num);
 		peer.sync.write().maintain_sync(&mut TestIo::new(&mut peer.chain, &peer.snapshot_service, &mut peer.queue, None));
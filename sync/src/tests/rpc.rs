@@ -14,10 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::super::NetworkConfiguration;
+use super::super::{NetworkConfiguration, SyncConfig};
 use network::NetworkConfiguration as BasicNetworkConfiguration;
 use std::convert::From;
 use ipc::binary::{serialize, deserialize};
+use util::{H256, H512};
 
 #[test]
 fn network_settings_serialize() {
@@ -27,3 +28,20 @@ fn network_settings_serialize() {
 
 	assert_eq!(net_cfg.udp_port, deserialized.udp_port);
 }
+
+#[test]
+fn sync_config_serialize() {
+	let mut sync_cfg = SyncConfig::default();
+	sync_cfg.max_mem_use = Some(1024);
+	sync_cfg.banned_hashes = vec![H256::from(1), H256::from(2)];
+	sync_cfg.pinned_chunks.insert(H256::from(3), H512::from(4));
+	sync_cfg.pinned_chunks.insert(H256::from(5), H512::from(6));
+	let serialized = serialize(&sync_cfg).unwrap();
+	let deserialized = deserialize::<SyncConfig>(&serialized).unwrap();
+
+	assert_eq!(sync_cfg.network_id, deserialized.network_id);
+	assert_eq!(sync_cfg.max_mem_use, deserialized.max_mem_use);
+	assert_eq!(sync_cfg.header_download_peers, deserialized.header_download_peers);
+	assert_eq!(sync_cfg.banned_hashes, deserialized.banned_hashes);
+	assert_eq!(sync_cfg.pinned_chunks, deserialized.pinned_chunks);
+}
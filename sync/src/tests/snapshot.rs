@@ -15,9 +15,9 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use util::*;
-use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus};
+use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus, CreationStatus};
 use ethcore::header::BlockNumber;
-use ethcore::client::{EachBlockWith};
+use ethcore::client::{BlockChainClient, EachBlockWith};
 use super::helpers::*;
 
 pub struct TestSnapshotService {
@@ -27,6 +27,8 @@ pub struct TestSnapshotService {
 	restoration_manifest: Mutex<Option<ManifestData>>,
 	state_restoration_chunks: Mutex<HashMap<H256, Bytes>>,
 	block_restoration_chunks: Mutex<HashMap<H256, Bytes>>,
+	fail_restoration: bool,
+	restoration_attempts: Mutex<usize>,
 }
 
 impl TestSnapshotService {
@@ -37,6 +39,8 @@ impl TestSnapshotService {
 			restoration_manifest: Mutex::new(None),
 			state_restoration_chunks: Mutex::new(HashMap::new()),
 			block_restoration_chunks: Mutex::new(HashMap::new()),
+			fail_restoration: false,
+			restoration_attempts: Mutex::new(0),
 		}
 	}
 
@@ -51,6 +55,9 @@ impl TestSnapshotService {
 			state_root: H256::new(),
 			block_number: block_number,
 			block_hash: block_hash,
+			state_chunk_roots: Vec::new(),
+			features: Vec::new(),
+			engine: String::new(),
 		};
 		let mut chunks: HashMap<H256, Bytes> = state_chunks.into_iter().map(|data| (data.sha3(), data)).collect();
 		chunks.extend(block_chunks.into_iter().map(|data| (data.sha3(), data)));
@@ -60,8 +67,16 @@ impl TestSnapshotService {
 			restoration_manifest: Mutex::new(None),
 			state_restoration_chunks: Mutex::new(HashMap::new()),
 			block_restoration_chunks: Mutex::new(HashMap::new()),
+			fail_restoration: false,
+			restoration_attempts: Mutex::new(0),
 		}
 	}
+
+	/// Make every restoration fail, as if the snapshot was corrupt.
+	pub fn with_failing_restoration(mut self) -> TestSnapshotService {
+		self.fail_restoration = true;
+		self
+	}
 }
 
 impl SnapshotService for TestSnapshotService {
@@ -75,6 +90,7 @@ impl SnapshotService for TestSnapshotService {
 
 	fn status(&self) -> RestorationStatus {
 		match *self.restoration_manifest.lock() {
+			Some(_) if self.fail_restoration => RestorationStatus::Failed,
 			Some(ref manifest) if self.state_restoration_chunks.lock().len() == manifest.state_hashes.len() &&
 				self.block_restoration_chunks.lock().len() == manifest.block_hashes.len() => RestorationStatus::Inactive,
 			Some(_) => RestorationStatus::Ongoing {
@@ -85,7 +101,16 @@ impl SnapshotService for TestSnapshotService {
 		}
 	}
 
+	fn creation_status(&self) -> Option<CreationStatus> {
+		None
+	}
+
+	fn request_snapshot(&self, _num: u64, _name: String) -> Result<(), String> {
+		Err("Not supported".into())
+	}
+
 	fn begin_restore(&self, manifest: ManifestData) {
+		*self.restoration_attempts.lock() += 1;
 		*self.restoration_manifest.lock() = Some(manifest);
 		self.state_restoration_chunks.lock().clear();
 		self.block_restoration_chunks.lock().clear();
@@ -121,3 +146,51 @@ fn snapshot_sync() {
 	assert_eq!(net.peer(1).snapshot_service.block_restoration_chunks.lock().len(), net.peer(0).snapshot_service.manifest.as_ref().unwrap().block_hashes.len());
 }
 
+#[test]
+fn falls_back_to_full_sync_when_snapshot_fails_to_restore() {
+	::env_logger::init().ok();
+	let mut net = TestNet::new(2);
+	net.peer_mut(0).snapshot_service = Arc::new(TestSnapshotService::new_with_snapshot(16, H256::new(), 1));
+	net.peer_mut(0).chain.add_blocks(1, EachBlockWith::Nothing);
+	net.peer_mut(1).snapshot_service = Arc::new(TestSnapshotService::new().with_failing_restoration());
+	net.sync();
+	// the snapshot is retried once before giving up on it.
+	assert_eq!(*net.peer(1).snapshot_service.restoration_attempts.lock(), 2);
+	assert_eq!(net.peer(1).chain.chain_info().best_block_number, 1);
+}
+
+#[test]
+fn tries_another_snapshot_when_snapshot_fails_to_restore() {
+	::env_logger::init().ok();
+	let mut net = TestNet::new(3);
+	net.peer_mut(0).snapshot_service = Arc::new(TestSnapshotService::new_with_snapshot(16, H256::new(), 1));
+	net.peer_mut(0).chain.add_blocks(1, EachBlockWith::Nothing);
+	net.peer_mut(1).snapshot_service = Arc::new(TestSnapshotService::new_with_snapshot(16, H256::new(), 1));
+	net.peer_mut(1).chain.add_blocks(1, EachBlockWith::Nothing);
+	net.peer_mut(2).snapshot_service = Arc::new(TestSnapshotService::new().with_failing_restoration());
+	net.sync();
+	// both snapshots are given up on before falling back to full sync.
+	assert_eq!(*net.peer(2).snapshot_service.restoration_attempts.lock(), 4);
+	assert_eq!(net.peer(2).chain.chain_info().best_block_number, 1);
+}
+
+#[test]
+fn fetches_wanted_chunks_from_peers() {
+	::env_logger::init().ok();
+	let mut net = TestNet::new(3);
+	let snapshot = TestSnapshotService::new_with_snapshot(4, H256::new(), 1);
+	let hash = snapshot.manifest.as_ref().unwrap().state_hashes[0];
+	net.peer_mut(1).snapshot_service = Arc::new(snapshot);
+	net.peer_mut(2).snapshot_service = Arc::new(TestSnapshotService::new_with_snapshot(4, H256::new(), 1));
+	let requests = net.peer(0).sync.read().chunk_requests();
+	let missing = H256::random();
+	requests.request(&hash);
+	requests.request(&missing);
+	net.sync();
+	assert_eq!(requests.take(&hash).map(|chunk| chunk.sha3()), Some(hash));
+	// both peers were asked for the chunk nobody has, and nothing was restored.
+	assert_eq!(requests.next_for(1), None);
+	assert_eq!(requests.next_for(2), None);
+	assert_eq!(requests.take(&missing), None);
+	assert_eq!(*net.peer(0).snapshot_service.restoration_attempts.lock(), 0);
+}
@@ -37,6 +37,8 @@ use tiny_keccak::Keccak;
 use crypto;
 
 const ENCRYPTED_HEADER_LEN: usize = 32;
+/// Largest frame payload accepted from a peer.
+pub const MAX_PAYLOAD_SIZE: usize = 8 * 1024 * 1024;
 const RECIEVE_PAYLOAD_TIMEOUT: u64 = 30000;
 
 pub trait GenericSocket : Read + Write {
@@ -367,6 +369,7 @@ impl EncryptedConnection {
 	}
 
 	/// Decrypt and authenticate an incoming packet header. Prepare for receiving payload.
+	/// Nothing in the header is trusted before its MAC is verified.
 	fn read_header(&mut self, header: &[u8]) -> Result<(), NetworkError> {
 		if header.len() != ENCRYPTED_HEADER_LEN {
 			return Err(From::from(NetworkError::Auth));
@@ -382,17 +385,21 @@ impl EncryptedConnection {
 		let mut hdec = H128::new();
 		self.decoder.decrypt(&mut RefReadBuffer::new(&header[0..16]), &mut RefWriteBuffer::new(&mut hdec), false).expect("Invalid length or padding");
 
-		let length = ((((hdec[0] as u32) << 8) + (hdec[1] as u32)) << 8) + (hdec[2] as u32);
+		let length = ((((hdec[0] as usize) << 8) + (hdec[1] as usize)) << 8) + (hdec[2] as usize);
+		if length > MAX_PAYLOAD_SIZE {
+			debug!(target: "network", "{}: Frame payload too large: {}", self.connection.token, length);
+			return Err(From::from(NetworkError::BadProtocol));
+		}
 		let header_rlp = UntrustedRlp::new(&hdec[3..6]);
 		let protocol_id = try!(header_rlp.val_at::<u16>(0));
 
-		self.payload_len = length as usize;
+		self.payload_len = length;
 		self.protocol_id = protocol_id;
 		self.read_state = EncryptedConnectionState::Payload;
 
 		let padding = (16 - (length % 16)) % 16;
 		let full_length = length + padding + 16;
-		self.connection.expect(full_length as usize);
+		self.connection.expect(full_length);
 		Ok(())
 	}
 
@@ -422,6 +429,18 @@ impl EncryptedConnection {
 		})
 	}
 
+	/// Count a frame failing to authenticate. Such a frame already went into the ingress MAC and cipher state,
+	/// so the stream can't be read any further and the peer has to be disconnected.
+	fn decryption_failure(&self, e: NetworkError) -> NetworkError {
+		match e {
+			NetworkError::Auth => {
+				self.connection.stats.inc_decryption_failures();
+				NetworkError::Disconnect(DisconnectReason::DecryptionFailure)
+			},
+			e => e,
+		}
+	}
+
 	/// Update MAC after reading or writing any data.
 	fn update_mac(mac: &mut Keccak, mac_encoder: &mut EcbEncryptor<AesSafe256Encryptor, EncPadding<NoPadding>>, seed: &[u8]) {
 		let mut prev = H128::new();
@@ -439,7 +458,7 @@ impl EncryptedConnection {
 		try!(io.clear_timer(self.connection.token));
 		if let EncryptedConnectionState::Header = self.read_state {
 			if let Some(data) = try!(self.connection.readable()) {
-				try!(self.read_header(&data));
+				try!(self.read_header(&data).map_err(|e| self.decryption_failure(e)));
 				try!(io.register_timer(self.connection.token, RECIEVE_PAYLOAD_TIMEOUT));
 			}
 		};
@@ -448,7 +467,7 @@ impl EncryptedConnection {
 				Some(data) => {
 					self.read_state = EncryptedConnectionState::Header;
 					self.connection.expect(ENCRYPTED_HEADER_LEN);
-					Ok(Some(try!(self.read_payload(&data))))
+					Ok(Some(try!(self.read_payload(&data).map_err(|e| self.decryption_failure(e)))))
 				},
 				None => Ok(None)
 			}
@@ -671,4 +690,52 @@ mod tests {
 		assert!(status.is_ok());
 		assert_eq!(0, connection.socket.cursor);
 	}
+
+	#[test]
+	fn corrupt_frame_header_disconnects() {
+		use std::net::TcpListener;
+		use std::thread;
+		use std::time::Duration;
+		use mio::tcp::TcpStream;
+		use rcrypto::blockmodes::{CtrMode, EcbEncryptor, NoPadding};
+		use rcrypto::aessafe::AesSafe256Encryptor;
+		use tiny_keccak::Keccak;
+		use util::hash::{H256, FixedHash};
+		use error::{NetworkError, DisconnectReason};
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let socket = TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+		let (mut remote, _) = listener.accept().unwrap();
+
+		let key = H256::random();
+		let stats = Arc::new(NetworkStats::new());
+		let mut connection = EncryptedConnection {
+			connection: Connection::new(0, socket, stats.clone()),
+			encoder: CtrMode::new(AesSafe256Encryptor::new(&key), vec![0u8; 16]),
+			decoder: CtrMode::new(AesSafe256Encryptor::new(&key), vec![0u8; 16]),
+			mac_encoder: EcbEncryptor::new(AesSafe256Encryptor::new(&key), NoPadding),
+			egress_mac: Keccak::new_keccak256(),
+			ingress_mac: Keccak::new_keccak256(),
+			read_state: EncryptedConnectionState::Header,
+			protocol_id: 0,
+			payload_len: 0,
+		};
+		connection.connection.expect(ENCRYPTED_HEADER_LEN);
+		remote.write_all(&[0xffu8; ENCRYPTED_HEADER_LEN]).unwrap();
+
+		let io = test_io();
+		let mut result = connection.readable(&io);
+		for _ in 0..100 {
+			match result {
+				Ok(None) => thread::sleep(Duration::from_millis(10)),
+				_ => break,
+			}
+			result = connection.readable(&io);
+		}
+		match result {
+			Err(NetworkError::Disconnect(DisconnectReason::DecryptionFailure)) => {},
+			_ => panic!("Corrupt header should disconnect the peer"),
+		}
+		assert_eq!(stats.decryption_failures(), 1);
+	}
 }
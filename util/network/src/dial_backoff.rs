@@ -0,0 +1,287 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-endpoint backoff for outgoing connection attempts.
+
+use std::cmp::min;
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use rand::Rng;
+
+/// Default delay after the first failed dial.
+pub const DEFAULT_DIAL_BACKOFF_MS: u64 = 5_000;
+/// Default upper bound on the delay between dials to the same endpoint.
+pub const DEFAULT_MAX_DIAL_BACKOFF_MS: u64 = 600_000;
+/// Refused connections mean nothing is listening, so they are backed off this many times longer.
+const REFUSED_BACKOFF_FACTOR: u32 = 4;
+/// Failure count after which the delay stops growing. Keeps the shift below from overflowing.
+const MAX_BACKOFF_EXPONENT: u32 = 16;
+
+/// Reason a dial failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialFailure {
+	/// Remote actively refused the connection.
+	Refused,
+	/// Connection or handshake did not complete in time.
+	Timeout,
+}
+
+impl DialFailure {
+	/// Classify an IO error returned while dialing.
+	pub fn from_io(e: &io::Error) -> DialFailure {
+		match e.kind() {
+			io::ErrorKind::ConnectionRefused => DialFailure::Refused,
+			_ => DialFailure::Timeout,
+		}
+	}
+}
+
+struct FailureEntry {
+	failures: u32,
+	retry_at: Instant,
+}
+
+/// Tracks recent dial failures and decides when an endpoint may be dialed again.
+pub struct DialBackoff {
+	base: Duration,
+	max: Duration,
+	entries: HashMap<SocketAddr, FailureEntry>,
+}
+
+impl DialBackoff {
+	/// Create a new tracker. The delay starts at `base` and doubles with each consecutive failure, up to `max`.
+	pub fn new(base: Duration, max: Duration) -> DialBackoff {
+		DialBackoff {
+			base: base,
+			max: max,
+			entries: HashMap::new(),
+		}
+	}
+
+	/// Check if the endpoint may be dialed at `now`.
+	pub fn can_dial(&self, address: &SocketAddr, now: Instant) -> bool {
+		self.entries.get(address).map_or(true, |e| now >= e.retry_at)
+	}
+
+	/// Record a failed dial. Returns the delay before the endpoint may be dialed again.
+	pub fn note_failure<R: Rng>(&mut self, address: SocketAddr, failure: DialFailure, now: Instant, rng: &mut R) -> Duration {
+		let failures = self.entries.get(&address).map_or(0, |e| e.failures) + 1;
+		let delay = jitter(self.delay(failures, failure), rng);
+		self.entries.insert(address, FailureEntry { failures: failures, retry_at: now + delay });
+		delay
+	}
+
+	/// Forget failures for an endpoint after a successful connection.
+	pub fn note_success(&mut self, address: &SocketAddr) {
+		self.entries.remove(address);
+	}
+
+	/// Drop entries whose backoff has elapsed long ago, so that the table does not grow without bound.
+	pub fn prune(&mut self, now: Instant) {
+		let max = self.max;
+		self.entries.retain(|_, e| now < e.retry_at + max);
+	}
+
+	fn delay(&self, failures: u32, failure: DialFailure) -> Duration {
+		let factor = match failure {
+			DialFailure::Refused => REFUSED_BACKOFF_FACTOR,
+			DialFailure::Timeout => 1,
+		};
+		let exponent = min(failures - 1, MAX_BACKOFF_EXPONENT);
+		let delay = to_millis(self.base).saturating_mul(1 << exponent).saturating_mul(factor as u64);
+		Duration::from_millis(min(delay, to_millis(self.max)))
+	}
+}
+
+/// Pick a delay uniformly between half and all of `delay`, so that endpoints failing together are not retried together.
+fn jitter<R: Rng>(delay: Duration, rng: &mut R) -> Duration {
+	let millis = to_millis(delay);
+	let half = millis / 2;
+	Duration::from_millis(half + rng.gen_range(0, millis - half + 1))
+}
+
+fn to_millis(d: Duration) -> u64 {
+	d.as_secs() * 1000 + (d.subsec_nanos() / 1_000_000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{DialBackoff, DialFailure, to_millis};
+	use std::cmp::min;
+	use std::collections::HashSet;
+	use std::net::SocketAddr;
+	use std::str::FromStr;
+	use std::time::{Duration, Instant};
+	use rand::{XorShiftRng, SeedableRng};
+
+	/// Dialer that fails for every endpoint it knows about and records which ones it was asked to dial.
+	struct MockDialer {
+		refused: HashSet<SocketAddr>,
+		timed_out: HashSet<SocketAddr>,
+		dialed: Vec<SocketAddr>,
+	}
+
+	impl MockDialer {
+		fn new(refused: &[SocketAddr], timed_out: &[SocketAddr]) -> MockDialer {
+			MockDialer {
+				refused: refused.iter().cloned().collect(),
+				timed_out: timed_out.iter().cloned().collect(),
+				dialed: Vec::new(),
+			}
+		}
+
+		fn dial_round(&mut self, backoff: &mut DialBackoff, addresses: &[SocketAddr], now: Instant, rng: &mut XorShiftRng) {
+			for address in addresses.iter().filter(|a| backoff.can_dial(a, now)) {
+				self.dialed.push(*address);
+				if self.refused.contains(address) {
+					backoff.note_failure(*address, DialFailure::Refused, now, rng);
+				} else if self.timed_out.contains(address) {
+					backoff.note_failure(*address, DialFailure::Timeout, now, rng);
+				} else {
+					backoff.note_success(address);
+				}
+			}
+		}
+	}
+
+	fn address(port: u16) -> SocketAddr {
+		SocketAddr::from_str(&format!("127.0.0.1:{}", port)).unwrap()
+	}
+
+	fn rng() -> XorShiftRng {
+		XorShiftRng::from_seed([1, 2, 3, 4])
+	}
+
+	fn backoff() -> DialBackoff {
+		DialBackoff::new(Duration::from_secs(10), Duration::from_secs(600))
+	}
+
+	#[test]
+	fn failed_endpoint_not_redialed_until_backoff_elapses() {
+		let mut backoff = backoff();
+		let mut rng = rng();
+		let start = Instant::now();
+		let failing = address(30303);
+		let mut dialer = MockDialer::new(&[], &[failing]);
+
+		dialer.dial_round(&mut backoff, &[failing], start, &mut rng);
+		assert_eq!(dialer.dialed.len(), 1);
+
+		// the first delay is at least half of the base delay
+		dialer.dial_round(&mut backoff, &[failing], start + Duration::from_millis(4_999), &mut rng);
+		assert_eq!(dialer.dialed.len(), 1);
+
+		// and at most the full base delay
+		dialer.dial_round(&mut backoff, &[failing], start + Duration::from_secs(10), &mut rng);
+		assert_eq!(dialer.dialed.len(), 2);
+	}
+
+	#[test]
+	fn healthy_endpoint_always_dialed() {
+		let mut backoff = backoff();
+		let mut rng = rng();
+		let start = Instant::now();
+		let healthy = address(30303);
+		let mut dialer = MockDialer::new(&[], &[]);
+
+		dialer.dial_round(&mut backoff, &[healthy], start, &mut rng);
+		dialer.dial_round(&mut backoff, &[healthy], start, &mut rng);
+		assert_eq!(dialer.dialed, vec![healthy, healthy]);
+	}
+
+	#[test]
+	fn delay_doubles_and_is_capped() {
+		let mut backoff = backoff();
+		let mut rng = rng();
+		let now = Instant::now();
+		let failing = address(30303);
+
+		for i in 0..10 {
+			let expected = min(10_000u64 << i, 600_000);
+			let delay = to_millis(backoff.note_failure(failing, DialFailure::Timeout, now, &mut rng));
+			assert!(delay >= expected / 2 && delay <= expected, "failure {}: {} not in [{}, {}]", i + 1, delay, expected / 2, expected);
+		}
+	}
+
+	#[test]
+	fn refused_backs_off_longer_than_timeout() {
+		let mut backoff = backoff();
+		let mut rng = rng();
+		let now = Instant::now();
+
+		for port in 0..50 {
+			let refused = backoff.note_failure(address(port), DialFailure::Refused, now, &mut rng);
+			let timed_out = backoff.note_failure(address(port + 1000), DialFailure::Timeout, now, &mut rng);
+			assert!(refused > timed_out);
+		}
+	}
+
+	#[test]
+	fn jitter_spreads_retries() {
+		let mut backoff = backoff();
+		let mut rng = rng();
+		let now = Instant::now();
+
+		let retries: HashSet<_> = (0..50)
+			.map(|port| backoff.note_failure(address(port), DialFailure::Timeout, now, &mut rng))
+			.collect();
+		assert!(retries.len() > 40);
+		assert!(retries.iter().all(|d| *d >= Duration::from_secs(5) && *d <= Duration::from_secs(10)));
+	}
+
+	#[test]
+	fn fixed_schedule_when_base_equals_max() {
+		let mut backoff = DialBackoff::new(Duration::from_secs(10), Duration::from_secs(10));
+		let mut rng = rng();
+		let now = Instant::now();
+		let endpoint = address(30303);
+
+		for _ in 0..10 {
+			let delay = backoff.note_failure(endpoint, DialFailure::Refused, now, &mut rng);
+			assert!(delay >= Duration::from_secs(5) && delay <= Duration::from_secs(10));
+		}
+	}
+
+	#[test]
+	fn success_resets_backoff() {
+		let mut backoff = backoff();
+		let mut rng = rng();
+		let now = Instant::now();
+		let endpoint = address(30303);
+
+		for _ in 0..5 {
+			backoff.note_failure(endpoint, DialFailure::Timeout, now, &mut rng);
+		}
+		backoff.note_success(&endpoint);
+		assert!(backoff.can_dial(&endpoint, now));
+		assert!(backoff.note_failure(endpoint, DialFailure::Timeout, now, &mut rng) <= Duration::from_secs(10));
+	}
+
+	#[test]
+	fn prune_drops_stale_entries() {
+		let mut backoff = backoff();
+		let mut rng = rng();
+		let now = Instant::now();
+		backoff.note_failure(address(30303), DialFailure::Timeout, now, &mut rng);
+
+		backoff.prune(now + Duration::from_secs(60));
+		assert_eq!(backoff.entries.len(), 1);
+		backoff.prune(now + Duration::from_secs(700));
+		assert!(backoff.entries.is_empty());
+	}
+}
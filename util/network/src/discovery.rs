@@ -592,6 +592,51 @@ mod tests {
 		assert_eq!(Discovery::nearest_node_entries(&NodeId::new(), &discovery2.node_buckets).len(), 3)
 	}
 
+	#[test]
+	fn refresh_restarts_discovery() {
+		fn exchange(discovery1: &mut Discovery, ep1: &NodeEndpoint, discovery2: &mut Discovery, ep2: &NodeEndpoint) {
+			for _ in 0 .. 10 {
+				while !discovery1.send_queue.is_empty() {
+					let datagramm = discovery1.send_queue.pop_front().unwrap();
+					if datagramm.address == ep2.address {
+						discovery2.on_packet(&datagramm.payload, ep1.address.clone()).ok();
+					}
+				}
+				while !discovery2.send_queue.is_empty() {
+					let datagramm = discovery2.send_queue.pop_front().unwrap();
+					if datagramm.address == ep1.address {
+						discovery1.on_packet(&datagramm.payload, ep2.address.clone()).ok();
+					}
+				}
+				discovery2.round();
+			}
+		}
+
+		let key1 = Random.generate().unwrap();
+		let key2 = Random.generate().unwrap();
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40448").unwrap(), udp_port: 40448 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40449").unwrap(), udp_port: 40449 };
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0);
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0);
+
+		discovery2.add_node(NodeEntry { id: key1.public().clone(), endpoint: ep1.clone() });
+		discovery2.refresh();
+		exchange(&mut discovery1, &ep1, &mut discovery2, &ep2);
+		assert_eq!(discovery2.discovery_round, DISCOVERY_MAX_STEPS);
+
+		// a node learned after the lookup completed is not found until the next refresh
+		let node = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7770").unwrap();
+		discovery1.add_node(NodeEntry { id: node.id.clone(), endpoint: node.endpoint.clone() });
+		exchange(&mut discovery1, &ep1, &mut discovery2, &ep2);
+		assert_eq!(Discovery::nearest_node_entries(&NodeId::new(), &discovery2.node_buckets).len(), 1);
+
+		discovery2.refresh();
+		exchange(&mut discovery1, &ep1, &mut discovery2, &ep2);
+		let nearest = Discovery::nearest_node_entries(&NodeId::new(), &discovery2.node_buckets);
+		assert_eq!(nearest.len(), 2);
+		assert!(nearest.iter().any(|n| n.id == node.id));
+	}
+
 	#[test]
 	fn removes_expired() {
 		let key = Random.generate().unwrap();
@@ -36,6 +36,7 @@ pub enum DisconnectReason
 	UnexpectedIdentity,
 	LocalIdentity,
 	PingTimeout,
+	DecryptionFailure,
 	Unknown,
 }
 
@@ -54,6 +55,7 @@ impl DisconnectReason {
 			9 => DisconnectReason::UnexpectedIdentity,
 			10 => DisconnectReason::LocalIdentity,
 			11 => DisconnectReason::PingTimeout,
+			12 => DisconnectReason::DecryptionFailure,
 			_ => DisconnectReason::Unknown,
 		}
 	}
@@ -76,6 +78,7 @@ impl fmt::Display for DisconnectReason {
 			UnexpectedIdentity => "unexpected identity",
 			LocalIdentity => "local identity",
 			PingTimeout => "ping timeout",
+			DecryptionFailure => "decryption failure",
 			Unknown => "unknown",
 		};
 
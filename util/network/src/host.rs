@@ -20,7 +20,8 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
 use std::ops::*;
-use std::cmp::min;
+use std::cmp::{min, max};
+use std::time::{Duration, Instant};
 use std::path::{Path, PathBuf};
 use std::io::{Read, Write};
 use std::fs;
@@ -29,16 +30,18 @@ use mio::*;
 use mio::tcp::*;
 use util::hash::*;
 use util::Hashable;
-use util::version;
+use util::{version, version_with_identity};
 use rlp::*;
 use session::{Session, SessionData};
 use error::*;
 use io::*;
 use {NetworkProtocolHandler, NonReservedPeerMode, PROTOCOL_VERSION};
 use node_table::*;
-use stats::NetworkStats;
+use stats::{NetworkStats, HandshakeFailure};
 use discovery::{Discovery, TableUpdates, NodeEntry};
 use ip_utils::{map_external_address, select_public_address};
+use dial_backoff::{DialBackoff, DialFailure, DEFAULT_DIAL_BACKOFF_MS, DEFAULT_MAX_DIAL_BACKOFF_MS};
+use session::DEFAULT_READ_ERROR_GRACE_MS;
 use util::path::restrict_permissions_owner;
 use parking_lot::{Mutex, RwLock};
 
@@ -48,6 +51,9 @@ const MAX_SESSIONS: usize = 1024 + MAX_HANDSHAKES;
 const MAX_HANDSHAKES: usize = 80;
 const MAX_HANDSHAKES_PER_ROUND: usize = 32;
 const MAINTENANCE_TIMEOUT: u64 = 1000;
+const DISCOVERY_REFRESH_TIMEOUT: u64 = 7200;
+// Reserved peers are re-dialed on a fixed schedule instead of backing off exponentially.
+const DEFAULT_RESERVED_DIAL_INTERVAL_MS: u64 = 10_000;
 
 #[derive(Debug, PartialEq, Clone)]
 /// Network service configuration
@@ -66,6 +72,8 @@ pub struct NetworkConfiguration {
 	pub nat_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Interval between discovery bucket refreshes. Default interval is used if none.
+	pub discovery_refresh_interval: Option<Duration>,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -78,6 +86,19 @@ pub struct NetworkConfiguration {
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub non_reserved_mode: NonReservedPeerMode,
+	/// Node name advertised in the client version string.
+	pub identity: String,
+	/// Delay before re-dialing an endpoint after its first failed dial. Doubles with each further failure. Default delay is used if none.
+	pub dial_backoff: Option<Duration>,
+	/// Upper bound on the delay between dials to a failing endpoint. Default bound is used if none.
+	pub max_dial_backoff: Option<Duration>,
+	/// Delay before re-dialing a reserved node after a failed dial, regardless of the number of failures. Default delay is used if none.
+	pub reserved_dial_interval: Option<Duration>,
+	/// Time a session may keep failing to read with interrupted, would-block or timed-out reads before it is dropped. Default period is used if none.
+	pub read_error_grace: Option<Duration>,
+	/// Offer snappy compression of capability packets to peers by advertising p2p protocol version 5.
+	/// Used only with peers advertising version 5 or later as well.
+	pub packet_compression: bool,
 }
 
 impl Default for NetworkConfiguration {
@@ -97,12 +118,19 @@ impl NetworkConfiguration {
 			udp_port: None,
 			nat_enabled: true,
 			discovery_enabled: true,
+			discovery_refresh_interval: None,
 			boot_nodes: Vec::new(),
 			use_secret: None,
 			min_peers: 25,
 			max_peers: 50,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			identity: String::new(),
+			dial_backoff: None,
+			max_dial_backoff: None,
+			reserved_dial_interval: None,
+			read_error_grace: None,
+			packet_compression: false,
 		}
 	}
 
@@ -120,6 +148,11 @@ impl NetworkConfiguration {
 		config.nat_enabled = false;
 		config
 	}
+
+	/// Interval between discovery bucket refreshes in milliseconds.
+	pub fn discovery_refresh_ms(&self) -> u64 {
+		self.discovery_refresh_interval.map_or(DISCOVERY_REFRESH_TIMEOUT, |d| max(1, d.as_secs() * 1000 + (d.subsec_nanos() / 1_000_000) as u64))
+	}
 }
 
 // Tokens
@@ -170,6 +203,15 @@ pub enum NetworkIoMessage {
 	NetworkStarted(String),
 }
 
+/// Connection status of a reserved peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReservedPeerStatus {
+	/// Node URL of the peer.
+	pub url: String,
+	/// Whether there is an established session with the peer.
+	pub connected: bool,
+}
+
 /// Local (temporary) peer session ID.
 pub type PeerId = usize;
 
@@ -283,6 +325,16 @@ impl<'s> NetworkContext<'s> {
 		"unknown".to_owned()
 	}
 
+	/// Returns the public key of the peer node, if known.
+	pub fn peer_node_id(&self, peer: PeerId) -> Option<NodeId> {
+		self.resolve_session(peer).and_then(|s| s.lock().info.id.clone())
+	}
+
+	/// Whether capability packets exchanged with the peer are compressed.
+	pub fn peer_compression(&self, peer: PeerId) -> bool {
+		self.resolve_session(peer).map_or(false, |s| s.lock().info.compression)
+	}
+
 	/// Returns max version for a given protocol.
 	pub fn protocol_version(&self, peer: PeerId, protocol: &str) -> Option<u8> {
 		let session = self.resolve_session(peer);
@@ -326,6 +378,33 @@ impl HostInfo {
 		self.nonce = self.nonce.sha3();
 		self.nonce.clone()
 	}
+
+	/// Time a session may keep failing to read with transient errors.
+	pub fn read_error_grace(&self) -> Duration {
+		self.config.read_error_grace.unwrap_or(Duration::from_millis(DEFAULT_READ_ERROR_GRACE_MS))
+	}
+
+	/// Whether capability packets should be compressed for peers supporting it.
+	pub fn packet_compression(&self) -> bool {
+		self.config.packet_compression
+	}
+}
+
+#[cfg(test)]
+impl HostInfo {
+	/// Info of a local host with random keys supporting given capabilities.
+	pub fn new_test(capabilities: Vec<CapabilityInfo>) -> HostInfo {
+		HostInfo {
+			keys: Random.generate().unwrap(),
+			config: NetworkConfiguration::new_local(),
+			nonce: H256::random(),
+			protocol_version: PROTOCOL_VERSION,
+			client_version: "Parity/test".into(),
+			capabilities: capabilities,
+			local_endpoint: NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:30303").unwrap(), udp_port: 30303 },
+			public_endpoint: None,
+		}
+	}
 }
 
 type SharedSession = Arc<Mutex<Session>>;
@@ -348,7 +427,12 @@ pub struct Host {
 	timer_counter: RwLock<usize>,
 	stats: Arc<NetworkStats>,
 	reserved_nodes: RwLock<HashSet<NodeId>>,
+	dial_backoff: Mutex<DialBackoff>,
+	reserved_dial_backoff: Mutex<DialBackoff>,
+	resolver: Arc<Resolver>,
+	unresolved_nodes: Mutex<UnresolvedNodes>,
 	num_sessions: AtomicUsize,
+	num_inbound_sessions: AtomicUsize,
 	stopping: AtomicBool,
 }
 
@@ -384,6 +468,19 @@ impl Host {
 
 		let boot_nodes = config.boot_nodes.clone();
 		let reserved_nodes = config.reserved_nodes.clone();
+		let client_version = Host::client_version(&config.identity);
+		let dial_backoff = DialBackoff::new(
+			config.dial_backoff.unwrap_or(Duration::from_millis(DEFAULT_DIAL_BACKOFF_MS)),
+			config.max_dial_backoff.unwrap_or(Duration::from_millis(DEFAULT_MAX_DIAL_BACKOFF_MS)));
+		let reserved_dial_interval = config.reserved_dial_interval.unwrap_or(Duration::from_millis(DEFAULT_RESERVED_DIAL_INTERVAL_MS));
+
+		// saved nodes given by host name are resolved later, along with other unresolved nodes
+		let nodes = NodeTable::new(path);
+		let mut unresolved_nodes = UnresolvedNodes::new();
+		let now = Instant::now();
+		for url in nodes.unresolved() {
+			unresolved_nodes.queue(url, false, now);
+		}
 
 		let mut host = Host {
 			info: RwLock::new(HostInfo {
@@ -391,7 +488,7 @@ impl Host {
 				config: config,
 				nonce: H256::random(),
 				protocol_version: PROTOCOL_VERSION,
-				client_version: version(),
+				client_version: client_version,
 				capabilities: Vec::new(),
 				public_endpoint: None,
 				local_endpoint: local_endpoint,
@@ -399,13 +496,18 @@ impl Host {
 			discovery: Mutex::new(None),
 			tcp_listener: Mutex::new(tcp_listener),
 			sessions: Arc::new(RwLock::new(Slab::new_starting_at(FIRST_SESSION, MAX_SESSIONS))),
-			nodes: RwLock::new(NodeTable::new(path)),
+			nodes: RwLock::new(nodes),
 			handlers: RwLock::new(HashMap::new()),
 			timers: RwLock::new(HashMap::new()),
 			timer_counter: RwLock::new(USER_TIMER),
 			stats: stats,
 			reserved_nodes: RwLock::new(HashSet::new()),
+			dial_backoff: Mutex::new(dial_backoff),
+			reserved_dial_backoff: Mutex::new(DialBackoff::new(reserved_dial_interval, reserved_dial_interval)),
+			resolver: Arc::new(SystemResolver),
+			unresolved_nodes: Mutex::new(unresolved_nodes),
 			num_sessions: AtomicUsize::new(0),
+			num_inbound_sessions: AtomicUsize::new(0),
 			stopping: AtomicBool::new(false),
 		};
 
@@ -422,31 +524,57 @@ impl Host {
 	}
 
 	pub fn add_node(&mut self, id: &str) {
-		match Node::from_str(id) {
+		match NodeUrl::from_str(id) {
 			Err(e) => { debug!(target: "network", "Could not add node {}: {:?}", id, e); },
-			Ok(n) => {
-				let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id.clone() };
-
-				self.nodes.write().add_node(n);
-				if let Some(ref mut discovery) = *self.discovery.lock() {
-					discovery.add_node(entry);
-				}
-			}
+			Ok(url) => self.resolve_node(url, false),
 		}
 	}
 
+	/// Add a reserved node. Host names which fail to resolve are retried later.
 	pub fn add_reserved_node(&self, id: &str) -> Result<(), NetworkError> {
-		let n = try!(Node::from_str(id));
+		let url = try!(NodeUrl::from_str(id));
+		self.reserved_nodes.write().insert(url.id.clone());
+		self.resolve_node(url, true);
+		Ok(())
+	}
 
-		let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id.clone() };
-		self.reserved_nodes.write().insert(n.id.clone());
-		self.nodes.write().add_node(Node::new(entry.id.clone(), entry.endpoint.clone()));
+	fn resolve_node(&self, url: NodeUrl, reserved: bool) {
+		match url.resolve(&*self.resolver) {
+			Ok(n) => self.add_resolved_node(n),
+			Err(e) => {
+				debug!(target: "network", "Could not resolve {}: {:?}, will retry", url.host, e);
+				self.unresolved_nodes.lock().add(url, reserved, Instant::now());
+			}
+		}
+	}
 
+	fn add_resolved_node(&self, n: Node) {
+		let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id.clone() };
+		self.nodes.write().add_node(n);
 		if let Some(ref mut discovery) = *self.discovery.lock() {
 			discovery.add_node(entry);
 		}
+	}
 
-		Ok(())
+	fn resolve_pending_nodes(&self) {
+		let due = {
+			let mut unresolved = self.unresolved_nodes.lock();
+			if unresolved.is_empty() {
+				return;
+			}
+			unresolved.take_due(Instant::now())
+		};
+		// resolving may block, so no lock is held meanwhile.
+		for entry in due {
+			// the node may have been removed from reserved ones meanwhile.
+			if entry.is_reserved() && !self.reserved_nodes.read().contains(&entry.url().id) {
+				continue;
+			}
+			match entry.url().resolve(&*self.resolver) {
+				Ok(n) => self.add_resolved_node(n),
+				Err(_) => self.unresolved_nodes.lock().retry(entry, Instant::now()),
+			}
+		}
 	}
 
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode, io: &IoContext<NetworkIoMessage>) {
@@ -480,14 +608,34 @@ impl Host {
 	}
 
 	pub fn remove_reserved_node(&self, id: &str) -> Result<(), NetworkError> {
-		let n = try!(Node::from_str(id));
+		let n = try!(NodeUrl::from_str(id));
 		self.reserved_nodes.write().remove(&n.id);
+		self.unresolved_nodes.lock().remove(&n.id);
 
 		Ok(())
 	}
 
-	pub fn client_version() -> String {
-		version()
+	/// Get connection status of all reserved peers, ordered by node URL.
+	pub fn reserved_peers_status(&self) -> Vec<ReservedPeerStatus> {
+		let reserved: Vec<NodeId> = self.reserved_nodes.read().iter().cloned().collect();
+		let urls: Vec<String> = {
+			let nodes = self.nodes.read();
+			reserved.iter().map(|id| nodes.get(id).map_or_else(|| format!("enode://{}", id.hex()), |n| format!("{}", n))).collect()
+		};
+		let mut status: Vec<_> = reserved.iter().zip(urls.into_iter()).map(|(id, url)| ReservedPeerStatus {
+			url: url,
+			connected: self.have_ready_session(id),
+		}).collect();
+		status.sort_by(|a, b| a.url.cmp(&b.url));
+		status
+	}
+
+	/// Client version string sent in the `Hello` packet by a node with given identity.
+	pub fn client_version(identity: &str) -> String {
+		match identity.is_empty() {
+			true => version(),
+			false => version_with_identity(identity),
+		}
 	}
 
 	pub fn external_url(&self) -> Option<String> {
@@ -562,7 +710,8 @@ impl Host {
 			discovery.add_node_list(self.nodes.read().unordered_entries());
 			*self.discovery.lock() = Some(discovery);
 			io.register_stream(DISCOVERY).expect("Error registering UDP listener");
-			io.register_timer(DISCOVERY_REFRESH, 7200).expect("Error registering discovery timer");
+			let refresh_ms = self.info.read().config.discovery_refresh_ms();
+			io.register_timer(DISCOVERY_REFRESH, refresh_ms).expect("Error registering discovery timer");
 			io.register_timer(DISCOVERY_ROUND, 300).expect("Error registering discovery timer");
 		}
 		try!(io.register_timer(NODE_TABLE, 300_000));
@@ -572,6 +721,9 @@ impl Host {
 
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage>) {
 		self.keep_alive(io);
+		self.dial_backoff.lock().prune(Instant::now());
+		self.reserved_dial_backoff.lock().prune(Instant::now());
+		self.resolve_pending_nodes();
 		self.connect_peers(io);
 	}
 
@@ -583,6 +735,20 @@ impl Host {
 		self.num_sessions.load(AtomicOrdering::Relaxed)
 	}
 
+	/// Get the number of ready sessions as `(inbound, outbound)`.
+	pub fn session_counts(&self) -> (usize, usize) {
+		let total = self.num_sessions.load(AtomicOrdering::Relaxed);
+		let inbound = self.num_inbound_sessions.load(AtomicOrdering::Relaxed);
+		(inbound, total.saturating_sub(inbound))
+	}
+
+	fn have_ready_session(&self, id: &NodeId) -> bool {
+		self.sessions.read().iter().any(|e| {
+			let s = e.lock();
+			s.is_ready() && s.id() == Some(id)
+		})
+	}
+
 	fn connecting_to(&self, id: &NodeId) -> bool {
 		self.sessions.read().iter().any(|e| e.lock().id() == Some(id))
 	}
@@ -632,29 +798,38 @@ impl Host {
 		let handshake_count = self.handshake_count();
 		// allow 16 slots for incoming connections
 		let handshake_limit = MAX_HANDSHAKES - 16;
-		if handshake_count >= handshake_limit {
-			return;
-		}
+		let handshake_slots = if handshake_count >= handshake_limit {
+			// reserved peers are dialed even if all slots are taken.
+			pin = true;
+			reserved_nodes.len()
+		} else {
+			min(MAX_HANDSHAKES_PER_ROUND, handshake_limit - handshake_count)
+		};
 
 		// iterate over all nodes, reserved ones coming first.
 		// if we are pinned to only reserved nodes, ignore all others.
-		let nodes = reserved_nodes.iter().cloned().chain(if !pin {
-			self.nodes.read().nodes()
+		let others = if !pin {
+			self.nodes.read().nodes().into_iter().filter(|id| !reserved_nodes.contains(id)).collect()
 		} else {
 			Vec::new()
-		});
+		};
+		let nodes: Vec<_> = reserved_nodes.iter().map(|id| (id.clone(), true))
+			.chain(others.into_iter().map(|id| (id, false)))
+			.filter(|&(ref id, reserved)| !self.have_session(id) && !self.connecting_to(id) && self.can_dial(id, reserved))
+			.take(handshake_slots)
+			.collect();
+		drop(reserved_nodes);
 
 		let mut started: usize = 0;
-		for id in nodes.filter(|ref id| !self.have_session(id) && !self.connecting_to(id))
-			.take(min(MAX_HANDSHAKES_PER_ROUND, handshake_limit - handshake_count)) {
-			self.connect_peer(&id, io);
+		for (id, reserved) in nodes {
+			self.connect_peer(&id, reserved, io);
 			started += 1;
 		}
 		debug!(target: "network", "Connecting peers: {} sessions, {} pending, {} started", self.session_count(), self.handshake_count(), started);
 	}
 
 	#[cfg_attr(feature="dev", allow(single_match))]
-	fn connect_peer(&self, id: &NodeId, io: &IoContext<NetworkIoMessage>) {
+	fn connect_peer(&self, id: &NodeId, reserved: bool, io: &IoContext<NetworkIoMessage>) {
 		if self.have_session(id)
 		{
 			trace!(target: "network", "Aborted connect. Node already connected.");
@@ -681,6 +856,7 @@ impl Host {
 				Ok(socket) => socket,
 				Err(e) => {
 					debug!(target: "network", "Can't connect to address {:?}: {:?}", address, e);
+					self.note_dial_failure(id, reserved, DialFailure::from_io(&e));
 					return;
 				}
 			}
@@ -690,6 +866,49 @@ impl Host {
 		}
 	}
 
+	fn backoff_for(&self, reserved: bool) -> &Mutex<DialBackoff> {
+		match reserved {
+			true => &self.reserved_dial_backoff,
+			false => &self.dial_backoff,
+		}
+	}
+
+	fn can_dial(&self, id: &NodeId, reserved: bool) -> bool {
+		let address = match self.nodes.read().get(id) {
+			Some(node) => node.endpoint.address,
+			None => return true,
+		};
+		self.backoff_for(reserved).lock().can_dial(&address, Instant::now())
+	}
+
+	fn note_dial_failure(&self, id: &NodeId, reserved: bool, failure: DialFailure) {
+		let address = match self.nodes.read().get(id) {
+			Some(node) => node.endpoint.address,
+			None => return,
+		};
+		let delay = self.backoff_for(reserved).lock().note_failure(address, failure, Instant::now(), &mut ::rand::thread_rng());
+		trace!(target: "network", "Dial to {} failed ({:?}), retrying in {:?}", address, failure, delay);
+
+		// nodes given by host name may have moved to another address.
+		// resolving may block, so the node table is only locked to update it.
+		let host = self.nodes.read().host(id);
+		if let Some((host, port)) = host {
+			match NodeEndpoint::resolve(&host, port, &*self.resolver) {
+				Ok(endpoint) => if self.nodes.write().set_endpoint(id, endpoint) {
+					debug!(target: "network", "Node {} moved to another address", id);
+				},
+				Err(e) => debug!(target: "network", "Could not resolve node {} again: {:?}", id, e),
+			}
+		}
+	}
+
+	fn note_dial_success(&self, id: &NodeId) {
+		if let Some(node) = self.nodes.read().get(id) {
+			self.dial_backoff.lock().note_success(&node.endpoint.address);
+			self.reserved_dial_backoff.lock().note_success(&node.endpoint.address);
+		}
+	}
+
 	#[cfg_attr(feature="dev", allow(block_in_if_condition_stmt))]
 	fn create_connection(&self, socket: TcpStream, id: Option<&NodeId>, io: &IoContext<NetworkIoMessage>) -> Result<(), NetworkError> {
 		let nonce = self.info.write().next_nonce();
@@ -763,6 +982,12 @@ impl Host {
 				match session_result {
 					Err(e) => {
 						trace!(target: "network", "Session read error: {}:{:?} ({:?}) {:?}", token, s.id(), s.remote_addr(), e);
+						if s.info.originated && !s.is_ready() {
+							if let Some(id) = s.id() {
+								let reserved = self.reserved_nodes.read().contains(id);
+								self.note_dial_failure(id, reserved, dial_failure(&e));
+							}
+						}
 						if let NetworkError::Disconnect(DisconnectReason::IncompatibleProtocol) = e {
 							if let Some(id) = s.id() {
 								if !self.reserved_nodes.read().contains(id) {
@@ -775,6 +1000,14 @@ impl Host {
 					},
 					Ok(SessionData::Ready) => {
 						self.num_sessions.fetch_add(1, AtomicOrdering::SeqCst);
+						if !s.info.originated {
+							self.num_inbound_sessions.fetch_add(1, AtomicOrdering::SeqCst);
+						}
+						if s.info.originated {
+							if let Some(id) = s.id() {
+								self.note_dial_success(id);
+							}
+						}
 						if !s.info.originated {
 							let session_count = self.session_count();
 							let (max_peers, reserved_only) = {
@@ -840,6 +1073,19 @@ impl Host {
 
 	fn connection_timeout(&self, token: StreamToken, io: &IoContext<NetworkIoMessage>) {
 		trace!(target: "network", "Connection timeout: {}", token);
+		let session = { self.sessions.read().get(token).cloned() };
+		if let Some(session) = session {
+			let mut s = session.lock();
+			if !s.is_ready() {
+				s.note_handshake_failure(HandshakeFailure::Timeout);
+				if s.info.originated {
+					if let Some(id) = s.id() {
+						let reserved = self.reserved_nodes.read().contains(id);
+						self.note_dial_failure(id, reserved, DialFailure::Timeout);
+					}
+				}
+			}
+		}
 		self.kill_connection(token, io, true)
 	}
 
@@ -856,6 +1102,9 @@ impl Host {
 				if !s.expired() {
 					if s.is_ready() {
 						self.num_sessions.fetch_sub(1, AtomicOrdering::SeqCst);
+						if !s.info.originated {
+							self.num_inbound_sessions.fetch_sub(1, AtomicOrdering::SeqCst);
+						}
 						for (p, _) in self.handlers.read().iter() {
 							if s.have_capability(p)  {
 								to_disconnect.push(p);
@@ -1094,6 +1343,14 @@ impl IoHandler<NetworkIoMessage> for Host {
 	}
 }
 
+/// Classify a session error seen before the handshake completed.
+fn dial_failure(e: &NetworkError) -> DialFailure {
+	match *e {
+		NetworkError::StdIo(ref e) | NetworkError::Io(IoError::Mio(ref e)) | NetworkError::Io(IoError::StdIo(ref e)) => DialFailure::from_io(e),
+		_ => DialFailure::Timeout,
+	}
+}
+
 fn save_key(path: &Path, key: &Secret) {
 	let mut path_buf = PathBuf::from(path);
 	if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
@@ -1163,3 +1420,23 @@ fn host_client_url() {
 	let host: Host = Host::new(config, Arc::new(NetworkStats::new())).unwrap();
 	assert!(host.local_url().starts_with("enode://101b3ef5a4ea7a1c7928e24c4c75fd053c235d7b80c22ae5c03d145d0ac7396e2a4ffff9adee3133a7b05044a5cee08115fd65145e5165d646bde371010d803c@"));
 }
+
+#[test]
+fn discovery_refresh_interval() {
+	let mut config = NetworkConfiguration::new();
+	assert_eq!(config.discovery_refresh_ms(), DISCOVERY_REFRESH_TIMEOUT);
+	config.discovery_refresh_interval = Some(Duration::from_secs(60));
+	assert_eq!(config.discovery_refresh_ms(), 60_000);
+	config.discovery_refresh_interval = Some(Duration::from_millis(250));
+	assert_eq!(config.discovery_refresh_ms(), 250);
+	config.discovery_refresh_interval = Some(Duration::new(0, 0));
+	assert_eq!(config.discovery_refresh_ms(), 1);
+}
+
+#[test]
+fn client_version_contains_identity() {
+	assert_eq!(Host::client_version(""), version());
+	let v = Host::client_version("mynode");
+	assert!(v.starts_with("Parity/mynode/v"));
+	assert_eq!(v.replace("mynode/", ""), version());
+}
@@ -59,6 +59,7 @@ impl SocketAddrExt for Ipv6Addr {
 #[cfg(not(windows))]
 mod getinterfaces {
 	use std::{mem, io, ptr};
+	use std::ffi::CStr;
 	use libc::{AF_INET, AF_INET6};
 	use libc::{getifaddrs, freeifaddrs, ifaddrs, sockaddr, sockaddr_in, sockaddr_in6};
 	use std::net::{Ipv4Addr, Ipv6Addr, IpAddr};
@@ -99,12 +100,18 @@ mod getinterfaces {
 		Some(addr)
 	}
 
-	fn convert_ifaddrs (ifa: *mut ifaddrs) -> Option<IpAddr> {
+	fn convert_ifaddrs (ifa: *mut ifaddrs) -> Option<(String, IpAddr)> {
 		let ifa = unsafe { &mut *ifa };
-		convert_sockaddr(ifa.ifa_addr)
+		if ifa.ifa_name == ptr::null_mut() { return None; }
+		let name = unsafe { CStr::from_ptr(ifa.ifa_name) }.to_string_lossy().into_owned();
+		convert_sockaddr(ifa.ifa_addr).map(|addr| (name, addr))
 	}
 
 	pub fn get_all() -> io::Result<Vec<IpAddr>> {
+		get_named().map(|addrs| addrs.into_iter().map(|(_, addr)| addr).collect())
+	}
+
+	pub fn get_named() -> io::Result<Vec<(String, IpAddr)>> {
 		let mut ifap: *mut ifaddrs = unsafe { mem::zeroed() };
 		if unsafe { getifaddrs(&mut ifap as *mut _) } != 0 {
 			return Err(io::Error::last_os_error());
@@ -113,8 +120,8 @@ mod getinterfaces {
 		let mut ret = Vec::new();
 		let mut cur: *mut ifaddrs = ifap;
 		while cur != ptr::null_mut() {
-			if let Some(ip_addr) = convert_ifaddrs(cur) {
-				ret.push(ip_addr);
+			if let Some(named_addr) = convert_ifaddrs(cur) {
+				ret.push(named_addr);
 			}
 
 			//TODO: do something else maybe?
@@ -136,6 +143,25 @@ fn get_if_addrs() -> io::Result<Vec<IpAddr>> {
 	Ok(Vec::new())
 }
 
+#[cfg(not(windows))]
+fn get_named_if_addrs() -> io::Result<Vec<(String, IpAddr)>> {
+	getinterfaces::get_named()
+}
+
+#[cfg(windows)]
+fn get_named_if_addrs() -> io::Result<Vec<(String, IpAddr)>> {
+	Ok(Vec::new())
+}
+
+/// Resolve a network interface name (e.g. `eth0`) to its first address.
+/// Returns `None` if there is no such interface or it has no address.
+pub fn interface_address(name: &str) -> Option<IpAddr> {
+	match get_named_if_addrs() {
+		Ok(list) => list.into_iter().find(|&(ref n, _)| n == name).map(|(_, addr)| addr),
+		Err(_) => None,
+	}
+}
+
 /// Select the best available public address
 pub fn select_public_address(port: u16) -> SocketAddr {
 	match get_if_addrs() {
@@ -202,6 +228,11 @@ fn can_select_public_address() {
 	assert!(pub_address.port() == 40477);
 }
 
+#[test]
+fn unknown_interface_has_no_address() {
+	assert_eq!(interface_address("no-such-interface0"), None);
+}
+
 #[ignore]
 #[test]
 fn can_map_external_address_or_fail() {
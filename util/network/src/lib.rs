@@ -87,6 +87,7 @@ mod error;
 mod node_table;
 mod stats;
 mod ip_utils;
+mod dial_backoff;
 
 #[cfg(test)]
 mod tests;
@@ -98,12 +99,16 @@ pub use service::NetworkService;
 pub use host::NetworkIoMessage;
 pub use error::NetworkError;
 pub use host::NetworkConfiguration;
-pub use stats::NetworkStats;
+pub use host::ReservedPeerStatus;
+pub use stats::{NetworkStats, HandshakeFailure};
 
 use io::TimerToken;
-pub use node_table::is_valid_node_url;
+pub use node_table::{NodeId, is_valid_node_url};
+pub use ip_utils::interface_address;
 
 const PROTOCOL_VERSION: u32 = 4;
+/// First p2p protocol version with snappy compressed capability packets.
+const COMPRESSION_PROTOCOL_VERSION: u32 = 5;
 
 /// Network IO protocol handler. This needs to be implemented for each new subprotocol.
 /// All the handler function are called from within IO event loop.
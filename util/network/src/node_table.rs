@@ -16,7 +16,7 @@
 
 use std::mem;
 use std::slice::from_raw_parts;
-use std::net::{SocketAddr, ToSocketAddrs, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr};
+use std::net::{SocketAddr, ToSocketAddrs, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr, IpAddr};
 use std::hash::{Hash, Hasher};
 use std::str::{FromStr};
 use std::collections::{HashMap, HashSet};
@@ -24,7 +24,9 @@ use std::fmt::{Display, Formatter};
 use std::path::{PathBuf};
 use std::fmt;
 use std::fs;
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
+use std::cmp::min;
+use std::time::{Duration, Instant};
 use util::hash::*;
 use util::UtilError;
 use rlp::*;
@@ -37,6 +39,26 @@ pub use rustc_serialize::json::Json;
 /// Node public key
 pub type NodeId = H512;
 
+/// Delay before the first retry of a host name which failed to resolve.
+const RESOLVE_RETRY_MS: u64 = 5_000;
+/// Upper bound on the delay between retries of a host name which failed to resolve.
+const MAX_RESOLVE_RETRY_MS: u64 = 300_000;
+
+/// Resolves host names to socket addresses.
+pub trait Resolver: Send + Sync {
+	/// Resolve the host and return its addresses with the given port.
+	fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>>;
+}
+
+/// Resolver using the system name service.
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+	fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+		(host, port).to_socket_addrs().map(|addresses| addresses.collect())
+	}
+}
+
 #[derive(Debug, Clone)]
 /// Node address info
 pub struct NodeEndpoint {
@@ -124,6 +146,78 @@ impl FromStr for NodeEndpoint {
 	}
 }
 
+impl NodeEndpoint {
+	/// Create endpoint from a host name or IP address. Only host names are passed to the resolver.
+	pub fn resolve(host: &str, port: u16, resolver: &Resolver) -> Result<NodeEndpoint, NetworkError> {
+		let address = match host.parse::<IpAddr>() {
+			Ok(ip) => Ok(Some(SocketAddr::new(ip, port))),
+			Err(_) => resolver.resolve(host, port).map(|addresses| addresses.into_iter().next()),
+		};
+		match address {
+			Ok(Some(a)) => Ok(NodeEndpoint {
+				address: a,
+				udp_port: a.port()
+			}),
+			Ok(_) => Err(NetworkError::AddressResolve(None)),
+			Err(e) => Err(NetworkError::AddressResolve(Some(e)))
+		}
+	}
+}
+
+/// Node URL as configured, the host may be given by name. Parsing does not resolve the host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeUrl {
+	/// Node public key, zero if not given.
+	pub id: NodeId,
+	/// Host name or IP address.
+	pub host: String,
+	/// Connection port.
+	pub port: u16,
+}
+
+impl NodeUrl {
+	/// Check if the host is given by name rather than by IP address.
+	pub fn is_host_name(&self) -> bool {
+		self.host.parse::<IpAddr>().is_err()
+	}
+
+	/// Resolve the host and create a node. Host names are kept, so that they can be resolved again.
+	pub fn resolve(&self, resolver: &Resolver) -> Result<Node, NetworkError> {
+		let endpoint = try!(NodeEndpoint::resolve(&self.host, self.port, resolver));
+		let mut node = Node::new(self.id.clone(), endpoint);
+		if self.is_host_name() {
+			node.host = Some(self.host.clone());
+		}
+		Ok(node)
+	}
+}
+
+impl FromStr for NodeUrl {
+	type Err = NetworkError;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (id, address) = if s.len() > 136 && &s[0..8] == "enode://" && &s[136..137] == "@" {
+			(try!(s[8..136].parse().map_err(UtilError::from)), &s[137..])
+		}
+		else {
+			(NodeId::new(), s)
+		};
+
+		let invalid = || NetworkError::AddressResolve(Some(io::Error::new(io::ErrorKind::InvalidInput, "invalid socket address")));
+		let split = try!(address.rfind(':').ok_or_else(|| invalid()));
+		let port = try!(address[split + 1..].parse::<u16>().map_err(|_| invalid()));
+		let host = address[..split].trim_left_matches('[').trim_right_matches(']');
+		if host.is_empty() || host.contains(|c: char| c.is_whitespace() || c == '/' || c == '@') {
+			return Err(invalid());
+		}
+
+		Ok(NodeUrl {
+			id: id,
+			host: host.to_owned(),
+			port: port,
+		})
+	}
+}
+
 #[derive(PartialEq, Eq, Copy, Clone)]
 pub enum PeerType {
 	_Required,
@@ -133,6 +227,8 @@ pub enum PeerType {
 pub struct Node {
 	pub id: NodeId,
 	pub endpoint: NodeEndpoint,
+	/// Host name the endpoint was resolved from, if given by name.
+	pub host: Option<String>,
 	pub peer_type: PeerType,
 	pub failures: u32,
 	pub last_attempted: Option<Tm>,
@@ -143,6 +239,7 @@ impl Node {
 		Node {
 			id: id,
 			endpoint: endpoint,
+			host: None,
 			peer_type: PeerType::Optional,
 			failures: 0,
 			last_attempted: None,
@@ -152,7 +249,9 @@ impl Node {
 
 impl Display for Node {
 	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-		if self.endpoint.udp_port != self.endpoint.address.port() {
+		if let Some(ref host) = self.host {
+			try!(write!(f, "enode://{}@{}:{}", self.id.hex(), host, self.endpoint.address.port()));
+		} else if self.endpoint.udp_port != self.endpoint.address.port() {
 			try!(write!(f, "enode://{}@{}+{}", self.id.hex(), self.endpoint.address, self.endpoint.udp_port));
 		} else {
 			try!(write!(f, "enode://{}@{}", self.id.hex(), self.endpoint.address));
@@ -163,21 +262,10 @@ impl Display for Node {
 
 impl FromStr for Node {
 	type Err = NetworkError;
+	/// Parse node URL. Performs name resolution if given a host name.
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let (id, endpoint) = if s.len() > 136 && &s[0..8] == "enode://" && &s[136..137] == "@" {
-			(try!(s[8..136].parse().map_err(UtilError::from)), try!(NodeEndpoint::from_str(&s[137..])))
-		}
-		else {
-			(NodeId::new(), try!(NodeEndpoint::from_str(s)))
-		};
-
-		Ok(Node {
-			id: id,
-			endpoint: endpoint,
-			peer_type: PeerType::Optional,
-			last_attempted: None,
-			failures: 0,
-		})
+		let url = try!(NodeUrl::from_str(s));
+		url.resolve(&SystemResolver)
 	}
 }
 
@@ -197,23 +285,34 @@ impl Hash for Node {
 /// Node table backed by disk file.
 pub struct NodeTable {
 	nodes: HashMap<NodeId, Node>,
+	/// Saved nodes given by host name which have not been resolved yet, with their failure counters.
+	unresolved: HashMap<NodeId, (NodeUrl, u32)>,
 	useless_nodes: HashSet<NodeId>,
 	path: Option<String>,
 }
 
 impl NodeTable {
 	pub fn new(path: Option<String>) -> NodeTable {
+		let (nodes, unresolved) = NodeTable::load(path.clone());
 		NodeTable {
-			path: path.clone(),
-			nodes: NodeTable::load(path),
+			path: path,
+			nodes: nodes,
+			unresolved: unresolved,
 			useless_nodes: HashSet::new(),
 		}
 	}
 
+	/// URLs of saved nodes given by host name. Resolving may block, so it is left to the caller.
+	/// The nodes are kept in the file until added back resolved.
+	pub fn unresolved(&self) -> Vec<NodeUrl> {
+		self.unresolved.values().map(|&(ref url, _)| url.clone()).collect()
+	}
+
 	/// Add a node to table
 	pub fn add_node(&mut self, mut node: Node) {
 		// preserve failure counter
-		let failures = self.nodes.get(&node.id).map_or(0, |n| n.failures);
+		let saved = self.unresolved.remove(&node.id).map(|(_, failures)| failures);
+		let failures = self.nodes.get(&node.id).map(|n| n.failures).or(saved).unwrap_or(0);
 		node.failures = failures;
 		self.nodes.insert(node.id.clone(), node);
 	}
@@ -231,6 +330,11 @@ impl NodeTable {
 		self.nodes.values().map(|n| NodeEntry { endpoint: n.endpoint.clone(), id: n.id.clone() }).collect()
 	}
 
+	/// Get particular node
+	pub fn get(&self, id: &NodeId) -> Option<&Node> {
+		self.nodes.get(id)
+	}
+
 	/// Get particular node
 	pub fn get_mut(&mut self, id: &NodeId) -> Option<&mut Node> {
 		self.nodes.get_mut(id)
@@ -249,6 +353,24 @@ impl NodeTable {
 		}
 	}
 
+	/// Host name and port of a node given by host name, so that it can be resolved again
+	/// e.g. after failing to connect to it.
+	pub fn host(&self, id: &NodeId) -> Option<(String, u16)> {
+		self.nodes.get(id).and_then(|node| node.host.clone().map(|host| (host, node.endpoint.address.port())))
+	}
+
+	/// Update the address of a node. Returns `true` if the node has moved to another address.
+	pub fn set_endpoint(&mut self, id: &NodeId, endpoint: NodeEndpoint) -> bool {
+		match self.nodes.get_mut(id) {
+			Some(node) => {
+				let moved = endpoint.address != node.endpoint.address;
+				node.endpoint = endpoint;
+				moved
+			},
+			None => false,
+		}
+	}
+
 	/// Increase failure counte for a node
 	pub fn note_failure(&mut self, id: &NodeId) {
 		if let Some(node) = self.nodes.get_mut(id) {
@@ -274,13 +396,19 @@ impl NodeTable {
 				return;
 			};
 			path_buf.push("nodes.json");
+			let mut entries: Vec<String> = self.nodes().iter().map(|id| {
+				let node = self.nodes.get(id).unwrap();
+				format!("\t{{ \"url\": \"{}\", \"failures\": {} }}", node, node.failures)
+			}).collect();
+			entries.extend(self.unresolved.values().map(|&(ref url, failures)| {
+				format!("\t{{ \"url\": \"enode://{}@{}:{}\", \"failures\": {} }}", url.id.hex(), url.host, url.port, failures)
+			}));
 			let mut json = String::new();
 			json.push_str("{\n");
 			json.push_str("\"nodes\": [\n");
-			let node_ids = self.nodes();
-			for i in 0 .. node_ids.len() {
-				let node = self.nodes.get(&node_ids[i]).unwrap();
-				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {} }}{}\n", node, node.failures, if i == node_ids.len() - 1 {""} else {","}))
+			json.push_str(&entries.join(",\n"));
+			if !entries.is_empty() {
+				json.push_str("\n");
 			}
 			json.push_str("]\n");
 			json.push_str("}");
@@ -297,8 +425,10 @@ impl NodeTable {
 		}
 	}
 
-	fn load(path: Option<String>) -> HashMap<NodeId, Node> {
+	/// Load saved nodes. Nodes given by host name are returned unresolved, so that loading never blocks.
+	fn load(path: Option<String>) -> (HashMap<NodeId, Node>, HashMap<NodeId, (NodeUrl, u32)>) {
 		let mut nodes: HashMap<NodeId, Node> = HashMap::new();
+		let mut unresolved = HashMap::new();
 		if let Some(path) = path {
 			let mut path_buf = PathBuf::from(path);
 			path_buf.push("nodes.json");
@@ -306,7 +436,7 @@ impl NodeTable {
 				Ok(file) => file,
 				Err(e) => {
 					debug!("Error opening node table file: {:?}", e);
-					return nodes;
+					return (nodes, unresolved);
 				}
 			};
 			let mut buf = String::new();
@@ -314,30 +444,33 @@ impl NodeTable {
 				Ok(_) => {},
 				Err(e) => {
 					warn!("Error reading node table file: {:?}", e);
-					return nodes;
+					return (nodes, unresolved);
 				}
 			}
 			let json = match Json::from_str(&buf) {
 				Ok(json) => json,
 				Err(e) => {
 					warn!("Error parsing node table file: {:?}", e);
-					return nodes;
+					return (nodes, unresolved);
 				}
 			};
 			if let Some(list) = json.as_object().and_then(|o| o.get("nodes")).and_then(|n| n.as_array()) {
 				for n in list.iter().filter_map(|n| n.as_object()) {
-					if let Some(url) = n.get("url").and_then(|u| u.as_string()) {
-						if let Ok(mut node) = Node::from_str(url) {
-							if let Some(failures) = n.get("failures").and_then(|f| f.as_u64()) {
-								node.failures = failures as u32;
-							}
-							nodes.insert(node.id.clone(), node);
-						}
+					let url = match n.get("url").and_then(|u| u.as_string()).and_then(|u| NodeUrl::from_str(u).ok()) {
+						Some(url) => url,
+						None => continue,
+					};
+					let failures = n.get("failures").and_then(|f| f.as_u64()).map_or(0, |f| f as u32);
+					if url.is_host_name() {
+						unresolved.insert(url.id.clone(), (url, failures));
+					} else if let Ok(mut node) = url.resolve(&SystemResolver) {
+						node.failures = failures;
+						nodes.insert(node.id.clone(), node);
 					}
 				}
 			}
 		}
-		nodes
+		(nodes, unresolved)
 	}
 }
 
@@ -347,10 +480,102 @@ impl Drop for NodeTable {
 	}
 }
 
-/// Check if node url is valid
+/// Node URL waiting to be resolved again.
+pub struct UnresolvedNode {
+	url: NodeUrl,
+	reserved: bool,
+	failures: u32,
+	retry_at: Instant,
+}
+
+impl UnresolvedNode {
+	/// URL of the node.
+	pub fn url(&self) -> &NodeUrl {
+		&self.url
+	}
+
+	/// Check if the node was added as a reserved one.
+	pub fn is_reserved(&self) -> bool {
+		self.reserved
+	}
+}
+
+/// Node URLs whose host name could not be resolved yet. Resolution is retried with exponential backoff.
+#[derive(Default)]
+pub struct UnresolvedNodes {
+	entries: Vec<UnresolvedNode>,
+}
+
+impl UnresolvedNodes {
+	pub fn new() -> UnresolvedNodes {
+		UnresolvedNodes {
+			entries: Vec::new(),
+		}
+	}
+
+	/// Add a URL which failed to resolve at `now`.
+	pub fn add(&mut self, url: NodeUrl, reserved: bool, now: Instant) {
+		self.entries.retain(|e| e.url.id != url.id);
+		self.entries.push(UnresolvedNode {
+			url: url,
+			reserved: reserved,
+			failures: 1,
+			retry_at: now + retry_delay(1),
+		});
+	}
+
+	/// Add a URL which has not been resolved yet, to be resolved at the first attempt due from `now`.
+	pub fn queue(&mut self, url: NodeUrl, reserved: bool, now: Instant) {
+		self.entries.retain(|e| e.url.id != url.id);
+		self.entries.push(UnresolvedNode {
+			url: url,
+			reserved: reserved,
+			failures: 0,
+			retry_at: now,
+		});
+	}
+
+	/// Stop resolving the node.
+	pub fn remove(&mut self, id: &NodeId) {
+		self.entries.retain(|e| &e.url.id != id);
+	}
+
+	/// Check if there is nothing left to resolve.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Take out the URLs which are due for another attempt at `now`.
+	/// Resolving may block, so it is left to the caller to do without holding any lock.
+	pub fn take_due(&mut self, now: Instant) -> Vec<UnresolvedNode> {
+		let (due, pending) = self.entries.drain(..).partition(|e| now >= e.retry_at);
+		self.entries = pending;
+		due
+	}
+
+	/// Put back a URL taken out with `take_due` which failed to resolve again at `now`.
+	/// The next attempt is made after a longer delay, unless the URL has been added anew meanwhile.
+	pub fn retry(&mut self, mut entry: UnresolvedNode, now: Instant) {
+		if self.entries.iter().any(|e| e.url.id == entry.url.id) {
+			return;
+		}
+		entry.failures += 1;
+		entry.retry_at = now + retry_delay(entry.failures);
+		debug!(target: "network", "Could not resolve {}, retrying in {:?}", entry.url.host, entry.retry_at - now);
+		self.entries.push(entry);
+	}
+}
+
+fn retry_delay(failures: u32) -> Duration {
+	// the shift is bounded, as the delay is capped long before that.
+	let delay = RESOLVE_RETRY_MS.saturating_mul(1 << min(failures - 1, 16));
+	Duration::from_millis(min(delay, MAX_RESOLVE_RETRY_MS))
+}
+
+/// Check if node url is valid. Host names are accepted without resolving them.
 pub fn is_valid_node_url(url: &str) -> bool {
 	use std::str::FromStr;
-	Node::from_str(url).is_ok()
+	NodeUrl::from_str(url).is_ok()
 }
 
 #[cfg(test)]
@@ -360,6 +585,33 @@ mod tests {
 	use std::net::*;
 	use util::hash::*;
 	use devtools::*;
+	use std::io;
+	use std::collections::HashMap;
+	use std::time::{Duration, Instant};
+	use parking_lot::Mutex;
+	use super::RESOLVE_RETRY_MS;
+
+	const NODE_ID: &'static str = "a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c";
+
+	#[derive(Default)]
+	struct TestResolver {
+		hosts: Mutex<HashMap<String, IpAddr>>,
+	}
+
+	impl TestResolver {
+		fn set(&self, host: &str, ip: &str) {
+			self.hosts.lock().insert(host.to_owned(), ip.parse().unwrap());
+		}
+	}
+
+	impl Resolver for TestResolver {
+		fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+			match self.hosts.lock().get(host) {
+				Some(ip) => Ok(vec![SocketAddr::new(*ip, port)]),
+				None => Err(io::Error::new(io::ErrorKind::Other, "unknown host")),
+			}
+		}
+	}
 
 	#[test]
 	fn endpoint_parse() {
@@ -432,4 +684,115 @@ mod tests {
 			assert_eq!(r[1][..], id2[..]);
 		}
 	}
+
+	#[test]
+	fn table_load_keeps_host_names_unresolved() {
+		let temp_path = RandomTempPath::create_dir();
+		let path = temp_path.as_path().to_str().unwrap().to_owned();
+		let url = NodeUrl::from_str(&format!("enode://{}@node1.example.com:30303", NODE_ID)).unwrap();
+		let id = url.id.clone();
+		{
+			let mut table = NodeTable::new(Some(path.clone()));
+			let mut node = Node::new(id.clone(), NodeEndpoint::from_str("22.99.55.44:30303").unwrap());
+			node.host = Some("node1.example.com".into());
+			table.add_node(node);
+			table.note_failure(&id);
+		}
+
+		// loading doesn't resolve the name and the node survives being saved unresolved
+		{
+			let table = NodeTable::new(Some(path.clone()));
+			assert!(table.nodes().is_empty());
+			assert_eq!(table.unresolved(), vec![url.clone()]);
+		}
+
+		let mut table = NodeTable::new(Some(path));
+		assert_eq!(table.unresolved(), vec![url.clone()]);
+		table.add_node(Node::new(id.clone(), NodeEndpoint::from_str("22.99.55.45:30303").unwrap()));
+		assert!(table.unresolved().is_empty());
+		assert_eq!(table.get(&id).unwrap().failures, 1);
+	}
+
+	#[test]
+	fn node_url_parse() {
+		let url = NodeUrl::from_str(&format!("enode://{}@node1.example.com:30303", NODE_ID)).unwrap();
+		assert_eq!(url.id, H512::from_str(NODE_ID).unwrap());
+		assert_eq!(url.host, "node1.example.com");
+		assert_eq!(url.port, 30303);
+		assert!(url.is_host_name());
+
+		let url = NodeUrl::from_str("[::1]:30303").unwrap();
+		assert_eq!(url.host, "::1");
+		assert!(!url.is_host_name());
+
+		// host names are valid without being resolved
+		assert!(is_valid_node_url(&format!("enode://{}@node1.example.invalid:30303", NODE_ID)));
+		assert!(!is_valid_node_url(&format!("enode://{}@node1.example.com", NODE_ID)));
+		assert!(!is_valid_node_url(&format!("enode://{}@:30303", NODE_ID)));
+		assert!(!is_valid_node_url(&format!("enode://{}@node1.example.com:303030", NODE_ID)));
+	}
+
+	#[test]
+	fn node_url_resolve() {
+		let resolver = TestResolver::default();
+		let url = NodeUrl::from_str(&format!("enode://{}@node1.example.com:30303", NODE_ID)).unwrap();
+		assert!(url.resolve(&resolver).is_err());
+
+		resolver.set("node1.example.com", "22.99.55.44");
+		let node = url.resolve(&resolver).unwrap();
+		assert_eq!(node.endpoint.address, "22.99.55.44:30303".parse().unwrap());
+		assert_eq!(node.host, Some("node1.example.com".to_owned()));
+		assert_eq!(format!("{}", node), format!("enode://{}@node1.example.com:30303", NODE_ID));
+
+		// IP addresses are never passed to the resolver
+		let url = NodeUrl::from_str(&format!("enode://{}@22.99.55.45:30303", NODE_ID)).unwrap();
+		let node = url.resolve(&TestResolver::default()).unwrap();
+		assert_eq!(node.endpoint.address, "22.99.55.45:30303".parse().unwrap());
+		assert_eq!(node.host, None);
+	}
+
+	#[test]
+	fn table_refresh_address() {
+		let resolver = TestResolver::default();
+		resolver.set("node1.example.com", "22.99.55.44");
+		let url = NodeUrl::from_str(&format!("enode://{}@node1.example.com:30303", NODE_ID)).unwrap();
+		let id = url.id.clone();
+		let mut table = NodeTable::new(None);
+		table.add_node(url.resolve(&resolver).unwrap());
+
+		let (host, port) = table.host(&id).unwrap();
+		assert_eq!((host.as_str(), port), ("node1.example.com", 30303));
+		let endpoint = NodeEndpoint::resolve(&host, port, &resolver).unwrap();
+		assert_eq!(table.set_endpoint(&id, endpoint), false);
+
+		resolver.set("node1.example.com", "22.99.55.45");
+		let endpoint = NodeEndpoint::resolve(&host, port, &resolver).unwrap();
+		assert_eq!(table.set_endpoint(&id, endpoint), true);
+		assert_eq!(table.get(&id).unwrap().endpoint.address, "22.99.55.45:30303".parse().unwrap());
+	}
+
+	#[test]
+	fn unresolved_nodes_retry_with_backoff() {
+		let resolver = TestResolver::default();
+		let url = NodeUrl::from_str(&format!("enode://{}@node1.example.com:30303", NODE_ID)).unwrap();
+		let start = Instant::now();
+		let at = |ms: u64| start + Duration::from_millis(ms);
+		let mut unresolved = UnresolvedNodes::new();
+		unresolved.add(url, true, start);
+
+		assert!(unresolved.take_due(at(RESOLVE_RETRY_MS - 1)).is_empty());
+		// still failing, next retry after twice the delay
+		let mut due = unresolved.take_due(at(RESOLVE_RETRY_MS));
+		assert_eq!(due.len(), 1);
+		assert!(due[0].url().resolve(&resolver).is_err());
+		unresolved.retry(due.remove(0), at(RESOLVE_RETRY_MS));
+
+		resolver.set("node1.example.com", "22.99.55.44");
+		assert!(unresolved.take_due(at(3 * RESOLVE_RETRY_MS - 1)).is_empty());
+		let due = unresolved.take_due(at(3 * RESOLVE_RETRY_MS));
+		assert_eq!(due.len(), 1);
+		assert!(due[0].is_reserved());
+		assert_eq!(due[0].url().resolve(&resolver).unwrap().endpoint.address, "22.99.55.44:30303".parse().unwrap());
+		assert!(unresolved.is_empty());
+	}
 }
@@ -16,7 +16,7 @@
 
 use {NetworkProtocolHandler, NetworkConfiguration, NonReservedPeerMode};
 use error::NetworkError;
-use host::{Host, NetworkContext, NetworkIoMessage, ProtocolId};
+use host::{Host, NetworkContext, NetworkIoMessage, ProtocolId, ReservedPeerStatus};
 use stats::NetworkStats;
 use io::*;
 use parking_lot::RwLock;
@@ -60,7 +60,7 @@ impl NetworkService {
 		panic_handler.forward_from(&io_service);
 
 		let stats = Arc::new(NetworkStats::new());
-		let host_info = Host::client_version();
+		let host_info = Host::client_version(&config.identity);
 		Ok(NetworkService {
 			io_service: io_service,
 			host_info: host_info,
@@ -161,6 +161,18 @@ impl NetworkService {
 		}
 	}
 
+	/// Get connection status of all reserved peers.
+	pub fn reserved_peers_status(&self) -> Vec<ReservedPeerStatus> {
+		let host = self.host.read();
+		host.as_ref().map_or_else(Vec::new, |h| h.reserved_peers_status())
+	}
+
+	/// Get the number of connected peers as `(inbound, outbound)`.
+	pub fn session_counts(&self) -> (usize, usize) {
+		let host = self.host.read();
+		host.as_ref().map_or((0, 0), |h| h.session_counts())
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode) {
 		let host = self.host.read();
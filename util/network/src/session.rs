@@ -20,18 +20,66 @@ use std::sync::*;
 use mio::*;
 use mio::tcp::*;
 use util::hash::*;
+use util::snappy;
 use rlp::*;
-use connection::{EncryptedConnection, Packet, Connection};
+use connection::{EncryptedConnection, Packet, Connection, MAX_PAYLOAD_SIZE};
 use handshake::Handshake;
 use io::{IoContext, StreamToken};
 use error::{NetworkError, DisconnectReason};
 use host::*;
 use node_table::NodeId;
-use stats::NetworkStats;
+use stats::{NetworkStats, HandshakeFailure};
+use COMPRESSION_PROTOCOL_VERSION;
 use time;
 
 const PING_TIMEOUT_SEC: u64 = 30;
 const PING_INTERVAL_SEC: u64 = 30;
+/// Default time a session may keep failing to read with transient errors before it is dropped.
+pub const DEFAULT_READ_ERROR_GRACE_MS: u64 = 5_000;
+
+/// Whether a read error may go away by itself: an interrupted system call, a spurious wakeup of
+/// the non-blocking socket or a read timeout leave the stream intact and the next read may succeed.
+/// Anything else, including connection resets, means the socket is closed or the peer is broken
+/// and retrying the same socket can't help.
+fn is_transient_read_error(e: &NetworkError) -> bool {
+	match *e {
+		NetworkError::StdIo(ref e) => match e.kind() {
+			io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => true,
+			_ => false,
+		},
+		_ => false,
+	}
+}
+
+/// Tracks consecutive transient read errors.
+struct TransientReadErrors {
+	/// Time of the first error since the last successful read, in nanoseconds
+	since: Option<u64>,
+}
+
+impl TransientReadErrors {
+	fn new() -> TransientReadErrors {
+		TransientReadErrors {
+			since: None,
+		}
+	}
+
+	/// Record an error at `now_ns`. Returns true if reads have been failing for longer than `grace_ns`.
+	fn note(&mut self, now_ns: u64, grace_ns: u64) -> bool {
+		match self.since {
+			Some(since) => now_ns - since >= grace_ns,
+			None => {
+				self.since = Some(now_ns);
+				grace_ns == 0
+			}
+		}
+	}
+
+	/// Record a successful read.
+	fn clear(&mut self) {
+		self.since = None;
+	}
+}
 
 /// Peer session over encrypted connection.
 /// When created waits for Hello packet exchange and signals ready state.
@@ -46,6 +94,10 @@ pub struct Session {
 	ping_time_ns: u64,
 	pong_time_ns: Option<u64>,
 	state: State,
+	/// Reason the handshake failed, if it did.
+	handshake_failure: Option<HandshakeFailure>,
+	read_errors: TransientReadErrors,
+	stats: Arc<NetworkStats>,
 }
 
 enum State {
@@ -85,6 +137,8 @@ pub struct SessionInfo {
 	pub ping_ms: Option<u64>,
 	/// True if this session was originated by us.
 	pub originated: bool,
+	/// True if both peers agreed to compress capability packet payloads.
+	pub compression: bool,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -120,6 +174,77 @@ const PACKET_PEERS: u8 = 0x05;
 const PACKET_USER: u8 = 0x10;
 const PACKET_LAST: u8 = 0x7f;
 
+/// Hello packet contents.
+#[derive(Debug)]
+struct Hello {
+	protocol: u32,
+	client_version: String,
+	capabilities: Vec<PeerCapabilityInfo>,
+	id: NodeId,
+}
+
+/// Packet received from the peer. Decoded, but not yet acted upon.
+#[derive(Debug)]
+enum SessionPacket {
+	Hello(Hello),
+	Disconnect(DisconnectReason),
+	Ping,
+	Pong,
+	GetPeers,
+	Peers,
+	/// Capability packet with the given id.
+	User(u8),
+	Unknown(u8),
+}
+
+/// Decode raw packet data. Never panics on malformed input.
+fn decode_packet(data: &[u8], had_hello: bool) -> Result<SessionPacket, NetworkError> {
+	if data.len() < 2 {
+		return Err(From::from(NetworkError::BadProtocol));
+	}
+	let packet_id = data[0];
+	if packet_id != PACKET_HELLO && packet_id != PACKET_DISCONNECT && !had_hello {
+		return Err(From::from(NetworkError::BadProtocol));
+	}
+	let rlp = UntrustedRlp::new(&data[1..]);
+	match packet_id {
+		PACKET_HELLO => {
+			if !rlp.is_list() || rlp.item_count() < 5 {
+				return Err(From::from(NetworkError::BadProtocol));
+			}
+			Ok(SessionPacket::Hello(Hello {
+				protocol: try!(rlp.val_at(0)),
+				client_version: try!(rlp.val_at(1)),
+				capabilities: try!(rlp.val_at(2)),
+				id: try!(rlp.val_at(4)),
+			}))
+		},
+		PACKET_DISCONNECT => {
+			let reason: u8 = try!(rlp.val_at(0));
+			Ok(SessionPacket::Disconnect(DisconnectReason::from_u8(reason)))
+		},
+		PACKET_PING => Ok(SessionPacket::Ping),
+		PACKET_PONG => Ok(SessionPacket::Pong),
+		PACKET_GET_PEERS => Ok(SessionPacket::GetPeers),
+		PACKET_PEERS => Ok(SessionPacket::Peers),
+		PACKET_USER ... PACKET_LAST => Ok(SessionPacket::User(packet_id)),
+		_ => Ok(SessionPacket::Unknown(packet_id)),
+	}
+}
+
+/// Decompress the payload of a capability packet, keeping the packet id in front.
+fn decompress_packet(data: &[u8]) -> Result<Vec<u8>, NetworkError> {
+	let payload = &data[1..];
+	let len = match snappy::decompressed_len(payload) {
+		Ok(len) if len <= MAX_PAYLOAD_SIZE => len,
+		_ => return Err(From::from(NetworkError::BadProtocol)),
+	};
+	let mut decompressed = Vec::with_capacity(len + 1);
+	decompressed.push(data[0]);
+	decompressed.extend(try!(snappy::decompress(payload).map_err(|_| NetworkError::BadProtocol)));
+	Ok(decompressed)
+}
+
 impl Session {
 	/// Create a new session out of comepleted handshake. This clones the handshake connection object
 	/// and leaves the handhsake in limbo to be deregistered from the event loop.
@@ -127,7 +252,7 @@ impl Session {
 		nonce: &H256, stats: Arc<NetworkStats>, host: &HostInfo) -> Result<Session, NetworkError>
 		where Message: Send + Clone {
 		let originated = id.is_some();
-		let mut handshake = Handshake::new(token, id, socket, nonce, stats).expect("Can't create handshake");
+		let mut handshake = Handshake::new(token, id, socket, nonce, stats.clone()).expect("Can't create handshake");
 		try!(handshake.start(io, host, originated));
 		Ok(Session {
 			state: State::Handshake(handshake),
@@ -139,20 +264,31 @@ impl Session {
 				capabilities: Vec::new(),
 				ping_ms: None,
 				originated: originated,
+				compression: false,
 			},
 			ping_time_ns: 0,
 			pong_time_ns: None,
 			expired: false,
+			handshake_failure: None,
+			read_errors: TransientReadErrors::new(),
+			stats: stats,
 		})
 	}
 
 	fn complete_handshake<Message>(&mut self, io: &IoContext<Message>, host: &HostInfo) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
 		let connection = if let State::Handshake(ref mut h) = self.state {
 			self.info.id = Some(h.id.clone());
-			try!(EncryptedConnection::new(h))
+			EncryptedConnection::new(h)
 		} else {
 			panic!("Unexpected state");
 		};
+		let connection = match connection {
+			Ok(c) => c,
+			Err(e) => {
+				self.note_handshake_failure(HandshakeFailure::Decrypt);
+				return Err(e);
+			}
+		};
 		self.state = State::Session(connection);
 		try!(self.write_hello(io, host));
 		try!(self.send_ping(io));
@@ -171,6 +307,37 @@ impl Session {
 		self.info.id.as_ref()
 	}
 
+	/// Record the reason this session failed before becoming ready. Only the first reason is kept.
+	pub fn note_handshake_failure(&mut self, failure: HandshakeFailure) {
+		if self.handshake_failure.is_none() && !self.had_hello {
+			trace!(target: "network", "Handshake failed: {}:{:?} ({:?})", self.token(), self.info.id, failure);
+			self.handshake_failure = Some(failure);
+			self.stats.inc_handshake_failures(failure);
+		}
+	}
+
+	/// Get the reason this session failed before becoming ready.
+	pub fn handshake_failure(&self) -> Option<HandshakeFailure> {
+		self.handshake_failure
+	}
+
+	/// Disconnect after a frame failed to decrypt or authenticate.
+	/// The frame already went into the running MAC and cipher state, so no later frame could be read either.
+	fn note_decryption_failure<Message>(&mut self, io: &IoContext<Message>) -> NetworkError where Message: Send + Sync + Clone {
+		debug!(target: "network", "Corrupt frame from {}:{:?}", self.token(), self.info.id);
+		self.disconnect(io, DisconnectReason::DecryptionFailure)
+	}
+
+	fn note_read_error(&mut self, e: NetworkError, host: &HostInfo) -> Result<SessionData, NetworkError> {
+		let grace = host.read_error_grace();
+		let grace_ns = grace.as_secs() * 1000_000_000 + grace.subsec_nanos() as u64;
+		if !is_transient_read_error(&e) || self.read_errors.note(time::precise_time_ns(), grace_ns) {
+			return Err(e);
+		}
+		trace!(target: "network", "Transient read error from {}:{:?}: {:?}", self.token(), self.info.id, e);
+		Ok(SessionData::None)
+	}
+
 	/// Check if session is ready to send/receive data
 	pub fn is_ready(&self) -> bool {
 		self.had_hello
@@ -206,21 +373,39 @@ impl Session {
 		}
 		let mut create_session = false;
 		let mut packet_data = None;
+		let mut handshake_error = None;
+		let mut decryption_failed = false;
+		let mut read_error = None;
 		match self.state {
 			State::Handshake(ref mut h) => {
-				try!(h.readable(io, host));
-				if h.done() {
-					create_session = true;
+				match h.readable(io, host) {
+					Ok(()) => create_session = h.done(),
+					Err(e) => handshake_error = Some(e),
 				}
 			}
 			State::Session(ref mut c) => {
-				match try!(c.readable(io)) {
-					data @ Some(_) => packet_data = data,
-					None => return Ok(SessionData::None)
+				match c.readable(io) {
+					Ok(data @ Some(_)) => packet_data = data,
+					Ok(None) => return Ok(SessionData::None),
+					Err(NetworkError::Disconnect(DisconnectReason::DecryptionFailure)) => decryption_failed = true,
+					Err(e) => read_error = Some(e),
 				}
 			}
 		}
+		if decryption_failed {
+			return Err(self.note_decryption_failure(io));
+		}
+		if let Some(e) = read_error {
+			return self.note_read_error(e, host);
+		}
+		if let Some(e) = handshake_error {
+			if let NetworkError::Auth = e {
+				self.note_handshake_failure(HandshakeFailure::Decrypt);
+			}
+			return Err(e);
+		}
 		if let Some(data) = packet_data {
+			self.read_errors.clear();
 			return Ok(try!(self.read_packet(io, data, host)));
 		}
 		if create_session {
@@ -290,7 +475,11 @@ impl Session {
 		let pid = self.info.capabilities[i].id_offset + packet_id;
 		let mut rlp = RlpStream::new();
 		rlp.append(&(pid as u32));
-		rlp.append_raw(data, 1);
+		if self.info.compression {
+			rlp.append_raw(&snappy::compress(data), 1);
+		} else {
+			rlp.append_raw(data, 1);
+		}
 		self.send(io, rlp)
 	}
 
@@ -319,54 +508,43 @@ impl Session {
 
 	fn read_packet<Message>(&mut self, io: &IoContext<Message>, packet: Packet, host: &HostInfo) -> Result<SessionData, NetworkError>
 	where Message: Send + Sync + Clone {
-		if packet.data.len() < 2 {
-			return Err(From::from(NetworkError::BadProtocol));
-		}
-		let packet_id = packet.data[0];
-		if packet_id != PACKET_HELLO && packet_id != PACKET_DISCONNECT && !self.had_hello {
-			return Err(From::from(NetworkError::BadProtocol));
-		}
-		match packet_id {
-			PACKET_HELLO => {
-				let rlp = UntrustedRlp::new(&packet.data[1..]); //TODO: validate rlp expected size
-				try!(self.read_hello(io, &rlp, host));
+		match try!(decode_packet(&packet.data, self.had_hello)) {
+			SessionPacket::Hello(hello) => {
+				try!(self.read_hello(io, hello, host));
 				Ok(SessionData::Ready)
 			},
-			PACKET_DISCONNECT => {
-				let rlp = UntrustedRlp::new(&packet.data[1..]);
-				let reason: u8 = try!(rlp.val_at(0));
+			SessionPacket::Disconnect(reason) => {
 				if self.had_hello {
-					debug!("Disconnected: {}: {:?}", self.token(), DisconnectReason::from_u8(reason));
+					debug!("Disconnected: {}: {:?}", self.token(), reason);
 				}
-				Err(From::from(NetworkError::Disconnect(DisconnectReason::from_u8(reason))))
+				Err(From::from(NetworkError::Disconnect(reason)))
 			}
-			PACKET_PING => {
+			SessionPacket::Ping => {
 				try!(self.send_pong(io));
 				Ok(SessionData::Continue)
 			},
-			PACKET_PONG => {
-				self.pong_time_ns = Some(time::precise_time_ns());
-				self.info.ping_ms = Some((self.pong_time_ns.unwrap() - self.ping_time_ns) / 1000_000);
+			SessionPacket::Pong => {
+				let pong_time_ns = time::precise_time_ns();
+				self.pong_time_ns = Some(pong_time_ns);
+				self.info.ping_ms = Some(pong_time_ns.saturating_sub(self.ping_time_ns) / 1000_000);
 				Ok(SessionData::Continue)
 			},
-			PACKET_GET_PEERS => Ok(SessionData::None), //TODO;
-			PACKET_PEERS => Ok(SessionData::None),
-			PACKET_USER ... PACKET_LAST => {
-				let mut i = 0usize;
-				while packet_id < self.info.capabilities[i].id_offset {
-					i += 1;
-					if i == self.info.capabilities.len() {
+			SessionPacket::GetPeers => Ok(SessionData::None), //TODO;
+			SessionPacket::Peers => Ok(SessionData::None),
+			SessionPacket::User(packet_id) => {
+				// capabilities may be empty after a rejected hello
+				let (protocol, pid) = match self.info.capabilities.iter().find(|c| packet_id >= c.id_offset) {
+					// map to protocol
+					Some(cap) => (cap.protocol, packet_id - cap.id_offset),
+					None => {
 						debug!(target: "network", "Unknown packet: {:?}", packet_id);
 						return Ok(SessionData::Continue)
 					}
-				}
-
-				// map to protocol
-				let protocol = self.info.capabilities[i].protocol;
-				let pid = packet_id - self.info.capabilities[i].id_offset;
-				Ok(SessionData::Packet { data: packet.data, protocol: protocol, packet_id: pid } )
+				};
+				let data = if self.info.compression { try!(decompress_packet(&packet.data)) } else { packet.data };
+				Ok(SessionData::Packet { data: data, protocol: protocol, packet_id: pid } )
 			},
-			_ => {
+			SessionPacket::Unknown(packet_id) => {
 				debug!(target: "network", "Unknown packet: {:?}", packet_id);
 				Ok(SessionData::Continue)
 			}
@@ -376,8 +554,10 @@ impl Session {
 	fn write_hello<Message>(&mut self, io: &IoContext<Message>, host: &HostInfo) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
 		let mut rlp = RlpStream::new();
 		rlp.append_raw(&[PACKET_HELLO as u8], 0);
+		// snappy support is advertised by the protocol version.
+		let protocol_version = if host.packet_compression() { COMPRESSION_PROTOCOL_VERSION } else { host.protocol_version };
 		rlp.begin_list(5)
-			.append(&host.protocol_version)
+			.append(&protocol_version)
 			.append(&host.client_version)
 			.append(&host.capabilities)
 			.append(&host.local_endpoint.address.port())
@@ -385,12 +565,9 @@ impl Session {
 		self.send(io, rlp)
 	}
 
-	fn read_hello<Message>(&mut self, io: &IoContext<Message>, rlp: &UntrustedRlp, host: &HostInfo) -> Result<(), NetworkError>
+	fn read_hello<Message>(&mut self, io: &IoContext<Message>, hello: Hello, host: &HostInfo) -> Result<(), NetworkError>
 	where Message: Send + Sync + Clone {
-		let protocol = try!(rlp.val_at::<u32>(0));
-		let client_version = try!(rlp.val_at::<String>(1));
-		let peer_caps = try!(rlp.val_at::<Vec<PeerCapabilityInfo>>(2));
-		let id = try!(rlp.val_at::<NodeId>(4));
+		let Hello { protocol, client_version, capabilities: peer_caps, id } = hello;
 
 		// Intersect with host capabilities
 		// Leave only highset mutually supported capability version
@@ -427,12 +604,21 @@ impl Session {
 		trace!(target: "network", "Hello: {} v{} {} {:?}", client_version, protocol, id, caps);
 		self.info.client_version = client_version;
 		self.info.capabilities = caps;
+		// compress only if both sides advertise a protocol version supporting it.
+		self.info.compression = host.packet_compression() && protocol >= COMPRESSION_PROTOCOL_VERSION;
+		if id == *host.id() {
+			trace!(target: "network", "Connected to self.");
+			self.note_handshake_failure(HandshakeFailure::SelfConnection);
+			return Err(From::from(self.disconnect(io, DisconnectReason::LocalIdentity)));
+		}
 		if self.info.capabilities.is_empty() {
 			trace!(target: "network", "No common capabilities with peer.");
+			self.note_handshake_failure(HandshakeFailure::CapabilityMismatch);
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
-		if protocol != host.protocol_version {
+		if protocol < host.protocol_version {
 			trace!(target: "network", "Peer protocol version mismatch: {}", protocol);
+			self.note_handshake_failure(HandshakeFailure::ProtocolMismatch);
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
 		self.had_hello = true;
@@ -483,3 +669,239 @@ impl Session {
 	}
 }
 
+#[cfg(test)]
+mod tests {
+	use std::io;
+	use error::{NetworkError, DisconnectReason};
+	use super::{TransientReadErrors, is_transient_read_error};
+
+	const SEC: u64 = 1000_000_000;
+
+	// whether the session survives the error at `now_ns`.
+	fn survives(errors: &mut TransientReadErrors, e: &NetworkError, now_ns: u64) -> bool {
+		is_transient_read_error(e) && !errors.note(now_ns, 5 * SEC)
+	}
+
+	#[test]
+	fn transient_read_error_is_tolerated() {
+		let mut errors = TransientReadErrors::new();
+		let interrupted = NetworkError::StdIo(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+		assert!(survives(&mut errors, &interrupted, 10 * SEC));
+		assert!(survives(&mut errors, &interrupted, 12 * SEC));
+		// successful read
+		errors.clear();
+		assert!(survives(&mut errors, &interrupted, 16 * SEC));
+		assert!(survives(&mut errors, &interrupted, 20 * SEC));
+		// failing for too long
+		assert!(!survives(&mut errors, &interrupted, 21 * SEC));
+	}
+
+	#[test]
+	fn transient_read_error_kinds() {
+		for kind in vec![io::ErrorKind::Interrupted, io::ErrorKind::WouldBlock, io::ErrorKind::TimedOut] {
+			assert!(is_transient_read_error(&NetworkError::StdIo(io::Error::new(kind, "transient"))), "{:?}", kind);
+		}
+	}
+
+	#[test]
+	fn fatal_read_error_expires_session() {
+		let mut errors = TransientReadErrors::new();
+		let reset = NetworkError::StdIo(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+		assert!(!survives(&mut errors, &reset, SEC));
+		assert!(!survives(&mut errors, &NetworkError::Disconnect(DisconnectReason::BadProtocol), SEC));
+		assert!(!survives(&mut errors, &NetworkError::BadProtocol, SEC));
+	}
+}
+
+#[cfg(test)]
+mod fuzz_tests {
+	use std::sync::Arc;
+	use std::net::TcpListener;
+	use mio::tcp::TcpStream;
+	use rlp::{RlpStream, Stream};
+	use util::hash::{H256, FixedHash};
+	use io::{IoContext, IoChannel};
+	use host::{HostInfo, CapabilityInfo};
+	use connection::Packet;
+	use stats::NetworkStats;
+	use node_table::NodeId;
+	use error::{NetworkError, DisconnectReason};
+	use util::snappy;
+	use super::{Session, decode_packet, decompress_packet, SessionPacket, PACKET_HELLO, PACKET_DISCONNECT, PACKET_PING};
+
+	fn hello_packet(client_version: &[u8]) -> Vec<u8> {
+		let mut rlp = RlpStream::new();
+		rlp.append_raw(&[PACKET_HELLO], 0);
+		rlp.begin_list(5)
+			.append(&4u32)
+			.append(&client_version)
+			.begin_list(1)
+				.begin_list(2).append(&"eth").append(&63u8)
+			.append(&30303u16)
+			.append(&NodeId::new());
+		rlp.out()
+	}
+
+	fn extended_hello_packet() -> Vec<u8> {
+		let mut rlp = RlpStream::new();
+		rlp.append_raw(&[PACKET_HELLO], 0);
+		rlp.begin_list(6)
+			.append(&4u32)
+			.append(&"Parity")
+			.begin_list(1)
+				.begin_list(2).append(&"eth").append(&63u8)
+			.append(&30303u16)
+			.append(&NodeId::new())
+			.begin_list(2).append(&1u8).append(&"future");
+		rlp.out()
+	}
+
+	fn disconnect_packet() -> Vec<u8> {
+		let mut rlp = RlpStream::new();
+		rlp.append(&(PACKET_DISCONNECT as u32));
+		rlp.begin_list(1);
+		rlp.append(&(DisconnectReason::TooManyPeers as u32));
+		rlp.out()
+	}
+
+	fn user_packet() -> Vec<u8> {
+		let mut rlp = RlpStream::new();
+		rlp.append(&0x13u32);
+		rlp.begin_list(3).append(&1u8).append(&"data").append(&NodeId::new());
+		rlp.out()
+	}
+
+	fn test_io() -> IoContext<i32> {
+		IoContext::new(IoChannel::disconnected(), 0)
+	}
+
+	fn test_host() -> HostInfo {
+		HostInfo::new_test(vec![CapabilityInfo { protocol: "eth", version: 63, packet_count: 17 }])
+	}
+
+	/// Inbound session waiting for the hello packet. Nothing is ever sent over its socket.
+	fn test_session(listener: &TcpListener, io: &IoContext<i32>, host: &HostInfo) -> Session {
+		let socket = TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+		Session::new(io, socket, 0, None, &H256::new(), Arc::new(NetworkStats::new()), host).unwrap()
+	}
+
+	fn packet(data: Vec<u8>) -> Packet {
+		Packet { protocol: 0, data: data }
+	}
+
+	/// Deterministic mutations of the seed: truncations, bit flips, replaced and inserted bytes.
+	fn mutations(seed: &[u8]) -> Vec<Vec<u8>> {
+		let mut result = Vec::new();
+		for len in 0..seed.len() {
+			result.push(seed[..len].to_vec());
+		}
+		for i in 0..seed.len() {
+			for &mask in &[0x01u8, 0x80, 0xff] {
+				let mut data = seed.to_vec();
+				data[i] ^= mask;
+				result.push(data);
+			}
+			for &byte in &[0x00u8, 0x7f, 0x80, 0xb8, 0xbf, 0xc0, 0xf8, 0xff] {
+				let mut data = seed.to_vec();
+				data[i] = byte;
+				result.push(data);
+				let mut data = seed.to_vec();
+				data.insert(i, byte);
+				result.push(data);
+			}
+		}
+		result
+	}
+
+	#[test]
+	fn seed_packets_are_valid() {
+		match decode_packet(&hello_packet(b"Parity"), false) {
+			Ok(SessionPacket::Hello(hello)) => {
+				assert_eq!(hello.protocol, 4);
+				assert_eq!(hello.client_version, "Parity");
+				assert_eq!(hello.capabilities.len(), 1);
+			},
+			other => panic!("Unexpected hello decoding result: {:?}", other),
+		}
+		match decode_packet(&disconnect_packet(), false) {
+			Ok(SessionPacket::Disconnect(DisconnectReason::TooManyPeers)) => {},
+			other => panic!("Unexpected disconnect decoding result: {:?}", other),
+		}
+		match decode_packet(&user_packet(), true) {
+			Ok(SessionPacket::User(0x13)) => {},
+			other => panic!("Unexpected user packet decoding result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn mutated_packets_never_panic() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let io = test_io();
+		let host = test_host();
+		// one session before and one after the hello exchange
+		let mut waiting = test_session(&listener, &io, &host);
+		let mut ready = test_session(&listener, &io, &host);
+		assert!(ready.read_packet(&io, packet(hello_packet(b"Parity")), &host).is_ok());
+
+		let ping = vec![PACKET_PING, 0xc0];
+		let seeds = vec![hello_packet(b"Parity"), disconnect_packet(), user_packet(), ping];
+		let mut rejected = 0;
+		for seed in &seeds {
+			for data in mutations(seed) {
+				if waiting.read_packet(&io, packet(data.clone()), &host).is_err() {
+					rejected += 1;
+				}
+				if ready.read_packet(&io, packet(data), &host).is_err() {
+					rejected += 1;
+				}
+			}
+		}
+		assert!(rejected > 0);
+	}
+
+	#[test]
+	fn packets_before_hello_are_rejected() {
+		match decode_packet(&user_packet(), false) {
+			Err(NetworkError::BadProtocol) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	// Regression: hello with a client version which is not valid UTF-8 used to panic while decoding.
+	#[test]
+	fn hello_with_invalid_utf8_is_rejected() {
+		assert!(decode_packet(&hello_packet(&[0xc3, 0x28]), false).is_err());
+	}
+
+	#[test]
+	fn hello_trailing_fields_are_ignored() {
+		match decode_packet(&extended_hello_packet(), false) {
+			Ok(SessionPacket::Hello(hello)) => {
+				assert_eq!(hello.protocol, 4);
+				assert_eq!(hello.client_version, "Parity");
+			},
+			other => panic!("Unexpected hello decoding result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn compressed_packet_round_trip() {
+		let packet = user_packet();
+		let payload: Vec<u8> = packet[1..].iter().cycle().take(64 * (packet.len() - 1)).cloned().collect();
+		let mut compressed = vec![0x13u8];
+		compressed.extend(snappy::compress(&payload));
+		assert!(compressed.len() < payload.len());
+
+		let decompressed = decompress_packet(&compressed).unwrap();
+		assert_eq!(decompressed[0], 0x13);
+		assert_eq!(&decompressed[1..], &payload[..]);
+	}
+
+	#[test]
+	fn invalid_compressed_packet_is_rejected() {
+		match decompress_packet(&user_packet()) {
+			Err(NetworkError::BadProtocol) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+}
@@ -17,6 +17,35 @@
 //! Network Statistics
 use std::sync::atomic::*;
 
+/// Reason a session failed before becoming ready.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeFailure {
+	/// Peer has no capabilities in common with us.
+	CapabilityMismatch,
+	/// Peer uses a different RLPx protocol version.
+	ProtocolMismatch,
+	/// Handshake did not complete in time.
+	Timeout,
+	/// Handshake messages could not be decrypted or authenticated.
+	Decrypt,
+	/// Peer turned out to be this node.
+	SelfConnection,
+}
+
+const HANDSHAKE_FAILURE_KINDS: usize = 5;
+
+impl HandshakeFailure {
+	fn index(&self) -> usize {
+		match *self {
+			HandshakeFailure::CapabilityMismatch => 0,
+			HandshakeFailure::ProtocolMismatch => 1,
+			HandshakeFailure::Timeout => 2,
+			HandshakeFailure::Decrypt => 3,
+			HandshakeFailure::SelfConnection => 4,
+		}
+	}
+}
+
 /// Network statistics structure
 #[derive(Default, Debug)]
 pub struct NetworkStats {
@@ -26,6 +55,10 @@ pub struct NetworkStats {
 	send: AtomicUsize,
 	/// Total number of sessions created
 	sessions: AtomicUsize,
+	/// Number of failed handshakes per failure reason
+	handshake_failures: [AtomicUsize; HANDSHAKE_FAILURE_KINDS],
+	/// Number of frames of established sessions that failed to decrypt or authenticate
+	decryption_failures: AtomicUsize,
 }
 
 impl NetworkStats {
@@ -47,6 +80,18 @@ impl NetworkStats {
 		self.sessions.fetch_add(1, Ordering::Relaxed);
 	}
 
+	/// Increase number of handshakes failed for given reason.
+	#[inline]
+	pub fn inc_handshake_failures(&self, failure: HandshakeFailure) {
+		self.handshake_failures[failure.index()].fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Increase number of frames failed to decrypt.
+	#[inline]
+	pub fn inc_decryption_failures(&self) {
+		self.decryption_failures.fetch_add(1, Ordering::Relaxed);
+	}
+
 	/// Get bytes sent.
 	#[inline]
 	pub fn send(&self) -> usize {
@@ -65,12 +110,26 @@ impl NetworkStats {
 		self.sessions.load(Ordering::Relaxed)
 	}
 
+	/// Get number of handshakes failed for given reason.
+	#[inline]
+	pub fn handshake_failures(&self, failure: HandshakeFailure) -> usize {
+		self.handshake_failures[failure.index()].load(Ordering::Relaxed)
+	}
+
+	/// Get number of frames failed to decrypt.
+	#[inline]
+	pub fn decryption_failures(&self) -> usize {
+		self.decryption_failures.load(Ordering::Relaxed)
+	}
+
 	/// Create a new empty instance.
 	pub fn new() -> NetworkStats {
 		NetworkStats {
 			recv: AtomicUsize::new(0),
 			send: AtomicUsize::new(0),
 			sessions: AtomicUsize::new(0),
+			handshake_failures: Default::default(),
+			decryption_failures: AtomicUsize::new(0),
 		}
 	}
 }
@@ -88,6 +88,66 @@ impl NetworkProtocolHandler for TestProtocol {
 	}
 }
 
+/// Sends a large compressible packet to every connected peer.
+pub struct PayloadProtocol {
+	payload: Bytes,
+	pub received: Mutex<Bytes>,
+	pub compression: Mutex<Option<bool>>,
+}
+
+impl PayloadProtocol {
+	pub fn register(service: &mut NetworkService) -> Arc<PayloadProtocol> {
+		let handler = Arc::new(PayloadProtocol {
+			payload: b"parity".iter().cycle().take(16 * 1024).cloned().collect(),
+			received: Mutex::new(Vec::new()),
+			compression: Mutex::new(None),
+		});
+		service.register_protocol(handler.clone(), "test", &[42u8]).expect("Error registering test protocol handler");
+		handler
+	}
+
+	pub fn got_payload(&self) -> bool {
+		*self.received.lock() == self.payload
+	}
+}
+
+impl NetworkProtocolHandler for PayloadProtocol {
+	fn read(&self, _io: &NetworkContext, _peer: &PeerId, packet_id: u8, data: &[u8]) {
+		assert_eq!(packet_id, 1);
+		*self.received.lock() = data.to_vec();
+	}
+
+	fn connected(&self, io: &NetworkContext, peer: &PeerId) {
+		*self.compression.lock() = Some(io.peer_compression(*peer));
+		io.respond(1, self.payload.clone()).unwrap();
+	}
+
+	fn disconnected(&self, _io: &NetworkContext, _peer: &PeerId) {}
+}
+
+/// Exchange a payload between two hosts, returning whether each side compressed the session.
+fn exchange_payload(compression1: bool, compression2: bool) -> (Option<bool>, Option<bool>) {
+	let key1 = Random.generate().unwrap();
+	let mut config1 = NetworkConfiguration::new_local();
+	config1.use_secret = Some(key1.secret().clone());
+	config1.packet_compression = compression1;
+	let mut service1 = NetworkService::new(config1).unwrap();
+	service1.start().unwrap();
+	let handler1 = PayloadProtocol::register(&mut service1);
+	let mut config2 = NetworkConfiguration::new_local();
+	config2.boot_nodes = vec![ service1.local_url().unwrap() ];
+	config2.packet_compression = compression2;
+	let mut service2 = NetworkService::new(config2).unwrap();
+	service2.start().unwrap();
+	let handler2 = PayloadProtocol::register(&mut service2);
+	let start = Instant::now();
+	while !(handler1.got_payload() && handler2.got_payload()) {
+		assert!(start.elapsed() < Duration::from_secs(10), "Payload was not exchanged in time");
+		thread::sleep(Duration::from_millis(50));
+	}
+	let compression = (*handler1.compression.lock(), *handler2.compression.lock());
+	compression
+}
 
 #[test]
 fn net_service() {
@@ -159,3 +219,134 @@ fn net_timeout() {
 		thread::sleep(Duration::from_millis(50));
 	}
 }
+
+#[test]
+fn net_capability_mismatch_counted() {
+	let key1 = Random.generate().unwrap();
+	let mut config1 = NetworkConfiguration::new_local();
+	config1.use_secret = Some(key1.secret().clone());
+	config1.boot_nodes = vec![ ];
+	let mut service1 = NetworkService::new(config1).unwrap();
+	service1.start().unwrap();
+	TestProtocol::register(&mut service1, false);
+	let mut config2 = NetworkConfiguration::new_local();
+	config2.boot_nodes = vec![ service1.local_url().unwrap() ];
+	let service2 = NetworkService::new(config2).unwrap();
+	service2.start().unwrap();
+	service2.register_protocol(Arc::new(TestProtocol::new(false)), "othr", &[1u8]).unwrap();
+	let mismatches = || service1.stats().handshake_failures(HandshakeFailure::CapabilityMismatch)
+		+ service2.stats().handshake_failures(HandshakeFailure::CapabilityMismatch);
+	while mismatches() == 0 {
+		thread::sleep(Duration::from_millis(50));
+	}
+	assert_eq!(service1.stats().sessions() + service2.stats().sessions(), 0);
+	assert_eq!(service1.stats().handshake_failures(HandshakeFailure::ProtocolMismatch), 0);
+}
+
+#[test]
+fn net_reserved_peer_dialed_when_it_comes_up_late() {
+	// the reserved peer listens from the start but refuses sessions until it supports the protocol
+	let mut service2 = NetworkService::new(NetworkConfiguration::new_local()).unwrap();
+	service2.start().unwrap();
+	let mut config1 = NetworkConfiguration::new_local();
+	config1.reserved_nodes = vec![ service2.local_url().unwrap() ];
+	config1.reserved_dial_interval = Some(Duration::from_millis(500));
+	let mut service1 = NetworkService::new(config1).unwrap();
+	service1.start().unwrap();
+	TestProtocol::register(&mut service1, false);
+
+	// first dials fail while the peer is down
+	thread::sleep(Duration::from_secs(1));
+	let status = service1.reserved_peers_status();
+	assert_eq!(status.len(), 1);
+	assert!(!status[0].connected);
+
+	TestProtocol::register(&mut service2, false);
+
+	// reserved peers are retried on a fixed schedule, regardless of how often they failed
+	let up = Instant::now();
+	while !service1.reserved_peers_status()[0].connected {
+		assert!(up.elapsed() < Duration::from_secs(5), "Reserved peer was not re-dialed in time");
+		thread::sleep(Duration::from_millis(50));
+	}
+}
+
+#[test]
+fn net_reserved_peer_keeps_non_reserved_peers() {
+	// only reserved peers are dialed, other peers connect on their own
+	let mut config1 = NetworkConfiguration::new_local();
+	config1.min_peers = 0;
+	config1.max_peers = 2;
+	let mut service1 = NetworkService::new(config1).unwrap();
+	service1.start().unwrap();
+	TestProtocol::register(&mut service1, false);
+
+	// a non-reserved peer takes the only inbound slot
+	let mut config2 = NetworkConfiguration::new_local();
+	config2.boot_nodes = vec![ service1.local_url().unwrap() ];
+	let mut service2 = NetworkService::new(config2).unwrap();
+	service2.start().unwrap();
+	TestProtocol::register(&mut service2, false);
+	let start = Instant::now();
+	while service1.session_counts() != (1, 0) {
+		assert!(start.elapsed() < Duration::from_secs(5), "Non-reserved peer did not connect in time");
+		thread::sleep(Duration::from_millis(50));
+	}
+
+	let mut service3 = NetworkService::new(NetworkConfiguration::new_local()).unwrap();
+	service3.start().unwrap();
+	TestProtocol::register(&mut service3, false);
+	service1.add_reserved_peer(&service3.local_url().unwrap()).unwrap();
+
+	// the reserved peer connects without taking the session of the non-reserved one,
+	// even though the peer limit is reached.
+	let start = Instant::now();
+	while !service1.reserved_peers_status()[0].connected {
+		assert!(start.elapsed() < Duration::from_secs(5), "Reserved peer did not connect in time");
+		thread::sleep(Duration::from_millis(50));
+	}
+	thread::sleep(Duration::from_millis(500));
+	assert_eq!(service1.session_counts(), (1, 1));
+}
+
+#[test]
+fn net_discovery_refresh_finds_late_node() {
+	let mut service1 = NetworkService::new(NetworkConfiguration::new_local()).unwrap();
+	service1.start().unwrap();
+	TestProtocol::register(&mut service1, false);
+
+	// the default refresh interval is longer than this test runs
+	let mut config2 = NetworkConfiguration::new_local();
+	config2.boot_nodes = vec![ service1.local_url().unwrap() ];
+	config2.discovery_refresh_interval = Some(Duration::from_millis(500));
+	let mut service2 = NetworkService::new(config2).unwrap();
+	service2.start().unwrap();
+	TestProtocol::register(&mut service2, false);
+
+	// let the initial lookup through the boot node complete
+	thread::sleep(Duration::from_secs(1));
+
+	let mut config3 = NetworkConfiguration::new_local();
+	config3.discovery_enabled = false;
+	let mut service3 = NetworkService::new(config3).unwrap();
+	service3.start().unwrap();
+	TestProtocol::register(&mut service3, false);
+	service1.add_reserved_peer(&service3.local_url().unwrap()).unwrap();
+
+	// the next refresh asks the boot node again and both nodes end up dialing the new one
+	let start = Instant::now();
+	while service3.session_counts().0 != 2 {
+		assert!(start.elapsed() < Duration::from_secs(3), "Late node was not discovered in time");
+		thread::sleep(Duration::from_millis(50));
+	}
+}
+
+#[test]
+fn net_compressed_packets() {
+	assert_eq!(exchange_payload(true, true), (Some(true), Some(true)));
+}
+
+#[test]
+fn net_compression_with_legacy_peer() {
+	assert_eq!(exchange_payload(true, false), (Some(false), Some(false)));
+}
@@ -17,6 +17,7 @@
 use std::{fmt, cmp};
 use std::str::FromStr;
 use ::{Encodable, RlpDecodable, UntrustedRlp, RlpStream, View, Stream, DecoderError};
+use bytes::FromBytesError;
 use bigint::uint::U256;
 
 #[test]
@@ -393,6 +394,15 @@ fn test_rlp_2bytes_data_length_check()
 	assert_eq!(Err(DecoderError::RlpInconsistentLengthAndData), as_val);
 }
 
+#[test]
+fn test_rlp_invalid_utf8_string() {
+	let data: Vec<u8> = vec![0x82, 0xc3, 0x28];
+	let rlp = UntrustedRlp::new(&data);
+
+	let as_val: Result<String, DecoderError> = rlp.as_val();
+	assert_eq!(Err(DecoderError::FromBytesError(FromBytesError::InvalidUtf8)), as_val);
+}
+
 #[test]
 fn test_rlp_nested_empty_list_encode() {
 	let mut stream = RlpStream::new_list(2);
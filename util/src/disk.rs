@@ -0,0 +1,94 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Disk space utilities
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[cfg(unix)]
+/// Get the number of bytes available to unprivileged users on the filesystem containing `path`.
+pub fn available_space(path: &Path) -> io::Result<u64> {
+	use std::ffi::CString;
+	use std::os::unix::ffi::OsStrExt;
+	use libc;
+
+	let c_path = try!(CString::new(path.as_os_str().as_bytes())
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e)));
+
+	let mut stat: libc::statvfs = unsafe { ::std::mem::zeroed() };
+	match unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } {
+		0 => Ok(stat.f_bavail as u64 * stat.f_frsize as u64),
+		_ => Err(io::Error::last_os_error()),
+	}
+}
+
+#[cfg(windows)]
+/// Get the number of bytes available to the current user on the volume containing `path`.
+pub fn available_space(path: &Path) -> io::Result<u64> {
+	use std::os::windows::ffi::OsStrExt;
+
+	#[link(name = "kernel32")]
+	extern "system" {
+		fn GetDiskFreeSpaceExW(dir: *const u16, available: *mut u64, total: *mut u64, free: *mut u64) -> i32;
+	}
+
+	let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+	let mut available = 0u64;
+	match unsafe { GetDiskFreeSpaceExW(wide_path.as_ptr(), &mut available, ::std::ptr::null_mut(), ::std::ptr::null_mut()) } {
+		0 => Err(io::Error::last_os_error()),
+		_ => Ok(available),
+	}
+}
+
+#[cfg(not(any(unix, windows)))]
+/// Get the number of bytes available on the filesystem containing `path`. Unknown on this platform.
+pub fn available_space(_path: &Path) -> io::Result<u64> {
+	Ok(u64::max_value())
+}
+
+/// Get the total size in bytes of all files under the given directory.
+pub fn directory_size(path: &Path) -> io::Result<u64> {
+	let mut size = 0;
+	for entry in try!(fs::read_dir(path)) {
+		let entry = try!(entry);
+		let metadata = try!(entry.metadata());
+		size += match metadata.is_dir() {
+			true => try!(directory_size(&entry.path())),
+			false => metadata.len(),
+		};
+	}
+	Ok(size)
+}
+
+#[test]
+fn available_space_of_existing_path() {
+	assert!(available_space(&::std::env::temp_dir()).unwrap() > 0);
+}
+
+#[test]
+fn directory_size_counts_nested_files() {
+	use std::io::Write;
+	use devtools::RandomTempPath;
+
+	let temp = RandomTempPath::create_dir();
+	let nested = temp.as_path().join("nested");
+	fs::create_dir(&nested).unwrap();
+	fs::File::create(temp.as_path().join("a")).unwrap().write_all(&[0u8; 100]).unwrap();
+	fs::File::create(nested.join("b")).unwrap().write_all(&[0u8; 23]).unwrap();
+
+	assert_eq!(directory_size(temp.as_path()).unwrap(), 123);
+}
@@ -141,7 +141,9 @@ pub mod nibblevec;
 pub mod semantic_version;
 pub mod log;
 pub mod path;
+pub mod disk;
 pub mod snappy;
+pub mod sntp;
 mod timer;
 
 pub use common::*;
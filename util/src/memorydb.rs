@@ -23,7 +23,8 @@ use sha3::*;
 use hashdb::*;
 use heapsize::*;
 use std::mem;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use parking_lot::{Mutex, RwLock};
 
 const STATIC_NULL_RLP: (&'static [u8], i32) = (&[0x80; 1], 1);
 use std::collections::hash_map::Entry;
@@ -151,6 +152,16 @@ impl MemoryDB {
 		self.raw(key).unwrap()
 	}
 
+	/// Returns all entries ordered by key, along with their reference counts.
+	///
+	/// Iteration order of the underlying map varies from run to run; this provides
+	/// a reproducible one for tests and diagnostics at the cost of sorting on every call.
+	pub fn iter_sorted(&self) -> ::std::vec::IntoIter<(&H256, &[u8], i32)> {
+		let mut entries: Vec<_> = self.data.iter().map(|(k, &(ref v, rc))| (k, &v[..], rc)).collect();
+		entries.sort_by(|a, b| a.0.cmp(b.0));
+		entries.into_iter()
+	}
+
 	/// Returns the size of allocated heap memory
 	pub fn mem_used(&self) -> usize {
 		self.data.heap_size_of_children()
@@ -175,6 +186,27 @@ impl MemoryDB {
 		}
 	}
 
+	/// Move the entries whose keys match the predicate into a new instance, along with their
+	/// reference counts. Entries with zero or negative reference counts are moved as well.
+	///
+	/// Used to split out nodes of a single account, as namespaced by `AccountDB`.
+	pub fn extract_if<F>(&mut self, pred: F) -> MemoryDB where F: Fn(&H256) -> bool {
+		let keys: Vec<H256> = self.data.keys().filter(|k| pred(k)).cloned().collect();
+		let mut extracted = MemoryDB::new();
+		for key in keys {
+			let entry = self.data.remove(&key).expect("key has just been found in the map; qed");
+			extracted.data.insert(key, entry);
+		}
+		extracted
+	}
+
+	/// Move all the entries and auxiliary data of `other` into `self`. Inverse of `extract_if`.
+	/// Reference counts of entries present in both are summed up as in `consolidate`.
+	pub fn absorb(&mut self, mut other: Self) {
+		self.aux.extend(other.drain_aux());
+		self.consolidate(other);
+	}
+
 	/// Consolidate all the entries of `other` into `self`.
 	pub fn consolidate(&mut self, mut other: Self) {
 		for (key, (value, rc)) in other.drain() {
@@ -286,6 +318,293 @@ impl HashDB for MemoryDB {
 	}
 }
 
+/// Order in which entries of an `LruMemoryDB` were last used.
+#[derive(Default)]
+struct Recency {
+	tick: u64,
+	ticks: H256FastMap<u64>,
+	order: BTreeMap<u64, H256>,
+}
+
+impl Recency {
+	fn touch(&mut self, key: &H256) {
+		self.tick += 1;
+		if let Some(old) = self.ticks.insert(key.clone(), self.tick) {
+			self.order.remove(&old);
+		}
+		self.order.insert(self.tick, key.clone());
+	}
+
+	fn forget(&mut self, key: &H256) {
+		if let Some(old) = self.ticks.remove(key) {
+			self.order.remove(&old);
+		}
+	}
+}
+
+/// `MemoryDB` with a soft capacity, suitable as a read cache of trie nodes.
+///
+/// When the total size of the stored values exceeds the capacity, the least recently used
+/// entries with a zero reference count (as left by `denote()` or by removing all references)
+/// are evicted. Entries with a non-zero reference count are never evicted, so the capacity
+/// may be exceeded if they alone take more space.
+pub struct LruMemoryDB {
+	db: MemoryDB,
+	capacity: usize,
+	size: usize,
+	recency: Mutex<Recency>,
+}
+
+impl LruMemoryDB {
+	/// Create a new instance holding up to `capacity` bytes of evictable values.
+	pub fn new(capacity: usize) -> LruMemoryDB {
+		LruMemoryDB {
+			db: MemoryDB::new(),
+			capacity: capacity,
+			size: 0,
+			recency: Mutex::new(Recency::default()),
+		}
+	}
+
+	/// Total size of the stored values in bytes.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// Clear all data from the database.
+	pub fn clear(&mut self) {
+		self.db.clear();
+		self.size = 0;
+		*self.recency.lock() = Recency::default();
+	}
+
+	/// Get the data and reference count of the given key. See `MemoryDB::raw`.
+	pub fn raw(&self, key: &H256) -> Option<(&[u8], i32)> {
+		self.touch(key);
+		self.db.raw(key)
+	}
+
+	/// Denote than an existing value has the given key. See `MemoryDB::denote`.
+	///
+	/// May evict other zero-referenced entries to make room for the value.
+	pub fn denote(&mut self, key: &H256, value: Bytes) -> (&[u8], i32) {
+		let before = self.stored_len(key);
+		self.db.denote(key, value);
+		self.note_stored(key, before);
+		self.db.raw(key).expect("value has just been denoted and is never evicted right away; qed")
+	}
+
+	/// Returns the size of allocated heap memory
+	pub fn mem_used(&self) -> usize {
+		self.db.mem_used()
+	}
+
+	fn touch(&self, key: &H256) {
+		if self.db.data.contains_key(key) {
+			self.recency.lock().touch(key);
+		}
+	}
+
+	fn stored_len(&self, key: &H256) -> usize {
+		self.db.data.get(key).map_or(0, |&(ref v, _)| v.len())
+	}
+
+	fn note_stored(&mut self, key: &H256, before: usize) {
+		self.size = self.size + self.stored_len(key) - before;
+		self.recency.lock().touch(key);
+		self.evict(key);
+	}
+
+	// Evict least recently used zero-referenced entries other than `keep` until within capacity.
+	fn evict(&mut self, keep: &H256) {
+		let mut recency = self.recency.lock();
+		while self.size > self.capacity {
+			let victim = {
+				let data = &self.db.data;
+				recency.order.values()
+					.find(|k| *k != keep && data.get(*k).map_or(true, |&(_, rc)| rc == 0))
+					.cloned()
+			};
+			let victim = match victim {
+				Some(victim) => victim,
+				None => break,
+			};
+			recency.forget(&victim);
+			if let Some((value, _)) = self.db.data.remove(&victim) {
+				self.size -= value.len();
+			}
+		}
+	}
+}
+
+impl HashDB for LruMemoryDB {
+	fn get(&self, key: &H256) -> Option<&[u8]> {
+		self.touch(key);
+		self.db.get(key)
+	}
+
+	fn keys(&self) -> HashMap<H256, i32> {
+		self.db.keys()
+	}
+
+	fn contains(&self, key: &H256) -> bool {
+		self.db.contains(key)
+	}
+
+	fn insert(&mut self, value: &[u8]) -> H256 {
+		let key = value.sha3();
+		let before = self.stored_len(&key);
+		self.db.insert(value);
+		self.note_stored(&key, before);
+		key
+	}
+
+	fn emplace(&mut self, key: H256, value: Bytes) {
+		let before = self.stored_len(&key);
+		self.db.emplace(key.clone(), value);
+		self.note_stored(&key, before);
+	}
+
+	fn remove(&mut self, key: &H256) {
+		let before = self.stored_len(key);
+		self.db.remove(key);
+		self.note_stored(key, before);
+	}
+
+	fn insert_aux(&mut self, hash: Vec<u8>, value: Vec<u8>) {
+		self.db.insert_aux(hash, value);
+	}
+
+	fn get_aux(&self, hash: &[u8]) -> Option<Vec<u8>> {
+		self.db.get_aux(hash)
+	}
+
+	fn remove_aux(&mut self, hash: &[u8]) {
+		self.db.remove_aux(hash);
+	}
+}
+
+/// `MemoryDB` behind a read-write lock, so that it can be shared between threads.
+///
+/// Every call takes the lock for its own duration only: lookups take the read lock and
+/// proceed in parallel, writes take the write lock and stall all readers meanwhile. Suitable
+/// for read-mostly workloads; with frequent writes a `MemoryDB` per thread merged with
+/// `consolidate()` scales better.
+///
+/// Besides the `HashDB` methods, values can be added and removed through a shared reference
+/// with `insert_shared()`, `emplace_shared()` and `remove_shared()`. These only change reference
+/// counts of values already stored and never replace or drop them, so slices returned by `get()`
+/// stay valid until the database is mutably borrowed, e.g. to `purge()` unreferenced values.
+#[derive(Default)]
+pub struct SharedMemoryDB {
+	db: RwLock<MemoryDB>,
+}
+
+impl SharedMemoryDB {
+	/// Create a new, empty instance.
+	pub fn new() -> SharedMemoryDB {
+		SharedMemoryDB::default()
+	}
+
+	/// Wrap an existing database.
+	pub fn from_memorydb(db: MemoryDB) -> SharedMemoryDB {
+		SharedMemoryDB {
+			db: RwLock::new(db),
+		}
+	}
+
+	/// Unwrap the inner database.
+	pub fn into_inner(self) -> MemoryDB {
+		self.db.into_inner()
+	}
+
+	/// Insert a value through a shared reference. See `HashDB::insert`.
+	pub fn insert_shared(&self, value: &[u8]) -> H256 {
+		if value == &NULL_RLP {
+			return SHA3_NULL_RLP.clone();
+		}
+		let key = value.sha3();
+		self.emplace_shared(key.clone(), value.to_vec());
+		key
+	}
+
+	/// Insert a value with the given key through a shared reference. See `HashDB::emplace`.
+	pub fn emplace_shared(&self, key: H256, value: Bytes) {
+		if value == &NULL_RLP {
+			return;
+		}
+
+		let mut db = self.db.write();
+		let entry = db.data.entry(key).or_insert_with(|| (Bytes::new(), 0));
+		// only the empty placeholder left by removing an unknown key is replaced; it owns
+		// no memory a reader could still refer to.
+		if entry.0.is_empty() {
+			entry.0 = value;
+		}
+		entry.1 += 1;
+	}
+
+	/// Remove a reference to a value through a shared reference. See `HashDB::remove`.
+	pub fn remove_shared(&self, key: &H256) {
+		if key == &SHA3_NULL_RLP {
+			return;
+		}
+		self.db.write().data.entry(key.clone()).or_insert_with(|| (Bytes::new(), 0)).1 -= 1;
+	}
+
+	/// Purge all zero-referenced data from the database.
+	pub fn purge(&mut self) {
+		self.db.get_mut().purge();
+	}
+
+	/// Returns the size of allocated heap memory
+	pub fn mem_used(&self) -> usize {
+		self.db.read().mem_used()
+	}
+}
+
+impl HashDB for SharedMemoryDB {
+	fn get(&self, key: &H256) -> Option<&[u8]> {
+		let db = self.db.read();
+		// The slice points into the heap buffer of a stored value rather than into the map, so
+		// it is unaffected by the map growing. Values are only replaced or dropped through
+		// `&mut self`, which cannot happen while the returned slice borrows `self`.
+		db.get(key).map(|value| unsafe { &*(value as *const [u8]) })
+	}
+
+	fn keys(&self) -> HashMap<H256, i32> {
+		self.db.read().keys()
+	}
+
+	fn contains(&self, key: &H256) -> bool {
+		self.db.read().contains(key)
+	}
+
+	fn insert(&mut self, value: &[u8]) -> H256 {
+		self.db.write().insert(value)
+	}
+
+	fn emplace(&mut self, key: H256, value: Bytes) {
+		self.db.write().emplace(key, value)
+	}
+
+	fn remove(&mut self, key: &H256) {
+		self.db.write().remove(key)
+	}
+
+	fn insert_aux(&mut self, hash: Vec<u8>, value: Vec<u8>) {
+		self.db.write().insert_aux(hash, value)
+	}
+
+	fn get_aux(&self, hash: &[u8]) -> Option<Vec<u8>> {
+		self.db.read().get_aux(hash)
+	}
+
+	fn remove_aux(&mut self, hash: &[u8]) {
+		self.db.write().remove_aux(hash)
+	}
+}
+
 #[test]
 fn memorydb_denote() {
 	let mut m = MemoryDB::new();
@@ -345,4 +664,161 @@ fn consolidate() {
 
 	assert_eq!(overlay.get(&remove_key).unwrap(), &(b"doggo".to_vec(), 0));
 	assert_eq!(overlay.get(&insert_key).unwrap(), &(b"arf".to_vec(), 2));
-}
\ No newline at end of file
+}
+
+#[test]
+fn memorydb_extract_if_preserves_reference_counts() {
+	let mut m = MemoryDB::new();
+	let twice = m.insert(b"twice");
+	m.insert(b"twice");
+	let once = m.insert(b"once");
+	let removed = b"removed".sha3();
+	m.remove(&removed);
+	let denoted = b"denoted".sha3();
+	m.denote(&denoted, b"denoted".to_vec());
+
+	let picked = vec![twice, removed, denoted];
+	let extracted = m.extract_if(|k| picked.contains(k));
+
+	assert_eq!(extracted.raw(&twice), Some((&b"twice"[..], 2)));
+	assert_eq!(extracted.raw(&removed), Some((&[][..], -1)));
+	assert_eq!(extracted.raw(&denoted), Some((&b"denoted"[..], 0)));
+	assert_eq!(extracted.raw(&once), None);
+	assert_eq!(m.raw(&once), Some((&b"once"[..], 1)));
+	assert!(picked.iter().all(|k| m.raw(k).is_none()));
+}
+
+#[test]
+fn memorydb_absorb_extracted_is_identity() {
+	let mut m = MemoryDB::new();
+	for i in 0..100u8 {
+		m.insert(&[i]);
+	}
+	m.insert(&[0u8]);
+	m.remove(&b"missing".sha3());
+	m.insert_aux(b"aux".to_vec(), b"value".to_vec());
+	let original = m.clone();
+
+	let extracted = m.extract_if(|k| k[0] % 2 == 0);
+	assert!(extracted.data.len() > 0 && m.data.len() > 0);
+	assert!(m != original);
+
+	m.absorb(extracted);
+	assert!(m == original);
+}
+
+#[test]
+fn memorydb_iter_sorted() {
+	let mut m = MemoryDB::new();
+	for i in 0..100u8 {
+		m.insert(&[i]);
+	}
+	m.insert(&[0u8]);
+	m.remove(&b"missing".sha3());
+
+	let entries: Vec<_> = m.iter_sorted().collect();
+	assert_eq!(entries.len(), 101);
+	assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
+	assert_eq!(entries.iter().find(|e| *e.0 == [0u8].sha3()).unwrap().2, 2);
+	assert_eq!(entries, m.iter_sorted().collect::<Vec<_>>());
+}
+
+#[test]
+fn lru_memorydb_evicts_least_recently_used() {
+	let values: Vec<[u8; 10]> = (0..4u8).map(|i| [i; 10]).collect();
+	let keys: Vec<H256> = values.iter().map(|v| v.sha3()).collect();
+	let mut m = LruMemoryDB::new(30);
+
+	for i in 0..3 {
+		m.denote(&keys[i], values[i].to_vec());
+	}
+	assert_eq!(m.size(), 30);
+
+	// use the oldest entry so that the second one becomes the least recently used
+	assert!(m.raw(&keys[0]).is_some());
+	m.denote(&keys[3], values[3].to_vec());
+
+	assert_eq!(m.size(), 30);
+	assert_eq!(m.raw(&keys[1]), None);
+	assert_eq!(m.raw(&keys[0]).unwrap(), (&values[0][..], 0));
+	assert_eq!(m.raw(&keys[2]).unwrap(), (&values[2][..], 0));
+	assert_eq!(m.raw(&keys[3]).unwrap(), (&values[3][..], 0));
+}
+
+#[test]
+fn lru_memorydb_keeps_referenced_entries() {
+	let mut m = LruMemoryDB::new(20);
+	let referenced = m.insert(b"referenced value");
+
+	for i in 0..100u8 {
+		let value = [i; 10];
+		m.denote(&value.sha3(), value.to_vec());
+		assert!(m.contains(&referenced));
+	}
+	assert_eq!(m.get(&referenced).unwrap(), b"referenced value");
+	// the referenced value alone exceeds the capacity, only the newest cached entry is kept
+	assert_eq!(m.size(), 26);
+
+	// once unreferenced, the entry is evictable as well
+	m.remove(&referenced);
+	m.denote(&[200u8; 10].sha3(), vec![200u8; 10]);
+	assert_eq!(m.raw(&referenced), None);
+	assert_eq!(m.size(), 10);
+}
+
+#[test]
+fn shared_memorydb_concurrent_readers_and_writer() {
+	use std::sync::Arc;
+	use std::thread;
+
+	let values: Vec<Bytes> = (0..200u8).map(|i| vec![i; 32]).collect();
+	let keys: Vec<H256> = values.iter().map(|v| v.sha3()).collect();
+	let db = Arc::new(SharedMemoryDB::new());
+	for v in &values[..100] {
+		db.insert_shared(v);
+	}
+
+	let readers: Vec<_> = (0..4).map(|_| {
+		let (db, values, keys) = (db.clone(), values.clone(), keys.clone());
+		thread::spawn(move || {
+			for _ in 0..20 {
+				for (k, v) in keys.iter().zip(values.iter()) {
+					// values are either not yet written or complete.
+					match db.get(k) {
+						Some(found) => assert_eq!(found, &v[..]),
+						None => assert!(*k != keys[0]),
+					}
+				}
+			}
+		})
+	}).collect();
+
+	let writer = {
+		let db = db.clone();
+		let values = values.clone();
+		thread::spawn(move || {
+			for v in &values[100..] {
+				db.insert_shared(v);
+			}
+			// adding and dropping a reference keeps the value around.
+			for v in &values[..100] {
+				let key = db.insert_shared(v);
+				db.remove_shared(&key);
+			}
+		})
+	};
+
+	writer.join().unwrap();
+	for reader in readers {
+		reader.join().unwrap();
+	}
+
+	let mut db = Arc::try_unwrap(db).ok().expect("all threads joined; qed");
+	for (k, v) in keys.iter().zip(values.iter()) {
+		assert_eq!(db.get(k).unwrap(), &v[..]);
+		db.remove(k);
+	}
+	db.purge();
+	assert!(db.keys().is_empty());
+	assert!(db.into_inner() == MemoryDB::new());
+}
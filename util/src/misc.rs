@@ -43,6 +43,11 @@ pub fn version() -> String {
 	format!("Parity/v{}-unstable{}{}{}{}/{}-{}{}{}/rustc{}", env!("CARGO_PKG_VERSION"), sha3_dash, sha3, date_dash, commit_date, Target::arch(), Target::os(), env_dash, env, rustc_version())
 }
 
+/// Get the version string for a node with the given identity, e.g. `Parity/<identity>/v1.4.0-...`.
+pub fn version_with_identity(identity: &str) -> String {
+	version().replace("Parity/", &format!("Parity/{}/", identity))
+}
+
 /// Get the standard version data for this software.
 pub fn version_data() -> Bytes {
 	let mut s = RlpStream::new_list(4);
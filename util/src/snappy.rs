@@ -85,7 +85,8 @@ pub fn decompressed_len(compressed: &[u8]) -> Result<usize, InvalidInput> {
 /// Compress a buffer using snappy.
 pub fn compress(input: &[u8]) -> Vec<u8> {
 	let mut buf = Vec::new();
-	compress_into(input, &mut buf);
+	let len = compress_into(input, &mut buf);
+	buf.truncate(len);
 	buf
 }
 
@@ -0,0 +1,95 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal SNTP client for checking the local clock.
+use std::io;
+use std::net::{UdpSocket, ToSocketAddrs};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds between the NTP epoch (1900) and the unix epoch (1970).
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+const PACKET_SIZE: usize = 48;
+/// Offset of the transmit timestamp in the packet.
+const TRANSMIT_TIMESTAMP: usize = 40;
+/// No leap second warning, version 3, client mode.
+const CLIENT_REQUEST: u8 = 0x1b;
+const SERVER_MODE: u8 = 4;
+
+/// Query the server for the time and return the offset of the local clock in milliseconds.
+/// A positive offset means the local clock is ahead of the server.
+pub fn clock_offset_ms<A: ToSocketAddrs>(server: A, timeout: Duration) -> io::Result<i64> {
+	let socket = try!(UdpSocket::bind("0.0.0.0:0"));
+	try!(socket.set_read_timeout(Some(timeout)));
+	try!(socket.set_write_timeout(Some(timeout)));
+
+	let mut request = [0u8; PACKET_SIZE];
+	request[0] = CLIENT_REQUEST;
+	let sent = unix_ms(SystemTime::now());
+	try!(socket.send_to(&request, server));
+
+	let mut response = [0u8; PACKET_SIZE];
+	let (len, _) = try!(socket.recv_from(&mut response));
+	let received = unix_ms(SystemTime::now());
+	if len < PACKET_SIZE || response[0] & 0x07 != SERVER_MODE {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid SNTP response"));
+	}
+
+	let server_time = try!(ntp_to_unix_ms(&response[TRANSMIT_TIMESTAMP..TRANSMIT_TIMESTAMP + 8])
+		.ok_or(io::Error::new(io::ErrorKind::InvalidData, "invalid SNTP timestamp")));
+	Ok(offset_ms(sent, received, server_time))
+}
+
+/// Offset of the local clock, assuming the request and the response took equally long.
+fn offset_ms(sent: i64, received: i64, server_time: i64) -> i64 {
+	sent + (received - sent) / 2 - server_time
+}
+
+/// Convert a 64-bit NTP timestamp to milliseconds since the unix epoch.
+fn ntp_to_unix_ms(timestamp: &[u8]) -> Option<i64> {
+	let read_u32 = |b: &[u8]| (b[0] as u64) << 24 | (b[1] as u64) << 16 | (b[2] as u64) << 8 | b[3] as u64;
+	let secs = read_u32(&timestamp[0..4]);
+	let fraction = read_u32(&timestamp[4..8]);
+	if secs < NTP_UNIX_OFFSET_SECS {
+		return None;
+	}
+	Some(((secs - NTP_UNIX_OFFSET_SECS) * 1000 + (fraction * 1000 >> 32)) as i64)
+}
+
+fn unix_ms(time: SystemTime) -> i64 {
+	time.duration_since(UNIX_EPOCH)
+		.map(|d| (d.as_secs() * 1000 + d.subsec_nanos() as u64 / 1_000_000) as i64)
+		.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ntp_to_unix_ms, offset_ms};
+
+	#[test]
+	fn converts_ntp_timestamps() {
+		// 2016-01-01 00:00:00.5 UTC
+		let timestamp = [0xda, 0x30, 0x40, 0x00, 0x80, 0x00, 0x00, 0x00];
+		assert_eq!(ntp_to_unix_ms(&timestamp), Some(1_451_606_400_500));
+		assert_eq!(ntp_to_unix_ms(&[0u8; 8]), None);
+	}
+
+	#[test]
+	fn computes_offset() {
+		assert_eq!(offset_ms(1000, 1100, 1050), 0);
+		assert_eq!(offset_ms(3000, 3100, 1050), 2000);
+		assert_eq!(offset_ms(1000, 1100, 3050), -2000);
+	}
+}
@@ -285,36 +285,47 @@ impl<'a> TrieDBIterator<'a> {
 			trail: vec![],
 			key_nibbles: Vec::new(),
 		};
-		r.descend(db.root_data(&mut NoOp).unwrap());
+		r.descend(db.root_data(&mut NoOp).unwrap()).unwrap();
 		r
 	}
 
 	/// Descend into a payload.
-	fn descend(&mut self, d: &'a [u8]) {
+	fn descend(&mut self, d: &'a [u8]) -> super::Result<()> {
+		let node = try!(self.db.get_node(d, &mut NoOp, 0));
+		match node {
+			Node::Leaf(ref n, _) | Node::Extension(ref n, _) => { self.key_nibbles.extend(n.iter()); },
+			_ => {}
+		}
 		self.trail.push(Crumb {
 			status: Status::Entering,
-			node: self.db.get_node(d, &mut NoOp, 0).unwrap(),
+			node: node,
 		});
-		match self.trail.last().unwrap().node {
-			Node::Leaf(n, _) | Node::Extension(n, _) => { self.key_nibbles.extend(n.iter()); },
-			_ => {}
-		}
+		Ok(())
 	}
 
 	/// Descend into a payload and get the next item.
-	fn descend_next(&mut self, d: &'a [u8]) -> Option<(Bytes, &'a [u8])> { self.descend(d); self.next() }
+	fn descend_next(&mut self, d: &'a [u8]) -> Option<super::Result<(Bytes, &'a [u8])>> {
+		match self.descend(d) {
+			Ok(()) => self.next_checked(),
+			Err(e) => Some(Err(e)),
+		}
+	}
 
 	/// The present key.
-	fn key(&self) -> Bytes {
+	///
+	/// After a failed lookup this is the path to the missing node, with an odd trailing nibble padded.
+	pub fn key(&self) -> Bytes {
 		// collapse the key_nibbles down to bytes.
-		self.key_nibbles.iter().step(2).zip(self.key_nibbles.iter().skip(1).step(2)).map(|(h, l)| h * 16 + l).collect()
+		let mut key: Bytes = self.key_nibbles.iter().step(2).zip(self.key_nibbles.iter().skip(1).step(2)).map(|(h, l)| h * 16 + l).collect();
+		if self.key_nibbles.len() % 2 == 1 {
+			key.push(self.key_nibbles[self.key_nibbles.len() - 1] * 16);
+		}
+		key
 	}
-}
 
-impl<'a> Iterator for TrieDBIterator<'a> {
-	type Item = (Bytes, &'a [u8]);
-
-	fn next(&mut self) -> Option<Self::Item> {
+	/// Get the next item or the error encountered looking up a node on the way.
+	/// The part of the trie below a missing node is skipped, so iteration may go on after an error.
+	pub fn next_checked(&mut self) -> Option<super::Result<(Bytes, &'a [u8])>> {
 		let b = match self.trail.last_mut() {
 			Some(mut b) => { b.increment(); b.clone() },
 			None => return None
@@ -330,11 +341,11 @@ impl<'a> Iterator for TrieDBIterator<'a> {
 					_ => {}
 				}
 				self.trail.pop();
-				self.next()
+				self.next_checked()
 			},
-			(Status::At, Node::Leaf(_, v)) | (Status::At, Node::Branch(_, Some(v))) => Some((self.key(), v)),
+			(Status::At, Node::Leaf(_, v)) | (Status::At, Node::Branch(_, Some(v))) => Some(Ok((self.key(), v))),
 			(Status::At, Node::Extension(_, d)) => self.descend_next(d),
-			(Status::At, Node::Branch(_, _)) => self.next(),
+			(Status::At, Node::Branch(_, _)) => self.next_checked(),
 			(Status::AtChild(i), Node::Branch(children, _)) if children[i].len() > 0 => {
 				match i {
 					0 => self.key_nibbles.push(0),
@@ -344,13 +355,21 @@ impl<'a> Iterator for TrieDBIterator<'a> {
 			},
 			(Status::AtChild(i), Node::Branch(_, _)) => {
 				if i == 0 { self.key_nibbles.push(0); }
-				self.next()
+				self.next_checked()
 			},
 			_ => panic!() // Should never see Entering or AtChild without a Branch here.
 		}
 	}
 }
 
+impl<'a> Iterator for TrieDBIterator<'a> {
+	type Item = (Bytes, &'a [u8]);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.next_checked().map(|item| item.unwrap_or_else(|e| panic!("Trie iteration failed: {}", e)))
+	}
+}
+
 impl<'db> Trie for TrieDB<'db> {
 	fn iter<'a>(&'a self) -> Box<Iterator<Item = TrieItem> + 'a> {
 		Box::new(TrieDBIterator::new(self))
@@ -395,3 +414,41 @@ fn iterator() {
 	assert_eq!(d.iter().map(|i|i.to_vec()).collect::<Vec<_>>(), t.iter().map(|x|x.0).collect::<Vec<_>>());
 	assert_eq!(d, t.iter().map(|x|x.1).collect::<Vec<_>>());
 }
+
+#[test]
+fn iterator_skips_missing_nodes() {
+	use memorydb::*;
+	use super::TrieMut;
+	use super::triedbmut::*;
+
+	let mut memdb = MemoryDB::new();
+	let mut root = H256::new();
+	{
+		let mut t = TrieDBMut::new(&mut memdb, &mut root);
+		for i in 0..16u8 {
+			t.insert(&[i * 16], &[i; 32]).unwrap();
+		}
+	}
+
+	// values are long enough for every leaf to be stored by hash.
+	let missing = memdb.keys().into_iter()
+		.map(|(hash, _)| hash)
+		.find(|hash| memdb.get(hash).unwrap().windows(32).any(|w| w == &[5u8; 32][..]))
+		.unwrap();
+	memdb.remove(&missing);
+
+	let t = TrieDB::new(&memdb, &root).unwrap();
+	let mut iter = TrieDBIterator::new(&t);
+	let mut keys = Vec::new();
+	let mut errors = Vec::new();
+	while let Some(item) = iter.next_checked() {
+		match item {
+			Ok((key, _)) => keys.push(key),
+			Err(e) => errors.push((iter.key(), *e)),
+		}
+	}
+
+	assert_eq!(keys.len(), 15);
+	assert!(!keys.contains(&vec![0x50]));
+	assert_eq!(errors, vec![(vec![0x50], TrieError::IncompleteDatabase(missing))]);
+}